@@ -0,0 +1,257 @@
+//! What goes in each file: YAML frontmatter between two `---` lines, then a Markdown body.
+//!
+//! Only the names of the fields are fixed, so that other tools (Obsidian's properties, Dataview
+//! queries, `grep status: Done` ...) can read them. Fields which aren't set are left out.
+
+use std::{borrow::Cow, time::Duration};
+
+use anyhow::{Context, anyhow};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use helixflow_core::{
+    HelixFlowError, HelixFlowResult,
+    actor::Actor,
+    sorting::SortRule,
+    task::{Colour, Priority, Status, Task, TaskList},
+    timestamps::LastModified,
+    versions::Version,
+};
+
+/// The frontmatter & body of `text`, or `None` if it doesn't start with frontmatter.
+fn split_frontmatter(text: &str) -> Option<(&str, &str)> {
+    let rest = text
+        .strip_prefix("---\n")
+        .or_else(|| text.strip_prefix("---\r\n"))?;
+    let mut end = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == "---" {
+            return Some((&rest[..end], &rest[end + line.len()..]));
+        }
+        end += line.len();
+    }
+    None
+}
+
+fn parse_frontmatter<T: for<'de> Deserialize<'de>>(text: &str) -> HelixFlowResult<(T, &str)> {
+    let (yaml, body) = split_frontmatter(text).ok_or_else(|| anyhow!("No frontmatter"))?;
+    let frontmatter = serde_yaml::from_str(yaml).context("Reading frontmatter")?;
+    Ok((frontmatter, body))
+}
+
+fn with_frontmatter<T: Serialize>(frontmatter: &T, body: &str) -> String {
+    let yaml = serde_yaml::to_string(frontmatter).expect("frontmatter is serializable");
+    format!("---\n{yaml}---\n{body}")
+}
+
+/// The frontmatter of a task's file. The file is named after the task's id & the body is its
+/// description.
+#[derive(Debug, Serialize, Deserialize)]
+struct TaskFile {
+    name: String,
+    /// The name again, for Obsidian to find the task by name in links & the quick switcher.
+    #[serde(default)]
+    aliases: Vec<String>,
+    #[serde(default)]
+    status: Status,
+    #[serde(default)]
+    priority: Priority,
+    /// `#rrggbb`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    colour: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    due: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    estimate_secs: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    actual_secs: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    completed_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pinned: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    updated_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    version: u64,
+}
+
+fn parse_colour(hex: &str) -> HelixFlowResult<Colour> {
+    let channel = |n: usize| {
+        hex.strip_prefix('#')
+            .filter(|digits| digits.len() == 6)
+            .and_then(|digits| digits.get(2 * n..2 + 2 * n))
+            .and_then(|channel| u8::from_str_radix(channel, 16).ok())
+            .ok_or_else(|| HelixFlowError::InvalidValue {
+                field: "colour".into(),
+                value: hex.into(),
+            })
+    };
+    Ok(Colour::rgb(channel(0)?, channel(1)?, channel(2)?))
+}
+
+/// The contents of the file for `task`, stored at `version`.
+///
+/// The description is followed by a newline, as editors add one when saving, so a task without a
+/// description has an empty body and one with an empty description has a body of just `\n`.
+pub(crate) fn task_markdown(task: &Task, version: u64) -> String {
+    let frontmatter = TaskFile {
+        name: task.name.to_string(),
+        aliases: vec![task.name.to_string()],
+        status: task.status,
+        priority: task.priority,
+        colour: task
+            .colour
+            .map(|colour| format!("#{:02x}{:02x}{:02x}", colour.red, colour.green, colour.blue)),
+        due: task.due,
+        estimate_secs: task.estimate.map(|estimate| estimate.as_secs()),
+        actual_secs: task.actual.map(|actual| actual.as_secs()),
+        completed_at: task.completed_at,
+        pinned: task.pinned,
+        updated_at: task.updated_at.0,
+        version,
+    };
+    let body = match &task.description {
+        Some(description) => format!("{description}\n"),
+        None => String::new(),
+    };
+    with_frontmatter(&frontmatter, &body)
+}
+
+/// The task with `id` from the contents of its file, see `task_markdown`.
+pub(crate) fn parse_task(id: Uuid, text: &str) -> HelixFlowResult<Task> {
+    let (task, body): (TaskFile, _) = parse_frontmatter(text)?;
+    let description = match body {
+        "" => None,
+        body => Some(body.strip_suffix('\n').unwrap_or(body).to_string()),
+    };
+    Ok(Task {
+        name: Cow::Owned(task.name),
+        id,
+        description: description.map(Cow::Owned),
+        colour: task.colour.as_deref().map(parse_colour).transpose()?,
+        due: task.due,
+        status: task.status,
+        priority: task.priority,
+        estimate: task.estimate_secs.map(Duration::from_secs),
+        actual: task.actual_secs.map(Duration::from_secs),
+        completed_at: task.completed_at,
+        pinned: task.pinned,
+        updated_at: LastModified(task.updated_at),
+        version: Version(Some(task.version)),
+    })
+}
+
+/// A task in a list.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct Entry {
+    pub(crate) task: Uuid,
+    pub(crate) sortorder: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) created_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) created_by: Option<Actor>,
+}
+
+/// The frontmatter of a list's index file, named after the list's id.
+///
+/// The body is a checklist linking to each task, for reading & navigating in Obsidian. It is
+/// written from the frontmatter every time the list changes, so edits to it are not kept.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct ListFile {
+    pub(crate) name: String,
+    /// The name again, see `TaskFile::aliases`.
+    #[serde(default)]
+    aliases: Vec<String>,
+    #[serde(default)]
+    pub(crate) sort: SortRule,
+    /// By `sortorder`, then oldest first.
+    #[serde(default)]
+    pub(crate) tasks: Vec<Entry>,
+}
+
+impl ListFile {
+    pub(crate) fn new(tasklist: &TaskList, tasks: Vec<Entry>) -> Self {
+        ListFile {
+            name: tasklist.name.to_string(),
+            aliases: vec![tasklist.name.to_string()],
+            sort: tasklist.sort.clone(),
+            tasks,
+        }
+    }
+
+    pub(crate) fn parse(text: &str) -> HelixFlowResult<Self> {
+        let (list, _body) = parse_frontmatter(text)?;
+        Ok(list)
+    }
+
+    pub(crate) fn tasklist(&self, id: Uuid) -> TaskList {
+        TaskList {
+            name: Cow::Owned(self.name.clone()),
+            id,
+            sort: self.sort.clone(),
+        }
+    }
+
+    /// Keep `tasks` in order after adding or moving one.
+    pub(crate) fn sort_tasks(&mut self) {
+        self.tasks.sort_by(|a, b| a.sortorder.cmp(&b.sortorder));
+    }
+
+    /// The contents of the file, with `task` giving each task for the checklist, if it can be
+    /// read.
+    pub(crate) fn markdown(&self, task: impl Fn(&Uuid) -> Option<Task>) -> String {
+        let mut body = format!("# {}\n\n", self.name);
+        for entry in &self.tasks {
+            let line = match task(&entry.task) {
+                Some(task) => format!(
+                    "- [{}] [[{}|{}]]\n",
+                    if task.status == Status::Done {
+                        'x'
+                    } else {
+                        ' '
+                    },
+                    entry.task,
+                    // Would end the link early
+                    task.name.replace(['[', ']', '|'], "")
+                ),
+                None => format!("- [ ] [[{}]]\n", entry.task),
+            };
+            body.push_str(&line);
+        }
+        with_frontmatter(self, &body)
+    }
+}
+
+#[cfg(test)]
+#[coverage(off)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frontmatter() {
+        assert_eq!(
+            split_frontmatter("---\nname: a\n---\nbody\n"),
+            Some(("name: a\n", "body\n"))
+        );
+        assert_eq!(
+            split_frontmatter("---\r\nname: a\r\n---\r\n"),
+            Some(("name: a\r\n", ""))
+        );
+        assert_eq!(split_frontmatter("---\n---"), Some(("", "")));
+        assert_eq!(split_frontmatter("name: a\n---\n"), None);
+        assert_eq!(split_frontmatter("---\nname: a\n"), None);
+    }
+
+    #[test]
+    fn descriptions() {
+        for description in [None, Some(""), Some("One line"), Some("Two\nlines\n")] {
+            let task = Task::new("Write report", description);
+            let text = task_markdown(&task, 0);
+            assert_eq!(
+                parse_task(task.id, &text).unwrap().description,
+                task.description
+            );
+        }
+    }
+}
@@ -0,0 +1,620 @@
+#![feature(assert_matches)]
+#![feature(coverage_attribute)]
+//! Functionality to keep everything in a folder of plain Markdown files, which can be read,
+//! edited & searched with any text editor, opened as an [Obsidian](https://obsidian.md) vault and
+//! kept in step between devices by any file sync tool.
+//!
+//! Each task is one file in `Tasks/`, named by its id, with its fields as YAML frontmatter & its
+//! description as the body. Each list is an index file in `Lists/`, which holds the ids of its
+//! tasks in order. See `files` for what goes in each.
+
+use std::{
+    collections::{HashMap, hash_map},
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{Mutex, MutexGuard},
+};
+
+use anyhow::{Context, anyhow};
+use chrono::Utc;
+use log::debug;
+use uuid::Uuid;
+
+use helixflow_core::{
+    HelixFlowError, HelixFlowResult, Page, Relate, Store, rank,
+    search::{self, Search},
+    task::{Backlogs, Contains, Task, TaskList},
+    versions::next_version,
+};
+
+mod files;
+use files::{Entry, ListFile};
+
+const TASKS: &str = "Tasks";
+const LISTS: &str = "Lists";
+
+/// A folder of Markdown files.
+///
+/// Changes from different threads wait their turn. Changes made by other programs while a file
+/// is being rewritten may be lost, as with any two editors open on the same file.
+#[derive(Debug)]
+pub struct MarkdownVault {
+    root: PathBuf,
+    lock: Mutex<()>,
+}
+
+impl MarkdownVault {
+    /// Use the folder `root`, creating it if needed. Any other files & folders in it are left
+    /// alone, so it can be (part of) an existing Obsidian vault.
+    pub fn open(root: &Path) -> anyhow::Result<Self> {
+        debug!("Opening {:#?}", root);
+        for folder in [TASKS, LISTS] {
+            fs::create_dir_all(root.join(folder))
+                .with_context(|| format!("Creating {:#?}", root.join(folder)))?;
+        }
+        Ok(MarkdownVault {
+            root: root.to_path_buf(),
+            lock: Mutex::new(()),
+        })
+    }
+
+    /// Held while changing files, from reading them to writing them back.
+    fn lock(&self) -> MutexGuard<'_, ()> {
+        self.lock.lock().unwrap()
+    }
+
+    fn path(&self, folder: &str, id: &Uuid) -> PathBuf {
+        self.root.join(folder).join(format!("{id}.md"))
+    }
+
+    /// The ids of the items in `folder`, from the names of their files. Other files are ignored.
+    fn ids(&self, folder: &str) -> HelixFlowResult<Vec<Uuid>> {
+        let path = self.root.join(folder);
+        let mut ids = Vec::new();
+        for file in fs::read_dir(&path).with_context(|| format!("Reading {:#?}", path))? {
+            let file = file.with_context(|| format!("Reading {:#?}", path))?;
+            let name = file.file_name();
+            if let Some(id) = name
+                .to_str()
+                .and_then(|name| name.strip_suffix(".md"))
+                .and_then(|id| Uuid::parse_str(id).ok())
+            {
+                ids.push(id);
+            }
+        }
+        Ok(ids)
+    }
+
+    fn read(&self, folder: &str, itemtype: &str, id: &Uuid) -> HelixFlowResult<String> {
+        let path = self.path(folder, id);
+        match fs::read_to_string(&path) {
+            Ok(text) => Ok(text),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Err(HelixFlowError::NotFound {
+                itemtype: itemtype.into(),
+                id: *id,
+            }),
+            Err(err) => Err(anyhow::Error::from(err)
+                .context(format!("Reading {:#?}", path))
+                .into()),
+        }
+    }
+
+    /// Writes a hidden temporary file then renames it, so that a half-written file is never
+    /// read or synced.
+    fn write(&self, folder: &str, id: &Uuid, contents: &str) -> HelixFlowResult<()> {
+        let path = self.path(folder, id);
+        let temporary = self.root.join(folder).join(format!(".{id}.md.tmp"));
+        fs::write(&temporary, contents).with_context(|| format!("Writing {:#?}", path))?;
+        fs::rename(&temporary, &path).with_context(|| format!("Writing {:#?}", path))?;
+        Ok(())
+    }
+
+    fn remove(&self, folder: &str, itemtype: &str, id: &Uuid) -> HelixFlowResult<()> {
+        let path = self.path(folder, id);
+        match fs::remove_file(&path) {
+            Ok(()) => Ok(()),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Err(HelixFlowError::NotFound {
+                itemtype: itemtype.into(),
+                id: *id,
+            }),
+            Err(err) => Err(anyhow::Error::from(err)
+                .context(format!("Deleting {:#?}", path))
+                .into()),
+        }
+    }
+
+    /// Fails if there is already a file for `id` in `folder`.
+    fn ensure_new(&self, folder: &str, id: &Uuid) -> HelixFlowResult<()> {
+        let path = self.path(folder, id);
+        match path.try_exists() {
+            Ok(false) => Ok(()),
+            Ok(true) => Err(anyhow!("{:#?} already exists", path).into()),
+            Err(err) => Err(anyhow::Error::from(err)
+                .context(format!("Reading {:#?}", path))
+                .into()),
+        }
+    }
+
+    /// Say which file `err` came from, if the file couldn't be understood.
+    fn in_file(&self, err: HelixFlowError, folder: &str, id: &Uuid) -> HelixFlowError {
+        match err {
+            HelixFlowError::BackendError(err) => err
+                .context(format!("Reading {:#?}", self.path(folder, id)))
+                .into(),
+            err => err,
+        }
+    }
+
+    fn read_task(&self, id: &Uuid) -> HelixFlowResult<Task> {
+        let text = self.read(TASKS, "Task", id)?;
+        files::parse_task(*id, &text).map_err(|err| self.in_file(err, TASKS, id))
+    }
+
+    fn write_task(&self, task: &Task, version: u64) -> HelixFlowResult<()> {
+        self.write(TASKS, &task.id, &files::task_markdown(task, version))
+    }
+
+    fn read_list(&self, id: &Uuid) -> HelixFlowResult<ListFile> {
+        let text = self.read(LISTS, "TaskList", id)?;
+        ListFile::parse(&text).map_err(|err| self.in_file(err, LISTS, id))
+    }
+
+    /// Reads each of the list's tasks for its checklist.
+    fn write_list(&self, id: &Uuid, list: &ListFile) -> HelixFlowResult<()> {
+        let markdown = list.markdown(|task| self.read_task(task).ok());
+        self.write(LISTS, id, &markdown)
+    }
+
+    /// Every list holding the task with `id`.
+    fn lists_with(&self, id: &Uuid) -> HelixFlowResult<Vec<(Uuid, ListFile)>> {
+        let mut lists = Vec::new();
+        for list_id in self.ids(LISTS)? {
+            let list = self.read_list(&list_id)?;
+            if list.tasks.iter().any(|entry| entry.task == *id) {
+                lists.push((list_id, list));
+            }
+        }
+        Ok(lists)
+    }
+
+    /// The `sortorder` for a link to `list` without one: after the last task.
+    fn next_rank(list: &ListFile, sortorder: &str) -> HelixFlowResult<String> {
+        match sortorder {
+            "" => {
+                let last = list
+                    .tasks
+                    .iter()
+                    .map(|entry| entry.sortorder.as_str())
+                    .max();
+                Ok(rank::between(last, None)?)
+            }
+            sortorder => Ok(sortorder.to_string()),
+        }
+    }
+}
+
+impl Store<Task> for MarkdownVault {
+    fn create(&self, task: &Task) -> HelixFlowResult<Task> {
+        let lock = self.lock();
+        self.ensure_new(TASKS, &task.id)?;
+        self.write_task(task, 0)?;
+        drop(lock);
+        self.get(&task.id)
+    }
+
+    fn get(&self, id: &Uuid) -> HelixFlowResult<Task> {
+        self.read_task(id)
+    }
+
+    /// Only replaces the stored task if it is still at the version `task` was read at, see
+    /// `versions`. The checklists of the lists holding the task are rewritten if its name or
+    /// status changed.
+    fn update(&self, task: &Task) -> HelixFlowResult<Task> {
+        let lock = self.lock();
+        let stored = self.read_task(&task.id)?;
+        let version = next_version(task, stored.version.0.unwrap_or_default())?;
+        self.write_task(task, version)?;
+        if stored.name != task.name || stored.status != task.status {
+            for (id, list) in self.lists_with(&task.id)? {
+                self.write_list(&id, &list)?;
+            }
+        }
+        drop(lock);
+        self.get(&task.id)
+    }
+
+    /// Also removes the task from its lists.
+    fn delete(&self, id: &Uuid) -> HelixFlowResult<()> {
+        let _lock = self.lock();
+        self.remove(TASKS, "Task", id)?;
+        for (list_id, mut list) in self.lists_with(id)? {
+            list.tasks.retain(|entry| entry.task != *id);
+            self.write_list(&list_id, &list)?;
+        }
+        Ok(())
+    }
+
+    /// Checks that none of the tasks exist before writing any of them.
+    fn create_all(&self, tasks: &[Task]) -> HelixFlowResult<Vec<Task>> {
+        let lock = self.lock();
+        for task in tasks {
+            self.ensure_new(TASKS, &task.id)?;
+        }
+        for task in tasks {
+            self.write_task(task, 0)?;
+        }
+        drop(lock);
+        tasks.iter().map(|task| self.get(&task.id)).collect()
+    }
+}
+
+impl Store<TaskList> for MarkdownVault {
+    fn create(&self, tasklist: &TaskList) -> HelixFlowResult<TaskList> {
+        let lock = self.lock();
+        self.ensure_new(LISTS, &tasklist.id)?;
+        self.write_list(&tasklist.id, &ListFile::new(tasklist, Vec::new()))?;
+        drop(lock);
+        self.get(&tasklist.id)
+    }
+
+    fn get(&self, id: &Uuid) -> HelixFlowResult<TaskList> {
+        Ok(self.read_list(id)?.tasklist(*id))
+    }
+
+    /// Keeps the list's tasks.
+    fn update(&self, tasklist: &TaskList) -> HelixFlowResult<TaskList> {
+        let lock = self.lock();
+        let stored = self.read_list(&tasklist.id)?;
+        self.write_list(&tasklist.id, &ListFile::new(tasklist, stored.tasks))?;
+        drop(lock);
+        self.get(&tasklist.id)
+    }
+
+    /// Leaves the tasks in place, only the index file is removed.
+    fn delete(&self, id: &Uuid) -> HelixFlowResult<()> {
+        let _lock = self.lock();
+        self.remove(LISTS, "TaskList", id)
+    }
+}
+
+impl MarkdownVault {
+    /// The links to `page` of the tasks in the list with `id`, in order. Only the tasks on the
+    /// page are read.
+    fn tasks_in(&self, id: &Uuid, page: Page) -> HelixFlowResult<Vec<Contains<TaskList, Task>>> {
+        let list = self.read_list(id)?;
+        let tasklist = list.tasklist(*id);
+        Ok(list
+            .tasks
+            .into_iter()
+            .skip(page.offset)
+            .take(page.limit)
+            .map(|entry| Contains {
+                left: Ok(tasklist.clone()),
+                sortorder: entry.sortorder,
+                created_at: entry.created_at,
+                created_by: entry.created_by,
+                right: self.read_task(&entry.task),
+            })
+            .collect())
+    }
+}
+
+impl Relate<Contains<TaskList, Task>> for MarkdownVault {
+    /// Creates the task's file & adds it to the list's index. A link without a `sortorder` places
+    /// the task last. Linking a task which is already in the list moves it to the link's
+    /// `sortorder`, rather than creating it again.
+    fn create_linked_item(
+        &self,
+        link: &Contains<TaskList, Task>,
+    ) -> HelixFlowResult<Contains<TaskList, Task>> {
+        // TODO - RelBetwErrs (or impl Try for &Contains ...)
+        let tasklist = link.left.as_ref().unwrap();
+        let task = link.right.as_ref().unwrap();
+        let lock = self.lock();
+        let mut list = self.read_list(&tasklist.id)?;
+        let sortorder = Self::next_rank(&list, &link.sortorder)?;
+        let entry = match list.tasks.iter_mut().find(|entry| entry.task == task.id) {
+            // Moving keeps the original creation metadata
+            Some(existing) => {
+                existing.sortorder = sortorder;
+                existing.clone()
+            }
+            None => {
+                self.ensure_new(TASKS, &task.id)?;
+                self.write_task(task, 0)?;
+                let entry = Entry {
+                    task: task.id,
+                    sortorder,
+                    created_at: Some(Utc::now()),
+                    created_by: link.created_by.clone(),
+                };
+                list.tasks.push(entry.clone());
+                entry
+            }
+        };
+        list.sort_tasks();
+        self.write_list(&tasklist.id, &list)?;
+        drop(lock);
+        Ok(Contains {
+            left: Ok(list.tasklist(tasklist.id)),
+            sortorder: entry.sortorder,
+            created_at: entry.created_at,
+            created_by: entry.created_by,
+            right: self.get(&task.id),
+        })
+    }
+
+    fn get_linked_items(
+        &self,
+        left: &TaskList,
+    ) -> HelixFlowResult<impl Iterator<Item = Contains<TaskList, Task>>> {
+        let all = Page {
+            offset: 0,
+            limit: usize::MAX,
+        };
+        Ok(self.tasks_in(&left.id, all)?.into_iter())
+    }
+
+    fn get_linked_page(
+        &self,
+        left: &TaskList,
+        page: Page,
+    ) -> HelixFlowResult<impl Iterator<Item = Contains<TaskList, Task>>> {
+        Ok(self.tasks_in(&left.id, page)?.into_iter())
+    }
+
+    fn delete_link(&self, link: &Contains<TaskList, Task>) -> HelixFlowResult<()> {
+        // TODO - RelBetwErrs (or impl Try for &Contains ...)
+        let tasklist = link.left.as_ref().unwrap();
+        let task = link.right.as_ref().unwrap();
+        let _lock = self.lock();
+        let mut list = self.read_list(&tasklist.id)?;
+        list.tasks.retain(|entry| entry.task != task.id);
+        self.write_list(&tasklist.id, &list)
+    }
+
+    /// Adds an existing task, see `move_all`.
+    fn create_link(&self, link: &Contains<TaskList, Task>) -> HelixFlowResult<()> {
+        self.move_all(&[], std::slice::from_ref(link))
+    }
+
+    /// Every link is checked before any file is written, so a move to a missing list or task
+    /// changes nothing. Links without a `sortorder` place their tasks last, in order.
+    fn move_all(
+        &self,
+        from: &[Contains<TaskList, Task>],
+        to: &[Contains<TaskList, Task>],
+    ) -> HelixFlowResult<()> {
+        // TODO - RelBetwErrs (or impl Try for &Contains ...)
+        let _lock = self.lock();
+        let mut lists: HashMap<Uuid, ListFile> = HashMap::new();
+        for link in from.iter().chain(to) {
+            let id = link.left.as_ref().unwrap().id;
+            if let hash_map::Entry::Vacant(list) = lists.entry(id) {
+                list.insert(self.read_list(&id)?);
+            }
+        }
+        for link in from {
+            let task = link.right.as_ref().unwrap();
+            lists
+                .get_mut(&link.left.as_ref().unwrap().id)
+                .expect("every list was read")
+                .tasks
+                .retain(|entry| entry.task != task.id);
+        }
+        for link in to {
+            let task = link.right.as_ref().unwrap();
+            self.read(TASKS, "Task", &task.id)?;
+            let list = lists
+                .get_mut(&link.left.as_ref().unwrap().id)
+                .expect("every list was read");
+            let sortorder = Self::next_rank(list, &link.sortorder)?;
+            list.tasks.retain(|entry| entry.task != task.id);
+            list.tasks.push(Entry {
+                task: task.id,
+                sortorder,
+                created_at: Some(Utc::now()),
+                created_by: link.created_by.clone(),
+            });
+            list.sort_tasks();
+        }
+        for (id, list) in &lists {
+            self.write_list(id, list)?;
+        }
+        Ok(())
+    }
+}
+
+/// The default, via `get_linked_items`.
+impl Backlogs for MarkdownVault {}
+
+/// Reads every task, via `search::task_matches`. Tasks whose name matches come first, then by
+/// name.
+impl Search<Task> for MarkdownVault {
+    fn search(&self, query: &str) -> HelixFlowResult<impl Iterator<Item = Task>> {
+        if query.split_whitespace().next().is_none() {
+            return Ok(Vec::new().into_iter());
+        }
+        let mut tasks = Vec::new();
+        for id in self.ids(TASKS)? {
+            let task = self.read_task(&id)?;
+            if search::task_matches(&task, query) {
+                tasks.push(task);
+            }
+        }
+        let named = |task: &Task| {
+            let name = task.name.to_lowercase();
+            !query
+                .split_whitespace()
+                .all(|word| name.contains(&word.to_lowercase()))
+        };
+        tasks.sort_by(|a, b| (named(a), &a.name).cmp(&(named(b), &b.name)));
+        Ok(tasks.into_iter())
+    }
+}
+
+#[cfg(test)]
+#[coverage(off)]
+mod tests {
+    use std::{assert_matches::assert_matches, time::Duration};
+
+    use chrono::Utc;
+    use helixflow_core::{
+        CRUD, Link as _,
+        task::{Colour, Priority},
+        versions::Version,
+    };
+    use tempfile::tempdir;
+
+    use super::*;
+
+    #[test]
+    fn task_roundtrip() {
+        let dir = tempdir().unwrap();
+        let backend = MarkdownVault::open(dir.path()).unwrap();
+        let task = Task {
+            colour: Some(Colour::rgb(255, 128, 0)),
+            due: Some(Utc::now()),
+            priority: Priority::P0,
+            estimate: Some(Duration::from_secs(3600)),
+            ..Task::new("Write report", Some("Quarterly\n\n- [ ] Figures"))
+        };
+        task.create(&backend).unwrap();
+        let stored = Task::get(&backend, &task.id).unwrap();
+        assert_eq!(stored, task);
+        assert_eq!(stored.version, Version(Some(0)));
+        let text =
+            fs::read_to_string(dir.path().join(TASKS).join(format!("{}.md", task.id))).unwrap();
+        assert!(text.contains("name: Write report\n"));
+        assert!(text.ends_with("---\nQuarterly\n\n- [ ] Figures\n"));
+
+        let mut changed = stored.clone();
+        changed.complete().unwrap();
+        changed.update(&backend).unwrap();
+        // `stored` is now out of date
+        assert_matches!(
+            stored.update(&backend),
+            Err(HelixFlowError::Conflict { .. })
+        );
+        task.delete(&backend).unwrap();
+        assert_matches!(
+            Task::get(&backend, &task.id),
+            Err(HelixFlowError::NotFound { .. })
+        );
+    }
+
+    #[test]
+    fn edited_outside() {
+        let dir = tempdir().unwrap();
+        let backend = MarkdownVault::open(dir.path()).unwrap();
+        let task = Task::new("Write report", None);
+        task.create(&backend).unwrap();
+        let path = dir.path().join(TASKS).join(format!("{}.md", task.id));
+        let text = fs::read_to_string(&path).unwrap();
+        fs::write(
+            &path,
+            text.replace("status: Todo", "status: Done") + "Notes\r\n",
+        )
+        .unwrap();
+        fs::write(dir.path().join(TASKS).join("Ideas.md"), "Not a task").unwrap();
+
+        let stored = Task::get(&backend, &task.id).unwrap();
+        assert_eq!(stored.status, helixflow_core::task::Status::Done);
+        assert_eq!(stored.description.as_deref(), Some("Notes\r"));
+        assert_eq!(backend.search("report").unwrap().count(), 1);
+
+        fs::write(&path, "status: Done").unwrap();
+        assert!(Task::get(&backend, &task.id).is_err());
+    }
+
+    #[test]
+    fn tasks_in_order() {
+        let dir = tempdir().unwrap();
+        let backlog = TaskList::new("Backlog");
+        let tasks: Vec<Task> = (1..=3)
+            .map(|n| Task::new(format!("Task {n}"), None))
+            .collect();
+        {
+            let backend = MarkdownVault::open(dir.path()).unwrap();
+            backlog.create(&backend).unwrap();
+            for task in &tasks {
+                backlog.link(task).create_linked_item(&backend).unwrap();
+            }
+        }
+        let backend = MarkdownVault::open(dir.path()).unwrap();
+        let names = |tasklist: &TaskList| {
+            backend
+                .backlog(tasklist, true)
+                .unwrap()
+                .into_iter()
+                .map(|task| task.name.to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names(&backlog), vec!["Task 1", "Task 2", "Task 3"]);
+
+        let mut done = tasks[1].clone();
+        done.complete().unwrap();
+        done.update(&backend).unwrap();
+        let index =
+            fs::read_to_string(dir.path().join(LISTS).join(format!("{}.md", backlog.id))).unwrap();
+        assert!(index.contains(&format!("- [x] [[{}|Task 2]]\n", tasks[1].id)));
+        assert_eq!(backend.backlog(&backlog, false).unwrap().len(), 2);
+        assert_eq!(
+            backlog
+                .tasks_page(
+                    &backend,
+                    Page {
+                        offset: 1,
+                        limit: 1
+                    }
+                )
+                .unwrap()[0]
+                .name,
+            "Task 2"
+        );
+
+        let later = TaskList::new("Later");
+        later.create(&backend).unwrap();
+        backend
+            .move_all(&[backlog.link(&tasks[0])], &[later.link(&tasks[0])])
+            .unwrap();
+        assert_eq!(names(&backlog), vec!["Task 2", "Task 3"]);
+        assert_eq!(names(&later), vec!["Task 1"]);
+
+        // Nothing moves if any task is missing
+        let missing = Task::new("Missing", None);
+        assert_matches!(
+            backend.move_all(
+                &[backlog.link(&tasks[1]), backlog.link(&missing)],
+                &[later.link(&tasks[1]), later.link(&missing)]
+            ),
+            Err(HelixFlowError::NotFound { .. })
+        );
+        assert_eq!(names(&backlog), vec!["Task 2", "Task 3"]);
+
+        tasks[2].delete(&backend).unwrap();
+        assert_eq!(names(&backlog), vec!["Task 2"]);
+        later.delete(&backend).unwrap();
+        assert!(Task::get(&backend, &tasks[0].id).is_ok());
+    }
+
+    #[test]
+    fn search_tasks() {
+        let dir = tempdir().unwrap();
+        let backend = MarkdownVault::open(dir.path()).unwrap();
+        Task::new("Pay rent", None).create(&backend).unwrap();
+        Task::new("Write report", Some("About rent"))
+            .create(&backend)
+            .unwrap();
+        let names = |query| {
+            backend
+                .search(query)
+                .unwrap()
+                .map(|task| task.name.to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names("RENT"), vec!["Pay rent", "Write report"]);
+        assert_eq!(names("rent report"), vec!["Write report"]);
+        assert!(names("").is_empty());
+    }
+}
@@ -0,0 +1,861 @@
+#![feature(assert_matches)]
+#![feature(coverage_attribute)]
+//! A backend which keeps everything in `HashMap`s: for demos, benchmarks, WASM and tests which
+//! need a working backend rather than the fixed data in `TestBackend`. Nothing is saved, items
+//! are gone when the backend is dropped.
+//!
+//! Every backend trait in `helixflow_core` is implemented, as simply as possible, so this is also
+//! the reference for how a backend should behave.
+
+use std::{
+    collections::HashMap,
+    sync::{RwLock, RwLockReadGuard, RwLockWriteGuard},
+};
+
+use anyhow::anyhow;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use helixflow_core::{
+    HelixFlowError, HelixFlowItem, HelixFlowResult, Store,
+    actor::Actor,
+    attachment::Attachment,
+    comments::{Comment, Comments},
+    contexts::{Context, Contexts},
+    events::{self, Event, Subscribe, Subscription},
+    history::{Change, ChangeKind, History},
+    person::{People, Person},
+    project::{Project, Projects},
+    query::{Query, TaskQuery},
+    reminders::{Reminder, Reminders},
+    reports::{Burndown, CompletionCounts},
+    search::{self, Search},
+    settings::Settings,
+    share::{Share, ShareToken},
+    state::State,
+    tag::{Tag, Tagged, TaggedWith},
+    task::{Backlogs, Task, TaskList},
+    transaction::Transaction,
+    trash::{Trash, Trashed},
+    user::{User, Watch, Watches},
+    versions::Version,
+    worklog::WorkLog,
+    workspace::{DEFAULT_NAMESPACE, Namespaces},
+};
+
+mod links;
+use links::Edge;
+
+/// Everything stored in one namespace.
+#[derive(Debug, Clone, Default)]
+struct Items {
+    tasks: HashMap<Uuid, Task>,
+    tasklists: HashMap<Uuid, TaskList>,
+    projects: HashMap<Uuid, Project>,
+    tags: HashMap<Uuid, Tag>,
+    contexts: HashMap<Uuid, Context>,
+    people: HashMap<Uuid, Person>,
+    users: HashMap<Uuid, User>,
+    reminders: HashMap<Uuid, Reminder>,
+    worklogs: HashMap<Uuid, WorkLog>,
+    attachments: HashMap<Uuid, Attachment>,
+    comments: HashMap<Uuid, Comment>,
+    settings: HashMap<Uuid, Settings>,
+    states: HashMap<Uuid, State>,
+    shares: HashMap<Uuid, ShareToken>,
+    /// Every relationship, in the order they were made, see `links`
+    links: Vec<Edge>,
+    /// Keyed by task id, oldest change first
+    history: HashMap<Uuid, Vec<Change<Task>>>,
+    /// Keyed by task id
+    trash: HashMap<Uuid, Trashed>,
+}
+
+#[derive(Debug)]
+struct Spaces {
+    current: String,
+    /// Every namespace, including the `current` one
+    items: HashMap<String, Items>,
+    /// The `current` namespace as it was at `Transaction::begin`, restored by `rollback`
+    snapshot: Option<Items>,
+}
+
+/// Keeps all items in memory, in the namespace chosen with `Namespaces`, starting with
+/// `DEFAULT_NAMESPACE`.
+///
+/// Each call waits for exclusive access to change anything, and either makes all its changes or
+/// none, including `create_all`, `update_all` & `move_all`.
+#[derive(Debug)]
+pub struct MemBackend {
+    spaces: RwLock<Spaces>,
+    /// Who changes are attributed to in the `history`, see `act_as`
+    actor: RwLock<Option<Actor>>,
+}
+
+impl Default for MemBackend {
+    fn default() -> Self {
+        MemBackend::new()
+    }
+}
+
+impl MemBackend {
+    /// An empty backend.
+    pub fn new() -> Self {
+        MemBackend {
+            spaces: RwLock::new(Spaces {
+                current: DEFAULT_NAMESPACE.into(),
+                items: HashMap::from([(DEFAULT_NAMESPACE.into(), Items::default())]),
+                snapshot: None,
+            }),
+            actor: RwLock::new(None),
+        }
+    }
+
+    /// Attribute future changes in the `history` to `actor`, e.g. the one from `Settings`.
+    pub fn act_as(&self, actor: &Actor) {
+        *self.actor.write().unwrap() = Some(actor.clone());
+    }
+
+    fn read(&self) -> ItemsRef<'_> {
+        ItemsRef(self.spaces.read().unwrap())
+    }
+
+    /// Make changes to the current namespace. `change` must check it can succeed before making
+    /// any changes, see `write_all`.
+    fn write<T>(
+        &self,
+        change: impl FnOnce(&mut Items) -> HelixFlowResult<T>,
+    ) -> HelixFlowResult<T> {
+        let mut spaces = self.spaces.write().unwrap();
+        let current = spaces.current.clone();
+        change(
+            spaces
+                .items
+                .get_mut(&current)
+                .expect("current namespace exists"),
+        )
+    }
+
+    /// As `write`, for changes in several steps: if any step fails, none of the changes are kept.
+    fn write_all<T>(
+        &self,
+        change: impl FnOnce(&mut Items) -> HelixFlowResult<T>,
+    ) -> HelixFlowResult<T> {
+        self.write(|items| {
+            let mut changed = items.clone();
+            let result = change(&mut changed)?;
+            *items = changed;
+            Ok(result)
+        })
+    }
+
+    /// An entry for the `history`, made now by the current actor.
+    fn change<ITEM>(
+        &self,
+        kind: ChangeKind,
+        before: Option<ITEM>,
+        after: Option<ITEM>,
+    ) -> Change<ITEM> {
+        Change {
+            kind,
+            by: self.actor.read().unwrap().clone(),
+            at: Utc::now(),
+            before,
+            after,
+        }
+    }
+}
+
+/// Read access to the current namespace.
+struct ItemsRef<'spaces>(RwLockReadGuard<'spaces, Spaces>);
+
+impl std::ops::Deref for ItemsRef<'_> {
+    type Target = Items;
+    fn deref(&self) -> &Items {
+        &self.0.items[&self.0.current]
+    }
+}
+
+/// An item type kept in its own `HashMap` in `Items`.
+trait Stored: HelixFlowItem + Clone {
+    /// The name of the type, for `HelixFlowError::NotFound`
+    const NAME: &'static str;
+    fn table(items: &Items) -> &HashMap<Uuid, Self>;
+    fn table_mut(items: &mut Items) -> &mut HashMap<Uuid, Self>;
+    /// This item, as stored at `version`. Only items with a `version` field keep it.
+    fn at_version(self, version: u64) -> Self {
+        let _ = version;
+        self
+    }
+    /// Where changes to this type are recorded, if they are, see `History`.
+    fn history(items: &mut Items) -> Option<&mut HashMap<Uuid, Vec<Change<Self>>>> {
+        let _ = items;
+        None
+    }
+}
+
+macro_rules! stored {
+    ($($item:ident in $table:ident),* $(,)?) => {$(
+        impl Stored for $item {
+            const NAME: &'static str = stringify!($item);
+            fn table(items: &Items) -> &HashMap<Uuid, Self> {
+                &items.$table
+            }
+            fn table_mut(items: &mut Items) -> &mut HashMap<Uuid, Self> {
+                &mut items.$table
+            }
+        }
+    )*};
+}
+
+stored!(
+    TaskList in tasklists,
+    Project in projects,
+    Tag in tags,
+    Context in contexts,
+    Person in people,
+    User in users,
+    Reminder in reminders,
+    WorkLog in worklogs,
+    Attachment in attachments,
+    Comment in comments,
+    Settings in settings,
+    State in states,
+    ShareToken in shares,
+);
+
+/// Versioned, with a `History`.
+impl Stored for Task {
+    const NAME: &'static str = "Task";
+    fn table(items: &Items) -> &HashMap<Uuid, Self> {
+        &items.tasks
+    }
+    fn table_mut(items: &mut Items) -> &mut HashMap<Uuid, Self> {
+        &mut items.tasks
+    }
+    fn at_version(self, version: u64) -> Self {
+        Task {
+            version: Version(Some(version)),
+            ..self
+        }
+    }
+    fn history(items: &mut Items) -> Option<&mut HashMap<Uuid, Vec<Change<Self>>>> {
+        Some(&mut items.history)
+    }
+}
+
+fn not_found<ITEM: Stored>(id: &Uuid) -> HelixFlowError {
+    HelixFlowError::NotFound {
+        itemtype: ITEM::NAME.into(),
+        id: *id,
+    }
+}
+
+impl MemBackend {
+    fn record<ITEM: Stored>(
+        &self,
+        items: &mut Items,
+        id: &Uuid,
+        kind: ChangeKind,
+        before: Option<&ITEM>,
+        after: Option<&ITEM>,
+    ) {
+        if let Some(history) = ITEM::history(items) {
+            let change = self.change(kind, before.cloned(), after.cloned());
+            history.entry(*id).or_default().push(change);
+        }
+    }
+
+    fn create_in<ITEM: Stored>(&self, items: &mut Items, item: &ITEM) -> HelixFlowResult<ITEM> {
+        let id = *item.id();
+        if ITEM::table(items).contains_key(&id) {
+            return Err(anyhow!("{} {id} already exists", ITEM::NAME).into());
+        }
+        let stored = item.clone().at_version(0);
+        ITEM::table_mut(items).insert(id, stored.clone());
+        self.record(items, &id, ChangeKind::Created, None, Some(&stored));
+        Ok(stored)
+    }
+
+    /// See `helixflow_core::versions`.
+    fn update_in<ITEM: Stored>(&self, items: &mut Items, item: &ITEM) -> HelixFlowResult<ITEM> {
+        let id = *item.id();
+        let before = ITEM::table(items)
+            .get(&id)
+            .cloned()
+            .ok_or_else(|| not_found::<ITEM>(&id))?;
+        let version = helixflow_core::versions::next_version(item, before.version().unwrap_or(0))?;
+        let stored = item.clone().at_version(version);
+        ITEM::table_mut(items).insert(id, stored.clone());
+        self.record(
+            items,
+            &id,
+            ChangeKind::Updated,
+            Some(&before),
+            Some(&stored),
+        );
+        Ok(stored)
+    }
+}
+
+impl<ITEM: Stored> Store<ITEM> for MemBackend {
+    fn create(&self, item: &ITEM) -> HelixFlowResult<ITEM> {
+        self.write(|items| self.create_in(items, item))
+    }
+
+    fn get(&self, id: &Uuid) -> HelixFlowResult<ITEM> {
+        ITEM::table(&self.read())
+            .get(id)
+            .cloned()
+            .ok_or_else(|| not_found::<ITEM>(id))
+    }
+
+    fn update(&self, item: &ITEM) -> HelixFlowResult<ITEM> {
+        self.write(|items| self.update_in(items, item))
+    }
+
+    /// Also removes every link to or from the item.
+    fn delete(&self, id: &Uuid) -> HelixFlowResult<()> {
+        self.write(|items| {
+            let before = ITEM::table_mut(items)
+                .remove(id)
+                .ok_or_else(|| not_found::<ITEM>(id))?;
+            items
+                .links
+                .retain(|edge| edge.left != *id && edge.right != *id);
+            self.record(items, id, ChangeKind::Deleted, Some(&before), None);
+            Ok(())
+        })
+    }
+
+    fn create_all(&self, items: &[ITEM]) -> HelixFlowResult<Vec<ITEM>> {
+        self.write_all(|stored| {
+            items
+                .iter()
+                .map(|item| self.create_in(stored, item))
+                .collect()
+        })
+    }
+
+    fn update_all(&self, items: &[ITEM]) -> HelixFlowResult<Vec<ITEM>> {
+        self.write_all(|stored| {
+            items
+                .iter()
+                .map(|item| self.update_in(stored, item))
+                .collect()
+        })
+    }
+
+    fn put(&self, item: &ITEM) -> HelixFlowResult<ITEM> {
+        self.write(|items| match ITEM::table(items).contains_key(item.id()) {
+            true => self.update_in(items, item),
+            false => self.create_in(items, item),
+        })
+    }
+}
+
+impl Namespaces for MemBackend {
+    fn use_namespace(&self, namespace: &str) -> HelixFlowResult<()> {
+        let mut spaces = self.spaces.write().unwrap();
+        spaces.items.entry(namespace.into()).or_default();
+        spaces.current = namespace.into();
+        Ok(())
+    }
+}
+
+/// Only one transaction at a time: `rollback` discards every change made since `begin`, from any
+/// thread.
+impl Transaction for MemBackend {
+    fn begin(&self) -> HelixFlowResult<()> {
+        let mut spaces: RwLockWriteGuard<Spaces> = self.spaces.write().unwrap();
+        if spaces.snapshot.is_some() {
+            return Err(anyhow!("A transaction is already in progress").into());
+        }
+        spaces.snapshot = Some(spaces.items[&spaces.current].clone());
+        Ok(())
+    }
+
+    fn commit(&self) -> HelixFlowResult<()> {
+        self.spaces.write().unwrap().snapshot = None;
+        Ok(())
+    }
+
+    fn rollback(&self) -> HelixFlowResult<()> {
+        let mut spaces = self.spaces.write().unwrap();
+        let snapshot = spaces
+            .snapshot
+            .take()
+            .ok_or_else(|| anyhow!("No transaction in progress"))?;
+        let current = spaces.current.clone();
+        spaces.items.insert(current, snapshot);
+        Ok(())
+    }
+}
+
+/// Recorded by `Store<Task>`.
+impl History<Task> for MemBackend {
+    fn history(&self, id: &Uuid) -> HelixFlowResult<Vec<Change<Task>>> {
+        Ok(self.read().history.get(id).cloned().unwrap_or_default())
+    }
+}
+
+/// Only this process can change the items, so these are the events from `events::subscribe`.
+impl Subscribe<Task> for MemBackend {
+    fn subscribe<F>(&self, subscriber: F) -> HelixFlowResult<Subscription>
+    where
+        F: Fn(&Event) + Send + Sync + 'static,
+    {
+        Ok(events::subscribe(move |event| match event {
+            Event::Created { itemtype, .. }
+            | Event::Updated { itemtype, .. }
+            | Event::Deleted { itemtype, .. }
+                if *itemtype == "Task" =>
+            {
+                subscriber(event)
+            }
+            Event::TaskCompleted { .. } => subscriber(event),
+            _ => (),
+        }))
+    }
+}
+
+/// The default, via `get_linked_items`.
+impl Backlogs for MemBackend {}
+
+/// Via `search::task_matches`, tasks whose name matches come first, then by name.
+impl Search<Task> for MemBackend {
+    fn search(&self, query: &str) -> HelixFlowResult<impl Iterator<Item = Task>> {
+        if query.split_whitespace().next().is_none() {
+            return Ok(Vec::new().into_iter());
+        }
+        let mut tasks: Vec<Task> = self
+            .read()
+            .tasks
+            .values()
+            .filter(|task| search::task_matches(task, query))
+            .cloned()
+            .collect();
+        let named = |task: &Task| {
+            let name = task.name.to_lowercase();
+            !query
+                .split_whitespace()
+                .all(|word| name.contains(&word.to_lowercase()))
+        };
+        tasks.sort_by(|a, b| (named(a), &a.name).cmp(&(named(b), &b.name)));
+        Ok(tasks.into_iter())
+    }
+}
+
+/// Via `TaskQuery::run`.
+impl Query<Task> for MemBackend {
+    fn query(&self, filter: &TaskQuery) -> HelixFlowResult<impl Iterator<Item = Task>> {
+        let tasks: Vec<Task> = self.read().tasks.values().cloned().collect();
+        Ok(filter.run(tasks, self, Utc::now())?.into_iter())
+    }
+}
+
+impl Burndown for MemBackend {
+    fn completion_counts(
+        &self,
+        tasklist: &TaskList,
+        cutoffs: &[DateTime<Utc>],
+    ) -> HelixFlowResult<CompletionCounts> {
+        let tasks = self.backlog(tasklist, true)?;
+        Ok(CompletionCounts::of(&tasks, cutoffs))
+    }
+}
+
+/// Sorted by id, i.e. oldest first.
+fn sorted_by_id<ITEM: Stored>(items: &Items) -> Vec<ITEM> {
+    let mut all: Vec<ITEM> = ITEM::table(items).values().cloned().collect();
+    all.sort_by_key(|item| *item.id());
+    all
+}
+
+impl Projects for MemBackend {
+    /// Oldest first.
+    fn all_projects(&self) -> HelixFlowResult<Vec<Project>> {
+        Ok(sorted_by_id(&self.read()))
+    }
+}
+
+impl People for MemBackend {
+    /// Oldest first.
+    fn all_people(&self) -> HelixFlowResult<Vec<Person>> {
+        Ok(sorted_by_id(&self.read()))
+    }
+}
+
+impl Contexts for MemBackend {
+    /// Oldest first.
+    fn all_contexts(&self) -> HelixFlowResult<Vec<Context>> {
+        Ok(sorted_by_id(&self.read()))
+    }
+}
+
+impl Comments for MemBackend {
+    /// Oldest first.
+    fn comments_on(&self, task: &Task) -> HelixFlowResult<Vec<Comment>> {
+        let mut comments: Vec<Comment> = self
+            .read()
+            .comments
+            .values()
+            .filter(|comment| comment.task == task.id)
+            .cloned()
+            .collect();
+        comments.sort_by_key(|comment| (comment.annotation.at, comment.id));
+        Ok(comments)
+    }
+}
+
+impl Tagged for MemBackend {
+    /// In the order they were tagged.
+    fn tagged_tasks(&self, tag: &Tag) -> HelixFlowResult<Vec<Task>> {
+        let items = self.read();
+        Ok(items
+            .links
+            .iter()
+            .filter(|edge| edge.is::<TaggedWith<Task, Tag>>() && edge.right == tag.id)
+            .filter_map(|edge| items.tasks.get(&edge.left).cloned())
+            .collect())
+    }
+}
+
+impl Reminders for MemBackend {
+    fn due_reminders(&self, now: DateTime<Utc>) -> HelixFlowResult<Vec<Reminder>> {
+        let mut due: Vec<Reminder> = self
+            .read()
+            .reminders
+            .values()
+            .filter(|reminder| reminder.fire_at <= now)
+            .cloned()
+            .collect();
+        due.sort_by_key(|reminder| (reminder.fire_at, reminder.id));
+        Ok(due)
+    }
+}
+
+/// Keyed by the id of the task.
+impl Trash for MemBackend {
+    fn put_in_trash(&self, trashed: &Trashed) -> HelixFlowResult<()> {
+        self.write(|items| {
+            items.trash.insert(trashed.task.id, trashed.clone());
+            Ok(())
+        })
+    }
+
+    fn trashed(&self) -> HelixFlowResult<Vec<Trashed>> {
+        let mut trashed: Vec<Trashed> = self.read().trash.values().cloned().collect();
+        trashed.sort_by(|a, b| {
+            b.deleted_at
+                .cmp(&a.deleted_at)
+                .then(a.task.id.cmp(&b.task.id))
+        });
+        Ok(trashed)
+    }
+
+    fn take_from_trash(&self, id: &Uuid) -> HelixFlowResult<Trashed> {
+        self.write(|items| {
+            items.trash.remove(id).ok_or(HelixFlowError::NotFound {
+                itemtype: "Trashed".into(),
+                id: *id,
+            })
+        })
+    }
+
+    fn purge_trash(&self, deleted_before: DateTime<Utc>) -> HelixFlowResult<usize> {
+        self.write(|items| {
+            let before = items.trash.len();
+            items
+                .trash
+                .retain(|_, trashed| trashed.deleted_at >= deleted_before);
+            Ok(before - items.trash.len())
+        })
+    }
+}
+
+impl Share for MemBackend {
+    fn create_token(&self, token: &ShareToken) -> HelixFlowResult<ShareToken> {
+        self.create(token)
+    }
+
+    fn get_token(&self, id: &Uuid) -> HelixFlowResult<ShareToken> {
+        self.get(id)
+    }
+
+    fn revoke_token(&self, id: &Uuid) -> HelixFlowResult<()> {
+        self.write(|items| {
+            let token = items
+                .shares
+                .get_mut(id)
+                .ok_or_else(|| not_found::<ShareToken>(id))?;
+            token.revoked = true;
+            Ok(())
+        })
+    }
+}
+
+/// Both the user & task must already exist. Watching a task twice is the same as once.
+impl Watch for MemBackend {
+    fn create_watch(&self, watches: &Watches<User, Task>) -> HelixFlowResult<()> {
+        self.write(|items| {
+            if !items.users.contains_key(&watches.left.id) {
+                return Err(not_found::<User>(&watches.left.id));
+            }
+            if !items.tasks.contains_key(&watches.right.id) {
+                return Err(not_found::<Task>(&watches.right.id));
+            }
+            let edge = Edge::new::<Watches<User, Task>>(&watches.left.id, &watches.right.id);
+            if !items.links.iter().any(|existing| existing.joins(&edge)) {
+                items.links.push(edge);
+            }
+            Ok(())
+        })
+    }
+
+    /// In the order they started watching.
+    fn watchers(&self, task: &Task) -> HelixFlowResult<Vec<User>> {
+        let items = self.read();
+        Ok(items
+            .links
+            .iter()
+            .filter(|edge| edge.is::<Watches<User, Task>>() && edge.right == task.id)
+            .filter_map(|edge| items.users.get(&edge.left).cloned())
+            .collect())
+    }
+}
+
+#[cfg(test)]
+#[coverage(off)]
+mod tests {
+    use std::assert_matches::assert_matches;
+
+    use helixflow_core::{
+        CRUD, Link as _, Page, Relate,
+        comments::{Notification, Notifier},
+        task::Status,
+        transaction::atomically,
+    };
+
+    use super::*;
+
+    #[test]
+    fn versions_and_history() {
+        let backend = MemBackend::new();
+        let actor = Actor::new("me", &Uuid::now_v7());
+        backend.act_as(&actor);
+        let task = Task::new("Write report", None);
+        task.create(&backend).unwrap();
+        let stored = Task::get(&backend, &task.id).unwrap();
+        assert_eq!(stored.version, Version(Some(0)));
+
+        let mut done = stored.clone();
+        done.complete().unwrap();
+        done.update(&backend).unwrap();
+        assert_matches!(
+            stored.update(&backend),
+            Err(HelixFlowError::Conflict { .. })
+        );
+        assert_matches!(task.create(&backend), Err(HelixFlowError::BackendError(_)));
+
+        task.delete(&backend).unwrap();
+        let history = task.history(&backend).unwrap();
+        let kinds: Vec<ChangeKind> = history.iter().map(|change| change.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ChangeKind::Created,
+                ChangeKind::Updated,
+                ChangeKind::Deleted
+            ]
+        );
+        assert_eq!(history[1].after.as_ref().unwrap().status, Status::Done);
+        assert_eq!(history[2].by, Some(actor));
+    }
+
+    #[test]
+    fn tasks_in_order() {
+        let backend = MemBackend::new();
+        let backlog = TaskList::new("Backlog");
+        backlog.create(&backend).unwrap();
+        let tasks: Vec<Task> = (1..=3)
+            .map(|n| Task::new(format!("Task {n}"), None))
+            .collect();
+        for task in &tasks {
+            backlog.link(task).create_linked_item(&backend).unwrap();
+        }
+        let names = |tasklist: &TaskList| {
+            backend
+                .backlog(tasklist, true)
+                .unwrap()
+                .into_iter()
+                .map(|task| task.name.to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names(&backlog), vec!["Task 1", "Task 2", "Task 3"]);
+        assert_eq!(
+            backlog
+                .tasks_page(
+                    &backend,
+                    Page {
+                        offset: 1,
+                        limit: 1
+                    }
+                )
+                .unwrap()[0]
+                .name,
+            "Task 2"
+        );
+
+        let later = TaskList::new("Later");
+        later.create(&backend).unwrap();
+        backend
+            .move_all(&[backlog.link(&tasks[0])], &[later.link(&tasks[0])])
+            .unwrap();
+        assert_eq!(names(&backlog), vec!["Task 2", "Task 3"]);
+        assert_eq!(names(&later), vec!["Task 1"]);
+
+        // Nothing moves if any link can't be made
+        let missing = Task::new("Missing", None);
+        assert_matches!(
+            backend.move_all(
+                &[backlog.link(&tasks[1]), backlog.link(&missing)],
+                &[later.link(&tasks[1]), later.link(&missing)]
+            ),
+            Err(HelixFlowError::NotFound { .. })
+        );
+        assert_eq!(names(&backlog), vec!["Task 2", "Task 3"]);
+
+        // Linking doesn't create the task
+        assert_matches!(
+            backend.create_link(&later.link(&missing)),
+            Err(HelixFlowError::NotFound { .. })
+        );
+        backend.create_link(&later.link(&tasks[2])).unwrap();
+        assert_eq!(names(&later), vec!["Task 1", "Task 3"]);
+
+        tasks[1].delete(&backend).unwrap();
+        assert_eq!(names(&backlog), vec!["Task 3"]);
+        later.delete(&backend).unwrap();
+        assert!(Task::get(&backend, &tasks[0].id).is_ok());
+    }
+
+    #[test]
+    fn one_assignee() {
+        let backend = MemBackend::new();
+        let task = Task::new("Write report", None);
+        task.create(&backend).unwrap();
+        for name in ["Ann", "Bob"] {
+            task.assign_to(&Person::new(name, None::<&str>))
+                .create_linked_item(&backend)
+                .unwrap();
+        }
+        assert_eq!(task.assignee(&backend).unwrap().unwrap().name, "Bob");
+        assert_eq!(backend.all_people().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn comments_on_task() {
+        let backend = MemBackend::new();
+        let task = Task::new("Write report", None);
+        let actor = Actor::new("me", &Uuid::now_v7());
+        let first = Comment::new(&task, "First draft done", &actor);
+        let second = Comment::new(&task, "Sent for review", &actor);
+        second.create(&backend).unwrap();
+        first.create(&backend).unwrap();
+        Comment::new(&Task::new("Other", None), "Elsewhere", &actor)
+            .create(&backend)
+            .unwrap();
+        assert_eq!(backend.comments_on(&task).unwrap(), vec![first, second]);
+    }
+
+    #[test]
+    fn post_comment() {
+        #[derive(Default)]
+        struct Outbox(RwLock<Vec<Notification>>);
+        impl Notifier for Outbox {
+            fn notify(&self, notification: Notification) -> HelixFlowResult<()> {
+                self.0.write().unwrap().push(notification);
+                Ok(())
+            }
+        }
+
+        let backend = MemBackend::new();
+        let task = Task::new("Write report", None);
+        let bob = User::new("bob");
+        let comment = Comment::new(
+            &task,
+            "@bob can you review?",
+            &Actor::new("me", &Uuid::now_v7()),
+        );
+        let outbox = Outbox::default();
+        comment
+            .post(&task, std::slice::from_ref(&bob), &backend, &outbox)
+            .unwrap();
+        assert_eq!(backend.comments_on(&task).unwrap(), vec![comment]);
+        let outbox = outbox.0.read().unwrap();
+        assert_eq!(outbox.len(), 1);
+        assert_eq!(outbox[0].recipient, bob);
+    }
+
+    #[test]
+    fn rollback() {
+        let backend = MemBackend::new();
+        let kept = Task::new("Kept", None);
+        let failed: HelixFlowResult<()> = atomically(&backend, || {
+            kept.create(&backend)?;
+            Err(anyhow!("Something went wrong").into())
+        });
+        assert!(failed.is_err());
+        assert_matches!(
+            Task::get(&backend, &kept.id),
+            Err(HelixFlowError::NotFound { .. })
+        );
+        atomically(&backend, || kept.create(&backend)).unwrap();
+        assert!(Task::get(&backend, &kept.id).is_ok());
+    }
+
+    #[test]
+    fn separate_namespaces() {
+        let backend = MemBackend::new();
+        let task = Task::new("At work", None);
+        backend.use_namespace("work").unwrap();
+        task.create(&backend).unwrap();
+        backend.use_namespace(DEFAULT_NAMESPACE).unwrap();
+        assert!(Task::get(&backend, &task.id).is_err());
+        backend.use_namespace("work").unwrap();
+        assert!(Task::get(&backend, &task.id).is_ok());
+    }
+
+    #[test]
+    fn search_and_query() {
+        let backend = MemBackend::new();
+        Task::new("Pay rent", None).create(&backend).unwrap();
+        let mut report = Task::new("Write report", Some("About rent"));
+        report.complete().unwrap();
+        report.create(&backend).unwrap();
+        let names = |tasks: Vec<Task>| {
+            tasks
+                .into_iter()
+                .map(|task| task.name.to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            names(backend.search("RENT").unwrap().collect()),
+            vec!["Pay rent", "Write report"]
+        );
+        assert_eq!(
+            names(backend.search("report").unwrap().collect()),
+            vec!["Write report"]
+        );
+        assert_eq!(
+            names(
+                backend
+                    .query(&TaskQuery::default().status(Status::Done))
+                    .unwrap()
+                    .collect()
+            ),
+            vec!["Write report"]
+        );
+    }
+}
@@ -0,0 +1,318 @@
+//! Every relationship is kept as an `Edge` between the ids of its items, in one list, so that
+//! deleting an item can remove all the links to it.
+
+use anyhow::anyhow;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use helixflow_core::{
+    HelixFlowItem, HelixFlowResult, Link, Page, Relate, Relationship,
+    actor::Actor,
+    attachment::{Attachment, HasAttachment},
+    contexts::{Context, InContext},
+    dependency::DependsOn,
+    person::{AssignedTo, Person},
+    project::Project,
+    rank,
+    tag::{Tag, TaggedWith},
+    task::{Contains, Task, TaskList},
+    worklog::{LoggedAgainst, WorkLog},
+};
+
+use crate::{Items, MemBackend, Stored, not_found};
+
+/// `left` is related to `right` by `relationship`.
+#[derive(Debug, Clone)]
+pub(crate) struct Edge {
+    /// `Relationship::NAME`
+    pub(crate) relationship: &'static str,
+    pub(crate) left: Uuid,
+    pub(crate) right: Uuid,
+    /// Only used by `Contains`
+    pub(crate) sortorder: String,
+    pub(crate) created_at: Option<DateTime<Utc>>,
+    pub(crate) created_by: Option<Actor>,
+}
+
+impl Edge {
+    pub(crate) fn new<REL: Relationship>(left: &Uuid, right: &Uuid) -> Self {
+        Edge {
+            relationship: REL::NAME,
+            left: *left,
+            right: *right,
+            sortorder: String::new(),
+            created_at: None,
+            created_by: None,
+        }
+    }
+
+    /// Is this a `REL`? Relationships between different types of item can share a name, e.g.
+    /// `Contains`, but never link the same pair of ids.
+    pub(crate) fn is<REL: Relationship>(&self) -> bool {
+        self.relationship == REL::NAME
+    }
+
+    /// Does this link the same items as `other`, by the same relationship?
+    pub(crate) fn joins(&self, other: &Edge) -> bool {
+        self.relationship == other.relationship
+            && self.left == other.left
+            && self.right == other.right
+    }
+}
+
+/// What `create_linked_item` does with `right`, see the docs for each relationship.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Creates {
+    /// Always create it
+    Right,
+    /// Create it unless it already exists
+    RightIfMissing,
+    /// It must already exist
+    Nothing,
+}
+
+/// A relationship kept as an `Edge`.
+trait Edged: Link<Left: Stored, Right: Stored> {
+    const CREATES: Creates;
+    /// Each `left` has at most one `right`, e.g. a task's assignee
+    const ONE_PER_LEFT: bool = false;
+    /// Links without a `sortorder` are placed last, rather than oldest first
+    const RANKED: bool = false;
+    fn left(&self) -> &HelixFlowResult<Self::Left>;
+    fn right(&self) -> &HelixFlowResult<Self::Right>;
+    /// The `Edge` to store, as yet without `created_at`
+    fn edge(&self, left: &Uuid, right: &Uuid) -> Edge {
+        Edge::new::<Self>(left, right)
+    }
+    fn from_edge(
+        edge: &Edge,
+        left: HelixFlowResult<Self::Left>,
+        right: HelixFlowResult<Self::Right>,
+    ) -> Self;
+}
+
+macro_rules! edged {
+    ($($rel:ident<$left:ty, $right:ty>: $creates:expr $(, one per left = $one:expr)?;)*) => {$(
+        impl Edged for $rel<$left, $right> {
+            const CREATES: Creates = $creates;
+            $(const ONE_PER_LEFT: bool = $one;)?
+            fn left(&self) -> &HelixFlowResult<$left> {
+                &self.left
+            }
+            fn right(&self) -> &HelixFlowResult<$right> {
+                &self.right
+            }
+            fn from_edge(
+                _edge: &Edge,
+                left: HelixFlowResult<$left>,
+                right: HelixFlowResult<$right>,
+            ) -> Self {
+                $rel { left, right }
+            }
+        }
+    )*};
+}
+
+edged! {
+    DependsOn<Task, Task>: Creates::Nothing;
+    TaggedWith<Task, Tag>: Creates::RightIfMissing;
+    InContext<Task, Context>: Creates::RightIfMissing;
+    AssignedTo<Task, Person>: Creates::RightIfMissing, one per left = true;
+    LoggedAgainst<Task, WorkLog>: Creates::Right;
+    HasAttachment<Task, Attachment>: Creates::Right;
+}
+
+macro_rules! contains {
+    ($($left:ty => $right:ty: $creates:expr, ranked = $ranked:expr;)*) => {$(
+        impl Edged for Contains<$left, $right> {
+            const CREATES: Creates = $creates;
+            const RANKED: bool = $ranked;
+            fn left(&self) -> &HelixFlowResult<$left> {
+                &self.left
+            }
+            fn right(&self) -> &HelixFlowResult<$right> {
+                &self.right
+            }
+            fn edge(&self, left: &Uuid, right: &Uuid) -> Edge {
+                Edge {
+                    sortorder: self.sortorder.clone(),
+                    created_by: self.created_by.clone(),
+                    ..Edge::new::<Self>(left, right)
+                }
+            }
+            fn from_edge(
+                edge: &Edge,
+                left: HelixFlowResult<$left>,
+                right: HelixFlowResult<$right>,
+            ) -> Self {
+                Contains {
+                    left,
+                    sortorder: edge.sortorder.clone(),
+                    created_at: edge.created_at,
+                    created_by: edge.created_by.clone(),
+                    right,
+                }
+            }
+        }
+    )*};
+}
+
+contains! {
+    TaskList => Task: Creates::Right, ranked = true;
+    Task => Task: Creates::Right, ranked = false;
+    Project => TaskList: Creates::RightIfMissing, ranked = false;
+    TaskList => TaskList: Creates::RightIfMissing, ranked = false;
+}
+
+/// The ids of the items `link` joins. Fails if either failed to load.
+fn ends<REL: Edged>(link: &REL) -> HelixFlowResult<(&REL::Left, &REL::Right)> {
+    match (link.left(), link.right()) {
+        (Ok(left), Ok(right)) => Ok((left, right)),
+        _ => Err(anyhow!(
+            "Cannot store a {} link to an item which failed to load",
+            REL::NAME
+        )
+        .into()),
+    }
+}
+
+/// The links from `left`, by `sortorder` then oldest first.
+fn edges_from<'items, REL: Edged>(items: &'items Items, left: &Uuid) -> Vec<&'items Edge> {
+    let mut edges: Vec<&Edge> = items
+        .links
+        .iter()
+        .filter(|edge| {
+            edge.is::<REL>()
+                && edge.left == *left
+                && REL::Right::table(items).contains_key(&edge.right)
+        })
+        .collect();
+    edges.sort_by(|a, b| a.sortorder.cmp(&b.sortorder));
+    edges
+}
+
+impl MemBackend {
+    /// Store `link`, creating its `right` item if `create` & the relationship does. Linking items
+    /// which are already linked only updates the `sortorder`. Nothing is changed unless the link
+    /// can be stored.
+    fn link_in<REL: Edged>(
+        &self,
+        items: &mut Items,
+        link: &REL,
+        create: bool,
+    ) -> HelixFlowResult<Edge> {
+        let (left, right) = ends(link)?;
+        if !REL::Left::table(items).contains_key(left.id()) {
+            return Err(not_found::<REL::Left>(left.id()));
+        }
+        let mut edge = link.edge(left.id(), right.id());
+        if REL::RANKED && edge.sortorder.is_empty() {
+            let last = items
+                .links
+                .iter()
+                .filter(|existing| existing.is::<REL>() && existing.left == edge.left)
+                .map(|existing| existing.sortorder.as_str())
+                .max();
+            edge.sortorder = rank::between(last, None)?;
+        }
+        if let Some(existing) = items
+            .links
+            .iter_mut()
+            .find(|existing| existing.joins(&edge))
+        {
+            existing.sortorder = edge.sortorder;
+            return Ok(existing.clone());
+        }
+        let exists = REL::Right::table(items).contains_key(right.id());
+        match (REL::CREATES, exists) {
+            (Creates::Right, _) | (Creates::RightIfMissing, false) if create => {
+                self.create_in(items, right)?;
+            }
+            (_, true) => (),
+            (_, false) => return Err(not_found::<REL::Right>(right.id())),
+        }
+        if REL::ONE_PER_LEFT {
+            items
+                .links
+                .retain(|existing| !(existing.is::<REL>() && existing.left == edge.left));
+        }
+        edge.created_at = Some(Utc::now());
+        items.links.push(edge.clone());
+        Ok(edge)
+    }
+}
+
+impl<REL: Edged> Relate<REL> for MemBackend {
+    fn create_linked_item(&self, link: &REL) -> HelixFlowResult<REL> {
+        self.write(|items| {
+            let edge = self.link_in(items, link, true)?;
+            let left = REL::Left::table(items).get(&edge.left).cloned();
+            let right = REL::Right::table(items).get(&edge.right).cloned();
+            Ok(REL::from_edge(
+                &edge,
+                left.ok_or_else(|| not_found::<REL::Left>(&edge.left)),
+                right.ok_or_else(|| not_found::<REL::Right>(&edge.right)),
+            ))
+        })
+    }
+
+    /// By `sortorder`, then oldest first.
+    fn get_linked_items(&self, left: &REL::Left) -> HelixFlowResult<impl Iterator<Item = REL>> {
+        self.get_linked_page(
+            left,
+            Page {
+                offset: 0,
+                limit: usize::MAX,
+            },
+        )
+    }
+
+    fn get_linked_page(
+        &self,
+        left: &REL::Left,
+        page: Page,
+    ) -> HelixFlowResult<impl Iterator<Item = REL>> {
+        let items = self.read();
+        let links: Vec<REL> = edges_from::<REL>(&items, left.id())
+            .into_iter()
+            .skip(page.offset)
+            .take(page.limit)
+            .map(|edge| {
+                REL::from_edge(
+                    edge,
+                    Ok(left.clone()),
+                    Ok(REL::Right::table(&items)[&edge.right].clone()),
+                )
+            })
+            .collect();
+        Ok(links.into_iter())
+    }
+
+    fn delete_link(&self, link: &REL) -> HelixFlowResult<()> {
+        let (left, right) = ends(link)?;
+        let edge = Edge::new::<REL>(left.id(), right.id());
+        self.write(|items| {
+            items.links.retain(|existing| !existing.joins(&edge));
+            Ok(())
+        })
+    }
+
+    fn create_link(&self, link: &REL) -> HelixFlowResult<()> {
+        self.write(|items| self.link_in(items, link, false).map(|_| ()))
+    }
+
+    /// All or nothing. Links without a `sortorder` place their items last, in order.
+    fn move_all(&self, from: &[REL], to: &[REL]) -> HelixFlowResult<()> {
+        self.write_all(|items| {
+            for link in from {
+                let (left, right) = ends(link)?;
+                let edge = Edge::new::<REL>(left.id(), right.id());
+                items.links.retain(|existing| !existing.joins(&edge));
+            }
+            for link in to {
+                self.link_in(items, link, false)?;
+            }
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,107 @@
+//! The database schema, built up one `Migration` at a time so that a database shared with
+//! desktops running an older version of HelixFlow is upgraded by the first newer one to connect.
+//!
+//! How many migrations have been applied is stored in the `helixflow_schema` table.
+
+use anyhow::{Context, bail};
+use log::debug;
+use tokio_postgres::Client;
+
+/// One change to the schema.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Migration {
+    pub description: &'static str,
+    /// SQL, run in one transaction with storing the new schema version.
+    pub statements: &'static str,
+}
+
+/// Every change to the schema, oldest first. Never change a released migration: add a new one.
+pub const MIGRATIONS: &[Migration] = &[Migration {
+    description: "Tasks, lists & the `contains` junction table between them",
+    // Ranks compare byte by byte, see `helixflow_core::rank`, so `sortorder` is collated as "C".
+    // TIMESTAMPTZ only keeps microseconds, so when a task is due & was completed are stored as
+    // nanoseconds since the Unix epoch, to read back exactly as written.
+    statements: "CREATE TABLE tasks (
+        id UUID PRIMARY KEY,
+        name TEXT NOT NULL,
+        description TEXT,
+        colour TEXT,
+        due_ns BIGINT,
+        status TEXT NOT NULL DEFAULT 'Todo',
+        priority TEXT NOT NULL DEFAULT 'P2',
+        estimate_secs BIGINT,
+        actual_secs BIGINT,
+        completed_at_ns BIGINT,
+        pinned BOOLEAN NOT NULL DEFAULT FALSE,
+        updated_at TIMESTAMPTZ,
+        version BIGINT NOT NULL DEFAULT 0
+    );
+    CREATE TABLE tasklists (
+        id UUID PRIMARY KEY,
+        name TEXT NOT NULL,
+        sort JSONB NOT NULL DEFAULT '[]'
+    );
+    CREATE TABLE contains (
+        tasklist UUID NOT NULL REFERENCES tasklists (id) ON DELETE CASCADE,
+        task UUID NOT NULL REFERENCES tasks (id) ON DELETE CASCADE,
+        sortorder TEXT COLLATE \"C\" NOT NULL,
+        created_at TIMESTAMPTZ,
+        created_by JSONB,
+        PRIMARY KEY (tasklist, task)
+    );
+    CREATE INDEX contains_in_order ON contains (tasklist, sortorder);",
+}];
+
+/// Key for the advisory lock held while migrating.
+const MIGRATION_LOCK: i64 = 0x4865_6c69_7846_6c6f;
+
+/// Apply any `MIGRATIONS` newer than the stored schema version, all in one transaction. Desktops
+/// connecting at the same time wait for the first to finish. Refuses to use a database migrated
+/// by a newer version of HelixFlow, which this one might corrupt.
+pub(crate) async fn migrate(client: &mut Client) -> anyhow::Result<()> {
+    let tx = client.transaction().await.context("Migrating schema")?;
+    tx.execute("SELECT pg_advisory_xact_lock($1)", &[&MIGRATION_LOCK])
+        .await
+        .context("Waiting for other clients to migrate the schema")?;
+    tx.batch_execute("CREATE TABLE IF NOT EXISTS helixflow_schema (version BIGINT NOT NULL)")
+        .await
+        .context("Reading schema version")?;
+    let current: i64 = tx
+        .query_opt("SELECT version FROM helixflow_schema", &[])
+        .await
+        .context("Reading schema version")?
+        .map_or(0, |row| row.get(0));
+    let current = usize::try_from(current).context("Reading schema version")?;
+    if current > MIGRATIONS.len() {
+        bail!(
+            "The database has schema version {current}, this version of HelixFlow only \
+            understands up to {}",
+            MIGRATIONS.len()
+        );
+    }
+    if current == MIGRATIONS.len() {
+        return Ok(());
+    }
+    for (applied, migration) in MIGRATIONS.iter().enumerate().skip(current) {
+        debug!(
+            "Migrating schema to version {}: {}",
+            applied + 1,
+            migration.description
+        );
+        tx.batch_execute(migration.statements)
+            .await
+            .with_context(|| format!("Migrating schema to version {}", applied + 1))?;
+    }
+    let version = MIGRATIONS.len() as i64;
+    tx.execute("DELETE FROM helixflow_schema", &[])
+        .await
+        .context("Storing schema version")?;
+    tx.execute(
+        "INSERT INTO helixflow_schema (version) VALUES ($1)",
+        &[&version],
+    )
+    .await
+    .context("Storing schema version")?;
+    tx.commit().await.context("Migrating schema")?;
+    Ok(())
+}
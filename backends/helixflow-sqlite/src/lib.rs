@@ -0,0 +1,947 @@
+#![feature(assert_matches)]
+#![feature(coverage_attribute)]
+//! Functionality to utilise a [SQLite](https://sqlite.org) backend: everything in one ordinary
+//! `.db` file, which any SQLite tool can inspect.
+//!
+//! Each item type has a table with a column per field. Which tasks a list holds is kept in the
+//! `contains` junction table, see `schema`.
+
+use std::{
+    borrow::Cow,
+    collections::{HashMap, hash_map::Entry},
+    path::Path,
+    sync::{Condvar, Mutex, MutexGuard},
+    thread::{self, ThreadId},
+    time::Duration,
+};
+
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use log::debug;
+use rusqlite::{Connection, OptionalExtension, Row, params, params_from_iter};
+use serde::{Serialize, de::DeserializeOwned};
+use uuid::Uuid;
+
+use helixflow_core::{
+    HelixFlowError, HelixFlowResult, Page, Relate, Store,
+    actor::Actor,
+    rank,
+    search::Search,
+    task::{Backlogs, Colour, Contains, Task, TaskList},
+    timestamps::LastModified,
+    transaction::Transaction,
+    versions::{Version, next_version},
+};
+
+mod schema;
+pub use schema::{MIGRATIONS, Migration};
+
+/// A SQLite database, in a file or in memory.
+///
+/// SQLite connections can't be shared between threads, so queries wait their turn for the one
+/// connection. While a thread has a transaction open, other threads wait for it to finish.
+#[derive(Debug)]
+pub struct SqliteDb {
+    conn: Mutex<Connection>,
+    /// The thread which has begun a transaction, see `impl Transaction for SqliteDb`
+    transaction: Mutex<Option<ThreadId>>,
+    transaction_finished: Condvar,
+}
+
+impl SqliteDb {
+    /// Open (or create) the database in the file `path`. Every change is written as it is made.
+    pub fn open(path: &Path) -> anyhow::Result<Self> {
+        debug!("Opening {:#?}", path);
+        let conn = Connection::open(path).with_context(|| format!("Opening {:#?}", path))?;
+        Self::with_connection(conn)
+    }
+
+    /// A database held in memory, gone when dropped.
+    pub fn in_memory() -> anyhow::Result<Self> {
+        Self::with_connection(Connection::open_in_memory().context("Opening in-memory database")?)
+    }
+
+    fn with_connection(mut conn: Connection) -> anyhow::Result<Self> {
+        conn.pragma_update(None, "foreign_keys", true)
+            .context("Enabling foreign keys")?;
+        schema::migrate(&mut conn)?;
+        Ok(SqliteDb {
+            conn: Mutex::new(conn),
+            transaction: Mutex::new(None),
+            transaction_finished: Condvar::new(),
+        })
+    }
+
+    /// Wait until no other thread has a transaction open.
+    fn wait_turn(&self) -> MutexGuard<'_, Option<ThreadId>> {
+        let current = thread::current().id();
+        let mut transaction = self.transaction.lock().unwrap();
+        while transaction.is_some_and(|owner| owner != current) {
+            transaction = self.transaction_finished.wait(transaction).unwrap();
+        }
+        transaction
+    }
+
+    fn conn(&self) -> MutexGuard<'_, Connection> {
+        // Held until the connection is ours, so no other thread can begin a transaction first
+        let _turn = self.wait_turn();
+        self.conn.lock().unwrap()
+    }
+
+    /// End the open transaction with `statement`, rolling back if that fails.
+    fn finish_transaction(&self, statement: &str) -> HelixFlowResult<()> {
+        let mut transaction = self.wait_turn();
+        let conn = self.conn.lock().unwrap();
+        let finished = conn.execute_batch(statement);
+        if !conn.is_autocommit() {
+            let _ = conn.execute_batch("ROLLBACK");
+        }
+        *transaction = None;
+        self.transaction_finished.notify_all();
+        Ok(finished.with_context(|| format!("{statement} in SQLite"))?)
+    }
+}
+
+/// The name of a unit enum variant, e.g. `Status::Todo` => `"Todo"`, as stored in a column.
+fn variant<T: Serialize>(value: &T) -> String {
+    serde_json::to_value(value)
+        .ok()
+        .and_then(|value| value.as_str().map(String::from))
+        .unwrap_or_default()
+}
+
+fn from_variant<T: DeserializeOwned>(name: &str) -> HelixFlowResult<T> {
+    Ok(
+        serde_json::from_value(serde_json::Value::String(name.into()))
+            .with_context(|| format!("Unknown value {name:?}"))?,
+    )
+}
+
+fn parse_id(id: &str) -> HelixFlowResult<Uuid> {
+    Uuid::parse_str(id).map_err(|_| HelixFlowError::InvalidID { id: id.into() })
+}
+
+/// A row of the `tasks` table.
+#[derive(Debug)]
+struct SqliteTask {
+    id: String,
+    name: String,
+    description: Option<String>,
+    /// `#rrggbb`
+    colour: Option<String>,
+    due: Option<DateTime<Utc>>,
+    status: String,
+    priority: String,
+    estimate_secs: Option<i64>,
+    actual_secs: Option<i64>,
+    completed_at: Option<DateTime<Utc>>,
+    pinned: bool,
+    updated_at: Option<DateTime<Utc>>,
+    version: i64,
+}
+
+const TASK_COLUMNS: &str = "tasks.id, tasks.name, tasks.description, tasks.colour, tasks.due, \
+    tasks.status, tasks.priority, tasks.estimate_secs, tasks.actual_secs, tasks.completed_at, \
+    tasks.pinned, tasks.updated_at, tasks.version";
+
+impl SqliteTask {
+    /// From a row starting with the `TASK_COLUMNS`.
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(SqliteTask {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            description: row.get(2)?,
+            colour: row.get(3)?,
+            due: row.get(4)?,
+            status: row.get(5)?,
+            priority: row.get(6)?,
+            estimate_secs: row.get(7)?,
+            actual_secs: row.get(8)?,
+            completed_at: row.get(9)?,
+            pinned: row.get(10)?,
+            updated_at: row.get(11)?,
+            version: row.get(12)?,
+        })
+    }
+
+    fn insert(&self, tx: &Connection) -> rusqlite::Result<usize> {
+        tx.execute(
+            "INSERT INTO tasks (id, name, description, colour, due, status, priority, \
+            estimate_secs, actual_secs, completed_at, pinned, updated_at, version) \
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+            params![
+                self.id,
+                self.name,
+                self.description,
+                self.colour,
+                self.due,
+                self.status,
+                self.priority,
+                self.estimate_secs,
+                self.actual_secs,
+                self.completed_at,
+                self.pinned,
+                self.updated_at,
+                self.version,
+            ],
+        )
+    }
+}
+
+impl From<&Task> for SqliteTask {
+    fn from(task: &Task) -> Self {
+        let secs = |duration: Option<Duration>| {
+            duration.map(|duration| i64::try_from(duration.as_secs()).unwrap_or(i64::MAX))
+        };
+        SqliteTask {
+            id: task.id.to_string(),
+            name: task.name.to_string(),
+            description: task.description.as_ref().map(|desc| desc.to_string()),
+            colour: task.colour.map(|colour| {
+                format!("#{:02x}{:02x}{:02x}", colour.red, colour.green, colour.blue)
+            }),
+            due: task.due,
+            status: variant(&task.status),
+            priority: variant(&task.priority),
+            estimate_secs: secs(task.estimate),
+            actual_secs: secs(task.actual),
+            completed_at: task.completed_at,
+            pinned: task.pinned,
+            updated_at: task.updated_at.0,
+            version: 0,
+        }
+    }
+}
+
+impl TryFrom<SqliteTask> for Task {
+    type Error = HelixFlowError;
+    fn try_from(task: SqliteTask) -> HelixFlowResult<Self> {
+        let colour = match task.colour.as_deref() {
+            Some(hex) => {
+                let channel = |n: usize| {
+                    hex.get(1 + 2 * n..3 + 2 * n)
+                        .and_then(|channel| u8::from_str_radix(channel, 16).ok())
+                        .ok_or_else(|| HelixFlowError::InvalidValue {
+                            field: "colour".into(),
+                            value: hex.into(),
+                        })
+                };
+                Some(Colour::rgb(channel(0)?, channel(1)?, channel(2)?))
+            }
+            None => None,
+        };
+        let duration =
+            |secs: Option<i64>| secs.map(|secs| Duration::from_secs(secs.max(0).unsigned_abs()));
+        Ok(Task {
+            name: Cow::Owned(task.name),
+            id: parse_id(&task.id)?,
+            description: task.description.map(Cow::Owned),
+            colour,
+            due: task.due,
+            status: from_variant(&task.status)?,
+            priority: from_variant(&task.priority)?,
+            estimate: duration(task.estimate_secs),
+            actual: duration(task.actual_secs),
+            completed_at: task.completed_at,
+            pinned: task.pinned,
+            updated_at: LastModified(task.updated_at),
+            version: Version(Some(task.version.max(0).unsigned_abs())),
+        })
+    }
+}
+
+/// A row of the `tasklists` table.
+#[derive(Debug)]
+struct SqliteTaskList {
+    id: String,
+    name: String,
+    /// The `SortRule` as JSON
+    sort: String,
+}
+
+impl SqliteTaskList {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(SqliteTaskList {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            sort: row.get(2)?,
+        })
+    }
+}
+
+impl From<&TaskList> for SqliteTaskList {
+    fn from(tasklist: &TaskList) -> Self {
+        SqliteTaskList {
+            id: tasklist.id.to_string(),
+            name: tasklist.name.to_string(),
+            sort: serde_json::to_string(&tasklist.sort).expect("sort rules are serializable"),
+        }
+    }
+}
+
+impl TryFrom<SqliteTaskList> for TaskList {
+    type Error = HelixFlowError;
+    fn try_from(tasklist: SqliteTaskList) -> HelixFlowResult<Self> {
+        Ok(TaskList {
+            name: Cow::Owned(tasklist.name),
+            id: parse_id(&tasklist.id)?,
+            sort: serde_json::from_str(&tasklist.sort).context("Reading sort rule")?,
+        })
+    }
+}
+
+impl Store<Task> for SqliteDb {
+    fn create(&self, task: &Task) -> HelixFlowResult<Task> {
+        let mut conn = self.conn();
+        let tx = conn.savepoint().map_err(anyhow::Error::from)?;
+        SqliteTask::from(task)
+            .insert(&tx)
+            .with_context(|| format!("Creating new record for {:#?} in SQLite", task))?;
+        tx.commit().map_err(anyhow::Error::from)?;
+        drop(conn);
+        self.get(&task.id)
+    }
+
+    fn get(&self, id: &Uuid) -> HelixFlowResult<Task> {
+        let task = self
+            .conn()
+            .query_row(
+                &format!("SELECT {TASK_COLUMNS} FROM tasks WHERE id = ?1"),
+                [id.to_string()],
+                SqliteTask::from_row,
+            )
+            .optional()
+            .map_err(anyhow::Error::from)?;
+        match task {
+            Some(task) => task.try_into(),
+            None => Err(HelixFlowError::NotFound {
+                itemtype: "Task".into(),
+                id: *id,
+            }),
+        }
+    }
+
+    /// Only replaces the stored task if it is still at the version `task` was read at, see
+    /// `versions`.
+    fn update(&self, task: &Task) -> HelixFlowResult<Task> {
+        let mut conn = self.conn();
+        let tx = conn.savepoint().map_err(anyhow::Error::from)?;
+        let stored: Option<i64> = tx
+            .query_row(
+                "SELECT version FROM tasks WHERE id = ?1",
+                [task.id.to_string()],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(anyhow::Error::from)?;
+        let Some(stored) = stored else {
+            return Err(HelixFlowError::NotFound {
+                itemtype: "Task".into(),
+                id: task.id,
+            });
+        };
+        let version = next_version(task, stored.max(0).unsigned_abs())?;
+        let row = SqliteTask {
+            version: i64::try_from(version).unwrap_or(i64::MAX),
+            ..SqliteTask::from(task)
+        };
+        tx.execute(
+            "UPDATE tasks SET name = ?2, description = ?3, colour = ?4, due = ?5, status = ?6, \
+            priority = ?7, estimate_secs = ?8, actual_secs = ?9, completed_at = ?10, \
+            pinned = ?11, updated_at = ?12, version = ?13 WHERE id = ?1",
+            params![
+                row.id,
+                row.name,
+                row.description,
+                row.colour,
+                row.due,
+                row.status,
+                row.priority,
+                row.estimate_secs,
+                row.actual_secs,
+                row.completed_at,
+                row.pinned,
+                row.updated_at,
+                row.version,
+            ],
+        )
+        .with_context(|| format!("Updating {:#?} in SQLite", task))?;
+        tx.commit().map_err(anyhow::Error::from)?;
+        drop(conn);
+        self.get(&task.id)
+    }
+
+    /// Also removes the task from its lists.
+    fn delete(&self, id: &Uuid) -> HelixFlowResult<()> {
+        let deleted = self
+            .conn()
+            .execute("DELETE FROM tasks WHERE id = ?1", [id.to_string()])
+            .map_err(anyhow::Error::from)?;
+        match deleted {
+            0 => Err(HelixFlowError::NotFound {
+                itemtype: "Task".into(),
+                id: *id,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Inserts all the tasks in one transaction.
+    fn create_all(&self, tasks: &[Task]) -> HelixFlowResult<Vec<Task>> {
+        let mut conn = self.conn();
+        let tx = conn.savepoint().map_err(anyhow::Error::from)?;
+        for task in tasks {
+            SqliteTask::from(task)
+                .insert(&tx)
+                .with_context(|| format!("Creating new record for {:#?} in SQLite", task))?;
+        }
+        tx.commit().map_err(anyhow::Error::from)?;
+        drop(conn);
+        tasks.iter().map(|task| self.get(&task.id)).collect()
+    }
+}
+
+impl Store<TaskList> for SqliteDb {
+    fn create(&self, tasklist: &TaskList) -> HelixFlowResult<TaskList> {
+        let row = SqliteTaskList::from(tasklist);
+        self.conn()
+            .execute(
+                "INSERT INTO tasklists (id, name, sort) VALUES (?1, ?2, ?3)",
+                params![row.id, row.name, row.sort],
+            )
+            .with_context(|| format!("Creating new record for {:#?} in SQLite", tasklist))?;
+        self.get(&tasklist.id)
+    }
+
+    fn get(&self, id: &Uuid) -> HelixFlowResult<TaskList> {
+        let tasklist = self
+            .conn()
+            .query_row(
+                "SELECT id, name, sort FROM tasklists WHERE id = ?1",
+                [id.to_string()],
+                SqliteTaskList::from_row,
+            )
+            .optional()
+            .map_err(anyhow::Error::from)?;
+        match tasklist {
+            Some(tasklist) => tasklist.try_into(),
+            None => Err(HelixFlowError::NotFound {
+                itemtype: "TaskList".into(),
+                id: *id,
+            }),
+        }
+    }
+
+    fn update(&self, tasklist: &TaskList) -> HelixFlowResult<TaskList> {
+        let row = SqliteTaskList::from(tasklist);
+        let updated = self
+            .conn()
+            .execute(
+                "UPDATE tasklists SET name = ?2, sort = ?3 WHERE id = ?1",
+                params![row.id, row.name, row.sort],
+            )
+            .with_context(|| format!("Updating {:#?} in SQLite", tasklist))?;
+        match updated {
+            0 => Err(HelixFlowError::NotFound {
+                itemtype: "TaskList".into(),
+                id: tasklist.id,
+            }),
+            _ => self.get(&tasklist.id),
+        }
+    }
+
+    /// Leaves the tasks in place, only the links to them are removed.
+    fn delete(&self, id: &Uuid) -> HelixFlowResult<()> {
+        let deleted = self
+            .conn()
+            .execute("DELETE FROM tasklists WHERE id = ?1", [id.to_string()])
+            .map_err(anyhow::Error::from)?;
+        match deleted {
+            0 => Err(HelixFlowError::NotFound {
+                itemtype: "TaskList".into(),
+                id: *id,
+            }),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// A task read from the `contains` table, with the link's `sortorder`, `created_at` & `created_by`.
+type ContainedTask = (SqliteTask, String, Option<DateTime<Utc>>, Option<String>);
+
+fn contained_task(row: &Row) -> rusqlite::Result<ContainedTask> {
+    Ok((
+        SqliteTask::from_row(row)?,
+        row.get(13)?,
+        row.get(14)?,
+        row.get(15)?,
+    ))
+}
+
+impl SqliteDb {
+    /// The tasks in `tasklist`, by `sortorder` then oldest link first. Only one `page` of them
+    /// if given, and only those not `Done` unless `include_completed`.
+    fn tasks_in(
+        &self,
+        tasklist: &TaskList,
+        page: Option<Page>,
+        include_completed: bool,
+    ) -> HelixFlowResult<Vec<Contains<TaskList, Task>>> {
+        let page = page.unwrap_or(Page {
+            offset: 0,
+            limit: usize::MAX,
+        });
+        let conn = self.conn();
+        let mut query = conn
+            .prepare_cached(&format!(
+                "SELECT {TASK_COLUMNS}, contains.sortorder, contains.created_at, \
+                contains.created_by \
+                FROM contains JOIN tasks ON tasks.id = contains.task \
+                WHERE contains.tasklist = ?1 AND (?2 OR tasks.status != 'Done') \
+                ORDER BY contains.sortorder, contains.created_at \
+                LIMIT ?3 OFFSET ?4"
+            ))
+            .map_err(anyhow::Error::from)?;
+        let rows = query
+            .query_map(
+                params![
+                    tasklist.id.to_string(),
+                    include_completed,
+                    i64::try_from(page.limit).unwrap_or(i64::MAX),
+                    i64::try_from(page.offset).unwrap_or(i64::MAX),
+                ],
+                contained_task,
+            )
+            .map_err(anyhow::Error::from)?
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .map_err(anyhow::Error::from)?;
+        Ok(rows
+            .into_iter()
+            .map(|(task, sortorder, created_at, created_by)| Contains {
+                left: Ok(tasklist.clone()),
+                sortorder,
+                created_at,
+                created_by: created_by.and_then(|actor| serde_json::from_str(&actor).ok()),
+                right: task.try_into(),
+            })
+            .collect())
+    }
+
+    /// The `sortorder` of the last task in `tasklist`, if it has any.
+    fn last_rank(conn: &Connection, tasklist: &TaskList) -> HelixFlowResult<Option<String>> {
+        Ok(conn
+            .query_row(
+                "SELECT MAX(sortorder) FROM contains WHERE tasklist = ?1",
+                [tasklist.id.to_string()],
+                |row| row.get(0),
+            )
+            .map_err(anyhow::Error::from)?)
+    }
+}
+
+fn actor_json(actor: &Option<Actor>) -> Option<String> {
+    actor
+        .as_ref()
+        .map(|actor| serde_json::to_string(actor).expect("actors are serializable"))
+}
+
+impl Relate<Contains<TaskList, Task>> for SqliteDb {
+    /// Creates the task along with the link, in one transaction. A link without a `sortorder`
+    /// places the task last. Linking a task which is already in the tasklist moves it to the
+    /// link's `sortorder`, rather than creating it again.
+    fn create_linked_item(
+        &self,
+        link: &Contains<TaskList, Task>,
+    ) -> HelixFlowResult<Contains<TaskList, Task>> {
+        // TODO - RelBetwErrs (or impl Try for &Contains ...)
+        let tasklist = link.left.as_ref().unwrap();
+        let task = link.right.as_ref().unwrap();
+        let db_tasklist: TaskList = self.get(&tasklist.id)?;
+        let mut conn = self.conn();
+        let tx = conn.savepoint().map_err(anyhow::Error::from)?;
+        let sortorder = match link.sortorder.as_str() {
+            "" => rank::between(Self::last_rank(&tx, &db_tasklist)?.as_deref(), None)?,
+            sortorder => sortorder.to_string(),
+        };
+        let moved = tx
+            .execute(
+                "UPDATE contains SET sortorder = ?3 WHERE tasklist = ?1 AND task = ?2",
+                params![tasklist.id.to_string(), task.id.to_string(), sortorder],
+            )
+            .map_err(anyhow::Error::from)?;
+        let created_at = Some(Utc::now());
+        if moved == 0 {
+            SqliteTask::from(task)
+                .insert(&tx)
+                .with_context(|| format!("Creating {:#?} in its list in SQLite", task))?;
+            tx.execute(
+                "INSERT INTO contains (tasklist, task, sortorder, created_at, created_by) \
+                VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    tasklist.id.to_string(),
+                    task.id.to_string(),
+                    sortorder,
+                    created_at,
+                    actor_json(&link.created_by),
+                ],
+            )
+            .map_err(anyhow::Error::from)?;
+        }
+        tx.commit().map_err(anyhow::Error::from)?;
+        drop(conn);
+        // Moving keeps the original creation metadata
+        let stored = self
+            .tasks_in(&db_tasklist, None, true)?
+            .into_iter()
+            .find(|stored| stored.right.as_ref().is_ok_and(|right| right.id == task.id));
+        Ok(Contains {
+            left: Ok(db_tasklist),
+            sortorder,
+            created_at: stored
+                .as_ref()
+                .map_or(created_at, |stored| stored.created_at),
+            created_by: stored
+                .as_ref()
+                .map_or(link.created_by.clone(), |stored| stored.created_by.clone()),
+            right: self.get(&task.id),
+        })
+    }
+
+    fn get_linked_items(
+        &self,
+        left: &TaskList,
+    ) -> HelixFlowResult<impl Iterator<Item = Contains<TaskList, Task>>> {
+        Ok(self.tasks_in(left, None, true)?.into_iter())
+    }
+
+    /// Uses `LIMIT` & `OFFSET`, so only the page itself is read.
+    fn get_linked_page(
+        &self,
+        left: &TaskList,
+        page: Page,
+    ) -> HelixFlowResult<impl Iterator<Item = Contains<TaskList, Task>>> {
+        Ok(self.tasks_in(left, Some(page), true)?.into_iter())
+    }
+
+    fn delete_link(&self, link: &Contains<TaskList, Task>) -> HelixFlowResult<()> {
+        // TODO - RelBetwErrs (or impl Try for &Contains ...)
+        self.conn()
+            .execute(
+                "DELETE FROM contains WHERE tasklist = ?1 AND task = ?2",
+                params![
+                    link.left.as_ref().unwrap().id.to_string(),
+                    link.right.as_ref().unwrap().id.to_string()
+                ],
+            )
+            .map_err(anyhow::Error::from)?;
+        Ok(())
+    }
+
+    /// Adds an existing task, see `move_all`.
+    fn create_link(&self, link: &Contains<TaskList, Task>) -> HelixFlowResult<()> {
+        self.move_all(&[], std::slice::from_ref(link))
+    }
+
+    /// Makes all the changes in one transaction. Links without a `sortorder` place their tasks
+    /// last, in order.
+    fn move_all(
+        &self,
+        from: &[Contains<TaskList, Task>],
+        to: &[Contains<TaskList, Task>],
+    ) -> HelixFlowResult<()> {
+        // TODO - RelBetwErrs (or impl Try for &Contains ...)
+        let mut conn = self.conn();
+        let tx = conn.savepoint().map_err(anyhow::Error::from)?;
+        for link in from {
+            tx.execute(
+                "DELETE FROM contains WHERE tasklist = ?1 AND task = ?2",
+                params![
+                    link.left.as_ref().unwrap().id.to_string(),
+                    link.right.as_ref().unwrap().id.to_string()
+                ],
+            )
+            .map_err(anyhow::Error::from)?;
+        }
+        let mut last_ranks: HashMap<Uuid, Option<String>> = HashMap::new();
+        for link in to {
+            let tasklist = link.left.as_ref().unwrap();
+            let task = link.right.as_ref().unwrap();
+            let sortorder = match link.sortorder.as_str() {
+                "" => {
+                    let last = match last_ranks.entry(tasklist.id) {
+                        Entry::Occupied(last) => last.into_mut(),
+                        Entry::Vacant(last) => last.insert(Self::last_rank(&tx, tasklist)?),
+                    };
+                    let rank = rank::between(last.as_deref(), None)?;
+                    *last = Some(rank.clone());
+                    rank
+                }
+                sortorder => sortorder.to_string(),
+            };
+            tx.execute(
+                "INSERT OR REPLACE INTO contains \
+                (tasklist, task, sortorder, created_at, created_by) \
+                VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    tasklist.id.to_string(),
+                    task.id.to_string(),
+                    sortorder,
+                    Some(Utc::now()),
+                    actor_json(&link.created_by),
+                ],
+            )
+            .with_context(|| format!("Moving {} tasks in SQLite", to.len()))?;
+        }
+        tx.commit().map_err(anyhow::Error::from)?;
+        Ok(())
+    }
+}
+
+/// A transaction on the connection, which other threads wait for. Changes which are made in one
+/// step, e.g. `Store::create_all`, use a savepoint so they can be part of it.
+impl Transaction for SqliteDb {
+    fn begin(&self) -> HelixFlowResult<()> {
+        let mut transaction = self.wait_turn();
+        self.conn
+            .lock()
+            .unwrap()
+            .execute_batch("BEGIN")
+            .context("Beginning a transaction in SQLite")?;
+        *transaction = Some(thread::current().id());
+        Ok(())
+    }
+
+    fn commit(&self) -> HelixFlowResult<()> {
+        self.finish_transaction("COMMIT")
+    }
+
+    fn rollback(&self) -> HelixFlowResult<()> {
+        self.finish_transaction("ROLLBACK")
+    }
+}
+
+/// Completed tasks are filtered out by the query.
+impl Backlogs for SqliteDb {
+    fn backlog(&self, tasklist: &TaskList, include_completed: bool) -> HelixFlowResult<Vec<Task>> {
+        self.tasks_in(tasklist, None, include_completed)?
+            .into_iter()
+            .map(|contains| contains.right)
+            .collect()
+    }
+}
+
+/// `%` & `_` match themselves in a `LIKE ... ESCAPE '\'` pattern.
+fn like_escaped(word: &str) -> String {
+    word.replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+/// Words match anywhere in the name or description, ignoring ASCII case, without stemming.
+/// Tasks whose name matches the first word come first, then by name.
+impl Search<Task> for SqliteDb {
+    fn search(&self, query: &str) -> HelixFlowResult<impl Iterator<Item = Task>> {
+        let patterns: Vec<String> = query
+            .split_whitespace()
+            .map(|word| format!("%{}%", like_escaped(word)))
+            .collect();
+        if patterns.is_empty() {
+            return Ok(Vec::new().into_iter());
+        }
+        let conditions = (1..=patterns.len())
+            .map(|n| {
+                format!(
+                    "(name LIKE ?{n} ESCAPE '\\' OR IFNULL(description, '') LIKE ?{n} ESCAPE '\\')"
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(" AND ");
+        let conn = self.conn();
+        let mut search = conn
+            .prepare(&format!(
+                "SELECT {TASK_COLUMNS} FROM tasks WHERE {conditions} \
+                ORDER BY name LIKE ?1 ESCAPE '\\' DESC, name"
+            ))
+            .map_err(anyhow::Error::from)?;
+        let tasks = search
+            .query_map(params_from_iter(&patterns), SqliteTask::from_row)
+            .map_err(anyhow::Error::from)?
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .map_err(anyhow::Error::from)?;
+        let tasks = tasks
+            .into_iter()
+            .map(Task::try_from)
+            .collect::<HelixFlowResult<Vec<_>>>()?;
+        Ok(tasks.into_iter())
+    }
+}
+
+#[cfg(test)]
+#[coverage(off)]
+mod tests {
+    use std::assert_matches::assert_matches;
+
+    use helixflow_core::{CRUD, Link as _, task::Priority, transaction::atomically};
+    use tempfile::tempdir;
+
+    use super::*;
+
+    #[test]
+    fn task_roundtrip() {
+        let backend = SqliteDb::in_memory().unwrap();
+        let task = Task {
+            colour: Some(Colour::rgb(255, 128, 0)),
+            due: Some(Utc::now()),
+            priority: Priority::P0,
+            estimate: Some(Duration::from_secs(3600)),
+            ..Task::new("Write report", Some("Quarterly"))
+        };
+        task.create(&backend).unwrap();
+        let stored = Task::get(&backend, &task.id).unwrap();
+        assert_eq!(stored, task);
+        assert_eq!(stored.version, Version(Some(0)));
+
+        let mut changed = stored.clone();
+        changed.complete().unwrap();
+        changed.update(&backend).unwrap();
+        // `stored` is now out of date
+        assert_matches!(
+            stored.update(&backend),
+            Err(HelixFlowError::Conflict { .. })
+        );
+        task.delete(&backend).unwrap();
+        assert_matches!(
+            Task::get(&backend, &task.id),
+            Err(HelixFlowError::NotFound { .. })
+        );
+    }
+
+    #[test]
+    fn tasks_in_order() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("helixflow.db");
+        let backlog = TaskList::new("Backlog");
+        let tasks: Vec<Task> = (1..=3)
+            .map(|n| Task::new(format!("Task {n}"), None))
+            .collect();
+        {
+            let backend = SqliteDb::open(&path).unwrap();
+            backlog.create(&backend).unwrap();
+            for task in &tasks {
+                backlog.link(task).create_linked_item(&backend).unwrap();
+            }
+        }
+        let backend = SqliteDb::open(&path).unwrap();
+        let names = |backend: &SqliteDb| {
+            backend
+                .backlog(&backlog, true)
+                .unwrap()
+                .into_iter()
+                .map(|task| task.name.to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names(&backend), vec!["Task 1", "Task 2", "Task 3"]);
+
+        let last = backlog.get_linked_items(&backend).unwrap().last().unwrap();
+        let mut moved = backlog.link(&tasks[0]);
+        moved.sortorder = rank::between(Some(last.sortorder.as_str()), None).unwrap();
+        backend.create_linked_item(&moved).unwrap();
+        assert_eq!(names(&backend), vec!["Task 2", "Task 3", "Task 1"]);
+
+        let mut done = tasks[1].clone();
+        done.complete().unwrap();
+        done.update(&backend).unwrap();
+        assert_eq!(backend.backlog(&backlog, false).unwrap().len(), 2);
+        assert_eq!(
+            backlog
+                .tasks_page(
+                    &backend,
+                    Page {
+                        offset: 1,
+                        limit: 1
+                    }
+                )
+                .unwrap()[0]
+                .name,
+            "Task 3"
+        );
+
+        backlog.unlink(&tasks[2], &backend).unwrap();
+        assert_eq!(names(&backend), vec!["Task 2", "Task 1"]);
+        assert!(Task::get(&backend, &tasks[2].id).is_ok());
+    }
+
+    #[test]
+    fn search_tasks() {
+        let backend = SqliteDb::in_memory().unwrap();
+        Task::new("Pay rent", None).create(&backend).unwrap();
+        Task::new("Write report", Some("About rent"))
+            .create(&backend)
+            .unwrap();
+        Task::new("100% done", None).create(&backend).unwrap();
+        let names = |query| {
+            backend
+                .search(query)
+                .unwrap()
+                .map(|task| task.name.to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names("RENT"), vec!["Pay rent", "Write report"]);
+        assert_eq!(names("rent report"), vec!["Write report"]);
+        assert_eq!(names("%"), vec!["100% done"]);
+        assert!(names("").is_empty());
+    }
+
+    #[test]
+    fn all_or_nothing() {
+        let backend = SqliteDb::in_memory().unwrap();
+        let backlog = TaskList::new("Backlog");
+        backlog.create(&backend).unwrap();
+        let kept = Task::new("Kept", None);
+        atomically(&backend, || {
+            backlog.link(&kept).create_linked_item(&backend)?;
+            Task::new("Also kept", None).create(&backend)
+        })
+        .unwrap();
+        let lost = Task::new("Lost", None);
+        let failed = atomically(&backend, || {
+            backlog.link(&lost).create_linked_item(&backend)?;
+            // Already stored
+            kept.create(&backend)
+        });
+        assert_matches!(failed, Err(HelixFlowError::BackendError(_)));
+        assert_matches!(
+            Task::get(&backend, &lost.id),
+            Err(HelixFlowError::NotFound { .. })
+        );
+        assert_eq!(
+            backend.backlog(&backlog, true).unwrap(),
+            vec![Task::get(&backend, &kept.id).unwrap()]
+        );
+    }
+
+    #[test]
+    fn other_threads_wait_for_transaction() {
+        let backend = SqliteDb::in_memory().unwrap();
+        let task = Task::new("Rolled back", None);
+        backend.begin().unwrap();
+        task.create(&backend).unwrap();
+        thread::scope(|scope| {
+            let reader = scope.spawn(|| Task::get(&backend, &task.id));
+            backend.rollback().unwrap();
+            assert_matches!(reader.join().unwrap(), Err(HelixFlowError::NotFound { .. }));
+        });
+    }
+
+    #[test]
+    fn newer_schema() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("helixflow.db");
+        drop(SqliteDb::open(&path).unwrap());
+        Connection::open(&path)
+            .unwrap()
+            .pragma_update(None, "user_version", 1000)
+            .unwrap();
+        assert!(SqliteDb::open(&path).is_err());
+    }
+}
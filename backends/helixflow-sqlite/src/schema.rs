@@ -0,0 +1,77 @@
+//! The database schema, built up one migration at a time. SQLite's `user_version` holds how many
+//! have been applied, so a database from an older version of HelixFlow is upgraded when opened.
+
+use anyhow::{Context, bail};
+use log::debug;
+use rusqlite::Connection;
+
+/// One change to the schema.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Migration {
+    pub description: &'static str,
+    /// SQL, run in one transaction with storing the new `user_version`.
+    pub statements: &'static str,
+}
+
+/// Every change to the schema, oldest first. Never change a released migration: add a new one.
+pub const MIGRATIONS: &[Migration] = &[Migration {
+    description: "Tasks, lists & the `contains` junction table between them",
+    statements: "CREATE TABLE tasks (
+        id TEXT PRIMARY KEY NOT NULL,
+        name TEXT NOT NULL,
+        description TEXT,
+        colour TEXT,
+        due TEXT,
+        status TEXT NOT NULL DEFAULT 'Todo',
+        priority TEXT NOT NULL DEFAULT 'P2',
+        estimate_secs INTEGER,
+        actual_secs INTEGER,
+        completed_at TEXT,
+        pinned INTEGER NOT NULL DEFAULT 0,
+        updated_at TEXT,
+        version INTEGER NOT NULL DEFAULT 0
+    );
+    CREATE TABLE tasklists (
+        id TEXT PRIMARY KEY NOT NULL,
+        name TEXT NOT NULL,
+        sort TEXT NOT NULL DEFAULT '[]'
+    );
+    CREATE TABLE contains (
+        tasklist TEXT NOT NULL REFERENCES tasklists (id) ON DELETE CASCADE,
+        task TEXT NOT NULL REFERENCES tasks (id) ON DELETE CASCADE,
+        sortorder TEXT NOT NULL,
+        created_at TEXT,
+        created_by TEXT,
+        PRIMARY KEY (tasklist, task)
+    );
+    CREATE INDEX contains_in_order ON contains (tasklist, sortorder);",
+}];
+
+/// Apply any `MIGRATIONS` newer than the database's `user_version`, each in its own
+/// transaction. Refuses to use a database migrated by a newer version of HelixFlow.
+pub(crate) fn migrate(conn: &mut Connection) -> anyhow::Result<()> {
+    let current: i64 = conn
+        .query_row("PRAGMA user_version", [], |row| row.get(0))
+        .context("Reading schema version")?;
+    let current = usize::try_from(current).context("Reading schema version")?;
+    if current > MIGRATIONS.len() {
+        bail!(
+            "The database has schema version {current}, this version of HelixFlow only \
+            understands up to {}",
+            MIGRATIONS.len()
+        );
+    }
+    for (applied, migration) in MIGRATIONS.iter().enumerate().skip(current) {
+        let version = applied + 1;
+        debug!(
+            "Migrating schema to version {version}: {}",
+            migration.description
+        );
+        let tx = conn.transaction()?;
+        tx.execute_batch(migration.statements)
+            .and_then(|()| tx.pragma_update(None, "user_version", version as i64))
+            .and_then(|()| tx.commit())
+            .with_context(|| format!("Migrating schema to version {version}"))?;
+    }
+    Ok(())
+}
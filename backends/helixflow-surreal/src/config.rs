@@ -0,0 +1,43 @@
+//! Where in a SurrealDb HelixFlow keeps its data, so that several profiles or tenants can share
+//! one database server or directory.
+
+use helixflow_core::workspace::DEFAULT_NAMESPACE;
+
+use crate::{Passphrase, RemoteConfig};
+
+/// Passed to `SurrealDb::with_config` & `SurrealDb::open_with_config`.
+///
+/// Table names are fixed: they are part of every record id, so are stored in links & exports.
+/// Use a separate `database` for each tenant instead, which also keeps their workspaces apart.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SurrealConfig {
+    /// Where the default workspace is kept. Other workspaces have namespaces of their own.
+    pub namespace: String,
+    pub database: String,
+    /// Run queries on this many threads of their own, rather than on the calling thread
+    pub worker_threads: Option<usize>,
+    /// Encrypt the files saved by `SurrealDb::with_config`, see `Passphrase`
+    pub passphrase: Option<Passphrase>,
+}
+
+impl Default for SurrealConfig {
+    fn default() -> Self {
+        SurrealConfig {
+            namespace: DEFAULT_NAMESPACE.into(),
+            database: "HelixFlow".into(),
+            worker_threads: None,
+            passphrase: None,
+        }
+    }
+}
+
+impl From<&RemoteConfig> for SurrealConfig {
+    fn from(remote: &RemoteConfig) -> Self {
+        SurrealConfig {
+            namespace: remote.namespace.clone(),
+            database: remote.database.clone(),
+            worker_threads: remote.worker_threads,
+            passphrase: None,
+        }
+    }
+}
@@ -0,0 +1,540 @@
+//! Exporting a whole namespace on demand, rather than relying on the file saved on drop, and
+//! importing dumps from elsewhere one record at a time.
+
+use std::{collections::HashMap, path::Path};
+
+use anyhow::{Context, bail, ensure};
+use log::debug;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use surrealdb::{
+    Connection, Surreal, Uuid,
+    engine::local::{Db, Mem},
+    sql::{Id, Thing},
+};
+
+use helixflow_core::{
+    HelixFlowItem, HelixFlowResult, Store, Validate,
+    task::{Task, TaskList},
+};
+
+use crate::{ContainsLink, Link, SurrealDb, SurrealTask, SurrealTaskList, item_id};
+
+/// How `SurrealDb::export_to` writes a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// Everything in the namespace, as SurrealQL which SurrealDb can import as it is
+    SurrealQl,
+    /// A `Dump`, readable without SurrealDb
+    Json,
+}
+
+impl ExportFormat {
+    /// `Json` for `*.json`, otherwise `SurrealQl`.
+    pub fn for_path(path: &Path) -> Self {
+        match path.extension() {
+            Some(extension) if extension.eq_ignore_ascii_case("json") => ExportFormat::Json,
+            _ => ExportFormat::SurrealQl,
+        }
+    }
+}
+
+/// The tasks & tasklists in a namespace, and the edges between them.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Dump {
+    pub tasks: Vec<Task>,
+    pub tasklists: Vec<TaskList>,
+    pub edges: Vec<Edge>,
+}
+
+/// A relationship between two items in a `Dump`, e.g. a tasklist which `contains` a task.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Edge {
+    /// `contains` or `depends_on`
+    pub relation: String,
+    pub from: Uuid,
+    pub to: Uuid,
+    /// Where `to` is sorted among the items `from` contains, see `helixflow_core::rank`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sortorder: Option<String>,
+}
+
+impl Edge {
+    fn new(relation: &str, from: &Thing, to: &Thing) -> HelixFlowResult<Self> {
+        Ok(Edge {
+            relation: relation.into(),
+            from: item_id(from)?,
+            to: item_id(to)?,
+            sortorder: None,
+        })
+    }
+}
+
+/// Only edges between tasks & tasklists belong in a `Dump`.
+fn in_dump(thing: &Thing) -> bool {
+    thing.tb == "Tasks" || thing.tb == "Tasklists"
+}
+
+impl<C: Connection> SurrealDb<C> {
+    /// Save the namespace in use to `path`. Never encrypted, even with a
+    /// `SurrealConfig::passphrase`: this is for moving data somewhere else.
+    pub fn export_to(&self, path: &Path, format: ExportFormat) -> anyhow::Result<()> {
+        debug!("Exporting to {:#?} as {format:?}", path);
+        match format {
+            ExportFormat::SurrealQl => self
+                .rt
+                .block_on(self.db.export(path).into_future())
+                .with_context(|| format!("Exporting {:#?}", path)),
+            ExportFormat::Json => {
+                let json =
+                    serde_json::to_string_pretty(&self.dump()?).context("Serialising dump")?;
+                std::fs::write(path, json).with_context(|| format!("Writing {:#?}", path))
+            }
+        }
+    }
+
+    /// The tasks & tasklists in the namespace in use, and the edges between them.
+    pub fn dump(&self) -> HelixFlowResult<Dump> {
+        let tasks: Vec<SurrealTask> = self
+            .rt
+            .block_on(self.db.select("Tasks").into_future())
+            .map_err(anyhow::Error::from)?;
+        let tasklists: Vec<SurrealTaskList> = self
+            .rt
+            .block_on(self.db.select("Tasklists").into_future())
+            .map_err(anyhow::Error::from)?;
+        let contains: Vec<ContainsLink> = self
+            .rt
+            .block_on(
+                self.db
+                    .query("SELECT in, out, sortorder FROM contains")
+                    .into_future(),
+            )
+            .map_err(anyhow::Error::from)?
+            .take(0)
+            .map_err(anyhow::Error::from)?;
+        let depends_on: Vec<Link> = self
+            .rt
+            .block_on(
+                self.db
+                    .query("SELECT in, out FROM depends_on")
+                    .into_future(),
+            )
+            .map_err(anyhow::Error::from)?
+            .take(0)
+            .map_err(anyhow::Error::from)?;
+
+        let mut edges = vec![];
+        for link in contains {
+            if in_dump(&link.r#in) && in_dump(&link.out) {
+                edges.push(Edge {
+                    sortorder: link.sortorder,
+                    ..Edge::new("contains", &link.r#in, &link.out)?
+                });
+            }
+        }
+        for link in depends_on {
+            edges.push(Edge::new("depends_on", &link.r#in, &link.out)?);
+        }
+        Ok(Dump {
+            tasks: tasks
+                .into_iter()
+                .map(Task::try_from)
+                .collect::<HelixFlowResult<_>>()?,
+            tasklists: tasklists
+                .into_iter()
+                .map(TaskList::try_from)
+                .collect::<HelixFlowResult<_>>()?,
+            edges,
+        })
+    }
+}
+
+/// Outcome of `SurrealDb::import_dump`
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ImportReport {
+    pub tasks: usize,
+    pub tasklists: usize,
+    pub edges: usize,
+    /// Records which were not imported, and why
+    pub rejected: Vec<Rejected>,
+}
+
+/// A record which `SurrealDb::import_dump` did not import.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rejected {
+    /// Where the record is in the dump, e.g. `Tasks:42` or `tasks[3]`
+    pub record: String,
+    pub reason: String,
+}
+
+/// Each record in a dump, read on its own so that one bad record doesn't stop the rest.
+#[derive(Default)]
+struct Records {
+    tasks: Vec<(String, anyhow::Result<Task>)>,
+    tasklists: Vec<(String, anyhow::Result<TaskList>)>,
+    edges: Vec<(String, anyhow::Result<Edge>)>,
+}
+
+/// A JSON `Dump`, before checking the records in it.
+#[derive(Deserialize)]
+struct RawDump {
+    #[serde(default)]
+    tasks: Vec<serde_json::Value>,
+    #[serde(default)]
+    tasklists: Vec<serde_json::Value>,
+    #[serde(default)]
+    edges: Vec<serde_json::Value>,
+}
+
+fn parse_all<T: DeserializeOwned>(
+    field: &str,
+    values: Vec<serde_json::Value>,
+) -> Vec<(String, anyhow::Result<T>)> {
+    values
+        .into_iter()
+        .enumerate()
+        .map(|(n, value)| {
+            let record = serde_json::from_value(value).map_err(anyhow::Error::from);
+            (format!("{field}[{n}]"), record)
+        })
+        .collect()
+}
+
+fn read_json(path: &Path) -> anyhow::Result<Records> {
+    let json = std::fs::read_to_string(path).with_context(|| format!("Reading {:#?}", path))?;
+    let dump: RawDump = serde_json::from_str(&json).context("Not a HelixFlow JSON dump")?;
+    Ok(Records {
+        tasks: parse_all("tasks", dump.tasks),
+        tasklists: parse_all("tasklists", dump.tasklists),
+        edges: parse_all("edges", dump.edges),
+    })
+}
+
+impl<C: Connection> SurrealDb<C> {
+    /// Load `path` into a separate, in-memory database, to read it without touching this one.
+    pub(crate) fn stage(&self, path: &Path) -> anyhow::Result<Surreal<Db>> {
+        debug!("Staging {:#?}", path);
+        let staging = self
+            .rt
+            .block_on(Surreal::new::<Mem>(()).into_future())
+            .context("Initialising staging database")?;
+        self.rt
+            .block_on(
+                staging
+                    .use_ns("HelixFlow")
+                    .use_db("HelixFlow")
+                    .into_future(),
+            )
+            .context("Selecting staging database namespace")?;
+        self.rt
+            .block_on(staging.import(path).into_future())
+            .with_context(|| format!("Importing {:#?}", path))?;
+        Ok(staging)
+    }
+
+    /// Add the tasks, tasklists and edges in `path` to the namespace in use, checking each
+    /// record on its own. Records which can't be read, fail `Validate`, clash with an existing
+    /// record, or link to a record which wasn't imported are reported as `Rejected`, the rest
+    /// are imported.
+    ///
+    /// `path` is a `Dump` if it ends in `.json`, otherwise SurrealQL such as `export_to` or
+    /// `surreal export` write, e.g. from another SurrealDb instance.
+    pub fn import_dump(&self, path: &Path) -> anyhow::Result<ImportReport> {
+        let records = match ExportFormat::for_path(path) {
+            ExportFormat::Json => read_json(path)?,
+            ExportFormat::SurrealQl => self.read_surql(path)?,
+        };
+        let mut report = ImportReport::default();
+        let mut tables = HashMap::new();
+        report.tasklists =
+            self.import_items(records.tasklists, "Tasklists", &mut tables, &mut report);
+        report.tasks = self.import_items(records.tasks, "Tasks", &mut tables, &mut report);
+        for (record, edge) in records.edges {
+            match edge.and_then(|edge| self.import_edge(&edge, &tables)) {
+                Ok(()) => report.edges += 1,
+                Err(e) => report.rejected.push(Rejected {
+                    record,
+                    reason: format!("{e:#}"),
+                }),
+            }
+        }
+        debug!("Imported {:#?}: {report:?}", path);
+        Ok(report)
+    }
+
+    /// Each record of the tasks, tasklists & edges between them in a SurrealQL dump.
+    fn read_surql(&self, path: &Path) -> anyhow::Result<Records> {
+        let staging = self.stage(path)?;
+        let mut records = Records::default();
+        for id in self.record_ids(&staging, "Tasks")? {
+            let task = self
+                .staged::<SurrealTask>(&staging, &id)
+                .and_then(|task| Ok(Task::try_from(task)?));
+            records.tasks.push((id.to_string(), task));
+        }
+        for id in self.record_ids(&staging, "Tasklists")? {
+            let tasklist = self
+                .staged::<SurrealTaskList>(&staging, &id)
+                .and_then(|tasklist| Ok(TaskList::try_from(tasklist)?));
+            records.tasklists.push((id.to_string(), tasklist));
+        }
+        for id in self.record_ids(&staging, "contains")? {
+            let edge = match self.staged::<ContainsLink>(&staging, &id) {
+                Ok(link) if !(in_dump(&link.r#in) && in_dump(&link.out)) => continue,
+                Ok(link) => Edge::new("contains", &link.r#in, &link.out)
+                    .map(|edge| Edge {
+                        sortorder: link.sortorder,
+                        ..edge
+                    })
+                    .map_err(anyhow::Error::from),
+                Err(e) => Err(e),
+            };
+            records.edges.push((id.to_string(), edge));
+        }
+        for id in self.record_ids(&staging, "depends_on")? {
+            let edge = self
+                .staged::<Link>(&staging, &id)
+                .and_then(|link| Ok(Edge::new("depends_on", &link.r#in, &link.out)?));
+            records.edges.push((id.to_string(), edge));
+        }
+        Ok(records)
+    }
+
+    fn record_ids(&self, staging: &Surreal<Db>, table: &str) -> anyhow::Result<Vec<Thing>> {
+        self.rt
+            .block_on(
+                staging
+                    .query(format!("SELECT VALUE id FROM {table}"))
+                    .into_future(),
+            )?
+            .take(0)
+            .with_context(|| format!("Listing {table}"))
+    }
+
+    fn staged<T: DeserializeOwned>(&self, staging: &Surreal<Db>, id: &Thing) -> anyhow::Result<T> {
+        let record: Option<T> = self
+            .rt
+            .block_on(
+                staging
+                    .query("SELECT * FROM ONLY $id")
+                    .bind(("id", id.clone()))
+                    .into_future(),
+            )?
+            .take(0)?;
+        record.context("Missing")
+    }
+
+    /// Validate & create each of `items`, noting which `table` each is in. Returns how many were
+    /// imported.
+    fn import_items<ITEM>(
+        &self,
+        items: Vec<(String, anyhow::Result<ITEM>)>,
+        table: &'static str,
+        tables: &mut HashMap<Uuid, &'static str>,
+        report: &mut ImportReport,
+    ) -> usize
+    where
+        Self: Store<ITEM>,
+        ITEM: HelixFlowItem + Validate,
+    {
+        let mut imported = 0;
+        for (record, item) in items {
+            let created = item.and_then(|item| {
+                item.validate()?;
+                Ok(Store::<ITEM>::create(self, &item)?)
+            });
+            match created {
+                Ok(item) => {
+                    tables.insert(*item.id(), table);
+                    imported += 1;
+                }
+                Err(e) => report.rejected.push(Rejected {
+                    record,
+                    reason: format!("{e:#}"),
+                }),
+            }
+        }
+        imported
+    }
+
+    /// Store `edge` between two items imported by the same `import_dump`.
+    fn import_edge(&self, edge: &Edge, tables: &HashMap<Uuid, &'static str>) -> anyhow::Result<()> {
+        let thing = |id: &Uuid| {
+            tables
+                .get(id)
+                .map(|table| Thing::from((*table, Id::Uuid((*id).into()))))
+                .with_context(|| format!("Links to {id}, which was not imported"))
+        };
+        let (r#in, out) = (thing(&edge.from)?, thing(&edge.to)?);
+        match edge.relation.as_str() {
+            "contains" => {
+                let _: Vec<ContainsLink> = self.rt.block_on(
+                    self.db
+                        .insert("contains")
+                        .relation(ContainsLink {
+                            r#in,
+                            out,
+                            sortorder: edge.sortorder.clone(),
+                            created_at: None,
+                            created_by: None,
+                        })
+                        .into_future(),
+                )?;
+            }
+            "depends_on" => {
+                ensure!(
+                    r#in.tb == "Tasks" && out.tb == "Tasks",
+                    "Only tasks can depend on each other"
+                );
+                let _: Vec<Link> = self.rt.block_on(
+                    self.db
+                        .insert("depends_on")
+                        .relation(Link { r#in, out })
+                        .into_future(),
+                )?;
+            }
+            other => bail!("Unknown relation {other}"),
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[coverage(off)]
+mod tests {
+    use helixflow_core::{CRUD, Link as _};
+    use tempfile::tempdir;
+
+    use super::*;
+
+    #[test]
+    fn format_from_extension() {
+        assert_eq!(
+            ExportFormat::for_path(Path::new("backup.JSON")),
+            ExportFormat::Json
+        );
+        assert_eq!(
+            ExportFormat::for_path(Path::new("backup.surql")),
+            ExportFormat::SurrealQl
+        );
+        assert_eq!(
+            ExportFormat::for_path(Path::new("backup")),
+            ExportFormat::SurrealQl
+        );
+    }
+
+    #[test]
+    fn export_both_formats() {
+        let backend = SurrealDb::new(None).unwrap();
+        let backlog = TaskList::new("Backlog");
+        let write = Task::new("Write report", None);
+        let research = Task::new("Research", None);
+        backlog.create(&backend).unwrap();
+        research.create(&backend).unwrap();
+        backlog.link(&write).create_linked_item(&backend).unwrap();
+        write
+            .depends_on(&research)
+            .create_linked_item(&backend)
+            .unwrap();
+
+        let dir = tempdir().unwrap();
+        let json = dir.path().join("backup.json");
+        backend.export_to(&json, ExportFormat::Json).unwrap();
+        let dump: Dump = serde_json::from_str(&std::fs::read_to_string(json).unwrap()).unwrap();
+        assert_eq!(dump.tasks.len(), 2);
+        assert_eq!(dump.tasklists, vec![backlog.clone()]);
+        assert_eq!(dump.edges.len(), 2);
+        let contains = dump
+            .edges
+            .iter()
+            .find(|edge| edge.relation == "contains")
+            .unwrap();
+        assert_eq!((contains.from, contains.to), (backlog.id, write.id));
+        assert!(dump.edges.contains(&Edge {
+            relation: "depends_on".into(),
+            from: write.id,
+            to: research.id,
+            sortorder: None,
+        }));
+
+        let surql = dir.path().join("backup.surql");
+        backend.export_to(&surql, ExportFormat::SurrealQl).unwrap();
+        let restored = SurrealDb::new(None).unwrap();
+        restored.import(&surql).unwrap();
+        assert_eq!(restored.dump().unwrap(), backend.dump().unwrap());
+    }
+
+    #[test]
+    fn import_json_dump() {
+        let backlog = TaskList::new("Backlog");
+        let task = Task::new("Write report", None);
+        let missing = Uuid::now_v7();
+        let mut blank = serde_json::to_value(Task::new("Blank", None)).unwrap();
+        blank["name"] = "  ".into();
+        let dump = serde_json::json!({
+            "tasks": [task, blank, {"name": "No id"}],
+            "tasklists": [backlog],
+            "edges": [
+                {"relation": "contains", "from": backlog.id, "to": task.id, "sortorder": "m"},
+                {"relation": "contains", "from": backlog.id, "to": missing},
+                {"relation": "blocks", "from": task.id, "to": task.id},
+            ],
+        });
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("dump.json");
+        std::fs::write(&path, dump.to_string()).unwrap();
+
+        let backend = SurrealDb::new(None).unwrap();
+        let report = backend.import_dump(&path).unwrap();
+        assert_eq!((report.tasks, report.tasklists, report.edges), (1, 1, 1));
+        assert_eq!(
+            report
+                .rejected
+                .iter()
+                .map(|rejected| rejected.record.as_str())
+                .collect::<Vec<_>>(),
+            vec!["tasks[1]", "tasks[2]", "edges[1]", "edges[2]"]
+        );
+        assert!(report.rejected[2].reason.contains(&missing.to_string()));
+        assert_eq!(Task::get(&backend, &task.id).unwrap(), task);
+        assert_eq!(
+            backend.dump().unwrap().edges[0].sortorder.as_deref(),
+            Some("m")
+        );
+
+        // Everything clashes with what is already there
+        let again = backend.import_dump(&path).unwrap();
+        assert_eq!((again.tasks, again.tasklists, again.edges), (0, 0, 0));
+        assert_eq!(again.rejected.len(), 7);
+    }
+
+    #[test]
+    fn import_surql_dump() {
+        let source = SurrealDb::new(None).unwrap();
+        let backlog = TaskList::new("Backlog");
+        let task = Task::new("Write report", None);
+        backlog.create(&source).unwrap();
+        backlog.link(&task).create_linked_item(&source).unwrap();
+        source
+            .rt
+            .block_on(
+                source
+                    .db
+                    .query("CREATE Tasks:bad SET name = ''")
+                    .into_future(),
+            )
+            .unwrap()
+            .check()
+            .unwrap();
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("dump.surql");
+        source.export_to(&path, ExportFormat::SurrealQl).unwrap();
+
+        let backend = SurrealDb::new(None).unwrap();
+        let report = backend.import_dump(&path).unwrap();
+        assert_eq!((report.tasks, report.tasklists, report.edges), (1, 1, 1));
+        assert_eq!(report.rejected.len(), 1);
+        assert_eq!(report.rejected[0].record, "Tasks:bad");
+        assert_eq!(Task::get(&backend, &task.id).unwrap(), task);
+    }
+}
@@ -0,0 +1,111 @@
+//! Encrypting the files saved by `SurrealDb::new`, so that a stolen laptop doesn't give away
+//! every task. Uses [age](https://age-encryption.org) with a passphrase, so a file can also be
+//! decrypted by hand with `age --decrypt`.
+//!
+//! Only the exported files are encrypted: a database from `SurrealDb::open` is written to disk
+//! by SurrealKv as it is, so rely on full-disk encryption for those.
+
+use std::{
+    fmt,
+    io::{Read, Write},
+    iter,
+};
+
+use age::{Decryptor, Encryptor, scrypt, secrecy::SecretString};
+use anyhow::Context;
+
+/// Encrypts the saved files, see `SurrealConfig::passphrase`. Never shown in `Debug` output.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Passphrase(String);
+
+impl Passphrase {
+    fn secret(&self) -> SecretString {
+        SecretString::from(self.0.clone())
+    }
+}
+
+impl From<&str> for Passphrase {
+    fn from(passphrase: &str) -> Self {
+        Passphrase(passphrase.into())
+    }
+}
+
+impl From<String> for Passphrase {
+    fn from(passphrase: String) -> Self {
+        Passphrase(passphrase)
+    }
+}
+
+impl fmt::Debug for Passphrase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Passphrase(..)")
+    }
+}
+
+pub(crate) fn encrypt(plaintext: &[u8], passphrase: &Passphrase) -> anyhow::Result<Vec<u8>> {
+    let mut encrypted = vec![];
+    let mut writer = Encryptor::with_user_passphrase(passphrase.secret())
+        .wrap_output(&mut encrypted)
+        .context("Encrypting")?;
+    writer.write_all(plaintext).context("Encrypting")?;
+    writer.finish().context("Encrypting")?;
+    Ok(encrypted)
+}
+
+pub(crate) fn decrypt(encrypted: &[u8], passphrase: &Passphrase) -> anyhow::Result<Vec<u8>> {
+    let identity = scrypt::Identity::new(passphrase.secret());
+    let mut reader = Decryptor::new(encrypted)
+        .context("Not encrypted with a passphrase")?
+        .decrypt(iter::once(&identity as &dyn age::Identity))
+        .context("Wrong passphrase")?;
+    let mut plaintext = vec![];
+    reader.read_to_end(&mut plaintext).context("Decrypting")?;
+    Ok(plaintext)
+}
+
+#[cfg(test)]
+#[coverage(off)]
+mod tests {
+    use helixflow_core::{CRUD, task::Task};
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::{SurrealConfig, SurrealDb};
+
+    #[test]
+    fn roundtrip() {
+        let passphrase = Passphrase::from("correct horse battery staple");
+        let encrypted = encrypt(b"All my tasks", &passphrase).unwrap();
+        assert!(!encrypted.windows(5).any(|bytes| bytes == b"tasks"));
+        assert_eq!(decrypt(&encrypted, &passphrase).unwrap(), b"All my tasks");
+        assert!(decrypt(&encrypted, &Passphrase::from("guess")).is_err());
+        assert_eq!(format!("{passphrase:?}"), "Passphrase(..)");
+    }
+
+    #[test]
+    fn encrypted_file() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("helixflow.kv");
+        let config = |passphrase: Option<&str>| SurrealConfig {
+            passphrase: passphrase.map(Passphrase::from),
+            ..Default::default()
+        };
+        let task = Task::new("Secret plans", None);
+        {
+            let backend =
+                SurrealDb::with_config(Some(file.clone()), config(Some("s3cret"))).unwrap();
+            task.create(&backend).unwrap();
+            backend.backup(1).unwrap();
+        }
+        for saved in [&file, &dir.path().join("helixflow.kv.1")] {
+            let contents = std::fs::read(saved).unwrap();
+            assert!(!contents.windows(6).any(|bytes| bytes == b"Secret"));
+        }
+
+        let backend = SurrealDb::with_config(Some(file.clone()), config(Some("s3cret"))).unwrap();
+        assert_eq!(Task::get(&backend, &task.id).unwrap(), task);
+        drop(backend);
+        assert!(SurrealDb::with_config(Some(file.clone()), config(Some("guess"))).is_err());
+        assert!(SurrealDb::with_config(Some(file), config(None)).is_err());
+    }
+}
@@ -0,0 +1,210 @@
+//! Is the database reachable, and how quickly does it answer? Lets the app show that it is
+//! offline rather than blocking until a query times out. Also how much is stored, see `stats`.
+
+use std::{
+    collections::BTreeMap,
+    path::Path,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use surrealdb::Connection;
+
+use helixflow_core::HelixFlowResult;
+
+use crate::SurrealDb;
+
+/// Give up on a ping after this long, and report the database as offline.
+pub const PING_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The result of pinging the database, see `SurrealDb::status`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Health {
+    pub online: bool,
+    /// How long the ping took, `None` if it failed
+    pub latency: Option<Duration>,
+    pub checked_at: DateTime<Utc>,
+    /// Why the ping failed
+    pub error: Option<String>,
+}
+
+/// How much is stored, see `SurrealDb::stats`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Stats {
+    /// Number of records in each table of the namespace in use, e.g. `Tasks` or `contains`
+    pub records: BTreeMap<String, usize>,
+    /// Bytes on disk: the whole directory from `open`, or the namespace's file from `new`.
+    /// `None` for databases held in memory or on a server.
+    pub size_on_disk: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DbInfo {
+    tables: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Count {
+    count: usize,
+}
+
+/// Total size of the files in `path`, including subdirectories. Zero if it doesn't exist yet.
+fn size_on_disk(path: &Path) -> std::io::Result<u64> {
+    if !path.exists() {
+        return Ok(0);
+    }
+    if path.is_file() {
+        return Ok(path.metadata()?.len());
+    }
+    let mut size = 0;
+    for entry in std::fs::read_dir(path)? {
+        size += size_on_disk(&entry?.path())?;
+    }
+    Ok(size)
+}
+
+impl<C: Connection> SurrealDb<C> {
+    /// Count the records in every table, and measure the space used on disk.
+    pub fn stats(&self) -> HelixFlowResult<Stats> {
+        let info: Option<DbInfo> = self
+            .rt
+            .block_on(self.db.query("INFO FOR DB").into_future())
+            .map_err(anyhow::Error::from)?
+            .take(0)
+            .map_err(anyhow::Error::from)?;
+        let mut records = BTreeMap::new();
+        for table in info.map(|info| info.tables).unwrap_or_default().into_keys() {
+            let count: Option<Count> = self
+                .rt
+                .block_on(
+                    self.db
+                        .query(format!("SELECT count() FROM `{table}` GROUP ALL"))
+                        .into_future(),
+                )
+                .map_err(anyhow::Error::from)?
+                .take(0)
+                .map_err(anyhow::Error::from)?;
+            records.insert(table, count.map_or(0, |count| count.count));
+        }
+        let namespace = self.namespace.read().unwrap().clone();
+        let size_on_disk = match (&self.dir, self.namespace_file(&namespace)) {
+            (Some(dir), _) => Some(dir.clone()),
+            (None, file) => file,
+        }
+        .map(|path| size_on_disk(&path).with_context(|| format!("Measuring {:#?}", path)))
+        .transpose()?;
+        Ok(Stats {
+            records,
+            size_on_disk,
+        })
+    }
+
+    /// Ping the database, waiting at most `PING_TIMEOUT`.
+    pub fn status(&self) -> Health {
+        let checked_at = Utc::now();
+        let started = Instant::now();
+        let pinged = self.rt.block_on(tokio::time::timeout(
+            PING_TIMEOUT,
+            self.db.health().into_future(),
+        ));
+        match pinged {
+            Ok(Ok(())) => Health {
+                online: true,
+                latency: Some(started.elapsed()),
+                checked_at,
+                error: None,
+            },
+            Ok(Err(e)) => Health {
+                online: false,
+                latency: None,
+                checked_at,
+                error: Some(e.to_string()),
+            },
+            Err(_) => Health {
+                online: false,
+                latency: None,
+                checked_at,
+                error: Some(format!("No answer within {}s", PING_TIMEOUT.as_secs())),
+            },
+        }
+    }
+
+    /// Ping the database every `interval` in the background, until the backend is dropped, and
+    /// call `on_change` whenever it goes offline or comes back online.
+    pub fn watch_status<F>(self: &Arc<Self>, interval: Duration, on_change: F)
+    where
+        C: 'static,
+        F: Fn(&Health) + Send + 'static,
+    {
+        let online = Mutex::new(None);
+        self.every(interval, move |backend| {
+            let health = backend.status();
+            let mut online = online.lock().unwrap();
+            if *online != Some(health.online) {
+                *online = Some(health.online);
+                on_change(&health);
+            }
+            Ok(())
+        });
+    }
+}
+
+#[cfg(test)]
+#[coverage(off)]
+mod tests {
+    use std::sync::mpsc;
+
+    use helixflow_core::{CRUD, task::Task};
+    use tempfile::tempdir;
+
+    use super::*;
+
+    #[test]
+    fn ping() {
+        let backend = Arc::new(SurrealDb::new(None).unwrap());
+        let health = backend.status();
+        assert!(health.online);
+        assert!(health.latency.unwrap() < PING_TIMEOUT);
+        assert_eq!(health.error, None);
+
+        let (sender, changes) = mpsc::channel();
+        backend.watch_status(Duration::from_millis(10), move |health| {
+            let _ = sender.send(health.online);
+        });
+        assert!(changes.recv_timeout(Duration::from_secs(5)).unwrap());
+        // Only told about changes
+        assert!(changes.recv_timeout(Duration::from_millis(100)).is_err());
+    }
+
+    #[test]
+    fn count_records() {
+        let backend = SurrealDb::new(None).unwrap();
+        let empty = backend.stats().unwrap();
+        assert_eq!(empty.size_on_disk, None);
+        assert_eq!(empty.records.get("Tasks"), Some(&0));
+
+        Task::new("Task 1", None).create(&backend).unwrap();
+        Task::new("Task 2", None).create(&backend).unwrap();
+        let stats = backend.stats().unwrap();
+        assert_eq!(stats.records.get("Tasks"), Some(&2));
+        assert_eq!(stats.records.get("history"), Some(&2));
+    }
+
+    #[test]
+    fn measure_disk() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("helixflow.db");
+        let backend = SurrealDb::open(&path).unwrap();
+        Task::new("Task 1", None).create(&backend).unwrap();
+        assert!(backend.stats().unwrap().size_on_disk.unwrap() > 0);
+
+        let file = dir.path().join("helixflow.kv");
+        let backend = SurrealDb::new(Some(file)).unwrap();
+        assert_eq!(backend.stats().unwrap().size_on_disk, Some(0));
+        backend.flush().unwrap();
+        assert!(backend.stats().unwrap().size_on_disk.unwrap() > 0);
+    }
+}
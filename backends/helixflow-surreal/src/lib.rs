@@ -2,25 +2,111 @@
 #![feature(coverage_attribute)]
 //! Functionality to utilise a [`SurrealDb`](https://surrealdb.com) backend.
 
-use std::{borrow::Cow, path::PathBuf, rc::Rc};
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashMap, HashSet, hash_map::Entry},
+    path::{Path, PathBuf},
+    sync::{
+        Arc, Mutex, RwLock,
+        atomic::{AtomicUsize, Ordering},
+    },
+    thread,
+    time::Duration,
+};
 
 use anyhow::Context;
-use log::debug;
+use futures::StreamExt;
+use log::{debug, error};
 use serde::{Deserialize, Serialize};
+pub use surrealdb::engine::{any::Any, local::Db};
 use surrealdb::{
     Connection, Surreal, Uuid,
-    engine::local::{Db, Mem},
+    engine::local::{Mem, SurrealKv},
     error::Api,
     sql::{Id, Thing},
 };
 
 use helixflow_core::{
     HelixFlowError, HelixFlowResult,
-    state::State,
-    task::{Task, TaskList},
+    actor::{Actor, Annotation},
+    attachment::{Attachment, AttachmentContent, HasAttachment},
+    calendar::WorkingCalendar,
+    comments::{Comment, Comments},
+    contexts::{self, Contexts, InContext},
+    dependency::DependsOn,
+    history::{Change, ChangeKind, History},
+    ids::ItemId,
+    paging::{PAGE_SIZE, Pages},
+    person::{AssignedTo, People, Person},
+    project::{Project, Projects},
+    query::{Query, TaskQuery},
+    quickadd::{Locale, QuickAddRules},
+    rank,
+    reminders::{Reminder, Reminders, Repeat},
+    reports::{Burndown, CompletionCounts},
+    search::Search,
+    settings::Settings,
+    share::{Share, ShareToken},
+    sorting::{SortKey, SortRule},
+    state::{BacklogFilter, CardSettings, State, ViewMode, WindowGeometry},
+    table::TableLayout,
+    tag::{Tag, Tagged, TaggedWith},
+    task::{Backlogs, Colour, Priority, Status, Task, TaskList},
+    timestamps::LastModified,
+    transaction::Transaction,
+    trash::{Trash, Trashed},
+    user::{User, Watch, Watches},
+    versions::{Version, next_version},
+    worklog::{LoggedAgainst, WorkLog},
+    workspace::{DEFAULT_NAMESPACE, Namespaces, Workspace},
 };
 
+mod config;
+mod dump;
+mod encryption;
+mod health;
+mod live;
+mod metrics;
+mod remote;
+mod schema;
+pub use config::SurrealConfig;
+pub use dump::{Dump, Edge, ExportFormat, ImportReport, Rejected};
+pub use encryption::Passphrase;
+pub use health::{Health, PING_TIMEOUT, Stats};
+use metrics::TimedRuntime;
+pub use metrics::{LATENCY_BUCKETS, QueryMetrics, SLOW_QUERY};
+pub use remote::{Credentials, RemoteConfig, is_transient};
+pub use schema::{MIGRATIONS, Migration};
+
+/// The id of the item stored as `thing`. Records keyed by strings or numbers, e.g. in data
+/// imported from elsewhere, are given ids via `ItemId`.
+fn item_id(thing: &Thing) -> HelixFlowResult<Uuid> {
+    match &thing.id {
+        Id::Uuid(id) => Ok(Uuid::from(*id)),
+        Id::String(key) => Ok(key.to_uuid()),
+        Id::Number(key) => Ok(key.to_uuid()),
+        other => Err(HelixFlowError::InvalidID {
+            id: other.to_string(),
+        }),
+    }
+}
+
+/// The record key of `thing`, e.g. `Tasks:42`, unless it is a UUID.
+fn foreign_key(thing: &Thing) -> Option<String> {
+    match thing.id {
+        Id::Uuid(_) => None,
+        _ => Some(thing.to_string()),
+    }
+}
+
+/// Where an item was stored before being given a UUID, see `SurrealDb::original_key`.
 #[derive(Debug, Serialize, Deserialize)]
+struct IdAlias {
+    item: Thing,
+    original: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 /// SurrealDb returns a `Thing` as `id`.
 ///
 /// A `Thing` is a wierd SurrealDb Struct with a `tb` (= "table") and `id` field,
@@ -29,21 +115,46 @@ struct SurrealTask {
     name: Cow<'static, str>,
     id: Thing,
     description: Option<Cow<'static, str>>,
+    #[serde(default)]
+    colour: Option<Colour>,
+    #[serde(default)]
+    due: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default)]
+    status: Status,
+    #[serde(default)]
+    priority: Priority,
+    #[serde(default)]
+    estimate: Option<std::time::Duration>,
+    #[serde(default)]
+    actual: Option<std::time::Duration>,
+    #[serde(default)]
+    completed_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default)]
+    pinned: bool,
+    #[serde(default)]
+    updated_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default)]
+    version: u64,
 }
 
 impl TryFrom<SurrealTask> for Task {
     type Error = HelixFlowError;
     fn try_from(task: SurrealTask) -> HelixFlowResult<Task> {
-        let id = match task.id.id {
-            Id::Uuid(id) => Ok(id.into()),
-            _ => Err(HelixFlowError::InvalidID {
-                id: task.id.id.to_string(),
-            }),
-        };
+        let id = item_id(&task.id);
         Ok(Task {
             name: task.name,
             id: id?,
             description: task.description,
+            colour: task.colour,
+            due: task.due,
+            status: task.status,
+            priority: task.priority,
+            estimate: task.estimate,
+            actual: task.actual,
+            completed_at: task.completed_at,
+            pinned: task.pinned,
+            updated_at: LastModified(task.updated_at),
+            version: Version(Some(task.version)),
         })
     }
 }
@@ -54,6 +165,16 @@ impl From<&Task> for SurrealTask {
             name: task.name.clone(),
             id: Thing::from(("Tasks", Id::Uuid(task.id.into()))),
             description: task.description.clone(),
+            colour: task.colour,
+            due: task.due,
+            status: task.status,
+            priority: task.priority,
+            estimate: task.estimate,
+            actual: task.actual,
+            completed_at: task.completed_at,
+            pinned: task.pinned,
+            updated_at: task.updated_at.0,
+            version: task.version.0.unwrap_or_default(),
         }
     }
 }
@@ -66,20 +187,18 @@ impl From<&Task> for SurrealTask {
 struct SurrealTaskList {
     name: Cow<'static, str>,
     id: Thing,
+    #[serde(default)]
+    sort: SortRule,
 }
 
 impl TryFrom<SurrealTaskList> for TaskList {
     type Error = HelixFlowError;
     fn try_from(tasklist: SurrealTaskList) -> HelixFlowResult<TaskList> {
-        let id = match tasklist.id.id {
-            Id::Uuid(id) => Ok(id.into()),
-            _ => Err(HelixFlowError::InvalidID {
-                id: tasklist.id.id.to_string(),
-            }),
-        };
+        let id = item_id(&tasklist.id);
         Ok(TaskList {
             name: tasklist.name,
             id: id?,
+            sort: tasklist.sort,
         })
     }
 }
@@ -89,6 +208,7 @@ impl From<&TaskList> for SurrealTaskList {
         SurrealTaskList {
             name: tasklist.name.clone(),
             id: Thing::from(("Tasklists", Id::Uuid(tasklist.id.into()))),
+            sort: tasklist.sort.clone(),
         }
     }
 }
@@ -98,7 +218,60 @@ struct Link {
     out: Thing,
 }
 
-use helixflow_core::{Relate, Store, task::Contains};
+/// A `contains` edge with the metadata from `Contains`. Edges stored before each field was
+/// recorded have none.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct ContainsLink {
+    r#in: Thing,
+    out: Thing,
+    /// See `helixflow_core::rank`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    sortorder: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    created_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    created_by: Option<Actor>,
+}
+
+impl ContainsLink {
+    /// A new edge for `link`, created now.
+    fn new<LEFT, RIGHT>(r#in: Thing, out: Thing, link: &Contains<LEFT, RIGHT>) -> ContainsLink {
+        ContainsLink {
+            r#in,
+            out,
+            sortorder: Some(link.sortorder.clone()).filter(|rank| !rank.is_empty()),
+            created_at: Some(chrono::Utc::now()),
+            created_by: link.created_by.clone(),
+        }
+    }
+}
+
+/// An item at the `out` end of a `contains` edge, with the edge's metadata.
+#[derive(Deserialize, Debug)]
+struct ContainedItem<ITEM> {
+    item: ITEM,
+    sortorder: Option<String>,
+    created_at: Option<chrono::DateTime<chrono::Utc>>,
+    created_by: Option<Actor>,
+}
+
+impl<ITEM> ContainedItem<ITEM> {
+    fn contained_by<LEFT, RIGHT>(self, left: &LEFT) -> Contains<LEFT, RIGHT>
+    where
+        LEFT: Clone,
+        ITEM: TryInto<RIGHT, Error = HelixFlowError>,
+    {
+        Contains {
+            left: Ok(left.clone()),
+            sortorder: self.sortorder.unwrap_or_default(),
+            created_at: self.created_at,
+            created_by: self.created_by,
+            right: self.item.try_into(),
+        }
+    }
+}
+
+use helixflow_core::{Page, Relate, Store, task::Contains};
 /// An instance of a SurrealDb ready to use as a `StorageBackend`
 ///
 /// This requires some form of instantiation function, the exact specification of which will depend
@@ -112,16 +285,68 @@ pub struct SurrealDb<C: Connection> {
     /// called without further preamble.
     db: Surreal<C>,
 
-    /// A dedicated tokio runtime to allow for blocking operations
-    rt: Rc<tokio::runtime::Runtime>,
+    /// A dedicated tokio runtime to allow for blocking operations, timing each one
+    rt: Arc<TimedRuntime>,
 
     /// A file where the data will be persisted
     file: Option<PathBuf>,
+
+    /// The directory the database is stored in, see `open`
+    dir: Option<PathBuf>,
+
+    /// Who changes are attributed to in the `history`, see `act_as`
+    actor: RwLock<Option<Actor>>,
+
+    /// The namespace in use, see `Namespaces`
+    namespace: RwLock<String>,
+
+    /// Namespaces which have been imported from their files since opening `file`
+    loaded: RwLock<HashSet<String>>,
+
+    /// Changes recorded since `file` was last saved, see `flush`
+    unsaved: AtomicUsize,
+
+    /// The namespace in use as it was when the open transaction began, see
+    /// `impl Transaction for SurrealDb`
+    snapshot: Mutex<Option<String>>,
+
+    /// The namespace & database to use
+    config: SurrealConfig,
+}
+
+/// Save `file` after this many changes have been recorded in the `history`, as well as on
+/// `flush` & drop.
+pub const FLUSH_AFTER_CHANGES: usize = 50;
+
+/// An entry in the `history` table. `before` & `after` are copies of the record, not links to it.
+#[derive(Debug, Serialize, Deserialize)]
+struct SurrealChange<ITEM> {
+    item: Thing,
+    kind: ChangeKind,
+    by: Option<Actor>,
+    at: chrono::DateTime<chrono::Utc>,
+    before: Option<ITEM>,
+    after: Option<ITEM>,
+}
+
+impl<ITEM> SurrealChange<ITEM> {
+    fn try_into_change<T>(self) -> HelixFlowResult<Change<T>>
+    where
+        ITEM: TryInto<T, Error = HelixFlowError>,
+    {
+        Ok(Change {
+            kind: self.kind,
+            by: self.by,
+            at: self.at,
+            before: self.before.map(TryInto::try_into).transpose()?,
+            after: self.after.map(TryInto::try_into).transpose()?,
+        })
+    }
 }
 
 impl<C: Connection> Store<Task> for SurrealDb<C> {
     fn create(&self, task: &Task) -> HelixFlowResult<Task> {
-        dbg!(task);
+        let _span = tracing::debug_span!("create", table = "Tasks", id = %task.id).entered();
         let dbtask: SurrealTask = self
             .rt
             .block_on(
@@ -132,8 +357,14 @@ impl<C: Connection> Store<Task> for SurrealDb<C> {
             )
             .map_err(anyhow::Error::from)?
             .with_context(|| format!("Creating new record for {:#?} in SurrealDb", task))?;
+        self.record_change(
+            dbtask.id.clone(),
+            ChangeKind::Created,
+            None,
+            Some(dbtask.clone()),
+        )?;
         let checktask = dbtask.try_into()?;
-        dbg!(&checktask);
+        tracing::debug!("Created record");
         Ok(checktask)
     }
 
@@ -151,11 +382,329 @@ impl<C: Connection> Store<Task> for SurrealDb<C> {
             })
         }
     }
+
+    /// Only replaces the stored task if it is still at the version `before` was read at, so that
+    /// a concurrent change is reported as a `HelixFlowError::Conflict` rather than overwritten.
+    fn update(&self, task: &Task) -> HelixFlowResult<Task> {
+        let before: Option<SurrealTask> = self
+            .rt
+            .block_on(self.db.select(("Tasks", task.id)).into_future())
+            .map_err(anyhow::Error::from)?;
+        let Some(before) = before else {
+            return Err(HelixFlowError::NotFound {
+                itemtype: "Task".into(),
+                id: task.id,
+            });
+        };
+        let dbtask = SurrealTask {
+            version: next_version(task, before.version)?,
+            ..SurrealTask::from(task)
+        };
+        let dbtask: Option<SurrealTask> = self
+            .rt
+            .block_on(
+                self.db
+                    .query("UPDATE $task.id CONTENT $task WHERE (version OR 0) = $version")
+                    .bind(("task", dbtask))
+                    .bind(("version", before.version))
+                    .into_future(),
+            )
+            .map_err(anyhow::Error::from)?
+            .take(0)
+            .map_err(anyhow::Error::from)?;
+        match dbtask {
+            Some(dbtask) => {
+                self.record_change(
+                    dbtask.id.clone(),
+                    ChangeKind::Updated,
+                    Some(before),
+                    Some(dbtask.clone()),
+                )?;
+                Ok(dbtask.try_into()?)
+            }
+            // Stored again, or deleted, since `before` was read
+            None => match self.get(&task.id) {
+                Ok(stored) => Err(HelixFlowError::Conflict {
+                    itemtype: "Task".into(),
+                    id: task.id,
+                    expected: before.version,
+                    stored: stored.version.0.unwrap_or_default(),
+                }),
+                Err(e) => Err(e),
+            },
+        }
+    }
+
+    fn delete(&self, id: &Uuid) -> HelixFlowResult<()> {
+        let deleted: Option<SurrealTask> = self
+            .rt
+            .block_on(self.db.delete(("Tasks", *id)).into_future())
+            .map_err(anyhow::Error::from)?;
+        match deleted {
+            Some(task) => {
+                self.record_change(
+                    task.id.clone(),
+                    ChangeKind::Deleted,
+                    Some(task.clone()),
+                    None,
+                )?;
+                self.delete_edges(task.id)
+            }
+            None => Err(HelixFlowError::NotFound {
+                itemtype: "Task".into(),
+                id: *id,
+            }),
+        }
+    }
+
+    /// Inserts all the tasks, and their `history`, in one transaction.
+    fn create_all(&self, tasks: &[Task]) -> HelixFlowResult<Vec<Task>> {
+        let dbtasks: Vec<SurrealTask> = tasks.iter().map(SurrealTask::from).collect();
+        let changes: Vec<SurrealChange<SurrealTask>> = dbtasks
+            .iter()
+            .map(|task| {
+                self.change(
+                    task.id.clone(),
+                    ChangeKind::Created,
+                    None,
+                    Some(task.clone()),
+                )
+            })
+            .collect();
+        self.rt
+            .block_on(
+                self.db
+                    .query(
+                        "BEGIN TRANSACTION; \
+                        INSERT INTO Tasks $tasks; \
+                        INSERT INTO history $changes; \
+                        COMMIT TRANSACTION;",
+                    )
+                    .bind(("tasks", dbtasks))
+                    .bind(("changes", changes))
+                    .into_future(),
+            )
+            .map_err(anyhow::Error::from)?
+            .check()
+            .with_context(|| format!("Creating {} tasks in SurrealDb", tasks.len()))?;
+        self.stored_tasks(tasks)?
+            .into_iter()
+            .map(Task::try_from)
+            .collect()
+    }
+
+    /// Replaces all the tasks, and records their `history`, in one transaction. Nothing is
+    /// replaced if any of them has been stored again since it was read.
+    fn update_all(&self, tasks: &[Task]) -> HelixFlowResult<Vec<Task>> {
+        let before = self.stored_tasks(tasks)?;
+        let dbtasks: Vec<SurrealTask> = tasks
+            .iter()
+            .zip(&before)
+            .map(|(task, before)| {
+                Ok(SurrealTask {
+                    version: next_version(task, before.version)?,
+                    ..SurrealTask::from(task)
+                })
+            })
+            .collect::<HelixFlowResult<_>>()?;
+        let changes: Vec<SurrealChange<SurrealTask>> = before
+            .into_iter()
+            .zip(&dbtasks)
+            .map(|(before, after)| {
+                self.change(
+                    after.id.clone(),
+                    ChangeKind::Updated,
+                    Some(before),
+                    Some(after.clone()),
+                )
+            })
+            .collect();
+        self.rt
+            .block_on(
+                self.db
+                    .query(
+                        "BEGIN TRANSACTION; \
+                        FOR $task IN $tasks { UPDATE $task.id CONTENT $task; }; \
+                        INSERT INTO history $changes; \
+                        COMMIT TRANSACTION;",
+                    )
+                    .bind(("tasks", dbtasks))
+                    .bind(("changes", changes))
+                    .into_future(),
+            )
+            .map_err(anyhow::Error::from)?
+            .check()
+            .with_context(|| format!("Updating {} tasks in SurrealDb", tasks.len()))?;
+        self.stored_tasks(tasks)?
+            .into_iter()
+            .map(Task::try_from)
+            .collect()
+    }
+}
+
+/// Only one transaction at a time: `begin` keeps a copy of the namespace in use and `rollback`
+/// restores it, discarding every change made since `begin`, from any thread.
+///
+/// The SurrealDb client cannot hold `BEGIN TRANSACTION` open across separate queries, so changes
+/// which are made in one step, e.g. `Store::create_all`, are still sent as a single
+/// `BEGIN TRANSACTION; ... COMMIT TRANSACTION;` query.
+impl<C: Connection> Transaction for SurrealDb<C> {
+    fn begin(&self) -> HelixFlowResult<()> {
+        let mut snapshot = self.snapshot.lock().unwrap();
+        if snapshot.is_some() {
+            return Err(anyhow::anyhow!("A transaction is already in progress").into());
+        }
+        let export = self
+            .export_bytes()
+            .context("Beginning a transaction in SurrealDb")?;
+        *snapshot = Some(String::from_utf8(export).context("SurrealDb export is not SurrealQL")?);
+        Ok(())
+    }
+
+    fn commit(&self) -> HelixFlowResult<()> {
+        *self.snapshot.lock().unwrap() = None;
+        Ok(())
+    }
+
+    fn rollback(&self) -> HelixFlowResult<()> {
+        let snapshot = self
+            .snapshot
+            .lock()
+            .unwrap()
+            .take()
+            .ok_or_else(|| anyhow::anyhow!("No transaction in progress"))?;
+        self.rt
+            .block_on(
+                self.db
+                    .query(
+                        "FOR $table IN object::keys((INFO FOR DB).tables) { \
+                            DELETE type::table($table); \
+                        };",
+                    )
+                    .into_future(),
+            )
+            .and_then(|response| response.check())
+            .context("Discarding changes in SurrealDb")?;
+        self.rt
+            .block_on(self.db.query(snapshot).into_future())
+            .and_then(|response| response.check())
+            .context("Restoring SurrealDb to the start of the transaction")?;
+        Ok(())
+    }
+}
+
+impl<C: Connection> SurrealDb<C> {
+    /// The stored records for `tasks`, in the same order. Fails if any are missing.
+    fn stored_tasks(&self, tasks: &[Task]) -> HelixFlowResult<Vec<SurrealTask>> {
+        let ids: Vec<Thing> = tasks
+            .iter()
+            .map(|task| SurrealTask::from(task).id)
+            .collect();
+        let mut response = self
+            .rt
+            .block_on(
+                self.db
+                    .query("SELECT * FROM $ids")
+                    .bind(("ids", ids))
+                    .into_future(),
+            )
+            .map_err(anyhow::Error::from)?;
+        let stored: Vec<SurrealTask> = response.take(0).map_err(anyhow::Error::from)?;
+        let mut stored: HashMap<Thing, SurrealTask> = stored
+            .into_iter()
+            .map(|task| (task.id.clone(), task))
+            .collect();
+        tasks
+            .iter()
+            .map(|task| {
+                stored
+                    .remove(&SurrealTask::from(task).id)
+                    .ok_or(HelixFlowError::NotFound {
+                        itemtype: "Task".into(),
+                        id: task.id,
+                    })
+            })
+            .collect()
+    }
+}
+
+/// Recorded by `Store<Task>`.
+impl<C: Connection> History<Task> for SurrealDb<C> {
+    fn history(&self, id: &Uuid) -> HelixFlowResult<Vec<Change<Task>>> {
+        let mut response = self
+            .rt
+            .block_on(
+                self.db
+                    .query("SELECT * FROM history WHERE item = $item ORDER BY at")
+                    .bind(("item", Thing::from(("Tasks", Id::Uuid((*id).into())))))
+                    .into_future(),
+            )
+            .map_err(anyhow::Error::from)?;
+        let changes: Vec<SurrealChange<SurrealTask>> =
+            response.take(0).map_err(anyhow::Error::from)?;
+        changes
+            .into_iter()
+            .map(SurrealChange::try_into_change)
+            .collect()
+    }
+}
+
+impl<C: Connection> SurrealDb<C> {
+    /// Attribute future changes in the `history` to `actor`, e.g. the one from `Settings`.
+    pub fn act_as(&self, actor: &Actor) {
+        *self.actor.write().unwrap() = Some(actor.clone());
+    }
+
+    /// An entry for the `history` of `item`, made now by the current actor.
+    fn change<ITEM>(
+        &self,
+        item: Thing,
+        kind: ChangeKind,
+        before: Option<ITEM>,
+        after: Option<ITEM>,
+    ) -> SurrealChange<ITEM> {
+        SurrealChange {
+            item,
+            kind,
+            by: self.actor.read().unwrap().clone(),
+            at: chrono::Utc::now(),
+            before,
+            after,
+        }
+    }
+
+    /// Add an entry to the `history` of `item`, made now by the current actor.
+    fn record_change<ITEM: Serialize + 'static>(
+        &self,
+        item: Thing,
+        kind: ChangeKind,
+        before: Option<ITEM>,
+        after: Option<ITEM>,
+    ) -> HelixFlowResult<()> {
+        self.rt
+            .block_on(
+                self.db
+                    .query("CREATE history CONTENT $change")
+                    .bind(("change", self.change(item, kind, before, after)))
+                    .into_future(),
+            )
+            .map_err(anyhow::Error::from)?
+            .check()
+            .map_err(anyhow::Error::from)?;
+        // The change is stored even if saving fails, the next flush will try again
+        if self.unsaved.fetch_add(1, Ordering::SeqCst) + 1 >= FLUSH_AFTER_CHANGES
+            && let Err(e) = self.flush()
+        {
+            error!("Could not save changes: {e:#}");
+        }
+        Ok(())
+    }
 }
 
 impl<C: Connection> Store<TaskList> for SurrealDb<C> {
     fn create(&self, tasklist: &TaskList) -> HelixFlowResult<TaskList> {
-        dbg!(tasklist);
+        let _span =
+            tracing::debug_span!("create", table = "Tasklists", id = %tasklist.id).entered();
         let dbtasklist: SurrealTaskList = self
             .rt
             .block_on(
@@ -167,7 +716,7 @@ impl<C: Connection> Store<TaskList> for SurrealDb<C> {
             .map_err(anyhow::Error::from)?
             .with_context(|| format!("Creating new record for {:#?} in SurrealDb", tasklist))?;
         let check_tasklist = dbtasklist.try_into()?;
-        dbg!(&check_tasklist);
+        tracing::debug!("Created record");
         Ok(check_tasklist)
     }
 
@@ -185,269 +734,4579 @@ impl<C: Connection> Store<TaskList> for SurrealDb<C> {
             })
         }
     }
+
+    fn update(&self, tasklist: &TaskList) -> HelixFlowResult<TaskList> {
+        let dbtasklist: Option<SurrealTaskList> = self
+            .rt
+            .block_on(
+                self.db
+                    .update(("Tasklists", tasklist.id))
+                    .content(SurrealTaskList::from(tasklist))
+                    .into_future(),
+            )
+            .map_err(anyhow::Error::from)?;
+        if let Some(tasklist) = dbtasklist {
+            Ok(tasklist.try_into()?)
+        } else {
+            Err(HelixFlowError::NotFound {
+                itemtype: "TaskList".into(),
+                id: tasklist.id,
+            })
+        }
+    }
+
+    fn delete(&self, id: &Uuid) -> HelixFlowResult<()> {
+        let deleted: Option<SurrealTaskList> = self
+            .rt
+            .block_on(self.db.delete(("Tasklists", *id)).into_future())
+            .map_err(anyhow::Error::from)?;
+        match deleted {
+            Some(tasklist) => self.delete_edges(tasklist.id),
+            None => Err(HelixFlowError::NotFound {
+                itemtype: "TaskList".into(),
+                id: *id,
+            }),
+        }
+    }
 }
 
 impl<C: Connection> Relate<Contains<TaskList, Task>> for SurrealDb<C> {
+    /// A link without a `sortorder` places the task last. Linking a task which is already in
+    /// the tasklist moves it to the link's `sortorder`, rather than creating it again.
     fn create_linked_item(
         &self,
         link: &Contains<TaskList, Task>,
     ) -> HelixFlowResult<Contains<TaskList, Task>> {
-        // TODO make this atomic
         let tasklist = link.left.as_ref().unwrap();
         // TODO - RelBetwErrs (or impl Try for &Contains ...)
         let task = link.right.as_ref().unwrap();
-        dbg!(tasklist);
+        tracing::debug!(table = "Tasklists", id = %tasklist.id, task = %task.id, "Adding task");
         let db_tasklist = self.get(&tasklist.id)?;
-        let db_task = self.create(task)?;
-        let confirmed_link: Vec<Link> = self
+        let edge = Link {
+            r#in: SurrealTaskList::from(&db_tasklist).id,
+            out: SurrealTask::from(task).id,
+        };
+        let sortorder = match link.sortorder.as_str() {
+            "" => rank::between(self.last_rank(&db_tasklist)?.as_deref(), None)?,
+            sortorder => sortorder.to_string(),
+        };
+        let edge = ContainsLink {
+            sortorder: Some(sortorder.clone()),
+            ..ContainsLink::new(edge.r#in, edge.out, link)
+        };
+        let db_task = SurrealTask::from(task);
+        let change = self.change(
+            db_task.id.clone(),
+            ChangeKind::Created,
+            None,
+            Some(db_task.clone()),
+        );
+        // Checking for the link & moving or creating in one transaction, so two callers adding
+        // the same task can't both create it. Moving keeps the original creation metadata.
+        self.rt
+            .block_on(
+                self.db
+                    .query(
+                        "BEGIN TRANSACTION; \
+                        IF array::len((SELECT VALUE id FROM contains WHERE in = $in AND out = $out)) > 0 { \
+                            UPDATE contains SET sortorder = $sortorder WHERE in = $in AND out = $out; \
+                        } ELSE { \
+                            CREATE $task.id CONTENT $task; \
+                            CREATE history CONTENT $change; \
+                            INSERT RELATION INTO contains $edge; \
+                        }; \
+                        COMMIT TRANSACTION;",
+                    )
+                    .bind(("in", edge.r#in.clone()))
+                    .bind(("out", edge.out.clone()))
+                    .bind(("sortorder", sortorder.clone()))
+                    .bind(("task", db_task))
+                    .bind(("change", change))
+                    .bind(("edge", edge.clone()))
+                    .into_future(),
+            )
+            .map_err(anyhow::Error::from)?
+            .check()
+            .with_context(|| format!("Adding {:#?} to {:#?} in SurrealDb", task, tasklist))?;
+        let stored: Vec<ContainsLink> = self
             .rt
             .block_on(
                 self.db
-                    .insert("contains")
-                    .relation(Link {
-                        r#in: SurrealTaskList::from(&db_tasklist).id,
-                        out: SurrealTask::from(&db_task).id,
-                    })
+                    .query(
+                        "SELECT in, out, sortorder, created_at, created_by FROM contains \
+                        WHERE in = $in AND out = $out",
+                    )
+                    .bind(("in", edge.r#in))
+                    .bind(("out", edge.out))
                     .into_future(),
             )
+            .map_err(anyhow::Error::from)?
+            .take(0)
             .map_err(anyhow::Error::from)?;
-        dbg!(confirmed_link);
+        let stored = stored.into_iter().next();
         Ok(Contains {
             left: Ok(db_tasklist),
-            sortorder: "a".into(),
-            right: Ok(db_task),
+            sortorder,
+            created_at: stored.as_ref().and_then(|edge| edge.created_at),
+            created_by: stored.and_then(|edge| edge.created_by),
+            right: self.get(&task.id),
         })
     }
+
+    /// Fetches `PAGE_SIZE` tasks at a time, as they are needed. If a later page can't be fetched
+    /// the last link holds the error as its `right`.
     fn get_linked_items(
         &self,
         left: &TaskList,
     ) -> HelixFlowResult<impl Iterator<Item = Contains<TaskList, Task>>> {
-        let tasklist: SurrealTaskList = left.into();
-        dbg!(&tasklist);
-        let mut tasks = self
-            .rt
+        Pages::new(self, left, PAGE_SIZE, |e| Contains {
+            left: Ok(left.clone()),
+            sortorder: "".into(),
+            created_at: None,
+            created_by: None,
+            right: Err(e),
+        })
+    }
+
+    /// Uses `LIMIT` & `START`, so only the page itself is fetched.
+    fn get_linked_page(
+        &self,
+        left: &TaskList,
+        page: Page,
+    ) -> HelixFlowResult<impl Iterator<Item = Contains<TaskList, Task>>> {
+        Ok(self.tasks_in(left, page)?.into_iter())
+    }
+
+    fn delete_link(&self, link: &Contains<TaskList, Task>) -> HelixFlowResult<()> {
+        // TODO - RelBetwErrs (or impl Try for &Contains ...)
+        self.delete_edge(
+            "contains",
+            Link {
+                r#in: SurrealTaskList::from(link.left.as_ref().unwrap()).id,
+                out: SurrealTask::from(link.right.as_ref().unwrap()).id,
+            },
+        )
+    }
+
+    /// Adds an existing task, see `move_all`.
+    fn create_link(&self, link: &Contains<TaskList, Task>) -> HelixFlowResult<()> {
+        self.move_all(&[], std::slice::from_ref(link))
+    }
+
+    /// Replaces all the edges in one transaction. Links without a `sortorder` place their tasks
+    /// last, in order.
+    fn move_all(
+        &self,
+        from: &[Contains<TaskList, Task>],
+        to: &[Contains<TaskList, Task>],
+    ) -> HelixFlowResult<()> {
+        // TODO - RelBetwErrs (or impl Try for &Contains ...)
+        let removed: Vec<Link> = from
+            .iter()
+            .map(|link| Link {
+                r#in: SurrealTaskList::from(link.left.as_ref().unwrap()).id,
+                out: SurrealTask::from(link.right.as_ref().unwrap()).id,
+            })
+            .collect();
+        let mut last_ranks: HashMap<Uuid, Option<String>> = HashMap::new();
+        let mut added = Vec::with_capacity(to.len());
+        for link in to {
+            let tasklist = link.left.as_ref().unwrap();
+            let task = link.right.as_ref().unwrap();
+            let sortorder = match link.sortorder.as_str() {
+                "" => {
+                    let last = match last_ranks.entry(tasklist.id) {
+                        Entry::Occupied(last) => last.into_mut(),
+                        Entry::Vacant(last) => last.insert(self.last_rank(tasklist)?),
+                    };
+                    let rank = rank::between(last.as_deref(), None)?;
+                    *last = Some(rank.clone());
+                    rank
+                }
+                sortorder => sortorder.to_string(),
+            };
+            added.push(ContainsLink {
+                sortorder: Some(sortorder),
+                ..ContainsLink::new(
+                    SurrealTaskList::from(tasklist).id,
+                    SurrealTask::from(task).id,
+                    link,
+                )
+            });
+        }
+        self.rt
             .block_on(
                 self.db
-                    .query("SELECT ->contains->Tasks.* AS tasks FROM $tl")
-                    .bind(("tl", tasklist.id))
+                    .query(
+                        "BEGIN TRANSACTION; \
+                        FOR $link IN $removed { \
+                            DELETE contains WHERE in = $link.in AND out = $link.out; \
+                        }; \
+                        FOR $link IN $added { \
+                            DELETE contains WHERE in = $link.in AND out = $link.out; \
+                        }; \
+                        INSERT RELATION INTO contains $added; \
+                        COMMIT TRANSACTION;",
+                    )
+                    .bind(("removed", removed))
+                    .bind(("added", added))
                     .into_future(),
             )
-            .map_err(anyhow::Error::from)?;
-        dbg!(&tasks);
-        let tasks: Vec<Vec<SurrealTask>> = tasks.take("tasks").map_err(anyhow::Error::from)?;
-        dbg!(&tasks);
-        let relationships = tasks
-            .into_iter()
-            .next()
-            .unwrap()
-            .into_iter()
-            .map(|task| Contains {
-                left: Ok(left.clone()),
-                sortorder: "a".into(),
-                right: task.try_into(),
-            });
-        Ok(relationships)
+            .map_err(anyhow::Error::from)?
+            .check()
+            .with_context(|| format!("Moving {} tasks in SurrealDb", to.len()))?;
+        Ok(())
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct SurrealState {
-    visible_backlog: Option<Uuid>,
-    id: Thing,
+/// Completed tasks are filtered out by the query.
+impl<C: Connection> Backlogs for SurrealDb<C> {
+    fn backlog(&self, tasklist: &TaskList, include_completed: bool) -> HelixFlowResult<Vec<Task>> {
+        let condition = (!include_completed).then_some("out.status != 'Done'");
+        let tasks: Vec<ContainedItem<SurrealTask>> =
+            self.contained(SurrealTaskList::from(tasklist).id, "Tasks", None, condition)?;
+        tasks.into_iter().map(|task| task.item.try_into()).collect()
+    }
 }
 
-impl TryFrom<SurrealState> for State {
-    type Error = HelixFlowError;
-    fn try_from(state: SurrealState) -> HelixFlowResult<State> {
-        let id = match state.id.id {
-            Id::Uuid(id) => Ok(id.into()),
-            _ => Err(HelixFlowError::InvalidID {
-                id: state.id.id.to_string(),
-            }),
-        };
-        let mut stored_state = State::new(&id?);
-        stored_state.visible_backlog(&TaskList {
-            name: "".into(),
-            id: state.visible_backlog.unwrap(),
-        });
-        Ok(stored_state)
+impl<C: Connection> SurrealDb<C> {
+    /// Create `task` and the `contains` edge to it in one transaction, so that a failure can't
+    /// leave the task outside its list.
+    fn create_contained(&self, task: &Task, edge: &ContainsLink) -> HelixFlowResult<Task> {
+        let db_task = SurrealTask::from(task);
+        let change = self.change(
+            db_task.id.clone(),
+            ChangeKind::Created,
+            None,
+            Some(db_task.clone()),
+        );
+        self.rt
+            .block_on(
+                self.db
+                    .query(
+                        "BEGIN TRANSACTION; \
+                        CREATE $task.id CONTENT $task; \
+                        CREATE history CONTENT $change; \
+                        INSERT RELATION INTO contains $edge; \
+                        COMMIT TRANSACTION;",
+                    )
+                    .bind(("task", db_task))
+                    .bind(("change", change))
+                    .bind(("edge", edge.clone()))
+                    .into_future(),
+            )
+            .map_err(anyhow::Error::from)?
+            .check()
+            .with_context(|| format!("Creating {:#?} in its list in SurrealDb", task))?;
+        self.get(&task.id)
     }
-}
 
-impl From<&State> for SurrealState {
-    fn from(state: &State) -> Self {
-        SurrealState {
-            visible_backlog: *state.visible_backlog_id(),
-            id: Thing::from(("State", Id::Uuid(state.id.into()))),
-        }
+    /// One `page` of the tasks in `tasklist`, by `sortorder` then oldest first.
+    fn tasks_in(
+        &self,
+        tasklist: &TaskList,
+        page: Page,
+    ) -> HelixFlowResult<Vec<Contains<TaskList, Task>>> {
+        let tasks: Vec<ContainedItem<SurrealTask>> = self.contained(
+            SurrealTaskList::from(tasklist).id,
+            "Tasks",
+            Some(page),
+            None,
+        )?;
+        Ok(tasks
+            .into_iter()
+            .map(|task| task.contained_by(tasklist))
+            .collect())
     }
-}
 
-impl<C: Connection> Store<State> for SurrealDb<C> {
-    fn create(&self, state: &State) -> HelixFlowResult<State> {
-        dbg!(state);
-        let dbstate: SurrealState = self
+    /// The items from `table` which `parent` contains, by `sortorder` then oldest first, or just
+    /// one `page` of them. Only items matching `condition` on `out`, if given.
+    ///
+    /// A tasklist can contain both tasks & other tasklists, so `table` picks which.
+    fn contained<ITEM>(
+        &self,
+        parent: Thing,
+        table: &'static str,
+        page: Option<Page>,
+        condition: Option<&str>,
+    ) -> HelixFlowResult<Vec<ContainedItem<ITEM>>>
+    where
+        ITEM: serde::de::DeserializeOwned,
+    {
+        let condition = condition
+            .map(|condition| format!(" AND {condition}"))
+            .unwrap_or_default();
+        let mut sql = format!(
+            "SELECT out.* AS item, sortorder, created_at, created_by, out \
+            FROM contains WHERE in = $parent AND record::tb(out) = $table{condition} \
+            ORDER BY sortorder, out"
+        );
+        if page.is_some() {
+            sql += " LIMIT $limit START $offset";
+        }
+        let mut response = self
             .rt
             .block_on(
                 self.db
-                    .create("State")
-                    .content(SurrealState::from(state))
+                    .query(sql)
+                    .bind(("parent", parent))
+                    .bind(("table", table))
+                    .bind(("limit", page.map(|page| page.limit)))
+                    .bind(("offset", page.map(|page| page.offset)))
                     .into_future(),
             )
-            .map_err(anyhow::Error::from)?
-            .with_context(|| format!("Creating new record for {:#?} in SurrealDb", state))?;
-        let checkstate = dbstate.try_into()?;
-        dbg!(&checkstate);
-        Ok(checkstate)
+            .map_err(anyhow::Error::from)?;
+        Ok(response.take(0).map_err(anyhow::Error::from)?)
     }
 
-    fn get(&self, id: &Uuid) -> HelixFlowResult<State> {
-        let dbstate: Option<SurrealState> = self
+    /// The highest `sortorder` in `tasklist`, if any task has one.
+    fn last_rank(&self, tasklist: &TaskList) -> HelixFlowResult<Option<String>> {
+        let mut response = self
             .rt
-            .block_on(self.db.select(("State", *id)).into_future())
+            .block_on(
+                self.db
+                    .query(
+                        "SELECT VALUE sortorder FROM contains WHERE in = $tl AND sortorder != NONE \
+                        ORDER BY sortorder DESC LIMIT 1",
+                    )
+                    .bind(("tl", SurrealTaskList::from(tasklist).id))
+                    .into_future(),
+            )
             .map_err(anyhow::Error::from)?;
-        if let Some(state) = dbstate {
-            Ok(state.try_into()?)
+        let ranks: Vec<String> = response.take(0).map_err(anyhow::Error::from)?;
+        Ok(ranks.into_iter().next())
+    }
+}
+
+/// Subtasks share the `contains` edges with tasklists.
+impl<C: Connection> Relate<Contains<Task, Task>> for SurrealDb<C> {
+    /// The parent task must already exist, the subtask is created.
+    fn create_linked_item(
+        &self,
+        link: &Contains<Task, Task>,
+    ) -> HelixFlowResult<Contains<Task, Task>> {
+        // TODO - RelBetwErrs (or impl Try for &Contains ...)
+        let parent = link.left.as_ref().unwrap();
+        let child = link.right.as_ref().unwrap();
+        let db_parent: Task = self.get(&parent.id)?;
+        let edge = ContainsLink::new(
+            SurrealTask::from(&db_parent).id,
+            SurrealTask::from(child).id,
+            link,
+        );
+        let db_child = self.create_contained(child, &edge)?;
+        Ok(Contains {
+            left: Ok(db_parent),
+            sortorder: link.sortorder.clone(),
+            created_at: edge.created_at,
+            created_by: edge.created_by,
+            right: Ok(db_child),
+        })
+    }
+
+    /// Subtasks are oldest first, unless they have a `sortorder`.
+    fn get_linked_items(
+        &self,
+        left: &Task,
+    ) -> HelixFlowResult<impl Iterator<Item = Contains<Task, Task>>> {
+        let tasks: Vec<ContainedItem<SurrealTask>> =
+            self.contained(SurrealTask::from(left).id, "Tasks", None, None)?;
+        Ok(tasks.into_iter().map(|task| task.contained_by(left)))
+    }
+
+    fn delete_link(&self, link: &Contains<Task, Task>) -> HelixFlowResult<()> {
+        // TODO - RelBetwErrs (or impl Try for &Contains ...)
+        self.delete_edge(
+            "contains",
+            Link {
+                r#in: SurrealTask::from(link.left.as_ref().unwrap()).id,
+                out: SurrealTask::from(link.right.as_ref().unwrap()).id,
+            },
+        )
+    }
+}
+
+impl<C: Connection> Relate<DependsOn<Task, Task>> for SurrealDb<C> {
+    /// Both tasks must already exist. Cycles are checked by `Link::create_linked_item` before
+    /// this is called.
+    fn create_linked_item(
+        &self,
+        link: &DependsOn<Task, Task>,
+    ) -> HelixFlowResult<DependsOn<Task, Task>> {
+        // TODO - RelBetwErrs (or impl Try for &DependsOn ...)
+        let task = link.left.as_ref().unwrap();
+        let dependency = link.right.as_ref().unwrap();
+        let db_task: Task = self.get(&task.id)?;
+        let db_dependency: Task = self.get(&dependency.id)?;
+        let _: Vec<Link> = self
+            .rt
+            .block_on(
+                self.db
+                    .insert("depends_on")
+                    .relation(Link {
+                        r#in: SurrealTask::from(&db_task).id,
+                        out: SurrealTask::from(&db_dependency).id,
+                    })
+                    .into_future(),
+            )
+            .map_err(anyhow::Error::from)?;
+        Ok(DependsOn {
+            left: Ok(db_task),
+            right: Ok(db_dependency),
+        })
+    }
+
+    fn get_linked_items(
+        &self,
+        left: &Task,
+    ) -> HelixFlowResult<impl Iterator<Item = DependsOn<Task, Task>>> {
+        let mut response = self
+            .rt
+            .block_on(
+                self.db
+                    .query("SELECT ->depends_on->Tasks.* AS tasks FROM $task")
+                    .bind(("task", SurrealTask::from(left).id))
+                    .into_future(),
+            )
+            .map_err(anyhow::Error::from)?;
+        let tasks: Vec<Vec<SurrealTask>> = response.take("tasks").map_err(anyhow::Error::from)?;
+        let relationships: Vec<DependsOn<Task, Task>> = tasks
+            .into_iter()
+            .flatten()
+            .map(|task| DependsOn {
+                left: Ok(left.clone()),
+                right: task.try_into(),
+            })
+            .collect();
+        Ok(relationships.into_iter())
+    }
+
+    fn delete_link(&self, link: &DependsOn<Task, Task>) -> HelixFlowResult<()> {
+        // TODO - RelBetwErrs (or impl Try for &DependsOn ...)
+        self.delete_edge(
+            "depends_on",
+            Link {
+                r#in: SurrealTask::from(link.left.as_ref().unwrap()).id,
+                out: SurrealTask::from(link.right.as_ref().unwrap()).id,
+            },
+        )
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SurrealProject {
+    name: Cow<'static, str>,
+    description: Option<Cow<'static, str>>,
+    id: Thing,
+}
+
+impl TryFrom<SurrealProject> for Project {
+    type Error = HelixFlowError;
+    fn try_from(project: SurrealProject) -> HelixFlowResult<Project> {
+        let id = item_id(&project.id);
+        Ok(Project {
+            name: project.name,
+            description: project.description,
+            id: id?,
+        })
+    }
+}
+
+impl From<&Project> for SurrealProject {
+    fn from(project: &Project) -> Self {
+        SurrealProject {
+            name: project.name.clone(),
+            description: project.description.clone(),
+            id: Thing::from(("Projects", Id::Uuid(project.id.into()))),
+        }
+    }
+}
+
+impl<C: Connection> Store<Project> for SurrealDb<C> {
+    fn create(&self, project: &Project) -> HelixFlowResult<Project> {
+        let dbproject: SurrealProject = self
+            .rt
+            .block_on(
+                self.db
+                    .create("Projects")
+                    .content(SurrealProject::from(project))
+                    .into_future(),
+            )
+            .map_err(anyhow::Error::from)?
+            .with_context(|| format!("Creating new record for {:#?} in SurrealDb", project))?;
+        dbproject.try_into()
+    }
+
+    fn get(&self, id: &Uuid) -> HelixFlowResult<Project> {
+        let dbproject: Option<SurrealProject> = self
+            .rt
+            .block_on(self.db.select(("Projects", *id)).into_future())
+            .map_err(anyhow::Error::from)?;
+        if let Some(project) = dbproject {
+            Ok(project.try_into()?)
         } else {
             Err(HelixFlowError::NotFound {
-                itemtype: "State".into(),
+                itemtype: "Project".into(),
                 id: *id,
             })
         }
     }
-}
 
-impl SurrealDb<Db> {
-    /// Instantiate an local Db, with data saved in `Some(file)` on drop,
-    /// or simply held in memory (`None`).
-    ///
-    /// Note:
-    /// - `ns` & `db` = "HelixFlow"
-    /// - This is a blocking operation until the db is available.
-    pub fn new(file: Option<PathBuf>) -> anyhow::Result<Self> {
-        debug!("Initialising tokio runtime");
-        let rt = tokio::runtime::Builder::new_current_thread()
-            .enable_all()
-            .build()
-            .context("Initialising dedicated tokio runtime for surreal in memory database.")?;
-        debug!("Initialising database");
-        let db = rt
-            .block_on(Surreal::new::<Mem>(()).into_future())
-            .context("Initialising database")?;
-        debug!("Selecting database namespace");
-        rt.block_on(db.use_ns("HelixFlow").use_db("HelixFlow").into_future())
-            .context("Selecting database namespace")?;
-        if let Some(file) = &file {
-            let imported = rt.block_on(db.import(file).into_future());
-
-            if let Err(e) = &imported
-                && let surrealdb::Error::Api(Api::FileOpen { error, path }) = e
-                && error.kind() == std::io::ErrorKind::NotFound
-                && path == file
-            {
-                Ok(())
-            } else {
-                imported
-            }
-            .context(format!("Importing {:#?}", file))?
+    fn update(&self, project: &Project) -> HelixFlowResult<Project> {
+        let dbproject: Option<SurrealProject> = self
+            .rt
+            .block_on(
+                self.db
+                    .update(("Projects", project.id))
+                    .content(SurrealProject::from(project))
+                    .into_future(),
+            )
+            .map_err(anyhow::Error::from)?;
+        if let Some(project) = dbproject {
+            Ok(project.try_into()?)
+        } else {
+            Err(HelixFlowError::NotFound {
+                itemtype: "Project".into(),
+                id: project.id,
+            })
         }
-        debug!("Stuffing the runtime in an Rc");
-        let runtime = Rc::new(rt);
-        debug!("Done connecting to database");
-        Ok(Self {
-            db,
-            rt: runtime,
-            file,
+    }
+
+    fn delete(&self, id: &Uuid) -> HelixFlowResult<()> {
+        let deleted: Option<SurrealProject> = self
+            .rt
+            .block_on(self.db.delete(("Projects", *id)).into_future())
+            .map_err(anyhow::Error::from)?;
+        match deleted {
+            Some(project) => self.delete_edges(project.id),
+            None => Err(HelixFlowError::NotFound {
+                itemtype: "Project".into(),
+                id: *id,
+            }),
+        }
+    }
+}
+
+impl<C: Connection> Relate<Contains<Project, TaskList>> for SurrealDb<C> {
+    /// The project must already exist, the tasklist is created if needed.
+    fn create_linked_item(
+        &self,
+        link: &Contains<Project, TaskList>,
+    ) -> HelixFlowResult<Contains<Project, TaskList>> {
+        // TODO - RelBetwErrs (or impl Try for &Contains ...)
+        let project = link.left.as_ref().unwrap();
+        let tasklist = link.right.as_ref().unwrap();
+        let db_project: Project = self.get(&project.id)?;
+        let db_tasklist: TaskList = match self.get(&tasklist.id) {
+            Err(HelixFlowError::NotFound { .. }) => self.create(tasklist)?,
+            existing => existing?,
+        };
+        let edge = ContainsLink::new(
+            SurrealProject::from(&db_project).id,
+            SurrealTaskList::from(&db_tasklist).id,
+            link,
+        );
+        let _: Vec<ContainsLink> = self
+            .rt
+            .block_on(
+                self.db
+                    .insert("contains")
+                    .relation(edge.clone())
+                    .into_future(),
+            )
+            .map_err(anyhow::Error::from)?;
+        Ok(Contains {
+            left: Ok(db_project),
+            sortorder: link.sortorder.clone(),
+            created_at: edge.created_at,
+            created_by: edge.created_by,
+            right: Ok(db_tasklist),
         })
     }
+
+    /// Tasklists are oldest first, unless they have a `sortorder`.
+    fn get_linked_items(
+        &self,
+        left: &Project,
+    ) -> HelixFlowResult<impl Iterator<Item = Contains<Project, TaskList>>> {
+        let tasklists: Vec<ContainedItem<SurrealTaskList>> =
+            self.contained(SurrealProject::from(left).id, "Tasklists", None, None)?;
+        Ok(tasklists
+            .into_iter()
+            .map(|tasklist| tasklist.contained_by(left)))
+    }
+
+    fn delete_link(&self, link: &Contains<Project, TaskList>) -> HelixFlowResult<()> {
+        // TODO - RelBetwErrs (or impl Try for &Contains ...)
+        self.delete_edge(
+            "contains",
+            Link {
+                r#in: SurrealProject::from(link.left.as_ref().unwrap()).id,
+                out: SurrealTaskList::from(link.right.as_ref().unwrap()).id,
+            },
+        )
+    }
 }
 
-impl<C> Drop for SurrealDb<C>
-where
-    C: Connection,
-{
-    fn drop(&mut self) {
-        if let Some(file) = &self.file {
-            println!("Saving to {:#?}", file);
-            self.rt
-                .block_on(self.db.export(file).into_future())
-                .unwrap()
-            // TODO - handle errors nicely
-        }
+/// Nested tasklists share the `contains` edges with their tasks.
+impl<C: Connection> Relate<Contains<TaskList, TaskList>> for SurrealDb<C> {
+    /// The parent tasklist must already exist, the nested tasklist is created if needed.
+    fn create_linked_item(
+        &self,
+        link: &Contains<TaskList, TaskList>,
+    ) -> HelixFlowResult<Contains<TaskList, TaskList>> {
+        // TODO - RelBetwErrs (or impl Try for &Contains ...)
+        let parent = link.left.as_ref().unwrap();
+        let child = link.right.as_ref().unwrap();
+        let db_parent: TaskList = self.get(&parent.id)?;
+        let db_child: TaskList = match self.get(&child.id) {
+            Err(HelixFlowError::NotFound { .. }) => self.create(child)?,
+            existing => existing?,
+        };
+        let edge = ContainsLink::new(
+            SurrealTaskList::from(&db_parent).id,
+            SurrealTaskList::from(&db_child).id,
+            link,
+        );
+        let _: Vec<ContainsLink> = self
+            .rt
+            .block_on(
+                self.db
+                    .insert("contains")
+                    .relation(edge.clone())
+                    .into_future(),
+            )
+            .map_err(anyhow::Error::from)?;
+        Ok(Contains {
+            left: Ok(db_parent),
+            sortorder: link.sortorder.clone(),
+            created_at: edge.created_at,
+            created_by: edge.created_by,
+            right: Ok(db_child),
+        })
+    }
+
+    /// Nested tasklists are oldest first, unless they have a `sortorder`.
+    fn get_linked_items(
+        &self,
+        left: &TaskList,
+    ) -> HelixFlowResult<impl Iterator<Item = Contains<TaskList, TaskList>>> {
+        let tasklists: Vec<ContainedItem<SurrealTaskList>> =
+            self.contained(SurrealTaskList::from(left).id, "Tasklists", None, None)?;
+        Ok(tasklists
+            .into_iter()
+            .map(|tasklist| tasklist.contained_by(left)))
+    }
+
+    fn delete_link(&self, link: &Contains<TaskList, TaskList>) -> HelixFlowResult<()> {
+        // TODO - RelBetwErrs (or impl Try for &Contains ...)
+        self.delete_edge(
+            "contains",
+            Link {
+                r#in: SurrealTaskList::from(link.left.as_ref().unwrap()).id,
+                out: SurrealTaskList::from(link.right.as_ref().unwrap()).id,
+            },
+        )
     }
 }
 
-// Can't run blocking on wasm as `runtime::Builder::enable_all()` needs `time` AND
-// `block_on()` will not run either, as rt cannot be idle.
-#[cfg(test)]
-#[coverage(off)]
-mod tests {
-    use std::assert_matches::assert_matches;
+impl<C: Connection> Projects for SurrealDb<C> {
+    fn all_projects(&self) -> HelixFlowResult<Vec<Project>> {
+        let mut response = self
+            .rt
+            .block_on(
+                self.db
+                    .query("SELECT * FROM Projects ORDER BY id")
+                    .into_future(),
+            )
+            .map_err(anyhow::Error::from)?;
+        let projects: Vec<SurrealProject> = response.take(0).map_err(anyhow::Error::from)?;
+        projects.into_iter().map(Project::try_from).collect()
+    }
+}
 
-    use super::*;
+#[derive(Debug, Serialize, Deserialize)]
+struct SurrealPerson {
+    name: Cow<'static, str>,
+    email: Option<Cow<'static, str>>,
+    id: Thing,
+}
 
-    use rstest::*;
+impl TryFrom<SurrealPerson> for Person {
+    type Error = HelixFlowError;
+    fn try_from(person: SurrealPerson) -> HelixFlowResult<Person> {
+        let id = item_id(&person.id);
+        Ok(Person {
+            name: person.name,
+            email: person.email,
+            id: id?,
+        })
+    }
+}
 
-    use tempfile::{NamedTempFile, TempPath};
+impl From<&Person> for SurrealPerson {
+    fn from(person: &Person) -> Self {
+        SurrealPerson {
+            name: person.name.clone(),
+            email: person.email.clone(),
+            id: Thing::from(("People", Id::Uuid(person.id.into()))),
+        }
+    }
+}
 
-    #[derive(Clone, Copy, Debug)]
-    enum BackendKind {
-        Mem,
-        File,
+impl<C: Connection> Store<Person> for SurrealDb<C> {
+    fn create(&self, person: &Person) -> HelixFlowResult<Person> {
+        let dbperson: SurrealPerson = self
+            .rt
+            .block_on(
+                self.db
+                    .create("People")
+                    .content(SurrealPerson::from(person))
+                    .into_future(),
+            )
+            .map_err(anyhow::Error::from)?
+            .with_context(|| {
+                format!(
+                    "Creating new record for person {} in SurrealDb",
+                    person.name
+                )
+            })?;
+        dbperson.try_into()
+    }
+
+    fn get(&self, id: &Uuid) -> HelixFlowResult<Person> {
+        let dbperson: Option<SurrealPerson> = self
+            .rt
+            .block_on(self.db.select(("People", *id)).into_future())
+            .map_err(anyhow::Error::from)?;
+        if let Some(person) = dbperson {
+            Ok(person.try_into()?)
+        } else {
+            Err(HelixFlowError::NotFound {
+                itemtype: "Person".into(),
+                id: *id,
+            })
+        }
+    }
+
+    fn update(&self, person: &Person) -> HelixFlowResult<Person> {
+        let dbperson: Option<SurrealPerson> = self
+            .rt
+            .block_on(
+                self.db
+                    .update(("People", person.id))
+                    .content(SurrealPerson::from(person))
+                    .into_future(),
+            )
+            .map_err(anyhow::Error::from)?;
+        if let Some(person) = dbperson {
+            Ok(person.try_into()?)
+        } else {
+            Err(HelixFlowError::NotFound {
+                itemtype: "Person".into(),
+                id: person.id,
+            })
+        }
+    }
+
+    fn delete(&self, id: &Uuid) -> HelixFlowResult<()> {
+        let deleted: Option<SurrealPerson> = self
+            .rt
+            .block_on(self.db.delete(("People", *id)).into_future())
+            .map_err(anyhow::Error::from)?;
+        match deleted {
+            Some(person) => self.delete_edges(person.id),
+            None => Err(HelixFlowError::NotFound {
+                itemtype: "Person".into(),
+                id: *id,
+            }),
+        }
     }
+}
+
+impl<C: Connection> Relate<AssignedTo<Task, Person>> for SurrealDb<C> {
+    /// The task must already exist, the person is created if needed. Any previous assignment of
+    /// the task is removed.
+    fn create_linked_item(
+        &self,
+        link: &AssignedTo<Task, Person>,
+    ) -> HelixFlowResult<AssignedTo<Task, Person>> {
+        // TODO - RelBetwErrs (or impl Try for &AssignedTo ...)
+        let task = link.left.as_ref().unwrap();
+        let person = link.right.as_ref().unwrap();
+        let db_task: Task = self.get(&task.id)?;
+        let db_person: Person = match self.get(&person.id) {
+            Err(HelixFlowError::NotFound { .. }) => self.create(person)?,
+            existing => existing?,
+        };
+        self.rt
+            .block_on(
+                self.db
+                    .query("DELETE assigned_to WHERE in = $task")
+                    .bind(("task", SurrealTask::from(&db_task).id))
+                    .into_future(),
+            )
+            .map_err(anyhow::Error::from)?
+            .check()
+            .map_err(anyhow::Error::from)?;
+        let _: Vec<Link> = self
+            .rt
+            .block_on(
+                self.db
+                    .insert("assigned_to")
+                    .relation(Link {
+                        r#in: SurrealTask::from(&db_task).id,
+                        out: SurrealPerson::from(&db_person).id,
+                    })
+                    .into_future(),
+            )
+            .map_err(anyhow::Error::from)?;
+        Ok(AssignedTo {
+            left: Ok(db_task),
+            right: Ok(db_person),
+        })
+    }
+
+    fn get_linked_items(
+        &self,
+        left: &Task,
+    ) -> HelixFlowResult<impl Iterator<Item = AssignedTo<Task, Person>>> {
+        let mut response = self
+            .rt
+            .block_on(
+                self.db
+                    .query("SELECT ->assigned_to->People.* AS people FROM $task")
+                    .bind(("task", SurrealTask::from(left).id))
+                    .into_future(),
+            )
+            .map_err(anyhow::Error::from)?;
+        let people: Vec<Vec<SurrealPerson>> =
+            response.take("people").map_err(anyhow::Error::from)?;
+        let relationships: Vec<AssignedTo<Task, Person>> = people
+            .into_iter()
+            .flatten()
+            .map(|person| AssignedTo {
+                left: Ok(left.clone()),
+                right: person.try_into(),
+            })
+            .collect();
+        Ok(relationships.into_iter())
+    }
+
+    fn delete_link(&self, link: &AssignedTo<Task, Person>) -> HelixFlowResult<()> {
+        // TODO - RelBetwErrs (or impl Try for &AssignedTo ...)
+        self.delete_edge(
+            "assigned_to",
+            Link {
+                r#in: SurrealTask::from(link.left.as_ref().unwrap()).id,
+                out: SurrealPerson::from(link.right.as_ref().unwrap()).id,
+            },
+        )
+    }
+}
+
+impl<C: Connection> People for SurrealDb<C> {
+    fn all_people(&self) -> HelixFlowResult<Vec<Person>> {
+        let mut response = self
+            .rt
+            .block_on(
+                self.db
+                    .query("SELECT * FROM People ORDER BY id")
+                    .into_future(),
+            )
+            .map_err(anyhow::Error::from)?;
+        let people: Vec<SurrealPerson> = response.take(0).map_err(anyhow::Error::from)?;
+        people.into_iter().map(Person::try_from).collect()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SurrealState {
+    #[serde(default)]
+    visible_project: Option<Uuid>,
+    visible_backlog: Option<Uuid>,
+    #[serde(default)]
+    view_mode: ViewMode,
+    #[serde(default)]
+    task_details: Option<Uuid>,
+    #[serde(default)]
+    cards: BTreeMap<ViewMode, CardSettings>,
+    /// Keyed by `TaskList` id as a string, object keys can't be `Uuid`s
+    #[serde(default)]
+    tables: BTreeMap<String, TableLayout>,
+    #[serde(default)]
+    window: Option<WindowGeometry>,
+    #[serde(default)]
+    selected_task: Option<Uuid>,
+    #[serde(default)]
+    sort: SortRule,
+    #[serde(default)]
+    filter: BacklogFilter,
+    #[serde(default)]
+    workspaces: Vec<Workspace>,
+    #[serde(default)]
+    workspace: Option<String>,
+    id: Thing,
+}
+
+impl TryFrom<SurrealState> for State {
+    type Error = HelixFlowError;
+    fn try_from(state: SurrealState) -> HelixFlowResult<State> {
+        let id = item_id(&state.id);
+        let mut stored_state = State::new(&id?);
+        if let Some(project) = state.visible_project {
+            stored_state.visible_project(&Project {
+                name: "".into(),
+                description: None,
+                id: project,
+            });
+        }
+        if let Some(backlog) = state.visible_backlog {
+            stored_state.visible_backlog(&TaskList {
+                name: "".into(),
+                id: backlog,
+                sort: Default::default(),
+            });
+        }
+        stored_state.view_mode(state.view_mode);
+        if let Some(task) = state.task_details {
+            stored_state.task_details(&Task {
+                name: "".into(),
+                id: task,
+                description: None,
+                due: None,
+                colour: None,
+                status: Status::Todo,
+                priority: Priority::P2,
+                estimate: None,
+                actual: None,
+                completed_at: None,
+                pinned: false,
+                updated_at: Default::default(),
+                version: Default::default(),
+            });
+        }
+        for (view, settings) in state.cards {
+            stored_state.card_settings(view, settings);
+        }
+        for (tasklist, layout) in state.tables {
+            let id = Uuid::try_parse(&tasklist)
+                .map_err(|_| HelixFlowError::InvalidID { id: tasklist })?;
+            stored_state.table_layout(
+                &TaskList {
+                    name: "".into(),
+                    id,
+                    sort: Default::default(),
+                },
+                layout,
+            );
+        }
+        if let Some(geometry) = state.window {
+            stored_state.window(geometry);
+        }
+        if let Some(task) = state.selected_task {
+            stored_state.select_task(&Task {
+                id: task,
+                ..Task::new("", None)
+            });
+        }
+        stored_state.sort_mode(state.sort);
+        stored_state.filter(state.filter);
+        for workspace in state.workspaces {
+            stored_state.remember_workspace(workspace);
+        }
+        if let Some(namespace) = state.workspace {
+            stored_state.workspace(&namespace);
+        }
+        Ok(stored_state)
+    }
+}
+
+impl From<&State> for SurrealState {
+    fn from(state: &State) -> Self {
+        SurrealState {
+            visible_project: *state.visible_project_id(),
+            visible_backlog: *state.visible_backlog_id(),
+            view_mode: state.current_view_mode(),
+            task_details: *state.task_details_id(),
+            cards: state.all_card_settings().clone(),
+            tables: state
+                .all_table_layouts()
+                .iter()
+                .map(|(tasklist, layout)| (tasklist.to_string(), layout.clone()))
+                .collect(),
+            window: state.window_geometry(),
+            selected_task: *state.selected_task_id(),
+            sort: state.current_sort_mode().clone(),
+            filter: state.current_filter().clone(),
+            workspaces: state.workspaces(),
+            workspace: Some(state.current_workspace().namespace),
+            id: Thing::from(("State", Id::Uuid(state.id.into()))),
+        }
+    }
+}
+
+impl<C: Connection> Store<State> for SurrealDb<C> {
+    fn create(&self, state: &State) -> HelixFlowResult<State> {
+        let _span = tracing::debug_span!("create", table = "State", id = %state.id).entered();
+        let dbstate: SurrealState = self
+            .rt
+            .block_on(
+                self.db
+                    .create("State")
+                    .content(SurrealState::from(state))
+                    .into_future(),
+            )
+            .map_err(anyhow::Error::from)?
+            .with_context(|| format!("Creating new record for {:#?} in SurrealDb", state))?;
+        let checkstate = dbstate.try_into()?;
+        tracing::debug!("Created record");
+        Ok(checkstate)
+    }
+
+    fn get(&self, id: &Uuid) -> HelixFlowResult<State> {
+        let dbstate: Option<SurrealState> = self
+            .rt
+            .block_on(self.db.select(("State", *id)).into_future())
+            .map_err(anyhow::Error::from)?;
+        if let Some(state) = dbstate {
+            Ok(state.try_into()?)
+        } else {
+            Err(HelixFlowError::NotFound {
+                itemtype: "State".into(),
+                id: *id,
+            })
+        }
+    }
+
+    fn update(&self, state: &State) -> HelixFlowResult<State> {
+        let dbstate: Option<SurrealState> = self
+            .rt
+            .block_on(
+                self.db
+                    .update(("State", state.id))
+                    .content(SurrealState::from(state))
+                    .into_future(),
+            )
+            .map_err(anyhow::Error::from)?;
+        if let Some(state) = dbstate {
+            Ok(state.try_into()?)
+        } else {
+            Err(HelixFlowError::NotFound {
+                itemtype: "State".into(),
+                id: state.id,
+            })
+        }
+    }
+
+    /// One `UPSERT`, so saving the UI state is a single idempotent query.
+    fn put(&self, state: &State) -> HelixFlowResult<State> {
+        let dbstate: Option<SurrealState> = self
+            .rt
+            .block_on(
+                self.db
+                    .upsert(("State", state.id))
+                    .content(SurrealState::from(state))
+                    .into_future(),
+            )
+            .map_err(anyhow::Error::from)?;
+        let dbstate = dbstate.with_context(|| format!("Storing {:#?} in SurrealDb", state))?;
+        Ok(dbstate.try_into()?)
+    }
+
+    fn delete(&self, id: &Uuid) -> HelixFlowResult<()> {
+        let deleted: Option<SurrealState> = self
+            .rt
+            .block_on(self.db.delete(("State", *id)).into_future())
+            .map_err(anyhow::Error::from)?;
+        match deleted {
+            Some(_) => Ok(()),
+            None => Err(HelixFlowError::NotFound {
+                itemtype: "State".into(),
+                id: *id,
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SurrealSettings {
+    display_timezone: chrono_tz::Tz,
+    #[serde(default)]
+    working_calendar: WorkingCalendar,
+    #[serde(default)]
+    actor: Actor,
+    #[serde(default)]
+    quick_add: QuickAddRules,
+    #[serde(default)]
+    locale: Locale,
+    #[serde(default, alias = "telemetry_endpoint")]
+    telemetry_path: Option<PathBuf>,
+    id: Thing,
+}
+
+impl TryFrom<SurrealSettings> for Settings {
+    type Error = HelixFlowError;
+    fn try_from(settings: SurrealSettings) -> HelixFlowResult<Settings> {
+        let id = item_id(&settings.id);
+        let mut stored_settings = Settings::new(&id?);
+        stored_settings.display_timezone(settings.display_timezone);
+        stored_settings.working_calendar(settings.working_calendar);
+        stored_settings.actor(settings.actor);
+        stored_settings.quick_add_rules(settings.quick_add);
+        stored_settings.display_locale(settings.locale);
+        stored_settings.share_telemetry(settings.telemetry_path);
+        Ok(stored_settings)
+    }
+}
+
+impl From<&Settings> for SurrealSettings {
+    fn from(settings: &Settings) -> Self {
+        SurrealSettings {
+            display_timezone: settings.timezone(),
+            working_calendar: settings.calendar().clone(),
+            actor: settings.current_actor().clone(),
+            quick_add: settings.quick_add().clone(),
+            locale: settings.locale(),
+            telemetry_path: settings.telemetry_path().map(Into::into),
+            id: Thing::from(("Settings", Id::Uuid(settings.id.into()))),
+        }
+    }
+}
+
+impl<C: Connection> Store<Settings> for SurrealDb<C> {
+    fn create(&self, settings: &Settings) -> HelixFlowResult<Settings> {
+        let dbsettings: SurrealSettings = self
+            .rt
+            .block_on(
+                self.db
+                    .create("Settings")
+                    .content(SurrealSettings::from(settings))
+                    .into_future(),
+            )
+            .map_err(anyhow::Error::from)?
+            .with_context(|| format!("Creating new record for {:#?} in SurrealDb", settings))?;
+        dbsettings.try_into()
+    }
+
+    fn get(&self, id: &Uuid) -> HelixFlowResult<Settings> {
+        let dbsettings: Option<SurrealSettings> = self
+            .rt
+            .block_on(self.db.select(("Settings", *id)).into_future())
+            .map_err(anyhow::Error::from)?;
+        if let Some(settings) = dbsettings {
+            Ok(settings.try_into()?)
+        } else {
+            Err(HelixFlowError::NotFound {
+                itemtype: "Settings".into(),
+                id: *id,
+            })
+        }
+    }
+
+    fn update(&self, settings: &Settings) -> HelixFlowResult<Settings> {
+        let dbsettings: Option<SurrealSettings> = self
+            .rt
+            .block_on(
+                self.db
+                    .update(("Settings", settings.id))
+                    .content(SurrealSettings::from(settings))
+                    .into_future(),
+            )
+            .map_err(anyhow::Error::from)?;
+        if let Some(settings) = dbsettings {
+            Ok(settings.try_into()?)
+        } else {
+            Err(HelixFlowError::NotFound {
+                itemtype: "Settings".into(),
+                id: settings.id,
+            })
+        }
+    }
+
+    fn delete(&self, id: &Uuid) -> HelixFlowResult<()> {
+        let deleted: Option<SurrealSettings> = self
+            .rt
+            .block_on(self.db.delete(("Settings", *id)).into_future())
+            .map_err(anyhow::Error::from)?;
+        match deleted {
+            Some(_) => Ok(()),
+            None => Err(HelixFlowError::NotFound {
+                itemtype: "Settings".into(),
+                id: *id,
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SurrealShareToken {
+    id: Thing,
+    tasklist: Uuid,
+    expires: chrono::DateTime<chrono::Utc>,
+    revoked: bool,
+}
+
+impl TryFrom<SurrealShareToken> for ShareToken {
+    type Error = HelixFlowError;
+    fn try_from(token: SurrealShareToken) -> HelixFlowResult<ShareToken> {
+        let id = item_id(&token.id);
+        Ok(ShareToken {
+            id: id?,
+            tasklist: token.tasklist,
+            expires: token.expires,
+            revoked: token.revoked,
+        })
+    }
+}
+
+impl From<&ShareToken> for SurrealShareToken {
+    fn from(token: &ShareToken) -> Self {
+        SurrealShareToken {
+            id: Thing::from(("Shares", Id::Uuid(token.id.into()))),
+            tasklist: token.tasklist,
+            expires: token.expires,
+            revoked: token.revoked,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Revoked {
+    revoked: bool,
+}
+
+impl<C: Connection> Share for SurrealDb<C> {
+    fn create_token(&self, token: &ShareToken) -> HelixFlowResult<ShareToken> {
+        let dbtoken: SurrealShareToken = self
+            .rt
+            .block_on(
+                self.db
+                    .create("Shares")
+                    .content(SurrealShareToken::from(token))
+                    .into_future(),
+            )
+            .map_err(anyhow::Error::from)?
+            .with_context(|| format!("Creating new record for {:#?} in SurrealDb", token))?;
+        dbtoken.try_into()
+    }
+
+    fn get_token(&self, id: &Uuid) -> HelixFlowResult<ShareToken> {
+        let dbtoken: Option<SurrealShareToken> = self
+            .rt
+            .block_on(self.db.select(("Shares", *id)).into_future())
+            .map_err(anyhow::Error::from)?;
+        if let Some(token) = dbtoken {
+            Ok(token.try_into()?)
+        } else {
+            Err(HelixFlowError::NotFound {
+                itemtype: "ShareToken".into(),
+                id: *id,
+            })
+        }
+    }
+
+    fn revoke_token(&self, id: &Uuid) -> HelixFlowResult<()> {
+        let dbtoken: Option<SurrealShareToken> = self
+            .rt
+            .block_on(
+                self.db
+                    .update(("Shares", *id))
+                    .merge(Revoked { revoked: true })
+                    .into_future(),
+            )
+            .map_err(anyhow::Error::from)?;
+        match dbtoken {
+            Some(_) => Ok(()),
+            None => Err(HelixFlowError::NotFound {
+                itemtype: "ShareToken".into(),
+                id: *id,
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SurrealUser {
+    name: Cow<'static, str>,
+    id: Thing,
+}
+
+impl TryFrom<SurrealUser> for User {
+    type Error = HelixFlowError;
+    fn try_from(user: SurrealUser) -> HelixFlowResult<User> {
+        let id = item_id(&user.id);
+        Ok(User {
+            name: user.name,
+            id: id?,
+        })
+    }
+}
+
+impl From<&User> for SurrealUser {
+    fn from(user: &User) -> Self {
+        SurrealUser {
+            name: user.name.clone(),
+            id: Thing::from(("Users", Id::Uuid(user.id.into()))),
+        }
+    }
+}
+
+impl<C: Connection> Store<User> for SurrealDb<C> {
+    fn create(&self, user: &User) -> HelixFlowResult<User> {
+        let dbuser: SurrealUser = self
+            .rt
+            .block_on(
+                self.db
+                    .create("Users")
+                    .content(SurrealUser::from(user))
+                    .into_future(),
+            )
+            .map_err(anyhow::Error::from)?
+            .with_context(|| format!("Creating new record for {:#?} in SurrealDb", user))?;
+        dbuser.try_into()
+    }
+
+    fn get(&self, id: &Uuid) -> HelixFlowResult<User> {
+        let dbuser: Option<SurrealUser> = self
+            .rt
+            .block_on(self.db.select(("Users", *id)).into_future())
+            .map_err(anyhow::Error::from)?;
+        if let Some(user) = dbuser {
+            Ok(user.try_into()?)
+        } else {
+            Err(HelixFlowError::NotFound {
+                itemtype: "User".into(),
+                id: *id,
+            })
+        }
+    }
+
+    fn update(&self, user: &User) -> HelixFlowResult<User> {
+        let dbuser: Option<SurrealUser> = self
+            .rt
+            .block_on(
+                self.db
+                    .update(("Users", user.id))
+                    .content(SurrealUser::from(user))
+                    .into_future(),
+            )
+            .map_err(anyhow::Error::from)?;
+        if let Some(user) = dbuser {
+            Ok(user.try_into()?)
+        } else {
+            Err(HelixFlowError::NotFound {
+                itemtype: "User".into(),
+                id: user.id,
+            })
+        }
+    }
+
+    fn delete(&self, id: &Uuid) -> HelixFlowResult<()> {
+        let deleted: Option<SurrealUser> = self
+            .rt
+            .block_on(self.db.delete(("Users", *id)).into_future())
+            .map_err(anyhow::Error::from)?;
+        match deleted {
+            Some(user) => self.delete_edges(user.id),
+            None => Err(HelixFlowError::NotFound {
+                itemtype: "User".into(),
+                id: *id,
+            }),
+        }
+    }
+}
+
+impl<C: Connection> Watch for SurrealDb<C> {
+    fn create_watch(&self, watches: &Watches<User, Task>) -> HelixFlowResult<()> {
+        let user: User = self.get(&watches.left.id)?;
+        let task: Task = self.get(&watches.right.id)?;
+        let _: Vec<Link> = self
+            .rt
+            .block_on(
+                self.db
+                    .insert("watches")
+                    .relation(Link {
+                        r#in: SurrealUser::from(&user).id,
+                        out: SurrealTask::from(&task).id,
+                    })
+                    .into_future(),
+            )
+            .map_err(anyhow::Error::from)?;
+        Ok(())
+    }
+
+    fn watchers(&self, task: &Task) -> HelixFlowResult<Vec<User>> {
+        let mut response = self
+            .rt
+            .block_on(
+                self.db
+                    .query("SELECT in, out FROM watches WHERE out = $task")
+                    .bind(("task", SurrealTask::from(task).id))
+                    .into_future(),
+            )
+            .map_err(anyhow::Error::from)?;
+        let links: Vec<Link> = response.take(0).map_err(anyhow::Error::from)?;
+        links
+            .into_iter()
+            .map(|link| item_id(&link.r#in).and_then(|id| self.get(&id)))
+            .collect()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SurrealTag {
+    name: Cow<'static, str>,
+    id: Thing,
+}
+
+impl TryFrom<SurrealTag> for Tag {
+    type Error = HelixFlowError;
+    fn try_from(tag: SurrealTag) -> HelixFlowResult<Tag> {
+        let id = item_id(&tag.id);
+        Ok(Tag {
+            name: tag.name,
+            id: id?,
+        })
+    }
+}
+
+impl From<&Tag> for SurrealTag {
+    fn from(tag: &Tag) -> Self {
+        SurrealTag {
+            name: tag.name.clone(),
+            id: Thing::from(("Tags", Id::Uuid(tag.id.into()))),
+        }
+    }
+}
+
+impl<C: Connection> Store<Tag> for SurrealDb<C> {
+    fn create(&self, tag: &Tag) -> HelixFlowResult<Tag> {
+        let dbtag: SurrealTag = self
+            .rt
+            .block_on(
+                self.db
+                    .create("Tags")
+                    .content(SurrealTag::from(tag))
+                    .into_future(),
+            )
+            .map_err(anyhow::Error::from)?
+            .with_context(|| format!("Creating new record for {:#?} in SurrealDb", tag))?;
+        dbtag.try_into()
+    }
+
+    fn get(&self, id: &Uuid) -> HelixFlowResult<Tag> {
+        let dbtag: Option<SurrealTag> = self
+            .rt
+            .block_on(self.db.select(("Tags", *id)).into_future())
+            .map_err(anyhow::Error::from)?;
+        if let Some(tag) = dbtag {
+            Ok(tag.try_into()?)
+        } else {
+            Err(HelixFlowError::NotFound {
+                itemtype: "Tag".into(),
+                id: *id,
+            })
+        }
+    }
+
+    fn update(&self, tag: &Tag) -> HelixFlowResult<Tag> {
+        let dbtag: Option<SurrealTag> = self
+            .rt
+            .block_on(
+                self.db
+                    .update(("Tags", tag.id))
+                    .content(SurrealTag::from(tag))
+                    .into_future(),
+            )
+            .map_err(anyhow::Error::from)?;
+        if let Some(tag) = dbtag {
+            Ok(tag.try_into()?)
+        } else {
+            Err(HelixFlowError::NotFound {
+                itemtype: "Tag".into(),
+                id: tag.id,
+            })
+        }
+    }
+
+    fn delete(&self, id: &Uuid) -> HelixFlowResult<()> {
+        let deleted: Option<SurrealTag> = self
+            .rt
+            .block_on(self.db.delete(("Tags", *id)).into_future())
+            .map_err(anyhow::Error::from)?;
+        match deleted {
+            Some(tag) => self.delete_edges(tag.id),
+            None => Err(HelixFlowError::NotFound {
+                itemtype: "Tag".into(),
+                id: *id,
+            }),
+        }
+    }
+}
+
+impl<C: Connection> Relate<TaggedWith<Task, Tag>> for SurrealDb<C> {
+    /// The task must already exist, the tag is created if needed.
+    fn create_linked_item(
+        &self,
+        link: &TaggedWith<Task, Tag>,
+    ) -> HelixFlowResult<TaggedWith<Task, Tag>> {
+        // TODO - RelBetwErrs (or impl Try for &TaggedWith ...)
+        let task = link.left.as_ref().unwrap();
+        let tag = link.right.as_ref().unwrap();
+        let db_task: Task = self.get(&task.id)?;
+        let db_tag: Tag = match self.get(&tag.id) {
+            Err(HelixFlowError::NotFound { .. }) => self.create(tag)?,
+            existing => existing?,
+        };
+        let _: Vec<Link> = self
+            .rt
+            .block_on(
+                self.db
+                    .insert("tagged_with")
+                    .relation(Link {
+                        r#in: SurrealTask::from(&db_task).id,
+                        out: SurrealTag::from(&db_tag).id,
+                    })
+                    .into_future(),
+            )
+            .map_err(anyhow::Error::from)?;
+        Ok(TaggedWith {
+            left: Ok(db_task),
+            right: Ok(db_tag),
+        })
+    }
+
+    fn get_linked_items(
+        &self,
+        left: &Task,
+    ) -> HelixFlowResult<impl Iterator<Item = TaggedWith<Task, Tag>>> {
+        let mut response = self
+            .rt
+            .block_on(
+                self.db
+                    .query("SELECT in, out FROM tagged_with WHERE in = $task")
+                    .bind(("task", SurrealTask::from(left).id))
+                    .into_future(),
+            )
+            .map_err(anyhow::Error::from)?;
+        let links: Vec<Link> = response.take(0).map_err(anyhow::Error::from)?;
+        let relationships: Vec<TaggedWith<Task, Tag>> = links
+            .into_iter()
+            .map(|link| TaggedWith {
+                left: Ok(left.clone()),
+                right: item_id(&link.out).and_then(|id| self.get(&id)),
+            })
+            .collect();
+        Ok(relationships.into_iter())
+    }
+
+    fn delete_link(&self, link: &TaggedWith<Task, Tag>) -> HelixFlowResult<()> {
+        // TODO - RelBetwErrs (or impl Try for &TaggedWith ...)
+        self.delete_edge(
+            "tagged_with",
+            Link {
+                r#in: SurrealTask::from(link.left.as_ref().unwrap()).id,
+                out: SurrealTag::from(link.right.as_ref().unwrap()).id,
+            },
+        )
+    }
+}
+
+impl<C: Connection> Tagged for SurrealDb<C> {
+    fn tagged_tasks(&self, tag: &Tag) -> HelixFlowResult<Vec<Task>> {
+        let mut response = self
+            .rt
+            .block_on(
+                self.db
+                    .query("SELECT in, out FROM tagged_with WHERE out = $tag")
+                    .bind(("tag", SurrealTag::from(tag).id))
+                    .into_future(),
+            )
+            .map_err(anyhow::Error::from)?;
+        let links: Vec<Link> = response.take(0).map_err(anyhow::Error::from)?;
+        links
+            .into_iter()
+            .map(|link| item_id(&link.r#in).and_then(|id| self.get(&id)))
+            .collect()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SurrealContext {
+    name: Cow<'static, str>,
+    id: Thing,
+}
+
+impl TryFrom<SurrealContext> for contexts::Context {
+    type Error = HelixFlowError;
+    fn try_from(context: SurrealContext) -> HelixFlowResult<contexts::Context> {
+        let id = item_id(&context.id);
+        Ok(contexts::Context {
+            name: context.name,
+            id: id?,
+        })
+    }
+}
+
+impl From<&contexts::Context> for SurrealContext {
+    fn from(context: &contexts::Context) -> Self {
+        SurrealContext {
+            name: context.name.clone(),
+            id: Thing::from(("Contexts", Id::Uuid(context.id.into()))),
+        }
+    }
+}
+
+impl<C: Connection> Store<contexts::Context> for SurrealDb<C> {
+    fn create(&self, context: &contexts::Context) -> HelixFlowResult<contexts::Context> {
+        let dbcontext: SurrealContext = self
+            .rt
+            .block_on(
+                self.db
+                    .create("Contexts")
+                    .content(SurrealContext::from(context))
+                    .into_future(),
+            )
+            .map_err(anyhow::Error::from)?
+            .with_context(|| format!("Creating new record for {:#?} in SurrealDb", context))?;
+        dbcontext.try_into()
+    }
+
+    fn get(&self, id: &Uuid) -> HelixFlowResult<contexts::Context> {
+        let dbcontext: Option<SurrealContext> = self
+            .rt
+            .block_on(self.db.select(("Contexts", *id)).into_future())
+            .map_err(anyhow::Error::from)?;
+        if let Some(context) = dbcontext {
+            Ok(context.try_into()?)
+        } else {
+            Err(HelixFlowError::NotFound {
+                itemtype: "Context".into(),
+                id: *id,
+            })
+        }
+    }
+
+    fn update(&self, context: &contexts::Context) -> HelixFlowResult<contexts::Context> {
+        let dbcontext: Option<SurrealContext> = self
+            .rt
+            .block_on(
+                self.db
+                    .update(("Contexts", context.id))
+                    .content(SurrealContext::from(context))
+                    .into_future(),
+            )
+            .map_err(anyhow::Error::from)?;
+        if let Some(context) = dbcontext {
+            Ok(context.try_into()?)
+        } else {
+            Err(HelixFlowError::NotFound {
+                itemtype: "Context".into(),
+                id: context.id,
+            })
+        }
+    }
+
+    fn delete(&self, id: &Uuid) -> HelixFlowResult<()> {
+        let deleted: Option<SurrealContext> = self
+            .rt
+            .block_on(self.db.delete(("Contexts", *id)).into_future())
+            .map_err(anyhow::Error::from)?;
+        match deleted {
+            Some(context) => self.delete_edges(context.id),
+            None => Err(HelixFlowError::NotFound {
+                itemtype: "Context".into(),
+                id: *id,
+            }),
+        }
+    }
+}
+
+impl<C: Connection> Relate<InContext<Task, contexts::Context>> for SurrealDb<C> {
+    /// The task must already exist, the context is created if needed.
+    fn create_linked_item(
+        &self,
+        link: &InContext<Task, contexts::Context>,
+    ) -> HelixFlowResult<InContext<Task, contexts::Context>> {
+        // TODO - RelBetwErrs (or impl Try for &InContext ...)
+        let task = link.left.as_ref().unwrap();
+        let context = link.right.as_ref().unwrap();
+        let db_task: Task = self.get(&task.id)?;
+        let db_context: contexts::Context = match self.get(&context.id) {
+            Err(HelixFlowError::NotFound { .. }) => self.create(context)?,
+            existing => existing?,
+        };
+        let _: Vec<Link> = self
+            .rt
+            .block_on(
+                self.db
+                    .insert("in_context")
+                    .relation(Link {
+                        r#in: SurrealTask::from(&db_task).id,
+                        out: SurrealContext::from(&db_context).id,
+                    })
+                    .into_future(),
+            )
+            .map_err(anyhow::Error::from)?;
+        Ok(InContext {
+            left: Ok(db_task),
+            right: Ok(db_context),
+        })
+    }
+
+    fn get_linked_items(
+        &self,
+        left: &Task,
+    ) -> HelixFlowResult<impl Iterator<Item = InContext<Task, contexts::Context>>> {
+        let mut response = self
+            .rt
+            .block_on(
+                self.db
+                    .query("SELECT in, out FROM in_context WHERE in = $task")
+                    .bind(("task", SurrealTask::from(left).id))
+                    .into_future(),
+            )
+            .map_err(anyhow::Error::from)?;
+        let links: Vec<Link> = response.take(0).map_err(anyhow::Error::from)?;
+        let relationships: Vec<InContext<Task, contexts::Context>> = links
+            .into_iter()
+            .map(|link| InContext {
+                left: Ok(left.clone()),
+                right: item_id(&link.out).and_then(|id| self.get(&id)),
+            })
+            .collect();
+        Ok(relationships.into_iter())
+    }
+
+    fn delete_link(&self, link: &InContext<Task, contexts::Context>) -> HelixFlowResult<()> {
+        // TODO - RelBetwErrs (or impl Try for &InContext ...)
+        self.delete_edge(
+            "in_context",
+            Link {
+                r#in: SurrealTask::from(link.left.as_ref().unwrap()).id,
+                out: SurrealContext::from(link.right.as_ref().unwrap()).id,
+            },
+        )
+    }
+}
+
+impl<C: Connection> Contexts for SurrealDb<C> {
+    fn all_contexts(&self) -> HelixFlowResult<Vec<contexts::Context>> {
+        let mut response = self
+            .rt
+            .block_on(
+                self.db
+                    .query("SELECT * FROM Contexts ORDER BY id")
+                    .into_future(),
+            )
+            .map_err(anyhow::Error::from)?;
+        let dbcontexts: Vec<SurrealContext> = response.take(0).map_err(anyhow::Error::from)?;
+        dbcontexts
+            .into_iter()
+            .map(contexts::Context::try_from)
+            .collect()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SurrealWorkLog {
+    start: chrono::DateTime<chrono::Utc>,
+    end: chrono::DateTime<chrono::Utc>,
+    note: Option<Cow<'static, str>>,
+    id: Thing,
+}
+
+impl TryFrom<SurrealWorkLog> for WorkLog {
+    type Error = HelixFlowError;
+    fn try_from(worklog: SurrealWorkLog) -> HelixFlowResult<WorkLog> {
+        let id = item_id(&worklog.id);
+        Ok(WorkLog {
+            start: worklog.start,
+            end: worklog.end,
+            note: worklog.note,
+            id: id?,
+        })
+    }
+}
+
+impl From<&WorkLog> for SurrealWorkLog {
+    fn from(worklog: &WorkLog) -> Self {
+        SurrealWorkLog {
+            start: worklog.start,
+            end: worklog.end,
+            note: worklog.note.clone(),
+            id: Thing::from(("WorkLogs", Id::Uuid(worklog.id.into()))),
+        }
+    }
+}
+
+impl<C: Connection> Store<WorkLog> for SurrealDb<C> {
+    fn create(&self, worklog: &WorkLog) -> HelixFlowResult<WorkLog> {
+        let dbworklog: SurrealWorkLog = self
+            .rt
+            .block_on(
+                self.db
+                    .create("WorkLogs")
+                    .content(SurrealWorkLog::from(worklog))
+                    .into_future(),
+            )
+            .map_err(anyhow::Error::from)?
+            .with_context(|| format!("Creating new record for {:#?} in SurrealDb", worklog))?;
+        dbworklog.try_into()
+    }
+
+    fn get(&self, id: &Uuid) -> HelixFlowResult<WorkLog> {
+        let dbworklog: Option<SurrealWorkLog> = self
+            .rt
+            .block_on(self.db.select(("WorkLogs", *id)).into_future())
+            .map_err(anyhow::Error::from)?;
+        if let Some(worklog) = dbworklog {
+            Ok(worklog.try_into()?)
+        } else {
+            Err(HelixFlowError::NotFound {
+                itemtype: "WorkLog".into(),
+                id: *id,
+            })
+        }
+    }
+
+    fn update(&self, worklog: &WorkLog) -> HelixFlowResult<WorkLog> {
+        let dbworklog: Option<SurrealWorkLog> = self
+            .rt
+            .block_on(
+                self.db
+                    .update(("WorkLogs", worklog.id))
+                    .content(SurrealWorkLog::from(worklog))
+                    .into_future(),
+            )
+            .map_err(anyhow::Error::from)?;
+        if let Some(worklog) = dbworklog {
+            Ok(worklog.try_into()?)
+        } else {
+            Err(HelixFlowError::NotFound {
+                itemtype: "WorkLog".into(),
+                id: worklog.id,
+            })
+        }
+    }
+
+    fn delete(&self, id: &Uuid) -> HelixFlowResult<()> {
+        let deleted: Option<SurrealWorkLog> = self
+            .rt
+            .block_on(self.db.delete(("WorkLogs", *id)).into_future())
+            .map_err(anyhow::Error::from)?;
+        match deleted {
+            Some(worklog) => self.delete_edges(worklog.id),
+            None => Err(HelixFlowError::NotFound {
+                itemtype: "WorkLog".into(),
+                id: *id,
+            }),
+        }
+    }
+}
+
+impl<C: Connection> Relate<LoggedAgainst<Task, WorkLog>> for SurrealDb<C> {
+    /// The task must already exist, the work log is created.
+    fn create_linked_item(
+        &self,
+        link: &LoggedAgainst<Task, WorkLog>,
+    ) -> HelixFlowResult<LoggedAgainst<Task, WorkLog>> {
+        // TODO - RelBetwErrs (or impl Try for &LoggedAgainst ...)
+        let task = link.left.as_ref().unwrap();
+        let worklog = link.right.as_ref().unwrap();
+        let db_task: Task = self.get(&task.id)?;
+        let db_worklog = self.create(worklog)?;
+        let _: Vec<Link> = self
+            .rt
+            .block_on(
+                self.db
+                    .insert("logged_against")
+                    .relation(Link {
+                        r#in: SurrealTask::from(&db_task).id,
+                        out: SurrealWorkLog::from(&db_worklog).id,
+                    })
+                    .into_future(),
+            )
+            .map_err(anyhow::Error::from)?;
+        Ok(LoggedAgainst {
+            left: Ok(db_task),
+            right: Ok(db_worklog),
+        })
+    }
+
+    fn get_linked_items(
+        &self,
+        left: &Task,
+    ) -> HelixFlowResult<impl Iterator<Item = LoggedAgainst<Task, WorkLog>>> {
+        let mut response = self
+            .rt
+            .block_on(
+                self.db
+                    .query("SELECT ->logged_against->WorkLogs.* AS worklogs FROM $task")
+                    .bind(("task", SurrealTask::from(left).id))
+                    .into_future(),
+            )
+            .map_err(anyhow::Error::from)?;
+        let worklogs: Vec<Vec<SurrealWorkLog>> =
+            response.take("worklogs").map_err(anyhow::Error::from)?;
+        let relationships: Vec<LoggedAgainst<Task, WorkLog>> = worklogs
+            .into_iter()
+            .flatten()
+            .map(|worklog| LoggedAgainst {
+                left: Ok(left.clone()),
+                right: worklog.try_into(),
+            })
+            .collect();
+        Ok(relationships.into_iter())
+    }
+
+    fn delete_link(&self, link: &LoggedAgainst<Task, WorkLog>) -> HelixFlowResult<()> {
+        // TODO - RelBetwErrs (or impl Try for &LoggedAgainst ...)
+        self.delete_edge(
+            "logged_against",
+            Link {
+                r#in: SurrealTask::from(link.left.as_ref().unwrap()).id,
+                out: SurrealWorkLog::from(link.right.as_ref().unwrap()).id,
+            },
+        )
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SurrealAttachment {
+    filename: Cow<'static, str>,
+    mime_type: Cow<'static, str>,
+    content: AttachmentContent,
+    id: Thing,
+}
+
+impl TryFrom<SurrealAttachment> for Attachment {
+    type Error = HelixFlowError;
+    fn try_from(attachment: SurrealAttachment) -> HelixFlowResult<Attachment> {
+        let id = item_id(&attachment.id);
+        Ok(Attachment {
+            filename: attachment.filename,
+            mime_type: attachment.mime_type,
+            content: attachment.content,
+            id: id?,
+        })
+    }
+}
+
+impl From<&Attachment> for SurrealAttachment {
+    fn from(attachment: &Attachment) -> Self {
+        SurrealAttachment {
+            filename: attachment.filename.clone(),
+            mime_type: attachment.mime_type.clone(),
+            content: attachment.content.clone(),
+            id: Thing::from(("Attachments", Id::Uuid(attachment.id.into()))),
+        }
+    }
+}
+
+/// Blobs are stored in the record along with the metadata.
+impl<C: Connection> Store<Attachment> for SurrealDb<C> {
+    fn create(&self, attachment: &Attachment) -> HelixFlowResult<Attachment> {
+        let dbattachment: SurrealAttachment = self
+            .rt
+            .block_on(
+                self.db
+                    .create("Attachments")
+                    .content(SurrealAttachment::from(attachment))
+                    .into_future(),
+            )
+            .map_err(anyhow::Error::from)?
+            .with_context(|| {
+                format!(
+                    "Creating new record for attachment {} in SurrealDb",
+                    attachment.filename
+                )
+            })?;
+        dbattachment.try_into()
+    }
+
+    fn get(&self, id: &Uuid) -> HelixFlowResult<Attachment> {
+        let dbattachment: Option<SurrealAttachment> = self
+            .rt
+            .block_on(self.db.select(("Attachments", *id)).into_future())
+            .map_err(anyhow::Error::from)?;
+        if let Some(attachment) = dbattachment {
+            Ok(attachment.try_into()?)
+        } else {
+            Err(HelixFlowError::NotFound {
+                itemtype: "Attachment".into(),
+                id: *id,
+            })
+        }
+    }
+
+    fn update(&self, attachment: &Attachment) -> HelixFlowResult<Attachment> {
+        let dbattachment: Option<SurrealAttachment> = self
+            .rt
+            .block_on(
+                self.db
+                    .update(("Attachments", attachment.id))
+                    .content(SurrealAttachment::from(attachment))
+                    .into_future(),
+            )
+            .map_err(anyhow::Error::from)?;
+        if let Some(attachment) = dbattachment {
+            Ok(attachment.try_into()?)
+        } else {
+            Err(HelixFlowError::NotFound {
+                itemtype: "Attachment".into(),
+                id: attachment.id,
+            })
+        }
+    }
+
+    fn delete(&self, id: &Uuid) -> HelixFlowResult<()> {
+        let deleted: Option<SurrealAttachment> = self
+            .rt
+            .block_on(self.db.delete(("Attachments", *id)).into_future())
+            .map_err(anyhow::Error::from)?;
+        match deleted {
+            Some(attachment) => self.delete_edges(attachment.id),
+            None => Err(HelixFlowError::NotFound {
+                itemtype: "Attachment".into(),
+                id: *id,
+            }),
+        }
+    }
+}
+
+impl<C: Connection> Relate<HasAttachment<Task, Attachment>> for SurrealDb<C> {
+    /// The task must already exist, the attachment is created.
+    fn create_linked_item(
+        &self,
+        link: &HasAttachment<Task, Attachment>,
+    ) -> HelixFlowResult<HasAttachment<Task, Attachment>> {
+        // TODO - RelBetwErrs (or impl Try for &HasAttachment ...)
+        let task = link.left.as_ref().unwrap();
+        let attachment = link.right.as_ref().unwrap();
+        let db_task: Task = self.get(&task.id)?;
+        let db_attachment = self.create(attachment)?;
+        let _: Vec<Link> = self
+            .rt
+            .block_on(
+                self.db
+                    .insert("has_attachment")
+                    .relation(Link {
+                        r#in: SurrealTask::from(&db_task).id,
+                        out: SurrealAttachment::from(&db_attachment).id,
+                    })
+                    .into_future(),
+            )
+            .map_err(anyhow::Error::from)?;
+        Ok(HasAttachment {
+            left: Ok(db_task),
+            right: Ok(db_attachment),
+        })
+    }
+
+    fn get_linked_items(
+        &self,
+        left: &Task,
+    ) -> HelixFlowResult<impl Iterator<Item = HasAttachment<Task, Attachment>>> {
+        let mut response = self
+            .rt
+            .block_on(
+                self.db
+                    .query("SELECT ->has_attachment->Attachments.* AS attachments FROM $task")
+                    .bind(("task", SurrealTask::from(left).id))
+                    .into_future(),
+            )
+            .map_err(anyhow::Error::from)?;
+        let attachments: Vec<Vec<SurrealAttachment>> =
+            response.take("attachments").map_err(anyhow::Error::from)?;
+        let relationships: Vec<HasAttachment<Task, Attachment>> = attachments
+            .into_iter()
+            .flatten()
+            .map(|attachment| HasAttachment {
+                left: Ok(left.clone()),
+                right: attachment.try_into(),
+            })
+            .collect();
+        Ok(relationships.into_iter())
+    }
+
+    fn delete_link(&self, link: &HasAttachment<Task, Attachment>) -> HelixFlowResult<()> {
+        // TODO - RelBetwErrs (or impl Try for &HasAttachment ...)
+        self.delete_edge(
+            "has_attachment",
+            Link {
+                r#in: SurrealTask::from(link.left.as_ref().unwrap()).id,
+                out: SurrealAttachment::from(link.right.as_ref().unwrap()).id,
+            },
+        )
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SurrealComment {
+    task: Thing,
+    text: String,
+    annotation: Annotation,
+    id: Thing,
+}
+
+impl TryFrom<SurrealComment> for Comment {
+    type Error = HelixFlowError;
+    fn try_from(comment: SurrealComment) -> HelixFlowResult<Comment> {
+        Ok(Comment {
+            id: item_id(&comment.id)?,
+            task: item_id(&comment.task)?,
+            text: comment.text,
+            annotation: comment.annotation,
+        })
+    }
+}
+
+impl From<&Comment> for SurrealComment {
+    fn from(comment: &Comment) -> Self {
+        SurrealComment {
+            task: Thing::from(("Tasks", Id::Uuid(comment.task.into()))),
+            text: comment.text.clone(),
+            annotation: comment.annotation.clone(),
+            id: Thing::from(("Comments", Id::Uuid(comment.id.into()))),
+        }
+    }
+}
+
+impl<C: Connection> Store<Comment> for SurrealDb<C> {
+    fn create(&self, comment: &Comment) -> HelixFlowResult<Comment> {
+        let dbcomment: SurrealComment = self
+            .rt
+            .block_on(
+                self.db
+                    .create("Comments")
+                    .content(SurrealComment::from(comment))
+                    .into_future(),
+            )
+            .map_err(anyhow::Error::from)?
+            .with_context(|| format!("Creating new record for {:#?} in SurrealDb", comment))?;
+        dbcomment.try_into()
+    }
+
+    fn get(&self, id: &Uuid) -> HelixFlowResult<Comment> {
+        let dbcomment: Option<SurrealComment> = self
+            .rt
+            .block_on(self.db.select(("Comments", *id)).into_future())
+            .map_err(anyhow::Error::from)?;
+        if let Some(comment) = dbcomment {
+            Ok(comment.try_into()?)
+        } else {
+            Err(HelixFlowError::NotFound {
+                itemtype: "Comment".into(),
+                id: *id,
+            })
+        }
+    }
+
+    fn update(&self, comment: &Comment) -> HelixFlowResult<Comment> {
+        let dbcomment: Option<SurrealComment> = self
+            .rt
+            .block_on(
+                self.db
+                    .update(("Comments", comment.id))
+                    .content(SurrealComment::from(comment))
+                    .into_future(),
+            )
+            .map_err(anyhow::Error::from)?;
+        if let Some(comment) = dbcomment {
+            Ok(comment.try_into()?)
+        } else {
+            Err(HelixFlowError::NotFound {
+                itemtype: "Comment".into(),
+                id: comment.id,
+            })
+        }
+    }
+
+    fn delete(&self, id: &Uuid) -> HelixFlowResult<()> {
+        let deleted: Option<SurrealComment> = self
+            .rt
+            .block_on(self.db.delete(("Comments", *id)).into_future())
+            .map_err(anyhow::Error::from)?;
+        match deleted {
+            Some(_) => Ok(()),
+            None => Err(HelixFlowError::NotFound {
+                itemtype: "Comment".into(),
+                id: *id,
+            }),
+        }
+    }
+}
+
+impl<C: Connection> Comments for SurrealDb<C> {
+    /// Oldest first.
+    fn comments_on(&self, task: &Task) -> HelixFlowResult<Vec<Comment>> {
+        let mut response = self
+            .rt
+            .block_on(
+                self.db
+                    .query(
+                        "SELECT *, type::datetime(annotation.at) AS written FROM Comments \
+                        WHERE task = $task ORDER BY written, id",
+                    )
+                    .bind(("task", SurrealTask::from(task).id))
+                    .into_future(),
+            )
+            .map_err(anyhow::Error::from)?;
+        let comments: Vec<SurrealComment> = response.take(0).map_err(anyhow::Error::from)?;
+        comments.into_iter().map(Comment::try_from).collect()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SurrealReminder {
+    task: Thing,
+    fire_at: chrono::DateTime<chrono::Utc>,
+    repeat: Repeat,
+    id: Thing,
+}
+
+impl TryFrom<SurrealReminder> for Reminder {
+    type Error = HelixFlowError;
+    fn try_from(reminder: SurrealReminder) -> HelixFlowResult<Reminder> {
+        Ok(Reminder {
+            task: item_id(&reminder.task)?,
+            fire_at: reminder.fire_at,
+            repeat: reminder.repeat,
+            id: item_id(&reminder.id)?,
+        })
+    }
+}
+
+impl From<&Reminder> for SurrealReminder {
+    fn from(reminder: &Reminder) -> Self {
+        SurrealReminder {
+            task: Thing::from(("Tasks", Id::Uuid(reminder.task.into()))),
+            fire_at: reminder.fire_at,
+            repeat: reminder.repeat,
+            id: Thing::from(("Reminders", Id::Uuid(reminder.id.into()))),
+        }
+    }
+}
+
+impl<C: Connection> Store<Reminder> for SurrealDb<C> {
+    fn create(&self, reminder: &Reminder) -> HelixFlowResult<Reminder> {
+        let dbreminder: SurrealReminder = self
+            .rt
+            .block_on(
+                self.db
+                    .create("Reminders")
+                    .content(SurrealReminder::from(reminder))
+                    .into_future(),
+            )
+            .map_err(anyhow::Error::from)?
+            .with_context(|| format!("Creating new record for {:#?} in SurrealDb", reminder))?;
+        dbreminder.try_into()
+    }
+
+    fn get(&self, id: &Uuid) -> HelixFlowResult<Reminder> {
+        let dbreminder: Option<SurrealReminder> = self
+            .rt
+            .block_on(self.db.select(("Reminders", *id)).into_future())
+            .map_err(anyhow::Error::from)?;
+        if let Some(reminder) = dbreminder {
+            Ok(reminder.try_into()?)
+        } else {
+            Err(HelixFlowError::NotFound {
+                itemtype: "Reminder".into(),
+                id: *id,
+            })
+        }
+    }
+
+    fn update(&self, reminder: &Reminder) -> HelixFlowResult<Reminder> {
+        let dbreminder: Option<SurrealReminder> = self
+            .rt
+            .block_on(
+                self.db
+                    .update(("Reminders", reminder.id))
+                    .content(SurrealReminder::from(reminder))
+                    .into_future(),
+            )
+            .map_err(anyhow::Error::from)?;
+        if let Some(reminder) = dbreminder {
+            Ok(reminder.try_into()?)
+        } else {
+            Err(HelixFlowError::NotFound {
+                itemtype: "Reminder".into(),
+                id: reminder.id,
+            })
+        }
+    }
+
+    fn delete(&self, id: &Uuid) -> HelixFlowResult<()> {
+        let deleted: Option<SurrealReminder> = self
+            .rt
+            .block_on(self.db.delete(("Reminders", *id)).into_future())
+            .map_err(anyhow::Error::from)?;
+        match deleted {
+            Some(_) => Ok(()),
+            None => Err(HelixFlowError::NotFound {
+                itemtype: "Reminder".into(),
+                id: *id,
+            }),
+        }
+    }
+}
+
+impl<C: Connection> Reminders for SurrealDb<C> {
+    fn due_reminders(&self, now: chrono::DateTime<chrono::Utc>) -> HelixFlowResult<Vec<Reminder>> {
+        let mut response = self
+            .rt
+            .block_on(
+                self.db
+                    .query(
+                        "SELECT *, type::datetime(fire_at) AS fires FROM Reminders \
+                        WHERE type::datetime(fire_at) <= type::datetime($now) ORDER BY fires, id",
+                    )
+                    .bind(("now", now))
+                    .into_future(),
+            )
+            .map_err(anyhow::Error::from)?;
+        let reminders: Vec<SurrealReminder> = response.take(0).map_err(anyhow::Error::from)?;
+        reminders.into_iter().map(Reminder::try_from).collect()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SurrealTrashed {
+    id: Thing,
+    task: SurrealTask,
+    tasklist: Option<Thing>,
+    deleted_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl TryFrom<SurrealTrashed> for Trashed {
+    type Error = HelixFlowError;
+    fn try_from(trashed: SurrealTrashed) -> HelixFlowResult<Trashed> {
+        let tasklist = trashed.tasklist.as_ref().map(item_id).transpose()?;
+        Ok(Trashed {
+            task: trashed.task.try_into()?,
+            tasklist,
+            deleted_at: trashed.deleted_at,
+        })
+    }
+}
+
+impl From<&Trashed> for SurrealTrashed {
+    fn from(trashed: &Trashed) -> Self {
+        SurrealTrashed {
+            id: Thing::from(("Trash", Id::Uuid(trashed.task.id.into()))),
+            task: SurrealTask::from(&trashed.task),
+            tasklist: trashed
+                .tasklist
+                .map(|id| Thing::from(("Tasklists", Id::Uuid(id.into())))),
+            deleted_at: trashed.deleted_at,
+        }
+    }
+}
+
+/// Trashed tasks are kept in the `Trash` table, under the id of the task.
+impl<C: Connection> Trash for SurrealDb<C> {
+    fn put_in_trash(&self, trashed: &Trashed) -> HelixFlowResult<()> {
+        let _: Option<SurrealTrashed> = self
+            .rt
+            .block_on(
+                self.db
+                    .upsert(("Trash", trashed.task.id))
+                    .content(SurrealTrashed::from(trashed))
+                    .into_future(),
+            )
+            .map_err(anyhow::Error::from)?;
+        Ok(())
+    }
+
+    fn trashed(&self) -> HelixFlowResult<Vec<Trashed>> {
+        let mut response = self
+            .rt
+            .block_on(
+                self.db
+                    .query(
+                        "SELECT *, type::datetime(deleted_at) AS deleted FROM Trash \
+                        ORDER BY deleted DESC, id",
+                    )
+                    .into_future(),
+            )
+            .map_err(anyhow::Error::from)?;
+        let trashed: Vec<SurrealTrashed> = response.take(0).map_err(anyhow::Error::from)?;
+        trashed.into_iter().map(Trashed::try_from).collect()
+    }
+
+    fn take_from_trash(&self, id: &Uuid) -> HelixFlowResult<Trashed> {
+        let deleted: Option<SurrealTrashed> = self
+            .rt
+            .block_on(self.db.delete(("Trash", *id)).into_future())
+            .map_err(anyhow::Error::from)?;
+        match deleted {
+            Some(trashed) => trashed.try_into(),
+            None => Err(HelixFlowError::NotFound {
+                itemtype: "Trashed".into(),
+                id: *id,
+            }),
+        }
+    }
+
+    fn purge_trash(&self, deleted_before: chrono::DateTime<chrono::Utc>) -> HelixFlowResult<usize> {
+        let mut response = self
+            .rt
+            .block_on(
+                self.db
+                    .query(
+                        "DELETE Trash WHERE type::datetime(deleted_at) < type::datetime($before) \
+                        RETURN BEFORE",
+                    )
+                    .bind(("before", deleted_before))
+                    .into_future(),
+            )
+            .map_err(anyhow::Error::from)?;
+        let purged: Vec<SurrealTrashed> = response.take(0).map_err(anyhow::Error::from)?;
+        Ok(purged.len())
+    }
+
+    fn empty_trash(&self) -> HelixFlowResult<usize> {
+        let purged: Vec<SurrealTrashed> = self
+            .rt
+            .block_on(self.db.delete("Trash").into_future())
+            .map_err(anyhow::Error::from)?;
+        Ok(purged.len())
+    }
+}
+
+impl SurrealDb<Db> {
+    /// Instantiate an local Db, with data saved in `Some(file)` on drop,
+    /// or simply held in memory (`None`).
+    ///
+    /// Note:
+    /// - `ns` & `db` = "HelixFlow", see `with_config` to change them
+    /// - This is a blocking operation until the db is available.
+    pub fn new(file: Option<PathBuf>) -> anyhow::Result<Self> {
+        Self::with_config(file, SurrealConfig::default())
+    }
+
+    /// As `new`, using the namespace, database & threads in `config`.
+    pub fn with_config(file: Option<PathBuf>, config: SurrealConfig) -> anyhow::Result<Self> {
+        debug!("Initialising tokio runtime");
+        let rt = runtime(config.worker_threads)
+            .context("Initialising dedicated tokio runtime for surreal in memory database.")?;
+        debug!("Initialising database");
+        let db = rt
+            .block_on(Surreal::new::<Mem>(()).into_future())
+            .context("Initialising database")?;
+        debug!("Selecting database namespace");
+        rt.block_on(
+            db.use_ns(&config.namespace)
+                .use_db(&config.database)
+                .into_future(),
+        )
+        .context("Selecting database namespace")?;
+        if let Some(file) = &file {
+            import_if_exists(&rt, &db, file, config.passphrase.as_ref())?;
+        }
+        debug!("Stuffing the runtime in an Arc");
+        let runtime = Arc::new(TimedRuntime::new(rt));
+        let backend = Self {
+            db,
+            rt: runtime,
+            file,
+            dir: None,
+            actor: RwLock::new(None),
+            namespace: RwLock::new(config.namespace.clone()),
+            loaded: RwLock::new(HashSet::from([config.namespace.clone()])),
+            unsaved: AtomicUsize::new(0),
+            snapshot: Mutex::new(None),
+            config,
+        };
+        debug!("Migrating schema");
+        backend.migrate()?;
+        debug!("Done connecting to database");
+        Ok(backend)
+    }
+
+    /// Open (or create) the database stored in the directory `path`. Every change is written to
+    /// disk as it is made, so nothing is lost if the app crashes.
+    ///
+    /// Note:
+    /// - `ns` & `db` = "HelixFlow", see `open_with_config` to change them
+    /// - All namespaces are stored in `path`.
+    /// - Only one `SurrealDb` can open `path` at a time.
+    /// - This is a blocking operation until the db is available.
+    pub fn open(path: &Path) -> anyhow::Result<Self> {
+        Self::open_with_config(path, SurrealConfig::default())
+    }
+
+    /// As `open`, with a runtime of `worker_threads` threads, so that queries from several
+    /// threads, e.g. a background worker & the UI, run at the same time.
+    pub fn open_multi_thread(path: &Path, worker_threads: usize) -> anyhow::Result<Self> {
+        Self::open_with_config(
+            path,
+            SurrealConfig {
+                worker_threads: Some(worker_threads),
+                ..Default::default()
+            },
+        )
+    }
+
+    /// As `open`, using the namespace, database & threads in `config`.
+    pub fn open_with_config(path: &Path, config: SurrealConfig) -> anyhow::Result<Self> {
+        debug!("Initialising tokio runtime");
+        let rt = runtime(config.worker_threads)
+            .context("Initialising dedicated tokio runtime for surreal on-disk database.")?;
+        debug!("Opening {:#?}", path);
+        let db = rt
+            .block_on(Surreal::new::<SurrealKv>(path).into_future())
+            .context(format!("Opening {:#?}", path))?;
+        debug!("Selecting database namespace");
+        rt.block_on(
+            db.use_ns(&config.namespace)
+                .use_db(&config.database)
+                .into_future(),
+        )
+        .context("Selecting database namespace")?;
+        let backend = Self {
+            db,
+            rt: Arc::new(TimedRuntime::new(rt)),
+            file: None,
+            dir: Some(path.to_path_buf()),
+            actor: RwLock::new(None),
+            namespace: RwLock::new(config.namespace.clone()),
+            loaded: RwLock::new(HashSet::from([config.namespace.clone()])),
+            unsaved: AtomicUsize::new(0),
+            snapshot: Mutex::new(None),
+            config,
+        };
+        debug!("Migrating schema");
+        backend.migrate()?;
+        debug!("Done connecting to database");
+        Ok(backend)
+    }
+
+    /// Load everything in `file`, an export such as those saved by `new`, into the namespace
+    /// in use, e.g. to move to a database from `open`. Meant for an empty namespace, use
+    /// `merge_import` to combine data. A missing file is treated as empty.
+    ///
+    /// `file` is decrypted with the `SurrealConfig::passphrase`, if there is one.
+    pub fn import(&self, file: &Path) -> anyhow::Result<()> {
+        import_if_exists(&self.rt, &self.db, file, self.config.passphrase.as_ref())
+    }
+}
+
+/// A dedicated runtime to block on: on the calling thread, or with `worker_threads` threads of
+/// its own.
+fn runtime(worker_threads: Option<usize>) -> std::io::Result<tokio::runtime::Runtime> {
+    match worker_threads {
+        None => tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build(),
+        Some(threads) => tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(threads.max(1))
+            .enable_all()
+            .build(),
+    }
+}
+
+/// Import `file` into the namespace `db` is using, decrypting it with `passphrase`. A missing
+/// file is treated as empty.
+fn import_if_exists(
+    rt: &tokio::runtime::Runtime,
+    db: &Surreal<Db>,
+    file: &Path,
+    passphrase: Option<&Passphrase>,
+) -> anyhow::Result<()> {
+    if let Some(passphrase) = passphrase {
+        let encrypted = match std::fs::read(file) {
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            read => read.with_context(|| format!("Reading {:#?}", file))?,
+        };
+        let export = encryption::decrypt(&encrypted, passphrase)
+            .with_context(|| format!("Decrypting {:#?}", file))?;
+        let export = String::from_utf8(export).context("Decrypted export is not SurrealQL")?;
+        rt.block_on(db.query(export).into_future())
+            .and_then(|response| response.check())
+            .with_context(|| format!("Importing {:#?}", file))?;
+        return Ok(());
+    }
+    let imported = rt.block_on(db.import(file).into_future());
+
+    if let Err(e) = &imported
+        && let surrealdb::Error::Api(Api::FileOpen { error, path }) = e
+        && error.kind() == std::io::ErrorKind::NotFound
+        && path == file
+    {
+        Ok(())
+    } else {
+        imported
+    }
+    .context(format!("Importing {:#?}", file))
+}
+
+/// Each namespace is saved to its own file: the default namespace to `file`, others alongside
+/// it, e.g. `helixflow.work.kv` for the namespace "work".
+///
+/// `DEFAULT_NAMESPACE` is the namespace in the `SurrealConfig`.
+impl Namespaces for SurrealDb<Db> {
+    fn use_namespace(&self, namespace: &str) -> HelixFlowResult<()> {
+        let namespace = if namespace == DEFAULT_NAMESPACE {
+            self.config.namespace.as_str()
+        } else {
+            namespace
+        };
+        let current = self.namespace.read().unwrap().clone();
+        if namespace == current {
+            return Ok(());
+        }
+        self.flush()?;
+        self.rt
+            .block_on(
+                self.db
+                    .use_ns(namespace)
+                    .use_db(&self.config.database)
+                    .into_future(),
+            )
+            .context("Selecting database namespace")?;
+        *self.namespace.write().unwrap() = namespace.into();
+        if self.loaded.write().unwrap().insert(namespace.into()) {
+            if let Some(file) = self.namespace_file(namespace) {
+                import_if_exists(&self.rt, &self.db, &file, self.config.passphrase.as_ref())?;
+            }
+            self.migrate()?;
+        }
+        Ok(())
+    }
+}
+
+impl<C: Connection> SurrealDb<C> {
+    /// Save the namespace in use to its file now, rather than waiting for `FLUSH_AFTER_CHANGES`
+    /// or drop. Does nothing for databases which store every change as it is made.
+    pub fn flush(&self) -> HelixFlowResult<()> {
+        let namespace = self.namespace.read().unwrap().clone();
+        if let Some(file) = self.namespace_file(&namespace) {
+            debug!("Saving {namespace} to {:#?}", file);
+            self.export_file(&file)?;
+        }
+        self.unsaved.store(0, Ordering::SeqCst);
+        Ok(())
+    }
+
+    /// Also `flush` every `interval` in the background, until the backend is dropped.
+    pub fn flush_every(self: &Arc<Self>, interval: Duration)
+    where
+        C: 'static,
+    {
+        self.every(interval, Self::flush);
+    }
+
+    /// Save a copy of the namespace in use alongside its file, keeping the `keep` most recent:
+    /// `helixflow.kv.1` is the newest, `helixflow.kv.2` the one before etc. Does nothing for
+    /// databases without a file.
+    pub fn backup(&self, keep: usize) -> HelixFlowResult<()> {
+        let namespace = self.namespace.read().unwrap().clone();
+        let Some(file) = self.namespace_file(&namespace) else {
+            return Ok(());
+        };
+        if keep == 0 {
+            return Ok(());
+        }
+        let backup = |n: usize| {
+            let mut backup = file.clone().into_os_string();
+            backup.push(format!(".{n}"));
+            PathBuf::from(backup)
+        };
+        for n in (1..keep).rev() {
+            let older = backup(n);
+            if older.exists() {
+                std::fs::rename(&older, backup(n + 1))
+                    .with_context(|| format!("Rotating backup {:#?}", older))?;
+            }
+        }
+        debug!("Backing up {namespace} to {:#?}", backup(1));
+        self.export_file(&backup(1))?;
+        Ok(())
+    }
+
+    /// Export the namespace in use to `file`, encrypted if there is a `SurrealConfig::passphrase`.
+    fn export_file(&self, file: &Path) -> anyhow::Result<()> {
+        let Some(passphrase) = &self.config.passphrase else {
+            return self
+                .rt
+                .block_on(self.db.export(file).into_future())
+                .with_context(|| format!("Exporting {:#?}", file));
+        };
+        let export = self
+            .export_bytes()
+            .with_context(|| format!("Exporting {:#?}", file))?;
+        let encrypted = encryption::encrypt(&export, passphrase)
+            .with_context(|| format!("Encrypting {:#?}", file))?;
+        std::fs::write(file, encrypted).with_context(|| format!("Writing {:#?}", file))
+    }
+
+    /// The namespace in use, exported as SurrealQL.
+    fn export_bytes(&self) -> Result<Vec<u8>, surrealdb::Error> {
+        self.rt.block_on(async {
+            let mut export = vec![];
+            let mut chunks = self.db.export(()).await?;
+            while let Some(chunk) = chunks.next().await {
+                export.extend(chunk?);
+            }
+            Ok(export)
+        })
+    }
+
+    /// `flush` & `backup` every `interval` in the background, until the backend is dropped.
+    pub fn autosave(self: &Arc<Self>, interval: Duration, keep: usize)
+    where
+        C: 'static,
+    {
+        self.every(interval, move |backend| {
+            backend.flush()?;
+            backend.backup(keep)
+        });
+    }
+
+    /// Run `save` every `interval` in a background thread, logging any errors.
+    fn every<F>(self: &Arc<Self>, interval: Duration, save: F)
+    where
+        C: 'static,
+        F: Fn(&Self) -> HelixFlowResult<()> + Send + 'static,
+    {
+        let backend = Arc::downgrade(self);
+        thread::spawn(move || {
+            loop {
+                thread::sleep(interval);
+                let Some(backend) = backend.upgrade() else {
+                    break;
+                };
+                if let Err(e) = save(&backend) {
+                    error!("Could not save changes: {e:#}");
+                }
+            }
+        });
+    }
+
+    /// The key `id` was stored under before `merge_import` gave it a UUID, e.g. `Tasks:42`. `None`
+    /// if it was always a UUID.
+    pub fn original_key(&self, id: &Uuid) -> HelixFlowResult<Option<String>> {
+        let alias: Option<IdAlias> = self
+            .rt
+            .block_on(self.db.select(("id_aliases", *id)).into_future())
+            .map_err(anyhow::Error::from)?;
+        Ok(alias.map(|alias| alias.original))
+    }
+
+    fn record_alias(&self, item: Thing, original: String) -> HelixFlowResult<()> {
+        let id = item_id(&item)?;
+        let _: Option<IdAlias> = self
+            .rt
+            .block_on(
+                self.db
+                    .upsert(("id_aliases", id))
+                    .content(IdAlias { item, original })
+                    .into_future(),
+            )
+            .map_err(anyhow::Error::from)?;
+        Ok(())
+    }
+
+    /// Where the data in `namespace` is saved, `None` if held in memory.
+    fn namespace_file(&self, namespace: &str) -> Option<PathBuf> {
+        let file = self.file.as_ref()?;
+        if namespace == self.config.namespace {
+            return Some(file.clone());
+        }
+        let extension = file
+            .extension()
+            .map_or("kv".into(), |extension| extension.to_string_lossy());
+        Some(file.with_extension(format!("{namespace}.{extension}")))
+    }
+}
+
+/// Uses the full-text indexes on task names & descriptions, so words match after stemming:
+/// "paying" finds "pay". Names also match the start of a word, so "rep" finds "report" while
+/// typing.
+impl<C: Connection> Search<Task> for SurrealDb<C> {
+    fn search(&self, query: &str) -> HelixFlowResult<impl Iterator<Item = Task>> {
+        if query.trim().is_empty() {
+            return Ok(vec![].into_iter());
+        }
+        let mut response = self
+            .rt
+            .block_on(
+                self.db
+                    .query(
+                        "SELECT *, search::score(0) + search::score(1) + search::score(2) AS score \
+                        FROM Tasks WHERE name @0@ $query OR description @1@ $query \
+                        OR name @2@ $query ORDER BY score DESC",
+                    )
+                    .bind(("query", query.to_string()))
+                    .into_future(),
+            )
+            .map_err(anyhow::Error::from)?;
+        let tasks: Vec<SurrealTask> = response.take(0).map_err(anyhow::Error::from)?;
+        let tasks = tasks
+            .into_iter()
+            .map(Task::try_from)
+            .collect::<HelixFlowResult<Vec<Task>>>()?;
+        Ok(tasks.into_iter())
+    }
+}
+
+/// Filters, sorts & limits in the database. Due dates are compared as datetimes, whichever way
+/// they happen to be stored.
+impl<C: Connection> Query<Task> for SurrealDb<C> {
+    fn query(&self, filter: &TaskQuery) -> HelixFlowResult<impl Iterator<Item = Task>> {
+        let mut conditions = vec![];
+        if !filter.statuses.is_empty() {
+            conditions.push("status IN $statuses");
+        }
+        if filter.tag.is_some() {
+            conditions.push("id IN (SELECT VALUE in FROM tagged_with WHERE out = $tag)");
+        }
+        if filter.context.is_some() {
+            conditions.push("id IN (SELECT VALUE in FROM in_context WHERE out = $context)");
+        }
+        if filter.tasklist.is_some() {
+            conditions.push("id IN (SELECT VALUE out FROM contains WHERE in = $tasklist)");
+        }
+        if filter.due_from.is_some() {
+            conditions.push("due != NONE AND type::datetime(due) >= type::datetime($due_from)");
+        }
+        if filter.due_before.is_some() {
+            conditions.push("due != NONE AND type::datetime(due) < type::datetime($due_before)");
+        }
+        let mut order = vec![];
+        for key in &filter.sort.0 {
+            match key {
+                SortKey::OverdueFirst => order.push("overdue DESC"),
+                SortKey::DueDate => order.extend(["no_due", "due_at"]),
+                SortKey::Name => order.push("lowercase_name"),
+                SortKey::Priority => order.push("priority"),
+                SortKey::RecentlyUpdated => order.push("updated DESC"),
+                SortKey::DoneLast => order.push("done"),
+            }
+        }
+        order.push("id");
+        let mut sql = "SELECT *, \
+            (due != NONE AND type::datetime(due) < time::now()) AS overdue, \
+            (due = NONE) AS no_due, \
+            (IF due != NONE THEN type::datetime(due) END) AS due_at, \
+            string::lowercase(name) AS lowercase_name, \
+            (IF updated_at != NONE THEN type::datetime(updated_at) END) AS updated, \
+            (status = 'Done') AS done \
+            FROM Tasks"
+            .to_string();
+        if !conditions.is_empty() {
+            sql += &format!(" WHERE {}", conditions.join(" AND "));
+        }
+        sql += &format!(" ORDER BY {}", order.join(", "));
+        if filter.limit.is_some() {
+            sql += " LIMIT $limit";
+        }
+        let mut response = self
+            .rt
+            .block_on(
+                self.db
+                    .query(sql)
+                    .bind(("statuses", filter.statuses.clone()))
+                    .bind((
+                        "tag",
+                        filter
+                            .tag
+                            .map(|id| Thing::from(("Tags", Id::Uuid(id.into())))),
+                    ))
+                    .bind((
+                        "context",
+                        filter
+                            .context
+                            .map(|id| Thing::from(("Contexts", Id::Uuid(id.into())))),
+                    ))
+                    .bind((
+                        "tasklist",
+                        filter
+                            .tasklist
+                            .map(|id| Thing::from(("Tasklists", Id::Uuid(id.into())))),
+                    ))
+                    .bind(("due_from", filter.due_from))
+                    .bind(("due_before", filter.due_before))
+                    .bind(("limit", filter.limit))
+                    .into_future(),
+            )
+            .map_err(anyhow::Error::from)?;
+        let tasks: Vec<SurrealTask> = response.take(0).map_err(anyhow::Error::from)?;
+        let tasks = tasks
+            .into_iter()
+            .map(Task::try_from)
+            .collect::<HelixFlowResult<Vec<Task>>>()?;
+        Ok(tasks.into_iter())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct SurrealCompletionCounts {
+    total: usize,
+    completed_before: Vec<usize>,
+}
+
+impl<C: Connection> Burndown for SurrealDb<C> {
+    /// Counted in the database, only the totals are returned.
+    fn completion_counts(
+        &self,
+        tasklist: &TaskList,
+        cutoffs: &[chrono::DateTime<chrono::Utc>],
+    ) -> HelixFlowResult<CompletionCounts> {
+        let mut response = self
+            .rt
+            .block_on(
+                self.db
+                    .query(
+                        "LET $tasks = (SELECT VALUE out FROM contains WHERE in = $tasklist); \
+                        LET $done = (SELECT VALUE type::datetime(completed_at) FROM $tasks \
+                            WHERE completed_at != NONE); \
+                        RETURN { \
+                            total: array::len($tasks), \
+                            completed_before: array::map($cutoffs, |$cutoff| \
+                                array::len(array::filter($done, |$at| \
+                                    $at < type::datetime($cutoff)))) \
+                        };",
+                    )
+                    .bind(("tasklist", SurrealTaskList::from(tasklist).id))
+                    .bind(("cutoffs", cutoffs.to_vec()))
+                    .into_future(),
+            )
+            .map_err(anyhow::Error::from)?;
+        let counts: Option<SurrealCompletionCounts> =
+            response.take(2).map_err(anyhow::Error::from)?;
+        Ok(counts.map_or_else(
+            || CompletionCounts {
+                completed_before: vec![0; cutoffs.len()],
+                ..Default::default()
+            },
+            |counts| CompletionCounts {
+                total: counts.total,
+                completed_before: counts.completed_before,
+            },
+        ))
+    }
+}
+
+/// Outcome of `SurrealDb::merge_import`
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct MergeReport {
+    /// Records which did not exist yet
+    pub imported: usize,
+    /// Records whose id was already used by a different record, imported under a new id
+    pub remapped: usize,
+    /// Records which already existed unchanged
+    pub unchanged: usize,
+    /// Relationships created
+    pub links: usize,
+}
+
+impl<C: Connection> SurrealDb<C> {
+    /// Store `item`, unless an identical record already exists. If a _different_ record already
+    /// uses the same id, store `item` under a new id.
+    ///
+    /// Returns the id under which `item` is now stored.
+    fn merge_item<ITEM>(
+        &self,
+        mut item: ITEM,
+        id: fn(&mut ITEM) -> &mut Uuid,
+        report: &mut MergeReport,
+    ) -> HelixFlowResult<Uuid>
+    where
+        Self: Store<ITEM>,
+        ITEM: PartialEq,
+    {
+        match Store::<ITEM>::get(self, id(&mut item)) {
+            Ok(existing) if existing == item => {
+                report.unchanged += 1;
+                return Ok(*id(&mut item));
+            }
+            Ok(_) => {
+                report.remapped += 1;
+                *id(&mut item) = Uuid::now_v7();
+            }
+            Err(HelixFlowError::NotFound { .. }) => report.imported += 1,
+            Err(e) => return Err(e),
+        }
+        Store::<ITEM>::create(self, &item)?;
+        Ok(*id(&mut item))
+    }
+
+    /// Remove all relationships to or from `record`.
+    fn delete_edges(&self, record: Thing) -> HelixFlowResult<()> {
+        self.rt
+            .block_on(
+                self.db
+                    .query("DELETE contains WHERE in = $record OR out = $record")
+                    .query("DELETE watches WHERE in = $record OR out = $record")
+                    .query("DELETE tagged_with WHERE in = $record OR out = $record")
+                    .query("DELETE depends_on WHERE in = $record OR out = $record")
+                    .query("DELETE logged_against WHERE in = $record OR out = $record")
+                    .query("DELETE has_attachment WHERE in = $record OR out = $record")
+                    .query("DELETE assigned_to WHERE in = $record OR out = $record")
+                    .query("DELETE in_context WHERE in = $record OR out = $record")
+                    .bind(("record", record))
+                    .into_future(),
+            )
+            .map_err(anyhow::Error::from)?
+            .check()
+            .map_err(anyhow::Error::from)?;
+        Ok(())
+    }
+
+    /// Remove the `table` edge between `link.in` & `link.out`, if there is one.
+    fn delete_edge(&self, table: &'static str, link: Link) -> HelixFlowResult<()> {
+        self.rt
+            .block_on(
+                self.db
+                    .query(format!("DELETE {table} WHERE in = $in AND out = $out"))
+                    .bind(("in", link.r#in))
+                    .bind(("out", link.out))
+                    .into_future(),
+            )
+            .map_err(anyhow::Error::from)?
+            .check()
+            .map_err(anyhow::Error::from)?;
+        Ok(())
+    }
+
+    fn link_exists(&self, link: &Link) -> HelixFlowResult<bool> {
+        let mut response = self
+            .rt
+            .block_on(
+                self.db
+                    .query("SELECT in, out FROM contains WHERE in = $in AND out = $out")
+                    .bind(("in", link.r#in.clone()))
+                    .bind(("out", link.out.clone()))
+                    .into_future(),
+            )
+            .map_err(anyhow::Error::from)?;
+        let existing: Vec<Link> = response.take(0).map_err(anyhow::Error::from)?;
+        Ok(!existing.is_empty())
+    }
+}
+
+impl SurrealDb<Db> {
+    /// Merge the tasks, tasklists and their relationships from `file` (an export from another
+    /// HelixFlow instance) into this database, rather than replacing it.
+    ///
+    /// Records whose ids clash with different existing records are given new ids, and their
+    /// relationships follow them. As the original ids are not recorded, importing the same file
+    /// again will import such clashing records a second time.
+    ///
+    /// Records keyed by strings or numbers, e.g. from a SurrealDb database which HelixFlow did
+    /// not create, are stored with the ids given by `ItemId`. Their original keys are kept, see
+    /// `original_key`.
+    ///
+    /// UI `State` & `Settings` are per-instance and are not imported.
+    pub fn merge_import(&self, file: &Path) -> anyhow::Result<MergeReport> {
+        let staging = self.stage(file)?;
+
+        let tasklists: Vec<SurrealTaskList> = self
+            .rt
+            .block_on(staging.select("Tasklists").into_future())?;
+        let tasks: Vec<SurrealTask> = self.rt.block_on(staging.select("Tasks").into_future())?;
+        let links: Vec<ContainsLink> = self
+            .rt
+            .block_on(
+                staging
+                    .query("SELECT in, out, sortorder, created_at, created_by FROM contains")
+                    .into_future(),
+            )?
+            .take(0)?;
+
+        let mut report = MergeReport::default();
+        let mut new_ids: HashMap<Uuid, Uuid> = HashMap::new();
+        for tasklist in tasklists {
+            let original = foreign_key(&tasklist.id);
+            let tasklist: TaskList = tasklist.try_into()?;
+            let old_id = tasklist.id;
+            let new_id = self.merge_item(tasklist, |tl| &mut tl.id, &mut report)?;
+            new_ids.insert(old_id, new_id);
+            if let Some(original) = original {
+                self.record_alias(
+                    Thing::from(("Tasklists", Id::Uuid(new_id.into()))),
+                    original,
+                )?;
+            }
+        }
+        for task in tasks {
+            let original = foreign_key(&task.id);
+            let task: Task = task.try_into()?;
+            let old_id = task.id;
+            let new_id = self.merge_item(task, |t| &mut t.id, &mut report)?;
+            new_ids.insert(old_id, new_id);
+            if let Some(original) = original {
+                self.record_alias(Thing::from(("Tasks", Id::Uuid(new_id.into()))), original)?;
+            }
+        }
+
+        let remap = |thing: &Thing| item_id(thing).ok().and_then(|id| new_ids.get(&id).copied());
+        for link in links {
+            let (Some(parent_id), Some(child_id)) = (remap(&link.r#in), remap(&link.out)) else {
+                debug!("Skipping dangling link {:#?}", link);
+                continue;
+            };
+            // The parent is a tasklist, or a task for subtasks. The child is a task, or a
+            // tasklist nested in the parent.
+            let edge = Link {
+                r#in: Thing::from((link.r#in.tb.as_str(), Id::Uuid(parent_id.into()))),
+                out: Thing::from((link.out.tb.as_str(), Id::Uuid(child_id.into()))),
+            };
+            if self.link_exists(&edge)? {
+                continue;
+            }
+            let link = ContainsLink {
+                r#in: edge.r#in,
+                out: edge.out,
+                ..link
+            };
+            let _: Vec<ContainsLink> = self
+                .rt
+                .block_on(self.db.insert("contains").relation(link).into_future())?;
+            report.links += 1;
+        }
+        Ok(report)
+    }
+}
+
+impl<C> Drop for SurrealDb<C>
+where
+    C: Connection,
+{
+    fn drop(&mut self) {
+        if let Err(e) = self.flush() {
+            error!("Could not save changes on exit: {e:#}");
+        }
+    }
+}
+
+// Can't run blocking on wasm as `runtime::Builder::enable_all()` needs `time` AND
+// `block_on()` will not run either, as rt cannot be idle.
+#[cfg(test)]
+#[coverage(off)]
+mod tests {
+    use std::assert_matches::assert_matches;
+
+    use super::*;
+
+    use helixflow_core::{
+        CRUD, HelixFlowItem, Link as _, Linkable, graph::DotGraph, sorting::SortKey, table::Column,
+        transaction::atomically,
+    };
+    use rstest::*;
+
+    use tempfile::{NamedTempFile, TempPath, tempdir};
+
+    #[derive(Clone, Copy, Debug)]
+    enum BackendKind {
+        Mem,
+        File,
+    }
+
+    struct Backend {
+        _file_destructor: Option<TempPath>,
+        backend: SurrealDb<Db>,
+    }
+
+    impl From<BackendKind> for Backend {
+        fn from(kind: BackendKind) -> Self {
+            match kind {
+                BackendKind::Mem => Backend {
+                    _file_destructor: None,
+                    backend: SurrealDb::new(None).unwrap(),
+                },
+                BackendKind::File => {
+                    let tmpfile = NamedTempFile::new().unwrap();
+                    let location = tmpfile.path().into();
+                    let tmppath = tmpfile.into_temp_path();
+                    std::fs::remove_file(&location).unwrap();
+                    Backend {
+                        _file_destructor: Some(tmppath),
+                        backend: SurrealDb::new(Some(location)).unwrap(),
+                    }
+                }
+            }
+        }
+    }
+
+    #[rstest]
+    #[case(BackendKind::Mem)]
+    #[case(BackendKind::File)]
+    fn test_new_task(#[case] kind: BackendKind) {
+        let Backend {
+            _file_destructor,
+            backend,
+        } = kind.into();
+        let new_task = Task::new("Test Task 1", None);
+        backend.create(&new_task).unwrap();
+    }
+
+    #[rstest]
+    #[case(BackendKind::Mem)]
+    #[case(BackendKind::File)]
+    fn test_new_task_written_to_db(#[case] kind: BackendKind) {
+        let Backend {
+            _file_destructor,
+            backend,
+        } = kind.into();
+        let new_task = Task::new("Test Task 2", None);
+        backend.create(&new_task).unwrap();
+        let stored_task: Task = backend.get(&new_task.id).unwrap();
+        assert_eq!(stored_task, new_task);
+    }
+
+    #[rstest]
+    #[case(BackendKind::Mem)]
+    #[case(BackendKind::File)]
+    fn test_task_colour(#[case] kind: BackendKind) {
+        let Backend {
+            _file_destructor,
+            backend,
+        } = kind.into();
+        let mut new_task = Task::new("Colourful", None);
+        new_task.colour = Some(Colour::rgb(255, 128, 0));
+        backend.create(&new_task).unwrap();
+        let stored_task: Task = backend.get(&new_task.id).unwrap();
+        assert_eq!(stored_task, new_task);
+    }
+
+    #[rstest]
+    #[case(BackendKind::Mem)]
+    #[case(BackendKind::File)]
+    fn test_search(#[case] kind: BackendKind) {
+        let Backend {
+            _file_destructor,
+            backend,
+        } = kind.into();
+        let rent = Task::new("Pay rent", Some("Bank transfer to the landlord"));
+        let report = Task::new("Write report", Some("Quarterly figures"));
+        let bills = Task::new("Paying bills", None);
+        for task in [&rent, &report, &bills] {
+            backend.create(task).unwrap();
+        }
+        let names = |query| {
+            let mut names: Vec<String> = backend
+                .search(query)
+                .unwrap()
+                .map(|task| task.name.to_string())
+                .collect();
+            names.sort();
+            names
+        };
+        assert_eq!(names("RENT"), vec!["Pay rent"]);
+        assert_eq!(names("landlord"), vec!["Pay rent"]);
+        assert_eq!(names("pay"), vec!["Pay rent", "Paying bills"]);
+        assert_eq!(names("rep"), vec!["Write report"]);
+        assert_eq!(names("holiday"), Vec::<String>::new());
+        assert_eq!(names(""), Vec::<String>::new());
+    }
+
+    #[rstest]
+    #[case(BackendKind::Mem)]
+    #[case(BackendKind::File)]
+    fn test_query(#[case] kind: BackendKind) {
+        use chrono::TimeZone;
+
+        let Backend {
+            _file_destructor,
+            backend,
+        } = kind.into();
+        let june = |day| {
+            chrono::Utc
+                .with_ymd_and_hms(2025, 6, day, 12, 0, 0)
+                .unwrap()
+        };
+        let this_week = TaskList::new("This week");
+        backend.create(&this_week).unwrap();
+        let home = Tag::new("home");
+        let rent = Task {
+            due: Some(june(5)),
+            priority: Priority::P1,
+            ..Task::new("Pay rent", None)
+        };
+        let report = Task {
+            due: Some(june(12)),
+            priority: Priority::P0,
+            ..Task::new("Write report", None)
+        };
+        let mut shopping = Task::new("Shopping", None);
+        shopping.transition(Status::Done).unwrap();
+        let elsewhere = Task {
+            due: Some(june(1)),
+            ..Task::new("Not this week", None)
+        };
+        for task in [&rent, &report, &shopping] {
+            this_week.link(task).create_linked_item(&backend).unwrap();
+        }
+        backend.create(&elsewhere).unwrap();
+        rent.tag(&home).create_linked_item(&backend).unwrap();
+        shopping.tag(&home).create_linked_item(&backend).unwrap();
+
+        let names = |query: TaskQuery| -> Vec<String> {
+            backend
+                .query(&query)
+                .unwrap()
+                .map(|task| task.name.to_string())
+                .collect()
+        };
+        assert_eq!(
+            names(TaskQuery::default()),
+            vec!["Pay rent", "Write report", "Shopping", "Not this week"]
+        );
+        assert_eq!(
+            names(
+                TaskQuery::default()
+                    .in_list(&this_week)
+                    .status(Status::Todo)
+            ),
+            vec!["Pay rent", "Write report"]
+        );
+        assert_eq!(
+            names(TaskQuery::default().tagged(&home)),
+            vec!["Pay rent", "Shopping"]
+        );
+        assert_eq!(
+            names(TaskQuery::default().due_from(june(5)).due_before(june(12))),
+            vec!["Pay rent"]
+        );
+        assert_eq!(
+            names(
+                TaskQuery::default()
+                    .in_list(&this_week)
+                    .sort_by(SortKey::Priority)
+            ),
+            vec!["Write report", "Pay rent", "Shopping"]
+        );
+        assert_eq!(
+            names(TaskQuery::default().sort_by(SortKey::DueDate).limit(3)),
+            vec!["Not this week", "Pay rent", "Write report"]
+        );
+        assert_eq!(
+            names(
+                TaskQuery::default()
+                    .sort_by(SortKey::OverdueFirst)
+                    .sort_by(SortKey::Name)
+                    .limit(2)
+            ),
+            vec!["Not this week", "Pay rent"]
+        );
+    }
+
+    #[rstest]
+    #[case(BackendKind::Mem)]
+    #[case(BackendKind::File)]
+    fn test_create_linked_item_atomically(#[case] kind: BackendKind) {
+        let Backend {
+            _file_destructor,
+            backend,
+        } = kind.into();
+        let backlog = TaskList::new("Backlog");
+        backend.create(&backlog).unwrap();
+        // Make storing the edge fail, after the task has been created
+        backend
+            .rt
+            .block_on(
+                backend
+                    .db
+                    .query("DEFINE FIELD sortorder ON contains ASSERT $value != 'zz'")
+                    .into_future(),
+            )
+            .unwrap()
+            .check()
+            .unwrap();
+        let task = Task::new("Orphan", None);
+        let mut link = backlog.link(&task);
+        link.sortorder = "zz".into();
+        assert!(link.create_linked_item(&backend).is_err());
+        assert_matches!(
+            Task::get(&backend, &task.id),
+            Err(HelixFlowError::NotFound { .. })
+        );
+        assert!(
+            History::<Task>::history(&backend, &task.id)
+                .unwrap()
+                .is_empty()
+        );
+
+        backlog.link(&task).create_linked_item(&backend).unwrap();
+        assert_eq!(
+            backlog.tasks_page(&backend, Page::first(1)).unwrap(),
+            [task]
+        );
+    }
+
+    #[rstest]
+    #[case(BackendKind::Mem)]
+    #[case(BackendKind::File)]
+    fn test_rollback(#[case] kind: BackendKind) {
+        let Backend {
+            _file_destructor,
+            backend,
+        } = kind.into();
+        let backlog = TaskList::new("Backlog");
+        backend.create(&backlog).unwrap();
+        let kept = Task::new("Kept", None);
+        atomically(&backend, || {
+            backlog.link(&kept).create_linked_item(&backend)
+        })
+        .unwrap();
+        let lost = Task::new("Lost", None);
+        let failed = atomically(&backend, || {
+            backlog.link(&lost).create_linked_item(&backend)?;
+            // Already stored
+            kept.create(&backend)
+        });
+        assert!(failed.is_err());
+        assert_matches!(
+            Task::get(&backend, &lost.id),
+            Err(HelixFlowError::NotFound { .. })
+        );
+        assert_eq!(
+            backend.backlog(&backlog, true).unwrap(),
+            vec![Task::get(&backend, &kept.id).unwrap()]
+        );
+    }
+
+    #[rstest]
+    #[case(BackendKind::Mem)]
+    #[case(BackendKind::File)]
+    fn test_pages(#[case] kind: BackendKind) {
+        let Backend {
+            _file_destructor,
+            backend,
+        } = kind.into();
+        let backlog = TaskList::new("Backlog");
+        backend.create(&backlog).unwrap();
+        let tasks: Vec<Task> = (1..=5)
+            .map(|n| Task::new(format!("Task {n}"), None))
+            .collect();
+        for task in &tasks {
+            backlog.link(task).create_linked_item(&backend).unwrap();
+        }
+        let page = Page::first(2);
+        assert_eq!(backlog.tasks_page(&backend, page).unwrap(), tasks[0..2]);
+        assert_eq!(
+            backlog.tasks_page(&backend, page.next()).unwrap(),
+            tasks[2..4]
+        );
+        assert_eq!(
+            backlog.tasks_page(&backend, page.next().next()).unwrap(),
+            tasks[4..]
+        );
+        let all: Vec<Task> = backlog
+            .get_linked_items(&backend)
+            .unwrap()
+            .map(|contains| contains.right.unwrap())
+            .collect();
+        assert_eq!(all, tasks);
+
+        // Fetched a page at a time
+        let streamed: Vec<Task> = Pages::new(&backend, &backlog, 2, |e| panic!("{e}"))
+            .unwrap()
+            .map(|contains: Contains<TaskList, Task>| contains.right.unwrap())
+            .collect();
+        assert_eq!(streamed, tasks);
+    }
+
+    #[rstest]
+    #[case(BackendKind::Mem)]
+    #[case(BackendKind::File)]
+    fn test_completed_tasks(#[case] kind: BackendKind) {
+        let Backend {
+            _file_destructor,
+            backend,
+        } = kind.into();
+        let backlog = TaskList::new("Backlog");
+        backend.create(&backlog).unwrap();
+        let open = Task::new("Still to do", None);
+        let mut done = Task::new("Already done", None);
+        backlog.link(&done).create_linked_item(&backend).unwrap();
+        backlog.link(&open).create_linked_item(&backend).unwrap();
+
+        done.complete().unwrap();
+        done.update(&backend).unwrap();
+        let stored = Task::get(&backend, &done.id).unwrap();
+        assert_eq!(stored.completed_at, done.completed_at);
+        assert!(stored.completed_at.is_some());
+
+        assert_eq!(
+            backend.backlog(&backlog, false).unwrap(),
+            vec![open.clone()]
+        );
+        assert_eq!(
+            backend.backlog(&backlog, true).unwrap(),
+            vec![done.clone(), open.clone()]
+        );
+
+        done.reopen().unwrap();
+        done.update(&backend).unwrap();
+        assert_eq!(backend.backlog(&backlog, false).unwrap(), vec![done, open]);
+    }
+
+    #[rstest]
+    #[case(BackendKind::Mem)]
+    #[case(BackendKind::File)]
+    fn test_updated_at(#[case] kind: BackendKind) {
+        let Backend {
+            _file_destructor,
+            backend,
+        } = kind.into();
+        let backlog = TaskList::new("Backlog");
+        backend.create(&backlog).unwrap();
+        let older = Task::new("Older", None);
+        let newer = Task::new("Newer", None);
+        backlog.link(&older).create_linked_item(&backend).unwrap();
+        backlog.link(&newer).create_linked_item(&backend).unwrap();
+        let created = Task::get(&backend, &older.id)
+            .unwrap()
+            .updated_at()
+            .unwrap();
+        assert!(created >= older.created_at().unwrap());
+
+        older.update(&backend).unwrap();
+        let updated = Task::get(&backend, &older.id)
+            .unwrap()
+            .updated_at()
+            .unwrap();
+        assert!(updated > created);
+        let recent: Vec<String> = backend
+            .query(&TaskQuery::default().sort_by(SortKey::RecentlyUpdated))
+            .unwrap()
+            .map(|task| task.name.to_string())
+            .collect();
+        assert_eq!(recent, vec!["Older", "Newer"]);
+    }
+
+    #[rstest]
+    #[case(BackendKind::Mem)]
+    #[case(BackendKind::File)]
+    fn test_reorder(#[case] kind: BackendKind) {
+        let Backend {
+            _file_destructor,
+            backend,
+        } = kind.into();
+        let backlog = TaskList::new("Backlog");
+        backend.create(&backlog).unwrap();
+        let first = Task::new("First", None);
+        let second = Task::new("Second", None);
+        let third = Task::new("Third", None);
+        for task in [&first, &second, &third] {
+            backlog.link(task).create_linked_item(&backend).unwrap();
+        }
+        let names = || -> Vec<String> {
+            backlog
+                .get_linked_items(&backend)
+                .unwrap()
+                .map(|contains| contains.right.unwrap().name.to_string())
+                .collect()
+        };
+        assert_eq!(names(), vec!["First", "Second", "Third"]);
+
+        let siblings: Vec<_> = backlog.get_linked_items(&backend).unwrap().collect();
+        let moved = backlog
+            .link(&third)
+            .insert_before(&first, &siblings)
+            .unwrap();
+        backend.create_linked_item(&moved).unwrap();
+        assert_eq!(names(), vec!["Third", "First", "Second"]);
+
+        let siblings: Vec<_> = backlog.get_linked_items(&backend).unwrap().collect();
+        let new = Task::new("New", None);
+        let inserted = backlog.link(&new).insert_after(&third, &siblings).unwrap();
+        backend.create_linked_item(&inserted).unwrap();
+        assert_eq!(names(), vec!["Third", "New", "First", "Second"]);
+    }
+
+    #[rstest]
+    #[case(BackendKind::Mem)]
+    #[case(BackendKind::File)]
+    fn test_unlink(#[case] kind: BackendKind) {
+        let Backend {
+            _file_destructor,
+            backend,
+        } = kind.into();
+        let backlog = TaskList::new("Backlog");
+        backend.create(&backlog).unwrap();
+        let keep = Task::new("Keep", None);
+        let remove = Task::new("Remove", None);
+        backlog.link(&keep).create_linked_item(&backend).unwrap();
+        backlog.link(&remove).create_linked_item(&backend).unwrap();
+
+        backlog.unlink(&remove, &backend).unwrap();
+        let tasks: Vec<Task> = backlog
+            .get_linked_items(&backend)
+            .unwrap()
+            .map(|contains| contains.right.unwrap())
+            .collect();
+        assert_eq!(tasks, vec![keep]);
+        // The task itself is not deleted
+        assert_eq!(Task::get(&backend, &remove.id).unwrap(), remove);
+        // Unlinking again is fine
+        backlog.unlink(&remove, &backend).unwrap();
+    }
+
+    #[rstest]
+    #[case(BackendKind::Mem)]
+    #[case(BackendKind::File)]
+    fn test_link_metadata(#[case] kind: BackendKind) {
+        let Backend {
+            _file_destructor,
+            backend,
+        } = kind.into();
+        let backlog = TaskList::new("Backlog");
+        backend.create(&backlog).unwrap();
+        let alice = Actor::new("Alice", &Uuid::now_v7());
+        let before = chrono::Utc::now();
+        backlog
+            .link(&Task::new("Added by Alice", None))
+            .by(&alice)
+            .create_linked_item(&backend)
+            .unwrap();
+        backlog
+            .link(&Task::new("Added anonymously", None))
+            .create_linked_item(&backend)
+            .unwrap();
+        let links: Vec<Contains<TaskList, Task>> =
+            backlog.get_linked_items(&backend).unwrap().collect();
+        assert_eq!(links[0].created_by, Some(alice));
+        assert_eq!(links[1].created_by, None);
+        assert!(links.iter().all(|link| link.created_at.unwrap() >= before));
+    }
+
+    #[rstest]
+    #[case(BackendKind::Mem)]
+    #[case(BackendKind::File)]
+    fn test_history(#[case] kind: BackendKind) {
+        let Backend {
+            _file_destructor,
+            backend,
+        } = kind.into();
+        let alice = Actor::new("Alice", &Uuid::now_v7());
+        backend.act_as(&alice);
+        let task = Task::new("Write history", None);
+        backend.create(&task).unwrap();
+        let described = Task {
+            description: Some("Who did what, when".into()),
+            ..task.clone()
+        };
+        backend.update(&described).unwrap();
+        Store::<Task>::delete(&backend, &task.id).unwrap();
+
+        let history = task.history(&backend).unwrap();
+        let kinds: Vec<ChangeKind> = history.iter().map(|change| change.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ChangeKind::Created,
+                ChangeKind::Updated,
+                ChangeKind::Deleted
+            ]
+        );
+        assert_eq!(history[0].before, None);
+        assert_eq!(history[0].after, Some(task.clone()));
+        assert_eq!(history[1].before, Some(task));
+        assert_eq!(history[1].after, Some(described.clone()));
+        assert_eq!(history[2].before, Some(described));
+        assert_eq!(history[2].after, None);
+        assert!(
+            history
+                .iter()
+                .all(|change| change.by == Some(alice.clone()))
+        );
+        assert!(history.is_sorted_by_key(|change| change.at));
+    }
+
+    #[rstest]
+    #[case(BackendKind::Mem)]
+    #[case(BackendKind::File)]
+    fn test_task_estimate(#[case] kind: BackendKind) {
+        let Backend {
+            _file_destructor,
+            backend,
+        } = kind.into();
+        let mut new_task = Task::new("Estimated", None);
+        new_task.estimate = Some(std::time::Duration::from_secs(90 * 60));
+        new_task.actual = Some(std::time::Duration::from_secs(2 * 60 * 60));
+        backend.create(&new_task).unwrap();
+        let stored_task: Task = backend.get(&new_task.id).unwrap();
+        assert_eq!(stored_task, new_task);
+    }
+
+    #[rstest]
+    #[case(BackendKind::Mem)]
+    #[case(BackendKind::File)]
+    fn test_tasklist_sort(#[case] kind: BackendKind) {
+        let Backend {
+            _file_destructor,
+            backend,
+        } = kind.into();
+        let mut tasklist = TaskList::new("Sorted");
+        tasklist.sort = SortRule(vec![SortKey::OverdueFirst, SortKey::Name]);
+        backend.create(&tasklist).unwrap();
+        let stored_tasklist: TaskList = backend.get(&tasklist.id).unwrap();
+        assert_eq!(stored_tasklist, tasklist);
+    }
+
+    #[rstest]
+    #[case(BackendKind::Mem)]
+    #[case(BackendKind::File)]
+    fn test_task_status(#[case] kind: BackendKind) {
+        let Backend {
+            _file_destructor,
+            backend,
+        } = kind.into();
+        let mut new_task = Task::new("In progress", None);
+        new_task.transition(Status::InProgress).unwrap();
+        backend.create(&new_task).unwrap();
+        let stored_task: Task = backend.get(&new_task.id).unwrap();
+        assert_eq!(stored_task.status, Status::InProgress);
+    }
+
+    #[rstest]
+    #[case(BackendKind::Mem)]
+    #[case(BackendKind::File)]
+    fn test_tasks_by_priority(#[case] kind: BackendKind) {
+        let Backend {
+            _file_destructor,
+            backend,
+        } = kind.into();
+        let mut tasklist = TaskList::new("Most important first");
+        tasklist.sort = SortRule(vec![SortKey::Priority]);
+        backend.create(&tasklist).unwrap();
+        for (name, priority) in [
+            ("Someday", Priority::P3),
+            ("Urgent", Priority::P0),
+            ("Normal", Priority::P2),
+        ] {
+            let task = Task {
+                priority,
+                ..Task::new(name, None)
+            };
+            tasklist.link(&task).create_linked_item(&backend).unwrap();
+        }
+        let tasks = tasklist
+            .sorted_tasks(&backend, chrono::Utc::now(), chrono_tz::UTC)
+            .unwrap();
+        assert_eq!(
+            tasks
+                .iter()
+                .map(|task| (task.name.as_ref(), task.priority))
+                .collect::<Vec<_>>(),
+            vec![
+                ("Urgent", Priority::P0),
+                ("Normal", Priority::P2),
+                ("Someday", Priority::P3)
+            ]
+        );
+    }
+
+    #[rstest]
+    #[case(BackendKind::Mem)]
+    #[case(BackendKind::File)]
+    fn test_tags(#[case] kind: BackendKind) {
+        let Backend {
+            _file_destructor,
+            backend,
+        } = kind.into();
+        let home = Tag::new("home");
+        let work = Tag::new("work");
+        let rent = Task::new("Pay rent", None);
+        let report = Task::new("Write report", None);
+        let untagged = Task::new("Untagged", None);
+        for task in [&rent, &report, &untagged] {
+            backend.create(task).unwrap();
+        }
+        rent.tag(&home).create_linked_item(&backend).unwrap();
+        report.tag(&home).create_linked_item(&backend).unwrap();
+        report.tag(&work).create_linked_item(&backend).unwrap();
+
+        let stored_home: Tag = backend.get(&home.id).unwrap();
+        assert_eq!(stored_home, home);
+        let mut tagged_home = home.tasks(&backend).unwrap();
+        tagged_home.sort_by_key(|task| task.id);
+        assert_eq!(tagged_home, vec![rent, report.clone()]);
+        let mut report_tags = report.tags(&backend).unwrap();
+        report_tags.sort_by_key(|tag| tag.id);
+        assert_eq!(report_tags, vec![home, work.clone()]);
+        assert_eq!(untagged.tags(&backend).unwrap(), vec![]);
+
+        Store::<Tag>::delete(&backend, &work.id).unwrap();
+        assert_eq!(report.tags(&backend).unwrap().len(), 1);
+    }
+
+    #[rstest]
+    #[case(BackendKind::Mem)]
+    #[case(BackendKind::File)]
+    fn test_dot_graph(#[case] kind: BackendKind) {
+        let Backend {
+            _file_destructor,
+            backend,
+        } = kind.into();
+        let tasklist = TaskList::new("Backlog");
+        let task = Task::new("Pay rent", None);
+        let tag = Tag::new("home");
+        backend.create(&tasklist).unwrap();
+        tasklist.link(&task).create_linked_item(&backend).unwrap();
+        task.tag(&tag).create_linked_item(&backend).unwrap();
+        let dot = DotGraph::of(&[tasklist.clone()], &backend)
+            .unwrap()
+            .to_dot();
+        assert_eq!(
+            dot,
+            format!(
+                "digraph helixflow {{\n    \
+                \"{list}\" [label=\"Backlog\", shape=folder];\n    \
+                \"{task}\" [label=\"Pay rent\", shape=box];\n    \
+                \"{tag}\" [label=\"#home\", shape=ellipse];\n    \
+                \"{list}\" -> \"{task}\" [label=\"contains\"];\n    \
+                \"{task}\" -> \"{tag}\" [label=\"tagged\"];\n\
+                }}\n",
+                list = tasklist.id,
+                task = task.id,
+                tag = tag.id,
+            )
+        );
+    }
+
+    #[rstest]
+    #[case(BackendKind::Mem)]
+    #[case(BackendKind::File)]
+    fn test_tag_missing_task(#[case] kind: BackendKind) {
+        let Backend {
+            _file_destructor,
+            backend,
+        } = kind.into();
+        let task = Task::new("Not stored", None);
+        let err = task
+            .tag(&Tag::new("home"))
+            .create_linked_item(&backend)
+            .unwrap_err();
+        assert_matches!(err, HelixFlowError::NotFound { id, .. } if id == task.id);
+    }
+
+    #[rstest]
+    #[case(BackendKind::Mem)]
+    #[case(BackendKind::File)]
+    fn test_dependencies(#[case] kind: BackendKind) {
+        let Backend {
+            _file_destructor,
+            backend,
+        } = kind.into();
+        let design = Task::new("Design", None);
+        let build = Task::new("Build", None);
+        let release = Task::new("Release", None);
+        for task in [&design, &build, &release] {
+            backend.create(task).unwrap();
+        }
+        build
+            .depends_on(&design)
+            .create_linked_item(&backend)
+            .unwrap();
+        release
+            .depends_on(&build)
+            .create_linked_item(&backend)
+            .unwrap();
+        assert_eq!(release.dependencies(&backend).unwrap(), vec![build.clone()]);
+
+        let err = design
+            .depends_on(&release)
+            .create_linked_item(&backend)
+            .unwrap_err();
+        assert_matches!(
+            err,
+            HelixFlowError::DependencyCycle { cycle }
+                if cycle == vec![design.id, release.id, build.id, design.id]
+        );
+        assert_eq!(design.dependencies(&backend).unwrap(), vec![]);
+
+        Store::<Task>::delete(&backend, &build.id).unwrap();
+        assert_eq!(release.dependencies(&backend).unwrap(), vec![]);
+    }
+
+    #[rstest]
+    #[case(BackendKind::Mem)]
+    #[case(BackendKind::File)]
+    fn test_worklogs(#[case] kind: BackendKind) {
+        use chrono::{TimeDelta, TimeZone, Utc};
+
+        let Backend {
+            _file_destructor,
+            backend,
+        } = kind.into();
+        let tasklist = TaskList::new("Backlog");
+        let report = Task::new("Write report", None);
+        let review = Task::new("Review report", None);
+        backend.create(&tasklist).unwrap();
+        tasklist.link(&report).create_linked_item(&backend).unwrap();
+        tasklist.link(&review).create_linked_item(&backend).unwrap();
+        let start = Utc.with_ymd_and_hms(2025, 6, 2, 9, 0, 0).unwrap();
+        let drafting =
+            WorkLog::new(start, start + TimeDelta::hours(2), Some("First draft")).unwrap();
+        let editing = WorkLog::new(
+            start + TimeDelta::hours(3),
+            start + TimeDelta::minutes(200),
+            None::<&str>,
+        )
+        .unwrap();
+        let reading = WorkLog::new(start, start + TimeDelta::minutes(30), None::<&str>).unwrap();
+        report.log(&drafting).create_linked_item(&backend).unwrap();
+        report.log(&editing).create_linked_item(&backend).unwrap();
+        review.log(&reading).create_linked_item(&backend).unwrap();
+
+        let stored: WorkLog = backend.get(&drafting.id).unwrap();
+        assert_eq!(stored, drafting);
+        assert_eq!(
+            report.time_logged(&backend).unwrap(),
+            TimeDelta::minutes(140)
+        );
+        assert_eq!(
+            tasklist.time_logged(&backend).unwrap(),
+            TimeDelta::minutes(170)
+        );
+
+        Store::<WorkLog>::delete(&backend, &editing.id).unwrap();
+        assert_eq!(report.worklogs(&backend).unwrap(), vec![drafting]);
+    }
+
+    #[rstest]
+    #[case(BackendKind::Mem)]
+    #[case(BackendKind::File)]
+    fn test_attachments(#[case] kind: BackendKind) {
+        let Backend {
+            _file_destructor,
+            backend,
+        } = kind.into();
+        let task = Task::new("File taxes", None);
+        backend.create(&task).unwrap();
+        let receipt = Attachment::new(
+            "receipt.png",
+            "image/png",
+            AttachmentContent::Blob(vec![0x89, b'P', b'N', b'G', 0, 255]),
+        );
+        let statement = Attachment::link_to(Path::new("/shared/statement.pdf"));
+        task.attach(&receipt).create_linked_item(&backend).unwrap();
+        task.attach(&statement)
+            .create_linked_item(&backend)
+            .unwrap();
+
+        let stored: Attachment = backend.get(&receipt.id).unwrap();
+        assert_eq!(stored, receipt);
+        let mut attachments = task.attachments(&backend).unwrap();
+        attachments.sort_by_key(|attachment| attachment.id);
+        assert_eq!(attachments, vec![receipt, statement]);
+
+        Store::<Task>::delete(&backend, &task.id).unwrap();
+        assert_eq!(task.attachments(&backend).unwrap(), vec![]);
+    }
+
+    #[rstest]
+    #[case(BackendKind::Mem)]
+    #[case(BackendKind::File)]
+    fn test_projects(#[case] kind: BackendKind) {
+        let Backend {
+            _file_destructor,
+            backend,
+        } = kind.into();
+        let work = Project::new("Work", Some("Day job"));
+        let home = Project::new("Home", None::<&str>);
+        backend.create(&work).unwrap();
+        backend.create(&home).unwrap();
+        let backlog = TaskList::new("Backlog");
+        backend.create(&backlog).unwrap();
+        let sprint = TaskList::new("Sprint 1");
+        work.link(&backlog).create_linked_item(&backend).unwrap();
+        work.link(&sprint).create_linked_item(&backend).unwrap();
+
+        assert_eq!(
+            backend.all_projects().unwrap(),
+            vec![work.clone(), home.clone()]
+        );
+        let stored_sprint: TaskList = backend.get(&sprint.id).unwrap();
+        assert_eq!(stored_sprint, sprint);
+        let mut tasklists = work.tasklists(&backend).unwrap();
+        tasklists.sort_by_key(|tasklist| tasklist.id);
+        assert_eq!(tasklists, vec![backlog, sprint]);
+        assert_eq!(home.tasklists(&backend).unwrap(), vec![]);
+    }
+
+    #[rstest]
+    #[case(BackendKind::Mem)]
+    #[case(BackendKind::File)]
+    fn test_assignees(#[case] kind: BackendKind) {
+        let Backend {
+            _file_destructor,
+            backend,
+        } = kind.into();
+        let task = Task::new("Fix the roof", None);
+        let unassigned = Task::new("Someday", None);
+        backend.create(&task).unwrap();
+        backend.create(&unassigned).unwrap();
+        let alice = Person::new("Alice", Some("alice@example.com"));
+        let bob = Person::new("Bob", None::<&str>);
+
+        task.assign_to(&alice).create_linked_item(&backend).unwrap();
+        let stored: Person = backend.get(&alice.id).unwrap();
+        assert_eq!(stored, alice);
+        assert_eq!(task.assignee(&backend).unwrap(), Some(alice.clone()));
+        assert_eq!(unassigned.assignee(&backend).unwrap(), None);
+
+        // Reassigning replaces the previous assignee
+        task.assign_to(&bob).create_linked_item(&backend).unwrap();
+        assert_eq!(task.assignee(&backend).unwrap(), Some(bob.clone()));
+        assert_eq!(backend.all_people().unwrap(), vec![alice, bob.clone()]);
+        assert_eq!(backend.person_named("Bob").unwrap(), Some(bob.clone()));
+
+        Store::<Person>::delete(&backend, &bob.id).unwrap();
+        assert_eq!(task.assignee(&backend).unwrap(), None);
+    }
+
+    #[rstest]
+    #[case(BackendKind::Mem)]
+    #[case(BackendKind::File)]
+    fn test_pinned_first(#[case] kind: BackendKind) {
+        let Backend {
+            _file_destructor,
+            backend,
+        } = kind.into();
+        let tasklist = TaskList::new("Backlog");
+        backend.create(&tasklist).unwrap();
+        let mut tasks: Vec<Task> = (1..=4)
+            .map(|n| Task::new(format!("Task {n}"), None))
+            .collect();
+        for task in &tasks {
+            tasklist.link(task).create_linked_item(&backend).unwrap();
+        }
+        tasks[1].pin();
+        tasks[3].pin();
+        tasks[1].update(&backend).unwrap();
+        tasks[3].update(&backend).unwrap();
+        assert!(Task::get(&backend, &tasks[3].id).unwrap().pinned);
+
+        let names = |tasks: Vec<Task>| -> Vec<String> {
+            tasks
+                .into_iter()
+                .map(|task| task.name.to_string())
+                .collect()
+        };
+        assert_eq!(
+            names(backend.backlog_pinned_first(&tasklist, false).unwrap()),
+            vec!["Task 2", "Task 4", "Task 1", "Task 3"]
+        );
+        assert_eq!(
+            names(backend.backlog(&tasklist, false).unwrap()),
+            vec!["Task 1", "Task 2", "Task 3", "Task 4"]
+        );
+    }
+
+    #[rstest]
+    #[case(BackendKind::Mem)]
+    #[case(BackendKind::File)]
+    fn test_contexts(#[case] kind: BackendKind) {
+        use helixflow_core::{contexts::Context, query::TaskQuery};
+
+        let Backend {
+            _file_destructor,
+            backend,
+        } = kind.into();
+        let tasklist = TaskList::new("Backlog");
+        let stamps = Task::new("Buy stamps", None);
+        let email = Task::new("Answer email", None);
+        backend.create(&tasklist).unwrap();
+        tasklist.link(&stamps).create_linked_item(&backend).unwrap();
+        tasklist.link(&email).create_linked_item(&backend).unwrap();
+        let errands = Context::new("@errands");
+        let computer = Context::new("@computer");
+        stamps
+            .add_context(&errands)
+            .create_linked_item(&backend)
+            .unwrap();
+        email
+            .add_context(&computer)
+            .create_linked_item(&backend)
+            .unwrap();
+        // Existing contexts are reused
+        email
+            .add_context(&errands)
+            .create_linked_item(&backend)
+            .unwrap();
+
+        assert_eq!(
+            backend.all_contexts().unwrap(),
+            vec![errands.clone(), computer.clone()]
+        );
+        let mut email_contexts = email.contexts(&backend).unwrap();
+        email_contexts.sort_by_key(|context| context.id);
+        assert_eq!(email_contexts, vec![errands.clone(), computer.clone()]);
+        let at_computer: Vec<Task> = backend
+            .query(&TaskQuery::default().in_context(&computer))
+            .unwrap()
+            .collect();
+        assert_eq!(at_computer, vec![email.clone()]);
+
+        email.remove_context(&errands, &backend).unwrap();
+        let errands_tasks: Vec<Task> = backend
+            .query(&TaskQuery::default().in_context(&errands))
+            .unwrap()
+            .collect();
+        assert_eq!(errands_tasks, vec![stamps]);
+    }
+
+    #[rstest]
+    #[case(BackendKind::Mem)]
+    #[case(BackendKind::File)]
+    fn test_inbox(#[case] kind: BackendKind) {
+        let Backend {
+            _file_destructor,
+            backend,
+        } = kind.into();
+        let task = Task::new("Call the plumber", None);
+        task.capture(&backend).unwrap();
+        let inbox = TaskList::inbox(&backend).unwrap();
+        assert!(inbox.is_inbox());
+        assert_eq!(backend.backlog(&inbox, true).unwrap(), vec![task]);
+    }
+
+    #[rstest]
+    #[case(BackendKind::Mem)]
+    #[case(BackendKind::File)]
+    fn test_merge_duplicates(#[case] kind: BackendKind) {
+        let Backend {
+            _file_destructor,
+            backend,
+        } = kind.into();
+        let tasklist = TaskList::new("Calls");
+        backend.create(&tasklist).unwrap();
+        let mut task = Task::new("Call Mum", None);
+        let duplicate = Task::new("call mum", Some("Birthday"));
+        let unrelated = Task::new("Call Dad", None);
+        for t in [&task, &duplicate, &unrelated] {
+            tasklist.link(t).create_linked_item(&backend).unwrap();
+        }
+        let phone = Tag::new("phone");
+        let home = contexts::Context::new("@home");
+        duplicate.tag(&phone).create_linked_item(&backend).unwrap();
+        task.tag(&phone).create_linked_item(&backend).unwrap();
+        duplicate
+            .add_context(&home)
+            .create_linked_item(&backend)
+            .unwrap();
+        let checklist = Task::new("Ask about presents", None);
+        duplicate
+            .subtask(&checklist)
+            .create_linked_item(&backend)
+            .unwrap();
+
+        let groups = tasklist.duplicates(&backend).unwrap();
+        assert_eq!(groups, vec![vec![task.clone(), duplicate.clone()]]);
+
+        task.merge(&duplicate, &backend).unwrap();
+        assert_eq!(Task::get(&backend, &task.id).unwrap(), task);
+        assert_eq!(task.description.as_deref(), Some("Birthday"));
+        assert_eq!(task.tags(&backend).unwrap(), vec![phone]);
+        assert_eq!(task.contexts(&backend).unwrap(), vec![home]);
+        let subtasks = task.subtasks(&backend).unwrap();
+        assert_eq!(subtasks.len(), 1);
+        assert_eq!(subtasks[0].name, checklist.name);
+        assert_matches!(
+            Task::get(&backend, &duplicate.id),
+            Err(HelixFlowError::NotFound { .. })
+        );
+        assert_matches!(
+            Task::get(&backend, &checklist.id),
+            Err(HelixFlowError::NotFound { .. })
+        );
+        assert!(tasklist.duplicates(&backend).unwrap().is_empty());
+        assert_eq!(
+            tasklist
+                .get_linked_items(&backend)
+                .unwrap()
+                .map(|contains| contains.right.unwrap().name)
+                .collect::<Vec<_>>(),
+            vec!["Call Mum", "Call Dad"]
+        );
+    }
+
+    #[rstest]
+    #[case(BackendKind::Mem)]
+    #[case(BackendKind::File)]
+    fn test_burndown(#[case] kind: BackendKind) {
+        use chrono::{NaiveDate, TimeZone, Utc};
+
+        let Backend {
+            _file_destructor,
+            backend,
+        } = kind.into();
+        let sprint = TaskList::new("Sprint");
+        let other = TaskList::new("Other");
+        backend.create(&sprint).unwrap();
+        backend.create(&other).unwrap();
+        let done_on = |name, day| Task {
+            status: Status::Done,
+            completed_at: Some(Utc.with_ymd_and_hms(2025, 6, day, 12, 0, 0).unwrap()),
+            ..Task::new(name, None)
+        };
+        for task in [
+            done_on("Plan", 2),
+            done_on("Build", 3),
+            done_on("Test", 3),
+            Task::new("Release", None),
+        ] {
+            sprint.link(&task).create_linked_item(&backend).unwrap();
+        }
+        other
+            .link(&done_on("Elsewhere", 2))
+            .create_linked_item(&backend)
+            .unwrap();
+
+        let june = |day| NaiveDate::from_ymd_opt(2025, 6, day).unwrap();
+        let series = sprint
+            .burndown(&backend, june(1)..=june(4), chrono_tz::Tz::UTC)
+            .unwrap();
+        assert_eq!(
+            series
+                .iter()
+                .map(|point| (point.total, point.remaining()))
+                .collect::<Vec<_>>(),
+            vec![(4, 4), (4, 3), (4, 1), (4, 1)]
+        );
+        let empty = TaskList::new("Empty");
+        backend.create(&empty).unwrap();
+        let series = empty
+            .burndown(&backend, june(1)..=june(2), chrono_tz::Tz::UTC)
+            .unwrap();
+        assert!(series.iter().all(|point| point.total == 0));
+    }
+
+    #[rstest]
+    #[case(BackendKind::Mem)]
+    #[case(BackendKind::File)]
+    fn test_trash(#[case] kind: BackendKind) {
+        use chrono::{TimeDelta, TimeZone, Utc};
+        use helixflow_core::trash::{RETENTION, expire, restore};
+
+        let Backend {
+            _file_destructor,
+            backend,
+        } = kind.into();
+        let tasklist = TaskList::new("Backlog");
+        backend.create(&tasklist).unwrap();
+        let old = Task::new("Old", None);
+        let recent = Task::new("Recent", None);
+        let kept = Task::new("Kept", None);
+        for task in [&old, &recent, &kept] {
+            tasklist.link(task).create_linked_item(&backend).unwrap();
+        }
+        let monday = Utc.with_ymd_and_hms(2025, 6, 2, 9, 0, 0).unwrap();
+        old.trash(Some(&tasklist), &backend, monday).unwrap();
+        recent
+            .trash(Some(&tasklist), &backend, monday + TimeDelta::days(7))
+            .unwrap();
+        assert_matches!(
+            Task::get(&backend, &old.id),
+            Err(HelixFlowError::NotFound { .. })
+        );
+        let trashed: Vec<Task> = backend
+            .trashed()
+            .unwrap()
+            .into_iter()
+            .map(|trashed| trashed.task)
+            .collect();
+        assert_eq!(trashed, vec![recent.clone(), old.clone()]);
+
+        assert_eq!(restore(&backend, &recent.id).unwrap(), recent);
+        assert_eq!(
+            tasklist
+                .get_linked_items(&backend)
+                .unwrap()
+                .map(|contains| contains.right.unwrap().name)
+                .collect::<Vec<_>>(),
+            vec!["Kept", "Recent"]
+        );
+        assert_matches!(
+            restore(&backend, &recent.id),
+            Err(HelixFlowError::NotFound { .. })
+        );
+
+        assert_eq!(expire(&backend, monday + RETENTION).unwrap(), 0);
+        assert_eq!(
+            expire(&backend, monday + RETENTION + TimeDelta::hours(1)).unwrap(),
+            1
+        );
+        kept.trash(None, &backend, monday).unwrap();
+        assert_eq!(backend.empty_trash().unwrap(), 1);
+        assert_eq!(backend.trashed().unwrap(), vec![]);
+    }
+
+    #[rstest]
+    #[case(BackendKind::Mem)]
+    #[case(BackendKind::File)]
+    fn test_reminders(#[case] kind: BackendKind) {
+        use chrono::{TimeDelta, TimeZone, Utc};
+        use helixflow_core::reminders::fire_due;
+
+        let Backend {
+            _file_destructor,
+            backend,
+        } = kind.into();
+        let tasklist = TaskList::new("Backlog");
+        let task = Task::new("Water plants", None);
+        let deleted = Task::new("Deleted", None);
+        backend.create(&tasklist).unwrap();
+        tasklist.link(&task).create_linked_item(&backend).unwrap();
+        let monday = Utc.with_ymd_and_hms(2025, 6, 2, 9, 0, 0).unwrap();
+        let weekly = Reminder::new(&task, monday, Repeat::Weekly);
+        let once = Reminder::new(&task, monday + TimeDelta::hours(1), Repeat::Never);
+        let orphan = Reminder::new(&deleted, monday, Repeat::Daily);
+        let later = Reminder::new(&task, monday + TimeDelta::days(1), Repeat::Never);
+        for reminder in [&weekly, &once, &orphan, &later] {
+            reminder.create(&backend).unwrap();
+        }
+        assert_eq!(Reminder::get(&backend, &weekly.id).unwrap(), weekly);
+
+        let now = monday + TimeDelta::hours(2);
+        assert_eq!(backend.due_reminders(now).unwrap().len(), 3);
+        assert_eq!(fire_due(&backend, now).unwrap(), vec![task.clone(), task]);
+        assert_eq!(
+            Reminder::get(&backend, &weekly.id).unwrap().fire_at,
+            monday + TimeDelta::weeks(1)
+        );
+        assert_matches!(
+            Reminder::get(&backend, &once.id),
+            Err(HelixFlowError::NotFound { .. })
+        );
+        assert_matches!(
+            Reminder::get(&backend, &orphan.id),
+            Err(HelixFlowError::NotFound { .. })
+        );
+        assert_eq!(backend.due_reminders(now).unwrap(), vec![]);
+    }
+
+    #[rstest]
+    #[case(BackendKind::Mem)]
+    #[case(BackendKind::File)]
+    fn test_subtasks(#[case] kind: BackendKind) {
+        use helixflow_core::subtasks::Completion;
+
+        let Backend {
+            _file_destructor,
+            backend,
+        } = kind.into();
+        let tasklist = TaskList::new("Backlog");
+        let parent = Task::new("Move house", None);
+        let pack = Task::new("Pack", None);
+        let van = Task {
+            status: Status::Done,
+            ..Task::new("Book van", None)
+        };
+        let kitchen = Task::new("Kitchen", None);
+        backend.create(&tasklist).unwrap();
+        tasklist.link(&parent).create_linked_item(&backend).unwrap();
+        parent.subtask(&pack).create_linked_item(&backend).unwrap();
+        parent.subtask(&van).create_linked_item(&backend).unwrap();
+        pack.subtask(&kitchen).create_linked_item(&backend).unwrap();
+
+        let mut subtasks = parent.subtasks(&backend).unwrap();
+        subtasks.sort_by_key(|task| task.id);
+        assert_eq!(subtasks, vec![pack, van]);
+        let subtree = parent.subtree(&backend).unwrap();
+        assert_eq!(subtree.tasks().len(), 4);
+        assert_eq!(subtree.completion(), Completion { done: 1, total: 2 });
+        // Subtasks are not directly in the list
+        assert_eq!(tasklist.get_linked_items(&backend).unwrap().count(), 1);
+    }
+
+    #[rstest]
+    #[case(BackendKind::Mem)]
+    #[case(BackendKind::File)]
+    fn test_nested_tasklists(#[case] kind: BackendKind) {
+        let Backend {
+            _file_destructor,
+            backend,
+        } = kind.into();
+        let area = TaskList::new("Home");
+        let project = TaskList::new("Move house");
+        let week = TaskList::new("This week");
+        let task = Task::new("Book van", None);
+        backend.create(&area).unwrap();
+        area.sublist(&project).create_linked_item(&backend).unwrap();
+        project.sublist(&week).create_linked_item(&backend).unwrap();
+        project.link(&task).create_linked_item(&backend).unwrap();
+
+        assert_eq!(TaskList::get(&backend, &week.id).unwrap(), week);
+        let tree = area.tree(&backend).unwrap();
+        assert_eq!(tree.tasklists(), vec![&area, &project, &week]);
+        // Nested lists are not tasks
+        assert_eq!(project.sublists(&backend).unwrap(), vec![week.clone()]);
+        assert_eq!(backend.backlog(&project, true).unwrap(), vec![task]);
+        assert_eq!(project.get_linked_items(&backend).unwrap().count(), 1);
+
+        backend.delete_link(&area.sublist(&project)).unwrap();
+        assert_eq!(area.tree(&backend).unwrap().children, vec![]);
+    }
+
+    #[rstest]
+    #[case(BackendKind::Mem)]
+    #[case(BackendKind::File)]
+    fn test_bulk_changes(#[case] kind: BackendKind) {
+        let Backend {
+            _file_destructor,
+            backend,
+        } = kind.into();
+        let backlog = TaskList::new("Backlog");
+        let archive = TaskList::new("Archive");
+        backend.create(&backlog).unwrap();
+        backend.create(&archive).unwrap();
+        let unlisted = vec![Task::new("One", None), Task::new("Two", None)];
+        Task::create_all(&unlisted, &backend).unwrap();
+        assert_eq!(Task::get(&backend, &unlisted[1].id).unwrap(), unlisted[1]);
+        // One is already stored, so neither is
+        let more = vec![Task::new("Three", None), unlisted[0].clone()];
+        Task::create_all(&more, &backend).unwrap_err();
+        assert_matches!(
+            Task::get(&backend, &more[0].id),
+            Err(HelixFlowError::NotFound { .. })
+        );
+
+        let tasks: Vec<Task> = (1..=10)
+            .map(|n| Task::new(format!("Task {n}"), None))
+            .collect();
+        for task in &tasks {
+            backlog.link(task).create_linked_item(&backend).unwrap();
+        }
+        let completed = Task::complete_all(&tasks[..3], &backend).unwrap();
+        assert_eq!(Task::get(&backend, &tasks[2].id).unwrap(), completed[2]);
+        assert_eq!(tasks[0].history(&backend).unwrap().len(), 2);
+
+        backlog.move_all(&completed, &archive, &backend).unwrap();
+        assert_eq!(backend.backlog(&archive, true).unwrap(), completed);
+        assert_eq!(backend.backlog(&backlog, true).unwrap(), tasks[3..]);
+    }
+
+    #[rstest]
+    #[case(BackendKind::Mem)]
+    #[case(BackendKind::File)]
+    fn test_duplicate_into(#[case] kind: BackendKind) {
+        let Backend {
+            _file_destructor,
+            backend,
+        } = kind.into();
+        let chores = TaskList::new("Chores");
+        let next_week = TaskList::new("Next week");
+        let clean = Task::new("Clean house", None);
+        let kitchen = Task::new("Kitchen", None);
+        let home = Tag::new("home");
+        backend.create(&chores).unwrap();
+        backend.create(&next_week).unwrap();
+        chores.link(&clean).create_linked_item(&backend).unwrap();
+        clean
+            .subtask(&kitchen)
+            .create_linked_item(&backend)
+            .unwrap();
+        clean.tag(&home).create_linked_item(&backend).unwrap();
 
-    struct Backend {
-        _file_destructor: Option<TempPath>,
-        backend: SurrealDb<Db>,
+        let copy = clean.duplicate_into(&next_week, &backend).unwrap();
+        assert_eq!(copy.name, "Clean house (copy)");
+        assert_eq!(
+            backend.backlog(&next_week, true).unwrap(),
+            vec![copy.clone()]
+        );
+        assert_eq!(copy.tags(&backend).unwrap(), vec![home]);
+        let checklist = copy.subtasks(&backend).unwrap();
+        assert_eq!(checklist.len(), 1);
+        assert_eq!(checklist[0].name, "Kitchen");
+        assert_ne!(checklist[0].id, kitchen.id);
+        // The original is untouched
+        assert_eq!(backend.backlog(&chores, true).unwrap(), vec![clean.clone()]);
+        assert_eq!(clean.subtasks(&backend).unwrap(), vec![kitchen]);
     }
 
-    impl From<BackendKind> for Backend {
-        fn from(kind: BackendKind) -> Self {
-            match kind {
-                BackendKind::Mem => Backend {
-                    _file_destructor: None,
-                    backend: SurrealDb::new(None).unwrap(),
-                },
-                BackendKind::File => {
-                    let tmpfile = NamedTempFile::new().unwrap();
-                    let location = tmpfile.path().into();
-                    let tmppath = tmpfile.into_temp_path();
-                    std::fs::remove_file(&location).unwrap();
-                    Backend {
-                        _file_destructor: Some(tmppath),
-                        backend: SurrealDb::new(Some(location)).unwrap(),
-                    }
-                }
-            }
-        }
+    #[rstest]
+    #[case(BackendKind::Mem)]
+    #[case(BackendKind::File)]
+    fn test_watchers(#[case] kind: BackendKind) {
+        let Backend {
+            _file_destructor,
+            backend,
+        } = kind.into();
+        let alice = User::new("alice");
+        let bob = User::new("bob");
+        let task = Task::new("Watched", None);
+        let other_task = Task::new("Not watched", None);
+        backend.create(&alice).unwrap();
+        backend.create(&bob).unwrap();
+        backend.create(&task).unwrap();
+        backend.create(&other_task).unwrap();
+        alice.watch(&task, &backend).unwrap();
+        assert_eq!(backend.watchers(&task).unwrap(), vec![alice]);
+        assert!(backend.watchers(&other_task).unwrap().is_empty());
     }
 
     #[rstest]
     #[case(BackendKind::Mem)]
     #[case(BackendKind::File)]
-    fn test_new_task(#[case] kind: BackendKind) {
+    fn test_comments(#[case] kind: BackendKind) {
         let Backend {
             _file_destructor,
             backend,
         } = kind.into();
-        let new_task = Task::new("Test Task 1", None);
-        backend.create(&new_task).unwrap();
+        let task = Task::new("Write report", None);
+        let actor = Actor::new("alice", &Uuid::now_v7());
+        let first = Comment::new(&task, "First draft done", &actor);
+        let second = Comment::new(&task, "Sent for review", &actor);
+        second.create(&backend).unwrap();
+        first.create(&backend).unwrap();
+        Comment::new(&Task::new("Other", None), "Elsewhere", &actor)
+            .create(&backend)
+            .unwrap();
+        assert_eq!(Comment::get(&backend, &first.id).unwrap(), first);
+        assert_eq!(backend.comments_on(&task).unwrap(), vec![first, second]);
     }
 
     #[rstest]
     #[case(BackendKind::Mem)]
     #[case(BackendKind::File)]
-    fn test_new_task_written_to_db(#[case] kind: BackendKind) {
+    fn test_task_due(#[case] kind: BackendKind) {
         let Backend {
             _file_destructor,
             backend,
         } = kind.into();
-        let new_task = Task::new("Test Task 2", None);
+        let mut new_task = Task::new("Due soon", None);
+        new_task.due = Some("2025-03-30T01:30:00Z".parse().unwrap());
         backend.create(&new_task).unwrap();
         let stored_task: Task = backend.get(&new_task.id).unwrap();
         assert_eq!(stored_task, new_task);
     }
 
+    #[rstest]
+    #[case(BackendKind::Mem)]
+    #[case(BackendKind::File)]
+    fn test_settings_roundtrip(#[case] kind: BackendKind) {
+        let Backend {
+            _file_destructor,
+            backend,
+        } = kind.into();
+        let mut settings = Settings::new(&Uuid::now_v7());
+        settings.display_timezone(chrono_tz::Europe::Berlin);
+        let mut calendar = WorkingCalendar::default();
+        calendar
+            .holidays
+            .insert(chrono::NaiveDate::from_ymd_opt(2025, 12, 25).unwrap());
+        settings.working_calendar(calendar);
+        settings.actor(Actor::new("Alice", &Uuid::now_v7()));
+        settings.quick_add_rules(QuickAddRules::for_locale(Locale::De));
+        settings.display_locale(Locale::De);
+        settings.share_telemetry(Some("metrics.jsonl"));
+        backend.create(&settings).unwrap();
+        let stored_settings: Settings = backend.get(&settings.id).unwrap();
+        assert_eq!(stored_settings, settings);
+    }
+
     #[rstest]
     #[case(BackendKind::Mem)]
     #[case(BackendKind::File)]
@@ -482,4 +5341,583 @@ mod tests {
         let stored_task: Task = backend2.get(&new_task.id).unwrap();
         assert_eq!(stored_task, new_task);
     }
+
+    #[test]
+    fn test_persistent_engine() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("helixflow.db");
+        let export = NamedTempFile::new().unwrap();
+        let old_task = Task::new("Saved on drop", None);
+        {
+            let old = SurrealDb::new(Some(export.path().to_path_buf())).unwrap();
+            old_task.create(&old).unwrap();
+        }
+
+        let new_task = Task::new("Stored straight away", None);
+        {
+            let backend = SurrealDb::open(&path).unwrap();
+            backend.import(export.path()).unwrap();
+            new_task.create(&backend).unwrap();
+            assert_eq!(backend.namespace_file(DEFAULT_NAMESPACE), None);
+        } // nothing to export on drop
+
+        let backend = SurrealDb::open(&path).unwrap();
+        assert_eq!(Task::get(&backend, &old_task.id).unwrap(), old_task);
+        assert_eq!(Task::get(&backend, &new_task.id).unwrap(), new_task);
+    }
+
+    #[test]
+    fn test_separate_tenants() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("helixflow.db");
+        let tenant = |name: &str| SurrealConfig {
+            database: name.into(),
+            ..Default::default()
+        };
+        let task = Task::new("Tenant A's task", None);
+        {
+            let backend = SurrealDb::open_with_config(&path, tenant("a")).unwrap();
+            task.create(&backend).unwrap();
+        }
+        {
+            let backend = SurrealDb::open_with_config(&path, tenant("b")).unwrap();
+            assert!(Task::get(&backend, &task.id).is_err());
+        }
+        let backend = SurrealDb::open_with_config(
+            &path,
+            SurrealConfig {
+                namespace: "profile".into(),
+                ..tenant("a")
+            },
+        )
+        .unwrap();
+        assert!(Task::get(&backend, &task.id).is_err());
+        let profile_task = Task::new("Profile task", None);
+        profile_task.create(&backend).unwrap();
+        // The default workspace is in the configured namespace
+        backend.use_namespace("work").unwrap();
+        backend.use_namespace(DEFAULT_NAMESPACE).unwrap();
+        assert_eq!(Task::get(&backend, &profile_task.id).unwrap(), profile_task);
+    }
+
+    #[test]
+    fn test_multi_thread() {
+        fn send_and_sync<T: Send + Sync>() {}
+        send_and_sync::<SurrealDb<Db>>();
+        send_and_sync::<SurrealDb<Any>>();
+
+        let dir = tempdir().unwrap();
+        let backend =
+            Arc::new(SurrealDb::open_multi_thread(&dir.path().join("helixflow.db"), 2).unwrap());
+        let workers: Vec<_> = (0..4)
+            .map(|n| {
+                let backend = backend.clone();
+                std::thread::spawn(move || {
+                    let task = Task::new(format!("Task {n}"), None);
+                    task.create(backend.as_ref()).unwrap();
+                    task.id
+                })
+            })
+            .collect();
+        for worker in workers {
+            let id = worker.join().unwrap();
+            Task::get(backend.as_ref(), &id).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_flush() {
+        let export = NamedTempFile::new().unwrap();
+        let backend = SurrealDb::new(Some(export.path().to_path_buf())).unwrap();
+        let saved = || {
+            SurrealDb::new(None).and_then(|saved| {
+                saved.import(export.path())?;
+                Ok(saved)
+            })
+        };
+        let task = Task::new("Flushed", None);
+        task.create(&backend).unwrap();
+        assert!(Task::get(&saved().unwrap(), &task.id).is_err());
+
+        backend.flush().unwrap();
+        assert_eq!(Task::get(&saved().unwrap(), &task.id).unwrap(), task);
+
+        let tasks: Vec<Task> = (0..FLUSH_AFTER_CHANGES)
+            .map(|n| Task::new(format!("Task {n}"), None))
+            .collect();
+        for task in &tasks {
+            task.create(&backend).unwrap();
+        }
+        assert!(Task::get(&saved().unwrap(), &tasks.last().unwrap().id).is_ok());
+    }
+
+    #[test]
+    fn test_backup_rotation() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("helixflow.kv");
+        let backend = SurrealDb::new(Some(file.clone())).unwrap();
+        let tasks: Vec<Task> = (1..=3)
+            .map(|n| Task::new(format!("Task {n}"), None))
+            .collect();
+        for task in &tasks {
+            task.create(&backend).unwrap();
+            backend.backup(2).unwrap();
+        }
+        let backup = |n: usize| {
+            let saved = SurrealDb::new(None).unwrap();
+            saved
+                .import(&dir.path().join(format!("helixflow.kv.{n}")))
+                .unwrap();
+            saved
+        };
+        assert!(Task::get(&backup(1), &tasks[2].id).is_ok());
+        assert!(Task::get(&backup(2), &tasks[1].id).is_ok());
+        assert!(Task::get(&backup(2), &tasks[2].id).is_err());
+        assert!(!dir.path().join("helixflow.kv.3").exists());
+
+        let in_memory = SurrealDb::new(None).unwrap();
+        in_memory.backup(2).unwrap();
+    }
+
+    #[rstest]
+    #[case(BackendKind::Mem)]
+    #[case(BackendKind::File)]
+    fn test_state_roundtrip(#[case] kind: BackendKind) {
+        let Backend {
+            _file_destructor,
+            backend,
+        } = kind.into();
+        let mut state = State::new(&Uuid::now_v7());
+        state.view_mode(ViewMode::Board);
+        state.card_settings(
+            ViewMode::Board,
+            CardSettings {
+                show_tags: false,
+                ..Default::default()
+            },
+        );
+        state.task_details(&Task::new("Open task", None));
+        state.visible_project(&Project::new("Home", None::<&str>));
+        state.table_layout(
+            &TaskList::new("Backlog"),
+            TableLayout {
+                columns: vec![Column::Priority, Column::Name, Column::Tags],
+                sort_by: Some(Column::Priority),
+                descending: true,
+            },
+        );
+        state.window(WindowGeometry {
+            x: -1200,
+            y: 40,
+            width: 1024,
+            height: 768,
+        });
+        state.select_task(&Task::new("Selected task", None));
+        state.sort_mode(SortRule(vec![SortKey::DueDate, SortKey::Name]));
+        state.filter(BacklogFilter {
+            statuses: vec![Status::Blocked],
+            text: Some("report".into()),
+        });
+        state.add_workspace(Workspace::new("Work")).unwrap();
+        state.workspace("work");
+        backend.create(&state).unwrap();
+        let stored_state: State = backend.get(&state.id).unwrap();
+        assert_eq!(stored_state, state);
+        assert_eq!(stored_state.current_workspace().name, "Work");
+    }
+
+    #[rstest]
+    #[case(BackendKind::Mem)]
+    #[case(BackendKind::File)]
+    fn test_workspaces(#[case] kind: BackendKind) {
+        let Backend {
+            _file_destructor,
+            backend,
+        } = kind.into();
+        let personal = Task::new("Personal task", None);
+        personal.create(&backend).unwrap();
+        let mut state = State::new(&Uuid::now_v7());
+        state.add_workspace(Workspace::new("Work")).unwrap();
+        state.create(&backend).unwrap();
+
+        state.switch_workspace("work", &backend).unwrap();
+        assert_matches!(
+            Task::get(&backend, &personal.id),
+            Err(HelixFlowError::NotFound { .. })
+        );
+        let work = Task::new("Work task", None);
+        work.create(&backend).unwrap();
+        assert_eq!(State::get(&backend, &state.id).unwrap(), state);
+
+        state.switch_workspace(DEFAULT_NAMESPACE, &backend).unwrap();
+        assert_eq!(Task::get(&backend, &personal.id).unwrap(), personal);
+        assert_matches!(
+            Task::get(&backend, &work.id),
+            Err(HelixFlowError::NotFound { .. })
+        );
+
+        // Each namespace is saved to its own file
+        if let Some(file) = &_file_destructor {
+            state.switch_workspace("work", &backend).unwrap();
+            let work_file = backend.namespace_file("work").unwrap();
+            drop(backend);
+            let backend = SurrealDb::new(Some(file.to_path_buf())).unwrap();
+            let stored = State::get(&backend, &state.id).unwrap();
+            assert_eq!(stored.current_workspace().name, "Work");
+            backend.use_namespace("work").unwrap();
+            assert_eq!(Task::get(&backend, &work.id).unwrap(), work);
+            drop(backend);
+            std::fs::remove_file(work_file).unwrap();
+        }
+    }
+
+    #[rstest]
+    #[case(BackendKind::Mem)]
+    #[case(BackendKind::File)]
+    fn test_update_task(#[case] kind: BackendKind) {
+        let Backend {
+            _file_destructor,
+            backend,
+        } = kind.into();
+        let mut task = Task::new("Test Task", None);
+        backend.create(&task).unwrap();
+        task.name = "Renamed".into();
+        task.transition(Status::Done).unwrap();
+        let updated_task: Task = backend.update(&task).unwrap();
+        assert_eq!(updated_task, task);
+        let stored_task: Task = backend.get(&task.id).unwrap();
+        assert_eq!(stored_task, task);
+    }
+
+    /// Updates replace the whole task, so clearing an optional field removes it.
+    #[rstest]
+    #[case(BackendKind::Mem)]
+    #[case(BackendKind::File)]
+    fn test_update_optional_fields(#[case] kind: BackendKind) {
+        let Backend {
+            _file_destructor,
+            backend,
+        } = kind.into();
+        let task = Task::new("Test Task", Some("Description"));
+        let mut task: Task = backend.create(&task).unwrap();
+        task.description = None;
+        task.due = Some("2025-06-05T09:00:00Z".parse().unwrap());
+        let task: Task = backend.update(&task).unwrap();
+        let stored_task: Task = backend.get(&task.id).unwrap();
+        assert_eq!(stored_task.description, None);
+        assert_eq!(stored_task.due, task.due);
+
+        let mut task = stored_task;
+        task.due = None;
+        backend.update(&task).unwrap();
+        let stored_task: Task = backend.get(&task.id).unwrap();
+        assert_eq!(stored_task.due, None);
+        assert_eq!(stored_task.name, "Test Task");
+    }
+
+    #[rstest]
+    #[case(BackendKind::Mem)]
+    #[case(BackendKind::File)]
+    fn test_conflicting_updates(#[case] kind: BackendKind) {
+        let Backend {
+            _file_destructor,
+            backend,
+        } = kind.into();
+        let task = Task::new("Test Task", None);
+        task.create(&backend).unwrap();
+        let mut mine = Task::get(&backend, &task.id).unwrap();
+        let mut theirs = Task::get(&backend, &task.id).unwrap();
+        assert_eq!(mine.version(), Some(0));
+
+        theirs.name = "Their name".into();
+        theirs.update(&backend).unwrap();
+        mine.name = "My name".into();
+        assert_matches!(
+            mine.update(&backend),
+            Err(HelixFlowError::Conflict {
+                expected: 0,
+                stored: 1,
+                ..
+            })
+        );
+        assert_matches!(
+            Task::update_all(std::slice::from_ref(&mine), &backend),
+            Err(HelixFlowError::Conflict { .. })
+        );
+        assert_eq!(Task::get(&backend, &task.id).unwrap().name, "Their name");
+
+        let mut mine = Task::get(&backend, &task.id).unwrap();
+        mine.name = "My name".into();
+        mine.update(&backend).unwrap();
+        let stored = Task::get(&backend, &task.id).unwrap();
+        assert_eq!(stored.name, "My name");
+        assert_eq!(stored.version(), Some(2));
+    }
+
+    #[rstest]
+    #[case(BackendKind::Mem)]
+    #[case(BackendKind::File)]
+    fn test_shared_between_threads(#[case] kind: BackendKind) {
+        let Backend {
+            _file_destructor,
+            backend,
+        } = kind.into();
+        let backend = Arc::new(backend);
+        let task = Task::new("Created in the background", None);
+        let be = backend.clone();
+        let background_task = task.clone();
+        std::thread::spawn(move || be.create(&background_task).unwrap())
+            .join()
+            .unwrap();
+        let stored_task: Task = backend.get(&task.id).unwrap();
+        assert_eq!(stored_task, task);
+    }
+
+    #[rstest]
+    #[case(BackendKind::Mem)]
+    #[case(BackendKind::File)]
+    fn test_update_not_found(#[case] kind: BackendKind) {
+        let Backend {
+            _file_destructor,
+            backend,
+        } = kind.into();
+        let task = Task::new("Test Task", None);
+        let err = Store::<Task>::update(&backend, &task).unwrap_err();
+        assert_matches!(err, HelixFlowError::NotFound { itemtype, id } if itemtype == "Task" && id == task.id);
+    }
+
+    #[rstest]
+    #[case(BackendKind::Mem)]
+    #[case(BackendKind::File)]
+    fn test_delete_task(#[case] kind: BackendKind) {
+        use helixflow_core::{CRUD, Link as _};
+
+        let Backend {
+            _file_destructor,
+            backend,
+        } = kind.into();
+        let tasklist = TaskList::new("Backlog");
+        tasklist.create(&backend).unwrap();
+        let task = Task::new("Delete me", None);
+        let other_task = Task::new("Keep me", None);
+        tasklist.link(&task).create_linked_item(&backend).unwrap();
+        tasklist
+            .link(&other_task)
+            .create_linked_item(&backend)
+            .unwrap();
+
+        task.delete(&backend).unwrap();
+        let err = Task::get(&backend, &task.id).unwrap_err();
+        assert_matches!(err, HelixFlowError::NotFound { .. });
+        let remaining: Vec<Task> = tasklist
+            .get_linked_items(&backend)
+            .unwrap()
+            .map(|link| link.right.unwrap())
+            .collect();
+        assert_eq!(remaining, vec![other_task]);
+    }
+
+    #[rstest]
+    #[case(BackendKind::Mem)]
+    #[case(BackendKind::File)]
+    fn test_delete_tasklist(#[case] kind: BackendKind) {
+        use helixflow_core::{CRUD, Link as _};
+
+        let Backend {
+            _file_destructor,
+            backend,
+        } = kind.into();
+        let tasklist = TaskList::new("Backlog");
+        tasklist.create(&backend).unwrap();
+        let task = Task::new("Orphan", None);
+        tasklist.link(&task).create_linked_item(&backend).unwrap();
+
+        tasklist.delete(&backend).unwrap();
+        assert_matches!(
+            TaskList::get(&backend, &tasklist.id).unwrap_err(),
+            HelixFlowError::NotFound { .. }
+        );
+        let mut response = backend
+            .rt
+            .block_on(
+                backend
+                    .db
+                    .query("SELECT in, out FROM contains")
+                    .into_future(),
+            )
+            .unwrap();
+        let links: Vec<Link> = response.take(0).unwrap();
+        assert!(links.is_empty());
+        // The task itself is not deleted
+        Task::get(&backend, &task.id).unwrap();
+    }
+
+    #[rstest]
+    #[case(BackendKind::Mem)]
+    #[case(BackendKind::File)]
+    fn test_delete_not_found(#[case] kind: BackendKind) {
+        let Backend {
+            _file_destructor,
+            backend,
+        } = kind.into();
+        let id = Uuid::now_v7();
+        let err = Store::<Task>::delete(&backend, &id).unwrap_err();
+        assert_matches!(err, HelixFlowError::NotFound { itemtype, .. } if itemtype == "Task");
+    }
+
+    #[rstest]
+    #[case(BackendKind::Mem)]
+    #[case(BackendKind::File)]
+    fn test_update_state(#[case] kind: BackendKind) {
+        let Backend {
+            _file_destructor,
+            backend,
+        } = kind.into();
+        let mut state = State::new(&Uuid::now_v7());
+        backend.create(&state).unwrap();
+        state.visible_backlog(&TaskList::new("Backlog"));
+        let _: State = backend.update(&state).unwrap();
+        let stored_state: State = backend.get(&state.id).unwrap();
+        assert_eq!(
+            stored_state.visible_backlog_id(),
+            state.visible_backlog_id()
+        );
+    }
+
+    #[rstest]
+    #[case(BackendKind::Mem)]
+    #[case(BackendKind::File)]
+    fn test_put_state(#[case] kind: BackendKind) {
+        let Backend {
+            _file_destructor,
+            backend,
+        } = kind.into();
+        let mut state = State::new(&Uuid::now_v7());
+        let _: State = backend.put(&state).unwrap();
+        state.visible_backlog(&TaskList::new("Backlog"));
+        let _: State = backend.put(&state).unwrap();
+        let stored_state: State = backend.get(&state.id).unwrap();
+        assert_eq!(stored_state, state);
+    }
+
+    #[rstest]
+    #[case(BackendKind::Mem)]
+    #[case(BackendKind::File)]
+    fn test_share_and_revoke(#[case] kind: BackendKind) {
+        use helixflow_core::share::SharedTaskList;
+
+        let Backend {
+            _file_destructor,
+            backend,
+        } = kind.into();
+        let tasklist = TaskList::new("Shared list");
+        backend.create(&tasklist).unwrap();
+        let token = tasklist
+            .share(&backend, chrono::TimeDelta::days(1))
+            .unwrap();
+        let shared = SharedTaskList::resolve(&backend, &token.id).unwrap();
+        assert_eq!(shared.tasklist, tasklist);
+        backend.revoke_token(&token.id).unwrap();
+        let err = SharedTaskList::resolve(&backend, &token.id).unwrap_err();
+        assert_matches!(err, HelixFlowError::ShareNotValid { id } if id == token.id);
+    }
+
+    #[test]
+    fn test_merge_import() {
+        use helixflow_core::{CRUD, Link as _};
+
+        let location = NamedTempFile::new().unwrap();
+        let tasklist = TaskList::new("Other machine");
+        let task = Task::new("Imported task", None);
+        {
+            let other = SurrealDb::new(Some(location.path().to_path_buf())).unwrap();
+            tasklist.create(&other).unwrap();
+            tasklist.link(&task).create_linked_item(&other).unwrap();
+        } // export on drop
+
+        let backend = SurrealDb::new(None).unwrap();
+        let clashing = TaskList {
+            name: "This machine".into(),
+            id: tasklist.id,
+            sort: Default::default(),
+        };
+        clashing.create(&backend).unwrap();
+
+        let report = backend.merge_import(location.path()).unwrap();
+        assert_eq!(
+            report,
+            MergeReport {
+                imported: 1,
+                remapped: 1,
+                unchanged: 0,
+                links: 1,
+            }
+        );
+        assert_eq!(TaskList::get(&backend, &tasklist.id).unwrap(), clashing);
+        assert_eq!(clashing.get_linked_items(&backend).unwrap().count(), 0);
+        assert_eq!(Task::get(&backend, &task.id).unwrap(), task);
+
+        let report = backend.merge_import(location.path()).unwrap();
+        assert_eq!(
+            report,
+            MergeReport {
+                imported: 0,
+                remapped: 1,
+                unchanged: 1,
+                links: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn test_import_foreign_ids() {
+        use std::io::Write;
+
+        let mut dump = NamedTempFile::new().unwrap();
+        writeln!(
+            dump,
+            "CREATE Tasklists:groceries SET name = 'Groceries'; \
+            CREATE Tasks:01ARZ3NDEKTSV4RRFFQ69G5FAV SET name = 'Milk'; \
+            CREATE Tasks:42 SET name = 'Bread'; \
+            RELATE Tasklists:groceries->contains->Tasks:01ARZ3NDEKTSV4RRFFQ69G5FAV; \
+            RELATE Tasklists:groceries->contains->Tasks:42;"
+        )
+        .unwrap();
+
+        let backend = SurrealDb::new(None).unwrap();
+        let report = backend.merge_import(dump.path()).unwrap();
+        assert_eq!(report.imported, 3);
+        assert_eq!(report.links, 2);
+        let groceries = TaskList::get(&backend, &"groceries".to_uuid()).unwrap();
+        let names: Vec<String> = groceries
+            .get_linked_items(&backend)
+            .unwrap()
+            .map(|contains| contains.right.unwrap().name.to_string())
+            .collect();
+        assert_eq!(names.len(), 2);
+        let milk = Task::get(&backend, &"01ARZ3NDEKTSV4RRFFQ69G5FAV".to_uuid()).unwrap();
+        assert_eq!(milk.name, "Milk");
+        assert!(milk.created_at().is_some());
+        assert_eq!(Task::get(&backend, &42.to_uuid()).unwrap().name, "Bread");
+        assert_eq!(
+            backend.original_key(&42.to_uuid()).unwrap().as_deref(),
+            Some("Tasks:42")
+        );
+        assert_eq!(
+            backend.original_key(&groceries.id).unwrap().as_deref(),
+            Some("Tasklists:groceries")
+        );
+        let native = Task::new("Native", None);
+        native.create(&backend).unwrap();
+        assert_eq!(backend.original_key(&native.id).unwrap(), None);
+    }
+
+    #[test]
+    fn test_state_without_backlog() {
+        let backend = SurrealDb::new(None).unwrap();
+        let state = State::new(&Uuid::now_v7());
+        backend.create(&state).unwrap();
+        let stored_state: State = backend.get(&state.id).unwrap();
+        assert_eq!(stored_state, state);
+    }
 }
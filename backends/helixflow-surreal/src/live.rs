@@ -0,0 +1,143 @@
+//! Changes made by anyone using the database, via SurrealDb's `LIVE SELECT`.
+
+use std::{pin::pin, thread};
+
+use futures::StreamExt;
+use log::error;
+use surrealdb::{Action, Connection, Notification};
+use tokio::{runtime::Runtime, sync::oneshot};
+
+use helixflow_core::{
+    HelixFlowResult,
+    events::{Event, Subscribe, Subscription},
+    task::Task,
+};
+
+use crate::{SurrealDb, SurrealTask, item_id};
+
+/// The event for a `LIVE SELECT` notification, if it is one HelixFlow knows about.
+fn event(itemtype: &'static str, notification: &Notification<SurrealTask>) -> Option<Event> {
+    let id = match item_id(&notification.data.id) {
+        Ok(id) => id,
+        Err(e) => {
+            error!("Ignoring change to {itemtype}: {e}");
+            return None;
+        }
+    };
+    match notification.action {
+        Action::Create => Some(Event::Created { itemtype, id }),
+        Action::Update => Some(Event::Updated { itemtype, id }),
+        Action::Delete => Some(Event::Deleted { itemtype, id }),
+        _ => None,
+    }
+}
+
+/// Only reports `Created`, `Updated` & `Deleted`: a live query doesn't say what a task was before,
+/// so there is no `TaskCompleted`.
+///
+/// The live query is tied to the namespace in use when subscribing, subscribe again after
+/// `use_namespace`.
+impl<C: Connection> Subscribe<Task> for SurrealDb<C> {
+    fn subscribe<F>(&self, subscriber: F) -> HelixFlowResult<Subscription>
+    where
+        F: Fn(&Event) + Send + Sync + 'static,
+    {
+        let changes = self
+            .rt
+            .block_on(
+                self.db
+                    .select::<Vec<SurrealTask>>("Tasks")
+                    .live()
+                    .into_future(),
+            )
+            .map_err(anyhow::Error::from)?;
+        let (stop, stopped) = oneshot::channel::<()>();
+        let rt = self.rt.clone();
+        // A current thread runtime only makes progress while something blocks on it. Not timed,
+        // this blocks until the subscription ends.
+        thread::spawn(move || {
+            Runtime::block_on(&rt, async move {
+                let mut changes = pin!(changes.take_until(stopped));
+                while let Some(notification) = changes.next().await {
+                    match notification {
+                        Ok(notification) => {
+                            if let Some(event) = event("Task", &notification) {
+                                subscriber(&event);
+                            }
+                        }
+                        Err(e) => error!("Live query for tasks failed: {e}"),
+                    }
+                }
+            });
+        });
+        // Ends the thread, dropping `changes` kills the live query
+        Ok(Subscription::new(move || {
+            let _ = stop.send(());
+        }))
+    }
+}
+
+#[cfg(test)]
+#[coverage(off)]
+mod tests {
+    use std::{sync::mpsc, time::Duration};
+
+    use helixflow_core::CRUD;
+
+    use super::*;
+
+    #[test]
+    fn changes_by_anyone() {
+        let backend = SurrealDb::new(None).unwrap();
+        let (sender, received) = mpsc::channel();
+        let subscription = Subscribe::<Task>::subscribe(&backend, move |event| {
+            let _ = sender.send(event.clone());
+        })
+        .unwrap();
+        let next = || received.recv_timeout(Duration::from_secs(5)).unwrap();
+
+        let task = Task::new("Task 1", None);
+        task.create(&backend).unwrap();
+        assert_eq!(
+            next(),
+            Event::Created {
+                itemtype: "Task",
+                id: task.id
+            }
+        );
+
+        // As if by another process, without going through `CRUD`
+        backend
+            .rt
+            .block_on(
+                backend
+                    .db
+                    .query("UPDATE $task SET name = 'Renamed'")
+                    .bind(("task", SurrealTask::from(&task).id))
+                    .into_future(),
+            )
+            .unwrap()
+            .check()
+            .unwrap();
+        assert_eq!(
+            next(),
+            Event::Updated {
+                itemtype: "Task",
+                id: task.id
+            }
+        );
+
+        task.delete(&backend).unwrap();
+        assert_eq!(
+            next(),
+            Event::Deleted {
+                itemtype: "Task",
+                id: task.id
+            }
+        );
+
+        drop(subscription);
+        Task::new("Task 2", None).create(&backend).unwrap();
+        assert!(received.recv_timeout(Duration::from_millis(200)).is_err());
+    }
+}
@@ -0,0 +1,163 @@
+//! How long queries take, so that the slow ones behind a UI freeze can be found.
+//!
+//! Every `block_on` through `TimedRuntime` is timed & grouped by where it was called from, so
+//! each query in this crate gets its own `QueryMetrics` without any changes to the query itself.
+
+use std::{
+    collections::HashMap,
+    ops::Deref,
+    panic::Location,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use log::warn;
+use surrealdb::Connection;
+use tokio::runtime::Runtime;
+
+use crate::SurrealDb;
+
+/// Upper bounds of the latency histogram buckets. Anything slower goes in a final bucket.
+pub const LATENCY_BUCKETS: [Duration; 6] = [
+    Duration::from_millis(1),
+    Duration::from_millis(5),
+    Duration::from_millis(25),
+    Duration::from_millis(100),
+    Duration::from_millis(500),
+    Duration::from_secs(2),
+];
+
+/// Log a warning about any query which takes longer than this.
+pub const SLOW_QUERY: Duration = Duration::from_millis(500);
+
+/// Timings of all the calls from one place.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct QueryMetrics {
+    /// `file:line` of the call, e.g. `backends/helixflow-surreal/src/lib.rs:321`
+    pub location: String,
+    pub count: u64,
+    pub total: Duration,
+    pub max: Duration,
+    /// How many calls took up to each of `LATENCY_BUCKETS`, then how many took longer
+    pub histogram: [u64; LATENCY_BUCKETS.len() + 1],
+}
+
+impl QueryMetrics {
+    pub fn mean(&self) -> Duration {
+        self.total
+            .checked_div(self.count.try_into().unwrap_or(u32::MAX))
+            .unwrap_or_default()
+    }
+
+    fn record(&mut self, elapsed: Duration) {
+        self.count += 1;
+        self.total += elapsed;
+        self.max = self.max.max(elapsed);
+        let bucket = LATENCY_BUCKETS
+            .iter()
+            .position(|bound| elapsed <= *bound)
+            .unwrap_or(LATENCY_BUCKETS.len());
+        self.histogram[bucket] += 1;
+    }
+}
+
+/// A tokio runtime which times every `block_on`. Everything else is the plain `Runtime`.
+#[derive(Debug)]
+pub(crate) struct TimedRuntime {
+    runtime: Runtime,
+    metrics: Mutex<HashMap<&'static Location<'static>, QueryMetrics>>,
+}
+
+impl TimedRuntime {
+    pub(crate) fn new(runtime: Runtime) -> Self {
+        TimedRuntime {
+            runtime,
+            metrics: Mutex::default(),
+        }
+    }
+
+    /// `Runtime::block_on`, timed.
+    #[track_caller]
+    pub(crate) fn block_on<F: Future>(&self, future: F) -> F::Output {
+        let location = Location::caller();
+        let started = Instant::now();
+        let output = self.runtime.block_on(future);
+        let elapsed = started.elapsed();
+        if elapsed > SLOW_QUERY {
+            warn!("Slow query at {location}: {elapsed:?}");
+        }
+        self.metrics
+            .lock()
+            .unwrap()
+            .entry(location)
+            .or_insert_with(|| QueryMetrics {
+                location: format!("{}:{}", location.file(), location.line()),
+                ..Default::default()
+            })
+            .record(elapsed);
+        output
+    }
+}
+
+impl Deref for TimedRuntime {
+    type Target = Runtime;
+
+    fn deref(&self) -> &Runtime {
+        &self.runtime
+    }
+}
+
+impl<C: Connection> SurrealDb<C> {
+    /// Timings of every query made so far, slowest in total first.
+    pub fn query_metrics(&self) -> Vec<QueryMetrics> {
+        let mut metrics: Vec<QueryMetrics> =
+            self.rt.metrics.lock().unwrap().values().cloned().collect();
+        metrics.sort_by(|a, b| b.total.cmp(&a.total));
+        metrics
+    }
+
+    /// Start timing from scratch.
+    pub fn reset_query_metrics(&self) {
+        self.rt.metrics.lock().unwrap().clear();
+    }
+}
+
+#[cfg(test)]
+#[coverage(off)]
+mod tests {
+    use helixflow_core::{CRUD, task::Task};
+
+    use super::*;
+
+    #[test]
+    fn time_queries() {
+        let backend = SurrealDb::new(None).unwrap();
+        backend.reset_query_metrics();
+        let task = Task::new("Timed", None);
+        task.create(&backend).unwrap();
+        Task::get(&backend, &task.id).unwrap();
+        Task::get(&backend, &task.id).unwrap();
+
+        let metrics = backend.query_metrics();
+        assert!(
+            metrics
+                .iter()
+                .all(|query| query.location.contains("lib.rs"))
+        );
+        let gets = metrics.iter().find(|query| query.count == 2).unwrap();
+        assert_eq!(gets.histogram.iter().sum::<u64>(), 2);
+        assert!(gets.max <= gets.total);
+        assert!(gets.mean() <= gets.max);
+    }
+
+    #[test]
+    fn histogram() {
+        let mut metrics = QueryMetrics::default();
+        metrics.record(Duration::from_micros(500));
+        metrics.record(Duration::from_millis(30));
+        metrics.record(Duration::from_secs(10));
+        assert_eq!(metrics.histogram, [1, 0, 0, 1, 0, 0, 1]);
+        assert_eq!(metrics.max, Duration::from_secs(10));
+        assert_eq!(metrics.mean(), Duration::from_micros(3_343_500));
+    }
+}
@@ -0,0 +1,352 @@
+//! Connecting to a shared SurrealDb server rather than an embedded database.
+
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex, RwLock, atomic::AtomicUsize},
+    time::Duration,
+};
+
+use anyhow::{Context, bail};
+use helixflow_core::HelixFlowError;
+use log::debug;
+use serde::Serialize;
+use surrealdb::{
+    Surreal,
+    engine::any::{self, Any},
+    error::{Api, Db},
+    opt::{
+        Config,
+        auth::{Database, Namespace, Record, Root},
+    },
+};
+
+use crate::{SurrealDb, TimedRuntime, runtime};
+
+/// A user to sign in to the server as. Namespace & database users are signed in to the
+/// `namespace` & `database` in the `RemoteConfig`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Credentials {
+    /// Can use every namespace on the server
+    Root {
+        username: String,
+        password: String,
+    },
+    Namespace {
+        username: String,
+        password: String,
+    },
+    Database {
+        username: String,
+        password: String,
+    },
+    /// A user record, signed in via the `DEFINE ACCESS ... TYPE RECORD` method named `access`,
+    /// which is given `username` & `password` as parameters. (Scope users before SurrealDb 2)
+    Record {
+        access: String,
+        username: String,
+        password: String,
+    },
+}
+
+impl Credentials {
+    pub fn username(&self) -> &str {
+        match self {
+            Credentials::Root { username, .. }
+            | Credentials::Namespace { username, .. }
+            | Credentials::Database { username, .. }
+            | Credentials::Record { username, .. } => username,
+        }
+    }
+}
+
+/// The parameters given to a record access method when signing in.
+#[derive(Serialize)]
+struct RecordParams<'a> {
+    username: &'a str,
+    password: &'a str,
+}
+
+/// Where to find a SurrealDb server & how to use it, see `SurrealDb::connect`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteConfig {
+    /// e.g. `ws://localhost:8000` or `https://surreal.example.com`. `mem://` connects to an
+    /// in-memory database instead.
+    pub endpoint: String,
+    pub namespace: String,
+    pub database: String,
+    /// Sign in as this user, or not at all if `None`
+    pub credentials: Option<Credentials>,
+    /// Give up on any query which takes longer than this
+    pub query_timeout: Option<Duration>,
+    /// Run queries on this many threads of their own, rather than on the calling thread
+    pub worker_threads: Option<usize>,
+}
+
+impl Default for RemoteConfig {
+    fn default() -> Self {
+        RemoteConfig {
+            endpoint: "ws://localhost:8000".into(),
+            namespace: "HelixFlow".into(),
+            database: "HelixFlow".into(),
+            credentials: None,
+            query_timeout: None,
+            worker_threads: None,
+        }
+    }
+}
+
+impl RemoteConfig {
+    /// The default config, changed by any of these environment variables which are set:
+    /// - `HELIXFLOW_SURREAL_ENDPOINT`, `HELIXFLOW_SURREAL_NAMESPACE`, `HELIXFLOW_SURREAL_DATABASE`
+    /// - `HELIXFLOW_SURREAL_USER` & `HELIXFLOW_SURREAL_PASS` to sign in, as the kind of user in
+    ///   `HELIXFLOW_SURREAL_AUTH`: `root` (the default), `namespace`, `database` or `record`.
+    /// - `HELIXFLOW_SURREAL_ACCESS`, the access method for `record` users.
+    pub fn from_env() -> anyhow::Result<Self> {
+        Self::from_vars(|name| std::env::var(name).ok())
+    }
+
+    fn from_vars(var: impl Fn(&str) -> Option<String>) -> anyhow::Result<Self> {
+        let default = RemoteConfig::default();
+        let credentials = match (var("HELIXFLOW_SURREAL_USER"), var("HELIXFLOW_SURREAL_PASS")) {
+            (Some(username), Some(password)) => {
+                let auth = var("HELIXFLOW_SURREAL_AUTH");
+                Some(match auth.as_deref().unwrap_or("root") {
+                    "root" => Credentials::Root { username, password },
+                    "namespace" => Credentials::Namespace { username, password },
+                    "database" => Credentials::Database { username, password },
+                    "record" => Credentials::Record {
+                        access: var("HELIXFLOW_SURREAL_ACCESS").context(
+                            "HELIXFLOW_SURREAL_ACCESS is needed to sign in a record user",
+                        )?,
+                        username,
+                        password,
+                    },
+                    other => bail!("Unknown HELIXFLOW_SURREAL_AUTH {other:?}"),
+                })
+            }
+            (None, None) => None,
+            _ => bail!("HELIXFLOW_SURREAL_USER & HELIXFLOW_SURREAL_PASS must be set together"),
+        };
+        Ok(RemoteConfig {
+            endpoint: var("HELIXFLOW_SURREAL_ENDPOINT").unwrap_or(default.endpoint),
+            namespace: var("HELIXFLOW_SURREAL_NAMESPACE").unwrap_or(default.namespace),
+            database: var("HELIXFLOW_SURREAL_DATABASE").unwrap_or(default.database),
+            credentials,
+            query_timeout: default.query_timeout,
+            worker_threads: default.worker_threads,
+        })
+    }
+}
+
+impl SurrealDb<Any> {
+    /// Connect to the server described by `config`, over WebSocket (`ws://`, `wss://`) or
+    /// HTTP (`http://`, `https://`).
+    ///
+    /// Note:
+    /// - The network protocols need the `remote` feature.
+    /// - Everything is stored by the server as it happens, there is no file to save on drop.
+    /// - This is a blocking operation until the server has answered.
+    pub fn connect(config: &RemoteConfig) -> anyhow::Result<Self> {
+        debug!("Initialising tokio runtime");
+        let rt = runtime(config.worker_threads)
+            .context("Initialising dedicated tokio runtime for surreal server connection.")?;
+        let mut options = Config::new();
+        if let Some(timeout) = config.query_timeout {
+            options = options.query_timeout(timeout);
+        }
+        debug!("Connecting to {}", config.endpoint);
+        let db: Surreal<Any> = rt
+            .block_on(any::connect((config.endpoint.as_str(), options)).into_future())
+            .with_context(|| format!("Connecting to {}", config.endpoint))?;
+        if let Some(credentials) = &config.credentials {
+            debug!("Signing in as {}", credentials.username());
+            rt.block_on(signin(&db, config, credentials))
+                .with_context(|| format!("Signing in to {}", config.endpoint))?;
+        }
+        debug!("Selecting database namespace");
+        rt.block_on(
+            db.use_ns(&config.namespace)
+                .use_db(&config.database)
+                .into_future(),
+        )
+        .context("Selecting database namespace")?;
+        let backend = SurrealDb {
+            db,
+            rt: Arc::new(TimedRuntime::new(rt)),
+            file: None,
+            dir: None,
+            actor: RwLock::new(None),
+            namespace: RwLock::new(config.namespace.clone()),
+            loaded: RwLock::new(HashSet::from([config.namespace.clone()])),
+            unsaved: AtomicUsize::new(0),
+            snapshot: Mutex::new(None),
+            config: config.into(),
+        };
+        debug!("Migrating schema");
+        backend.migrate()?;
+        debug!("Done connecting to database");
+        Ok(backend)
+    }
+}
+
+/// Is `error` worth retrying? True for lost connections, timeouts & transaction conflicts, false
+/// for anything the server rejected. Use with `Resilient::retry_if`:
+///
+/// ```ignore
+/// let backend = Resilient::new(SurrealDb::connect(&config)?, RetryPolicy::default(), CircuitBreakerPolicy::default())
+///     .retry_if(helixflow_surreal::is_transient);
+/// ```
+pub fn is_transient(error: &HelixFlowError) -> bool {
+    let HelixFlowError::BackendError(error) = error else {
+        return false;
+    };
+    error.chain().any(|cause| {
+        matches!(
+            cause.downcast_ref::<surrealdb::Error>(),
+            Some(
+                surrealdb::Error::Api(Api::Ws(_) | Api::Http(_) | Api::ConnectionUninitialised)
+                    | surrealdb::Error::Db(Db::QueryTimedout | Db::TxRetryable)
+            )
+        )
+    })
+}
+
+/// Sign in to the namespace & database in `config`, as needed by `credentials`.
+async fn signin(
+    db: &Surreal<Any>,
+    config: &RemoteConfig,
+    credentials: &Credentials,
+) -> surrealdb::Result<()> {
+    match credentials {
+        Credentials::Root { username, password } => {
+            db.signin(Root { username, password }).await?;
+        }
+        Credentials::Namespace { username, password } => {
+            db.signin(Namespace {
+                namespace: &config.namespace,
+                username,
+                password,
+            })
+            .await?;
+        }
+        Credentials::Database { username, password } => {
+            db.signin(Database {
+                namespace: &config.namespace,
+                database: &config.database,
+                username,
+                password,
+            })
+            .await?;
+        }
+        Credentials::Record {
+            access,
+            username,
+            password,
+        } => {
+            db.signin(Record {
+                namespace: &config.namespace,
+                database: &config.database,
+                access,
+                params: RecordParams { username, password },
+            })
+            .await?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+#[coverage(off)]
+mod tests {
+    use helixflow_core::{CRUD, task::Task};
+
+    use super::*;
+
+    #[test]
+    fn connect_in_memory() {
+        let backend = SurrealDb::connect(&RemoteConfig {
+            endpoint: "mem://".into(),
+            query_timeout: Some(Duration::from_secs(5)),
+            ..Default::default()
+        })
+        .unwrap();
+        let task = Task::new("Remote task", None);
+        task.create(&backend).unwrap();
+        assert_eq!(Task::get(&backend, &task.id).unwrap(), task);
+    }
+
+    #[test]
+    fn unsupported_endpoint() {
+        let connected = SurrealDb::connect(&RemoteConfig {
+            endpoint: "carrier-pigeon://loft".into(),
+            ..Default::default()
+        });
+        assert!(connected.is_err());
+    }
+
+    #[test]
+    fn transient_errors() {
+        let dropped =
+            anyhow::Error::from(surrealdb::Error::Api(Api::Ws("Connection reset".into())))
+                .context("Creating task");
+        assert!(is_transient(&dropped.into()));
+        let timeout = anyhow::Error::from(surrealdb::Error::Db(Db::QueryTimedout));
+        assert!(is_transient(&timeout.into()));
+        assert!(!is_transient(&anyhow::anyhow!("Parse error").into()));
+        assert!(!is_transient(&HelixFlowError::ValidationFailed {
+            field: "name".into(),
+            reason: "must not be blank".into()
+        }));
+    }
+
+    #[test]
+    fn config_from_environment() {
+        let vars = |vars: &'static [(&'static str, &'static str)]| {
+            move |name: &str| {
+                vars.iter()
+                    .find(|(var, _)| *var == name)
+                    .map(|(_, value)| value.to_string())
+            }
+        };
+        assert_eq!(
+            RemoteConfig::from_vars(vars(&[])).unwrap(),
+            RemoteConfig::default()
+        );
+
+        let config = RemoteConfig::from_vars(vars(&[
+            ("HELIXFLOW_SURREAL_ENDPOINT", "wss://surreal.example.com"),
+            ("HELIXFLOW_SURREAL_USER", "me"),
+            ("HELIXFLOW_SURREAL_PASS", "secret"),
+            ("HELIXFLOW_SURREAL_AUTH", "record"),
+            ("HELIXFLOW_SURREAL_ACCESS", "account"),
+        ]))
+        .unwrap();
+        assert_eq!(config.endpoint, "wss://surreal.example.com");
+        assert_eq!(config.namespace, "HelixFlow");
+        assert_eq!(
+            config.credentials,
+            Some(Credentials::Record {
+                access: "account".into(),
+                username: "me".into(),
+                password: "secret".into()
+            })
+        );
+
+        let root = RemoteConfig::from_vars(vars(&[
+            ("HELIXFLOW_SURREAL_USER", "root"),
+            ("HELIXFLOW_SURREAL_PASS", "root"),
+        ]))
+        .unwrap();
+        assert_eq!(root.credentials.unwrap().username(), "root");
+
+        assert!(RemoteConfig::from_vars(vars(&[("HELIXFLOW_SURREAL_USER", "me")])).is_err());
+        assert!(
+            RemoteConfig::from_vars(vars(&[
+                ("HELIXFLOW_SURREAL_USER", "me"),
+                ("HELIXFLOW_SURREAL_PASS", "secret"),
+                ("HELIXFLOW_SURREAL_AUTH", "scope"),
+            ]))
+            .is_err()
+        );
+    }
+}
@@ -0,0 +1,159 @@
+//! The database schema, built up one `Migration` at a time so that databases & exports made by
+//! older versions of HelixFlow are upgraded when opened, rather than misread.
+//!
+//! Each namespace stores how many migrations have been applied to it as its `schema_version`.
+
+use anyhow::{Context, bail};
+use log::debug;
+use serde::Deserialize;
+use surrealdb::Connection;
+
+use crate::SurrealDb;
+
+/// One change to the schema.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Migration {
+    pub description: &'static str,
+    /// SurrealQL, run in one transaction with storing the new `schema_version`.
+    pub statements: &'static str,
+}
+
+/// Every change to the schema, oldest first. Never change a released migration: add a new one.
+///
+/// Exports from before `schema_version` was stored may already include some of these
+/// definitions, so use `IF NOT EXISTS` and only update records which need it.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        description: "Full-text search on task names & descriptions",
+        statements: "DEFINE ANALYZER IF NOT EXISTS helixflow_text \
+            TOKENIZERS class FILTERS lowercase, ascii, snowball(english); \
+            DEFINE INDEX IF NOT EXISTS tasks_name_search ON TABLE Tasks \
+            FIELDS name SEARCH ANALYZER helixflow_text BM25; \
+            DEFINE INDEX IF NOT EXISTS tasks_description_search ON TABLE Tasks \
+            FIELDS description SEARCH ANALYZER helixflow_text BM25;",
+    },
+    Migration {
+        description: "Status, priority & version for tasks stored before they existed",
+        statements: "DEFINE TABLE IF NOT EXISTS Tasks SCHEMALESS; \
+            DEFINE FIELD IF NOT EXISTS status ON TABLE Tasks DEFAULT 'Todo'; \
+            DEFINE FIELD IF NOT EXISTS priority ON TABLE Tasks DEFAULT 'P2'; \
+            DEFINE FIELD IF NOT EXISTS version ON TABLE Tasks DEFAULT 0; \
+            UPDATE Tasks SET status = 'Todo' WHERE status IS NONE; \
+            UPDATE Tasks SET priority = 'P2' WHERE priority IS NONE; \
+            UPDATE Tasks SET version = 0 WHERE version IS NONE;",
+    },
+    Migration {
+        description: "Prefix search on task names, for search as you type",
+        statements: "DEFINE ANALYZER IF NOT EXISTS helixflow_prefix \
+            TOKENIZERS class FILTERS lowercase, ascii, edgengram(1, 20); \
+            DEFINE INDEX IF NOT EXISTS tasks_name_prefix ON TABLE Tasks \
+            FIELDS name SEARCH ANALYZER helixflow_prefix BM25;",
+    },
+];
+
+#[derive(Debug, Deserialize)]
+struct SchemaVersion {
+    version: usize,
+}
+
+impl<C: Connection> SurrealDb<C> {
+    /// How many `MIGRATIONS` have been applied to the namespace in use.
+    pub fn schema_version(&self) -> anyhow::Result<usize> {
+        let stored: Option<SchemaVersion> = self
+            .rt
+            .block_on(
+                self.db
+                    .select(("helixflow_schema", "current"))
+                    .into_future(),
+            )
+            .context("Reading schema version")?;
+        Ok(stored.map_or(0, |stored| stored.version))
+    }
+
+    /// Apply any `MIGRATIONS` newer than the namespace's `schema_version`. Refuses to use a
+    /// namespace migrated by a newer version of HelixFlow, which this one might corrupt.
+    pub(crate) fn migrate(&self) -> anyhow::Result<()> {
+        let current = self.schema_version()?;
+        if current > MIGRATIONS.len() {
+            bail!(
+                "The database has schema version {current}, this version of HelixFlow only \
+                understands up to {}",
+                MIGRATIONS.len()
+            );
+        }
+        for (applied, migration) in MIGRATIONS.iter().enumerate().skip(current) {
+            let version = applied + 1;
+            debug!(
+                "Migrating schema to version {version}: {}",
+                migration.description
+            );
+            self.rt
+                .block_on(
+                    self.db
+                        .query("BEGIN TRANSACTION")
+                        .query(migration.statements)
+                        .query("UPSERT helixflow_schema:current SET version = $version")
+                        .query("COMMIT TRANSACTION")
+                        .bind(("version", version))
+                        .into_future(),
+                )
+                .and_then(|response| response.check())
+                .with_context(|| format!("Migrating schema: {}", migration.description))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[coverage(off)]
+mod tests {
+    use helixflow_core::{CRUD, search::Search, task::Task};
+
+    use super::*;
+
+    #[test]
+    fn migrate_old_tasks() {
+        let backend = SurrealDb::new(None).unwrap();
+        assert_eq!(backend.schema_version().unwrap(), MIGRATIONS.len());
+
+        // As stored before tasks had a status, priority or version
+        let task = Task::new("Old task", None);
+        backend
+            .rt
+            .block_on(
+                backend
+                    .db
+                    .query("DELETE helixflow_schema; CREATE $id SET name = 'Old task'")
+                    .bind(("id", crate::SurrealTask::from(&task).id))
+                    .into_future(),
+            )
+            .unwrap()
+            .check()
+            .unwrap();
+        assert_eq!(backend.schema_version().unwrap(), 0);
+
+        backend.migrate().unwrap();
+        assert_eq!(backend.schema_version().unwrap(), MIGRATIONS.len());
+        assert_eq!(Task::get(&backend, &task.id).unwrap(), task);
+        assert_eq!(backend.search("old").unwrap().count(), 1);
+        // Nothing left to do
+        backend.migrate().unwrap();
+    }
+
+    #[test]
+    fn newer_schema() {
+        let backend = SurrealDb::new(None).unwrap();
+        backend
+            .rt
+            .block_on(
+                backend
+                    .db
+                    .query("UPSERT helixflow_schema:current SET version = 1000")
+                    .into_future(),
+            )
+            .unwrap()
+            .check()
+            .unwrap();
+        assert!(backend.migrate().is_err());
+    }
+}
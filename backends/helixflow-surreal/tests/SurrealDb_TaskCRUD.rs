@@ -4,7 +4,6 @@
 #![cfg(false)]
 #![cfg(test)]
 
-use assert_unordered::assert_eq_unordered_sort;
 use std::assert_matches::assert_matches;
 
 use surrealdb::Uuid;
@@ -85,6 +84,5 @@ fn create_two_tasks_in_tasklist() {
         .unwrap()
         .map(|link| link.right.unwrap())
         .collect();
-    // TODO - Return in sorted order using a sort-criteria stored in the relationship
-    assert_eq_unordered_sort!(tasks, vec![task2, task3]);
+    assert_eq!(tasks, vec![task2, task3]);
 }
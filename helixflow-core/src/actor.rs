@@ -0,0 +1,71 @@
+//! Who made a change. There is no multi-user auth, so this is simply whatever the local user
+//! calls themselves plus an id for the device, enough to tell people apart in a shared database.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// A person on a specific device.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Actor {
+    pub name: String,
+    pub device: Uuid,
+}
+
+impl Actor {
+    pub fn new<S: Into<String>>(name: S, device: &Uuid) -> Self {
+        Actor {
+            name: name.into(),
+            device: *device,
+        }
+    }
+
+    /// The current OS user on a newly identified device.
+    pub fn local() -> Self {
+        let name = std::env::var("USER")
+            .or_else(|_| std::env::var("USERNAME"))
+            .unwrap_or_else(|_| "unknown".into());
+        Actor::new(name, &Uuid::now_v7())
+    }
+}
+
+/// Who made a change and when. Recorded alongside mutations & comments.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Annotation {
+    pub by: Actor,
+    pub at: DateTime<Utc>,
+}
+
+impl Annotation {
+    /// `actor` made a change just now.
+    pub fn now(actor: &Actor) -> Self {
+        Annotation {
+            by: actor.clone(),
+            at: Utc::now(),
+        }
+    }
+}
+
+#[cfg(test)]
+#[coverage(off)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn local_actors_have_distinct_devices() {
+        let first = Actor::local();
+        let second = Actor::local();
+        assert!(!first.name.is_empty());
+        assert_eq!(first.name, second.name);
+        assert_ne!(first.device, second.device);
+    }
+
+    #[test]
+    fn annotate() {
+        let actor = Actor::new("Alice", &Uuid::now_v7());
+        let before = Utc::now();
+        let annotation = Annotation::now(&actor);
+        assert_eq!(annotation.by, actor);
+        assert!(annotation.at >= before);
+    }
+}
@@ -0,0 +1,87 @@
+//! Aggregated figures about the contents of a workspace, for dashboards and reports.
+
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use serde::Serialize;
+
+use crate::{
+    HelixFlowResult, Relate,
+    task::{Contains, Task, TaskList},
+};
+
+/// Headline numbers for a single `TaskList`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TaskListSummary {
+    pub tasklist: TaskList,
+    pub total: usize,
+    pub overdue: usize,
+    pub due_today: usize,
+}
+
+impl TaskListSummary {
+    /// Summarise `tasklist` as seen at `now` by someone in `tz`.
+    pub fn of<B>(
+        tasklist: &TaskList,
+        backend: &B,
+        now: DateTime<Utc>,
+        tz: Tz,
+    ) -> HelixFlowResult<Self>
+    where
+        B: Relate<Contains<TaskList, Task>>,
+    {
+        let mut summary = TaskListSummary {
+            tasklist: tasklist.clone(),
+            total: 0,
+            overdue: 0,
+            due_today: 0,
+        };
+        for link in tasklist.get_linked_items(backend)? {
+            let task = link.right?;
+            summary.total += 1;
+            if task.is_overdue(now, tz) {
+                summary.overdue += 1;
+            }
+            if task.is_due_today(now, tz) {
+                summary.due_today += 1;
+            }
+        }
+        Ok(summary)
+    }
+}
+
+#[cfg(test)]
+#[coverage(off)]
+mod tests {
+    use std::assert_matches::assert_matches;
+
+    use uuid::uuid;
+
+    use super::*;
+    use crate::{HelixFlowError, task::TestBackend};
+
+    #[test]
+    fn summarise_tasklist() {
+        let tasklist = TaskList {
+            name: "Test TaskList 1".into(),
+            id: uuid!("0196fe23-7c01-7d6b-9e09-5968eb370549"),
+            sort: Default::default(),
+        };
+        let summary = TaskListSummary::of(&tasklist, &TestBackend, Utc::now(), Tz::UTC).unwrap();
+        assert_eq!(
+            summary,
+            TaskListSummary {
+                tasklist,
+                total: 2,
+                overdue: 0,
+                due_today: 0
+            }
+        );
+    }
+
+    #[test]
+    fn unknown_tasklist() {
+        let tasklist = TaskList::new("Unknown");
+        let err = TaskListSummary::of(&tasklist, &TestBackend, Utc::now(), Tz::UTC).unwrap_err();
+        assert_matches!(err, HelixFlowError::NotFound { .. });
+    }
+}
@@ -0,0 +1,255 @@
+//! Snapshots of finished sprints, written to disk so the live database only needs to hold
+//! current work.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use serde::Serialize;
+
+use crate::{
+    CRUD, HelixFlowResult, Relate, Store,
+    analytics::TaskListSummary,
+    dates::local_date,
+    task::{Contains, Status, Task, TaskList},
+    transaction::{Transaction, atomically},
+};
+
+/// Everything in a sprint at the moment it was closed, plus its final figures.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SprintArchive {
+    pub closed: DateTime<Utc>,
+    pub timezone: Tz,
+    pub summary: TaskListSummary,
+    pub tasks: Vec<Task>,
+}
+
+impl SprintArchive {
+    /// Snapshot `sprint` as it stands at `now`, with figures as seen by someone in `tz`.
+    pub fn of<B>(
+        sprint: &TaskList,
+        backend: &B,
+        now: DateTime<Utc>,
+        tz: Tz,
+    ) -> HelixFlowResult<Self>
+    where
+        B: Relate<Contains<TaskList, Task>>,
+    {
+        let summary = TaskListSummary::of(sprint, backend, now, tz)?;
+        let tasks = sprint
+            .get_linked_items(backend)?
+            .map(|link| link.right)
+            .collect::<HelixFlowResult<Vec<Task>>>()?;
+        Ok(SprintArchive {
+            closed: now,
+            timezone: tz,
+            summary,
+            tasks,
+        })
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("archive contains only serializable data")
+    }
+
+    /// A human-readable summary of the sprint.
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = format!(
+            "# {}\n\nClosed {}\n\n| Tasks | Overdue |\n| ----- | ------- |\n| {} | {} |\n\n## Tasks\n\n",
+            self.summary.tasklist.name,
+            local_date(self.closed, self.timezone),
+            self.summary.total,
+            self.summary.overdue,
+        );
+        for task in &self.tasks {
+            markdown.push_str(&format!("- {}\n", task.name));
+        }
+        markdown
+    }
+
+    /// Write `<date>-<sprint id>.json` & `.md` into `dir`, creating it if needed. Returns the
+    /// paths written.
+    pub fn write_to(&self, dir: &Path) -> HelixFlowResult<(PathBuf, PathBuf)> {
+        let stem = format!(
+            "{}-{}",
+            local_date(self.closed, self.timezone),
+            self.summary.tasklist.id
+        );
+        let json = dir.join(format!("{stem}.json"));
+        let markdown = dir.join(format!("{stem}.md"));
+        fs::create_dir_all(dir).map_err(anyhow::Error::from)?;
+        fs::write(&json, self.to_json()).map_err(anyhow::Error::from)?;
+        fs::write(&markdown, self.to_markdown()).map_err(anyhow::Error::from)?;
+        Ok((json, markdown))
+    }
+}
+
+impl TaskList {
+    /// Close this list as a finished sprint: snapshot it, write the archive to `dir` and delete
+    /// the finished tasks, which are now only kept in the archive. Unfinished tasks stay in the
+    /// list, to be carried over to the next sprint.
+    pub fn close_sprint<B>(
+        &self,
+        backend: &B,
+        dir: &Path,
+        now: DateTime<Utc>,
+        tz: Tz,
+    ) -> HelixFlowResult<SprintArchive>
+    where
+        B: Relate<Contains<TaskList, Task>> + Store<Task> + Transaction,
+    {
+        atomically(backend, || {
+            let archive = SprintArchive::of(self, backend, now, tz)?;
+            archive.write_to(dir)?;
+            for task in &archive.tasks {
+                if task.status == Status::Done {
+                    task.delete(backend)?;
+                }
+            }
+            Ok(archive)
+        })
+    }
+}
+
+#[cfg(test)]
+#[coverage(off)]
+mod tests {
+    use std::sync::Mutex;
+
+    use chrono::TimeZone;
+    use uuid::{Uuid, uuid};
+
+    use super::*;
+    use crate::{HelixFlowError, Link, task::TestBackend};
+
+    /// A single sprint, holding whichever tasks are stored.
+    #[derive(Default)]
+    struct SprintBackend {
+        tasks: Mutex<Vec<Task>>,
+    }
+
+    impl Store<Task> for SprintBackend {
+        fn create(&self, task: &Task) -> HelixFlowResult<Task> {
+            self.tasks.lock().unwrap().push(task.clone());
+            Ok(task.clone())
+        }
+        fn get(&self, id: &Uuid) -> HelixFlowResult<Task> {
+            self.tasks
+                .lock()
+                .unwrap()
+                .iter()
+                .find(|task| task.id == *id)
+                .cloned()
+                .ok_or(HelixFlowError::NotFound {
+                    itemtype: "Task".into(),
+                    id: *id,
+                })
+        }
+        fn update(&self, task: &Task) -> HelixFlowResult<Task> {
+            self.delete(&task.id)?;
+            self.create(task)
+        }
+        fn delete(&self, id: &Uuid) -> HelixFlowResult<()> {
+            self.tasks.lock().unwrap().retain(|task| task.id != *id);
+            Ok(())
+        }
+    }
+
+    impl Relate<Contains<TaskList, Task>> for SprintBackend {
+        fn create_linked_item(
+            &self,
+            link: &Contains<TaskList, Task>,
+        ) -> HelixFlowResult<Contains<TaskList, Task>> {
+            let tasklist = link.left.as_ref().unwrap();
+            let task = link.right.as_ref().unwrap();
+            self.create(task)?;
+            Ok(tasklist.link(task))
+        }
+        fn get_linked_items(
+            &self,
+            left: &TaskList,
+        ) -> HelixFlowResult<impl Iterator<Item = Contains<TaskList, Task>>> {
+            let links: Vec<_> = self
+                .tasks
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|task| left.link(task))
+                .collect();
+            Ok(links.into_iter())
+        }
+        fn delete_link(&self, link: &Contains<TaskList, Task>) -> HelixFlowResult<()> {
+            self.delete(&link.right.as_ref().unwrap().id)
+        }
+    }
+
+    impl Transaction for SprintBackend {}
+
+    fn sprint() -> TaskList {
+        TaskList {
+            name: "Test TaskList 1".into(),
+            id: uuid!("0196fe23-7c01-7d6b-9e09-5968eb370549"),
+            sort: Default::default(),
+        }
+    }
+
+    #[test]
+    fn markdown_summary() {
+        let now = Utc.with_ymd_and_hms(2025, 6, 6, 23, 0, 0).unwrap();
+        let archive =
+            SprintArchive::of(&sprint(), &TestBackend, now, chrono_tz::Europe::Berlin).unwrap();
+        assert_eq!(
+            archive.to_markdown(),
+            "# Test TaskList 1\n\nClosed 2025-06-07\n\n\
+            | Tasks | Overdue |\n| ----- | ------- |\n| 2 | 0 |\n\n\
+            ## Tasks\n\n- Task 1\n- Task 2\n"
+        );
+    }
+
+    #[test]
+    fn close_sprint() {
+        let dir = std::env::temp_dir().join(Uuid::now_v7().to_string());
+        let now = Utc.with_ymd_and_hms(2025, 6, 6, 12, 0, 0).unwrap();
+        let archive = sprint()
+            .close_sprint(&TestBackend, &dir, now, Tz::UTC)
+            .unwrap();
+        let json =
+            fs::read_to_string(dir.join("2025-06-06-0196fe23-7c01-7d6b-9e09-5968eb370549.json"))
+                .unwrap();
+        assert_eq!(json, archive.to_json());
+        assert!(
+            dir.join("2025-06-06-0196fe23-7c01-7d6b-9e09-5968eb370549.md")
+                .exists()
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn close_sprint_deletes_done_tasks() {
+        let backend = SprintBackend::default();
+        let sprint = sprint();
+        let done = Task {
+            status: Status::Done,
+            ..Task::new("Finished", None)
+        };
+        let carried_over = Task::new("Carried over", None);
+        for task in [&done, &carried_over] {
+            sprint.link(task).create_linked_item(&backend).unwrap();
+        }
+        let dir = std::env::temp_dir().join(Uuid::now_v7().to_string());
+        let now = Utc.with_ymd_and_hms(2025, 6, 6, 12, 0, 0).unwrap();
+
+        let archive = sprint.close_sprint(&backend, &dir, now, Tz::UTC).unwrap();
+        assert_eq!(archive.tasks, vec![done, carried_over.clone()]);
+        let remaining: Vec<Task> = sprint
+            .get_linked_items(&backend)
+            .unwrap()
+            .map(|link| link.right.unwrap())
+            .collect();
+        assert_eq!(remaining, vec![carried_over]);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
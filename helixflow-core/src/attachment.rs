@@ -0,0 +1,209 @@
+//! Files attached to tasks.
+
+use std::{
+    borrow::Cow,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use uuid::{Uuid, uuid};
+
+use crate::{
+    HelixFlowItem, HelixFlowResult, Linkable, Relate, Relationship, Validate,
+    task::{Task, TestBackend},
+};
+
+/// Where the file itself is.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum AttachmentContent {
+    /// Stored along with the attachment
+    Blob(Vec<u8>),
+    /// Left where it is, e.g. on a shared drive
+    Path(PathBuf),
+}
+
+/// A file attached to a task.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, HelixFlowItem)]
+pub struct Attachment {
+    pub filename: Cow<'static, str>,
+    pub mime_type: Cow<'static, str>,
+    pub content: AttachmentContent,
+    pub id: Uuid,
+}
+
+impl Validate for Attachment {}
+
+impl Attachment {
+    /// Create a new `Attachment` with valid `id`, suitable for usage as database key.
+    pub fn new<S1, S2>(filename: S1, mime_type: S2, content: AttachmentContent) -> Attachment
+    where
+        S1: Into<Cow<'static, str>>,
+        S2: Into<Cow<'static, str>>,
+    {
+        Attachment {
+            filename: filename.into(),
+            mime_type: mime_type.into(),
+            content,
+            id: Uuid::now_v7(),
+        }
+    }
+
+    /// Attach a copy of the file at `path`, with the mime type guessed from its extension.
+    pub fn copy_of(path: &Path) -> io::Result<Attachment> {
+        Ok(Attachment::new(
+            filename(path),
+            mime_type_for(path),
+            AttachmentContent::Blob(fs::read(path)?),
+        ))
+    }
+
+    /// Refer to the file at `path` without copying it, with the mime type guessed from its
+    /// extension.
+    pub fn link_to(path: &Path) -> Attachment {
+        Attachment::new(
+            filename(path),
+            mime_type_for(path),
+            AttachmentContent::Path(path.to_path_buf()),
+        )
+    }
+
+    /// The contents of the file, read from disk if it is not stored in the attachment.
+    pub fn bytes(&self) -> io::Result<Cow<'_, [u8]>> {
+        match &self.content {
+            AttachmentContent::Blob(bytes) => Ok(Cow::Borrowed(bytes)),
+            AttachmentContent::Path(path) => Ok(Cow::Owned(fs::read(path)?)),
+        }
+    }
+}
+
+fn filename(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+/// Common file types, anything else is `application/octet-stream`.
+fn mime_type_for(path: &Path) -> &'static str {
+    let extension = path
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_lowercase());
+    match extension.as_deref() {
+        Some("txt") => "text/plain",
+        Some("md") => "text/markdown",
+        Some("csv") => "text/csv",
+        Some("html" | "htm") => "text/html",
+        Some("json") => "application/json",
+        Some("pdf") => "application/pdf",
+        Some("zip") => "application/zip",
+        Some("png") => "image/png",
+        Some("jpg" | "jpeg") => "image/jpeg",
+        Some("gif") => "image/gif",
+        Some("svg") => "image/svg+xml",
+        _ => "application/octet-stream",
+    }
+}
+
+impl Task {
+    /// Attach `attachment` to this task, ready for `create_linked_item`.
+    pub fn attach(&self, attachment: &Attachment) -> HasAttachment<Task, Attachment> {
+        Linkable::<HasAttachment<Task, Attachment>>::link(self, attachment)
+    }
+
+    /// All files attached to this task.
+    pub fn attachments<B>(&self, backend: &B) -> HelixFlowResult<Vec<Attachment>>
+    where
+        B: Relate<HasAttachment<Task, Attachment>>,
+    {
+        Linkable::<HasAttachment<Task, Attachment>>::get_linked_items(self, backend)?
+            .map(|attached| attached.right)
+            .collect()
+    }
+}
+
+/// `right` is attached to `left`.
+///
+/// The task must already exist, `Relate::create_linked_item` creates the `Attachment`.
+#[derive(Debug, Relationship)]
+#[relationship(Task => Attachment)]
+pub struct HasAttachment<LEFT, RIGHT> {
+    pub left: HelixFlowResult<LEFT>,
+    pub right: HelixFlowResult<RIGHT>,
+}
+
+/// `Task 1` has `notes.txt` attached, no other task has attachments.
+impl Relate<HasAttachment<Task, Attachment>> for TestBackend {
+    fn create_linked_item(
+        &self,
+        link: &HasAttachment<Task, Attachment>,
+    ) -> HelixFlowResult<HasAttachment<Task, Attachment>> {
+        Ok(HasAttachment {
+            left: Ok(link.left.as_ref().unwrap().clone()),
+            right: Ok(link.right.as_ref().unwrap().clone()),
+        })
+    }
+    fn get_linked_items(
+        &self,
+        left: &Task,
+    ) -> HelixFlowResult<impl Iterator<Item = HasAttachment<Task, Attachment>>> {
+        let attachments = match left.id.to_string().as_str() {
+            "0196b4c9-8447-7959-ae1f-72c7c8a3dd36" => vec![Attachment {
+                filename: "notes.txt".into(),
+                mime_type: "text/plain".into(),
+                content: AttachmentContent::Blob(b"Remember the milk".to_vec()),
+                id: uuid!("0197c3e5-2b3c-7d4e-9f5a-6b7c8d9e0f1a"),
+            }],
+            _ => vec![],
+        };
+        let left = left.clone();
+        Ok(attachments
+            .into_iter()
+            .map(move |attachment| left.attach(&attachment)))
+    }
+    fn delete_link(&self, _link: &HasAttachment<Task, Attachment>) -> HelixFlowResult<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[coverage(off)]
+mod tests {
+    use super::*;
+    use crate::CRUD;
+
+    #[test]
+    fn attachments_from_backend() {
+        let task_1 =
+            Task::get(&TestBackend, &uuid!("0196b4c9-8447-7959-ae1f-72c7c8a3dd36")).unwrap();
+        let attachments = task_1.attachments(&TestBackend).unwrap();
+        assert_eq!(attachments.len(), 1);
+        assert_eq!(attachments[0].filename, "notes.txt");
+        assert_eq!(
+            attachments[0].bytes().unwrap().as_ref(),
+            b"Remember the milk"
+        );
+    }
+
+    #[test]
+    fn copy_and_link() {
+        let file = std::env::temp_dir().join(format!("{}.MD", Uuid::now_v7()));
+        fs::write(&file, "# Plan").unwrap();
+
+        let copy = Attachment::copy_of(&file).unwrap();
+        assert_eq!(copy.mime_type, "text/markdown");
+        assert_eq!(copy.content, AttachmentContent::Blob(b"# Plan".to_vec()));
+
+        let link = Attachment::link_to(&file);
+        assert_eq!(link.filename, copy.filename);
+        assert_eq!(link.bytes().unwrap().as_ref(), b"# Plan");
+        fs::remove_file(&file).unwrap();
+        assert!(link.bytes().is_err());
+    }
+
+    #[test]
+    fn unknown_type() {
+        let attachment = Attachment::link_to(Path::new("/tmp/data.bin"));
+        assert_eq!(attachment.filename, "data.bin");
+        assert_eq!(attachment.mime_type, "application/octet-stream");
+    }
+}
@@ -0,0 +1,132 @@
+//! Changing many tasks at once, e.g. to complete a selection, in one backend round trip rather
+//! than one per task.
+//!
+//! See also `CRUD::create_all` & `CRUD::update_all`.
+
+use crate::{
+    CRUD, HelixFlowItem, HelixFlowResult, Relate, Relationship, Store,
+    events::{self, Event},
+    task::{Contains, Task, TaskList},
+};
+
+impl Task {
+    /// Complete all of `tasks` together, returning the completed tasks. Nothing is stored unless
+    /// every task can be completed.
+    pub fn complete_all<B: Store<Task>>(tasks: &[Task], backend: &B) -> HelixFlowResult<Vec<Task>> {
+        let completed = tasks
+            .iter()
+            .map(|task| {
+                let mut task = task.clone();
+                task.complete()?;
+                Ok(task)
+            })
+            .collect::<HelixFlowResult<Vec<Task>>>()?;
+        Task::update_all(&completed, backend)?;
+        Ok(completed)
+    }
+}
+
+impl TaskList {
+    /// Move `tasks` from this list to the end of `to`, keeping their order.
+    pub fn move_all<B>(&self, tasks: &[Task], to: &TaskList, backend: &B) -> HelixFlowResult<()>
+    where
+        B: Relate<Contains<TaskList, Task>>,
+    {
+        let from_links: Vec<Contains<TaskList, Task>> =
+            tasks.iter().map(|task| self.link(task)).collect();
+        let to_links: Vec<Contains<TaskList, Task>> =
+            tasks.iter().map(|task| to.link(task)).collect();
+        backend.move_all(&from_links, &to_links)?;
+        let relationship = <Contains<TaskList, Task> as Relationship>::NAME;
+        for task in tasks {
+            events::emit(Event::Unlinked {
+                relationship,
+                left: self.id,
+                right: *task.id(),
+            });
+            events::emit(Event::Linked {
+                relationship,
+                left: to.id,
+                right: *task.id(),
+            });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[coverage(off)]
+mod tests {
+    use std::assert_matches::assert_matches;
+
+    use uuid::uuid;
+
+    use super::*;
+    use crate::{
+        HelixFlowError,
+        task::{Status, TestBackend},
+    };
+
+    fn known_tasks() -> Vec<Task> {
+        [
+            uuid!("0196b4c9-8447-7959-ae1f-72c7c8a3dd36"),
+            uuid!("0196ca5f-d934-7ec8-b042-ae37b94b8432"),
+        ]
+        .iter()
+        .map(|id| Task::get(&TestBackend, id).unwrap())
+        .collect()
+    }
+
+    #[test]
+    fn complete_tasks() {
+        let completed = Task::complete_all(&known_tasks(), &TestBackend).unwrap();
+        assert!(completed.iter().all(|task| task.status == Status::Done));
+
+        let mut tasks = known_tasks();
+        tasks[1].status = Status::Blocked;
+        assert_matches!(
+            Task::complete_all(&tasks, &TestBackend),
+            Err(HelixFlowError::InvalidTransition { .. })
+        );
+    }
+
+    #[test]
+    fn create_tasks() {
+        let tasks = vec![Task::new("One", None), Task::new("Two", None)];
+        Task::create_all(&tasks, &TestBackend).unwrap();
+        let invalid = vec![Task::new("Valid", None), Task::new("", None)];
+        assert_matches!(
+            Task::create_all(&invalid, &TestBackend),
+            Err(HelixFlowError::ValidationFailed { .. })
+        );
+        let mismatched = vec![Task::new("One", None), Task::new("MISMATCH", None)];
+        assert_matches!(
+            Task::create_all(&mismatched, &TestBackend),
+            Err(HelixFlowError::Mismatch { .. })
+        );
+    }
+
+    #[test]
+    fn move_tasks() {
+        // `TestBackend` only knows one tasklist
+        let backlog =
+            TaskList::get(&TestBackend, &uuid!("0196fe23-7c01-7d6b-9e09-5968eb370549")).unwrap();
+        backlog
+            .move_all(&known_tasks(), &backlog, &TestBackend)
+            .unwrap();
+    }
+
+    #[test]
+    fn move_to_as_many_places() {
+        let backlog =
+            TaskList::get(&TestBackend, &uuid!("0196fe23-7c01-7d6b-9e09-5968eb370549")).unwrap();
+        let from: Vec<_> = known_tasks()
+            .iter()
+            .map(|task| backlog.link(task))
+            .collect();
+        assert_matches!(
+            TestBackend.move_all(&from, &from[..1]),
+            Err(HelixFlowError::ValidationFailed { .. })
+        );
+    }
+}
@@ -0,0 +1,265 @@
+//! A backend wrapper which remembers recently used items, so that getting the same item again
+//! doesn't need another round trip to the backend.
+
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    sync::Mutex,
+};
+
+use uuid::Uuid;
+
+use crate::{HelixFlowItem, HelixFlowResult, Link, Relate, Store};
+
+/// How many items `CachedBackend::new` remembers.
+pub const DEFAULT_CAPACITY: usize = 1000;
+
+/// Wraps any backend `B` and keeps up to `capacity` items, dropping the least recently used
+/// first.
+///
+/// Items are cached when they are created, got or updated through this wrapper, and forgotten
+/// when they are deleted or an update fails, e.g. with a `Conflict`. Changes made by anything
+/// else which uses the backend are not seen: call `clear` to fetch everything again.
+///
+/// ```ignore
+/// let backend = CachedBackend::new(SurrealDb::new(None)?);
+/// let task = Task::get(&backend, &id)?; // from the backend
+/// let task = Task::get(&backend, &id)?; // from the cache
+/// ```
+#[derive(Debug)]
+pub struct CachedBackend<B> {
+    backend: B,
+    capacity: usize,
+    cache: Mutex<Lru>,
+}
+
+/// Items by type & id, with when each was last used.
+#[derive(Debug, Default)]
+struct Lru {
+    items: HashMap<(TypeId, Uuid), (u64, Box<dyn Any + Send + Sync>)>,
+    clock: u64,
+}
+
+impl Lru {
+    fn get<ITEM: Clone + 'static>(&mut self, id: &Uuid) -> Option<ITEM> {
+        self.clock += 1;
+        let (used, item) = self.items.get_mut(&(TypeId::of::<ITEM>(), *id))?;
+        *used = self.clock;
+        item.downcast_ref::<ITEM>().cloned()
+    }
+
+    fn insert<ITEM: HelixFlowItem + Clone>(&mut self, item: &ITEM, capacity: usize) {
+        self.clock += 1;
+        self.items.insert(
+            (TypeId::of::<ITEM>(), *item.id()),
+            (self.clock, Box::new(item.clone())),
+        );
+        // Only ever one over, so finding the oldest each time is cheap enough
+        if self.items.len() > capacity
+            && let Some(oldest) = self
+                .items
+                .iter()
+                .min_by_key(|(_, (used, _))| *used)
+                .map(|(key, _)| *key)
+        {
+            self.items.remove(&oldest);
+        }
+    }
+
+    fn remove<ITEM: 'static>(&mut self, id: &Uuid) {
+        self.items.remove(&(TypeId::of::<ITEM>(), *id));
+    }
+}
+
+impl<B> CachedBackend<B> {
+    /// Cache up to `DEFAULT_CAPACITY` items from `backend`.
+    pub fn new(backend: B) -> Self {
+        Self::with_capacity(backend, DEFAULT_CAPACITY)
+    }
+
+    pub fn with_capacity(backend: B, capacity: usize) -> Self {
+        CachedBackend {
+            backend,
+            capacity,
+            cache: Mutex::default(),
+        }
+    }
+
+    /// The wrapped backend. Changes made directly are not seen by the cache.
+    pub fn inner(&self) -> &B {
+        &self.backend
+    }
+
+    /// Forget every cached item.
+    pub fn clear(&self) {
+        self.cache.lock().unwrap().items.clear();
+    }
+
+    /// How many items are cached.
+    pub fn len(&self) -> usize {
+        self.cache.lock().unwrap().items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<ITEM, B> Store<ITEM> for CachedBackend<B>
+where
+    ITEM: HelixFlowItem + Clone,
+    B: Store<ITEM>,
+{
+    fn create(&self, item: &ITEM) -> HelixFlowResult<ITEM> {
+        let created = self.backend.create(item)?;
+        self.cache.lock().unwrap().insert(&created, self.capacity);
+        Ok(created)
+    }
+
+    fn get(&self, id: &Uuid) -> HelixFlowResult<ITEM> {
+        if let Some(item) = self.cache.lock().unwrap().get(id) {
+            return Ok(item);
+        }
+        let item = self.backend.get(id)?;
+        self.cache.lock().unwrap().insert(&item, self.capacity);
+        Ok(item)
+    }
+
+    fn update(&self, item: &ITEM) -> HelixFlowResult<ITEM> {
+        self.cache.lock().unwrap().remove::<ITEM>(item.id());
+        let updated = self.backend.update(item)?;
+        self.cache.lock().unwrap().insert(&updated, self.capacity);
+        Ok(updated)
+    }
+
+    fn put(&self, item: &ITEM) -> HelixFlowResult<ITEM> {
+        self.cache.lock().unwrap().remove::<ITEM>(item.id());
+        let stored = self.backend.put(item)?;
+        self.cache.lock().unwrap().insert(&stored, self.capacity);
+        Ok(stored)
+    }
+
+    fn delete(&self, id: &Uuid) -> HelixFlowResult<()> {
+        self.cache.lock().unwrap().remove::<ITEM>(id);
+        self.backend.delete(id)
+    }
+}
+
+/// Links are not cached.
+impl<REL, B> Relate<REL> for CachedBackend<B>
+where
+    REL: Link,
+    B: Relate<REL>,
+{
+    fn create_linked_item(&self, link: &REL) -> HelixFlowResult<REL> {
+        self.backend.create_linked_item(link)
+    }
+
+    fn get_linked_items(&self, left: &REL::Left) -> HelixFlowResult<impl Iterator<Item = REL>> {
+        self.backend.get_linked_items(left)
+    }
+
+    fn delete_link(&self, link: &REL) -> HelixFlowResult<()> {
+        self.backend.delete_link(link)
+    }
+
+    fn create_link(&self, link: &REL) -> HelixFlowResult<()> {
+        self.backend.create_link(link)
+    }
+
+    fn move_all(&self, from: &[REL], to: &[REL]) -> HelixFlowResult<()> {
+        self.backend.move_all(from, to)
+    }
+}
+
+#[cfg(test)]
+#[coverage(off)]
+mod tests {
+    use std::{
+        assert_matches::assert_matches,
+        sync::atomic::{AtomicU32, Ordering},
+    };
+
+    use super::*;
+    use crate::{CRUD, HelixFlowError, task::Task};
+
+    /// Keeps tasks & counts the calls to `get`
+    #[derive(Default)]
+    struct Counting {
+        tasks: Mutex<HashMap<Uuid, Task>>,
+        gets: AtomicU32,
+    }
+
+    impl Store<Task> for Counting {
+        fn create(&self, task: &Task) -> HelixFlowResult<Task> {
+            self.tasks.lock().unwrap().insert(task.id, task.clone());
+            Ok(task.clone())
+        }
+
+        fn get(&self, id: &Uuid) -> HelixFlowResult<Task> {
+            self.gets.fetch_add(1, Ordering::SeqCst);
+            self.tasks
+                .lock()
+                .unwrap()
+                .get(id)
+                .cloned()
+                .ok_or(HelixFlowError::NotFound {
+                    itemtype: "Task".into(),
+                    id: *id,
+                })
+        }
+
+        fn update(&self, task: &Task) -> HelixFlowResult<Task> {
+            self.create(task)
+        }
+
+        fn delete(&self, id: &Uuid) -> HelixFlowResult<()> {
+            self.tasks.lock().unwrap().remove(id);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn repeated_gets() {
+        let backend = CachedBackend::new(Counting::default());
+        let mut task = Task::new("Task 1", None);
+        task.create(&backend).unwrap();
+        assert_eq!(Task::get(&backend, &task.id).unwrap(), task);
+        assert_eq!(Task::get(&backend, &task.id).unwrap(), task);
+        assert_eq!(backend.inner().gets.load(Ordering::SeqCst), 0);
+
+        task.name = "Renamed".into();
+        task.update(&backend).unwrap();
+        assert_eq!(Task::get(&backend, &task.id).unwrap().name, "Renamed");
+
+        backend.clear();
+        assert!(backend.is_empty());
+        Task::get(&backend, &task.id).unwrap();
+        assert_eq!(backend.inner().gets.load(Ordering::SeqCst), 1);
+
+        task.delete(&backend).unwrap();
+        assert_matches!(
+            Task::get(&backend, &task.id),
+            Err(HelixFlowError::NotFound { .. })
+        );
+    }
+
+    #[test]
+    fn least_recently_used() {
+        let backend = CachedBackend::with_capacity(Counting::default(), 2);
+        let tasks: Vec<Task> = (1..=3)
+            .map(|n| Task::new(format!("Task {n}"), None))
+            .collect();
+        tasks[0].create(&backend).unwrap();
+        tasks[1].create(&backend).unwrap();
+        // Task 1 is now more recent than Task 2
+        Task::get(&backend, &tasks[0].id).unwrap();
+        tasks[2].create(&backend).unwrap();
+        assert_eq!(backend.len(), 2);
+
+        Task::get(&backend, &tasks[0].id).unwrap();
+        assert_eq!(backend.inner().gets.load(Ordering::SeqCst), 0);
+        Task::get(&backend, &tasks[1].id).unwrap();
+        assert_eq!(backend.inner().gets.load(Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,177 @@
+//! Which days (and hours) are available for work, for scheduling and capacity planning.
+
+use std::collections::BTreeSet;
+
+use chrono::{DateTime, Datelike, NaiveDate, NaiveTime, TimeDelta, Utc, Weekday};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+use crate::dates::to_utc;
+
+/// Working week, public holidays and daily working hours.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WorkingCalendar {
+    pub working_days: Vec<Weekday>,
+    pub holidays: BTreeSet<NaiveDate>,
+    pub start_of_work: NaiveTime,
+    pub end_of_work: NaiveTime,
+}
+
+impl Default for WorkingCalendar {
+    /// Monday to Friday, 09:00 - 17:00, no holidays
+    fn default() -> Self {
+        WorkingCalendar {
+            working_days: vec![
+                Weekday::Mon,
+                Weekday::Tue,
+                Weekday::Wed,
+                Weekday::Thu,
+                Weekday::Fri,
+            ],
+            holidays: BTreeSet::new(),
+            start_of_work: NaiveTime::from_hms_opt(9, 0, 0).unwrap(),
+            end_of_work: NaiveTime::from_hms_opt(17, 0, 0).unwrap(),
+        }
+    }
+}
+
+impl WorkingCalendar {
+    pub fn is_working_day(&self, date: NaiveDate) -> bool {
+        self.working_days.contains(&date.weekday()) && !self.holidays.contains(&date)
+    }
+
+    /// The date `days` working days after `date`. `date` itself does not count, so
+    /// "in 1 working day" from a Friday is the following Monday.
+    ///
+    /// Returns `date` unchanged if there are no working days at all.
+    pub fn add_working_days(&self, date: NaiveDate, days: u32) -> NaiveDate {
+        if self.working_days.is_empty() {
+            return date;
+        }
+        let mut remaining = days;
+        let mut current = date;
+        while remaining > 0 {
+            current = current
+                .succ_opt()
+                .expect("date within chrono's supported range");
+            if self.is_working_day(current) {
+                remaining -= 1;
+            }
+        }
+        current
+    }
+
+    /// Number of working days in the half-open range `[start, end)`.
+    pub fn working_days_between(&self, start: NaiveDate, end: NaiveDate) -> u32 {
+        start
+            .iter_days()
+            .take_while(|date| *date < end)
+            .filter(|date| self.is_working_day(*date))
+            .count() as u32
+    }
+
+    pub fn hours_per_day(&self) -> TimeDelta {
+        (self.end_of_work - self.start_of_work).max(TimeDelta::zero())
+    }
+
+    /// Total working time available in `[start, end)`, for capacity planning.
+    pub fn capacity(&self, start: NaiveDate, end: NaiveDate) -> TimeDelta {
+        self.hours_per_day() * self.working_days_between(start, end) as i32
+    }
+
+    /// "Due in `days` working days": the end of the working day, `days` working days after
+    /// today in `tz`.
+    pub fn due_in_working_days(&self, now: DateTime<Utc>, days: u32, tz: Tz) -> DateTime<Utc> {
+        let today = now.with_timezone(&tz).date_naive();
+        let due_date = self.add_working_days(today, days);
+        to_utc(due_date.and_time(self.end_of_work), tz)
+    }
+}
+
+#[cfg(test)]
+#[coverage(off)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn weekends_are_skipped() {
+        let calendar = WorkingCalendar::default();
+        // Fri 6th June 2025
+        assert_eq!(
+            calendar.add_working_days(date(2025, 6, 6), 1),
+            date(2025, 6, 9)
+        );
+        assert_eq!(
+            calendar.add_working_days(date(2025, 6, 6), 3),
+            date(2025, 6, 11)
+        );
+    }
+
+    #[test]
+    fn holidays_are_skipped() {
+        let mut calendar = WorkingCalendar::default();
+        calendar.holidays.insert(date(2025, 6, 9));
+        assert!(!calendar.is_working_day(date(2025, 6, 9)));
+        assert_eq!(
+            calendar.add_working_days(date(2025, 6, 6), 1),
+            date(2025, 6, 10)
+        );
+    }
+
+    #[test]
+    fn zero_days() {
+        let calendar = WorkingCalendar::default();
+        assert_eq!(
+            calendar.add_working_days(date(2025, 6, 7), 0),
+            date(2025, 6, 7)
+        );
+    }
+
+    #[test]
+    fn no_working_days() {
+        let calendar = WorkingCalendar {
+            working_days: vec![],
+            ..Default::default()
+        };
+        assert_eq!(
+            calendar.add_working_days(date(2025, 6, 7), 3),
+            date(2025, 6, 7)
+        );
+    }
+
+    #[test]
+    fn capacity() {
+        let mut calendar = WorkingCalendar::default();
+        calendar.holidays.insert(date(2025, 6, 9));
+        // Mon 2nd - Sun 15th: 10 weekdays, 1 holiday
+        assert_eq!(
+            calendar.working_days_between(date(2025, 6, 2), date(2025, 6, 16)),
+            9
+        );
+        assert_eq!(
+            calendar.capacity(date(2025, 6, 2), date(2025, 6, 16)),
+            TimeDelta::hours(72)
+        );
+    }
+
+    #[test]
+    fn due_in_working_days() {
+        let calendar = WorkingCalendar::default();
+        // Friday evening in Berlin, already Saturday in Tokyo
+        let now = Utc.with_ymd_and_hms(2025, 6, 6, 16, 0, 0).unwrap();
+        assert_eq!(
+            calendar.due_in_working_days(now, 1, chrono_tz::Europe::Berlin),
+            Utc.with_ymd_and_hms(2025, 6, 9, 15, 0, 0).unwrap()
+        );
+        assert_eq!(
+            calendar.due_in_working_days(now, 1, chrono_tz::Asia::Tokyo),
+            Utc.with_ymd_and_hms(2025, 6, 9, 8, 0, 0).unwrap()
+        );
+    }
+}
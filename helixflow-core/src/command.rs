@@ -0,0 +1,148 @@
+//! Changes made by the user which can be undone & redone.
+//!
+//! Each `Command` records enough to both `apply` & `revert` itself, so `State` only needs to keep
+//! a stack of the commands it has executed.
+
+use std::borrow::Cow;
+
+use crate::{
+    CRUD, HelixFlowResult, Link, Relate, Store,
+    task::{Contains, Status, Task, TaskList},
+};
+
+/// A single undoable change.
+///
+/// Commands hold the task as it was _before_ the change, which `revert` restores.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    /// Create `task` at the end of `tasklist`
+    CreateTask { task: Task, tasklist: TaskList },
+    /// Give `task` a new `name`
+    Rename { task: Task, name: Cow<'static, str> },
+    /// Move `task` from the end of one list to the end of another
+    Move {
+        task: Task,
+        from: TaskList,
+        to: TaskList,
+    },
+    /// Move `task` to `status` in its workflow, e.g. to complete it
+    Transition { task: Task, status: Status },
+}
+
+impl Command {
+    /// Finish `task`.
+    pub fn complete(task: &Task) -> Command {
+        Command::Transition {
+            task: task.clone(),
+            status: Status::Done,
+        }
+    }
+
+    /// The task changed by this command
+    pub fn task(&self) -> &Task {
+        match self {
+            Command::CreateTask { task, .. }
+            | Command::Rename { task, .. }
+            | Command::Move { task, .. }
+            | Command::Transition { task, .. } => task,
+        }
+    }
+
+    /// Make the change in `backend`.
+    pub fn apply<B>(&self, backend: &B) -> HelixFlowResult<()>
+    where
+        B: Store<Task> + Relate<Contains<TaskList, Task>>,
+    {
+        match self {
+            Command::CreateTask { task, tasklist } => {
+                tasklist.link(task).create_linked_item(backend)
+            }
+            Command::Rename { task, name } => Task {
+                name: name.clone(),
+                ..task.clone()
+            }
+            .update(backend),
+            Command::Move { task, from, to } => move_task(task, from, to, backend),
+            Command::Transition { task, status } => {
+                let mut changed = task.clone();
+                changed.transition(*status)?;
+                changed.update(backend)
+            }
+        }
+    }
+
+    /// Undo the change in `backend`, which must have been `apply`ed.
+    pub fn revert<B>(&self, backend: &B) -> HelixFlowResult<()>
+    where
+        B: Store<Task> + Relate<Contains<TaskList, Task>>,
+    {
+        match self {
+            Command::CreateTask { task, .. } => Store::<Task>::delete(backend, &task.id),
+            Command::Rename { task, .. } | Command::Transition { task, .. } => task.update(backend),
+            Command::Move { task, from, to } => move_task(task, to, from, backend),
+        }
+    }
+}
+
+fn move_task<B>(task: &Task, from: &TaskList, to: &TaskList, backend: &B) -> HelixFlowResult<()>
+where
+    B: Store<Task> + Relate<Contains<TaskList, Task>>,
+{
+    from.unlink(task, backend)?;
+    to.link(task).create_linked_item(backend)
+}
+
+#[cfg(test)]
+#[coverage(off)]
+mod tests {
+    use std::assert_matches::assert_matches;
+
+    use uuid::uuid;
+
+    use super::*;
+    use crate::{HelixFlowError, task::TestBackend};
+
+    #[test]
+    fn apply_and_revert() {
+        let task = Task::get(&TestBackend, &uuid!("0196b4c9-8447-7959-ae1f-72c7c8a3dd36")).unwrap();
+        // `TestBackend` only knows one tasklist & can only delete the tasks it knows
+        let new_task =
+            Task::get(&TestBackend, &uuid!("0196ca5f-d934-7ec8-b042-ae37b94b8432")).unwrap();
+        let backlog =
+            TaskList::get(&TestBackend, &uuid!("0196fe23-7c01-7d6b-9e09-5968eb370549")).unwrap();
+        let commands = [
+            Command::CreateTask {
+                task: new_task,
+                tasklist: backlog.clone(),
+            },
+            Command::Rename {
+                task: task.clone(),
+                name: "Renamed".into(),
+            },
+            Command::Move {
+                task: task.clone(),
+                from: backlog.clone(),
+                to: backlog,
+            },
+            Command::complete(&task),
+        ];
+        for command in commands {
+            command.apply(&TestBackend).unwrap();
+            command.revert(&TestBackend).unwrap();
+        }
+    }
+
+    #[test]
+    fn invalid_transition() {
+        let blocked = Task {
+            status: Status::Blocked,
+            ..Task::new("Blocked", None)
+        };
+        let complete = Command::complete(&blocked);
+        assert_eq!(complete.task(), &blocked);
+        assert_matches!(
+            complete.apply(&TestBackend),
+            Err(HelixFlowError::InvalidTransition { .. })
+        );
+    }
+}
@@ -0,0 +1,254 @@
+//! Comments on tasks, with `@mentions` and notifications for everyone involved.
+
+use chrono::{TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::{Uuid, uuid};
+
+use crate::{
+    CRUD, HelixFlowItem, HelixFlowResult, Store, Validate,
+    actor::{Actor, Annotation},
+    task::{Task, TestBackend},
+    user::{User, Watch},
+};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, HelixFlowItem)]
+pub struct Comment {
+    pub id: Uuid,
+    /// The commented `Task`
+    pub task: Uuid,
+    pub text: String,
+    pub annotation: Annotation,
+}
+
+impl Validate for Comment {}
+
+/// A backend which stores comments.
+pub trait Comments {
+    /// The comments on `task`, oldest first.
+    fn comments_on(&self, task: &Task) -> HelixFlowResult<Vec<Comment>>;
+}
+
+/// `Task 1` has one comment, by alice.
+impl Comments for TestBackend {
+    fn comments_on(&self, task: &Task) -> HelixFlowResult<Vec<Comment>> {
+        Ok(match task.id.to_string().as_str() {
+            "0196b4c9-8447-7959-ae1f-72c7c8a3dd36" => vec![Comment {
+                id: uuid!("0199c2a4-5e6f-7a8b-9c0d-1e2f3a4b5c6d"),
+                task: task.id,
+                text: "@bob did you get the milk?".into(),
+                annotation: Annotation {
+                    by: Actor::new("alice", &uuid!("0199c2a4-0000-7000-8000-000000000001")),
+                    at: Utc.with_ymd_and_hms(2025, 6, 2, 9, 0, 0).unwrap(),
+                },
+            }],
+            _ => vec![],
+        })
+    }
+}
+
+/// Why someone is being notified.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reason {
+    Mentioned,
+    Watching,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Notification {
+    pub recipient: User,
+    pub reason: Reason,
+    pub comment: Comment,
+}
+
+/// Delivers notifications, e.g. as desktop notifications or to an inbox.
+pub trait Notifier {
+    fn notify(&self, notification: Notification) -> HelixFlowResult<()>;
+}
+
+impl Comment {
+    /// A new comment on `task` by `author`, written now.
+    pub fn new<S: Into<String>>(task: &Task, text: S, author: &Actor) -> Self {
+        Comment {
+            id: Uuid::now_v7(),
+            task: task.id,
+            text: text.into(),
+            annotation: Annotation::now(author),
+        }
+    }
+
+    /// Names `@mentioned` in the text, in order of first appearance, without the `@`.
+    ///
+    /// A mention ends at the first character which is not alphanumeric, `_`, `-` or `.`; trailing
+    /// `.`s are treated as punctuation. An `@` preceded by a word character (`me@example.org`) is
+    /// not a mention.
+    pub fn mentions(&self) -> Vec<&str> {
+        let mut mentions = Vec::new();
+        for (start, _) in self.text.match_indices('@') {
+            let preceded_by_word = self.text[..start]
+                .chars()
+                .next_back()
+                .is_some_and(|c| c.is_alphanumeric() || c == '_');
+            if preceded_by_word {
+                continue;
+            }
+            let rest = &self.text[start + 1..];
+            let end = rest
+                .find(|c: char| !(c.is_alphanumeric() || matches!(c, '_' | '-' | '.')))
+                .unwrap_or(rest.len());
+            let name = rest[..end].trim_end_matches('.');
+            if !name.is_empty() && !mentions.contains(&name) {
+                mentions.push(name);
+            }
+        }
+        mentions
+    }
+
+    /// Store the comment, then `notify` everyone involved.
+    pub fn post<B, N>(
+        &self,
+        task: &Task,
+        users: &[User],
+        backend: &B,
+        notifier: &N,
+    ) -> HelixFlowResult<()>
+    where
+        B: Store<Comment> + Watch,
+        N: Notifier,
+    {
+        self.create(backend)?;
+        self.notify(task, users, backend, notifier)
+    }
+
+    /// Notify everyone `@mentioned` (looked up by name in `users`) and everyone watching the task.
+    /// Each person is notified at most once, and never about their own comment.
+    pub fn notify<B, N>(
+        &self,
+        task: &Task,
+        users: &[User],
+        backend: &B,
+        notifier: &N,
+    ) -> HelixFlowResult<()>
+    where
+        B: Watch,
+        N: Notifier,
+    {
+        let mentioned = self
+            .mentions()
+            .into_iter()
+            .filter_map(|name| users.iter().find(|user| user.name == name))
+            .map(|user| (user.clone(), Reason::Mentioned));
+        let watching = backend
+            .watchers(task)?
+            .into_iter()
+            .map(|user| (user, Reason::Watching));
+        let mut notified: Vec<Uuid> = Vec::new();
+        for (recipient, reason) in mentioned.chain(watching) {
+            if notified.contains(&recipient.id) || recipient.name == self.annotation.by.name {
+                continue;
+            }
+            notified.push(recipient.id);
+            notifier.notify(Notification {
+                recipient,
+                reason,
+                comment: self.clone(),
+            })?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[coverage(off)]
+mod tests {
+    use std::{cell::RefCell, collections::HashMap};
+
+    use super::*;
+    use crate::user::Watches;
+
+    fn comment(text: &str) -> Comment {
+        Comment::new(
+            &Task::new("Test Task", None),
+            text,
+            &Actor::new("alice", &Uuid::now_v7()),
+        )
+    }
+
+    #[test]
+    fn parse_mentions() {
+        let comment = comment("@bob.smith & @carol-b: see mail@example.org (cc @bob.smith).");
+        assert_eq!(comment.mentions(), vec!["bob.smith", "carol-b"]);
+    }
+
+    #[test]
+    fn no_mentions() {
+        assert!(comment("nothing to see @ all").mentions().is_empty());
+    }
+
+    #[derive(Default)]
+    struct WatchBackend {
+        watches: RefCell<HashMap<Uuid, Vec<User>>>,
+    }
+
+    impl Watch for WatchBackend {
+        fn create_watch(&self, watches: &Watches<User, Task>) -> HelixFlowResult<()> {
+            self.watches
+                .borrow_mut()
+                .entry(watches.right.id)
+                .or_default()
+                .push(watches.left.clone());
+            Ok(())
+        }
+        fn watchers(&self, task: &Task) -> HelixFlowResult<Vec<User>> {
+            Ok(self
+                .watches
+                .borrow()
+                .get(&task.id)
+                .cloned()
+                .unwrap_or_default())
+        }
+    }
+
+    #[derive(Default)]
+    struct TestNotifier {
+        sent: RefCell<Vec<(String, Reason)>>,
+    }
+
+    impl Notifier for TestNotifier {
+        fn notify(&self, notification: Notification) -> HelixFlowResult<()> {
+            self.sent
+                .borrow_mut()
+                .push((notification.recipient.name.into(), notification.reason));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn notify_watchers_and_mentioned() {
+        let alice = User::new("alice");
+        let bob = User::new("bob");
+        let carol = User::new("carol");
+        let dave = User::new("dave");
+        let users = [alice.clone(), bob.clone(), carol.clone(), dave.clone()];
+        let task = Task::new("Test Task", None);
+        let backend = WatchBackend::default();
+        alice.watch(&task, &backend).unwrap();
+        bob.watch(&task, &backend).unwrap();
+        carol.watch(&task, &backend).unwrap();
+
+        let comment = Comment::new(
+            &task,
+            "@bob @dave @erin what do you think?",
+            &Actor::new("alice", &Uuid::now_v7()),
+        );
+        let notifier = TestNotifier::default();
+        comment.notify(&task, &users, &backend, &notifier).unwrap();
+        assert_eq!(
+            *notifier.sent.borrow(),
+            vec![
+                ("bob".to_string(), Reason::Mentioned),
+                ("dave".to_string(), Reason::Mentioned),
+                ("carol".to_string(), Reason::Watching),
+            ]
+        );
+    }
+}
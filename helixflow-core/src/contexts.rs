@@ -0,0 +1,191 @@
+//! GTD contexts: where or with what a task can be done, e.g. "@home" or "@computer", so the
+//! next actions can be filtered to what is possible right now.
+
+use std::borrow::Cow;
+
+use serde::{Deserialize, Serialize};
+use uuid::{Uuid, uuid};
+
+use crate::{
+    HelixFlowError, HelixFlowItem, HelixFlowResult, Linkable, Relate, Relationship, Store,
+    Validate,
+    task::{Task, TestBackend},
+    validate_name,
+};
+
+/// A place, tool or situation needed for a task, e.g. "@errands". Like tags, contexts are shared
+/// between tasks, and a task can have several.
+#[derive(
+    Serialize, Deserialize, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, HelixFlowItem,
+)]
+pub struct Context {
+    pub name: Cow<'static, str>,
+    pub id: Uuid,
+}
+
+/// A context needs a name, see `MAX_NAME_LENGTH`.
+impl Validate for Context {
+    fn validate(&self) -> HelixFlowResult<()> {
+        validate_name(&self.name)
+    }
+}
+
+impl Context {
+    /// Create a new `Context` with valid `id`, suitable for usage as database key.
+    pub fn new<S>(name: S) -> Context
+    where
+        S: Into<Cow<'static, str>>,
+    {
+        Context {
+            name: name.into(),
+            id: Uuid::now_v7(),
+        }
+    }
+}
+
+impl Task {
+    /// Make this task doable in `context`, ready for `create_linked_item`.
+    pub fn add_context(&self, context: &Context) -> InContext<Task, Context> {
+        Linkable::<InContext<Task, Context>>::link(self, context)
+    }
+
+    /// This task can no longer be done in `context`. The context itself remains.
+    pub fn remove_context<B>(&self, context: &Context, backend: &B) -> HelixFlowResult<()>
+    where
+        B: Relate<InContext<Task, Context>>,
+    {
+        Linkable::<InContext<Task, Context>>::unlink(self, context, backend)
+    }
+
+    /// All contexts this task can be done in.
+    pub fn contexts<B>(&self, backend: &B) -> HelixFlowResult<Vec<Context>>
+    where
+        B: Relate<InContext<Task, Context>>,
+    {
+        Linkable::<InContext<Task, Context>>::get_linked_items(self, backend)?
+            .map(|in_context| in_context.right)
+            .collect()
+    }
+}
+
+/// `left` can be done in `right`.
+///
+/// `Relate::create_linked_item` should only create the context if it does not exist yet; the
+/// task must already exist.
+#[derive(Debug, Relationship)]
+#[relationship(Task => Context)]
+pub struct InContext<LEFT, RIGHT> {
+    pub left: HelixFlowResult<LEFT>,
+    pub right: HelixFlowResult<RIGHT>,
+}
+
+/// Find contexts, e.g. to offer them in a picker.
+pub trait Contexts: Store<Context> + Relate<InContext<Task, Context>> {
+    /// Every context known to the backend, oldest first.
+    fn all_contexts(&self) -> HelixFlowResult<Vec<Context>>;
+}
+
+fn test_contexts() -> Vec<Context> {
+    vec![
+        Context {
+            name: "@home".into(),
+            id: uuid!("0199b7a0-1c2d-7e3f-8a4b-5c6d7e8f9a01"),
+        },
+        Context {
+            name: "@computer".into(),
+            id: uuid!("0199b7a0-1c2d-7e3f-8a4b-5c6d7e8f9a02"),
+        },
+        Context {
+            name: "@errands".into(),
+            id: uuid!("0199b7a0-1c2d-7e3f-8a4b-5c6d7e8f9a03"),
+        },
+    ]
+}
+
+impl Store<Context> for TestBackend {
+    fn create(&self, context: &Context) -> HelixFlowResult<Context> {
+        Ok(context.clone())
+    }
+
+    fn get(&self, id: &Uuid) -> HelixFlowResult<Context> {
+        test_contexts()
+            .into_iter()
+            .find(|context| context.id == *id)
+            .ok_or(HelixFlowError::NotFound {
+                itemtype: "Context".into(),
+                id: *id,
+            })
+    }
+
+    fn update(&self, context: &Context) -> HelixFlowResult<Context> {
+        Ok(context.clone())
+    }
+
+    fn delete(&self, _id: &Uuid) -> HelixFlowResult<()> {
+        Ok(())
+    }
+}
+
+/// `Task 1` can be done `@computer`, no other task has a context.
+impl Relate<InContext<Task, Context>> for TestBackend {
+    fn create_linked_item(
+        &self,
+        link: &InContext<Task, Context>,
+    ) -> HelixFlowResult<InContext<Task, Context>> {
+        Ok(InContext {
+            left: Ok(link.left.as_ref().unwrap().clone()),
+            right: Ok(link.right.as_ref().unwrap().clone()),
+        })
+    }
+    fn get_linked_items(
+        &self,
+        left: &Task,
+    ) -> HelixFlowResult<impl Iterator<Item = InContext<Task, Context>>> {
+        let contexts = match left.id.to_string().as_str() {
+            "0196b4c9-8447-7959-ae1f-72c7c8a3dd36" => vec![test_contexts().swap_remove(1)],
+            _ => vec![],
+        };
+        let left = left.clone();
+        Ok(contexts
+            .into_iter()
+            .map(move |context| left.add_context(&context)))
+    }
+    fn delete_link(&self, _link: &InContext<Task, Context>) -> HelixFlowResult<()> {
+        Ok(())
+    }
+}
+
+/// `@home`, `@computer` & `@errands`.
+impl Contexts for TestBackend {
+    fn all_contexts(&self) -> HelixFlowResult<Vec<Context>> {
+        Ok(test_contexts())
+    }
+}
+
+#[cfg(test)]
+#[coverage(off)]
+mod tests {
+    use super::*;
+    use crate::{CRUD, Link};
+
+    #[test]
+    fn task_contexts() {
+        let task_1 =
+            Task::get(&TestBackend, &uuid!("0196b4c9-8447-7959-ae1f-72c7c8a3dd36")).unwrap();
+        let names: Vec<String> = task_1
+            .contexts(&TestBackend)
+            .unwrap()
+            .into_iter()
+            .map(|context| context.name.to_string())
+            .collect();
+        assert_eq!(names, vec!["@computer"]);
+
+        let errands = Context::new("@errands");
+        let task = Task::new("Buy stamps", None);
+        task.add_context(&errands)
+            .create_linked_item(&TestBackend)
+            .unwrap();
+        task.remove_context(&errands, &TestBackend).unwrap();
+        assert_eq!(TestBackend.all_contexts().unwrap().len(), 3);
+    }
+}
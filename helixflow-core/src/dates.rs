@@ -0,0 +1,145 @@
+//! Date handling. All instants are stored in UTC and only converted to a time zone for display
+//! or for questions like "is this due today?", which depend on where the user currently is.
+
+use chrono::{
+    DateTime, LocalResult, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, TimeZone, Utc,
+};
+use chrono_tz::Tz;
+
+use crate::task::Task;
+
+/// The calendar date of `instant` as seen in `tz`.
+pub fn local_date(instant: DateTime<Utc>, tz: Tz) -> NaiveDate {
+    instant.with_timezone(&tz).date_naive()
+}
+
+/// Convert a wall-clock time in `tz` to UTC.
+///
+/// Wall-clock times which are skipped by a DST transition are moved forward to the first valid
+/// instant afterwards. Ambiguous times (when the clocks go back) resolve to the earlier instant.
+pub fn to_utc(local: NaiveDateTime, tz: Tz) -> DateTime<Utc> {
+    let mut candidate = local;
+    loop {
+        match tz.from_local_datetime(&candidate) {
+            LocalResult::Single(instant) => return instant.with_timezone(&Utc),
+            LocalResult::Ambiguous(earliest, _) => return earliest.with_timezone(&Utc),
+            // No DST gap is longer than a few hours and they are aligned to whole minutes
+            LocalResult::None => candidate += TimeDelta::minutes(1),
+        }
+    }
+}
+
+/// The first instant of `date` in `tz`, which is not always midnight.
+pub fn start_of_day(date: NaiveDate, tz: Tz) -> DateTime<Utc> {
+    to_utc(date.and_time(NaiveTime::MIN), tz)
+}
+
+/// The half-open range `[start, end)` covering `date` in `tz`. This is not always 24 hours.
+pub fn day_bounds(date: NaiveDate, tz: Tz) -> (DateTime<Utc>, DateTime<Utc>) {
+    let next_day = date
+        .succ_opt()
+        .expect("date within chrono's supported range");
+    (start_of_day(date, tz), start_of_day(next_day, tz))
+}
+
+impl Task {
+    /// Is this task due on the same day as `now`, from the point of view of someone in `tz`?
+    pub fn is_due_today(&self, now: DateTime<Utc>, tz: Tz) -> bool {
+        self.due
+            .is_some_and(|due| local_date(due, tz) == local_date(now, tz))
+    }
+
+    /// Is this task due before the start of the current day in `tz`?
+    pub fn is_overdue(&self, now: DateTime<Utc>, tz: Tz) -> bool {
+        self.due
+            .is_some_and(|due| due < start_of_day(local_date(now, tz), tz))
+    }
+}
+
+#[cfg(test)]
+#[coverage(off)]
+mod tests {
+    use chrono::NaiveDate;
+    use chrono_tz::{America::Santiago, Asia::Tokyo, Europe::Berlin, UTC};
+
+    use super::*;
+
+    fn utc(year: i32, month: u32, day: u32, hour: u32, min: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(year, month, day, hour, min, 0)
+            .unwrap()
+    }
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn day_length_spring_forward() {
+        let (start, end) = day_bounds(date(2025, 3, 30), Berlin);
+        assert_eq!(start, utc(2025, 3, 29, 23, 0));
+        assert_eq!(end - start, TimeDelta::hours(23));
+    }
+
+    #[test]
+    fn day_length_fall_back() {
+        let (start, end) = day_bounds(date(2025, 10, 26), Berlin);
+        assert_eq!(start, utc(2025, 10, 25, 22, 0));
+        assert_eq!(end - start, TimeDelta::hours(25));
+    }
+
+    #[test]
+    fn skipped_wall_clock_time() {
+        let local = date(2025, 3, 30).and_hms_opt(2, 30, 0).unwrap();
+        assert_eq!(to_utc(local, Berlin), utc(2025, 3, 30, 1, 0));
+    }
+
+    #[test]
+    fn ambiguous_wall_clock_time() {
+        let local = date(2025, 10, 26).and_hms_opt(2, 30, 0).unwrap();
+        assert_eq!(to_utc(local, Berlin), utc(2025, 10, 26, 0, 30));
+    }
+
+    #[test]
+    fn midnight_does_not_exist() {
+        // Chile moves clocks forward at midnight
+        let start = start_of_day(date(2024, 9, 8), Santiago);
+        assert_eq!(start, utc(2024, 9, 8, 4, 0));
+        assert_eq!(
+            start.with_timezone(&Santiago).time(),
+            NaiveTime::from_hms_opt(1, 0, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn due_today_depends_on_location() {
+        let task = Task {
+            due: Some(utc(2025, 6, 1, 20, 0)),
+            ..Task::new("Call home", None)
+        };
+        let now = utc(2025, 6, 1, 10, 0);
+        assert!(task.is_due_today(now, UTC));
+        assert!(task.is_due_today(now, Berlin));
+        // Already the 2nd in Tokyo when it is due, but still the 1st now
+        assert!(!task.is_due_today(now, Tokyo));
+    }
+
+    #[test]
+    fn overdue() {
+        let task = Task {
+            due: Some(utc(2025, 6, 1, 23, 30)),
+            ..Task::new("Pay rent", None)
+        };
+        let now = utc(2025, 6, 2, 2, 0);
+        assert!(task.is_overdue(now, UTC));
+        // Still 1st June in New York, so not overdue yet
+        assert!(!task.is_overdue(now, chrono_tz::America::New_York));
+    }
+
+    #[test]
+    fn no_due_date() {
+        let task = Task::new("Someday", None);
+        let now = utc(2025, 6, 2, 8, 0);
+        assert!(!task.is_due_today(now, UTC));
+        assert!(!task.is_overdue(now, UTC));
+    }
+}
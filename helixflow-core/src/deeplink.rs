@@ -0,0 +1,95 @@
+//! `helixflow://` URIs, so that notes and chat messages outside the app can point at items.
+
+use std::{fmt::Display, str::FromStr};
+
+use uuid::Uuid;
+
+use crate::{HelixFlowError, task::Task};
+
+pub const SCHEME: &str = "helixflow";
+
+/// Something in HelixFlow which can be linked to directly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeepLink {
+    /// `helixflow://task/<uuid>`
+    Task(Uuid),
+}
+
+impl Display for DeepLink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DeepLink::Task(id) => write!(f, "{SCHEME}://task/{id}"),
+        }
+    }
+}
+
+impl FromStr for DeepLink {
+    type Err = HelixFlowError;
+    fn from_str(link: &str) -> Result<Self, Self::Err> {
+        let invalid = || HelixFlowError::InvalidLink { link: link.into() };
+        let path = link
+            .strip_prefix(SCHEME)
+            .and_then(|rest| rest.strip_prefix("://"))
+            .ok_or_else(invalid)?;
+        match path.trim_end_matches('/').split_once('/') {
+            Some(("task", id)) => Uuid::try_parse(id)
+                .map(DeepLink::Task)
+                .map_err(|_| invalid()),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl Task {
+    pub fn deep_link(&self) -> DeepLink {
+        DeepLink::Task(self.id)
+    }
+}
+
+#[cfg(test)]
+#[coverage(off)]
+mod tests {
+    use std::assert_matches::assert_matches;
+
+    use uuid::uuid;
+
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let task = Task {
+            id: uuid!("0196b4c9-8447-7959-ae1f-72c7c8a3dd36"),
+            ..Task::new("Linked", None)
+        };
+        let link = task.deep_link().to_string();
+        assert_eq!(
+            link,
+            "helixflow://task/0196b4c9-8447-7959-ae1f-72c7c8a3dd36"
+        );
+        assert_eq!(link.parse::<DeepLink>().unwrap(), task.deep_link());
+    }
+
+    #[test]
+    fn trailing_slash() {
+        let link: DeepLink = "helixflow://task/0196b4c9-8447-7959-ae1f-72c7c8a3dd36/"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            link,
+            DeepLink::Task(uuid!("0196b4c9-8447-7959-ae1f-72c7c8a3dd36"))
+        );
+    }
+
+    #[test]
+    fn invalid_links() {
+        for link in [
+            "https://task/0196b4c9-8447-7959-ae1f-72c7c8a3dd36",
+            "helixflow://list/0196b4c9-8447-7959-ae1f-72c7c8a3dd36",
+            "helixflow://task/foo",
+            "helixflow://task",
+        ] {
+            let err = link.parse::<DeepLink>().unwrap_err();
+            assert_matches!(err, HelixFlowError::InvalidLink { link: l } if l == link);
+        }
+    }
+}
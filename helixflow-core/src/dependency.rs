@@ -0,0 +1,216 @@
+//! Tasks which cannot start until other tasks are done.
+//!
+//! Dependencies must never form a cycle - otherwise none of the tasks involved could ever be
+//! started. This is checked here, before the backend is asked to store a new dependency, so every
+//! backend gets the same guarantee.
+
+use std::collections::HashSet;
+
+use uuid::{Uuid, uuid};
+
+use crate::{
+    HelixFlowError, HelixFlowResult, Link, Linkable, Relate, Relationship, Store,
+    events::{self, Event},
+    task::{Status, Task, TestBackend},
+};
+
+/// `left` cannot start until `right` is done.
+///
+/// Both tasks must already exist, `Relate::create_linked_item` only stores the dependency.
+#[derive(Debug, Relationship)]
+#[relationship(Task => Task, manual_link)]
+pub struct DependsOn<LEFT, RIGHT> {
+    pub left: HelixFlowResult<LEFT>,
+    pub right: HelixFlowResult<RIGHT>,
+}
+
+impl Link for DependsOn<Task, Task> {
+    /// Fails with `DependencyCycle`, without storing anything, if `right` already (indirectly)
+    /// depends on `left`.
+    fn create_linked_item<B: Relate<DependsOn<Task, Task>>>(
+        self,
+        backend: &B,
+    ) -> HelixFlowResult<()> {
+        let valid_relationship = self?;
+        // Both sides are `Ok`, checked by `?` above
+        let task = valid_relationship.left.as_ref().unwrap();
+        let dependency = valid_relationship.right.as_ref().unwrap();
+        if let Some(mut cycle) = dependency.path_to(task, backend)? {
+            cycle.insert(0, task.id);
+            return Err(HelixFlowError::DependencyCycle { cycle });
+        }
+        let created = backend.create_linked_item(&valid_relationship)?;
+        let task = created.left?;
+        let expected = valid_relationship.right?;
+        match created.right {
+            Ok(dependency) if dependency == expected => {
+                events::emit(Event::Linked {
+                    relationship: Self::NAME,
+                    left: task.id,
+                    right: dependency.id,
+                });
+                Ok(())
+            }
+            Ok(_) => Err(HelixFlowError::Mismatch {
+                expected: Box::new(expected.clone()),
+                actual: Box::new(created.right?.clone()),
+            }),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+impl Task {
+    /// This task cannot start until `dependency` is done. Ready for `create_linked_item`.
+    pub fn depends_on(&self, dependency: &Task) -> DependsOn<Task, Task> {
+        Linkable::<DependsOn<Task, Task>>::link(self, dependency)
+    }
+
+    /// The tasks this task directly depends on.
+    pub fn dependencies<B>(&self, backend: &B) -> HelixFlowResult<Vec<Task>>
+    where
+        B: Relate<DependsOn<Task, Task>>,
+    {
+        Linkable::<DependsOn<Task, Task>>::get_linked_items(self, backend)?
+            .map(|depends_on| depends_on.right)
+            .collect()
+    }
+
+    /// Is this task still waiting for any of the tasks it directly depends on to be done?
+    pub fn is_blocked<B>(&self, backend: &B) -> HelixFlowResult<bool>
+    where
+        B: Relate<DependsOn<Task, Task>>,
+    {
+        Ok(self
+            .dependencies(backend)?
+            .iter()
+            .any(|dependency| dependency.status != Status::Done))
+    }
+
+    /// The chain of dependencies from this task to `target`, both included, if this task
+    /// (indirectly) depends on `target`.
+    pub fn path_to<B>(&self, target: &Task, backend: &B) -> HelixFlowResult<Option<Vec<Uuid>>>
+    where
+        B: Relate<DependsOn<Task, Task>>,
+    {
+        self.path_to_excluding(target, backend, &mut HashSet::new())
+    }
+
+    fn path_to_excluding<B>(
+        &self,
+        target: &Task,
+        backend: &B,
+        seen: &mut HashSet<Uuid>,
+    ) -> HelixFlowResult<Option<Vec<Uuid>>>
+    where
+        B: Relate<DependsOn<Task, Task>>,
+    {
+        if self.id == target.id {
+            return Ok(Some(vec![self.id]));
+        }
+        if !seen.insert(self.id) {
+            return Ok(None);
+        }
+        for dependency in self.dependencies(backend)? {
+            if let Some(mut path) = dependency.path_to_excluding(target, backend, seen)? {
+                path.insert(0, self.id);
+                return Ok(Some(path));
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// `Task 2` depends on `Task 1`.
+impl Relate<DependsOn<Task, Task>> for TestBackend {
+    fn create_linked_item(
+        &self,
+        link: &DependsOn<Task, Task>,
+    ) -> HelixFlowResult<DependsOn<Task, Task>> {
+        Ok(DependsOn {
+            left: Ok(link.left.as_ref().unwrap().clone()),
+            right: Ok(link.right.as_ref().unwrap().clone()),
+        })
+    }
+    fn get_linked_items(
+        &self,
+        left: &Task,
+    ) -> HelixFlowResult<impl Iterator<Item = DependsOn<Task, Task>>> {
+        let dependencies: Vec<Task> = match left.id.to_string().as_str() {
+            "0196ca5f-d934-7ec8-b042-ae37b94b8432" => {
+                vec![self.get(&uuid!("0196b4c9-8447-7959-ae1f-72c7c8a3dd36"))?]
+            }
+            _ => vec![],
+        };
+        let left = left.clone();
+        Ok(dependencies
+            .into_iter()
+            .map(move |dependency| left.depends_on(&dependency)))
+    }
+    fn delete_link(&self, _link: &DependsOn<Task, Task>) -> HelixFlowResult<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[coverage(off)]
+mod tests {
+    use std::assert_matches::assert_matches;
+
+    use super::*;
+    use crate::CRUD;
+
+    const TASK_1: Uuid = uuid!("0196b4c9-8447-7959-ae1f-72c7c8a3dd36");
+    const TASK_2: Uuid = uuid!("0196ca5f-d934-7ec8-b042-ae37b94b8432");
+
+    #[test]
+    fn dependencies() {
+        let task_2 = Task::get(&TestBackend, &TASK_2).unwrap();
+        let dependencies = task_2.dependencies(&TestBackend).unwrap();
+        assert_eq!(dependencies.len(), 1);
+        assert_eq!(dependencies[0].id, TASK_1);
+        let task_1 = &dependencies[0];
+        assert_eq!(
+            task_2.path_to(task_1, &TestBackend).unwrap(),
+            Some(vec![TASK_2, TASK_1])
+        );
+        assert_eq!(task_1.path_to(&task_2, &TestBackend).unwrap(), None);
+    }
+
+    #[test]
+    fn blocked() {
+        let task_1 = Task::get(&TestBackend, &TASK_1).unwrap();
+        let task_2 = Task::get(&TestBackend, &TASK_2).unwrap();
+        assert!(task_2.is_blocked(&TestBackend).unwrap());
+        assert!(!task_1.is_blocked(&TestBackend).unwrap());
+    }
+
+    #[test]
+    fn new_dependency() {
+        let task_2 = Task::get(&TestBackend, &TASK_2).unwrap();
+        let task_3 = Task::new("Task 3", None);
+        task_3
+            .depends_on(&task_2)
+            .create_linked_item(&TestBackend)
+            .unwrap();
+    }
+
+    #[test]
+    fn cycle() {
+        let task_1 = Task::get(&TestBackend, &TASK_1).unwrap();
+        let task_2 = Task::get(&TestBackend, &TASK_2).unwrap();
+        assert_matches!(
+            task_1.depends_on(&task_2).create_linked_item(&TestBackend),
+            Err(HelixFlowError::DependencyCycle { cycle }) if cycle == vec![TASK_1, TASK_2, TASK_1]
+        );
+    }
+
+    #[test]
+    fn depends_on_itself() {
+        let task_1 = Task::get(&TestBackend, &TASK_1).unwrap();
+        assert_matches!(
+            task_1.depends_on(&task_1).create_linked_item(&TestBackend),
+            Err(HelixFlowError::DependencyCycle { cycle }) if cycle == vec![TASK_1, TASK_1]
+        );
+    }
+}
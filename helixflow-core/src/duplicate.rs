@@ -0,0 +1,272 @@
+//! Copying tasks, e.g. to reuse a checklist for a recurring chore, and finding & merging tasks
+//! which were accidentally captured twice.
+
+use std::collections::HashMap;
+
+use uuid::Uuid;
+
+use crate::{
+    CRUD, HelixFlowResult, Link, Relate, Store,
+    contexts::{Context, InContext},
+    dependency::DependsOn,
+    tag::{Tag, TaggedWith},
+    task::{Contains, Task, TaskList},
+    timestamps::LastModified,
+    transaction::{Transaction, atomically},
+    versions::Version,
+};
+
+impl Task {
+    /// A copy of this task with a new id, which has not been stored yet. With `mark_copy` the
+    /// name ends " (copy)", so both can be told apart in the same list.
+    pub fn duplicate(&self, mark_copy: bool) -> Task {
+        let name = if mark_copy {
+            format!("{} (copy)", self.name).into()
+        } else {
+            self.name.clone()
+        };
+        Task {
+            name,
+            id: Uuid::now_v7(),
+            updated_at: LastModified::default(),
+            version: Version::default(),
+            ..self.clone()
+        }
+    }
+
+    /// Store a copy of this task at the end of `tasklist`, along with copies of all its subtasks
+    /// (its checklist) and the same tags. Returns the copy.
+    pub fn duplicate_into<B>(&self, tasklist: &TaskList, backend: &B) -> HelixFlowResult<Task>
+    where
+        B: Relate<Contains<TaskList, Task>>
+            + Relate<Contains<Task, Task>>
+            + Relate<TaggedWith<Task, Tag>>,
+    {
+        let copy = self.duplicate(true);
+        tasklist.link(&copy).create_linked_item(backend)?;
+        self.copy_details(&copy, backend)?;
+        Ok(copy)
+    }
+
+    /// Give `copy` the same tags as this task, and copies of its subtasks.
+    fn copy_details<B>(&self, copy: &Task, backend: &B) -> HelixFlowResult<()>
+    where
+        B: Relate<Contains<Task, Task>> + Relate<TaggedWith<Task, Tag>>,
+    {
+        for tag in self.tags(backend)? {
+            copy.tag(&tag).create_linked_item(backend)?;
+        }
+        for subtask in self.subtasks(backend)? {
+            let subtask_copy = subtask.duplicate(false);
+            copy.subtask(&subtask_copy).create_linked_item(backend)?;
+            subtask.copy_details(&subtask_copy, backend)?;
+        }
+        Ok(())
+    }
+
+    /// Merge `other`, a duplicate of this task, into this one & delete it.
+    ///
+    /// Details this task lacks (description, due date, estimate & colour) are taken from `other`;
+    /// if both have a different description they are combined. This task gains the tags,
+    /// contexts & dependencies of `other` and copies of its subtasks. `other` is deleted, along
+    /// with its subtasks, in the same transaction. Without transactions it is deleted last, so a
+    /// failure never loses any of its details.
+    pub fn merge<B>(&mut self, other: &Task, backend: &B) -> HelixFlowResult<()>
+    where
+        B: Store<Task>
+            + Relate<Contains<Task, Task>>
+            + Relate<TaggedWith<Task, Tag>>
+            + Relate<InContext<Task, Context>>
+            + Relate<DependsOn<Task, Task>>
+            + Transaction,
+    {
+        let mut merged = self.clone();
+        atomically(backend, || merged.merge_steps(other, backend))?;
+        *self = merged;
+        Ok(())
+    }
+
+    fn merge_steps<B>(&mut self, other: &Task, backend: &B) -> HelixFlowResult<()>
+    where
+        B: Store<Task>
+            + Relate<Contains<Task, Task>>
+            + Relate<TaggedWith<Task, Tag>>
+            + Relate<InContext<Task, Context>>
+            + Relate<DependsOn<Task, Task>>,
+    {
+        self.description = match (self.description.take(), &other.description) {
+            (Some(mine), Some(theirs)) if mine != *theirs => {
+                Some(format!("{mine}\n\n{theirs}").into())
+            }
+            (None, theirs) => theirs.clone(),
+            (mine, _) => mine,
+        };
+        self.due = self.due.or(other.due);
+        self.estimate = self.estimate.or(other.estimate);
+        self.colour = self.colour.or(other.colour);
+
+        let tags = self.tags(backend)?;
+        for tag in other.tags(backend)? {
+            if !tags.contains(&tag) {
+                self.tag(&tag).create_linked_item(backend)?;
+            }
+        }
+        let contexts = self.contexts(backend)?;
+        for context in other.contexts(backend)? {
+            if !contexts.contains(&context) {
+                self.add_context(&context).create_linked_item(backend)?;
+            }
+        }
+        let dependencies = self.dependencies(backend)?;
+        for dependency in other.dependencies(backend)? {
+            if dependency.id != self.id && !dependencies.contains(&dependency) {
+                self.depends_on(&dependency).create_linked_item(backend)?;
+            }
+        }
+        for subtask in other.subtasks(backend)? {
+            if subtask.id == self.id {
+                continue;
+            }
+            let subtask_copy = subtask.duplicate(false);
+            self.subtask(&subtask_copy).create_linked_item(backend)?;
+            subtask.copy_details(&subtask_copy, backend)?;
+        }
+        self.update(backend)?;
+
+        for task in other.subtree(backend)?.tasks() {
+            if task.id != self.id {
+                task.delete(backend)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The name with case, surrounding punctuation & repeated whitespace ignored.
+fn normalised_name(task: &Task) -> String {
+    task.name
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .trim_matches(|c: char| c.is_ascii_punctuation())
+        .to_lowercase()
+}
+
+/// Groups of `tasks` which are probably the same, as they have the same name once normalised.
+/// Each group keeps the order of `tasks`, so the first one is usually the one to `merge` into.
+pub fn likely_duplicates(tasks: &[Task]) -> Vec<Vec<Task>> {
+    let mut groups: Vec<Vec<Task>> = Vec::new();
+    let mut by_name: HashMap<String, usize> = HashMap::new();
+    for task in tasks {
+        let name = normalised_name(task);
+        if name.is_empty() {
+            continue;
+        }
+        match by_name.get(&name) {
+            Some(&group) => groups[group].push(task.clone()),
+            None => {
+                by_name.insert(name, groups.len());
+                groups.push(vec![task.clone()]);
+            }
+        }
+    }
+    groups.retain(|group| group.len() > 1);
+    groups
+}
+
+impl TaskList {
+    /// Tasks in this list which are probably duplicates of each other, see `likely_duplicates`.
+    pub fn duplicates<B>(&self, backend: &B) -> HelixFlowResult<Vec<Vec<Task>>>
+    where
+        B: Relate<Contains<TaskList, Task>>,
+    {
+        let tasks = self
+            .get_linked_items(backend)?
+            .map(|contains| contains.right)
+            .collect::<HelixFlowResult<Vec<Task>>>()?;
+        Ok(likely_duplicates(&tasks))
+    }
+}
+
+#[cfg(test)]
+#[coverage(off)]
+mod tests {
+    use uuid::uuid;
+
+    use super::*;
+    use crate::{CRUD, task::TestBackend};
+
+    #[test]
+    fn duplicate_task() {
+        let mut task =
+            Task::get(&TestBackend, &uuid!("0196b4c9-8447-7959-ae1f-72c7c8a3dd36")).unwrap();
+        task.complete().unwrap();
+
+        let copy = task.duplicate(true);
+        assert_ne!(copy.id, task.id);
+        assert_eq!(copy.name, "Task 1 (copy)");
+        assert_eq!(
+            Task {
+                name: task.name.clone(),
+                id: task.id,
+                ..copy
+            },
+            task
+        );
+        assert_eq!(task.duplicate(false).name, "Task 1");
+    }
+
+    #[test]
+    fn find_duplicates() {
+        let tasks = vec![
+            Task::new("Call  Mum", None),
+            Task::new("Buy milk", None),
+            Task::new("call mum!", None),
+            Task::new("Buy milk", None),
+            Task::new("Buy oat milk", None),
+            Task::new("...", None),
+            Task::new("?", None),
+        ];
+        let groups = likely_duplicates(&tasks);
+        let names: Vec<Vec<&str>> = groups
+            .iter()
+            .map(|group| group.iter().map(|task| task.name.as_ref()).collect())
+            .collect();
+        assert_eq!(
+            names,
+            vec![vec!["Call  Mum", "call mum!"], vec!["Buy milk", "Buy milk"]]
+        );
+
+        let tasklist =
+            TaskList::get(&TestBackend, &uuid!("0196fe23-7c01-7d6b-9e09-5968eb370549")).unwrap();
+        assert!(tasklist.duplicates(&TestBackend).unwrap().is_empty());
+    }
+
+    #[test]
+    fn merge_duplicate() {
+        // `Task 2` is a subtask of `Task 1`, which should be ignored when merging
+        let task_2 =
+            Task::get(&TestBackend, &uuid!("0196ca5f-d934-7ec8-b042-ae37b94b8432")).unwrap();
+        let mut task = Task {
+            description: Some("About Sunday".into()),
+            ..task_2.clone()
+        };
+        let duplicate =
+            Task::get(&TestBackend, &uuid!("0196b4c9-8447-7959-ae1f-72c7c8a3dd36")).unwrap();
+        let duplicate = Task {
+            description: Some("Birthday".into()),
+            estimate: Some(std::time::Duration::from_secs(600)),
+            ..duplicate
+        };
+        task.merge(&duplicate, &TestBackend).unwrap();
+        assert_eq!(
+            task.description.as_deref(),
+            Some("About Sunday\n\nBirthday")
+        );
+        assert_eq!(task.estimate, duplicate.estimate);
+
+        let mut undescribed = task_2;
+        undescribed.merge(&duplicate, &TestBackend).unwrap();
+        assert_eq!(undescribed.description.as_deref(), Some("Birthday"));
+    }
+}
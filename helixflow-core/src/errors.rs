@@ -0,0 +1,135 @@
+//! Describing a `HelixFlowError` to the user rather than to a developer.
+//!
+//! `code()` identifies the kind of error and never changes once released, so it can be used to
+//! look up translations or in support requests. `user_message()` is a short English sentence
+//! suitable for a toast, and `severity()` says how prominently to show it.
+
+use crate::HelixFlowError;
+
+/// How much an error matters to the user.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// Nothing was changed, e.g. an empty task name. Can usually be ignored.
+    Info,
+    /// The action failed but can be corrected or retried.
+    Warning,
+    /// Something is wrong with the data or the backend.
+    Error,
+}
+
+impl HelixFlowError {
+    /// Stable identifier for this kind of error, e.g. `"HF0004"`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            HelixFlowError::BackendError(_) => "HF0001",
+            HelixFlowError::Mismatch { .. } => "HF0002",
+            HelixFlowError::InvalidID { .. } => "HF0003",
+            HelixFlowError::NotFound { .. } => "HF0004",
+            HelixFlowError::RelationshipBetweenErrors { .. } => "HF0005",
+            HelixFlowError::CircuitOpen { .. } => "HF0006",
+            HelixFlowError::ShareNotValid { .. } => "HF0007",
+            HelixFlowError::InvalidLink { .. } => "HF0008",
+            HelixFlowError::InvalidValue { .. } => "HF0009",
+            HelixFlowError::InvalidTransition { .. } => "HF0010",
+            HelixFlowError::DependencyCycle { .. } => "HF0011",
+            HelixFlowError::ValidationFailed { .. } => "HF0012",
+            HelixFlowError::Conflict { .. } => "HF0013",
+        }
+    }
+
+    pub fn severity(&self) -> Severity {
+        match self {
+            HelixFlowError::ValidationFailed { .. } | HelixFlowError::InvalidTransition { .. } => {
+                Severity::Info
+            }
+            HelixFlowError::NotFound { .. }
+            | HelixFlowError::CircuitOpen { .. }
+            | HelixFlowError::ShareNotValid { .. }
+            | HelixFlowError::InvalidLink { .. }
+            | HelixFlowError::InvalidValue { .. }
+            | HelixFlowError::DependencyCycle { .. }
+            | HelixFlowError::Conflict { .. } => Severity::Warning,
+            HelixFlowError::BackendError(_)
+            | HelixFlowError::Mismatch { .. }
+            | HelixFlowError::InvalidID { .. }
+            | HelixFlowError::RelationshipBetweenErrors { .. } => Severity::Error,
+        }
+    }
+
+    /// What went wrong, without ids or internal details. Use `Display` for the full details.
+    pub fn user_message(&self) -> String {
+        match self {
+            HelixFlowError::BackendError(_) => "The database could not be read or saved.".into(),
+            HelixFlowError::Mismatch { .. } | HelixFlowError::RelationshipBetweenErrors { .. } => {
+                "The change was not saved correctly.".into()
+            }
+            HelixFlowError::InvalidID { .. } => "The database contains an invalid item.".into(),
+            HelixFlowError::NotFound { itemtype, .. } => {
+                format!("The {} no longer exists.", itemtype.to_lowercase())
+            }
+            HelixFlowError::CircuitOpen { retry_in } => format!(
+                "The database is unavailable, try again in {}s.",
+                retry_in.as_secs().max(1)
+            ),
+            HelixFlowError::ShareNotValid { .. } => {
+                "This share link has expired or been revoked.".into()
+            }
+            HelixFlowError::InvalidLink { .. } => "This is not a HelixFlow link.".into(),
+            HelixFlowError::InvalidValue { field, value } => {
+                format!("\"{value}\" is not a valid {field}.")
+            }
+            HelixFlowError::InvalidTransition { from, to } => {
+                format!("A task cannot go from {from:?} to {to:?}.")
+            }
+            HelixFlowError::DependencyCycle { .. } => {
+                "Tasks cannot depend on each other in a circle.".into()
+            }
+            HelixFlowError::ValidationFailed { field, reason } => format!("The {field} {reason}."),
+            HelixFlowError::Conflict { itemtype, .. } => format!(
+                "The {} was changed elsewhere, please try again.",
+                itemtype.to_lowercase()
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+#[coverage(off)]
+mod tests {
+    use std::time::Duration;
+
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::{CRUD, task::Task};
+
+    #[test]
+    fn describe_errors() {
+        let missing = HelixFlowError::NotFound {
+            itemtype: "TaskList".into(),
+            id: Uuid::now_v7(),
+        };
+        assert_eq!(missing.code(), "HF0004");
+        assert_eq!(missing.severity(), Severity::Warning);
+        assert_eq!(missing.user_message(), "The tasklist no longer exists.");
+
+        let unavailable = HelixFlowError::CircuitOpen {
+            retry_in: Duration::from_millis(200),
+        };
+        assert_eq!(
+            unavailable.user_message(),
+            "The database is unavailable, try again in 1s."
+        );
+
+        let broken = HelixFlowError::from(anyhow::anyhow!("disk full"));
+        assert_eq!(broken.severity(), Severity::Error);
+        assert!(!broken.user_message().contains("disk full"));
+
+        let unnamed = Task::new("", None)
+            .create(&crate::task::TestBackend)
+            .unwrap_err();
+        assert_eq!(unnamed.severity(), Severity::Info);
+        assert_eq!(unnamed.code(), "HF0012");
+        assert_eq!(unnamed.user_message(), "The name must not be blank.");
+    }
+}
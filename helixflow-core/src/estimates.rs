@@ -0,0 +1,136 @@
+//! How long tasks are expected to take, and how loaded a tasklist is as a result.
+
+use std::time::Duration;
+
+use crate::{
+    HelixFlowResult, Relate,
+    task::{Contains, Status, Task, TaskList},
+};
+
+/// The estimated work still to do in a set of tasks.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Workload {
+    /// Sum of the estimates of all open tasks
+    pub estimated: Duration,
+    /// Open tasks without an estimate, which are therefore missing from `estimated`
+    pub unestimated: usize,
+}
+
+impl Workload {
+    /// The workload of `tasks`. Done tasks are not counted.
+    pub fn of<'t>(tasks: impl IntoIterator<Item = &'t Task>) -> Workload {
+        tasks
+            .into_iter()
+            .filter(|task| task.status != Status::Done)
+            .fold(Workload::default(), |mut workload, task| {
+                match task.estimate {
+                    Some(estimate) => workload.estimated += estimate,
+                    None => workload.unestimated += 1,
+                }
+                workload
+            })
+    }
+}
+
+impl TaskList {
+    /// The estimated work still to do in this list, e.g. to see how full "This week" is.
+    pub fn workload<B>(&self, backend: &B) -> HelixFlowResult<Workload>
+    where
+        B: Relate<Contains<TaskList, Task>>,
+    {
+        let tasks = self
+            .get_linked_items(backend)?
+            .map(|contains| contains.right)
+            .collect::<HelixFlowResult<Vec<Task>>>()?;
+        Ok(Workload::of(&tasks))
+    }
+}
+
+/// A duration in whole hours & minutes, as shown in a table cell: "2h", "45m" or "1h 30m".
+pub fn short(duration: Duration) -> String {
+    let minutes = duration.as_secs() / 60;
+    match (minutes / 60, minutes % 60) {
+        (0, minutes) => format!("{minutes}m"),
+        (hours, 0) => format!("{hours}h"),
+        (hours, minutes) => format!("{hours}h {minutes}m"),
+    }
+}
+
+/// Read a duration written as by `short`. Spaces are optional, so "1h30m" is also fine.
+pub fn parse_short(text: &str) -> Option<Duration> {
+    let text: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+    let (hours, rest) = match text.split_once(['h', 'H']) {
+        Some((hours, rest)) => (hours.parse::<u64>().ok()?, rest),
+        None => (0, text.as_str()),
+    };
+    let minutes = match rest {
+        "" if text.is_empty() => return None,
+        "" => 0,
+        rest => rest.strip_suffix(['m', 'M'])?.parse::<u64>().ok()?,
+    };
+    Some(Duration::from_secs((hours * 60 + minutes) * 60))
+}
+
+#[cfg(test)]
+#[coverage(off)]
+mod tests {
+    use uuid::uuid;
+
+    use super::*;
+    use crate::{CRUD, task::TestBackend};
+
+    fn task(estimate_minutes: Option<u64>, status: Status) -> Task {
+        Task {
+            estimate: estimate_minutes.map(|minutes| Duration::from_secs(minutes * 60)),
+            status,
+            ..Task::new("Task", None)
+        }
+    }
+
+    #[test]
+    fn workload_of_tasks() {
+        let tasks = [
+            task(Some(60), Status::Todo),
+            task(Some(30), Status::InProgress),
+            task(None, Status::Blocked),
+            task(Some(120), Status::Done),
+            task(None, Status::Done),
+        ];
+        assert_eq!(
+            Workload::of(&tasks),
+            Workload {
+                estimated: Duration::from_secs(90 * 60),
+                unestimated: 1,
+            }
+        );
+        assert_eq!(Workload::of(&[]), Workload::default());
+    }
+
+    #[test]
+    fn workload_of_tasklist() {
+        let tasklist =
+            TaskList::get(&TestBackend, &uuid!("0196fe23-7c01-7d6b-9e09-5968eb370549")).unwrap();
+        assert_eq!(
+            tasklist.workload(&TestBackend).unwrap(),
+            Workload {
+                estimated: Duration::from_secs(2 * 60 * 60),
+                unestimated: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn short_durations() {
+        let cases = [(45, "45m"), (60, "1h"), (90, "1h 30m"), (0, "0m")];
+        for (minutes, text) in cases {
+            let duration = Duration::from_secs(minutes * 60);
+            assert_eq!(short(duration), text);
+            assert_eq!(parse_short(text), Some(duration));
+        }
+        assert_eq!(parse_short("2H15M"), Some(Duration::from_secs(135 * 60)));
+        assert_eq!(parse_short("1h30m"), Some(Duration::from_secs(90 * 60)));
+        for invalid in ["", "90", "h", "1.5h", "1h 30", "soon"] {
+            assert_eq!(parse_short(invalid), None, "{invalid}");
+        }
+    }
+}
@@ -0,0 +1,253 @@
+//! Notifications of changes made through `CRUD` & `Link`, for anything which needs to react to
+//! them - e.g. refreshing the UI, sending notifications or syncing.
+//!
+//! Subscribers are process-wide: every change made by any backend is delivered to every
+//! subscriber, on the thread which made the change. Backends which can also see changes made by
+//! other processes implement `Subscribe`.
+
+use std::sync::{
+    Arc, Mutex,
+    atomic::{AtomicU64, Ordering},
+};
+
+use uuid::Uuid;
+
+use crate::HelixFlowResult;
+
+/// Something which changed. Events only identify what changed, subscribers can load the current
+/// version from a backend if they need it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event {
+    /// A new item was stored, e.g. a new task is `Created { itemtype: "Task", .. }`
+    Created {
+        itemtype: &'static str,
+        id: Uuid,
+    },
+    Updated {
+        itemtype: &'static str,
+        id: Uuid,
+    },
+    Deleted {
+        itemtype: &'static str,
+        id: Uuid,
+    },
+    /// A task was updated with status `Done`, after not being done. Also sent as `Updated`.
+    TaskCompleted {
+        id: Uuid,
+    },
+    /// `left` & `right` were linked, e.g. a task added to a list is
+    /// `Linked { relationship: "Contains", .. }`
+    Linked {
+        relationship: &'static str,
+        left: Uuid,
+        right: Uuid,
+    },
+    Unlinked {
+        relationship: &'static str,
+        left: Uuid,
+        right: Uuid,
+    },
+}
+
+type Subscriber = Arc<dyn Fn(&Event) + Send + Sync>;
+
+static SUBSCRIBERS: Mutex<Vec<(u64, Subscriber)>> = Mutex::new(Vec::new());
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Call `subscriber` for every event, until the returned `Subscription` is dropped.
+#[must_use = "the subscriber is removed when the `Subscription` is dropped"]
+pub fn subscribe<F>(subscriber: F) -> Subscription
+where
+    F: Fn(&Event) + Send + Sync + 'static,
+{
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    SUBSCRIBERS.lock().unwrap().push((id, Arc::new(subscriber)));
+    Subscription::new(move || SUBSCRIBERS.lock().unwrap().retain(|(i, _)| *i != id))
+}
+
+/// Deliver `event` to every subscriber. `CRUD` & `Link` do this, backends only need to for
+/// changes made some other way.
+///
+/// Subscribers may make changes themselves, which are delivered as normal.
+pub fn emit(event: Event) {
+    let subscribers: Vec<Subscriber> = SUBSCRIBERS
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|(_, subscriber)| subscriber.clone())
+        .collect();
+    for subscriber in subscribers {
+        subscriber(&event);
+    }
+}
+
+/// Is anyone listening? Lets callers skip extra work needed only to create an event.
+pub fn has_subscribers() -> bool {
+    !SUBSCRIBERS.lock().unwrap().is_empty()
+}
+
+/// A backend which sees every change to an `ITEM`, including those made by other processes, e.g.
+/// the sync service or another copy of the app using the same database.
+pub trait Subscribe<ITEM>: Send + Sync {
+    /// Call `subscriber` for every change to an `ITEM`, until the returned `Subscription` is
+    /// dropped. Changes made through this process are delivered here as well as via `subscribe`.
+    fn subscribe<F>(&self, subscriber: F) -> HelixFlowResult<Subscription>
+    where
+        F: Fn(&Event) + Send + Sync + 'static;
+}
+
+/// Keeps a subscriber registered, see `subscribe` & `Subscribe`.
+pub struct Subscription {
+    unsubscribe: Option<Box<dyn FnOnce() + Send + Sync>>,
+}
+
+impl Subscription {
+    /// Call `unsubscribe` when the subscription is dropped, for backends implementing `Subscribe`.
+    pub fn new<F>(unsubscribe: F) -> Self
+    where
+        F: FnOnce() + Send + Sync + 'static,
+    {
+        Subscription {
+            unsubscribe: Some(Box::new(unsubscribe)),
+        }
+    }
+
+    /// Keep the subscriber for as long as the app runs.
+    pub fn detach(self) {
+        std::mem::forget(self);
+    }
+}
+
+impl std::fmt::Debug for Subscription {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Subscription").finish_non_exhaustive()
+    }
+}
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        if let Some(unsubscribe) = self.unsubscribe.take() {
+            unsubscribe();
+        }
+    }
+}
+
+#[cfg(test)]
+#[coverage(off)]
+mod tests {
+    use uuid::uuid;
+
+    use super::*;
+    use crate::{
+        CRUD, Link,
+        task::{Status, Task, TaskList, TestBackend},
+    };
+
+    /// Collects the events about `ids` from this thread, other tests may be changing the same
+    /// items at the same time.
+    fn record(ids: Vec<Uuid>) -> (Subscription, Arc<Mutex<Vec<Event>>>) {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let recorded = events.clone();
+        let thread = std::thread::current().id();
+        let subscription = subscribe(move |event| {
+            if std::thread::current().id() != thread {
+                return;
+            }
+            let about = match event {
+                Event::Created { id, .. }
+                | Event::Updated { id, .. }
+                | Event::Deleted { id, .. }
+                | Event::TaskCompleted { id } => ids.contains(id),
+                Event::Linked { right, .. } | Event::Unlinked { right, .. } => ids.contains(right),
+            };
+            if about {
+                recorded.lock().unwrap().push(event.clone());
+            }
+        });
+        (subscription, events)
+    }
+
+    #[test]
+    fn task_lifecycle() {
+        let task = Task::get(&TestBackend, &uuid!("0196b4c9-8447-7959-ae1f-72c7c8a3dd36")).unwrap();
+        let tasklist =
+            TaskList::get(&TestBackend, &uuid!("0196fe23-7c01-7d6b-9e09-5968eb370549")).unwrap();
+        let (subscription, events) = record(vec![task.id]);
+
+        task.create(&TestBackend).unwrap();
+        tasklist
+            .link(&task)
+            .create_linked_item(&TestBackend)
+            .unwrap();
+        tasklist.unlink(&task, &TestBackend).unwrap();
+        let done = Task {
+            status: Status::Done,
+            ..task.clone()
+        };
+        done.update(&TestBackend).unwrap();
+        task.delete(&TestBackend).unwrap();
+        // Not stored, so nothing happened
+        Task {
+            name: "FAIL".into(),
+            ..task.clone()
+        }
+        .create(&TestBackend)
+        .unwrap_err();
+
+        let task = task.id;
+        let tasklist = tasklist.id;
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                Event::Created {
+                    itemtype: "Task",
+                    id: task
+                },
+                Event::Linked {
+                    relationship: "Contains",
+                    left: tasklist,
+                    right: task
+                },
+                Event::Unlinked {
+                    relationship: "Contains",
+                    left: tasklist,
+                    right: task
+                },
+                Event::Updated {
+                    itemtype: "Task",
+                    id: task
+                },
+                Event::TaskCompleted { id: task },
+                Event::Deleted {
+                    itemtype: "Task",
+                    id: task
+                },
+            ]
+        );
+
+        drop(subscription);
+        Task::get(&TestBackend, &task)
+            .unwrap()
+            .create(&TestBackend)
+            .unwrap();
+        assert_eq!(events.lock().unwrap().len(), 6);
+    }
+
+    #[test]
+    fn unsubscribe_once() {
+        let unsubscribed = Arc::new(AtomicU64::new(0));
+        let counter = unsubscribed.clone();
+        let subscription = Subscription::new(move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+        drop(subscription);
+        assert_eq!(unsubscribed.load(Ordering::SeqCst), 1);
+
+        let counter = unsubscribed.clone();
+        Subscription::new(move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+        })
+        .detach();
+        assert_eq!(unsubscribed.load(Ordering::SeqCst), 1);
+    }
+}
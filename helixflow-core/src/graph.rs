@@ -0,0 +1,118 @@
+//! Render how items relate to each other as a [Graphviz](https://graphviz.org) DOT graph, e.g. to
+//! spot tangled structures: `helixflow dot | dot -Tsvg > helixflow.svg`
+
+use uuid::Uuid;
+
+use crate::{
+    HelixFlowResult, Relate,
+    dependency::DependsOn,
+    tag::{Tag, TaggedWith},
+    task::{Contains, Task, TaskList},
+};
+
+/// Nodes and labelled edges, in the order they were found.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DotGraph {
+    /// `(id, label, shape)`
+    nodes: Vec<(Uuid, String, &'static str)>,
+    /// `(from, to, label)`
+    edges: Vec<(Uuid, Uuid, &'static str)>,
+}
+
+impl DotGraph {
+    /// `tasklists`, the tasks they contain, the tags on those tasks and the tasks they depend on.
+    pub fn of<B>(tasklists: &[TaskList], backend: &B) -> HelixFlowResult<Self>
+    where
+        B: Relate<Contains<TaskList, Task>>
+            + Relate<TaggedWith<Task, Tag>>
+            + Relate<DependsOn<Task, Task>>,
+    {
+        let mut graph = DotGraph::default();
+        let mut tasks = Vec::new();
+        for tasklist in tasklists {
+            graph.node(tasklist.id, &tasklist.name, "folder");
+            for contains in tasklist.get_linked_items(backend)? {
+                let task = contains.right?;
+                graph.node(task.id, &task.name, "box");
+                graph.edges.push((tasklist.id, task.id, "contains"));
+                for tag in task.tags(backend)? {
+                    graph.node(tag.id, &format!("#{}", tag.name), "ellipse");
+                    graph.edges.push((task.id, tag.id, "tagged"));
+                }
+                tasks.push(task);
+            }
+        }
+        // Dependencies may be on tasks in other lists, which are added as they are found
+        for task in tasks {
+            for dependency in task.dependencies(backend)? {
+                graph.node(dependency.id, &dependency.name, "box");
+                graph.edges.push((task.id, dependency.id, "depends on"));
+            }
+        }
+        Ok(graph)
+    }
+
+    /// Add a node, unless it is already in the graph (e.g. a tag used on several tasks).
+    fn node(&mut self, id: Uuid, label: &str, shape: &'static str) {
+        if !self.nodes.iter().any(|(existing, _, _)| *existing == id) {
+            self.nodes.push((id, label.to_string(), shape));
+        }
+    }
+
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph helixflow {\n");
+        for (id, label, shape) in &self.nodes {
+            dot.push_str(&format!(
+                "    \"{id}\" [label=\"{}\", shape={shape}];\n",
+                escape_dot(label)
+            ));
+        }
+        for (from, to, label) in &self.edges {
+            dot.push_str(&format!(
+                "    \"{from}\" -> \"{to}\" [label=\"{label}\"];\n"
+            ));
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+fn escape_dot(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+#[coverage(off)]
+mod tests {
+    use uuid::uuid;
+
+    use super::*;
+
+    #[test]
+    fn escaped_labels() {
+        let id = uuid!("0196fe23-7c01-7d6b-9e09-5968eb370549");
+        let mut graph = DotGraph::default();
+        graph.node(id, r#"Say "hi" \o/"#, "box");
+        graph.node(id, "Duplicate", "box");
+        assert_eq!(
+            graph.to_dot(),
+            "digraph helixflow {\n    \
+            \"0196fe23-7c01-7d6b-9e09-5968eb370549\" [label=\"Say \\\"hi\\\" \\\\o/\", shape=box];\n\
+            }\n"
+        );
+    }
+
+    #[test]
+    fn edges() {
+        let list = uuid!("0196fe23-7c01-7d6b-9e09-5968eb370549");
+        let task = uuid!("0196b4c9-8447-7959-ae1f-72c7c8a3dd36");
+        let mut graph = DotGraph::default();
+        graph.node(list, "Backlog", "folder");
+        graph.node(task, "Task 1", "box");
+        graph.edges.push((list, task, "contains"));
+        assert!(graph.to_dot().contains(
+            "    \"0196fe23-7c01-7d6b-9e09-5968eb370549\" -> \
+            \"0196b4c9-8447-7959-ae1f-72c7c8a3dd36\" [label=\"contains\"];\n"
+        ));
+    }
+}
@@ -0,0 +1,117 @@
+//! A record of every change made to an item, e.g. to see when a task's description changed.
+
+use chrono::{DateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    HelixFlowResult, Store,
+    actor::Actor,
+    task::{Task, TestBackend},
+};
+
+/// What happened to an item.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ChangeKind {
+    Created,
+    Updated,
+    Deleted,
+}
+
+/// One entry in an item's history. Entries are never changed once recorded.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Change<ITEM> {
+    pub kind: ChangeKind,
+    /// Who made the change, if the backend knows
+    pub by: Option<Actor>,
+    pub at: DateTime<Utc>,
+    /// The item before the change, `None` when it was created
+    pub before: Option<ITEM>,
+    /// The item after the change, `None` when it was deleted
+    pub after: Option<ITEM>,
+}
+
+/// Methods to look up the changes a backend recorded for `ITEM`s
+pub trait History<ITEM>: Send + Sync {
+    /// Every change to the `ITEM` with this `id`, oldest first. Items which were never stored,
+    /// or were stored before history was recorded, have no history.
+    fn history(&self, id: &Uuid) -> HelixFlowResult<Vec<Change<ITEM>>>;
+}
+
+impl Task {
+    /// Every change made to this task, oldest first.
+    pub fn history<B: History<Task>>(&self, backend: &B) -> HelixFlowResult<Vec<Change<Task>>> {
+        backend.history(&self.id)
+    }
+}
+
+/// `Task 1` was created on 1st June 2025, then given a description.
+impl History<Task> for TestBackend {
+    fn history(&self, id: &Uuid) -> HelixFlowResult<Vec<Change<Task>>> {
+        match id.to_string().as_str() {
+            "0196b4c9-8447-7959-ae1f-72c7c8a3dd36" => {
+                let current: Task = self.get(id)?;
+                let created = Task {
+                    description: None,
+                    ..current.clone()
+                };
+                let described = Task {
+                    description: Some("Now with a description".into()),
+                    ..current
+                };
+                let at = |day| Utc.with_ymd_and_hms(2025, 6, day, 9, 0, 0).unwrap();
+                Ok(vec![
+                    Change {
+                        kind: ChangeKind::Created,
+                        by: None,
+                        at: at(1),
+                        before: None,
+                        after: Some(created.clone()),
+                    },
+                    Change {
+                        kind: ChangeKind::Updated,
+                        by: None,
+                        at: at(2),
+                        before: Some(created),
+                        after: Some(described),
+                    },
+                ])
+            }
+            _ => Ok(vec![]),
+        }
+    }
+}
+
+#[cfg(test)]
+#[coverage(off)]
+mod tests {
+    use uuid::uuid;
+
+    use super::*;
+    use crate::CRUD;
+
+    #[test]
+    fn task_history() {
+        let task = Task::get(&TestBackend, &uuid!("0196b4c9-8447-7959-ae1f-72c7c8a3dd36")).unwrap();
+        let history = task.history(&TestBackend).unwrap();
+        let kinds: Vec<ChangeKind> = history.iter().map(|change| change.kind).collect();
+        assert_eq!(kinds, vec![ChangeKind::Created, ChangeKind::Updated]);
+        let description_changed = history
+            .iter()
+            .find(|change| match (&change.before, &change.after) {
+                (Some(before), Some(after)) => before.description != after.description,
+                _ => false,
+            })
+            .unwrap();
+        assert_eq!(
+            description_changed.at,
+            Utc.with_ymd_and_hms(2025, 6, 2, 9, 0, 0).unwrap()
+        );
+        assert!(
+            Task::new("Never stored", None)
+                .history(&TestBackend)
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
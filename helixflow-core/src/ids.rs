@@ -0,0 +1,102 @@
+//! Keys which backends store items under.
+//!
+//! Items are always identified by a `Uuid`, ideally a UUIDv7 so that `created_at` works.
+//! Backends whose records have other keys, e.g. strings or ULIDs in existing data, convert them
+//! with `ItemId` instead of rejecting them.
+
+use uuid::{Builder, Uuid, uuid};
+
+/// Namespace for the UUIDv5s made from keys which are neither UUIDs nor ULIDs.
+pub const NAMESPACE: Uuid = uuid!("74429a3d-c1e3-42d0-91be-b4ec6ccf58be");
+
+/// A key which a backend can store an item under.
+pub trait ItemId {
+    /// The id of the item stored under this key. The same key always gives the same id.
+    fn to_uuid(&self) -> Uuid;
+}
+
+impl ItemId for Uuid {
+    fn to_uuid(&self) -> Uuid {
+        *self
+    }
+}
+
+/// UUIDs are used as they are, ULIDs keep their timestamp as a UUIDv7, anything else is hashed
+/// to a UUIDv5 in `NAMESPACE`.
+impl ItemId for str {
+    fn to_uuid(&self) -> Uuid {
+        Uuid::try_parse(self)
+            .ok()
+            .or_else(|| ulid(self))
+            .unwrap_or_else(|| Uuid::new_v5(&NAMESPACE, self.as_bytes()))
+    }
+}
+
+impl ItemId for String {
+    fn to_uuid(&self) -> Uuid {
+        self.as_str().to_uuid()
+    }
+}
+
+impl ItemId for i64 {
+    fn to_uuid(&self) -> Uuid {
+        self.to_string().to_uuid()
+    }
+}
+
+const CROCKFORD: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+/// Parse `key` as a ULID, which has the same layout as a UUIDv7 apart from the version & variant
+/// bits. Setting those discards 6 of the ULID's 80 random bits.
+fn ulid(key: &str) -> Option<Uuid> {
+    if key.len() != 26 {
+        return None;
+    }
+    let mut value: u128 = 0;
+    for (position, char) in key.bytes().enumerate() {
+        let digit = CROCKFORD
+            .iter()
+            .position(|digit| *digit == char.to_ascii_uppercase())?;
+        // 26 digits hold 130 bits, the first may only use 3
+        if position == 0 && digit > 7 {
+            return None;
+        }
+        value = value << 5 | digit as u128;
+    }
+    let bytes = value.to_be_bytes();
+    let millis = u64::try_from(value >> 80).ok()?;
+    let random = bytes[6..].try_into().ok()?;
+    Some(Builder::from_unix_timestamp_millis(millis, random).into_uuid())
+}
+
+#[cfg(test)]
+#[coverage(off)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::*;
+    use crate::timestamps::created_at;
+
+    #[test]
+    fn keys_to_uuids() {
+        let id = uuid!("0196b4c9-8447-7959-ae1f-72c7c8a3dd36");
+        assert_eq!(id.to_uuid(), id);
+        assert_eq!("0196b4c9-8447-7959-ae1f-72c7c8a3dd36".to_uuid(), id);
+
+        let from_ulid = "01ARZ3NDEKTSV4RRFFQ69G5FAV".to_uuid();
+        assert_eq!(from_ulid.get_version_num(), 7);
+        assert_eq!(
+            created_at(&from_ulid),
+            Some(Utc.timestamp_millis_opt(1_469_922_850_259).unwrap())
+        );
+        assert_eq!("01arz3ndektsv4rrffq69g5fav".to_uuid(), from_ulid);
+        // Too large to be a ULID
+        assert_eq!("81ARZ3NDEKTSV4RRFFQ69G5FAV".to_uuid().get_version_num(), 5);
+
+        let named = "groceries".to_uuid();
+        assert_eq!(named.get_version_num(), 5);
+        assert_eq!("groceries".to_string().to_uuid(), named);
+        assert_ne!("chores".to_uuid(), named);
+        assert_eq!(42.to_uuid(), "42".to_uuid());
+    }
+}
@@ -0,0 +1,73 @@
+//! The inbox: a well-known tasklist to capture tasks into quickly, without deciding where they
+//! belong. They are sorted into real lists later, during triage.
+
+use uuid::{Uuid, uuid};
+
+use crate::{
+    CRUD, HelixFlowError, HelixFlowResult, Link, Relate, Store, Validate,
+    task::{Contains, Task, TaskList},
+};
+
+/// The id of the inbox, the same in every backend.
+pub const INBOX_ID: Uuid = uuid!("0199b6d0-0000-7000-8000-00000000001b");
+
+impl TaskList {
+    /// The inbox from `backend`, created on first use.
+    pub fn inbox<B: Store<TaskList>>(backend: &B) -> HelixFlowResult<TaskList> {
+        match TaskList::get(backend, &INBOX_ID) {
+            Err(HelixFlowError::NotFound { .. }) => {
+                let inbox = TaskList {
+                    id: INBOX_ID,
+                    ..TaskList::new("Inbox")
+                };
+                inbox.create(backend)?;
+                Ok(inbox)
+            }
+            existing => existing,
+        }
+    }
+
+    pub fn is_inbox(&self) -> bool {
+        self.id == INBOX_ID
+    }
+}
+
+impl Task {
+    /// Store this task at the end of the inbox. Invalid tasks are never stored.
+    pub fn capture<B>(&self, backend: &B) -> HelixFlowResult<()>
+    where
+        B: Store<TaskList> + Relate<Contains<TaskList, Task>>,
+    {
+        self.validate()?;
+        TaskList::inbox(backend)?
+            .link(self)
+            .create_linked_item(backend)
+    }
+}
+
+#[cfg(test)]
+#[coverage(off)]
+mod tests {
+    use std::assert_matches::assert_matches;
+
+    use super::*;
+    use crate::task::TestBackend;
+
+    #[test]
+    fn capture_into_inbox() {
+        let inbox = TaskList::inbox(&TestBackend).unwrap();
+        assert!(inbox.is_inbox());
+        assert_eq!(inbox.name, "Inbox");
+        Task::new("Call the plumber", None)
+            .capture(&TestBackend)
+            .unwrap();
+    }
+
+    #[test]
+    fn capture_blank_name() {
+        assert_matches!(
+            Task::new(" ", None).capture(&TestBackend),
+            Err(HelixFlowError::ValidationFailed { .. })
+        );
+    }
+}
@@ -0,0 +1,236 @@
+//! Exchanging data with other tools, as plain JSON.
+
+use std::path::PathBuf;
+
+use serde::Serialize;
+
+use crate::{
+    HelixFlowResult, Relate,
+    attachment::{Attachment, AttachmentContent, HasAttachment},
+    comments::{Comment, Comments},
+    task::{Contains, Task, TaskList},
+};
+
+pub const REDACTED: &str = "[redacted]";
+
+/// Whether an export includes free-text content.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ExportMode {
+    #[default]
+    Full,
+    /// Keep structure, names, dates and statuses but replace free text with `REDACTED`, so the
+    /// export can be shared for debugging or reporting without leaking content.
+    Redacted,
+}
+
+/// Remove free-text content while keeping the shape of the item.
+pub trait Redact {
+    fn redacted(&self) -> Self;
+}
+
+impl Redact for Task {
+    fn redacted(&self) -> Self {
+        Task {
+            description: self.description.as_ref().map(|_| REDACTED.into()),
+            ..self.clone()
+        }
+    }
+}
+
+impl Redact for Comment {
+    fn redacted(&self) -> Self {
+        Comment {
+            text: REDACTED.into(),
+            ..self.clone()
+        }
+    }
+}
+
+/// Keeps the type, but not the name or content of the file.
+impl Redact for Attachment {
+    fn redacted(&self) -> Self {
+        Attachment {
+            filename: REDACTED.into(),
+            content: match self.content {
+                AttachmentContent::Blob(_) => AttachmentContent::Blob(vec![]),
+                AttachmentContent::Path(_) => AttachmentContent::Path(PathBuf::from(REDACTED)),
+            },
+            ..self.clone()
+        }
+    }
+}
+
+impl ExportMode {
+    fn apply<T: Redact>(self, item: T) -> T {
+        match self {
+            ExportMode::Full => item,
+            ExportMode::Redacted => item.redacted(),
+        }
+    }
+}
+
+/// A task, with its comments & attachments.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ExportedTask {
+    #[serde(flatten)]
+    pub task: Task,
+    pub comments: Vec<Comment>,
+    pub attachments: Vec<Attachment>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ExportedTaskList {
+    pub tasklist: TaskList,
+    pub tasks: Vec<ExportedTask>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Export {
+    pub tasklists: Vec<ExportedTaskList>,
+}
+
+impl Export {
+    /// Export `tasklists` and their tasks, with the tasks' comments & attachments, from
+    /// `backend`.
+    pub fn of<B>(tasklists: &[TaskList], backend: &B, mode: ExportMode) -> HelixFlowResult<Self>
+    where
+        B: Relate<Contains<TaskList, Task>> + Relate<HasAttachment<Task, Attachment>> + Comments,
+    {
+        let tasklists = tasklists
+            .iter()
+            .map(|tasklist| {
+                let tasks = tasklist
+                    .get_linked_items(backend)?
+                    .map(|link| {
+                        let task = link.right?;
+                        Ok(ExportedTask {
+                            comments: backend
+                                .comments_on(&task)?
+                                .into_iter()
+                                .map(|comment| mode.apply(comment))
+                                .collect(),
+                            attachments: task
+                                .attachments(backend)?
+                                .into_iter()
+                                .map(|attachment| mode.apply(attachment))
+                                .collect(),
+                            task: mode.apply(task),
+                        })
+                    })
+                    .collect::<HelixFlowResult<Vec<ExportedTask>>>()?;
+                Ok(ExportedTaskList {
+                    tasklist: tasklist.clone(),
+                    tasks,
+                })
+            })
+            .collect::<HelixFlowResult<Vec<ExportedTaskList>>>()?;
+        Ok(Export { tasklists })
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("export contains only serializable data")
+    }
+}
+
+#[cfg(test)]
+#[coverage(off)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+    use uuid::{Uuid, uuid};
+
+    use super::*;
+    use crate::{actor::Actor, task::TestBackend};
+
+    fn tasklist() -> TaskList {
+        TaskList {
+            name: "Test TaskList 1".into(),
+            id: uuid!("0196fe23-7c01-7d6b-9e09-5968eb370549"),
+            sort: Default::default(),
+        }
+    }
+
+    #[test]
+    fn redact_task() {
+        let task = Task {
+            due: Some(Utc.with_ymd_and_hms(2025, 6, 2, 12, 0, 0).unwrap()),
+            ..Task::new("Renew passport", Some("Number 12345678"))
+        };
+        let redacted = task.redacted();
+        assert_eq!(redacted.description.as_deref(), Some(REDACTED));
+        assert_eq!(
+            Task {
+                description: task.description.clone(),
+                ..redacted
+            },
+            task
+        );
+    }
+
+    #[test]
+    fn no_description_stays_none() {
+        let task = Task::new("Renew passport", None);
+        assert_eq!(task.redacted(), task);
+    }
+
+    #[test]
+    fn redact_comment() {
+        let task = Task::new("Renew passport", None);
+        let comment = Comment::new(
+            &task,
+            "@bob it's 12345678",
+            &Actor::new("alice", &Uuid::now_v7()),
+        );
+        let redacted = comment.redacted();
+        assert_eq!(redacted.text, REDACTED);
+        assert_eq!(redacted.annotation, comment.annotation);
+    }
+
+    #[test]
+    fn redact_attachment() {
+        let blob = Attachment::new(
+            "passport.txt",
+            "text/plain",
+            AttachmentContent::Blob(b"12345678".to_vec()),
+        );
+        let redacted = blob.redacted();
+        assert_eq!(redacted.filename, REDACTED);
+        assert_eq!(redacted.mime_type, "text/plain");
+        assert_eq!(redacted.content, AttachmentContent::Blob(vec![]));
+        let link = Attachment::link_to(&PathBuf::from("/home/alice/passport.pdf"));
+        assert_eq!(
+            link.redacted().content,
+            AttachmentContent::Path(PathBuf::from(REDACTED))
+        );
+    }
+
+    #[test]
+    fn export_modes() {
+        let full = Export::of(&[tasklist()], &TestBackend, ExportMode::Full).unwrap();
+        let redacted = Export::of(&[tasklist()], &TestBackend, ExportMode::Redacted).unwrap();
+        let tasks = &full.tasklists[0].tasks;
+        assert_eq!(tasks.len(), 2);
+        assert_eq!(tasks[0].comments[0].text, "@bob did you get the milk?");
+        assert_eq!(tasks[0].attachments[0].filename, "notes.txt");
+
+        let task_1 = &redacted.tasklists[0].tasks[0];
+        // Test tasks have no descriptions
+        assert_eq!(task_1.task, tasks[0].task);
+        assert_eq!(task_1.comments[0].text, REDACTED);
+        assert_eq!(
+            task_1.comments[0].annotation,
+            tasks[0].comments[0].annotation
+        );
+        assert_eq!(task_1.attachments[0].filename, REDACTED);
+        assert_eq!(task_1.attachments[0].id, tasks[0].attachments[0].id);
+        assert_eq!(redacted.tasklists[0].tasks[1], tasks[1]);
+    }
+
+    #[test]
+    fn export_json() {
+        let json = Export::of(&[tasklist()], &TestBackend, ExportMode::Redacted)
+            .unwrap()
+            .to_json();
+        assert!(json.contains("\"name\": \"Task 1\""));
+        assert!(!json.contains("milk"));
+    }
+}
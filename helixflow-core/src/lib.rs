@@ -6,24 +6,125 @@
 #![feature(coverage_attribute)]
 #![feature(try_trait_v2)]
 
+// Lets the derive macros refer to `::helixflow_core` from inside this crate too.
+extern crate self as helixflow_core;
+
 use std::any::Any;
 
+use chrono::{DateTime, Utc};
+pub use helixflow_macros::{HelixFlowItem, Relationship};
+
+use serde::Serialize;
+use serde_json::Value;
 use uuid::Uuid;
 
+use crate::{
+    events::Event,
+    task::{Status, Task},
+};
+
+pub mod actor;
+pub mod analytics;
+pub mod archive;
+pub mod attachment;
+pub mod bulk;
+pub mod cache;
+pub mod calendar;
+pub mod command;
+pub mod comments;
+pub mod contexts;
+pub mod dates;
+pub mod deeplink;
+pub mod dependency;
+pub mod duplicate;
+pub mod errors;
+pub mod estimates;
+pub mod events;
+pub mod graph;
+pub mod history;
+pub mod ids;
+pub mod inbox;
+pub mod interop;
+pub mod non_blocking;
+pub mod paging;
+pub mod person;
+pub mod project;
+pub mod query;
+pub mod quickadd;
+pub mod rank;
+pub mod relative;
+pub mod reminders;
+pub mod reports;
+pub mod resilient;
+pub mod search;
+pub mod settings;
+pub mod share;
+pub mod sorting;
 pub mod state;
+pub mod sublists;
+pub mod subtasks;
+pub mod suggestions;
+pub mod table;
+pub mod tag;
 pub mod task;
+#[cfg(feature = "telemetry")]
+pub mod telemetry;
+pub mod timestamps;
+pub mod transaction;
+pub mod trash;
+pub mod user;
+pub mod versions;
+pub mod worklog;
+pub mod workspace;
 
 /// Marker trait for our data items
-// TODO: Derive macro for HelixFlowItem, as we can't have a standard impl of `as_any`
-//       and remain dyn compatible
+///
+/// Derive this, as there can't be a standard impl of `as_any` which remains dyn compatible.
 pub trait HelixFlowItem
 where
     // required for Mismatch Error (which uses `Box<dyn HelixFlowItem>`)
     Self: std::fmt::Debug + Send + Sync + 'static + Any,
 {
     fn as_any(&self) -> &dyn Any;
+    /// The unique id used as database key
+    fn id(&self) -> &Uuid;
+    /// The name of the item's type, e.g. `"Task"`, as used in `events`.
+    fn itemtype(&self) -> &'static str;
+    /// When the item was created, taken from its `id`. `None` unless the id is a UUIDv7.
+    fn created_at(&self) -> Option<DateTime<Utc>> {
+        timestamps::created_at(self.id())
+    }
+    /// When the item was last stored, for items with an `updated_at: LastModified` field.
+    fn updated_at(&self) -> Option<DateTime<Utc>> {
+        None
+    }
+    /// The version of the stored record this item was read from, for items with a
+    /// `version: Version` field. `None` if the item was not read from a backend.
+    fn version(&self) -> Option<u64> {
+        None
+    }
+    /// A copy of the item, marked as stored `at`. Used by `CRUD` to maintain `updated_at`.
+    fn stamped(&self, at: DateTime<Utc>) -> Self
+    where
+        Self: Clone,
+    {
+        let _ = at;
+        self.clone()
+    }
 }
 
+/// The bounds every `HelixFlowItem` needs, checked by `#[derive(HelixFlowItem)]`.
+#[doc(hidden)]
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` cannot be a HelixFlowItem",
+    label = "must be `Debug + Send + Sync + 'static`",
+    note = "items are shared between the UI & background threads, and reported in errors"
+)]
+pub trait ItemBounds: std::fmt::Debug + Send + Sync + 'static {}
+
+impl<T: std::fmt::Debug + Send + Sync + 'static> ItemBounds for T {}
+
+/// See `errors` for codes & messages to show the user.
 #[derive(Debug, thiserror::Error)]
 pub enum HelixFlowError {
     // The #[from] anyhow::Error will convert anything that offers `into anyhow::Error`.
@@ -47,20 +148,170 @@ pub enum HelixFlowError {
         left: Box<HelixFlowResult<Box<dyn HelixFlowItem>>>,
         right: Box<HelixFlowResult<Box<dyn HelixFlowItem>>>,
     },
+
+    #[error("backend unavailable after repeated failures, retry in {retry_in:?}")]
+    CircuitOpen { retry_in: std::time::Duration },
+
+    #[error("share token {id} has expired or been revoked")]
+    ShareNotValid { id: Uuid },
+
+    #[error("{link} is not a valid HelixFlow link")]
+    InvalidLink { link: String },
+
+    #[error("{value:?} is not a valid {field}")]
+    InvalidValue { field: String, value: String },
+
+    #[error("task cannot move from {from:?} to {to:?}")]
+    InvalidTransition {
+        from: task::Status,
+        to: task::Status,
+    },
+
+    #[error("dependency would create a cycle: {cycle:?}")]
+    DependencyCycle { cycle: Vec<Uuid> },
+
+    #[error("invalid {field}: {reason}")]
+    ValidationFailed { field: String, reason: String },
+
+    #[error("{itemtype} {id} was changed elsewhere: read at version {expected}, now {stored}")]
+    Conflict {
+        itemtype: String,
+        id: Uuid,
+        expected: u64,
+        stored: u64,
+    },
 }
 
 pub type HelixFlowResult<T> = std::result::Result<T, HelixFlowError>;
 
+/// How closely the record returned by a backend must match the item which was sent to it.
+///
+/// Backends may legitimately normalise data (trimming whitespace) or enrich records (server-side
+/// timestamps), which should not be reported as a `Mismatch`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Verification {
+    /// Stored record must be identical
+    #[default]
+    Strict,
+    /// Leading & trailing whitespace in any text is ignored
+    Normalised,
+    /// The named top-level fields are ignored
+    IgnoreFields(Vec<&'static str>),
+}
+
+impl Verification {
+    /// Does `actual` match `expected` under this policy?
+    pub fn matches<ITEM>(&self, expected: &ITEM, actual: &ITEM) -> bool
+    where
+        ITEM: Serialize + PartialEq,
+    {
+        if expected == actual {
+            return true;
+        }
+        let (Ok(expected), Ok(actual)) =
+            (serde_json::to_value(expected), serde_json::to_value(actual))
+        else {
+            return false;
+        };
+        // As with `PartialEq`, when & how often the record was stored is not part of the item
+        let (expected, actual) = (
+            without(expected, &["updated_at", "version"]),
+            without(actual, &["updated_at", "version"]),
+        );
+        match self {
+            Verification::Strict => false,
+            Verification::Normalised => normalise(expected) == normalise(actual),
+            Verification::IgnoreFields(fields) => {
+                without(expected, fields) == without(actual, fields)
+            }
+        }
+    }
+}
+
+fn normalise(value: Value) -> Value {
+    match value {
+        Value::String(text) => Value::String(text.trim().to_string()),
+        Value::Array(values) => Value::Array(values.into_iter().map(normalise).collect()),
+        Value::Object(fields) => Value::Object(
+            fields
+                .into_iter()
+                .map(|(name, value)| (name, normalise(value)))
+                .collect(),
+        ),
+        other => other,
+    }
+}
+
+fn without(mut value: Value, fields: &[&str]) -> Value {
+    if let Value::Object(object) = &mut value {
+        for field in fields {
+            object.remove(*field);
+        }
+    }
+    value
+}
+
+/// Longest name allowed for any item, in characters.
+pub const MAX_NAME_LENGTH: usize = 200;
+
+/// Checks made on an item before it is sent to a backend, by `CRUD::create` & `CRUD::update`.
+///
+/// The default accepts anything, so items without rules can use an empty `impl`.
+pub trait Validate {
+    /// Fails with `ValidationFailed` for the first invalid field.
+    fn validate(&self) -> HelixFlowResult<()> {
+        Ok(())
+    }
+}
+
+/// A `name` must not be blank, nor longer than `MAX_NAME_LENGTH`.
+pub(crate) fn validate_name(name: &str) -> HelixFlowResult<()> {
+    let reason = if name.trim().is_empty() {
+        "must not be blank".to_string()
+    } else if name.chars().count() > MAX_NAME_LENGTH {
+        format!("must be at most {MAX_NAME_LENGTH} characters")
+    } else {
+        return Ok(());
+    };
+    Err(HelixFlowError::ValidationFailed {
+        field: "name".into(),
+        reason,
+    })
+}
+
 pub trait CRUD
 where
     Self: Sized,
 {
     fn create<B: Store<Self>>(&self, backend: &B) -> HelixFlowResult<()>;
+    /// Create, allowing the stored record to differ from `self` as permitted by `verification`.
+    fn create_with<B: Store<Self>>(
+        &self,
+        backend: &B,
+        verification: &Verification,
+    ) -> HelixFlowResult<()>;
     fn get<B: Store<Self>>(backend: &B, id: &Uuid) -> HelixFlowResult<Self>;
+    fn update<B: Store<Self>>(&self, backend: &B) -> HelixFlowResult<()>;
+    fn delete<B: Store<Self>>(&self, backend: &B) -> HelixFlowResult<()>;
+    /// Update, allowing the stored record to differ from `self` as permitted by `verification`.
+    fn update_with<B: Store<Self>>(
+        &self,
+        backend: &B,
+        verification: &Verification,
+    ) -> HelixFlowResult<()>;
+    /// Create all of `items` in one go, see `Store::create_all`.
+    fn create_all<B: Store<Self>>(items: &[Self], backend: &B) -> HelixFlowResult<()>;
+    /// Update all of `items` in one go, see `Store::update_all`.
+    fn update_all<B: Store<Self>>(items: &[Self], backend: &B) -> HelixFlowResult<()>;
+    /// Create or update, whichever is needed, see `Store::put`.
+    fn put<B: Store<Self>>(&self, backend: &B) -> HelixFlowResult<()>;
 }
 
 /// Methods to store and retrieve `ITEM` in a backend
-pub trait Store<ITEM> {
+///
+/// Backends are shared between the UI and background threads via `Arc`, so must be
+/// `Send + Sync`.
+pub trait Store<ITEM>: Send + Sync {
     /// Create a new `ITEM` in the backend.
     ///
     /// The returned `ITEM` should be the actual stored record from the backend - to allow
@@ -69,49 +320,247 @@ pub trait Store<ITEM> {
 
     /// Get an `ITEM` from the backend
     fn get(&self, id: &Uuid) -> HelixFlowResult<ITEM>;
+
+    /// Replace the stored `ITEM` which has the same id as `item`.
+    ///
+    /// Backends which version their records fail with `HelixFlowError::Conflict` if `item` was
+    /// read at a different version to the one stored, see `versions::next_version`.
+    ///
+    /// The returned `ITEM` should be the actual stored record from the backend - to allow
+    /// validation by `CRUD<ITEM>::update()`
+    fn update(&self, item: &ITEM) -> HelixFlowResult<ITEM>;
+
+    /// Remove the `ITEM` with `id` from the backend, along with any relationships to it.
+    fn delete(&self, id: &Uuid) -> HelixFlowResult<()>;
+
+    /// Create all of `items`, returning the stored records in the same order.
+    ///
+    /// The default creates each item in turn, stopping at the first error. Backends should
+    /// override this to store them in one round trip, and either store all of them or none.
+    fn create_all(&self, items: &[ITEM]) -> HelixFlowResult<Vec<ITEM>> {
+        items.iter().map(|item| self.create(item)).collect()
+    }
+
+    /// Replace all of `items`, returning the stored records in the same order.
+    ///
+    /// The default updates each item in turn, stopping at the first error. Backends should
+    /// override this to store them in one round trip, and either store all of them or none.
+    fn update_all(&self, items: &[ITEM]) -> HelixFlowResult<Vec<ITEM>> {
+        items.iter().map(|item| self.update(item)).collect()
+    }
+
+    /// Store `item`, replacing the stored `ITEM` with the same id or creating it if there is
+    /// none.
+    ///
+    /// The default tries `update`, then `create`. Backends should override this with a single
+    /// upsert.
+    fn put(&self, item: &ITEM) -> HelixFlowResult<ITEM> {
+        match self.update(item) {
+            Err(HelixFlowError::NotFound { .. }) => self.create(item),
+            result => result,
+        }
+    }
 }
 
 impl<ITEM> CRUD for ITEM
 where
-    ITEM: HelixFlowItem + PartialEq + Clone,
+    ITEM: HelixFlowItem + Validate + PartialEq + Clone + Serialize,
 {
     /// Create this item in a given storage backend.
     fn create<B: Store<ITEM>>(&self, backend: &B) -> HelixFlowResult<()> {
-        let created_item = backend.create(self)?;
-        if &created_item == self {
-            Ok(())
-        } else {
-            Err(HelixFlowError::Mismatch {
-                expected: Box::new(self.clone()),
-                actual: Box::new(created_item),
-            })
-        }
+        self.create_with(backend, &Verification::Strict)
+    }
+
+    /// Create this item in a given storage backend, verifying the stored record according to
+    /// `verification`. Invalid items are never sent to the backend.
+    ///
+    /// The stored record's `updated_at` is set to now, see `HelixFlowItem::stamped`.
+    fn create_with<B: Store<ITEM>>(
+        &self,
+        backend: &B,
+        verification: &Verification,
+    ) -> HelixFlowResult<()> {
+        self.validate()?;
+        let item = self.stamped(Utc::now());
+        let created_item = backend.create(&item)?;
+        verify(&item, created_item, verification)?;
+        events::emit(Event::Created {
+            itemtype: self.itemtype(),
+            id: *self.id(),
+        });
+        Ok(())
     }
 
     /// Get item from `backend` by `id`
     fn get<B: Store<ITEM>>(backend: &B, id: &Uuid) -> HelixFlowResult<ITEM> {
         backend.get(id)
     }
+
+    /// Save changes to this item in a given storage backend.
+    fn update<B: Store<ITEM>>(&self, backend: &B) -> HelixFlowResult<()> {
+        self.update_with(backend, &Verification::Strict)
+    }
+
+    /// Save changes to this item in a given storage backend, verifying the stored record
+    /// according to `verification`. Invalid items are never sent to the backend.
+    ///
+    /// The stored record's `updated_at` is set to now, see `HelixFlowItem::stamped`. Fails with
+    /// `HelixFlowError::Conflict` if the record has changed since this item was read, see
+    /// `crate::versions`.
+    fn update_with<B: Store<ITEM>>(
+        &self,
+        backend: &B,
+        verification: &Verification,
+    ) -> HelixFlowResult<()> {
+        self.validate()?;
+        let before = before_update(self, backend);
+        let item = self.stamped(Utc::now());
+        let updated_item = backend.update(&item)?;
+        verify(&item, updated_item, verification)?;
+        emit_updated(self, before);
+        Ok(())
+    }
+
+    /// Create all of `items` in a given storage backend, verifying each stored record strictly.
+    /// Nothing is sent to the backend unless every item is valid.
+    fn create_all<B: Store<ITEM>>(items: &[ITEM], backend: &B) -> HelixFlowResult<()> {
+        items.iter().try_for_each(Validate::validate)?;
+        let now = Utc::now();
+        let items: Vec<ITEM> = items.iter().map(|item| item.stamped(now)).collect();
+        let created_items = backend.create_all(&items)?;
+        verify_all(&items, created_items)?;
+        for item in &items {
+            events::emit(Event::Created {
+                itemtype: item.itemtype(),
+                id: *item.id(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Save changes to all of `items` in a given storage backend, verifying each stored record
+    /// strictly. Nothing is sent to the backend unless every item is valid.
+    fn update_all<B: Store<ITEM>>(items: &[ITEM], backend: &B) -> HelixFlowResult<()> {
+        items.iter().try_for_each(Validate::validate)?;
+        let before: Vec<Option<ITEM>> = items
+            .iter()
+            .map(|item| before_update(item, backend))
+            .collect();
+        let now = Utc::now();
+        let stamped: Vec<ITEM> = items.iter().map(|item| item.stamped(now)).collect();
+        let updated_items = backend.update_all(&stamped)?;
+        verify_all(&stamped, updated_items)?;
+        for (item, before) in items.iter().zip(before) {
+            emit_updated(item, before);
+        }
+        Ok(())
+    }
+
+    /// Store this item in a given storage backend, whether or not it has been stored before,
+    /// verifying the stored record strictly. Announced as `Updated`.
+    fn put<B: Store<ITEM>>(&self, backend: &B) -> HelixFlowResult<()> {
+        self.validate()?;
+        let before = before_update(self, backend);
+        let item = self.stamped(Utc::now());
+        let stored_item = backend.put(&item)?;
+        verify(&item, stored_item, &Verification::Strict)?;
+        emit_updated(self, before);
+        Ok(())
+    }
+
+    /// Remove this item from a given storage backend.
+    fn delete<B: Store<ITEM>>(&self, backend: &B) -> HelixFlowResult<()> {
+        backend.delete(self.id())?;
+        events::emit(Event::Deleted {
+            itemtype: self.itemtype(),
+            id: *self.id(),
+        });
+        Ok(())
+    }
+}
+
+/// The stored version of `item`, if needed to spot a task being completed by an update.
+fn before_update<ITEM, B>(item: &ITEM, backend: &B) -> Option<ITEM>
+where
+    ITEM: HelixFlowItem,
+    B: Store<ITEM>,
+{
+    match item.as_any().downcast_ref::<Task>() {
+        Some(_) if events::has_subscribers() => backend.get(item.id()).ok(),
+        _ => None,
+    }
+}
+
+/// Announce that `item` was updated from `before`.
+fn emit_updated<ITEM: HelixFlowItem>(item: &ITEM, before: Option<ITEM>) {
+    events::emit(Event::Updated {
+        itemtype: item.itemtype(),
+        id: *item.id(),
+    });
+    let done = |item: &ITEM| {
+        item.as_any()
+            .downcast_ref::<Task>()
+            .is_some_and(|task| task.status == Status::Done)
+    };
+    if done(item) && before.is_some_and(|before| !done(&before)) {
+        events::emit(Event::TaskCompleted { id: *item.id() });
+    }
+}
+
+/// Check the records returned by a bulk operation against the `expected` items, in order.
+fn verify_all<ITEM>(expected: &[ITEM], stored: Vec<ITEM>) -> HelixFlowResult<()>
+where
+    ITEM: HelixFlowItem + PartialEq + Clone + Serialize,
+{
+    if stored.len() != expected.len() {
+        return Err(anyhow::anyhow!(
+            "Backend stored {} records, expected {}",
+            stored.len(),
+            expected.len()
+        )
+        .into());
+    }
+    expected
+        .iter()
+        .zip(stored)
+        .try_for_each(|(expected, stored)| verify(expected, stored, &Verification::Strict))
+}
+
+/// Check the record returned by a backend against the `expected` item.
+fn verify<ITEM>(expected: &ITEM, stored: ITEM, verification: &Verification) -> HelixFlowResult<()>
+where
+    ITEM: HelixFlowItem + PartialEq + Clone + Serialize,
+{
+    if verification.matches(expected, &stored) {
+        Ok(())
+    } else {
+        Err(HelixFlowError::Mismatch {
+            expected: Box::new(expected.clone()),
+            actual: Box::new(stored),
+        })
+    }
 }
 
 /// A valid usage of a relationship struct, defining acceptable types for left & right.
 ///
+/// Derive this, along with `Link`, `Linkable`, `Try` & `FromResidual`, for each valid pairing.
 /// E.g. to allow `Contains`to be used for `TaskList -> Contains -> Task`:
 /// ```ignore
-/// impl Relationship for Contains<TaskList, Task> {
-///    type Left = TaskList;
-///    type Right = Task;
+/// #[derive(Debug, Relationship)]
+/// #[relationship(TaskList => Task)]
+/// pub struct Contains<LEFT, RIGHT> {
+///     pub left: HelixFlowResult<LEFT>,
+///     pub right: HelixFlowResult<RIGHT>,
 /// }
 /// ```
-// TODO: Add derive macro to generate Link, Linkable, Relate, Try & FromResidual for valid
-//       type pairings. Can't do this in a blanket impl as it requires guarantees for fields
-//       `left` and `right`
 pub trait Relationship
 where
     Self: Sized,
 {
     type Left: HelixFlowItem;
     type Right: HelixFlowItem;
+    /// The name of the relationship, e.g. `"Contains"`, as used in `events`.
+    const NAME: &'static str;
 }
 
 /// `impl Link<REL> for LEFT` gives `Left Rel:(-> link_type -> Right)`
@@ -128,11 +577,97 @@ pub trait Linkable<REL: Link> {
         &self,
         backend: &B,
     ) -> HelixFlowResult<impl Iterator<Item = REL>>;
+
+    /// Remove the link to `right`, leaving both items in place.
+    fn unlink<B: Relate<REL>>(&self, right: &REL::Right, backend: &B) -> HelixFlowResult<()>
+    where
+        Self: HelixFlowItem,
+    {
+        backend.delete_link(&self.link(right))?;
+        events::emit(Event::Unlinked {
+            relationship: REL::NAME,
+            left: *self.id(),
+            right: *right.id(),
+        });
+        Ok(())
+    }
 }
 
 /// Methods to relate items in a backend
-pub trait Relate<REL: Link> {
+///
+/// Backends are shared between the UI and background threads via `Arc`, so must be
+/// `Send + Sync`.
+pub trait Relate<REL: Link>: Send + Sync {
     /// Create and link the related item
     fn create_linked_item(&self, link: &REL) -> HelixFlowResult<REL>;
     fn get_linked_items(&self, left: &REL::Left) -> HelixFlowResult<impl Iterator<Item = REL>>;
+
+    /// Remove the relationship between `link.left` & `link.right`, without deleting either item.
+    /// Removing a link which does not exist is not an error.
+    fn delete_link(&self, link: &REL) -> HelixFlowResult<()>;
+
+    /// Link `link.left` & `link.right`, which must both already exist.
+    ///
+    /// The default fails: backends only need to implement this for relationships which are
+    /// changed with `move_all`.
+    fn create_link(&self, _link: &REL) -> HelixFlowResult<()> {
+        Err(anyhow::anyhow!("{} links can only be created with their item", REL::NAME).into())
+    }
+
+    /// Replace each link in `from` with the one at the same position in `to`, e.g. to move items
+    /// between lists. The items themselves must already exist.
+    ///
+    /// The default fails if `from` & `to` are different lengths, then makes each change in turn
+    /// with `delete_link` & `create_link`, stopping at the first error. Backends should override
+    /// this to make all the changes in one round trip, and either make all of them or none.
+    fn move_all(&self, from: &[REL], to: &[REL]) -> HelixFlowResult<()> {
+        if from.len() != to.len() {
+            return Err(HelixFlowError::ValidationFailed {
+                field: "links".into(),
+                reason: format!("moving {} links to {} places", from.len(), to.len()),
+            });
+        }
+        for (from, to) in from.iter().zip(to) {
+            self.delete_link(from)?;
+            self.create_link(to)?;
+        }
+        Ok(())
+    }
+
+    /// One `page` of the items from `get_linked_items`, in the same order.
+    ///
+    /// The default fetches every item and skips to the page. Backends should override this if
+    /// they can fetch just the page, so that long lists don't stall the UI.
+    fn get_linked_page(
+        &self,
+        left: &REL::Left,
+        page: Page,
+    ) -> HelixFlowResult<impl Iterator<Item = REL>> {
+        Ok(self
+            .get_linked_items(left)?
+            .skip(page.offset)
+            .take(page.limit))
+    }
+}
+
+/// A slice of a longer list of items: skip `offset` items, then take up to `limit`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Page {
+    pub offset: usize,
+    pub limit: usize,
+}
+
+impl Page {
+    /// The first `limit` items.
+    pub fn first(limit: usize) -> Page {
+        Page { offset: 0, limit }
+    }
+
+    /// The page after this one, of the same size.
+    pub fn next(&self) -> Page {
+        Page {
+            offset: self.offset + self.limit,
+            limit: self.limit,
+        }
+    }
 }
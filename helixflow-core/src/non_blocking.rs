@@ -0,0 +1,271 @@
+//! Async versions of `Store`, `Relate` & `CRUD`, for targets which cannot block a thread while
+//! waiting for the backend, e.g. WASM or a server handling many requests.
+//!
+//! The domain logic is shared: `Blocking` lets a blocking backend be used where an async one is
+//! expected, and `BlockOn` does the reverse, so everything written against the blocking traits
+//! also works with an async backend.
+
+use std::{
+    pin::pin,
+    sync::Arc,
+    task::{Context, Poll, Wake, Waker},
+    thread::{self, Thread},
+};
+
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::{
+    HelixFlowItem, HelixFlowResult, Link, Validate, Verification, emit_updated,
+    events::{self, Event},
+    task::Task,
+    verify,
+};
+
+/// Async version of `crate::Store`.
+pub trait Store<ITEM>: Send + Sync {
+    /// See `crate::Store::create`
+    fn create(&self, item: &ITEM) -> impl Future<Output = HelixFlowResult<ITEM>> + Send;
+    /// See `crate::Store::get`
+    fn get(&self, id: &Uuid) -> impl Future<Output = HelixFlowResult<ITEM>> + Send;
+    /// See `crate::Store::update`
+    fn update(&self, item: &ITEM) -> impl Future<Output = HelixFlowResult<ITEM>> + Send;
+    /// See `crate::Store::delete`
+    fn delete(&self, id: &Uuid) -> impl Future<Output = HelixFlowResult<()>> + Send;
+}
+
+/// Async version of `crate::Relate`. Linked items are collected, as an iterator cannot be
+/// awaited.
+pub trait Relate<REL: Link>: Send + Sync {
+    /// See `crate::Relate::create_linked_item`
+    fn create_linked_item(&self, link: &REL) -> impl Future<Output = HelixFlowResult<REL>> + Send;
+    /// See `crate::Relate::get_linked_items`
+    fn get_linked_items(
+        &self,
+        left: &REL::Left,
+    ) -> impl Future<Output = HelixFlowResult<Vec<REL>>> + Send;
+    /// See `crate::Relate::delete_link`
+    fn delete_link(&self, link: &REL) -> impl Future<Output = HelixFlowResult<()>> + Send;
+}
+
+/// Async version of `crate::CRUD`, with the same validation, verification & events.
+pub trait CRUD: Sized {
+    fn create<B: Store<Self>>(
+        &self,
+        backend: &B,
+    ) -> impl Future<Output = HelixFlowResult<()>> + Send;
+    fn get<B: Store<Self>>(
+        backend: &B,
+        id: &Uuid,
+    ) -> impl Future<Output = HelixFlowResult<Self>> + Send;
+    fn update<B: Store<Self>>(
+        &self,
+        backend: &B,
+    ) -> impl Future<Output = HelixFlowResult<()>> + Send;
+    fn delete<B: Store<Self>>(
+        &self,
+        backend: &B,
+    ) -> impl Future<Output = HelixFlowResult<()>> + Send;
+}
+
+impl<ITEM> CRUD for ITEM
+where
+    ITEM: HelixFlowItem + Validate + PartialEq + Clone + Serialize,
+{
+    async fn create<B: Store<ITEM>>(&self, backend: &B) -> HelixFlowResult<()> {
+        self.validate()?;
+        let item = self.stamped(chrono::Utc::now());
+        let created_item = backend.create(&item).await?;
+        verify(&item, created_item, &Verification::Strict)?;
+        events::emit(Event::Created {
+            itemtype: self.itemtype(),
+            id: *self.id(),
+        });
+        Ok(())
+    }
+
+    async fn get<B: Store<ITEM>>(backend: &B, id: &Uuid) -> HelixFlowResult<ITEM> {
+        backend.get(id).await
+    }
+
+    async fn update<B: Store<ITEM>>(&self, backend: &B) -> HelixFlowResult<()> {
+        self.validate()?;
+        // Needed to spot a task being completed, see `crate::CRUD::update`
+        let before = match self.as_any().downcast_ref::<Task>() {
+            Some(_) if events::has_subscribers() => backend.get(self.id()).await.ok(),
+            _ => None,
+        };
+        let item = self.stamped(chrono::Utc::now());
+        let updated_item = backend.update(&item).await?;
+        verify(&item, updated_item, &Verification::Strict)?;
+        emit_updated(self, before);
+        Ok(())
+    }
+
+    async fn delete<B: Store<ITEM>>(&self, backend: &B) -> HelixFlowResult<()> {
+        backend.delete(self.id()).await?;
+        events::emit(Event::Deleted {
+            itemtype: self.itemtype(),
+            id: *self.id(),
+        });
+        Ok(())
+    }
+}
+
+/// Use a blocking backend where an async one is expected.
+///
+/// Every call blocks the thread until the backend returns, so only use this where that is
+/// acceptable, e.g. for a backend held in memory.
+#[derive(Debug, Clone, Default)]
+pub struct Blocking<B>(pub B);
+
+impl<ITEM, B> Store<ITEM> for Blocking<B>
+where
+    ITEM: Send + Sync,
+    B: crate::Store<ITEM>,
+{
+    async fn create(&self, item: &ITEM) -> HelixFlowResult<ITEM> {
+        self.0.create(item)
+    }
+
+    async fn get(&self, id: &Uuid) -> HelixFlowResult<ITEM> {
+        self.0.get(id)
+    }
+
+    async fn update(&self, item: &ITEM) -> HelixFlowResult<ITEM> {
+        self.0.update(item)
+    }
+
+    async fn delete(&self, id: &Uuid) -> HelixFlowResult<()> {
+        self.0.delete(id)
+    }
+}
+
+impl<REL, B> Relate<REL> for Blocking<B>
+where
+    REL: Link + Send + Sync,
+    B: crate::Relate<REL>,
+{
+    async fn create_linked_item(&self, link: &REL) -> HelixFlowResult<REL> {
+        self.0.create_linked_item(link)
+    }
+
+    async fn get_linked_items(&self, left: &REL::Left) -> HelixFlowResult<Vec<REL>> {
+        Ok(self.0.get_linked_items(left)?.collect())
+    }
+
+    async fn delete_link(&self, link: &REL) -> HelixFlowResult<()> {
+        self.0.delete_link(link)
+    }
+}
+
+/// Use an async backend where a blocking one is expected, waiting for each call with
+/// `block_on`. Don't use this on a thread which drives the backend's own futures.
+#[derive(Debug, Clone, Default)]
+pub struct BlockOn<B>(pub B);
+
+impl<ITEM, B> crate::Store<ITEM> for BlockOn<B>
+where
+    B: Store<ITEM>,
+{
+    fn create(&self, item: &ITEM) -> HelixFlowResult<ITEM> {
+        block_on(self.0.create(item))
+    }
+
+    fn get(&self, id: &Uuid) -> HelixFlowResult<ITEM> {
+        block_on(self.0.get(id))
+    }
+
+    fn update(&self, item: &ITEM) -> HelixFlowResult<ITEM> {
+        block_on(self.0.update(item))
+    }
+
+    fn delete(&self, id: &Uuid) -> HelixFlowResult<()> {
+        block_on(self.0.delete(id))
+    }
+}
+
+impl<REL, B> crate::Relate<REL> for BlockOn<B>
+where
+    REL: Link,
+    B: Relate<REL>,
+{
+    fn create_linked_item(&self, link: &REL) -> HelixFlowResult<REL> {
+        block_on(self.0.create_linked_item(link))
+    }
+
+    fn get_linked_items(&self, left: &REL::Left) -> HelixFlowResult<impl Iterator<Item = REL>> {
+        Ok(block_on(self.0.get_linked_items(left))?.into_iter())
+    }
+
+    fn delete_link(&self, link: &REL) -> HelixFlowResult<()> {
+        block_on(self.0.delete_link(link))
+    }
+}
+
+/// Wakes a thread parked in `block_on`.
+struct Unpark(Thread);
+
+impl Wake for Unpark {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+/// Run `future` to completion on the current thread, parking it while the future is pending.
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let waker = Waker::from(Arc::new(Unpark(thread::current())));
+    let mut context = Context::from_waker(&waker);
+    loop {
+        match future.as_mut().poll(&mut context) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => thread::park(),
+        }
+    }
+}
+
+#[cfg(test)]
+#[coverage(off)]
+mod tests {
+    use uuid::uuid;
+
+    use super::*;
+    use crate::task::{Contains, TaskList, TestBackend};
+
+    #[test]
+    fn async_crud() {
+        let backend = Blocking(TestBackend);
+        let task = block_on(Task::get(
+            &backend,
+            &uuid!("0196b4c9-8447-7959-ae1f-72c7c8a3dd36"),
+        ))
+        .unwrap();
+        assert_eq!(task.name, "Task 1");
+        block_on(task.update(&backend)).unwrap();
+        block_on(Task::new("New", None).create(&backend)).unwrap();
+        assert!(block_on(Task::new("", None).create(&backend)).is_err());
+
+        let tasklist = block_on(TaskList::get(
+            &backend,
+            &uuid!("0196fe23-7c01-7d6b-9e09-5968eb370549"),
+        ))
+        .unwrap();
+        let contains: Vec<Contains<TaskList, Task>> =
+            block_on(backend.get_linked_items(&tasklist)).unwrap();
+        assert_eq!(contains.len(), 2);
+    }
+
+    #[test]
+    fn round_trip() {
+        let backend = BlockOn(Blocking(TestBackend));
+        let tasklist: TaskList =
+            crate::Store::get(&backend, &uuid!("0196fe23-7c01-7d6b-9e09-5968eb370549")).unwrap();
+        let names: Vec<String> = tasklist
+            .get_linked_items(&backend)
+            .unwrap()
+            .map(|contains| contains.right.unwrap().name.to_string())
+            .collect();
+        assert_eq!(names, vec!["Task 1", "Task 2"]);
+    }
+}
@@ -0,0 +1,211 @@
+//! Iterating over long lists of linked items without fetching them all at once.
+//!
+//! Backends which can fetch a single `Page` of links implement `Relate::get_linked_items` with
+//! `Pages`, so that only one page is held in memory at a time.
+
+use std::vec;
+
+use crate::{HelixFlowError, HelixFlowResult, Link, Page, Relate};
+
+/// How many links `Pages` fetches at once, unless told otherwise.
+pub const PAGE_SIZE: usize = 200;
+
+/// All the links from `left`, fetched one page at a time via `Relate::get_linked_page` as the
+/// previous page is used up.
+///
+/// The first page is fetched by `Pages::new`, so most errors are returned from there. If a
+/// later page cannot be fetched, `on_error` turns the error into a final link, so the failure
+/// isn't mistaken for the end of the list.
+///
+/// `B` must override `get_linked_page`: the default calls `get_linked_items`.
+pub struct Pages<'a, B, REL, E>
+where
+    REL: Link,
+{
+    backend: &'a B,
+    left: &'a REL::Left,
+    /// The next page to fetch, `None` once the last page has been fetched
+    next: Option<Page>,
+    links: vec::IntoIter<REL>,
+    on_error: E,
+}
+
+impl<'a, B, REL, E> Pages<'a, B, REL, E>
+where
+    B: Relate<REL>,
+    REL: Link,
+    E: Fn(HelixFlowError) -> REL,
+{
+    pub fn new(
+        backend: &'a B,
+        left: &'a REL::Left,
+        page_size: usize,
+        on_error: E,
+    ) -> HelixFlowResult<Self> {
+        let mut pages = Pages {
+            backend,
+            left,
+            next: Some(Page::first(page_size.max(1))),
+            links: Vec::new().into_iter(),
+            on_error,
+        };
+        pages.fetch()?;
+        Ok(pages)
+    }
+
+    /// Replace the used up page with the next one.
+    fn fetch(&mut self) -> HelixFlowResult<()> {
+        let Some(page) = self.next.take() else {
+            return Ok(());
+        };
+        let links: Vec<REL> = self.backend.get_linked_page(self.left, page)?.collect();
+        // A short page is the last one
+        if links.len() == page.limit {
+            self.next = Some(page.next());
+        }
+        self.links = links.into_iter();
+        Ok(())
+    }
+}
+
+impl<B, REL, E> Iterator for Pages<'_, B, REL, E>
+where
+    B: Relate<REL>,
+    REL: Link,
+    E: Fn(HelixFlowError) -> REL,
+{
+    type Item = REL;
+
+    fn next(&mut self) -> Option<REL> {
+        if let Some(link) = self.links.next() {
+            return Some(link);
+        }
+        match self.fetch() {
+            Ok(()) => self.links.next(),
+            Err(e) => Some((self.on_error)(e)),
+        }
+    }
+}
+
+#[cfg(test)]
+#[coverage(off)]
+mod tests {
+    use std::sync::Mutex;
+
+    use uuid::uuid;
+
+    use super::*;
+    use crate::{
+        CRUD,
+        task::{Contains, Task, TaskList, TestBackend},
+    };
+
+    /// Counts the pages fetched from `TestBackend`, failing after `fail_after` of them
+    struct Counted {
+        pages: Mutex<Vec<Page>>,
+        fail_after: usize,
+    }
+
+    impl Relate<Contains<TaskList, Task>> for Counted {
+        fn create_linked_item(
+            &self,
+            link: &Contains<TaskList, Task>,
+        ) -> HelixFlowResult<Contains<TaskList, Task>> {
+            TestBackend.create_linked_item(link)
+        }
+
+        fn get_linked_items(
+            &self,
+            left: &TaskList,
+        ) -> HelixFlowResult<impl Iterator<Item = Contains<TaskList, Task>>> {
+            Relate::<Contains<TaskList, Task>>::get_linked_items(&TestBackend, left)
+        }
+
+        fn get_linked_page(
+            &self,
+            left: &TaskList,
+            page: Page,
+        ) -> HelixFlowResult<impl Iterator<Item = Contains<TaskList, Task>>> {
+            let mut pages = self.pages.lock().unwrap();
+            let links =
+                Relate::<Contains<TaskList, Task>>::get_linked_page(&TestBackend, left, page)?;
+            if pages.len() == self.fail_after {
+                Err(anyhow::anyhow!("connection lost").into())
+            } else {
+                pages.push(page);
+                Ok(links)
+            }
+        }
+
+        fn delete_link(&self, link: &Contains<TaskList, Task>) -> HelixFlowResult<()> {
+            TestBackend.delete_link(link)
+        }
+    }
+
+    fn failed(e: HelixFlowError) -> Contains<TaskList, Task> {
+        Contains {
+            left: Err(HelixFlowError::NotFound {
+                itemtype: "TaskList".into(),
+                id: uuid!("0196fe23-7c01-7d6b-9e09-5968eb370549"),
+            }),
+            sortorder: "".into(),
+            created_at: None,
+            created_by: None,
+            right: Err(e),
+        }
+    }
+
+    #[test]
+    fn fetch_lazily() {
+        let tasklist =
+            TaskList::get(&TestBackend, &uuid!("0196fe23-7c01-7d6b-9e09-5968eb370549")).unwrap();
+        let backend = Counted {
+            pages: Mutex::default(),
+            fail_after: usize::MAX,
+        };
+        let mut pages = Pages::new(&backend, &tasklist, 1, failed).unwrap();
+        assert_eq!(backend.pages.lock().unwrap().len(), 1);
+        assert_eq!(pages.next().unwrap().right.unwrap().name, "Task 1");
+        assert_eq!(backend.pages.lock().unwrap().len(), 1);
+        assert_eq!(pages.next().unwrap().right.unwrap().name, "Task 2");
+        assert!(pages.next().is_none());
+        assert_eq!(
+            *backend.pages.lock().unwrap(),
+            vec![
+                Page::first(1),
+                Page {
+                    offset: 1,
+                    limit: 1
+                },
+                Page {
+                    offset: 2,
+                    limit: 1
+                }
+            ]
+        );
+        assert!(pages.next().is_none());
+        assert_eq!(backend.pages.lock().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn later_pages_fail() {
+        let tasklist =
+            TaskList::get(&TestBackend, &uuid!("0196fe23-7c01-7d6b-9e09-5968eb370549")).unwrap();
+        let backend = Counted {
+            pages: Mutex::default(),
+            fail_after: 0,
+        };
+        assert!(Pages::new(&backend, &tasklist, 1, failed).is_err());
+
+        let backend = Counted {
+            pages: Mutex::default(),
+            fail_after: 1,
+        };
+        let links: Vec<_> = Pages::new(&backend, &tasklist, 1, failed)
+            .unwrap()
+            .collect();
+        assert_eq!(links.len(), 2);
+        assert!(links[0].right.is_ok());
+        assert!(links[1].right.is_err());
+    }
+}
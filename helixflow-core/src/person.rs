@@ -0,0 +1,191 @@
+//! People who tasks can be assigned to.
+
+use std::borrow::Cow;
+
+use serde::{Deserialize, Serialize};
+use uuid::{Uuid, uuid};
+
+use crate::{
+    HelixFlowError, HelixFlowItem, HelixFlowResult, Linkable, Relate, Relationship, Store,
+    Validate,
+    task::{Task, TestBackend},
+    validate_name,
+};
+
+/// Someone responsible for getting a task done. Unlike a `User`, a `Person` does not need to use
+/// HelixFlow themselves, e.g. a colleague or contractor.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, HelixFlowItem)]
+pub struct Person {
+    pub name: Cow<'static, str>,
+    pub email: Option<Cow<'static, str>>,
+    pub id: Uuid,
+}
+
+/// A person needs a name, see `MAX_NAME_LENGTH`.
+impl Validate for Person {
+    fn validate(&self) -> HelixFlowResult<()> {
+        validate_name(&self.name)
+    }
+}
+
+impl Person {
+    /// Create a new `Person` with valid `id`, suitable for usage as database key.
+    pub fn new<S1, S2>(name: S1, email: Option<S2>) -> Person
+    where
+        S1: Into<Cow<'static, str>>,
+        S2: Into<Cow<'static, str>>,
+    {
+        Person {
+            name: name.into(),
+            email: email.map(Into::into),
+            id: Uuid::now_v7(),
+        }
+    }
+}
+
+impl Task {
+    /// Make `person` responsible for this task, ready for `create_linked_item`.
+    pub fn assign_to(&self, person: &Person) -> AssignedTo<Task, Person> {
+        Linkable::<AssignedTo<Task, Person>>::link(self, person)
+    }
+
+    /// Who this task is assigned to, if anyone.
+    pub fn assignee<B>(&self, backend: &B) -> HelixFlowResult<Option<Person>>
+    where
+        B: Relate<AssignedTo<Task, Person>>,
+    {
+        Linkable::<AssignedTo<Task, Person>>::get_linked_items(self, backend)?
+            .next()
+            .map(|assigned| assigned.right)
+            .transpose()
+    }
+}
+
+/// `left` is the responsibility of `right`.
+///
+/// A task has at most one assignee: `Relate::create_linked_item` replaces any previous
+/// assignment. The task must already exist, the person is created if needed.
+#[derive(Debug, Relationship)]
+#[relationship(Task => Person)]
+pub struct AssignedTo<LEFT, RIGHT> {
+    pub left: HelixFlowResult<LEFT>,
+    pub right: HelixFlowResult<RIGHT>,
+}
+
+/// Find people, e.g. to offer a choice of assignee.
+pub trait People: Store<Person> + Relate<AssignedTo<Task, Person>> {
+    /// Everyone known to the backend, oldest first.
+    fn all_people(&self) -> HelixFlowResult<Vec<Person>>;
+
+    /// The person called exactly `name`, if there is one.
+    fn person_named(&self, name: &str) -> HelixFlowResult<Option<Person>> {
+        Ok(self
+            .all_people()?
+            .into_iter()
+            .find(|person| person.name == name))
+    }
+}
+
+fn test_person() -> Person {
+    Person {
+        name: "Alice".into(),
+        email: Some("alice@example.com".into()),
+        id: uuid!("0197c5a7-4d5e-7f60-b1c2-3d4e5f6a7b8c"),
+    }
+}
+
+impl Store<Person> for TestBackend {
+    fn create(&self, person: &Person) -> HelixFlowResult<Person> {
+        Ok(person.clone())
+    }
+
+    fn get(&self, id: &Uuid) -> HelixFlowResult<Person> {
+        match id.to_string().as_str() {
+            "0197c5a7-4d5e-7f60-b1c2-3d4e5f6a7b8c" => Ok(test_person()),
+            _ => Err(HelixFlowError::NotFound {
+                itemtype: "Person".into(),
+                id: *id,
+            }),
+        }
+    }
+
+    fn update(&self, person: &Person) -> HelixFlowResult<Person> {
+        Ok(person.clone())
+    }
+
+    fn delete(&self, _id: &Uuid) -> HelixFlowResult<()> {
+        Ok(())
+    }
+}
+
+/// `Task 1` is assigned to `Alice`, no other task is assigned.
+impl Relate<AssignedTo<Task, Person>> for TestBackend {
+    fn create_linked_item(
+        &self,
+        link: &AssignedTo<Task, Person>,
+    ) -> HelixFlowResult<AssignedTo<Task, Person>> {
+        Ok(AssignedTo {
+            left: Ok(link.left.as_ref().unwrap().clone()),
+            right: Ok(link.right.as_ref().unwrap().clone()),
+        })
+    }
+    fn get_linked_items(
+        &self,
+        left: &Task,
+    ) -> HelixFlowResult<impl Iterator<Item = AssignedTo<Task, Person>>> {
+        let people = match left.id.to_string().as_str() {
+            "0196b4c9-8447-7959-ae1f-72c7c8a3dd36" => vec![test_person()],
+            _ => vec![],
+        };
+        let left = left.clone();
+        Ok(people
+            .into_iter()
+            .map(move |person| left.assign_to(&person)))
+    }
+    fn delete_link(&self, _link: &AssignedTo<Task, Person>) -> HelixFlowResult<()> {
+        Ok(())
+    }
+}
+
+impl People for TestBackend {
+    fn all_people(&self) -> HelixFlowResult<Vec<Person>> {
+        Ok(vec![test_person()])
+    }
+}
+
+#[cfg(test)]
+#[coverage(off)]
+mod tests {
+    use super::*;
+    use crate::{CRUD, Link};
+
+    #[test]
+    fn assignee() {
+        let task_1 =
+            Task::get(&TestBackend, &uuid!("0196b4c9-8447-7959-ae1f-72c7c8a3dd36")).unwrap();
+        let task_2 =
+            Task::get(&TestBackend, &uuid!("0196ca5f-d934-7ec8-b042-ae37b94b8432")).unwrap();
+        let alice = task_1.assignee(&TestBackend).unwrap().unwrap();
+        assert_eq!(alice.name, "Alice");
+        assert_eq!(alice.email.as_deref(), Some("alice@example.com"));
+        assert_eq!(task_2.assignee(&TestBackend).unwrap(), None);
+    }
+
+    #[test]
+    fn assign() {
+        let task = Task::new("Review budget", None);
+        let bob = Person::new("Bob", None::<&str>);
+        task.assign_to(&bob)
+            .create_linked_item(&TestBackend)
+            .unwrap();
+    }
+
+    #[test]
+    fn find_by_name() {
+        assert_eq!(
+            TestBackend.person_named("Alice").unwrap(),
+            Some(test_person())
+        );
+        assert_eq!(TestBackend.person_named("alice").unwrap(), None);
+    }
+}
@@ -0,0 +1,214 @@
+//! Projects group related tasklists, e.g. a backlog, a sprint and a "someday" list.
+
+use std::borrow::Cow;
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use uuid::{Uuid, uuid};
+
+use crate::{
+    HelixFlowError, HelixFlowItem, HelixFlowResult, Linkable, Relate, Store, Validate,
+    task::{Contains, Task, TaskList, TestBackend},
+    validate_name,
+};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, HelixFlowItem)]
+pub struct Project {
+    pub name: Cow<'static, str>,
+    pub description: Option<Cow<'static, str>>,
+    pub id: Uuid,
+}
+
+/// A project needs a name, see `MAX_NAME_LENGTH`.
+impl Validate for Project {
+    fn validate(&self) -> HelixFlowResult<()> {
+        validate_name(&self.name)
+    }
+}
+
+impl Project {
+    /// Create a new `Project` with valid `id`, suitable for usage as database key.
+    pub fn new<S1, S2>(name: S1, description: Option<S2>) -> Project
+    where
+        S1: Into<Cow<'static, str>>,
+        S2: Into<Cow<'static, str>>,
+    {
+        Project {
+            name: name.into(),
+            description: description.map(Into::into),
+            id: Uuid::now_v7(),
+        }
+    }
+
+    /// The tasklists in this project.
+    pub fn tasklists<B>(&self, backend: &B) -> HelixFlowResult<Vec<TaskList>>
+    where
+        B: Relate<Contains<Project, TaskList>>,
+    {
+        self.get_linked_items(backend)?
+            .map(|contains| contains.right)
+            .collect()
+    }
+
+    /// The first list in this project, or nested in one of its lists, which holds `task`, e.g. to
+    /// show the task in its list when following a link to it.
+    pub fn tasklist_containing<B>(
+        &self,
+        task: &Task,
+        backend: &B,
+    ) -> HelixFlowResult<Option<TaskList>>
+    where
+        B: Relate<Contains<Project, TaskList>>
+            + Relate<Contains<TaskList, TaskList>>
+            + Relate<Contains<TaskList, Task>>,
+    {
+        for root in self.tasklists(backend)? {
+            for tasklist in root.tree(backend)?.tasklists() {
+                let mut tasks =
+                    Linkable::<Contains<TaskList, Task>>::get_linked_items(tasklist, backend)?;
+                if tasks.any(|contains| contains.right.is_ok_and(|right| right.id == task.id)) {
+                    return Ok(Some(tasklist.clone()));
+                }
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Find all projects, e.g. to offer a choice of project.
+pub trait Projects: Store<Project> + Relate<Contains<Project, TaskList>> {
+    /// All projects, oldest first.
+    fn all_projects(&self) -> HelixFlowResult<Vec<Project>>;
+}
+
+impl Store<Project> for TestBackend {
+    fn create(&self, project: &Project) -> HelixFlowResult<Project> {
+        Ok(project.clone())
+    }
+
+    fn get(&self, id: &Uuid) -> HelixFlowResult<Project> {
+        match id.to_string().as_str() {
+            "0197c4f6-3c4d-7e5f-a0b1-2c3d4e5f6a7b" => Ok(Project {
+                name: "Test Project 1".into(),
+                description: None,
+                id: *id,
+            }),
+            _ => Err(HelixFlowError::NotFound {
+                itemtype: "Project".into(),
+                id: *id,
+            }),
+        }
+    }
+
+    fn update(&self, project: &Project) -> HelixFlowResult<Project> {
+        Ok(project.clone())
+    }
+
+    fn delete(&self, _id: &Uuid) -> HelixFlowResult<()> {
+        Ok(())
+    }
+}
+
+/// `Test Project 1` contains `Test TaskList 1`.
+impl Relate<Contains<Project, TaskList>> for TestBackend {
+    fn create_linked_item(
+        &self,
+        link: &Contains<Project, TaskList>,
+    ) -> HelixFlowResult<Contains<Project, TaskList>> {
+        let project: Project = self.get(&link.left.as_ref().unwrap().id)?;
+        Ok(Contains {
+            left: Ok(project),
+            sortorder: link.sortorder.clone(),
+            created_at: Some(Utc::now()),
+            created_by: link.created_by.clone(),
+            right: Ok(link.right.as_ref().unwrap().clone()),
+        })
+    }
+    fn get_linked_items(
+        &self,
+        left: &Project,
+    ) -> HelixFlowResult<impl Iterator<Item = Contains<Project, TaskList>>> {
+        let tasklists: Vec<TaskList> = match left.id.to_string().as_str() {
+            "0197c4f6-3c4d-7e5f-a0b1-2c3d4e5f6a7b" => {
+                vec![self.get(&uuid!("0196fe23-7c01-7d6b-9e09-5968eb370549"))?]
+            }
+            _ => vec![],
+        };
+        let left = left.clone();
+        Ok(tasklists
+            .into_iter()
+            .map(move |tasklist| left.link(&tasklist)))
+    }
+    fn delete_link(&self, _link: &Contains<Project, TaskList>) -> HelixFlowResult<()> {
+        Ok(())
+    }
+}
+
+impl Projects for TestBackend {
+    fn all_projects(&self) -> HelixFlowResult<Vec<Project>> {
+        Ok(vec![
+            self.get(&uuid!("0197c4f6-3c4d-7e5f-a0b1-2c3d4e5f6a7b"))?,
+        ])
+    }
+}
+
+#[cfg(test)]
+#[coverage(off)]
+mod tests {
+    use std::assert_matches::assert_matches;
+
+    use super::*;
+    use crate::{CRUD, Link};
+
+    #[test]
+    fn tasklists_in_project() {
+        let projects = TestBackend.all_projects().unwrap();
+        assert_eq!(projects.len(), 1);
+        let tasklists = projects[0].tasklists(&TestBackend).unwrap();
+        assert_eq!(tasklists.len(), 1);
+        assert_eq!(tasklists[0].name, "Test TaskList 1");
+    }
+
+    #[test]
+    fn find_tasks_list() {
+        let project =
+            Project::get(&TestBackend, &uuid!("0197c4f6-3c4d-7e5f-a0b1-2c3d4e5f6a7b")).unwrap();
+        let task = Task::get(&TestBackend, &uuid!("0196b4c9-8447-7959-ae1f-72c7c8a3dd36")).unwrap();
+        assert_eq!(
+            project
+                .tasklist_containing(&task, &TestBackend)
+                .unwrap()
+                .unwrap()
+                .name,
+            "Test TaskList 1"
+        );
+        // Nested lists are searched too, but `Test TaskList 2` isn't stored
+        let unlisted = Task::new("Not in a list", None);
+        assert_matches!(
+            project.tasklist_containing(&unlisted, &TestBackend),
+            Err(HelixFlowError::NotFound { id, .. })
+                if id == uuid!("0199a3f0-6a1b-7c2d-8e3f-4a5b6c7d8e9f")
+        );
+    }
+
+    #[test]
+    fn add_tasklist() {
+        let project =
+            Project::get(&TestBackend, &uuid!("0197c4f6-3c4d-7e5f-a0b1-2c3d4e5f6a7b")).unwrap();
+        project
+            .link(&TaskList::new("Someday"))
+            .create_linked_item(&TestBackend)
+            .unwrap();
+    }
+
+    #[test]
+    fn missing_project() {
+        let project = Project::new("Not stored", Some("Never created"));
+        assert_matches!(
+            project
+                .link(&TaskList::new("Someday"))
+                .create_linked_item(&TestBackend),
+            Err(HelixFlowError::NotFound { itemtype, .. }) if itemtype == "Project"
+        );
+    }
+}
@@ -0,0 +1,278 @@
+//! Fetching the tasks which match a filter, e.g. "overdue tasks in this list", without loading
+//! everything from the backend first.
+
+use chrono::{DateTime, Utc};
+use uuid::{Uuid, uuid};
+
+use crate::{
+    HelixFlowResult, Relate, Store,
+    contexts::{Context, InContext},
+    sorting::{SortKey, SortRule},
+    tag::{Tag, TaggedWith},
+    task::{Contains, Status, Task, TaskList, TestBackend},
+};
+
+/// An item which can be fetched with a `Query`, and the filter used to select it.
+pub trait Queryable {
+    type Filter;
+}
+
+impl Queryable for Task {
+    type Filter = TaskQuery;
+}
+
+/// Methods to fetch the `ITEM`s matching a filter from a backend
+pub trait Query<ITEM: Queryable>: Send + Sync {
+    /// All `ITEM`s matching `filter`, in the order & number it asks for.
+    fn query(&self, filter: &ITEM::Filter) -> HelixFlowResult<impl Iterator<Item = ITEM>>;
+}
+
+/// Which tasks to fetch. Every condition which is set must match; an empty query matches all
+/// tasks.
+///
+/// ```
+/// # use chrono::Utc;
+/// # use helixflow_core::{query::TaskQuery, sorting::SortKey, task::{Status, TaskList}};
+/// let this_week = TaskList::new("This week");
+/// let overdue = TaskQuery::default()
+///     .status(Status::Todo)
+///     .status(Status::InProgress)
+///     .in_list(&this_week)
+///     .due_before(Utc::now())
+///     .sort_by(SortKey::DueDate)
+///     .limit(10);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TaskQuery {
+    /// Any of these, or any status if empty
+    pub statuses: Vec<Status>,
+    /// Tagged with the tag with this id
+    pub tag: Option<Uuid>,
+    /// Can be done in the context with this id
+    pub context: Option<Uuid>,
+    /// In the tasklist with this id
+    pub tasklist: Option<Uuid>,
+    /// Due at or after this time. Tasks without a due date never match a due range.
+    pub due_from: Option<DateTime<Utc>>,
+    /// Due strictly before this time
+    pub due_before: Option<DateTime<Utc>>,
+    /// `SortKey::OverdueFirst` treats tasks due before the query runs as overdue. Tasks which are
+    /// still tied are returned oldest first.
+    pub sort: SortRule,
+    pub limit: Option<usize>,
+}
+
+impl TaskQuery {
+    pub fn status(mut self, status: Status) -> Self {
+        self.statuses.push(status);
+        self
+    }
+
+    pub fn tagged(mut self, tag: &Tag) -> Self {
+        self.tag = Some(tag.id);
+        self
+    }
+
+    pub fn in_context(mut self, context: &Context) -> Self {
+        self.context = Some(context.id);
+        self
+    }
+
+    pub fn in_list(mut self, tasklist: &TaskList) -> Self {
+        self.tasklist = Some(tasklist.id);
+        self
+    }
+
+    pub fn due_from(mut self, from: DateTime<Utc>) -> Self {
+        self.due_from = Some(from);
+        self
+    }
+
+    pub fn due_before(mut self, before: DateTime<Utc>) -> Self {
+        self.due_before = Some(before);
+        self
+    }
+
+    /// Sort by `key`, after any keys already given.
+    pub fn sort_by(mut self, key: SortKey) -> Self {
+        self.sort.0.push(key);
+        self
+    }
+
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Does `task` match the status & due date conditions? Tags, contexts & list membership need
+    /// a backend to check, see `run`.
+    pub fn matches(&self, task: &Task) -> bool {
+        let due_in_range = match (self.due_from, self.due_before, task.due) {
+            (None, None, _) => true,
+            (_, _, None) => false,
+            (from, before, Some(due)) => {
+                from.is_none_or(|from| due >= from) && before.is_none_or(|before| due < before)
+            }
+        };
+        (self.statuses.is_empty() || self.statuses.contains(&task.status)) && due_in_range
+    }
+
+    /// Run this query over `tasks` in memory, as at `now`, for backends without their own query
+    /// language. Fails with `NotFound` if the tasklist to filter by does not exist.
+    pub fn run<B>(
+        &self,
+        tasks: impl IntoIterator<Item = Task>,
+        backend: &B,
+        now: DateTime<Utc>,
+    ) -> HelixFlowResult<Vec<Task>>
+    where
+        B: Store<TaskList>
+            + Relate<Contains<TaskList, Task>>
+            + Relate<TaggedWith<Task, Tag>>
+            + Relate<InContext<Task, Context>>,
+    {
+        let in_list = match self.tasklist {
+            Some(id) => Some(
+                backend
+                    .get(&id)?
+                    .get_linked_items(backend)?
+                    .map(|contains| contains.right.map(|task| task.id))
+                    .collect::<HelixFlowResult<Vec<Uuid>>>()?,
+            ),
+            None => None,
+        };
+        let mut matching = vec![];
+        for task in tasks {
+            if !self.matches(&task) || in_list.as_ref().is_some_and(|ids| !ids.contains(&task.id)) {
+                continue;
+            }
+            if let Some(tag) = self.tag
+                && !task.tags(backend)?.iter().any(|tagged| tagged.id == tag)
+            {
+                continue;
+            }
+            if let Some(context) = self.context
+                && !task
+                    .contexts(backend)?
+                    .iter()
+                    .any(|in_context| in_context.id == context)
+            {
+                continue;
+            }
+            matching.push(task);
+        }
+        matching.sort_by_key(|task| task.id);
+        self.sort.sort(&mut matching, now, chrono_tz::UTC);
+        if let Some(limit) = self.limit {
+            matching.truncate(limit);
+        }
+        Ok(matching)
+    }
+}
+
+/// Queries `Task 1` & `Task 2`.
+impl Query<Task> for TestBackend {
+    fn query(&self, filter: &TaskQuery) -> HelixFlowResult<impl Iterator<Item = Task>> {
+        let tasks = [
+            uuid!("0196b4c9-8447-7959-ae1f-72c7c8a3dd36"),
+            uuid!("0196ca5f-d934-7ec8-b042-ae37b94b8432"),
+        ]
+        .iter()
+        .map(|id| self.get(id))
+        .collect::<HelixFlowResult<Vec<Task>>>()?;
+        Ok(filter.run(tasks, self, Utc::now())?.into_iter())
+    }
+}
+
+#[cfg(test)]
+#[coverage(off)]
+mod tests {
+    use std::assert_matches::assert_matches;
+
+    use chrono::TimeZone;
+
+    use super::*;
+    use crate::{CRUD, HelixFlowError, task::Priority};
+
+    fn names(query: &TaskQuery) -> Vec<String> {
+        TestBackend
+            .query(query)
+            .unwrap()
+            .map(|task| task.name.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn query_backend() {
+        let tasklist =
+            TaskList::get(&TestBackend, &uuid!("0196fe23-7c01-7d6b-9e09-5968eb370549")).unwrap();
+        let home = Tag {
+            name: "home".into(),
+            id: uuid!("0197a1b2-3c4d-7e5f-8a9b-0c1d2e3f4a5b"),
+        };
+        assert_eq!(names(&TaskQuery::default()), vec!["Task 1", "Task 2"]);
+        assert_eq!(names(&TaskQuery::default().tagged(&home)), vec!["Task 1"]);
+        let computer = Context {
+            name: "@computer".into(),
+            id: uuid!("0199b7a0-1c2d-7e3f-8a4b-5c6d7e8f9a02"),
+        };
+        assert_eq!(
+            names(&TaskQuery::default().in_context(&computer)),
+            vec!["Task 1"]
+        );
+        assert_eq!(
+            names(&TaskQuery::default().in_list(&tasklist).limit(1)),
+            vec!["Task 1"]
+        );
+        assert_eq!(
+            names(&TaskQuery::default().status(Status::Done)),
+            Vec::<String>::new()
+        );
+        assert_matches!(
+            TestBackend
+                .query(&TaskQuery::default().in_list(&TaskList::new("Not stored")))
+                .map(Iterator::count),
+            Err(HelixFlowError::NotFound { .. })
+        );
+    }
+
+    #[test]
+    fn due_range() {
+        let utc = |day| Utc.with_ymd_and_hms(2025, 6, day, 12, 0, 0).unwrap();
+        let due = |day| Task {
+            due: Some(utc(day)),
+            ..Task::new("Due", None)
+        };
+        let june_10_to_12 = TaskQuery::default().due_from(utc(10)).due_before(utc(12));
+        assert!(june_10_to_12.matches(&due(10)));
+        assert!(june_10_to_12.matches(&due(11)));
+        assert!(!june_10_to_12.matches(&due(12)));
+        assert!(!june_10_to_12.matches(&due(9)));
+        assert!(!june_10_to_12.matches(&Task::new("No date", None)));
+        assert!(TaskQuery::default().matches(&Task::new("No date", None)));
+    }
+
+    #[test]
+    fn sort_and_limit() {
+        let task = |name, priority| Task {
+            priority,
+            ..Task::new(name, None)
+        };
+        let tasks = vec![
+            task("b", Priority::P2),
+            task("a", Priority::P2),
+            task("c", Priority::P0),
+        ];
+        let query = TaskQuery::default()
+            .sort_by(SortKey::Priority)
+            .sort_by(SortKey::Name)
+            .limit(2);
+        let names: Vec<String> = query
+            .run(tasks, &TestBackend, Utc::now())
+            .unwrap()
+            .into_iter()
+            .map(|task| task.name.to_string())
+            .collect();
+        assert_eq!(names, vec!["c", "a"]);
+    }
+}
@@ -0,0 +1,482 @@
+//! Parsing quick-add text such as "Pay rent tomorrow 9am !p1 #finance @home" into a task. What
+//! counts as a date, tag, context or priority is configured per user via `Settings`.
+
+use std::{collections::BTreeMap, ops::Range};
+
+use chrono::{DateTime, Datelike, NaiveDate, NaiveTime, TimeDelta, Utc, Weekday};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    HelixFlowResult, Link, Relate, Store,
+    calendar::WorkingCalendar,
+    contexts::Contexts,
+    dates::to_utc,
+    task::{Contains, Priority, Task, TaskList},
+};
+
+/// Languages & regions with built-in `QuickAddRules`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Locale {
+    /// British English: `25/12/2025`
+    #[default]
+    EnGb,
+    /// American English: `12/25/2025`
+    EnUs,
+    /// German: `25.12.2025`
+    De,
+}
+
+/// How to recognise dates, tags, contexts and priorities in quick-add text.
+///
+/// Words are matched case-insensitively. Anything which isn't recognised becomes part of the
+/// task name. Times of day are always recognised as `9am`, `9:30pm` or `21:30`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuickAddRules {
+    /// Marks a tag, e.g. `#` in `#home`
+    pub tag_prefix: char,
+    /// Marks a context, e.g. `@` in `@home`
+    #[serde(default = "default_context_prefix")]
+    pub context_prefix: char,
+    /// Marks a priority, e.g. `!` in `!1` or `!p1`
+    pub priority_prefix: char,
+    /// `chrono` format strings for explicit dates, tried in order
+    pub date_formats: Vec<String>,
+    /// Words meaning "the next <weekday>" (never today), e.g. `"friday" -> Friday`
+    pub weekdays: BTreeMap<String, Weekday>,
+    /// Words meaning "in `n` days", e.g. `"tomorrow" -> 1`
+    pub relative_days: BTreeMap<String, u32>,
+}
+
+fn default_context_prefix() -> char {
+    '@'
+}
+
+/// The parts of a quick-add text.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct QuickAdd {
+    pub name: String,
+    pub due: Option<NaiveDate>,
+    /// Time of day on `due`; a time without a date is due today
+    pub time: Option<NaiveTime>,
+    pub tags: Vec<String>,
+    /// Context names without the prefix, e.g. `home` for `@home`
+    pub contexts: Vec<String>,
+    pub priority: Option<Priority>,
+    /// Every word in the text, in order, and what it was recognised as
+    pub tokens: Vec<Token>,
+}
+
+/// What a word in quick-add text was recognised as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    Name,
+    Due,
+    Time,
+    Tag,
+    Context,
+    Priority,
+}
+
+/// A word in quick-add text and its meaning, e.g. to highlight it while the user types.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    pub kind: TokenKind,
+    /// Byte offsets of the word in the text
+    pub span: Range<usize>,
+}
+
+impl Default for QuickAddRules {
+    fn default() -> Self {
+        QuickAddRules::for_locale(Locale::default())
+    }
+}
+
+const ENGLISH_WEEKDAYS: [(&str, Weekday); 7] = [
+    ("monday", Weekday::Mon),
+    ("tuesday", Weekday::Tue),
+    ("wednesday", Weekday::Wed),
+    ("thursday", Weekday::Thu),
+    ("friday", Weekday::Fri),
+    ("saturday", Weekday::Sat),
+    ("sunday", Weekday::Sun),
+];
+
+const GERMAN_WEEKDAYS: [(&str, Weekday); 7] = [
+    ("montag", Weekday::Mon),
+    ("dienstag", Weekday::Tue),
+    ("mittwoch", Weekday::Wed),
+    ("donnerstag", Weekday::Thu),
+    ("freitag", Weekday::Fri),
+    ("samstag", Weekday::Sat),
+    ("sonntag", Weekday::Sun),
+];
+
+impl QuickAddRules {
+    /// The built-in rules for `locale`, all using `#tag`, `@context` and `!priority`.
+    pub fn for_locale(locale: Locale) -> Self {
+        let (date_format, weekdays, relative_days) = match locale {
+            Locale::EnGb => (
+                "%d/%m/%Y",
+                ENGLISH_WEEKDAYS,
+                [("today", 0), ("tomorrow", 1)],
+            ),
+            Locale::EnUs => (
+                "%m/%d/%Y",
+                ENGLISH_WEEKDAYS,
+                [("today", 0), ("tomorrow", 1)],
+            ),
+            Locale::De => ("%d.%m.%Y", GERMAN_WEEKDAYS, [("heute", 0), ("morgen", 1)]),
+        };
+        QuickAddRules {
+            tag_prefix: '#',
+            context_prefix: default_context_prefix(),
+            priority_prefix: '!',
+            date_formats: vec!["%Y-%m-%d".into(), date_format.into()],
+            weekdays: weekdays
+                .into_iter()
+                .map(|(word, day)| (word.into(), day))
+                .collect(),
+            relative_days: relative_days
+                .into_iter()
+                .map(|(word, days)| (word.into(), days))
+                .collect(),
+        }
+    }
+
+    /// Parse `text` typed at `now` by someone in `tz`.
+    pub fn parse_at(&self, text: &str, now: DateTime<Utc>, tz: Tz) -> QuickAdd {
+        self.parse(text, now.with_timezone(&tz).date_naive())
+    }
+
+    /// Split `text` into name, due date & time, tags, contexts & priority. Relative dates are
+    /// based on `today`.
+    ///
+    /// Only the first date & time found are used, later ones are left in the name.
+    pub fn parse(&self, text: &str, today: NaiveDate) -> QuickAdd {
+        let mut parsed = QuickAdd::default();
+        let mut name = Vec::new();
+        for word in text.split_whitespace() {
+            let kind = if let Some(tag) = word.strip_prefix(self.tag_prefix)
+                && !tag.is_empty()
+            {
+                parsed.tags.push(tag.to_string());
+                TokenKind::Tag
+            } else if let Some(context) = word.strip_prefix(self.context_prefix)
+                && !context.is_empty()
+            {
+                parsed.contexts.push(context.to_string());
+                TokenKind::Context
+            } else if let Some(priority) = word.strip_prefix(self.priority_prefix)
+                && let Ok(level) = priority.trim_start_matches(['p', 'P']).parse()
+                && let Some(priority) = Priority::from_level(level)
+            {
+                parsed.priority = Some(priority);
+                TokenKind::Priority
+            } else if parsed.due.is_none()
+                && let Some(date) = self.date(word, today)
+            {
+                parsed.due = Some(date);
+                TokenKind::Due
+            } else if parsed.time.is_none()
+                && let Some(time) = time(word)
+            {
+                parsed.time = Some(time);
+                TokenKind::Time
+            } else {
+                name.push(word);
+                TokenKind::Name
+            };
+            // `word` is a slice of `text`
+            let start = word.as_ptr() as usize - text.as_ptr() as usize;
+            parsed.tokens.push(Token {
+                kind,
+                span: start..start + word.len(),
+            });
+        }
+        if parsed.time.is_some() && parsed.due.is_none() {
+            parsed.due = Some(today);
+        }
+        parsed.name = name.join(" ");
+        parsed
+    }
+
+    fn date(&self, word: &str, today: NaiveDate) -> Option<NaiveDate> {
+        let word = word.to_lowercase();
+        if let Some(days) = self.relative_days.get(&word) {
+            return today.checked_add_days(chrono::Days::new(*days as u64));
+        }
+        if let Some(weekday) = self.weekdays.get(&word) {
+            let ahead =
+                (weekday.num_days_from_monday() + 7 - today.weekday().num_days_from_monday()) % 7;
+            let ahead = if ahead == 0 { 7 } else { ahead };
+            return Some(today + TimeDelta::days(ahead as i64));
+        }
+        self.date_formats
+            .iter()
+            .find_map(|format| NaiveDate::parse_from_str(&word, format).ok())
+    }
+}
+
+/// A time of day such as `9am`, `9:30pm` or `21:30`. A bare number is not a time.
+fn time(word: &str) -> Option<NaiveTime> {
+    let word = word.to_lowercase();
+    let (digits, offset) = match (word.strip_suffix("am"), word.strip_suffix("pm")) {
+        (Some(digits), _) => (digits, Some(0)),
+        (_, Some(digits)) => (digits, Some(12)),
+        _ => (word.as_str(), None),
+    };
+    let (hour, minute) = match digits.split_once(':') {
+        Some((hour, minute)) if minute.len() == 2 => {
+            (hour.parse::<u32>().ok()?, minute.parse().ok()?)
+        }
+        Some(_) => return None,
+        None if offset.is_some() => (digits.parse::<u32>().ok()?, 0),
+        None => return None,
+    };
+    let hour = match offset {
+        Some(offset) if (1..=12).contains(&hour) => hour % 12 + offset,
+        Some(_) => return None,
+        None => hour,
+    };
+    NaiveTime::from_hms_opt(hour, minute, 0)
+}
+
+impl QuickAdd {
+    /// A new task, due on the parsed date in `tz` at the parsed time, or the end of the working
+    /// day. Without a parsed priority, the task has the default priority.
+    ///
+    /// Tags & contexts are left for the caller to link, as they may need to be created first.
+    pub fn to_task(&self, calendar: &WorkingCalendar, tz: Tz) -> Task {
+        Task {
+            due: self
+                .due
+                .map(|date| to_utc(date.and_time(self.time.unwrap_or(calendar.end_of_work)), tz)),
+            priority: self.priority.unwrap_or_default(),
+            ..Task::new(self.name.clone(), None)
+        }
+    }
+
+    /// Store the new task at the end of the inbox, see `Task::capture`, in each of the parsed
+    /// contexts which already exist. Contexts match ignoring case & any prefix in their name, so
+    /// `home` matches `@home`.
+    ///
+    /// Unknown contexts are left out, rather than creating one from a typo. Tags are left out
+    /// too, as an existing tag cannot yet be found by name.
+    pub fn capture<B>(
+        &self,
+        backend: &B,
+        calendar: &WorkingCalendar,
+        tz: Tz,
+    ) -> HelixFlowResult<Task>
+    where
+        B: Store<TaskList> + Relate<Contains<TaskList, Task>> + Contexts,
+    {
+        let task = self.to_task(calendar, tz);
+        task.capture(backend)?;
+        for context in backend.all_contexts()? {
+            let name = context
+                .name
+                .trim_start_matches(|c: char| !c.is_alphanumeric());
+            if self
+                .contexts
+                .iter()
+                .any(|parsed| parsed.eq_ignore_ascii_case(name))
+            {
+                task.add_context(&context).create_linked_item(backend)?;
+            }
+        }
+        Ok(task)
+    }
+}
+
+#[cfg(test)]
+#[coverage(off)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+    use crate::task::TestBackend;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    // Fri 6th June 2025
+    fn today() -> NaiveDate {
+        date(2025, 6, 6)
+    }
+
+    #[test]
+    fn en_gb() {
+        let rules = QuickAddRules::for_locale(Locale::EnGb);
+        let parsed = rules.parse("Pay rent 01/07/2025 #home !1", today());
+        let token = |kind, span| Token { kind, span };
+        assert_eq!(
+            parsed,
+            QuickAdd {
+                name: "Pay rent".into(),
+                due: Some(date(2025, 7, 1)),
+                time: None,
+                tags: vec!["home".into()],
+                contexts: vec![],
+                priority: Some(Priority::P1),
+                tokens: vec![
+                    token(TokenKind::Name, 0..3),
+                    token(TokenKind::Name, 4..8),
+                    token(TokenKind::Due, 9..19),
+                    token(TokenKind::Tag, 20..25),
+                    token(TokenKind::Priority, 26..28),
+                ],
+            }
+        );
+        assert_eq!(
+            rules.parse("Call mum Tomorrow", today()).due,
+            Some(date(2025, 6, 7))
+        );
+    }
+
+    #[test]
+    fn en_us() {
+        let rules = QuickAddRules::for_locale(Locale::EnUs);
+        assert_eq!(
+            rules.parse("Pay rent 07/01/2025", today()).due,
+            Some(date(2025, 7, 1))
+        );
+        assert_eq!(
+            rules.parse("Pay rent 2025-07-01", today()).due,
+            Some(date(2025, 7, 1))
+        );
+    }
+
+    #[test]
+    fn de() {
+        let rules = QuickAddRules::for_locale(Locale::De);
+        let parsed = rules.parse("Miete zahlen Montag #zuhause", today());
+        assert_eq!(parsed.name, "Miete zahlen");
+        assert_eq!(parsed.due, Some(date(2025, 6, 9)));
+        assert_eq!(parsed.tags, vec!["zuhause"]);
+        assert_eq!(
+            rules.parse("Miete zahlen 01.07.2025", today()).due,
+            Some(date(2025, 7, 1))
+        );
+        assert_eq!(rules.parse("Pay rent monday", today()).due, None);
+    }
+
+    #[test]
+    fn weekday_is_never_today() {
+        let rules = QuickAddRules::default();
+        assert_eq!(
+            rules.parse("Review friday", today()).due,
+            Some(date(2025, 6, 13))
+        );
+        assert_eq!(
+            rules.parse("Review Saturday", today()).due,
+            Some(date(2025, 6, 7))
+        );
+    }
+
+    #[test]
+    fn only_first_date() {
+        let rules = QuickAddRules::default();
+        let parsed = rules.parse("Move monday meeting to tuesday", today());
+        assert_eq!(parsed.name, "Move meeting to tuesday");
+        assert_eq!(parsed.due, Some(date(2025, 6, 9)));
+    }
+
+    #[test]
+    fn custom_prefixes() {
+        let rules = QuickAddRules {
+            tag_prefix: '@',
+            priority_prefix: 'p',
+            ..Default::default()
+        };
+        let parsed = rules.parse("Email #1 @work p0 pay p9", today());
+        assert_eq!(parsed.name, "Email #1 pay p9");
+        assert_eq!(parsed.tags, vec!["work"]);
+        assert_eq!(parsed.priority, Some(Priority::P0));
+    }
+
+    #[test]
+    fn natural_language() {
+        let rules = QuickAddRules::default();
+        let parsed = rules.parse("Pay rent tomorrow 9am !p1 #finance @home", today());
+        assert_eq!(parsed.name, "Pay rent");
+        assert_eq!(parsed.due, Some(date(2025, 6, 7)));
+        assert_eq!(parsed.time, NaiveTime::from_hms_opt(9, 0, 0));
+        assert_eq!(parsed.priority, Some(Priority::P1));
+        assert_eq!(parsed.tags, vec!["finance"]);
+        assert_eq!(parsed.contexts, vec!["home"]);
+        assert_eq!(
+            parsed
+                .tokens
+                .iter()
+                .map(|token| token.kind)
+                .collect::<Vec<_>>(),
+            vec![
+                TokenKind::Name,
+                TokenKind::Name,
+                TokenKind::Due,
+                TokenKind::Time,
+                TokenKind::Priority,
+                TokenKind::Tag,
+                TokenKind::Context,
+            ]
+        );
+
+        let at = |text| rules.parse(text, today()).time;
+        assert_eq!(at("Call 12am"), NaiveTime::from_hms_opt(0, 0, 0));
+        assert_eq!(at("Call 12pm"), NaiveTime::from_hms_opt(12, 0, 0));
+        assert_eq!(at("Call 9:30PM"), NaiveTime::from_hms_opt(21, 30, 0));
+        assert_eq!(at("Call 21:30"), NaiveTime::from_hms_opt(21, 30, 0));
+        assert_eq!(at("Call 13pm"), None);
+        assert_eq!(at("Call 25:00"), None);
+        assert_eq!(at("Call 9:5"), None);
+        assert_eq!(at("Buy 12 eggs"), None);
+
+        // A time without a date is today
+        let parsed = rules.parse("Standup 9:30 @work", today());
+        assert_eq!(parsed.name, "Standup");
+        assert_eq!(parsed.due, Some(today()));
+    }
+
+    #[test]
+    fn to_task() {
+        let now = Utc.with_ymd_and_hms(2025, 6, 6, 22, 30, 0).unwrap();
+        // Already Saturday in Berlin, so "tomorrow" is Sunday
+        let parsed = QuickAddRules::default().parse_at(
+            "Water plants tomorrow !1",
+            now,
+            chrono_tz::Europe::Berlin,
+        );
+        let task = parsed.to_task(&WorkingCalendar::default(), chrono_tz::Europe::Berlin);
+        assert_eq!(task.name, "Water plants");
+        assert_eq!(task.priority, Priority::P1);
+        assert_eq!(
+            task.due,
+            Some(Utc.with_ymd_and_hms(2025, 6, 8, 15, 0, 0).unwrap())
+        );
+        let parsed = QuickAddRules::default().parse_at(
+            "Water plants tomorrow 8am",
+            now,
+            chrono_tz::Europe::Berlin,
+        );
+        let task = parsed.to_task(&WorkingCalendar::default(), chrono_tz::Europe::Berlin);
+        assert_eq!(
+            task.due,
+            Some(Utc.with_ymd_and_hms(2025, 6, 8, 6, 0, 0).unwrap())
+        );
+    }
+
+    #[test]
+    fn capture() {
+        let rules = QuickAddRules::for_locale(Locale::EnGb);
+        let parsed = rules.parse("Call the plumber tomorrow !1 @HOME @nowhere", today());
+        let task = parsed
+            .capture(&TestBackend, &WorkingCalendar::default(), chrono_tz::UTC)
+            .unwrap();
+        assert_eq!(task.name, "Call the plumber");
+        assert_eq!(task.priority, Priority::P1);
+        assert!(task.due.is_some());
+    }
+}
@@ -0,0 +1,233 @@
+//! Ranks which keep items in a user-chosen order, stored as `Contains::sortorder`.
+//!
+//! A rank is a string of base-36 digits (`0-9`, `a-z`), read as a fraction: "i" is just over
+//! half way, "0i" just over 1/72. Ranks sort lexicographically, and there is always room for a
+//! new rank between two others, so moving an item only changes that item's rank.
+
+use crate::{HelixFlowError, HelixFlowItem, HelixFlowResult, task::Contains};
+
+const DIGITS: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+
+fn invalid(rank: &str) -> HelixFlowError {
+    HelixFlowError::InvalidValue {
+        field: "sortorder".into(),
+        value: rank.into(),
+    }
+}
+
+/// The digits in `rank`, which must not be empty or end in `0` (as there would be no room
+/// directly before it).
+fn digits(rank: &str) -> HelixFlowResult<Vec<u8>> {
+    let digits = rank
+        .bytes()
+        .map(|c| DIGITS.iter().position(|&digit| digit == c).map(|d| d as u8))
+        .collect::<Option<Vec<u8>>>()
+        .ok_or_else(|| invalid(rank))?;
+    match digits.last() {
+        Some(1..) => Ok(digits),
+        _ => Err(invalid(rank)),
+    }
+}
+
+/// Digits strictly between `a` and `b`, or `a` and 1 if `b` is `None`.
+fn midpoint(a: &[u8], b: Option<&[u8]>) -> Vec<u8> {
+    if let Some(b) = b {
+        let common = (0..b.len())
+            .take_while(|&n| a.get(n).copied().unwrap_or(0) == b[n])
+            .count();
+        if common > 0 {
+            let mut rank = b[..common].to_vec();
+            rank.extend(midpoint(
+                a.get(common..).unwrap_or_default(),
+                Some(&b[common..]),
+            ));
+            return rank;
+        }
+    }
+    let digit_a = a.first().copied().unwrap_or(0);
+    let digit_b = b.map_or(DIGITS.len() as u8, |b| b[0]);
+    if digit_b - digit_a > 1 {
+        vec![(digit_a + digit_b).div_ceil(2)]
+    } else if let Some(b) = b
+        && b.len() > 1
+    {
+        vec![b[0]]
+    } else {
+        let mut rank = vec![digit_a];
+        rank.extend(midpoint(a.get(1..).unwrap_or_default(), None));
+        rank
+    }
+}
+
+/// A rank which sorts after `before` and before `after`, either of which can be `None` to
+/// place the new rank at the start or end.
+///
+/// Fails with `InvalidValue` if either rank is not valid, or `before` is not before `after`.
+pub fn between(before: Option<&str>, after: Option<&str>) -> HelixFlowResult<String> {
+    let a = before.map(digits).transpose()?.unwrap_or_default();
+    let b = after.map(digits).transpose()?;
+    if let (Some(before), Some(after)) = (before, after)
+        && before >= after
+    {
+        return Err(invalid(after));
+    }
+    Ok(midpoint(&a, b.as_deref())
+        .into_iter()
+        .map(|digit| DIGITS[digit as usize] as char)
+        .collect())
+}
+
+impl<LEFT, RIGHT> Contains<LEFT, RIGHT>
+where
+    RIGHT: HelixFlowItem,
+{
+    /// Rank this link directly before `next` in `siblings` - the links already in the same
+    /// container, in order.
+    ///
+    /// Fails with `NotFound` if `next` is not one of the `siblings`.
+    pub fn insert_before(
+        mut self,
+        next: &RIGHT,
+        siblings: &[Contains<LEFT, RIGHT>],
+    ) -> HelixFlowResult<Self> {
+        let index = position(next, siblings)?;
+        let before = index.checked_sub(1).map(|i| siblings[i].sortorder.as_str());
+        self.sortorder = between(before, Some(&siblings[index].sortorder))?;
+        Ok(self)
+    }
+
+    /// Rank this link directly after `previous` in `siblings` - the links already in the same
+    /// container, in order.
+    ///
+    /// Fails with `NotFound` if `previous` is not one of the `siblings`.
+    pub fn insert_after(
+        mut self,
+        previous: &RIGHT,
+        siblings: &[Contains<LEFT, RIGHT>],
+    ) -> HelixFlowResult<Self> {
+        let index = position(previous, siblings)?;
+        let after = siblings.get(index + 1).map(|next| next.sortorder.as_str());
+        self.sortorder = between(Some(&siblings[index].sortorder), after)?;
+        Ok(self)
+    }
+}
+
+fn position<LEFT, RIGHT>(item: &RIGHT, siblings: &[Contains<LEFT, RIGHT>]) -> HelixFlowResult<usize>
+where
+    RIGHT: HelixFlowItem,
+{
+    siblings
+        .iter()
+        .position(|sibling| {
+            sibling
+                .right
+                .as_ref()
+                .is_ok_and(|right| right.id() == item.id())
+        })
+        .ok_or_else(|| HelixFlowError::NotFound {
+            itemtype: "sibling".into(),
+            id: *item.id(),
+        })
+}
+
+#[cfg(test)]
+#[coverage(off)]
+mod tests {
+    use std::assert_matches::assert_matches;
+
+    use uuid::uuid;
+
+    use super::*;
+    use crate::{
+        CRUD, Relate,
+        task::{Task, TaskList, TestBackend},
+    };
+
+    #[test]
+    fn ranks_between() {
+        assert_eq!(between(None, None).unwrap(), "i");
+        assert_eq!(between(Some("i"), None).unwrap(), "r");
+        assert_eq!(between(None, Some("i")).unwrap(), "9");
+        assert_eq!(between(Some("a"), Some("b")).unwrap(), "ai");
+        assert_eq!(between(Some("az"), Some("b")).unwrap(), "azi");
+        assert_eq!(between(Some("a"), Some("a1")).unwrap(), "a0i");
+        assert_eq!(between(None, Some("01")).unwrap(), "00i");
+    }
+
+    #[test]
+    fn always_room() {
+        let mut ranks = vec![between(None, None).unwrap()];
+        for _ in 0..50 {
+            let last = ranks.last().unwrap();
+            ranks.push(between(Some(last), None).unwrap());
+            let first = ranks.first().unwrap();
+            ranks.insert(0, between(None, Some(first)).unwrap());
+            let middle = ranks.len() / 2;
+            let rank = between(Some(&ranks[middle - 1]), Some(&ranks[middle])).unwrap();
+            ranks.insert(middle, rank);
+        }
+        let mut sorted = ranks.clone();
+        sorted.sort();
+        sorted.dedup();
+        assert_eq!(sorted, ranks);
+    }
+
+    #[test]
+    fn invalid_ranks() {
+        assert_matches!(
+            between(Some("b"), Some("a")),
+            Err(HelixFlowError::InvalidValue { field, .. }) if field == "sortorder"
+        );
+        assert_matches!(between(Some("a0"), None), Err(_));
+        assert_matches!(between(None, Some("A")), Err(_));
+        assert_matches!(between(Some(""), None), Err(_));
+    }
+
+    #[test]
+    fn insert_between_siblings() {
+        let tasklist = TaskList::new("Backlog");
+        let first = Task::new("First", None);
+        let last = Task::new("Last", None);
+        let siblings = [
+            Contains {
+                sortorder: "i".into(),
+                ..tasklist.link(&first)
+            },
+            Contains {
+                sortorder: "r".into(),
+                ..tasklist.link(&last)
+            },
+        ];
+        let new = Task::new("New", None);
+        let before_last = tasklist.link(&new).insert_before(&last, &siblings).unwrap();
+        assert_eq!(before_last.sortorder, "n");
+        let before_first = tasklist
+            .link(&new)
+            .insert_before(&first, &siblings)
+            .unwrap();
+        assert_eq!(before_first.sortorder, "9");
+        let after_last = tasklist.link(&new).insert_after(&last, &siblings).unwrap();
+        assert_eq!(after_last.sortorder, "w");
+        assert_matches!(
+            tasklist.link(&new).insert_after(&new, &siblings),
+            Err(HelixFlowError::NotFound { id, .. }) if id == new.id
+        );
+    }
+
+    #[test]
+    fn reorder_stored_tasks() {
+        let tasklist =
+            TaskList::get(&TestBackend, &uuid!("0196fe23-7c01-7d6b-9e09-5968eb370549")).unwrap();
+        let siblings: Vec<_> = tasklist.get_linked_items(&TestBackend).unwrap().collect();
+        let task_2 = siblings[1].right.as_ref().unwrap();
+        let moved = tasklist
+            .link(task_2)
+            .insert_before(siblings[0].right.as_ref().unwrap(), &siblings)
+            .unwrap();
+        assert!(moved.sortorder < siblings[0].sortorder);
+        let appended = TestBackend
+            .create_linked_item(&tasklist.link(&Task::new("New", None)))
+            .unwrap();
+        assert!(appended.sortorder > siblings[1].sortorder);
+    }
+}
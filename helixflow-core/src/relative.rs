@@ -0,0 +1,187 @@
+//! Localised wording for dates and durations relative to now, such as "due in 2 days" or
+//! "3 hours logged". UIs, CLI output & notifications should use these rather than formatting
+//! dates themselves, so the wording is the same everywhere.
+
+use chrono::{DateTime, TimeDelta, Utc};
+use chrono_tz::Tz;
+
+use crate::{dates::local_date, quickadd::Locale};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Unit {
+    Minute,
+    Hour,
+    Day,
+}
+
+impl Unit {
+    /// `count` of this unit, e.g. "1 day", "2 days". German plurals take the dative after "in",
+    /// "vor" & "seit": "in 2 Tagen" but "2 Tage".
+    fn count(self, count: i64, locale: Locale, dative: bool) -> String {
+        let word = match locale {
+            Locale::EnGb | Locale::EnUs => {
+                let (one, many) = match self {
+                    Unit::Minute => ("minute", "minutes"),
+                    Unit::Hour => ("hour", "hours"),
+                    Unit::Day => ("day", "days"),
+                };
+                if count == 1 { one } else { many }
+            }
+            Locale::De => {
+                let (one, many, many_dative) = match self {
+                    Unit::Minute => ("Minute", "Minuten", "Minuten"),
+                    Unit::Hour => ("Stunde", "Stunden", "Stunden"),
+                    Unit::Day => ("Tag", "Tage", "Tagen"),
+                };
+                match (count, dative) {
+                    (1, _) => one,
+                    (_, true) => many_dative,
+                    (_, false) => many,
+                }
+            }
+        };
+        format!("{count} {word}")
+    }
+}
+
+/// When `due` is, in calendar days from `now` as seen in `tz`: "due today", "due tomorrow",
+/// "due in 3 days", "due yesterday" or "3 days overdue".
+pub fn due(due: DateTime<Utc>, now: DateTime<Utc>, tz: Tz, locale: Locale) -> String {
+    let days = (local_date(due, tz) - local_date(now, tz)).num_days();
+    match locale {
+        Locale::EnGb | Locale::EnUs => match days {
+            0 => "due today".to_string(),
+            1 => "due tomorrow".to_string(),
+            -1 => "due yesterday".to_string(),
+            2.. => format!("due in {}", Unit::Day.count(days, locale, true)),
+            _ => format!("{} overdue", Unit::Day.count(-days, locale, false)),
+        },
+        Locale::De => match days {
+            0 => "heute fällig".to_string(),
+            1 => "morgen fällig".to_string(),
+            -1 => "gestern fällig".to_string(),
+            2.. => format!("in {} fällig", Unit::Day.count(days, locale, true)),
+            _ => format!("seit {} überfällig", Unit::Day.count(-days, locale, true)),
+        },
+    }
+}
+
+/// How long ago `instant` was: "just now", "5 minutes ago", "3 hours ago" or "2 days ago".
+/// Instants in the future (e.g. from a clock which is slightly ahead) count as "just now".
+pub fn ago(instant: DateTime<Utc>, now: DateTime<Utc>, locale: Locale) -> String {
+    let elapsed = now - instant;
+    let (count, unit) = if elapsed.num_days() > 0 {
+        (elapsed.num_days(), Unit::Day)
+    } else if elapsed.num_hours() > 0 {
+        (elapsed.num_hours(), Unit::Hour)
+    } else {
+        (elapsed.num_minutes(), Unit::Minute)
+    };
+    match locale {
+        Locale::EnGb | Locale::EnUs if count < 1 => "just now".to_string(),
+        Locale::De if count < 1 => "gerade eben".to_string(),
+        Locale::EnGb | Locale::EnUs => format!("{} ago", unit.count(count, locale, false)),
+        Locale::De => format!("vor {}", unit.count(count, locale, true)),
+    }
+}
+
+/// A length of time in hours and minutes, rounded down to the minute: "45 minutes", "1 hour",
+/// "2 hours 30 minutes". Work is measured in hours, so there are no days.
+pub fn duration(length: TimeDelta, locale: Locale) -> String {
+    let minutes = length.num_minutes().abs();
+    let (hours, minutes) = (minutes / 60, minutes % 60);
+    match (hours, minutes) {
+        (0, minutes) => Unit::Minute.count(minutes, locale, false),
+        (hours, 0) => Unit::Hour.count(hours, locale, false),
+        (hours, minutes) => format!(
+            "{} {}",
+            Unit::Hour.count(hours, locale, false),
+            Unit::Minute.count(minutes, locale, false)
+        ),
+    }
+}
+
+/// Time spent on something: "3 hours logged".
+pub fn logged(length: TimeDelta, locale: Locale) -> String {
+    match locale {
+        Locale::EnGb | Locale::EnUs => format!("{} logged", duration(length, locale)),
+        Locale::De => format!("{} erfasst", duration(length, locale)),
+    }
+}
+
+#[cfg(test)]
+#[coverage(off)]
+mod tests {
+    use chrono::TimeZone;
+    use chrono_tz::{Europe::Berlin, UTC};
+
+    use super::*;
+
+    fn utc(day: u32, hour: u32, min: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 6, day, hour, min, 0).unwrap()
+    }
+
+    #[test]
+    fn due_english() {
+        let now = utc(10, 12, 0);
+        let cases = [
+            (utc(10, 23, 0), "due today"),
+            (utc(11, 0, 0), "due tomorrow"),
+            (utc(13, 9, 0), "due in 3 days"),
+            (utc(9, 9, 0), "due yesterday"),
+            (utc(8, 9, 0), "2 days overdue"),
+        ];
+        for (due_at, expected) in cases {
+            assert_eq!(due(due_at, now, UTC, Locale::EnGb), expected);
+        }
+    }
+
+    #[test]
+    fn due_uses_local_days() {
+        // 23:00 UTC is already tomorrow in Berlin
+        let now = utc(10, 12, 0);
+        assert_eq!(
+            due(utc(10, 23, 0), now, Berlin, Locale::EnUs),
+            "due tomorrow"
+        );
+    }
+
+    #[test]
+    fn due_german() {
+        let now = utc(10, 12, 0);
+        assert_eq!(due(utc(10, 8, 0), now, UTC, Locale::De), "heute fällig");
+        assert_eq!(
+            due(utc(12, 8, 0), now, UTC, Locale::De),
+            "in 2 Tagen fällig"
+        );
+        assert_eq!(
+            due(utc(7, 8, 0), now, UTC, Locale::De),
+            "seit 3 Tagen überfällig"
+        );
+    }
+
+    #[test]
+    fn ago_units() {
+        let now = utc(10, 12, 0);
+        assert_eq!(ago(utc(10, 11, 59), now, Locale::EnGb), "1 minute ago");
+        assert_eq!(ago(utc(10, 9, 30), now, Locale::EnGb), "2 hours ago");
+        assert_eq!(ago(utc(8, 12, 0), now, Locale::EnGb), "2 days ago");
+        assert_eq!(ago(utc(8, 12, 0), now, Locale::De), "vor 2 Tagen");
+        assert_eq!(ago(now, now, Locale::EnGb), "just now");
+        assert_eq!(ago(utc(10, 12, 5), now, Locale::De), "gerade eben");
+    }
+
+    #[test]
+    fn durations() {
+        assert_eq!(duration(TimeDelta::minutes(45), Locale::EnGb), "45 minutes");
+        assert_eq!(duration(TimeDelta::minutes(60), Locale::EnGb), "1 hour");
+        assert_eq!(
+            duration(TimeDelta::minutes(150), Locale::EnGb),
+            "2 hours 30 minutes"
+        );
+        assert_eq!(duration(TimeDelta::seconds(30), Locale::EnGb), "0 minutes");
+        assert_eq!(logged(TimeDelta::hours(3), Locale::EnUs), "3 hours logged");
+        assert_eq!(logged(TimeDelta::hours(1), Locale::De), "1 Stunde erfasst");
+        assert_eq!(logged(TimeDelta::days(2), Locale::De), "48 Stunden erfasst");
+    }
+}
@@ -0,0 +1,185 @@
+//! Reminders to notify the user about a task at a given time.
+//!
+//! The app checks for due reminders with `fire_due` on a timer and shows a notification for
+//! each task returned.
+
+use chrono::{DateTime, TimeDelta, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::{Uuid, uuid};
+
+use crate::{
+    CRUD, HelixFlowError, HelixFlowItem, HelixFlowResult, Store, Validate,
+    task::{Task, TestBackend},
+};
+
+/// How often a reminder fires again.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Repeat {
+    /// Fire once, then delete the reminder
+    #[default]
+    Never,
+    Daily,
+    Weekly,
+}
+
+impl Repeat {
+    /// The time between firings, `None` if the reminder does not repeat.
+    pub fn interval(&self) -> Option<TimeDelta> {
+        match self {
+            Repeat::Never => None,
+            Repeat::Daily => Some(TimeDelta::days(1)),
+            Repeat::Weekly => Some(TimeDelta::weeks(1)),
+        }
+    }
+}
+
+/// Notify the user about the task with id `task` at `fire_at`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, HelixFlowItem)]
+pub struct Reminder {
+    pub task: Uuid,
+    pub fire_at: DateTime<Utc>,
+    pub repeat: Repeat,
+    pub id: Uuid,
+}
+
+impl Validate for Reminder {}
+
+impl Reminder {
+    /// Create a new `Reminder` for `task` with valid `id`, suitable for usage as database key.
+    pub fn new(task: &Task, fire_at: DateTime<Utc>, repeat: Repeat) -> Reminder {
+        Reminder {
+            task: task.id,
+            fire_at,
+            repeat,
+            id: Uuid::now_v7(),
+        }
+    }
+
+    pub fn is_due(&self, now: DateTime<Utc>) -> bool {
+        self.fire_at <= now
+    }
+
+    /// This reminder after firing at `now`: moved to the next time after `now`, or `None` if it
+    /// does not repeat. Firings missed while the app was closed are skipped.
+    pub fn next(&self, now: DateTime<Utc>) -> Option<Reminder> {
+        let interval = self.repeat.interval()?;
+        let mut fire_at = self.fire_at;
+        while fire_at <= now {
+            fire_at += interval;
+        }
+        Some(Reminder {
+            fire_at,
+            ..self.clone()
+        })
+    }
+}
+
+/// Find reminders which need to fire.
+pub trait Reminders: Store<Reminder> {
+    /// All reminders due at `now`, earliest first.
+    fn due_reminders(&self, now: DateTime<Utc>) -> HelixFlowResult<Vec<Reminder>>;
+}
+
+/// Fire all reminders due at `now`, returning the tasks to notify the user about. Repeating
+/// reminders are moved on, others are deleted, as are reminders for tasks which no longer exist.
+pub fn fire_due<B>(backend: &B, now: DateTime<Utc>) -> HelixFlowResult<Vec<Task>>
+where
+    B: Reminders + Store<Task>,
+{
+    let mut tasks = Vec::new();
+    for reminder in backend.due_reminders(now)? {
+        let task = match Task::get(backend, &reminder.task) {
+            Ok(task) => Some(task),
+            Err(HelixFlowError::NotFound { .. }) => None,
+            Err(e) => return Err(e),
+        };
+        match (&task, reminder.next(now)) {
+            (Some(_), Some(next)) => next.update(backend)?,
+            _ => reminder.delete(backend)?,
+        }
+        tasks.extend(task);
+    }
+    Ok(tasks)
+}
+
+fn test_reminder() -> Reminder {
+    Reminder {
+        task: uuid!("0196b4c9-8447-7959-ae1f-72c7c8a3dd36"),
+        fire_at: Utc.with_ymd_and_hms(2025, 6, 2, 9, 0, 0).unwrap(),
+        repeat: Repeat::Daily,
+        id: uuid!("0199b1c2-3d4e-7f5a-8b6c-7d8e9f0a1b2c"),
+    }
+}
+
+impl Store<Reminder> for TestBackend {
+    fn create(&self, reminder: &Reminder) -> HelixFlowResult<Reminder> {
+        Ok(reminder.clone())
+    }
+
+    fn get(&self, id: &Uuid) -> HelixFlowResult<Reminder> {
+        match id.to_string().as_str() {
+            "0199b1c2-3d4e-7f5a-8b6c-7d8e9f0a1b2c" => Ok(test_reminder()),
+            _ => Err(HelixFlowError::NotFound {
+                itemtype: "Reminder".into(),
+                id: *id,
+            }),
+        }
+    }
+
+    fn update(&self, reminder: &Reminder) -> HelixFlowResult<Reminder> {
+        Ok(reminder.clone())
+    }
+
+    fn delete(&self, _id: &Uuid) -> HelixFlowResult<()> {
+        Ok(())
+    }
+}
+
+/// `Task 1` has a daily reminder from 2 June 2025, 09:00 UTC.
+impl Reminders for TestBackend {
+    fn due_reminders(&self, now: DateTime<Utc>) -> HelixFlowResult<Vec<Reminder>> {
+        Ok(Some(test_reminder())
+            .filter(|reminder| reminder.is_due(now))
+            .into_iter()
+            .collect())
+    }
+}
+
+#[cfg(test)]
+#[coverage(off)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn next_firing() {
+        let reminder = test_reminder();
+        let now = Utc.with_ymd_and_hms(2025, 6, 4, 12, 0, 0).unwrap();
+        assert!(reminder.is_due(now));
+        let next = reminder.next(now).unwrap();
+        assert_eq!(
+            next.fire_at,
+            Utc.with_ymd_and_hms(2025, 6, 5, 9, 0, 0).unwrap()
+        );
+        assert!(!next.is_due(now));
+        let once = Reminder {
+            repeat: Repeat::Never,
+            ..reminder
+        };
+        assert_eq!(once.next(now), None);
+    }
+
+    #[test]
+    fn fire_due_reminders() {
+        let before = Utc.with_ymd_and_hms(2025, 6, 1, 12, 0, 0).unwrap();
+        assert_eq!(fire_due(&TestBackend, before).unwrap(), vec![]);
+        let after = Utc.with_ymd_and_hms(2025, 6, 2, 9, 0, 0).unwrap();
+        let tasks = fire_due(&TestBackend, after).unwrap();
+        assert_eq!(
+            tasks
+                .iter()
+                .map(|task| task.name.as_ref())
+                .collect::<Vec<_>>(),
+            vec!["Task 1"]
+        );
+    }
+}
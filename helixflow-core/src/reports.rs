@@ -0,0 +1,333 @@
+//! Statistics about getting things done over a period, e.g. for a weekly review. Everything is
+//! returned as plain data for the UI or CLI to render.
+//!
+//! See also `analytics` for a snapshot of each list.
+
+use std::ops::RangeInclusive;
+
+use chrono::{DateTime, NaiveDate, TimeDelta, Utc};
+use chrono_tz::Tz;
+use serde::Serialize;
+
+use crate::{
+    HelixFlowItem, HelixFlowResult, Relate,
+    dates::{day_bounds, local_date},
+    query::{Query, TaskQuery},
+    task::{Contains, Status, Task, TaskList, TestBackend},
+};
+
+/// How many tasks were completed on one day.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct DailyCount {
+    pub date: NaiveDate,
+    pub completed: usize,
+}
+
+/// How many tasks in one list are still open & how many are done.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ListCount {
+    pub tasklist: TaskList,
+    pub open: usize,
+    pub done: usize,
+}
+
+/// Statistics for a period of whole days.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Report {
+    /// Every day in the period, including those when nothing was completed.
+    pub completed_per_day: Vec<DailyCount>,
+    /// Average number of tasks completed per day over the period.
+    pub throughput: f64,
+    /// How long open tasks have existed on average, `None` if there are no open tasks.
+    pub average_open_age: Option<TimeDelta>,
+    pub per_list: Vec<ListCount>,
+}
+
+/// Count the `tasks` completed on each day of `period`, as seen in `tz`.
+pub fn completed_per_day<'t>(
+    tasks: impl IntoIterator<Item = &'t Task>,
+    period: &RangeInclusive<NaiveDate>,
+    tz: Tz,
+) -> Vec<DailyCount> {
+    let mut counts: Vec<DailyCount> = period
+        .start()
+        .iter_days()
+        .take_while(|date| date <= period.end())
+        .map(|date| DailyCount { date, completed: 0 })
+        .collect();
+    for task in tasks {
+        let Some(completed_at) = task.completed_at else {
+            continue;
+        };
+        let date = local_date(completed_at, tz);
+        if let Some(count) = counts.iter_mut().find(|count| count.date == date) {
+            count.completed += 1;
+        }
+    }
+    counts
+}
+
+/// The average time since `tasks` were created, as at `now`. `None` if there are no tasks.
+pub fn average_age<'t>(
+    tasks: impl IntoIterator<Item = &'t Task>,
+    now: DateTime<Utc>,
+) -> Option<TimeDelta> {
+    let ages: Vec<TimeDelta> = tasks
+        .into_iter()
+        .filter_map(|task| task.created_at())
+        .map(|created_at| now - created_at)
+        .collect();
+    let count = i32::try_from(ages.len()).ok().filter(|count| *count > 0)?;
+    Some(ages.into_iter().sum::<TimeDelta>() / count)
+}
+
+fn open() -> TaskQuery {
+    TaskQuery::default()
+        .status(Status::Todo)
+        .status(Status::InProgress)
+        .status(Status::Blocked)
+}
+
+impl Report {
+    /// Report on `period` in `tz`, as at `now`, with counts for each of `tasklists`.
+    pub fn generate<B: Query<Task>>(
+        backend: &B,
+        tasklists: &[TaskList],
+        period: RangeInclusive<NaiveDate>,
+        now: DateTime<Utc>,
+        tz: Tz,
+    ) -> HelixFlowResult<Report> {
+        let done = TaskQuery::default().status(Status::Done);
+        let (from, _) = day_bounds(*period.start(), tz);
+        let (_, until) = day_bounds(*period.end(), tz);
+        let completed: Vec<Task> = backend
+            .query(&done)?
+            .filter(|task| task.completed_at.is_some_and(|at| at >= from && at < until))
+            .collect();
+        let completed_per_day = completed_per_day(&completed, &period, tz);
+        let throughput = completed.len() as f64 / completed_per_day.len().max(1) as f64;
+        let open_tasks: Vec<Task> = backend.query(&open())?.collect();
+        let per_list = tasklists
+            .iter()
+            .map(|tasklist| {
+                Ok(ListCount {
+                    tasklist: tasklist.clone(),
+                    open: backend.query(&open().in_list(tasklist))?.count(),
+                    done: backend.query(&done.clone().in_list(tasklist))?.count(),
+                })
+            })
+            .collect::<HelixFlowResult<Vec<ListCount>>>()?;
+        Ok(Report {
+            completed_per_day,
+            throughput,
+            average_open_age: average_age(&open_tasks, now),
+            per_list,
+        })
+    }
+}
+
+/// One day in a burndown or burnup chart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct BurndownPoint {
+    pub date: NaiveDate,
+    /// Tasks in the list
+    pub total: usize,
+    /// Tasks completed by the end of `date`
+    pub completed: usize,
+}
+
+impl BurndownPoint {
+    /// Tasks still open at the end of `date`.
+    pub fn remaining(&self) -> usize {
+        self.total.saturating_sub(self.completed)
+    }
+}
+
+/// How many tasks are in a list, and how many of them had been completed at various instants.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CompletionCounts {
+    pub total: usize,
+    /// One count per cutoff, in the same order
+    pub completed_before: Vec<usize>,
+}
+
+impl CompletionCounts {
+    /// Count in memory, for backends which cannot aggregate themselves.
+    pub fn of<'t>(
+        tasks: impl IntoIterator<Item = &'t Task>,
+        cutoffs: &[DateTime<Utc>],
+    ) -> CompletionCounts {
+        let mut counts = CompletionCounts {
+            total: 0,
+            completed_before: vec![0; cutoffs.len()],
+        };
+        for task in tasks {
+            counts.total += 1;
+            let Some(completed_at) = task.completed_at else {
+                continue;
+            };
+            for (count, cutoff) in counts.completed_before.iter_mut().zip(cutoffs) {
+                if completed_at < *cutoff {
+                    *count += 1;
+                }
+            }
+        }
+        counts
+    }
+}
+
+/// Count completions in a tasklist without loading its tasks.
+pub trait Burndown: Relate<Contains<TaskList, Task>> {
+    /// How many tasks are in `tasklist` and how many of them were completed before each of
+    /// `cutoffs`.
+    fn completion_counts(
+        &self,
+        tasklist: &TaskList,
+        cutoffs: &[DateTime<Utc>],
+    ) -> HelixFlowResult<CompletionCounts>;
+}
+
+impl TaskList {
+    /// The burndown (or burnup) series for each day of `period` in `tz`.
+    ///
+    /// Tasks are not timestamped when added to a list, so `total` is the current size of the
+    /// list on every day.
+    pub fn burndown<B: Burndown>(
+        &self,
+        backend: &B,
+        period: RangeInclusive<NaiveDate>,
+        tz: Tz,
+    ) -> HelixFlowResult<Vec<BurndownPoint>> {
+        let dates: Vec<NaiveDate> = period
+            .start()
+            .iter_days()
+            .take_while(|date| date <= period.end())
+            .collect();
+        let cutoffs: Vec<DateTime<Utc>> =
+            dates.iter().map(|date| day_bounds(*date, tz).1).collect();
+        let counts = backend.completion_counts(self, &cutoffs)?;
+        Ok(dates
+            .into_iter()
+            .zip(counts.completed_before)
+            .map(|(date, completed)| BurndownPoint {
+                date,
+                total: counts.total,
+                completed,
+            })
+            .collect())
+    }
+}
+
+/// Counted in memory from `get_linked_items`.
+impl Burndown for TestBackend {
+    fn completion_counts(
+        &self,
+        tasklist: &TaskList,
+        cutoffs: &[DateTime<Utc>],
+    ) -> HelixFlowResult<CompletionCounts> {
+        let tasks = tasklist
+            .get_linked_items(self)?
+            .map(|contains| contains.right)
+            .collect::<HelixFlowResult<Vec<Task>>>()?;
+        Ok(CompletionCounts::of(&tasks, cutoffs))
+    }
+}
+
+#[cfg(test)]
+#[coverage(off)]
+mod tests {
+    use chrono::TimeZone;
+    use uuid::uuid;
+
+    use super::*;
+    use crate::CRUD;
+
+    fn june(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 6, day).unwrap()
+    }
+
+    #[test]
+    fn count_per_day() {
+        let done_at = |day, hour| Task {
+            status: Status::Done,
+            completed_at: Some(Utc.with_ymd_and_hms(2025, 6, day, hour, 0, 0).unwrap()),
+            ..Task::new("Done", None)
+        };
+        let tasks = vec![
+            done_at(2, 9),
+            done_at(2, 17),
+            // 3 June in New York
+            done_at(4, 1),
+            done_at(9, 12),
+            Task::new("Open", None),
+        ];
+        let counts = completed_per_day(&tasks, &(june(2)..=june(4)), chrono_tz::America::New_York);
+        assert_eq!(
+            counts
+                .iter()
+                .map(|count| (count.date, count.completed))
+                .collect::<Vec<_>>(),
+            vec![(june(2), 2), (june(3), 1), (june(4), 0)]
+        );
+    }
+
+    #[test]
+    fn report_from_backend() {
+        let tasklist =
+            TaskList::get(&TestBackend, &uuid!("0196fe23-7c01-7d6b-9e09-5968eb370549")).unwrap();
+        let task_1 =
+            Task::get(&TestBackend, &uuid!("0196b4c9-8447-7959-ae1f-72c7c8a3dd36")).unwrap();
+        let task_2 =
+            Task::get(&TestBackend, &uuid!("0196ca5f-d934-7ec8-b042-ae37b94b8432")).unwrap();
+        let now = Utc.with_ymd_and_hms(2025, 7, 1, 0, 0, 0).unwrap();
+        let report = Report::generate(
+            &TestBackend,
+            std::slice::from_ref(&tasklist),
+            june(1)..=june(30),
+            now,
+            Tz::UTC,
+        )
+        .unwrap();
+        assert_eq!(report.completed_per_day.len(), 30);
+        assert_eq!(report.throughput, 0.0);
+        let ages = (now - task_1.created_at().unwrap()) + (now - task_2.created_at().unwrap());
+        assert_eq!(report.average_open_age, Some(ages / 2));
+        assert_eq!(
+            report.per_list,
+            vec![ListCount {
+                tasklist,
+                open: 2,
+                done: 0
+            }]
+        );
+    }
+
+    #[test]
+    fn burndown_series() {
+        let done_on = |day| Task {
+            status: Status::Done,
+            completed_at: Some(Utc.with_ymd_and_hms(2025, 6, day, 12, 0, 0).unwrap()),
+            ..Task::new("Done", None)
+        };
+        let tasks = vec![done_on(2), done_on(3), done_on(3), Task::new("Open", None)];
+        let cutoffs: Vec<DateTime<Utc>> = (1..=3)
+            .map(|day| day_bounds(june(day), Tz::UTC).1)
+            .collect();
+        assert_eq!(
+            CompletionCounts::of(&tasks, &cutoffs),
+            CompletionCounts {
+                total: 4,
+                completed_before: vec![0, 1, 3]
+            }
+        );
+
+        let tasklist =
+            TaskList::get(&TestBackend, &uuid!("0196fe23-7c01-7d6b-9e09-5968eb370549")).unwrap();
+        let series = tasklist
+            .burndown(&TestBackend, june(1)..=june(7), Tz::UTC)
+            .unwrap();
+        assert_eq!(series.len(), 7);
+        assert_eq!(series[6].date, june(7));
+        assert!(series.iter().all(|point| point.remaining() == 2));
+    }
+}
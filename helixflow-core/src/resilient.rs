@@ -0,0 +1,383 @@
+//! A backend wrapper which retries transient failures and stops hammering a backend which is
+//! clearly down, so that a brief network blip doesn't bubble straight into the UI.
+
+use std::{
+    sync::{
+        Mutex,
+        atomic::{AtomicU32, Ordering},
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use uuid::Uuid;
+
+use crate::{HelixFlowError, HelixFlowResult, Link, Relate, Store};
+
+/// How often, and for how long, to retry a failing backend call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Total number of attempts, including the first one.
+    pub max_attempts: u32,
+    /// Wait before the first retry.
+    pub initial_backoff: Duration,
+    /// Each subsequent wait is multiplied by this factor ...
+    pub multiplier: u32,
+    /// ... up to this limit.
+    pub max_backoff: Duration,
+    /// Overall budget for a single call, including all retries and waits.
+    ///
+    /// A blocking backend call cannot be interrupted, so this limits when we _stop retrying_, it
+    /// does not abort an attempt which is already running.
+    pub timeout: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(50),
+            multiplier: 2,
+            max_backoff: Duration::from_secs(2),
+            timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// The wait before attempt number `attempt + 1` (`attempt` starts at 1).
+    fn backoff(&self, attempt: u32) -> Duration {
+        self.multiplier
+            .checked_pow(attempt - 1)
+            .and_then(|factor| self.initial_backoff.checked_mul(factor))
+            .map_or(self.max_backoff, |backoff| backoff.min(self.max_backoff))
+    }
+}
+
+/// When to stop calling a backend altogether.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CircuitBreakerPolicy {
+    /// Number of consecutive failed calls (after retries) before the circuit opens.
+    pub failure_threshold: u32,
+    /// How long the circuit stays open before a single trial call is let through.
+    pub cooldown: Duration,
+}
+
+impl Default for CircuitBreakerPolicy {
+    fn default() -> Self {
+        CircuitBreakerPolicy {
+            failure_threshold: 5,
+            cooldown: Duration::from_secs(30),
+        }
+    }
+}
+
+/// Wraps any backend `B` and retries calls which fail with a `BackendError`, or only those
+/// picked by `retry_if`.
+///
+/// Errors which are part of normal operation (`NotFound`, `Mismatch`, ...) are returned
+/// immediately. After `failure_threshold` consecutive failures the circuit opens and calls fail
+/// fast with `HelixFlowError::CircuitOpen` until the `cooldown` has passed.
+///
+/// ```ignore
+/// let backend = Resilient::new(SurrealDb::new(None)?, RetryPolicy::default(), CircuitBreakerPolicy::default());
+/// task.create(&backend)?;
+/// ```
+#[derive(Debug)]
+pub struct Resilient<B> {
+    backend: B,
+    retry: RetryPolicy,
+    breaker: CircuitBreakerPolicy,
+    is_transient: fn(&HelixFlowError) -> bool,
+    consecutive_failures: AtomicU32,
+    open_until: Mutex<Option<Instant>>,
+}
+
+impl<B> Resilient<B> {
+    pub fn new(backend: B, retry: RetryPolicy, breaker: CircuitBreakerPolicy) -> Self {
+        Resilient {
+            backend,
+            retry,
+            breaker,
+            is_transient: is_backend_error,
+            consecutive_failures: AtomicU32::new(0),
+            open_until: Mutex::new(None),
+        }
+    }
+
+    /// The wrapped backend.
+    pub fn inner(&self) -> &B {
+        &self.backend
+    }
+
+    /// Only retry, and count towards the circuit breaker, errors for which `is_transient` is
+    /// true. Backends know better than "any `BackendError`", e.g. a dropped connection is worth
+    /// retrying but a rejected query is not.
+    pub fn retry_if(mut self, is_transient: fn(&HelixFlowError) -> bool) -> Self {
+        self.is_transient = is_transient;
+        self
+    }
+
+    /// Run `operation` according to the retry & circuit breaker policies.
+    fn call<T>(&self, operation: impl Fn() -> HelixFlowResult<T>) -> HelixFlowResult<T> {
+        {
+            let mut open_until = self.open_until.lock().unwrap();
+            if let Some(until) = *open_until {
+                let now = Instant::now();
+                if now < until {
+                    return Err(HelixFlowError::CircuitOpen {
+                        retry_in: until - now,
+                    });
+                }
+                // half-open: let this call through as a trial
+                *open_until = None;
+            }
+        }
+
+        let started = Instant::now();
+        let mut attempt = 1;
+        let result = loop {
+            match operation() {
+                Err(e) if (self.is_transient)(&e) && attempt < self.retry.max_attempts => {
+                    let backoff = self.retry.backoff(attempt);
+                    if started.elapsed() + backoff > self.retry.timeout {
+                        break Err(e);
+                    }
+                    thread::sleep(backoff);
+                    attempt += 1;
+                }
+                result => break result,
+            }
+        };
+
+        match &result {
+            Err(e) if (self.is_transient)(e) => {
+                let failures = self.consecutive_failures.fetch_add(1, Ordering::SeqCst) + 1;
+                if failures >= self.breaker.failure_threshold {
+                    *self.open_until.lock().unwrap() = Some(Instant::now() + self.breaker.cooldown);
+                    self.consecutive_failures.store(0, Ordering::SeqCst);
+                }
+            }
+            _ => self.consecutive_failures.store(0, Ordering::SeqCst),
+        }
+        result
+    }
+}
+
+fn is_backend_error(error: &HelixFlowError) -> bool {
+    matches!(error, HelixFlowError::BackendError(_))
+}
+
+impl<ITEM, B> Store<ITEM> for Resilient<B>
+where
+    B: Store<ITEM>,
+{
+    fn create(&self, item: &ITEM) -> HelixFlowResult<ITEM> {
+        self.call(|| self.backend.create(item))
+    }
+
+    fn get(&self, id: &Uuid) -> HelixFlowResult<ITEM> {
+        self.call(|| self.backend.get(id))
+    }
+
+    fn update(&self, item: &ITEM) -> HelixFlowResult<ITEM> {
+        self.call(|| self.backend.update(item))
+    }
+
+    fn put(&self, item: &ITEM) -> HelixFlowResult<ITEM> {
+        self.call(|| self.backend.put(item))
+    }
+
+    fn delete(&self, id: &Uuid) -> HelixFlowResult<()> {
+        self.call(|| self.backend.delete(id))
+    }
+}
+
+impl<REL, B> Relate<REL> for Resilient<B>
+where
+    REL: Link,
+    B: Relate<REL>,
+{
+    fn create_linked_item(&self, link: &REL) -> HelixFlowResult<REL> {
+        self.call(|| self.backend.create_linked_item(link))
+    }
+
+    fn get_linked_items(&self, left: &REL::Left) -> HelixFlowResult<impl Iterator<Item = REL>> {
+        self.call(|| self.backend.get_linked_items(left))
+    }
+
+    fn delete_link(&self, link: &REL) -> HelixFlowResult<()> {
+        self.call(|| self.backend.delete_link(link))
+    }
+
+    fn create_link(&self, link: &REL) -> HelixFlowResult<()> {
+        self.call(|| self.backend.create_link(link))
+    }
+
+    fn move_all(&self, from: &[REL], to: &[REL]) -> HelixFlowResult<()> {
+        self.call(|| self.backend.move_all(from, to))
+    }
+}
+
+#[cfg(test)]
+#[coverage(off)]
+mod tests {
+    use std::assert_matches::assert_matches;
+
+    use anyhow::anyhow;
+    use uuid::uuid;
+
+    use super::*;
+    use crate::{CRUD, task::Task};
+
+    /// Fails with a `BackendError` for the first `failures` calls.
+    struct FlakyBackend {
+        failures: u32,
+        calls: AtomicU32,
+    }
+
+    impl FlakyBackend {
+        fn new(failures: u32) -> Self {
+            FlakyBackend {
+                failures,
+                calls: AtomicU32::new(0),
+            }
+        }
+
+        fn calls(&self) -> u32 {
+            self.calls.load(Ordering::SeqCst)
+        }
+    }
+
+    impl Store<Task> for FlakyBackend {
+        fn create(&self, task: &Task) -> HelixFlowResult<Task> {
+            let call = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
+            if call <= self.failures {
+                Err(anyhow!("Connection reset").into())
+            } else {
+                Ok(task.clone())
+            }
+        }
+
+        fn get(&self, id: &Uuid) -> HelixFlowResult<Task> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Err(HelixFlowError::NotFound {
+                itemtype: "Task".into(),
+                id: *id,
+            })
+        }
+
+        fn update(&self, task: &Task) -> HelixFlowResult<Task> {
+            self.create(task)
+        }
+
+        fn delete(&self, id: &Uuid) -> HelixFlowResult<()> {
+            self.get(id).map(|_| ())
+        }
+    }
+
+    fn no_wait() -> RetryPolicy {
+        RetryPolicy {
+            initial_backoff: Duration::ZERO,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn retry_until_success() {
+        let backend = Resilient::new(FlakyBackend::new(2), no_wait(), Default::default());
+        let task = Task::new("Test task", None);
+        task.create(&backend).unwrap();
+        assert_eq!(backend.inner().calls(), 3);
+    }
+
+    #[test]
+    fn give_up_after_max_attempts() {
+        let backend = Resilient::new(FlakyBackend::new(5), no_wait(), Default::default());
+        let task = Task::new("Test task", None);
+        let err = task.create(&backend).unwrap_err();
+        assert_matches!(err, HelixFlowError::BackendError(_));
+        assert_eq!(backend.inner().calls(), 3);
+    }
+
+    #[test]
+    fn retry_update() {
+        let backend = Resilient::new(FlakyBackend::new(1), no_wait(), Default::default());
+        let task = Task::new("Test task", None);
+        task.update(&backend).unwrap();
+        assert_eq!(backend.inner().calls(), 2);
+    }
+
+    #[test]
+    fn do_not_retry_not_found() {
+        let backend = Resilient::new(FlakyBackend::new(0), no_wait(), Default::default());
+        let id = uuid!("0196b4c9-8447-7959-ae1f-72c7c8a3dd36");
+        let err = Task::get(&backend, &id).unwrap_err();
+        assert_matches!(err, HelixFlowError::NotFound { .. });
+        assert_eq!(backend.inner().calls(), 1);
+    }
+
+    #[test]
+    fn only_retry_transient_errors() {
+        let backend = Resilient::new(FlakyBackend::new(5), no_wait(), Default::default())
+            .retry_if(|e| e.to_string().contains("timed out"));
+        let task = Task::new("Test task", None);
+        task.create(&backend).unwrap_err();
+        assert_eq!(backend.inner().calls(), 1);
+    }
+
+    #[test]
+    fn stop_retrying_after_timeout() {
+        let policy = RetryPolicy {
+            initial_backoff: Duration::from_secs(1),
+            timeout: Duration::from_millis(10),
+            ..Default::default()
+        };
+        let backend = Resilient::new(FlakyBackend::new(5), policy, Default::default());
+        let task = Task::new("Test task", None);
+        task.create(&backend).unwrap_err();
+        assert_eq!(backend.inner().calls(), 1);
+    }
+
+    #[test]
+    fn circuit_opens() {
+        let breaker = CircuitBreakerPolicy {
+            failure_threshold: 2,
+            cooldown: Duration::from_secs(60),
+        };
+        let backend = Resilient::new(FlakyBackend::new(100), no_wait(), breaker);
+        let task = Task::new("Test task", None);
+        task.create(&backend).unwrap_err();
+        task.create(&backend).unwrap_err();
+        let err = task.create(&backend).unwrap_err();
+        assert_matches!(err, HelixFlowError::CircuitOpen { .. });
+        assert_eq!(backend.inner().calls(), 6);
+    }
+
+    #[test]
+    fn circuit_half_open_after_cooldown() {
+        let breaker = CircuitBreakerPolicy {
+            failure_threshold: 1,
+            cooldown: Duration::ZERO,
+        };
+        let backend = Resilient::new(FlakyBackend::new(3), no_wait(), breaker);
+        let task = Task::new("Test task", None);
+        task.create(&backend).unwrap_err();
+        task.create(&backend).unwrap();
+        assert_eq!(backend.inner().calls(), 4);
+    }
+
+    #[test]
+    fn backoff_is_capped() {
+        let policy = RetryPolicy {
+            initial_backoff: Duration::from_millis(100),
+            multiplier: 10,
+            max_backoff: Duration::from_secs(2),
+            ..Default::default()
+        };
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_secs(1));
+        assert_eq!(policy.backoff(3), Duration::from_secs(2));
+        assert_eq!(policy.backoff(100), Duration::from_secs(2));
+    }
+}
@@ -0,0 +1,78 @@
+//! Finding items by the words they contain, rather than by id.
+
+use uuid::uuid;
+
+use crate::{
+    HelixFlowResult, Store,
+    task::{Task, TestBackend},
+};
+
+/// Methods to find `ITEM`s in a backend by text
+pub trait Search<ITEM>: Send + Sync {
+    /// All `ITEM`s matching every word in `query`, ignoring case, best matches first.
+    ///
+    /// How loosely a word matches (whole words, parts of words, stemming "paying" to "pay" ...)
+    /// depends on the backend. An empty `query` matches nothing.
+    fn search(&self, query: &str) -> HelixFlowResult<impl Iterator<Item = ITEM>>;
+}
+
+/// Does `task` contain every word in `query` in its name or description, ignoring case?
+///
+/// For backends without their own full-text search.
+pub fn task_matches(task: &Task, query: &str) -> bool {
+    let text = format!(
+        "{} {}",
+        task.name,
+        task.description.as_deref().unwrap_or_default()
+    )
+    .to_lowercase();
+    let mut words = query.split_whitespace().peekable();
+    words.peek().is_some() && words.all(|word| text.contains(&word.to_lowercase()))
+}
+
+/// Searches `Task 1` & `Task 2`.
+impl Search<Task> for TestBackend {
+    fn search(&self, query: &str) -> HelixFlowResult<impl Iterator<Item = Task>> {
+        let tasks: Vec<Task> = [
+            uuid!("0196b4c9-8447-7959-ae1f-72c7c8a3dd36"),
+            uuid!("0196ca5f-d934-7ec8-b042-ae37b94b8432"),
+        ]
+        .iter()
+        .map(|id| self.get(id))
+        .collect::<HelixFlowResult<_>>()?;
+        let query = query.to_string();
+        Ok(tasks
+            .into_iter()
+            .filter(move |task| task_matches(task, &query)))
+    }
+}
+
+#[cfg(test)]
+#[coverage(off)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn search_tasks() {
+        let names = |query| {
+            TestBackend
+                .search(query)
+                .unwrap()
+                .map(|task| task.name.to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names("task"), vec!["Task 1", "Task 2"]);
+        assert_eq!(names("TASK 2"), vec!["Task 2"]);
+        assert_eq!(names("task 3"), Vec::<String>::new());
+        assert_eq!(names("  "), Vec::<String>::new());
+    }
+
+    #[test]
+    fn matches_description() {
+        let task = Task::new("Pay rent", Some("Landlord wants a bank transfer"));
+        assert!(task_matches(&task, "rent transfer"));
+        assert!(task_matches(&task, "LANDLORD"));
+        assert!(!task_matches(&task, "rent cash"));
+        assert!(!task_matches(&Task::new("Untitled", None), ""));
+    }
+}
@@ -0,0 +1,162 @@
+//! User preferences which apply across the whole app.
+
+use std::path::{Path, PathBuf};
+
+use chrono_tz::Tz;
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::{
+    HelixFlowItem, Validate,
+    actor::Actor,
+    calendar::WorkingCalendar,
+    quickadd::{Locale, QuickAddRules},
+};
+
+/// App-wide settings. Uses builder pattern...
+#[derive(Debug, Default, PartialEq, Clone, Serialize, HelixFlowItem)]
+pub struct Settings {
+    display_timezone: Tz,
+    working_calendar: WorkingCalendar,
+    actor: Actor,
+    quick_add: QuickAddRules,
+    locale: Locale,
+    telemetry_path: Option<PathBuf>,
+    pub id: Uuid,
+}
+
+impl Validate for Settings {}
+
+impl Settings {
+    pub fn new(id: &Uuid) -> Self {
+        Settings {
+            actor: Actor::local(),
+            id: *id,
+            ..Default::default()
+        }
+    }
+
+    /// The time zone used to display dates and decide what is "today". Defaults to UTC.
+    pub fn display_timezone(&mut self, tz: Tz) {
+        self.display_timezone = tz;
+    }
+
+    pub fn timezone(&self) -> Tz {
+        self.display_timezone
+    }
+
+    /// Working days and hours used for scheduling. Defaults to Mon - Fri, 09:00 - 17:00.
+    pub fn working_calendar(&mut self, calendar: WorkingCalendar) {
+        self.working_calendar = calendar;
+    }
+
+    pub fn calendar(&self) -> &WorkingCalendar {
+        &self.working_calendar
+    }
+
+    /// Who changes made from this instance are attributed to. Defaults to the OS user.
+    pub fn actor(&mut self, actor: Actor) {
+        self.actor = actor;
+    }
+
+    pub fn current_actor(&self) -> &Actor {
+        &self.actor
+    }
+
+    /// How quick-add text is split into name, due date, tags & priority. Defaults to British
+    /// English.
+    pub fn quick_add_rules(&mut self, rules: QuickAddRules) {
+        self.quick_add = rules;
+    }
+
+    pub fn quick_add(&self) -> &QuickAddRules {
+        &self.quick_add
+    }
+
+    /// The language used for relative dates & durations, see `crate::relative`. Defaults to
+    /// British English.
+    pub fn display_locale(&mut self, locale: Locale) {
+        self.locale = locale;
+    }
+
+    pub fn locale(&self) -> Locale {
+        self.locale
+    }
+
+    /// The file to write anonymous usage metrics to, see `crate::telemetry`. Defaults to `None`,
+    /// which records nothing. Only used when built with the `telemetry` feature.
+    pub fn share_telemetry<P: Into<PathBuf>>(&mut self, path: Option<P>) {
+        self.telemetry_path = path.map(Into::into);
+    }
+
+    pub fn telemetry_path(&self) -> Option<&Path> {
+        self.telemetry_path.as_deref()
+    }
+}
+
+#[cfg(test)]
+#[coverage(off)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_timezone_is_utc() {
+        let settings = Settings::new(&Uuid::now_v7());
+        assert_eq!(settings.timezone(), Tz::UTC);
+    }
+
+    #[test]
+    fn set_timezone() {
+        let mut settings = Settings::new(&Uuid::now_v7());
+        settings.display_timezone(chrono_tz::Europe::London);
+        assert_eq!(settings.timezone(), chrono_tz::Europe::London);
+    }
+
+    #[test]
+    fn set_calendar() {
+        let mut settings = Settings::new(&Uuid::now_v7());
+        assert_eq!(settings.calendar(), &WorkingCalendar::default());
+        let calendar = WorkingCalendar {
+            working_days: vec![chrono::Weekday::Sat, chrono::Weekday::Sun],
+            ..Default::default()
+        };
+        settings.working_calendar(calendar.clone());
+        assert_eq!(settings.calendar(), &calendar);
+    }
+
+    #[test]
+    fn set_actor() {
+        let mut settings = Settings::new(&Uuid::now_v7());
+        assert!(!settings.current_actor().name.is_empty());
+        let actor = Actor::new("Alice", &Uuid::now_v7());
+        settings.actor(actor.clone());
+        assert_eq!(settings.current_actor(), &actor);
+    }
+
+    #[test]
+    fn set_quick_add_rules() {
+        let mut settings = Settings::new(&Uuid::now_v7());
+        assert_eq!(settings.quick_add(), &QuickAddRules::default());
+        let rules = QuickAddRules::for_locale(Locale::De);
+        settings.quick_add_rules(rules.clone());
+        assert_eq!(settings.quick_add(), &rules);
+    }
+
+    #[test]
+    fn telemetry_is_opt_in() {
+        let mut settings = Settings::new(&Uuid::now_v7());
+        assert_eq!(settings.telemetry_path(), None);
+        settings.share_telemetry(Some("metrics.jsonl"));
+        assert_eq!(settings.telemetry_path(), Some(Path::new("metrics.jsonl")));
+        settings.share_telemetry(None::<PathBuf>);
+        assert_eq!(settings.telemetry_path(), None);
+    }
+
+    #[test]
+    fn set_locale() {
+        let mut settings = Settings::new(&Uuid::now_v7());
+        assert_eq!(settings.locale(), Locale::EnGb);
+        settings.display_locale(Locale::De);
+        assert_eq!(settings.locale(), Locale::De);
+    }
+}
@@ -0,0 +1,357 @@
+//! Read-only sharing of a `TaskList` via a token which can expire or be revoked.
+
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    HelixFlowError, HelixFlowItem, HelixFlowResult, Relate, Store, Validate,
+    task::{Contains, Task, TaskList},
+};
+
+/// Grants read-only access to a single `TaskList` until `expires`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, HelixFlowItem)]
+pub struct ShareToken {
+    pub id: Uuid,
+    pub tasklist: Uuid,
+    pub expires: DateTime<Utc>,
+    pub revoked: bool,
+}
+
+impl Validate for ShareToken {}
+
+impl ShareToken {
+    /// Create a new token for `tasklist`, valid for `valid_for` from now.
+    ///
+    /// The id is the token itself, so it is random rather than time-ordered: anyone who can guess
+    /// it can read the list.
+    pub fn new(tasklist: &TaskList, valid_for: TimeDelta) -> Self {
+        ShareToken {
+            id: Uuid::new_v4(),
+            tasklist: tasklist.id,
+            expires: Utc::now() + valid_for,
+            revoked: false,
+        }
+    }
+
+    pub fn is_valid_at(&self, now: DateTime<Utc>) -> bool {
+        !self.revoked && now < self.expires
+    }
+}
+
+/// Methods to store, look up and revoke `ShareToken`s in a backend
+pub trait Share {
+    /// Store a new token, returning the stored record.
+    fn create_token(&self, token: &ShareToken) -> HelixFlowResult<ShareToken>;
+    fn get_token(&self, id: &Uuid) -> HelixFlowResult<ShareToken>;
+    /// Mark the token as revoked. It remains stored so that lookups can report _why_ it failed.
+    fn revoke_token(&self, id: &Uuid) -> HelixFlowResult<()>;
+}
+
+/// Everything a share recipient gets to see.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct SharedTaskList {
+    pub tasklist: TaskList,
+    pub tasks: Vec<Task>,
+}
+
+impl TaskList {
+    /// Create and store a token sharing this list for `valid_for`.
+    pub fn share<B: Share>(
+        &self,
+        backend: &B,
+        valid_for: TimeDelta,
+    ) -> HelixFlowResult<ShareToken> {
+        let token = ShareToken::new(self, valid_for);
+        let stored = backend.create_token(&token)?;
+        if stored == token {
+            Ok(stored)
+        } else {
+            Err(HelixFlowError::Mismatch {
+                expected: Box::new(token),
+                actual: Box::new(stored),
+            })
+        }
+    }
+}
+
+impl SharedTaskList {
+    /// Look up the list shared by token `id`, if the token is still valid.
+    pub fn resolve<B>(backend: &B, id: &Uuid) -> HelixFlowResult<Self>
+    where
+        B: Share + Store<TaskList> + Relate<Contains<TaskList, Task>>,
+    {
+        let token = backend.get_token(id)?;
+        if !token.is_valid_at(Utc::now()) {
+            return Err(HelixFlowError::ShareNotValid { id: *id });
+        }
+        let tasklist: TaskList = backend.get(&token.tasklist)?;
+        let tasks = tasklist
+            .get_linked_items(backend)?
+            .map(|link| link.right)
+            .collect::<HelixFlowResult<Vec<Task>>>()?;
+        Ok(SharedTaskList { tasklist, tasks })
+    }
+
+    /// A minimal standalone HTML page listing the tasks.
+    pub fn to_html(&self) -> String {
+        let items: String = self
+            .tasks
+            .iter()
+            .map(|task| match &task.description {
+                Some(description) => format!(
+                    "<li>{}<p>{}</p></li>",
+                    escape_html(&task.name),
+                    escape_html(description)
+                ),
+                None => format!("<li>{}</li>", escape_html(&task.name)),
+            })
+            .collect();
+        let name = escape_html(&self.tasklist.name);
+        format!(
+            "<!DOCTYPE html><html><head><meta charset=\"utf-8\"><title>{name}</title></head>\
+            <body><h1>{name}</h1><ul>{items}</ul></body></html>"
+        )
+    }
+}
+
+/// What a server sends back for a request to view a shared list, see `respond`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShareResponse {
+    /// The HTTP status code
+    pub status: u16,
+    /// HTML
+    pub body: String,
+}
+
+impl ShareResponse {
+    pub fn new<S: Into<String>>(status: u16, body: S) -> Self {
+        ShareResponse {
+            status,
+            body: body.into(),
+        }
+    }
+
+    /// The complete HTTP/1.1 response, closing the connection afterwards.
+    pub fn to_http(&self) -> String {
+        let reason = match self.status {
+            200 => "OK",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            410 => "Gone",
+            _ => "Internal Server Error",
+        };
+        format!(
+            "HTTP/1.1 {} {reason}\r\nContent-Type: text/html; charset=utf-8\r\n\
+            Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+            self.status,
+            self.body.len(),
+            self.body
+        )
+    }
+}
+
+/// Answer a `GET /share/<token id>` request with the shared list as HTML.
+///
+/// Tokens which have expired or been revoked get `410 Gone`; unknown tokens & other paths get
+/// `404 Not Found`. Fails if the backend does, leaving the server to decide what to send.
+pub fn respond<B>(backend: &B, method: &str, path: &str) -> HelixFlowResult<ShareResponse>
+where
+    B: Share + Store<TaskList> + Relate<Contains<TaskList, Task>>,
+{
+    if method != "GET" {
+        return Ok(ShareResponse::new(405, "Shared lists are read-only"));
+    }
+    let path = path.split('?').next().unwrap_or_default();
+    let Some(id) = path
+        .strip_prefix("/share/")
+        .and_then(|id| id.parse::<Uuid>().ok())
+    else {
+        return Ok(ShareResponse::new(404, "Not found"));
+    };
+    match SharedTaskList::resolve(backend, &id) {
+        Ok(shared) => Ok(ShareResponse::new(200, shared.to_html())),
+        Err(HelixFlowError::ShareNotValid { .. }) => Ok(ShareResponse::new(
+            410,
+            "This link has expired or been revoked",
+        )),
+        Err(HelixFlowError::NotFound { .. }) => Ok(ShareResponse::new(404, "Not found")),
+        Err(e) => Err(e),
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+#[cfg(test)]
+#[coverage(off)]
+mod tests {
+    use std::{assert_matches::assert_matches, collections::HashMap, sync::Mutex};
+
+    use uuid::uuid;
+
+    use super::*;
+    use crate::task::TestBackend;
+
+    #[derive(Default)]
+    struct ShareBackend {
+        tokens: Mutex<HashMap<Uuid, ShareToken>>,
+    }
+
+    impl Share for ShareBackend {
+        fn create_token(&self, token: &ShareToken) -> HelixFlowResult<ShareToken> {
+            self.tokens.lock().unwrap().insert(token.id, token.clone());
+            Ok(token.clone())
+        }
+        fn get_token(&self, id: &Uuid) -> HelixFlowResult<ShareToken> {
+            self.tokens
+                .lock()
+                .unwrap()
+                .get(id)
+                .cloned()
+                .ok_or(HelixFlowError::NotFound {
+                    itemtype: "ShareToken".into(),
+                    id: *id,
+                })
+        }
+        fn revoke_token(&self, id: &Uuid) -> HelixFlowResult<()> {
+            self.tokens
+                .lock()
+                .unwrap()
+                .get_mut(id)
+                .map(|token| token.revoked = true)
+                .ok_or(HelixFlowError::NotFound {
+                    itemtype: "ShareToken".into(),
+                    id: *id,
+                })
+        }
+    }
+
+    impl Store<TaskList> for ShareBackend {
+        fn create(&self, tasklist: &TaskList) -> HelixFlowResult<TaskList> {
+            TestBackend.create(tasklist)
+        }
+        fn get(&self, id: &Uuid) -> HelixFlowResult<TaskList> {
+            TestBackend.get(id)
+        }
+        fn update(&self, tasklist: &TaskList) -> HelixFlowResult<TaskList> {
+            TestBackend.update(tasklist)
+        }
+        fn delete(&self, id: &Uuid) -> HelixFlowResult<()> {
+            Store::<TaskList>::delete(&TestBackend, id)
+        }
+    }
+
+    impl Relate<Contains<TaskList, Task>> for ShareBackend {
+        fn create_linked_item(
+            &self,
+            link: &Contains<TaskList, Task>,
+        ) -> HelixFlowResult<Contains<TaskList, Task>> {
+            TestBackend.create_linked_item(link)
+        }
+        fn get_linked_items(
+            &self,
+            left: &TaskList,
+        ) -> HelixFlowResult<impl Iterator<Item = Contains<TaskList, Task>>> {
+            Relate::<Contains<TaskList, Task>>::get_linked_items(&TestBackend, left)
+        }
+        fn delete_link(&self, link: &Contains<TaskList, Task>) -> HelixFlowResult<()> {
+            TestBackend.delete_link(link)
+        }
+    }
+
+    fn tasklist() -> TaskList {
+        TaskList {
+            name: "Test TaskList 1".into(),
+            id: uuid!("0196fe23-7c01-7d6b-9e09-5968eb370549"),
+            sort: Default::default(),
+        }
+    }
+
+    #[test]
+    fn share_and_resolve() {
+        let backend = ShareBackend::default();
+        let token = tasklist().share(&backend, TimeDelta::days(1)).unwrap();
+        let shared = SharedTaskList::resolve(&backend, &token.id).unwrap();
+        assert_eq!(shared.tasklist, tasklist());
+        assert_eq!(
+            shared
+                .tasks
+                .iter()
+                .map(|task| task.name.as_ref())
+                .collect::<Vec<_>>(),
+            vec!["Task 1", "Task 2"]
+        );
+    }
+
+    #[test]
+    fn revoked() {
+        let backend = ShareBackend::default();
+        let token = tasklist().share(&backend, TimeDelta::days(1)).unwrap();
+        backend.revoke_token(&token.id).unwrap();
+        let err = SharedTaskList::resolve(&backend, &token.id).unwrap_err();
+        assert_matches!(err, HelixFlowError::ShareNotValid { id } if id == token.id);
+    }
+
+    #[test]
+    fn expired() {
+        let backend = ShareBackend::default();
+        let token = tasklist().share(&backend, TimeDelta::seconds(-1)).unwrap();
+        let err = SharedTaskList::resolve(&backend, &token.id).unwrap_err();
+        assert_matches!(err, HelixFlowError::ShareNotValid { id } if id == token.id);
+    }
+
+    #[test]
+    fn random_token() {
+        let token = ShareToken::new(&tasklist(), TimeDelta::days(1));
+        assert_eq!(token.id.get_version(), Some(uuid::Version::Random));
+    }
+
+    #[test]
+    fn respond_to_requests() {
+        let backend = ShareBackend::default();
+        let token = tasklist().share(&backend, TimeDelta::days(1)).unwrap();
+        let path = format!("/share/{}", token.id);
+
+        let shared = respond(&backend, "GET", &path).unwrap();
+        assert_eq!(shared.status, 200);
+        assert!(shared.body.contains("<li>Task 1</li>"));
+        assert!(
+            shared
+                .to_http()
+                .starts_with("HTTP/1.1 200 OK\r\nContent-Type: text/html")
+        );
+        assert_eq!(respond(&backend, "POST", &path).unwrap().status, 405);
+        assert_eq!(
+            respond(&backend, "GET", "/share/not-a-token")
+                .unwrap()
+                .status,
+            404
+        );
+        assert_eq!(
+            respond(&backend, "GET", &format!("/share/{}", Uuid::new_v4()))
+                .unwrap()
+                .status,
+            404
+        );
+
+        backend.revoke_token(&token.id).unwrap();
+        assert_eq!(respond(&backend, "GET", &path).unwrap().status, 410);
+    }
+
+    #[test]
+    fn html_is_escaped() {
+        let shared = SharedTaskList {
+            tasklist: TaskList::new("Mine & yours"),
+            tasks: vec![Task::new("<b>bold</b>", Some("\"quoted\""))],
+        };
+        let html = shared.to_html();
+        assert!(html.contains("<h1>Mine &amp; yours</h1>"));
+        assert!(html.contains("<li>&lt;b&gt;bold&lt;/b&gt;<p>&quot;quoted&quot;</p></li>"));
+    }
+}
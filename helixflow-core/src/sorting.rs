@@ -0,0 +1,285 @@
+//! Rules a `TaskList` can declare for automatically ordering its tasks.
+
+use std::cmp::Ordering;
+
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    HelixFlowItem, HelixFlowResult, Relate,
+    task::{Contains, Status, Task, TaskList},
+};
+
+/// A single criterion to sort by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SortKey {
+    /// Overdue tasks bubble to the top
+    OverdueFirst,
+    /// Nearest due date first, tasks without a due date last
+    DueDate,
+    /// Alphabetically by name, ignoring case
+    Name,
+    /// Most important (`P0`) first
+    Priority,
+    /// Most recently stored first, tasks which were never stored last
+    RecentlyUpdated,
+    /// Tasks which are `Done` sink to the bottom
+    DoneLast,
+}
+
+/// Sort keys applied in order, each one breaking ties left by the previous. Tasks which are
+/// still tied keep their original (manual) order.
+///
+/// The default, no keys, keeps the manual order.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SortRule(pub Vec<SortKey>);
+
+impl SortKey {
+    fn compare(&self, a: &Task, b: &Task, now: DateTime<Utc>, tz: Tz) -> Ordering {
+        match self {
+            // `true` sorts after `false`
+            SortKey::OverdueFirst => b.is_overdue(now, tz).cmp(&a.is_overdue(now, tz)),
+            SortKey::DueDate => match (a.due, b.due) {
+                (Some(a_due), Some(b_due)) => a_due.cmp(&b_due),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            },
+            SortKey::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+            SortKey::Priority => a.priority.cmp(&b.priority),
+            // `None` sorts before `Some`
+            SortKey::RecentlyUpdated => b.updated_at().cmp(&a.updated_at()),
+            SortKey::DoneLast => (a.status == Status::Done).cmp(&(b.status == Status::Done)),
+        }
+    }
+}
+
+impl SortRule {
+    /// This rule, or `fallback` if it has no keys. E.g. a sort chosen in the UI, falling back to
+    /// the list's own.
+    pub fn or<'r>(&'r self, fallback: &'r SortRule) -> &'r SortRule {
+        if self.0.is_empty() { fallback } else { self }
+    }
+
+    /// Sort `tasks` in place, as seen at `now` by someone in `tz`.
+    pub fn sort(&self, tasks: &mut [Task], now: DateTime<Utc>, tz: Tz) {
+        tasks.sort_by(|a, b| {
+            self.0.iter().fold(Ordering::Equal, |ordering, key| {
+                ordering.then_with(|| key.compare(a, b, now, tz))
+            })
+        });
+    }
+
+    /// As `sort`, but pinned tasks stay above unpinned ones, see
+    /// `Backlogs::backlog_pinned_first`.
+    pub fn sort_pinned_first(&self, tasks: &mut [Task], now: DateTime<Utc>, tz: Tz) {
+        self.sort(tasks, now, tz);
+        tasks.sort_by_key(|task| !task.pinned);
+    }
+}
+
+impl TaskList {
+    /// The tasks in this list, ordered by its `SortRule`.
+    pub fn sorted_tasks<B>(
+        &self,
+        backend: &B,
+        now: DateTime<Utc>,
+        tz: Tz,
+    ) -> HelixFlowResult<Vec<Task>>
+    where
+        B: Relate<Contains<TaskList, Task>>,
+    {
+        let mut tasks = self
+            .get_linked_items(backend)?
+            .map(|link| link.right)
+            .collect::<HelixFlowResult<Vec<Task>>>()?;
+        self.sort.sort(&mut tasks, now, tz);
+        Ok(tasks)
+    }
+}
+
+#[cfg(test)]
+#[coverage(off)]
+mod tests {
+    use chrono::TimeZone;
+    use uuid::uuid;
+
+    use super::*;
+    use crate::task::{Priority, TestBackend};
+
+    fn utc(day: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 6, day, 12, 0, 0).unwrap()
+    }
+
+    fn names(tasks: &[Task]) -> Vec<&str> {
+        tasks.iter().map(|task| task.name.as_ref()).collect()
+    }
+
+    fn tasks() -> Vec<Task> {
+        vec![
+            Task::new("b: no due date", None),
+            Task {
+                due: Some(utc(10)),
+                priority: Priority::P0,
+                ..Task::new("C: due later", None)
+            },
+            Task {
+                due: Some(utc(1)),
+                ..Task::new("a: overdue", None)
+            },
+            Task {
+                due: Some(utc(5)),
+                ..Task::new("d: due soon", None)
+            },
+        ]
+    }
+
+    #[test]
+    fn manual_order() {
+        let mut tasks = tasks();
+        SortRule::default().sort(&mut tasks, utc(3), Tz::UTC);
+        assert_eq!(
+            names(&tasks),
+            vec![
+                "b: no due date",
+                "C: due later",
+                "a: overdue",
+                "d: due soon"
+            ]
+        );
+    }
+
+    #[test]
+    fn overdue_first_keeps_manual_order() {
+        let mut tasks = tasks();
+        SortRule(vec![SortKey::OverdueFirst]).sort(&mut tasks, utc(3), Tz::UTC);
+        assert_eq!(
+            names(&tasks),
+            vec![
+                "a: overdue",
+                "b: no due date",
+                "C: due later",
+                "d: due soon"
+            ]
+        );
+    }
+
+    #[test]
+    fn by_due_date() {
+        let mut tasks = tasks();
+        SortRule(vec![SortKey::DueDate]).sort(&mut tasks, utc(3), Tz::UTC);
+        assert_eq!(
+            names(&tasks),
+            vec![
+                "a: overdue",
+                "d: due soon",
+                "C: due later",
+                "b: no due date"
+            ]
+        );
+    }
+
+    #[test]
+    fn by_name() {
+        let mut tasks = tasks();
+        SortRule(vec![SortKey::Name]).sort(&mut tasks, utc(3), Tz::UTC);
+        assert_eq!(
+            names(&tasks),
+            vec![
+                "a: overdue",
+                "b: no due date",
+                "C: due later",
+                "d: due soon"
+            ]
+        );
+    }
+
+    #[test]
+    fn by_recently_updated() {
+        let mut tasks = tasks();
+        tasks[0] = tasks[0].stamped(utc(2));
+        tasks[2] = tasks[2].stamped(utc(4));
+        SortRule(vec![SortKey::RecentlyUpdated]).sort(&mut tasks, utc(3), Tz::UTC);
+        assert_eq!(
+            names(&tasks),
+            vec![
+                "a: overdue",
+                "b: no due date",
+                "C: due later",
+                "d: due soon"
+            ]
+        );
+    }
+
+    #[test]
+    fn by_priority() {
+        let mut tasks = tasks();
+        tasks[3].priority = Priority::P3;
+        SortRule(vec![SortKey::Priority, SortKey::Name]).sort(&mut tasks, utc(3), Tz::UTC);
+        assert_eq!(
+            names(&tasks),
+            vec![
+                "C: due later",
+                "a: overdue",
+                "b: no due date",
+                "d: due soon"
+            ]
+        );
+    }
+
+    #[test]
+    fn done_last() {
+        let mut tasks = tasks();
+        tasks[0].status = Status::Done;
+        tasks[2].status = Status::Done;
+        SortRule(vec![SortKey::DoneLast]).sort(&mut tasks, utc(3), Tz::UTC);
+        assert_eq!(
+            names(&tasks),
+            vec![
+                "C: due later",
+                "d: due soon",
+                "b: no due date",
+                "a: overdue"
+            ]
+        );
+    }
+
+    #[test]
+    fn pinned_stay_first() {
+        let mut tasks = tasks();
+        tasks[3].pinned = true;
+        tasks[1].pinned = true;
+        SortRule(vec![SortKey::Name]).sort_pinned_first(&mut tasks, utc(3), Tz::UTC);
+        assert_eq!(
+            names(&tasks),
+            vec![
+                "C: due later",
+                "d: due soon",
+                "a: overdue",
+                "b: no due date"
+            ]
+        );
+    }
+
+    #[test]
+    fn fall_back_to_list_sort() {
+        let by_name = SortRule(vec![SortKey::Name]);
+        let by_priority = SortRule(vec![SortKey::Priority]);
+        assert_eq!(SortRule::default().or(&by_name), &by_name);
+        assert_eq!(by_priority.or(&by_name), &by_priority);
+    }
+
+    #[test]
+    fn sorted_tasks_in_list() {
+        let tasklist = TaskList {
+            name: "Test TaskList 1".into(),
+            id: uuid!("0196fe23-7c01-7d6b-9e09-5968eb370549"),
+            sort: SortRule(vec![SortKey::Name]),
+        };
+        let tasks = tasklist
+            .sorted_tasks(&TestBackend, utc(3), Tz::UTC)
+            .unwrap();
+        assert_eq!(names(&tasks), vec!["Task 1", "Task 2"]);
+    }
+}
@@ -1,19 +1,131 @@
-use std::any::Any;
+use std::collections::BTreeMap;
 
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::{HelixFlowItem, task::TaskList};
+use crate::{
+    CRUD, HelixFlowError, HelixFlowItem, HelixFlowResult, Relate, Store, Validate,
+    command::Command,
+    project::Project,
+    sorting::SortRule,
+    table::TableLayout,
+    task::{Contains, Status, Task, TaskList},
+    workspace::{DEFAULT_NAMESPACE, Namespaces, Workspace},
+};
+
+/// How many commands can be undone.
+pub const UNDO_LIMIT: usize = 100;
+
+/// How tasks are laid out in the main window.
+#[derive(Debug, Default, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Serialize, Deserialize)]
+pub enum ViewMode {
+    #[default]
+    List,
+    Board,
+    Table,
+}
+
+/// Which details are shown on each task's card.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub struct CardSettings {
+    pub show_description: bool,
+    pub show_due_date: bool,
+    pub show_tags: bool,
+}
+
+impl Default for CardSettings {
+    fn default() -> Self {
+        CardSettings {
+            show_description: true,
+            show_due_date: true,
+            show_tags: true,
+        }
+    }
+}
+
+/// Where the main window was on screen & how big it was, in physical pixels.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub struct WindowGeometry {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Which tasks the backlog shows. The default shows every task still to do.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct BacklogFilter {
+    /// Only tasks with any of these statuses, or any status but `Done` if empty
+    #[serde(default)]
+    pub statuses: Vec<Status>,
+    /// Only tasks whose name contains this text, ignoring case
+    #[serde(default)]
+    pub text: Option<String>,
+}
+
+impl BacklogFilter {
+    /// Does the backlog show `task`?
+    pub fn matches(&self, task: &Task) -> bool {
+        let status = if self.statuses.is_empty() {
+            task.status != Status::Done
+        } else {
+            self.statuses.contains(&task.status)
+        };
+        status
+            && self
+                .text
+                .as_ref()
+                .is_none_or(|text| task.name.to_lowercase().contains(&text.to_lowercase()))
+    }
+
+    /// Are any completed tasks shown?
+    pub fn shows_done(&self) -> bool {
+        self.statuses.contains(&Status::Done)
+    }
+}
 
 /// The UI State. Uses builder pattern...
-#[derive(Debug, Default, PartialEq, Clone)]
+#[derive(Debug, Default, Clone, Serialize, HelixFlowItem)]
 pub struct State {
+    visible_project: Option<Uuid>,
     visible_backlog: Option<Uuid>,
+    view_mode: ViewMode,
+    task_details: Option<Uuid>,
+    cards: BTreeMap<ViewMode, CardSettings>,
+    tables: BTreeMap<Uuid, TableLayout>,
+    window: Option<WindowGeometry>,
+    selected_task: Option<Uuid>,
+    sort: SortRule,
+    filter: BacklogFilter,
+    workspaces: Vec<Workspace>,
+    /// Namespace of the open workspace, `None` for the default
+    workspace: Option<String>,
+    /// Only kept while the app is running, the tasks may be changed elsewhere before next time.
+    #[serde(skip)]
+    undo: Vec<Command>,
+    #[serde(skip)]
+    redo: Vec<Command>,
     pub id: Uuid,
 }
 
-impl HelixFlowItem for State {
-    fn as_any(&self) -> &dyn Any {
-        self
+impl Validate for State {}
+
+/// Compares what is saved, so the undo & redo stacks don't stop a stored `State` from matching.
+impl PartialEq for State {
+    fn eq(&self, other: &Self) -> bool {
+        self.visible_project == other.visible_project
+            && self.visible_backlog == other.visible_backlog
+            && self.view_mode == other.view_mode
+            && self.task_details == other.task_details
+            && self.cards == other.cards
+            && self.tables == other.tables
+            && self.window == other.window
+            && self.selected_task == other.selected_task
+            && self.sort == other.sort
+            && self.filter == other.filter
+            && self.workspaces == other.workspaces
+            && self.workspace == other.workspace
+            && self.id == other.id
     }
 }
 
@@ -25,6 +137,14 @@ impl State {
         }
     }
 
+    pub fn visible_project(&mut self, project: &Project) {
+        self.visible_project = Some(project.id);
+    }
+
+    pub fn visible_project_id(&self) -> &Option<Uuid> {
+        &self.visible_project
+    }
+
     pub fn visible_backlog(&mut self, backlog: &TaskList) {
         self.visible_backlog = Some(backlog.id);
     }
@@ -32,4 +152,419 @@ impl State {
     pub fn visible_backlog_id(&self) -> &Option<Uuid> {
         &self.visible_backlog
     }
+
+    pub fn view_mode(&mut self, mode: ViewMode) {
+        self.view_mode = mode;
+    }
+
+    pub fn current_view_mode(&self) -> ViewMode {
+        self.view_mode
+    }
+
+    /// Remember that the details panel is open for `task`.
+    pub fn task_details(&mut self, task: &Task) {
+        self.task_details = Some(task.id);
+    }
+
+    pub fn close_task_details(&mut self) {
+        self.task_details = None;
+    }
+
+    pub fn task_details_id(&self) -> &Option<Uuid> {
+        &self.task_details
+    }
+
+    pub fn card_settings(&mut self, view: ViewMode, settings: CardSettings) {
+        self.cards.insert(view, settings);
+    }
+
+    /// The card settings for `view`, or the defaults if none have been chosen.
+    pub fn card_settings_for(&self, view: ViewMode) -> CardSettings {
+        self.cards.get(&view).copied().unwrap_or_default()
+    }
+
+    /// All card settings which differ from the defaults
+    pub fn all_card_settings(&self) -> &BTreeMap<ViewMode, CardSettings> {
+        &self.cards
+    }
+
+    /// Remember the columns & sorting chosen when showing `tasklist` as a table.
+    pub fn table_layout(&mut self, tasklist: &TaskList, layout: TableLayout) {
+        self.tables.insert(tasklist.id, layout);
+    }
+
+    /// The table layout for `tasklist`, or the default if none has been chosen.
+    pub fn table_layout_for(&self, tasklist: &TaskList) -> TableLayout {
+        self.tables.get(&tasklist.id).cloned().unwrap_or_default()
+    }
+
+    /// All table layouts which have been chosen, by `TaskList` id
+    pub fn all_table_layouts(&self) -> &BTreeMap<Uuid, TableLayout> {
+        &self.tables
+    }
+
+    /// Remember where the main window is & how big it is.
+    pub fn window(&mut self, geometry: WindowGeometry) {
+        self.window = Some(geometry);
+    }
+
+    /// Where the main window was last, `None` to let the OS decide.
+    pub fn window_geometry(&self) -> Option<WindowGeometry> {
+        self.window
+    }
+
+    /// Remember that `task` was the one last selected.
+    pub fn select_task(&mut self, task: &Task) {
+        self.selected_task = Some(task.id);
+    }
+
+    pub fn selected_task_id(&self) -> &Option<Uuid> {
+        &self.selected_task
+    }
+
+    /// Forget the selected task, e.g. once its details are closed.
+    pub fn deselect_task(&mut self) {
+        self.selected_task = None;
+    }
+
+    /// Order the backlog by `rule` rather than its own `SortRule`. An empty rule keeps the
+    /// list's order.
+    pub fn sort_mode(&mut self, rule: SortRule) {
+        self.sort = rule;
+    }
+
+    pub fn current_sort_mode(&self) -> &SortRule {
+        &self.sort
+    }
+
+    /// Only show the tasks matching `filter` in the backlog.
+    pub fn filter(&mut self, filter: BacklogFilter) {
+        self.filter = filter;
+    }
+
+    pub fn current_filter(&self) -> &BacklogFilter {
+        &self.filter
+    }
+
+    /// Apply `command` and remember it, so it can be undone. Anything undone before can no
+    /// longer be redone.
+    pub fn execute<B>(&mut self, command: Command, backend: &B) -> HelixFlowResult<()>
+    where
+        B: Store<Task> + Relate<Contains<TaskList, Task>>,
+    {
+        command.apply(backend)?;
+        if self.undo.len() == UNDO_LIMIT {
+            self.undo.remove(0);
+        }
+        self.undo.push(command);
+        self.redo.clear();
+        Ok(())
+    }
+
+    /// Revert the last command executed, returning it. `None` if there is nothing to undo.
+    ///
+    /// The command is kept if reverting fails, so it can be tried again.
+    pub fn undo<B>(&mut self, backend: &B) -> HelixFlowResult<Option<Command>>
+    where
+        B: Store<Task> + Relate<Contains<TaskList, Task>>,
+    {
+        let Some(command) = self.undo.pop() else {
+            return Ok(None);
+        };
+        if let Err(e) = command.revert(backend) {
+            self.undo.push(command);
+            return Err(e);
+        }
+        self.redo.push(command.clone());
+        Ok(Some(command))
+    }
+
+    /// Apply the last command undone again, returning it. `None` if there is nothing to redo.
+    pub fn redo<B>(&mut self, backend: &B) -> HelixFlowResult<Option<Command>>
+    where
+        B: Store<Task> + Relate<Contains<TaskList, Task>>,
+    {
+        let Some(command) = self.redo.pop() else {
+            return Ok(None);
+        };
+        if let Err(e) = command.apply(backend) {
+            self.redo.push(command);
+            return Err(e);
+        }
+        self.undo.push(command.clone());
+        Ok(Some(command))
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+}
+
+impl State {
+    /// All workspaces, starting with the default one.
+    pub fn workspaces(&self) -> Vec<Workspace> {
+        let mut workspaces = self.workspaces.clone();
+        if !workspaces
+            .iter()
+            .any(|workspace| workspace.namespace == DEFAULT_NAMESPACE)
+        {
+            workspaces.insert(0, Workspace::default());
+        }
+        workspaces
+    }
+
+    /// Offer `workspace` alongside the others. Fails if its namespace is empty or already used.
+    pub fn add_workspace(&mut self, workspace: Workspace) -> HelixFlowResult<()> {
+        if workspace.namespace.is_empty()
+            || self
+                .workspaces()
+                .iter()
+                .any(|existing| existing.namespace == workspace.namespace)
+        {
+            return Err(HelixFlowError::InvalidValue {
+                field: "workspace".into(),
+                value: workspace.name,
+            });
+        }
+        self.workspaces.push(workspace);
+        Ok(())
+    }
+
+    /// Remember where `workspace` was left, adding it if it is new.
+    pub fn remember_workspace(&mut self, workspace: Workspace) {
+        match self
+            .workspaces
+            .iter_mut()
+            .find(|existing| existing.namespace == workspace.namespace)
+        {
+            Some(existing) => *existing = workspace,
+            None => self.workspaces.push(workspace),
+        }
+    }
+
+    /// Mark the workspace in `namespace` as open, without changing which namespace the backend
+    /// uses. See `switch_workspace` to change workspace.
+    pub fn workspace(&mut self, namespace: &str) {
+        self.workspace = Some(namespace.into()).filter(|ns| ns != DEFAULT_NAMESPACE);
+    }
+
+    /// The workspace which is open.
+    pub fn current_workspace(&self) -> Workspace {
+        let namespace = self.workspace.as_deref().unwrap_or(DEFAULT_NAMESPACE);
+        let workspace = self
+            .workspaces()
+            .into_iter()
+            .find(|workspace| workspace.namespace == namespace)
+            .unwrap_or_default();
+        Workspace {
+            visible_project: self.visible_project,
+            visible_backlog: self.visible_backlog,
+            ..workspace
+        }
+    }
+
+    /// Open the workspace stored in `namespace`, showing the project & backlog shown when it
+    /// was last open.
+    ///
+    /// `backend` is left using `namespace`. The state is saved in both the default namespace,
+    /// to find the workspace again next time, and in `namespace`. Anything which refers to
+    /// items in the previous workspace, such as the undo history, is forgotten.
+    pub fn switch_workspace<B>(&mut self, namespace: &str, backend: &B) -> HelixFlowResult<()>
+    where
+        B: Namespaces + Store<State>,
+    {
+        let Some(target) = self
+            .workspaces()
+            .into_iter()
+            .find(|workspace| workspace.namespace == namespace)
+        else {
+            return Err(HelixFlowError::InvalidValue {
+                field: "workspace".into(),
+                value: namespace.into(),
+            });
+        };
+        self.remember_workspace(self.current_workspace());
+        self.workspace(&target.namespace);
+        self.visible_project = target.visible_project;
+        self.visible_backlog = target.visible_backlog;
+        self.task_details = None;
+        self.selected_task = None;
+        self.undo.clear();
+        self.redo.clear();
+
+        backend.use_namespace(DEFAULT_NAMESPACE)?;
+        self.put(backend)?;
+        backend.use_namespace(&target.namespace)?;
+        self.put(backend)
+    }
+}
+
+#[cfg(test)]
+#[coverage(off)]
+mod tests {
+    use uuid::uuid;
+
+    use super::*;
+    use crate::task::TestBackend;
+
+    #[test]
+    fn new_state_is_empty() {
+        let state = State::new(&Uuid::now_v7());
+        assert_eq!(state.visible_project_id(), &None);
+        assert_eq!(state.visible_backlog_id(), &None);
+        assert_eq!(state.current_view_mode(), ViewMode::List);
+        assert_eq!(state.task_details_id(), &None);
+    }
+
+    #[test]
+    fn task_details() {
+        let mut state = State::new(&Uuid::now_v7());
+        let task = Task::new("Task 1", None);
+        state.task_details(&task);
+        assert_eq!(state.task_details_id(), &Some(task.id));
+        state.close_task_details();
+        assert_eq!(state.task_details_id(), &None);
+    }
+
+    #[test]
+    fn table_layout_per_list() {
+        let mut state = State::new(&Uuid::now_v7());
+        let backlog = TaskList::new("Backlog");
+        let layout = TableLayout {
+            columns: vec![crate::table::Column::Name, crate::table::Column::Tags],
+            ..Default::default()
+        };
+        state.table_layout(&backlog, layout.clone());
+        assert_eq!(state.table_layout_for(&backlog), layout);
+        assert_eq!(
+            state.table_layout_for(&TaskList::new("Other")),
+            TableLayout::default()
+        );
+    }
+
+    #[test]
+    fn card_settings_per_view() {
+        let mut state = State::new(&Uuid::now_v7());
+        let compact = CardSettings {
+            show_description: false,
+            ..Default::default()
+        };
+        state.card_settings(ViewMode::Board, compact);
+        assert_eq!(state.card_settings_for(ViewMode::Board), compact);
+        assert_eq!(
+            state.card_settings_for(ViewMode::List),
+            CardSettings::default()
+        );
+    }
+
+    #[test]
+    fn undo_redo() {
+        let mut state = State::new(&Uuid::now_v7());
+        let saved = state.clone();
+        assert_eq!(state.undo(&TestBackend).unwrap(), None);
+        let task: Task = TestBackend
+            .get(&uuid!("0196b4c9-8447-7959-ae1f-72c7c8a3dd36"))
+            .unwrap();
+        let create = Command::CreateTask {
+            task: task.clone(),
+            tasklist: TestBackend
+                .get(&uuid!("0196fe23-7c01-7d6b-9e09-5968eb370549"))
+                .unwrap(),
+        };
+        state.execute(create.clone(), &TestBackend).unwrap();
+        state
+            .execute(Command::complete(&task), &TestBackend)
+            .unwrap();
+        assert!(state.can_undo());
+        assert!(!state.can_redo());
+        assert_eq!(state, saved);
+
+        assert_eq!(
+            state.undo(&TestBackend).unwrap(),
+            Some(Command::complete(&task))
+        );
+        assert!(state.can_redo());
+        assert_eq!(
+            state.redo(&TestBackend).unwrap(),
+            Some(Command::complete(&task))
+        );
+        assert_eq!(state.redo(&TestBackend).unwrap(), None);
+
+        state.undo(&TestBackend).unwrap();
+        state.undo(&TestBackend).unwrap();
+        assert!(!state.can_undo());
+        // A new command replaces anything which was undone
+        state.execute(create, &TestBackend).unwrap();
+        assert!(!state.can_redo());
+    }
+
+    #[test]
+    fn undo_limit() {
+        let mut state = State::new(&Uuid::now_v7());
+        let task: Task = TestBackend
+            .get(&uuid!("0196b4c9-8447-7959-ae1f-72c7c8a3dd36"))
+            .unwrap();
+        for n in 0..=UNDO_LIMIT {
+            let rename = Command::Rename {
+                task: task.clone(),
+                name: format!("Task {n}").into(),
+            };
+            state.execute(rename, &TestBackend).unwrap();
+        }
+        for _ in 0..UNDO_LIMIT {
+            assert!(state.undo(&TestBackend).unwrap().is_some());
+        }
+        assert_eq!(state.undo(&TestBackend).unwrap(), None);
+    }
+
+    #[test]
+    fn window_and_selection() {
+        let mut state = State::new(&Uuid::now_v7());
+        assert_eq!(state.window_geometry(), None);
+        let geometry = WindowGeometry {
+            x: 10,
+            y: 20,
+            width: 800,
+            height: 600,
+        };
+        state.window(geometry);
+        assert_eq!(state.window_geometry(), Some(geometry));
+        let task = Task::new("Task 1", None);
+        state.select_task(&task);
+        assert_eq!(state.selected_task_id(), &Some(task.id));
+        state.deselect_task();
+        assert_eq!(state.selected_task_id(), &None);
+    }
+
+    #[test]
+    fn backlog_filter() {
+        let mut state = State::new(&Uuid::now_v7());
+        let mut done = Task::new("Buy milk", None);
+        done.transition(Status::Done).unwrap();
+        let open = Task::new("Walk the dog", None);
+        assert!(state.current_filter().matches(&open));
+        assert!(!state.current_filter().matches(&done));
+
+        state.filter(BacklogFilter {
+            statuses: vec![Status::Todo, Status::Done],
+            text: Some("MILK".into()),
+        });
+        assert!(state.current_filter().shows_done());
+        assert!(state.current_filter().matches(&done));
+        assert!(!state.current_filter().matches(&open));
+
+        state.sort_mode(SortRule(vec![crate::sorting::SortKey::Name]));
+        assert_eq!(state.current_sort_mode().0.len(), 1);
+    }
+
+    #[test]
+    fn view_mode() {
+        let mut state = State::new(&Uuid::now_v7());
+        state.view_mode(ViewMode::Board);
+        assert_eq!(state.current_view_mode(), ViewMode::Board);
+    }
 }
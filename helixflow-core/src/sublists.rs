@@ -0,0 +1,135 @@
+//! Tasklists nested in other tasklists via `Contains<TaskList, TaskList>`, like folders, e.g.
+//! Area → Project → Week.
+
+use std::collections::HashSet;
+
+use chrono::Utc;
+use uuid::{Uuid, uuid};
+
+use crate::{
+    HelixFlowResult, Linkable, Relate, Store,
+    task::{Contains, TaskList, TestBackend},
+};
+
+/// A tasklist and all the lists nested in it, recursively.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ListTree {
+    pub tasklist: TaskList,
+    pub children: Vec<ListTree>,
+}
+
+impl TaskList {
+    /// Nest `child` in this list, ready for `create_linked_item`.
+    pub fn sublist(&self, child: &TaskList) -> Contains<TaskList, TaskList> {
+        Linkable::<Contains<TaskList, TaskList>>::link(self, child)
+    }
+
+    /// The lists directly nested in this list.
+    pub fn sublists<B>(&self, backend: &B) -> HelixFlowResult<Vec<TaskList>>
+    where
+        B: Relate<Contains<TaskList, TaskList>>,
+    {
+        Linkable::<Contains<TaskList, TaskList>>::get_linked_items(self, backend)?
+            .map(|contains| contains.right)
+            .collect()
+    }
+
+    /// Fetch this list with all the lists nested in it. A list which (wrongly) contains one of
+    /// its own ancestors is only included once, to avoid looping forever.
+    pub fn tree<B>(&self, backend: &B) -> HelixFlowResult<ListTree>
+    where
+        B: Relate<Contains<TaskList, TaskList>>,
+    {
+        self.tree_excluding(backend, &mut HashSet::new())
+    }
+
+    fn tree_excluding<B>(&self, backend: &B, seen: &mut HashSet<Uuid>) -> HelixFlowResult<ListTree>
+    where
+        B: Relate<Contains<TaskList, TaskList>>,
+    {
+        seen.insert(self.id);
+        let mut children = Vec::new();
+        for child in self.sublists(backend)? {
+            if !seen.contains(&child.id) {
+                children.push(child.tree_excluding(backend, seen)?);
+            }
+        }
+        Ok(ListTree {
+            tasklist: self.clone(),
+            children,
+        })
+    }
+}
+
+impl ListTree {
+    /// Every list in the tree, parents before their children.
+    pub fn tasklists(&self) -> Vec<&TaskList> {
+        let mut tasklists = vec![&self.tasklist];
+        for child in &self.children {
+            tasklists.extend(child.tasklists());
+        }
+        tasklists
+    }
+}
+
+/// `Test TaskList 1` contains `Test TaskList 2`, which is not stored itself.
+impl Relate<Contains<TaskList, TaskList>> for TestBackend {
+    fn create_linked_item(
+        &self,
+        link: &Contains<TaskList, TaskList>,
+    ) -> HelixFlowResult<Contains<TaskList, TaskList>> {
+        let parent: TaskList = self.get(&link.left.as_ref().unwrap().id)?;
+        Ok(Contains {
+            left: Ok(parent),
+            sortorder: link.sortorder.clone(),
+            created_at: Some(Utc::now()),
+            created_by: link.created_by.clone(),
+            right: Ok(link.right.as_ref().unwrap().clone()),
+        })
+    }
+    fn get_linked_items(
+        &self,
+        left: &TaskList,
+    ) -> HelixFlowResult<impl Iterator<Item = Contains<TaskList, TaskList>>> {
+        let sublists = match left.id.to_string().as_str() {
+            "0196fe23-7c01-7d6b-9e09-5968eb370549" => vec![TaskList {
+                id: uuid!("0199a3f0-6a1b-7c2d-8e3f-4a5b6c7d8e9f"),
+                ..TaskList::new("Test TaskList 2")
+            }],
+            _ => vec![],
+        };
+        let left = left.clone();
+        Ok(sublists
+            .into_iter()
+            .map(move |sublist| left.sublist(&sublist)))
+    }
+    fn delete_link(&self, _link: &Contains<TaskList, TaskList>) -> HelixFlowResult<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[coverage(off)]
+mod tests {
+    use super::*;
+    use crate::{CRUD, Link};
+
+    #[test]
+    fn tree_from_backend() {
+        let tasklist =
+            TaskList::get(&TestBackend, &uuid!("0196fe23-7c01-7d6b-9e09-5968eb370549")).unwrap();
+        let tree = tasklist.tree(&TestBackend).unwrap();
+        assert_eq!(
+            tree.tasklists()
+                .iter()
+                .map(|tasklist| tasklist.name.as_ref())
+                .collect::<Vec<_>>(),
+            vec!["Test TaskList 1", "Test TaskList 2"]
+        );
+        let week = TaskList::new("This week");
+        tasklist
+            .sublist(&week)
+            .create_linked_item(&TestBackend)
+            .unwrap();
+    }
+}
@@ -0,0 +1,174 @@
+//! Tasks broken down into subtasks via `Contains<Task, Task>`, and how far along they are.
+
+use std::collections::HashSet;
+
+use uuid::Uuid;
+
+use crate::{
+    HelixFlowResult, Linkable, Relate,
+    task::{Contains, Status, Task},
+};
+
+/// A task and all of its subtasks, recursively.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Subtree {
+    pub task: Task,
+    pub children: Vec<Subtree>,
+}
+
+/// How many of the tasks in a subtree are done.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Completion {
+    pub done: usize,
+    pub total: usize,
+}
+
+impl Completion {
+    /// `0.0` - `1.0`
+    pub fn fraction(&self) -> f64 {
+        if self.total == 0 {
+            0.0
+        } else {
+            self.done as f64 / self.total as f64
+        }
+    }
+}
+
+impl Task {
+    /// Link `child` as a subtask of this task, ready for `create_linked_item`.
+    pub fn subtask(&self, child: &Task) -> Contains<Task, Task> {
+        Linkable::<Contains<Task, Task>>::link(self, child)
+    }
+
+    /// The direct subtasks of this task.
+    pub fn subtasks<B>(&self, backend: &B) -> HelixFlowResult<Vec<Task>>
+    where
+        B: Relate<Contains<Task, Task>>,
+    {
+        Linkable::<Contains<Task, Task>>::get_linked_items(self, backend)?
+            .map(|contains| contains.right)
+            .collect()
+    }
+
+    /// Fetch this task with all its subtasks. A task which (wrongly) contains one of its own
+    /// ancestors is only included once, to avoid looping forever.
+    pub fn subtree<B>(&self, backend: &B) -> HelixFlowResult<Subtree>
+    where
+        B: Relate<Contains<Task, Task>>,
+    {
+        self.subtree_excluding(backend, &mut HashSet::new())
+    }
+
+    fn subtree_excluding<B>(
+        &self,
+        backend: &B,
+        seen: &mut HashSet<Uuid>,
+    ) -> HelixFlowResult<Subtree>
+    where
+        B: Relate<Contains<Task, Task>>,
+    {
+        seen.insert(self.id);
+        let mut children = Vec::new();
+        for child in self.subtasks(backend)? {
+            if !seen.contains(&child.id) {
+                children.push(child.subtree_excluding(backend, seen)?);
+            }
+        }
+        Ok(Subtree {
+            task: self.clone(),
+            children,
+        })
+    }
+}
+
+impl Subtree {
+    /// Rolled up from the leaves: a task with subtasks is as complete as they are, regardless of
+    /// its own status. A task without subtasks is either done or not.
+    pub fn completion(&self) -> Completion {
+        if self.children.is_empty() {
+            Completion {
+                done: (self.task.status == Status::Done) as usize,
+                total: 1,
+            }
+        } else {
+            self.children.iter().map(Subtree::completion).fold(
+                Completion::default(),
+                |sum, child| Completion {
+                    done: sum.done + child.done,
+                    total: sum.total + child.total,
+                },
+            )
+        }
+    }
+
+    /// Every task in the subtree, parents before their children.
+    pub fn tasks(&self) -> Vec<&Task> {
+        let mut tasks = vec![&self.task];
+        for child in &self.children {
+            tasks.extend(child.tasks());
+        }
+        tasks
+    }
+}
+
+#[cfg(test)]
+#[coverage(off)]
+mod tests {
+    use uuid::uuid;
+
+    use super::*;
+    use crate::{CRUD, task::TestBackend};
+
+    fn leaf(name: &'static str, status: Status) -> Subtree {
+        Subtree {
+            task: Task {
+                status,
+                ..Task::new(name, None)
+            },
+            children: vec![],
+        }
+    }
+
+    #[test]
+    fn subtree_from_backend() {
+        let task_1 =
+            Task::get(&TestBackend, &uuid!("0196b4c9-8447-7959-ae1f-72c7c8a3dd36")).unwrap();
+        let subtree = task_1.subtree(&TestBackend).unwrap();
+        assert_eq!(
+            subtree
+                .tasks()
+                .iter()
+                .map(|task| task.name.as_ref())
+                .collect::<Vec<_>>(),
+            vec!["Task 1", "Task 2"]
+        );
+        assert_eq!(subtree.completion(), Completion { done: 0, total: 1 });
+    }
+
+    #[test]
+    fn rollup() {
+        let tree = Subtree {
+            task: Task::new("Move house", None),
+            children: vec![
+                leaf("Book van", Status::Done),
+                Subtree {
+                    children: vec![
+                        leaf("Kitchen", Status::Done),
+                        leaf("Bedroom", Status::InProgress),
+                        leaf("Garage", Status::Todo),
+                    ],
+                    ..leaf("Pack", Status::Done)
+                },
+            ],
+        };
+        let completion = tree.completion();
+        assert_eq!(completion, Completion { done: 2, total: 4 });
+        assert_eq!(completion.fraction(), 0.5);
+    }
+
+    #[test]
+    fn single_task() {
+        assert_eq!(leaf("Done", Status::Done).completion().fraction(), 1.0);
+        assert_eq!(leaf("Todo", Status::Todo).completion().fraction(), 0.0);
+    }
+}
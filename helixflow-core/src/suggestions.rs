@@ -0,0 +1,140 @@
+//! Suggest what to work on next.
+
+use std::cmp::Ordering;
+
+use crate::{
+    HelixFlowResult, Linkable, Relate,
+    dependency::DependsOn,
+    task::{Contains, Status, Task, TaskList},
+};
+
+/// Ordering for suggestions: highest priority first, then nearest due date, tasks without a due
+/// date last, then oldest first.
+fn suggestion_order(a: &Task, b: &Task) -> Ordering {
+    a.priority
+        .cmp(&b.priority)
+        .then_with(|| match (a.due, b.due) {
+            (Some(a_due), Some(b_due)) => a_due.cmp(&b_due),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        })
+        // UUIDv7 sorts by creation time
+        .then_with(|| a.id.cmp(&b.id))
+}
+
+/// The (up to) `limit` tasks from `tasks` which should be worked on next. Tasks which are done or
+/// marked as blocked are never suggested.
+pub fn next_actions<I>(tasks: I, limit: usize) -> Vec<Task>
+where
+    I: IntoIterator<Item = Task>,
+{
+    let mut tasks: Vec<Task> = tasks
+        .into_iter()
+        .filter(|task| !matches!(task.status, Status::Done | Status::Blocked))
+        .collect();
+    tasks.sort_by(suggestion_order);
+    tasks.truncate(limit);
+    tasks
+}
+
+impl TaskList {
+    /// The (up to) `limit` tasks in this list which should be worked on next, leaving out any
+    /// still waiting for their dependencies to be done, see `Task::is_blocked`.
+    pub fn next_actions<B>(&self, backend: &B, limit: usize) -> HelixFlowResult<Vec<Task>>
+    where
+        B: Relate<Contains<TaskList, Task>> + Relate<DependsOn<Task, Task>>,
+    {
+        let mut unblocked = Vec::new();
+        for link in Linkable::<Contains<TaskList, Task>>::get_linked_items(self, backend)? {
+            let task = link.right?;
+            if !task.is_blocked(backend)? {
+                unblocked.push(task);
+            }
+        }
+        Ok(next_actions(unblocked, limit))
+    }
+}
+
+#[cfg(test)]
+#[coverage(off)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+    use uuid::uuid;
+
+    use super::*;
+    use crate::task::{Priority, TestBackend};
+
+    #[test]
+    fn nearest_due_first() {
+        let later = Task {
+            due: Some(Utc.with_ymd_and_hms(2025, 6, 2, 12, 0, 0).unwrap()),
+            ..Task::new("Later", None)
+        };
+        let sooner = Task {
+            due: Some(Utc.with_ymd_and_hms(2025, 6, 1, 12, 0, 0).unwrap()),
+            ..Task::new("Sooner", None)
+        };
+        let someday = Task::new("Someday", None);
+        let suggested = next_actions([someday.clone(), later.clone(), sooner.clone()], 10);
+        assert_eq!(suggested, vec![sooner, later, someday]);
+    }
+
+    #[test]
+    fn highest_priority_first() {
+        let urgent = Task {
+            priority: Priority::P0,
+            ..Task::new("Urgent", None)
+        };
+        let due = Task {
+            due: Some(Utc.with_ymd_and_hms(2025, 6, 1, 12, 0, 0).unwrap()),
+            ..Task::new("Due", None)
+        };
+        let suggested = next_actions([due.clone(), urgent.clone()], 10);
+        assert_eq!(suggested, vec![urgent, due]);
+    }
+
+    #[test]
+    fn not_done() {
+        let todo = Task::new("Todo", None);
+        let mut done = Task::new("Done", None);
+        done.complete().unwrap();
+        let blocked = Task {
+            status: Status::Blocked,
+            ..Task::new("Blocked", None)
+        };
+        let suggested = next_actions([done, blocked, todo.clone()], 10);
+        assert_eq!(suggested, vec![todo]);
+    }
+
+    #[test]
+    fn oldest_first_without_due_dates() {
+        let first = Task::new("First", None);
+        let second = Task::new("Second", None);
+        let suggested = next_actions([second.clone(), first.clone()], 10);
+        assert_eq!(suggested, vec![first, second]);
+    }
+
+    #[test]
+    fn limit() {
+        let tasks = [
+            Task::new("1", None),
+            Task::new("2", None),
+            Task::new("3", None),
+        ];
+        assert_eq!(next_actions(tasks, 2).len(), 2);
+    }
+
+    #[test]
+    fn from_tasklist() {
+        let tasklist = TaskList {
+            name: "Test TaskList 1".into(),
+            id: uuid!("0196fe23-7c01-7d6b-9e09-5968eb370549"),
+            sort: Default::default(),
+        };
+        // `Task 2` waits for `Task 1`
+        let suggested = tasklist.next_actions(&TestBackend, 10).unwrap();
+        assert_eq!(suggested.len(), 1);
+        assert_eq!(suggested[0].name, "Task 1");
+    }
+}
@@ -0,0 +1,324 @@
+//! A spreadsheet-like view of tasks, one row per task, with user-chosen columns.
+
+use std::cmp::Ordering;
+
+use chrono::NaiveDate;
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    HelixFlowError, HelixFlowResult, Relate,
+    dates::{local_date, start_of_day},
+    estimates,
+    tag::{Tag, TaggedWith},
+    task::{Contains, Priority, Status, Task, TaskList},
+};
+
+/// A task field which can be shown as a column.
+// TODO: custom fields once tasks have them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Column {
+    Name,
+    Status,
+    Due,
+    Priority,
+    Tags,
+    Estimate,
+}
+
+impl Column {
+    pub fn title(&self) -> &'static str {
+        match self {
+            Column::Name => "Name",
+            Column::Status => "Status",
+            Column::Due => "Due",
+            Column::Priority => "Priority",
+            Column::Tags => "Tags",
+            Column::Estimate => "Estimate",
+        }
+    }
+
+    /// Can this column be changed by typing into its cell?
+    pub fn is_editable(&self) -> bool {
+        !matches!(self, Column::Tags)
+    }
+}
+
+/// Which columns a table shows, in order, and which one it is sorted by.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TableLayout {
+    pub columns: Vec<Column>,
+    /// `None` keeps the list's own order
+    pub sort_by: Option<Column>,
+    pub descending: bool,
+}
+
+impl Default for TableLayout {
+    fn default() -> Self {
+        TableLayout {
+            columns: vec![Column::Name, Column::Status, Column::Due, Column::Priority],
+            sort_by: None,
+            descending: false,
+        }
+    }
+}
+
+/// A task and the tags on it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Row {
+    pub task: Task,
+    pub tags: Vec<Tag>,
+}
+
+impl Row {
+    /// The text shown in `column`, with dates as seen in `tz`.
+    pub fn cell(&self, column: Column, tz: Tz) -> String {
+        match column {
+            Column::Name => self.task.name.to_string(),
+            Column::Status => format!("{:?}", self.task.status),
+            Column::Due => self
+                .task
+                .due
+                .map(|due| local_date(due, tz).to_string())
+                .unwrap_or_default(),
+            Column::Priority => format!("{:?}", self.task.priority),
+            Column::Tags => self
+                .tags
+                .iter()
+                .map(|tag| tag.name.as_ref())
+                .collect::<Vec<_>>()
+                .join(", "),
+            Column::Estimate => self.task.estimate.map(estimates::short).unwrap_or_default(),
+        }
+    }
+
+    /// Change the task's `column` to `text`, as typed into its cell by someone in `tz`. Due dates
+    /// are `YYYY-MM-DD` and estimates e.g. `1h 30m`, either can be empty to remove it. Status
+    /// changes must follow the workflow.
+    ///
+    /// The task is unchanged if `text` is not valid.
+    pub fn edit(&mut self, column: Column, text: &str, tz: Tz) -> HelixFlowResult<()> {
+        let text = text.trim();
+        let invalid = || HelixFlowError::InvalidValue {
+            field: column.title().into(),
+            value: text.into(),
+        };
+        match column {
+            Column::Name => self.task.name = text.to_string().into(),
+            Column::Status => {
+                let status = [
+                    Status::Todo,
+                    Status::InProgress,
+                    Status::Blocked,
+                    Status::Done,
+                ]
+                .into_iter()
+                .find(|status| format!("{status:?}").eq_ignore_ascii_case(text))
+                .ok_or_else(invalid)?;
+                self.task.transition(status)?;
+            }
+            Column::Due if text.is_empty() => self.task.due = None,
+            Column::Due => {
+                let date = NaiveDate::parse_from_str(text, "%Y-%m-%d").map_err(|_| invalid())?;
+                self.task.due = Some(start_of_day(date, tz));
+            }
+            Column::Priority => {
+                self.task.priority = text
+                    .strip_prefix(['P', 'p'])
+                    .and_then(|level| level.parse().ok())
+                    .and_then(Priority::from_level)
+                    .ok_or_else(invalid)?;
+            }
+            Column::Tags => return Err(invalid()),
+            Column::Estimate if text.is_empty() => self.task.estimate = None,
+            Column::Estimate => {
+                self.task.estimate = Some(estimates::parse_short(text).ok_or_else(invalid)?);
+            }
+        }
+        Ok(())
+    }
+
+    fn compare(&self, other: &Row, column: Column) -> Ordering {
+        match column {
+            Column::Name => self
+                .task
+                .name
+                .to_lowercase()
+                .cmp(&other.task.name.to_lowercase()),
+            Column::Status => self.task.status.cmp(&other.task.status),
+            // Tasks without a due date last
+            Column::Due => match (self.task.due, other.task.due) {
+                (Some(a), Some(b)) => a.cmp(&b),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            },
+            Column::Priority => self.task.priority.cmp(&other.task.priority),
+            Column::Tags => self.tags.len().cmp(&other.tags.len()),
+            // Tasks without an estimate last
+            Column::Estimate => match (self.task.estimate, other.task.estimate) {
+                (Some(a), Some(b)) => a.cmp(&b),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            },
+        }
+    }
+}
+
+impl TableLayout {
+    /// One row per task in `tasklist`, in the order given by `sort_by`.
+    pub fn rows<B>(&self, tasklist: &TaskList, backend: &B) -> HelixFlowResult<Vec<Row>>
+    where
+        B: Relate<Contains<TaskList, Task>> + Relate<TaggedWith<Task, Tag>>,
+    {
+        let mut rows = tasklist
+            .get_linked_items(backend)?
+            .map(|contains| {
+                let task = contains.right?;
+                let tags = task.tags(backend)?;
+                Ok(Row { task, tags })
+            })
+            .collect::<HelixFlowResult<Vec<Row>>>()?;
+        self.sort(&mut rows);
+        Ok(rows)
+    }
+
+    /// Sort `rows` in place by `sort_by`. Rows which compare equal keep their order.
+    pub fn sort(&self, rows: &mut [Row]) {
+        if let Some(column) = self.sort_by {
+            rows.sort_by(|a, b| {
+                let ordering = a.compare(b, column);
+                if self.descending {
+                    ordering.reverse()
+                } else {
+                    ordering
+                }
+            });
+        }
+    }
+
+    /// Sort by `column`, or reverse the order if already sorted by it.
+    pub fn toggle_sort(&mut self, column: Column) {
+        if self.sort_by == Some(column) {
+            self.descending = !self.descending;
+        } else {
+            self.sort_by = Some(column);
+            self.descending = false;
+        }
+    }
+}
+
+#[cfg(test)]
+#[coverage(off)]
+mod tests {
+    use std::assert_matches::assert_matches;
+
+    use chrono::{TimeZone, Utc};
+    use chrono_tz::{Europe::Berlin, UTC};
+
+    use super::*;
+
+    fn row(name: &'static str, priority: Priority) -> Row {
+        Row {
+            task: Task {
+                priority,
+                ..Task::new(name, None)
+            },
+            tags: vec![],
+        }
+    }
+
+    fn names(rows: &[Row]) -> Vec<&str> {
+        rows.iter().map(|row| row.task.name.as_ref()).collect()
+    }
+
+    #[test]
+    fn cells() {
+        let row = Row {
+            task: Task {
+                due: Some(Utc.with_ymd_and_hms(2025, 6, 6, 23, 0, 0).unwrap()),
+                ..Task::new("Pay rent", None)
+            },
+            tags: vec![Tag::new("home"), Tag::new("money")],
+        };
+        assert_eq!(row.cell(Column::Name, UTC), "Pay rent");
+        assert_eq!(row.cell(Column::Status, UTC), "Todo");
+        assert_eq!(row.cell(Column::Due, UTC), "2025-06-06");
+        assert_eq!(row.cell(Column::Due, Berlin), "2025-06-07");
+        assert_eq!(row.cell(Column::Priority, UTC), "P2");
+        assert_eq!(row.cell(Column::Tags, UTC), "home, money");
+        assert_eq!(row.cell(Column::Estimate, UTC), "");
+    }
+
+    #[test]
+    fn sort_and_toggle() {
+        let mut rows = vec![
+            row("b", Priority::P1),
+            row("c", Priority::P0),
+            row("a", Priority::P3),
+        ];
+        let mut layout = TableLayout::default();
+        layout.sort(&mut rows);
+        assert_eq!(names(&rows), vec!["b", "c", "a"]);
+        layout.toggle_sort(Column::Priority);
+        layout.sort(&mut rows);
+        assert_eq!(names(&rows), vec!["c", "b", "a"]);
+        layout.toggle_sort(Column::Priority);
+        layout.sort(&mut rows);
+        assert_eq!(names(&rows), vec!["a", "b", "c"]);
+        layout.toggle_sort(Column::Name);
+        assert!(!layout.descending);
+    }
+
+    #[test]
+    fn edit_cells() {
+        let mut row = row("Pay rent", Priority::P2);
+        row.edit(Column::Name, " Pay the rent ", UTC).unwrap();
+        row.edit(Column::Status, "inprogress", UTC).unwrap();
+        row.edit(Column::Priority, "p0", UTC).unwrap();
+        row.edit(Column::Due, "2025-06-07", Berlin).unwrap();
+        assert_eq!(row.task.name, "Pay the rent");
+        assert_eq!(row.task.status, Status::InProgress);
+        assert_eq!(row.task.priority, Priority::P0);
+        assert_eq!(
+            row.task.due,
+            Some(Utc.with_ymd_and_hms(2025, 6, 6, 22, 0, 0).unwrap())
+        );
+        row.edit(Column::Due, "", UTC).unwrap();
+        assert_eq!(row.task.due, None);
+        row.edit(Column::Estimate, "1h 30m", UTC).unwrap();
+        assert_eq!(
+            row.task.estimate,
+            Some(std::time::Duration::from_secs(90 * 60))
+        );
+        assert_eq!(row.cell(Column::Estimate, UTC), "1h 30m");
+        row.edit(Column::Estimate, "", UTC).unwrap();
+        assert_eq!(row.task.estimate, None);
+    }
+
+    #[test]
+    fn invalid_edits() {
+        let mut row = row("Pay rent", Priority::P2);
+        let original = row.clone();
+        assert_matches!(
+            row.edit(Column::Priority, "P9", UTC),
+            Err(HelixFlowError::InvalidValue { field, value }) if field == "Priority" && value == "P9"
+        );
+        assert_matches!(
+            row.edit(Column::Due, "tomorrow", UTC),
+            Err(HelixFlowError::InvalidValue { .. })
+        );
+        assert_matches!(
+            row.edit(Column::Tags, "home", UTC),
+            Err(HelixFlowError::InvalidValue { .. })
+        );
+        row.edit(Column::Status, "Done", UTC).unwrap();
+        assert_matches!(
+            row.edit(Column::Status, "Blocked", UTC),
+            Err(HelixFlowError::InvalidTransition { .. })
+        );
+        row.task.reopen().unwrap();
+        assert_eq!(row, original);
+    }
+}
@@ -0,0 +1,233 @@
+//! Free-form labels which can be attached to any number of tasks.
+
+use std::borrow::Cow;
+
+use serde::{Deserialize, Serialize};
+use uuid::{Uuid, uuid};
+
+use crate::{
+    HelixFlowItem, HelixFlowResult, Linkable, Relate, Relationship, Validate,
+    task::{Task, TestBackend},
+    validate_name,
+};
+
+/// A label such as "home" or "waiting-for". Tags are shared: tagging a task with an existing tag
+/// links to it rather than creating a copy.
+#[derive(
+    Serialize, Deserialize, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, HelixFlowItem,
+)]
+pub struct Tag {
+    pub name: Cow<'static, str>,
+    pub id: Uuid,
+}
+
+/// A tag needs a name, see `MAX_NAME_LENGTH`.
+impl Validate for Tag {
+    fn validate(&self) -> HelixFlowResult<()> {
+        validate_name(&self.name)
+    }
+}
+
+impl Tag {
+    /// Create a new `Tag` with valid `id`, suitable for usage as database key.
+    pub fn new<S>(name: S) -> Tag
+    where
+        S: Into<Cow<'static, str>>,
+    {
+        Tag {
+            name: name.into(),
+            id: Uuid::now_v7(),
+        }
+    }
+
+    /// All tasks carrying this tag.
+    pub fn tasks<B: Tagged>(&self, backend: &B) -> HelixFlowResult<Vec<Task>> {
+        backend.tagged_tasks(self)
+    }
+}
+
+impl Task {
+    /// Link this task to `tag`, ready for `create_linked_item`.
+    pub fn tag(&self, tag: &Tag) -> TaggedWith<Task, Tag> {
+        Linkable::<TaggedWith<Task, Tag>>::link(self, tag)
+    }
+
+    /// Remove `tag` from this task. The tag itself remains, for use on other tasks.
+    pub fn untag<B>(&self, tag: &Tag, backend: &B) -> HelixFlowResult<()>
+    where
+        B: Relate<TaggedWith<Task, Tag>>,
+    {
+        Linkable::<TaggedWith<Task, Tag>>::unlink(self, tag, backend)
+    }
+
+    /// All tags on this task.
+    pub fn tags<B>(&self, backend: &B) -> HelixFlowResult<Vec<Tag>>
+    where
+        B: Relate<TaggedWith<Task, Tag>>,
+    {
+        Linkable::<TaggedWith<Task, Tag>>::get_linked_items(self, backend)?
+            .map(|tagged| tagged.right)
+            .collect()
+    }
+}
+
+/// `left` is labelled with `right`.
+#[derive(Debug, Relationship)]
+#[relationship(Task => Tag)]
+pub struct TaggedWith<LEFT, RIGHT> {
+    pub left: HelixFlowResult<LEFT>,
+    pub right: HelixFlowResult<RIGHT>,
+}
+
+/// Look up tagged items from the tag's side of a `TaggedWith`.
+///
+/// `Relate::create_linked_item` for `TaggedWith<Task, Tag>` should only create the tag if it does
+/// not exist yet; the task must already exist.
+pub trait Tagged: Relate<TaggedWith<Task, Tag>> {
+    fn tagged_tasks(&self, tag: &Tag) -> HelixFlowResult<Vec<Task>>;
+}
+
+/// `Task 1` is tagged with `home`, no other task has tags.
+impl Relate<TaggedWith<Task, Tag>> for TestBackend {
+    fn create_linked_item(
+        &self,
+        link: &TaggedWith<Task, Tag>,
+    ) -> HelixFlowResult<TaggedWith<Task, Tag>> {
+        Ok(TaggedWith {
+            left: Ok(link.left.as_ref().unwrap().clone()),
+            right: Ok(link.right.as_ref().unwrap().clone()),
+        })
+    }
+    fn get_linked_items(
+        &self,
+        left: &Task,
+    ) -> HelixFlowResult<impl Iterator<Item = TaggedWith<Task, Tag>>> {
+        let tags = match left.id.to_string().as_str() {
+            "0196b4c9-8447-7959-ae1f-72c7c8a3dd36" => vec![Tag {
+                name: "home".into(),
+                id: uuid!("0197a1b2-3c4d-7e5f-8a9b-0c1d2e3f4a5b"),
+            }],
+            _ => vec![],
+        };
+        Ok(tags.into_iter().map(|tag| left.link(&tag)))
+    }
+    fn delete_link(&self, _link: &TaggedWith<Task, Tag>) -> HelixFlowResult<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[coverage(off)]
+mod tests {
+    use std::{assert_matches::assert_matches, sync::Mutex};
+
+    use super::*;
+    use crate::{HelixFlowError, Link};
+
+    #[derive(Default)]
+    struct TagBackend {
+        tagged: Mutex<Vec<(Task, Tag)>>,
+    }
+
+    impl Relate<TaggedWith<Task, Tag>> for TagBackend {
+        fn create_linked_item(
+            &self,
+            link: &TaggedWith<Task, Tag>,
+        ) -> HelixFlowResult<TaggedWith<Task, Tag>> {
+            let task = link.left.as_ref().unwrap().clone();
+            let tag = link.right.as_ref().unwrap().clone();
+            self.tagged
+                .lock()
+                .unwrap()
+                .push((task.clone(), tag.clone()));
+            Ok(TaggedWith {
+                left: Ok(task),
+                right: Ok(tag),
+            })
+        }
+        fn get_linked_items(
+            &self,
+            left: &Task,
+        ) -> HelixFlowResult<impl Iterator<Item = TaggedWith<Task, Tag>>> {
+            let tags: Vec<_> = self
+                .tagged
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|(task, _)| task.id == left.id)
+                .map(|(task, tag)| TaggedWith {
+                    left: Ok(task.clone()),
+                    right: Ok(tag.clone()),
+                })
+                .collect();
+            Ok(tags.into_iter())
+        }
+        fn delete_link(&self, link: &TaggedWith<Task, Tag>) -> HelixFlowResult<()> {
+            let task = link.left.as_ref().unwrap();
+            let tag = link.right.as_ref().unwrap();
+            self.tagged
+                .lock()
+                .unwrap()
+                .retain(|(t, g)| !(t.id == task.id && g.id == tag.id));
+            Ok(())
+        }
+    }
+
+    impl Tagged for TagBackend {
+        fn tagged_tasks(&self, tag: &Tag) -> HelixFlowResult<Vec<Task>> {
+            Ok(self
+                .tagged
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|(_, t)| t.id == tag.id)
+                .map(|(task, _)| task.clone())
+                .collect())
+        }
+    }
+
+    #[test]
+    fn tag_tasks() {
+        let backend = TagBackend::default();
+        let home = Tag::new("home");
+        let work = Tag::new("work");
+        let rent = Task::new("Pay rent", None);
+        let report = Task::new("Write report", None);
+        rent.tag(&home).create_linked_item(&backend).unwrap();
+        report.tag(&work).create_linked_item(&backend).unwrap();
+        report.tag(&home).create_linked_item(&backend).unwrap();
+
+        assert_eq!(home.tasks(&backend).unwrap(), vec![rent, report.clone()]);
+        assert_eq!(work.tasks(&backend).unwrap(), vec![report.clone()]);
+        assert_eq!(report.tags(&backend).unwrap(), vec![work, home]);
+    }
+
+    #[test]
+    fn untag_task() {
+        let backend = TagBackend::default();
+        let home = Tag::new("home");
+        let work = Tag::new("work");
+        let report = Task::new("Write report", None);
+        report.tag(&work).create_linked_item(&backend).unwrap();
+        report.tag(&home).create_linked_item(&backend).unwrap();
+
+        report.untag(&work, &backend).unwrap();
+        assert_eq!(report.tags(&backend).unwrap(), vec![home.clone()]);
+        report.untag(&work, &backend).unwrap();
+        assert_eq!(report.tags(&backend).unwrap(), vec![home]);
+    }
+
+    #[test]
+    fn invalid_link() {
+        let backend = TagBackend::default();
+        let link: TaggedWith<Task, Tag> = TaggedWith {
+            left: Ok(Task::new("Pay rent", None)),
+            right: Err(HelixFlowError::InvalidID { id: "foo".into() }),
+        };
+        assert_matches!(
+            link.create_linked_item(&backend),
+            Err(HelixFlowError::RelationshipBetweenErrors { .. })
+        );
+        assert!(backend.tagged.lock().unwrap().is_empty());
+    }
+}
@@ -1,38 +1,126 @@
 //! The fundamental `Task` building block and related functions.
 
-use std::{
-    any::Any,
-    borrow::Cow,
-    ops::{ControlFlow, FromResidual, Try},
-};
+use std::{borrow::Cow, time::Duration};
 
 use anyhow::anyhow;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::{Uuid, uuid};
 
 use crate::{
-    HelixFlowError, HelixFlowItem, HelixFlowResult, Link, Linkable, Relate, Relationship, Store,
+    HelixFlowError, HelixFlowItem, HelixFlowResult, Linkable, Page, Relate, Relationship, Store,
+    Validate, actor::Actor, project::Project, rank, sorting::SortRule, timestamps::LastModified,
+    validate_name, versions::Version,
 };
 
-impl HelixFlowItem for Task {
-    fn as_any(&self) -> &dyn Any {
-        self
-    }
-}
-impl HelixFlowItem for TaskList {
-    fn as_any(&self) -> &dyn Any {
-        self
-    }
-}
-
 /// A Task
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, HelixFlowItem)]
 pub struct Task {
     // TODO check value of using a Cow here ... are we really saving when passing around - or would
     // it be better to have something that is `Copy`?
     pub name: Cow<'static, str>,
     pub id: Uuid,
     pub description: Option<Cow<'static, str>>,
+    /// A colour label, independent of any tags, e.g. to show as an accent on the task's card.
+    pub colour: Option<Colour>,
+    /// Always stored as UTC, see `crate::dates` for conversion to the user's time zone.
+    pub due: Option<DateTime<Utc>>,
+    /// Only change via `transition` to ensure the workflow is followed.
+    #[serde(default)]
+    pub status: Status,
+    #[serde(default)]
+    pub priority: Priority,
+    /// How long the task is expected to take.
+    #[serde(default)]
+    pub estimate: Option<Duration>,
+    /// How long the task actually took, once it is done.
+    #[serde(default)]
+    pub actual: Option<Duration>,
+    /// When the task was last moved to `Done`, maintained by `transition`.
+    #[serde(default)]
+    pub completed_at: Option<DateTime<Utc>>,
+    /// Pinned tasks stick to the top of their list, see `Backlogs::backlog_pinned_first`.
+    #[serde(default)]
+    pub pinned: bool,
+    /// When the task was last stored, maintained by `CRUD`.
+    #[serde(default)]
+    pub updated_at: LastModified,
+    /// Which version of the stored task this was read from, maintained by the backend.
+    #[serde(default)]
+    pub version: Version,
+}
+
+/// A task needs a name, see `MAX_NAME_LENGTH`.
+impl Validate for Task {
+    fn validate(&self) -> HelixFlowResult<()> {
+        validate_name(&self.name)
+    }
+}
+
+/// Where a task is in its workflow.
+#[derive(
+    Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash,
+)]
+pub enum Status {
+    #[default]
+    Todo,
+    InProgress,
+    Blocked,
+    Done,
+}
+
+impl Status {
+    /// Valid workflow steps. Anything not yet done can be finished or blocked; blocked tasks must
+    /// be unblocked before they are finished; finished tasks can only be reopened.
+    pub fn can_transition_to(&self, next: Status) -> bool {
+        use Status::*;
+        matches!(
+            (self, next),
+            (Todo, InProgress | Blocked | Done)
+                | (InProgress, Todo | Blocked | Done)
+                | (Blocked, Todo | InProgress)
+                | (Done, Todo)
+        )
+    }
+}
+
+/// How important a task is. `P0` is the most important and sorts first.
+#[derive(
+    Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash,
+)]
+pub enum Priority {
+    P0,
+    P1,
+    #[default]
+    P2,
+    P3,
+}
+
+impl Priority {
+    /// `0` => `P0` etc. `None` for anything above `3`.
+    pub fn from_level(level: u8) -> Option<Self> {
+        match level {
+            0 => Some(Priority::P0),
+            1 => Some(Priority::P1),
+            2 => Some(Priority::P2),
+            3 => Some(Priority::P3),
+            _ => None,
+        }
+    }
+}
+
+/// An opaque RGB colour
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Colour {
+    pub red: u8,
+    pub green: u8,
+    pub blue: u8,
+}
+
+impl Colour {
+    pub const fn rgb(red: u8, green: u8, blue: u8) -> Self {
+        Colour { red, green, blue }
+    }
 }
 
 impl Task {
@@ -51,15 +139,98 @@ impl Task {
             name: name.into(),
             id: Uuid::now_v7(),
             description: description.map(|desc| desc.into()),
+            colour: None,
+            due: None,
+            status: Status::Todo,
+            priority: Priority::P2,
+            estimate: None,
+            actual: None,
+            completed_at: None,
+            pinned: false,
+            updated_at: Default::default(),
+            version: Default::default(),
+        }
+    }
+
+    /// Move this task to `next` in its workflow, recording when it is completed.
+    pub fn transition(&mut self, next: Status) -> HelixFlowResult<()> {
+        if self.status.can_transition_to(next) {
+            self.status = next;
+            self.completed_at = (next == Status::Done).then(Utc::now);
+            Ok(())
+        } else {
+            Err(HelixFlowError::InvalidTransition {
+                from: self.status,
+                to: next,
+            })
         }
     }
+
+    /// Mark this task as `Done`, now. Use `CRUD::update` to save the change.
+    pub fn complete(&mut self) -> HelixFlowResult<()> {
+        self.transition(Status::Done)
+    }
+
+    /// Move a completed task back to `Todo`. Use `CRUD::update` to save the change.
+    pub fn reopen(&mut self) -> HelixFlowResult<()> {
+        self.transition(Status::Todo)
+    }
+
+    /// Keep this task at the top of its list. Use `CRUD::update` to save the change.
+    pub fn pin(&mut self) {
+        self.pinned = true;
+    }
+
+    /// Let this task move with the rest of its list again. Use `CRUD::update` to save the change.
+    pub fn unpin(&mut self) {
+        self.pinned = false;
+    }
+}
+
+/// Look up the tasks still to be done in a list.
+pub trait Backlogs: Relate<Contains<TaskList, Task>> {
+    /// The tasks in `tasklist`, in order. Completed tasks are left out unless
+    /// `include_completed`.
+    ///
+    /// The default fetches every task and filters them. Backends should override this if they
+    /// can filter in their query.
+    fn backlog(&self, tasklist: &TaskList, include_completed: bool) -> HelixFlowResult<Vec<Task>> {
+        self.get_linked_items(tasklist)?
+            .map(|contains| contains.right)
+            .filter(|task| {
+                include_completed || task.as_ref().is_ok_and(|task| task.status != Status::Done)
+            })
+            .collect()
+    }
+
+    /// As `backlog`, but with pinned tasks first so they stick to the top. Pinned & unpinned
+    /// tasks each keep their order.
+    fn backlog_pinned_first(
+        &self,
+        tasklist: &TaskList,
+        include_completed: bool,
+    ) -> HelixFlowResult<Vec<Task>> {
+        let mut tasks = self.backlog(tasklist, include_completed)?;
+        tasks.sort_by_key(|task| !task.pinned);
+        Ok(tasks)
+    }
 }
 
 /// A list of tasks
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, HelixFlowItem)]
 pub struct TaskList {
     pub name: Cow<'static, str>,
     pub id: Uuid,
+    /// How tasks are ordered when the list is displayed
+    #[serde(default)]
+    pub sort: SortRule,
+}
+
+/// A list needs a name, see `MAX_NAME_LENGTH`.
+impl Validate for TaskList {
+    fn validate(&self) -> HelixFlowResult<()> {
+        validate_name(&self.name)
+    }
 }
 
 impl TaskList {
@@ -71,115 +242,77 @@ impl TaskList {
         TaskList {
             name: name.into(),
             id: Uuid::now_v7(),
+            sort: SortRule::default(),
         }
     }
-}
-
-#[derive(Debug)]
-pub struct Contains<LEFT, RIGHT> {
-    pub left: HelixFlowResult<LEFT>,
-    pub sortorder: String,
-    pub right: HelixFlowResult<RIGHT>,
-}
 
-impl Relationship for Contains<TaskList, Task> {
-    type Left = TaskList;
-    type Right = Task;
-}
-
-impl<LEFT, RIGHT> Try for Contains<LEFT, RIGHT>
-where
-    Contains<LEFT, RIGHT>: Relationship,
-{
-    type Output = Self; // Continue
-    type Residual = Self; // Break
-    fn branch(self) -> ControlFlow<Self::Residual, Self::Output> {
-        if self.left.is_ok() && self.right.is_ok() {
-            ControlFlow::Continue(self)
-        } else {
-            ControlFlow::Break(self)
-        }
+    /// One `page` of the tasks in this list, e.g. to show a long backlog a screenful at a time.
+    pub fn tasks_page<B>(&self, backend: &B, page: Page) -> HelixFlowResult<Vec<Task>>
+    where
+        B: Relate<Contains<TaskList, Task>>,
+    {
+        backend
+            .get_linked_page(self, page)?
+            .map(|contains| contains.right)
+            .collect()
     }
-    fn from_output(_output: Self::Output) -> Self {
-        unimplemented!("Contains? should only be used in funtions returning a Result")
+
+    /// Link `task` into this list, ready for `create_linked_item`.
+    ///
+    /// A list can also contain other lists, so this and the other `Linkable` methods are
+    /// provided directly for tasks, see `crate::sublists` for nested lists.
+    pub fn link(&self, task: &Task) -> Contains<TaskList, Task> {
+        Linkable::<Contains<TaskList, Task>>::link(self, task)
     }
-}
 
-impl<LEFT, RIGHT> FromResidual<Contains<LEFT, RIGHT>> for Contains<LEFT, RIGHT>
-where
-    Contains<LEFT, RIGHT>: Relationship,
-{
-    fn from_residual(_residual: Contains<LEFT, RIGHT>) -> Self {
-        unimplemented!("Contains? should only be used in funtions returning a Result")
+    /// The tasks in this list, with their `Contains` links.
+    pub fn get_linked_items<B>(
+        &self,
+        backend: &B,
+    ) -> HelixFlowResult<impl Iterator<Item = Contains<TaskList, Task>>>
+    where
+        B: Relate<Contains<TaskList, Task>>,
+    {
+        Linkable::<Contains<TaskList, Task>>::get_linked_items(self, backend)
     }
-}
 
-impl<LEFT, RIGHT> FromResidual<Contains<LEFT, RIGHT>> for HelixFlowResult<()>
-where
-    Contains<LEFT, RIGHT>: Relationship,
-    LEFT: HelixFlowItem,
-    RIGHT: HelixFlowItem,
-{
-    fn from_residual(residual: Contains<LEFT, RIGHT>) -> Self {
-        Err(HelixFlowError::RelationshipBetweenErrors {
-            left: match residual.left {
-                Ok(item) => Box::new(Ok(Box::new(item))),
-                Err(e) => Box::new(Err(e)),
-            },
-            right: match residual.right {
-                Ok(item) => Box::new(Ok(Box::new(item))),
-                Err(e) => Box::new(Err(e)),
-            },
-        })
+    /// Remove `task` from this list, without deleting it.
+    pub fn unlink<B>(&self, task: &Task, backend: &B) -> HelixFlowResult<()>
+    where
+        B: Relate<Contains<TaskList, Task>>,
+    {
+        Linkable::<Contains<TaskList, Task>>::unlink(self, task, backend)
     }
 }
 
-impl<LEFT, RIGHT> Link for Contains<LEFT, RIGHT>
-where
-    Contains<LEFT, RIGHT>: Relationship,
-    LEFT: HelixFlowItem,
-    RIGHT: HelixFlowItem + Clone + PartialEq,
-{
-    fn create_linked_item<B: Relate<Contains<LEFT, RIGHT>>>(
-        self,
-        backend: &B,
-    ) -> HelixFlowResult<()> {
-        let valid_relationship = self?;
-        let created = backend.create_linked_item(&valid_relationship)?;
-        let _tasklist_ok = created.left?;
-        let expected = valid_relationship.right?;
-        match created.right {
-            Ok(task) if task == expected => Ok(()),
-            Ok(_) => Err(HelixFlowError::Mismatch {
-                expected: Box::new(expected.clone()),
-                actual: Box::new(created.right?.clone()),
-            }),
-            Err(e) => Err(e),
-        }
-    }
+/// `left` holds `right`, in the order given by `sortorder`:
+/// - `TaskList => Task`
+/// - `Task => Task`: subtasks, see `crate::subtasks`
+/// - `Project => TaskList`: `Relate::create_linked_item` should create the tasklist if it does
+///   not exist yet, so that existing lists can be moved into a project. The project must already
+///   exist.
+/// - `TaskList => TaskList`: nested lists, see `crate::sublists`. As with projects, the nested
+///   list is created if needed.
+#[derive(Debug, Relationship)]
+#[relationship(TaskList => Task, Task => Task, Project => TaskList, TaskList => TaskList)]
+pub struct Contains<LEFT, RIGHT> {
+    pub left: HelixFlowResult<LEFT>,
+    /// Position of `right` within `left`, see `crate::rank`. Empty until the backend stores the
+    /// link, when an empty rank places `right` last.
+    pub sortorder: String,
+    /// When the link was stored. `None` until the backend stores the link, and for links stored
+    /// before this was recorded.
+    pub created_at: Option<DateTime<Utc>>,
+    /// Who put `right` into `left`, if known, see `Contains::by`.
+    pub created_by: Option<Actor>,
+    pub right: HelixFlowResult<RIGHT>,
 }
 
-impl<LEFT, RIGHT> Linkable<Contains<LEFT, RIGHT>> for LEFT
-where
-    Contains<LEFT, RIGHT>: Relationship<Left = LEFT, Right = RIGHT>,
-    LEFT: HelixFlowItem + Clone + PartialEq,
-    RIGHT: HelixFlowItem + Clone + PartialEq,
-{
-    fn link(&self, task: &RIGHT) -> Contains<LEFT, RIGHT> {
-        Contains {
-            left: Ok(self.clone()),
-            sortorder: "a".into(),
-            right: Ok(task.clone()),
-        }
-    }
-    fn get_linked_items<B>(
-        &self,
-        backend: &B,
-    ) -> HelixFlowResult<impl Iterator<Item = Contains<LEFT, RIGHT>>>
-    where
-        B: Relate<Contains<LEFT, RIGHT>>,
-    {
-        backend.get_linked_items(self)
+impl<LEFT, RIGHT> Contains<LEFT, RIGHT> {
+    /// Record that `actor` is creating this link.
+    pub fn by(mut self, actor: &Actor) -> Self {
+        self.created_by = Some(actor.clone());
+        self
     }
 }
 
@@ -187,10 +320,16 @@ where
 pub struct TestBackend;
 
 impl Store<Task> for TestBackend {
+    /// Special task names: `FAIL` gives an error, `MISMATCH` returns a different task and
+    /// names with surrounding whitespace are trimmed.
     fn create(&self, task: &Task) -> HelixFlowResult<Task> {
         match task.name {
             Cow::Borrowed("FAIL") => Err(anyhow!("Failed to create task").into()),
             Cow::Borrowed("MISMATCH") => Ok(Task::new(task.name.clone(), task.description.clone())),
+            Cow::Borrowed(name) if name.trim() != name => Ok(Task {
+                name: name.trim().to_string().into(),
+                ..task.clone()
+            }),
             _ => Ok(task.clone()),
         }
     }
@@ -201,11 +340,31 @@ impl Store<Task> for TestBackend {
                 name: "Task 1".into(),
                 id: *id,
                 description: None,
+                due: None,
+                colour: None,
+                status: Status::Todo,
+                priority: Priority::P2,
+                estimate: Some(Duration::from_secs(2 * 60 * 60)),
+                actual: None,
+                completed_at: None,
+                pinned: false,
+                updated_at: Default::default(),
+                version: Default::default(),
             }),
             "0196ca5f-d934-7ec8-b042-ae37b94b8432" => Ok(Task {
                 name: "Task 2".into(),
                 id: *id,
                 description: None,
+                due: None,
+                colour: None,
+                status: Status::Todo,
+                priority: Priority::P2,
+                estimate: None,
+                actual: None,
+                completed_at: None,
+                pinned: false,
+                updated_at: Default::default(),
+                version: Default::default(),
             }),
             _ => Err(HelixFlowError::NotFound {
                 itemtype: "Task".into(),
@@ -213,17 +372,28 @@ impl Store<Task> for TestBackend {
             }),
         }
     }
+    /// Only the tasks known to `get` can be updated, with the same special names as `create`.
+    fn update(&self, task: &Task) -> HelixFlowResult<Task> {
+        let _: Task = self.get(&task.id)?;
+        self.create(task)
+    }
+
+    fn delete(&self, id: &Uuid) -> HelixFlowResult<()> {
+        let _: Task = self.get(id)?;
+        Ok(())
+    }
 }
 
 impl Store<TaskList> for TestBackend {
-    fn create(&self, _item: &TaskList) -> HelixFlowResult<TaskList> {
-        todo!()
+    fn create(&self, tasklist: &TaskList) -> HelixFlowResult<TaskList> {
+        Ok(tasklist.clone())
     }
     fn get(&self, id: &Uuid) -> HelixFlowResult<TaskList> {
         match id.to_string().as_str() {
             "0196fe23-7c01-7d6b-9e09-5968eb370549" => Ok(TaskList {
                 name: "Test TaskList 1".into(),
                 id: *id,
+                sort: Default::default(),
             }),
             _ => Err(HelixFlowError::NotFound {
                 itemtype: "Tasklist".into(),
@@ -231,6 +401,15 @@ impl Store<TaskList> for TestBackend {
             }),
         }
     }
+    fn update(&self, tasklist: &TaskList) -> HelixFlowResult<TaskList> {
+        let _: TaskList = self.get(&tasklist.id)?;
+        Ok(tasklist.clone())
+    }
+
+    fn delete(&self, id: &Uuid) -> HelixFlowResult<()> {
+        let _: TaskList = self.get(id)?;
+        Ok(())
+    }
 }
 
 impl Relate<Contains<TaskList, Task>> for TestBackend {
@@ -240,11 +419,19 @@ impl Relate<Contains<TaskList, Task>> for TestBackend {
     ) -> HelixFlowResult<Contains<TaskList, Task>> {
         let tasklist = link.left.as_ref().unwrap().clone();
         match tasklist.id.to_string().as_str() {
-            "0196fe23-7c01-7d6b-9e09-5968eb370549" => Ok(Contains {
-                left: Ok(tasklist),
-                sortorder: link.sortorder.clone(),
-                right: self.create(link.right.as_ref().unwrap()),
-            }),
+            // The inbox is created on demand, so can always be linked to
+            "0196fe23-7c01-7d6b-9e09-5968eb370549" | "0199b6d0-0000-7000-8000-00000000001b" => {
+                Ok(Contains {
+                    left: Ok(tasklist),
+                    sortorder: match link.sortorder.as_str() {
+                        "" => rank::between(Some("r"), None)?,
+                        rank => rank.into(),
+                    },
+                    created_at: Some(Utc::now()),
+                    created_by: link.created_by.clone(),
+                    right: self.create(link.right.as_ref().unwrap()),
+                })
+            }
             _ => Err(HelixFlowError::NotFound {
                 itemtype: "Tasklist".into(),
                 id: tasklist.id,
@@ -262,14 +449,40 @@ impl Relate<Contains<TaskList, Task>> for TestBackend {
                         name: "Task 1".into(),
                         id: uuid!("0196b4c9-8447-7959-ae1f-72c7c8a3dd36"),
                         description: None,
+                        due: None,
+                        colour: None,
+                        status: Status::Todo,
+                        priority: Priority::P2,
+                        estimate: Some(Duration::from_secs(2 * 60 * 60)),
+                        actual: None,
+                        completed_at: None,
+                        pinned: false,
+                        updated_at: Default::default(),
+                        version: Default::default(),
                     },
                     Task {
                         name: "Task 2".into(),
                         id: uuid!("0196ca5f-d934-7ec8-b042-ae37b94b8432"),
                         description: None,
+                        due: None,
+                        colour: None,
+                        status: Status::Todo,
+                        priority: Priority::P2,
+                        estimate: None,
+                        actual: None,
+                        completed_at: None,
+                        pinned: false,
+                        updated_at: Default::default(),
+                        version: Default::default(),
                     },
                 ];
-                Ok(tasks.into_iter().map(|task| left.link(&task)))
+                Ok(tasks
+                    .into_iter()
+                    .zip(["i", "r"])
+                    .map(|(task, rank)| Contains {
+                        sortorder: rank.into(),
+                        ..left.link(&task)
+                    }))
             }
             _ => Err(HelixFlowError::NotFound {
                 itemtype: "Tasklist".into(),
@@ -277,16 +490,89 @@ impl Relate<Contains<TaskList, Task>> for TestBackend {
             }),
         }
     }
+    fn delete_link(&self, _link: &Contains<TaskList, Task>) -> HelixFlowResult<()> {
+        Ok(())
+    }
+    fn create_link(&self, link: &Contains<TaskList, Task>) -> HelixFlowResult<()> {
+        let _: TaskList = self.get(&link.left.as_ref().unwrap().id)?;
+        Ok(())
+    }
 }
 
+/// `Task 1` contains `Task 2`, no other task has subtasks.
+impl Relate<Contains<Task, Task>> for TestBackend {
+    fn create_linked_item(
+        &self,
+        link: &Contains<Task, Task>,
+    ) -> HelixFlowResult<Contains<Task, Task>> {
+        let parent: Task = self.get(&link.left.as_ref().unwrap().id)?;
+        Ok(Contains {
+            left: Ok(parent),
+            sortorder: link.sortorder.clone(),
+            created_at: Some(Utc::now()),
+            created_by: link.created_by.clone(),
+            right: self.create(link.right.as_ref().unwrap()),
+        })
+    }
+    fn get_linked_items(
+        &self,
+        left: &Task,
+    ) -> HelixFlowResult<impl Iterator<Item = Contains<Task, Task>>> {
+        let children = match left.id.to_string().as_str() {
+            "0196b4c9-8447-7959-ae1f-72c7c8a3dd36" => {
+                vec![self.get(&uuid!("0196ca5f-d934-7ec8-b042-ae37b94b8432"))?]
+            }
+            _ => vec![],
+        };
+        Ok(children.into_iter().map(|child| left.link(&child)))
+    }
+    fn delete_link(&self, _link: &Contains<Task, Task>) -> HelixFlowResult<()> {
+        Ok(())
+    }
+}
+
+impl Backlogs for TestBackend {}
+
 #[cfg(test)]
 #[coverage(off)]
 mod tests {
-    use crate::CRUD;
+    use crate::{CRUD, MAX_NAME_LENGTH, Verification};
 
     use super::*;
     use std::assert_matches::assert_matches;
 
+    #[test]
+    fn pages_of_tasks() {
+        let tasklist =
+            TaskList::get(&TestBackend, &uuid!("0196fe23-7c01-7d6b-9e09-5968eb370549")).unwrap();
+        let page = Page::first(1);
+        let names = |page| -> Vec<String> {
+            tasklist
+                .tasks_page(&TestBackend, page)
+                .unwrap()
+                .into_iter()
+                .map(|task| task.name.to_string())
+                .collect()
+        };
+        assert_eq!(names(page), vec!["Task 1"]);
+        assert_eq!(names(page.next()), vec!["Task 2"]);
+        assert_eq!(names(page.next().next()), Vec::<String>::new());
+        assert_eq!(names(Page::first(10)), vec!["Task 1", "Task 2"]);
+    }
+
+    #[test]
+    fn link_metadata() {
+        let tasklist =
+            TaskList::get(&TestBackend, &uuid!("0196fe23-7c01-7d6b-9e09-5968eb370549")).unwrap();
+        let alice = Actor::new("Alice", &Uuid::now_v7());
+        let link = tasklist.link(&Task::new("Added by Alice", None)).by(&alice);
+        assert_eq!(link.created_at, None);
+        let before = Utc::now();
+        let created = TestBackend.create_linked_item(&link).unwrap();
+        assert_eq!(created.created_by, Some(alice));
+        assert!(created.created_at.unwrap() >= before);
+    }
+
     #[test]
     fn try_contains_oks() -> HelixFlowResult<()> {
         let tasklist = TaskList::new("tasklist");
@@ -294,11 +580,15 @@ mod tests {
         let contains = Contains {
             left: Ok(tasklist.clone()),
             sortorder: "a".into(),
+            created_at: None,
+            created_by: None,
             right: Ok(task.clone()),
         };
         let contains2 = Contains {
             left: Ok(tasklist.clone()),
             sortorder: "a".into(),
+            created_at: None,
+            created_by: None,
             right: Ok(task.clone()),
         };
         let contains = contains?;
@@ -316,6 +606,8 @@ mod tests {
                 id: uuid!("0196b4c9-8447-7959-ae1f-72c7c8a3dd36"),
             }),
             sortorder: "try_contains_err_left".into(),
+            created_at: None,
+            created_by: None,
             right: Ok(task.clone()),
         };
         fn is_valid(relationship: Contains<TaskList, Task>) -> HelixFlowResult<()> {
@@ -358,6 +650,58 @@ mod tests {
         assert_eq!(new_task.id.get_version(), Some(uuid::Version::SortRand));
     }
 
+    #[test]
+    fn test_transition() {
+        let mut task = Task::new("Test Task", None);
+        assert_eq!(task.status, Status::Todo);
+        task.transition(Status::InProgress).unwrap();
+        task.transition(Status::Blocked).unwrap();
+        task.transition(Status::InProgress).unwrap();
+        task.transition(Status::Done).unwrap();
+        task.transition(Status::Todo).unwrap();
+        assert_eq!(task.status, Status::Todo);
+    }
+
+    #[test]
+    fn test_invalid_transition() {
+        let mut task = Task::new("Test Task", None);
+        task.transition(Status::Blocked).unwrap();
+        let err = task.transition(Status::Done).unwrap_err();
+        assert_matches!(
+            err,
+            HelixFlowError::InvalidTransition {
+                from: Status::Blocked,
+                to: Status::Done
+            }
+        );
+        assert_eq!(task.status, Status::Blocked);
+    }
+
+    #[test]
+    fn complete_and_reopen() {
+        let mut task = Task::new("Test Task", None);
+        let before = Utc::now();
+        task.complete().unwrap();
+        assert_eq!(task.status, Status::Done);
+        assert!(task.completed_at.unwrap() >= before);
+        assert_matches!(
+            task.complete(),
+            Err(HelixFlowError::InvalidTransition { .. })
+        );
+        task.reopen().unwrap();
+        assert_eq!(task.status, Status::Todo);
+        assert_eq!(task.completed_at, None);
+    }
+
+    #[test]
+    fn backlog_of_open_tasks() {
+        let tasklist =
+            TaskList::get(&TestBackend, &uuid!("0196fe23-7c01-7d6b-9e09-5968eb370549")).unwrap();
+        let open = TestBackend.backlog(&tasklist, false).unwrap();
+        assert_eq!(open.len(), 2);
+        assert_eq!(TestBackend.backlog(&tasklist, true).unwrap(), open);
+    }
+
     #[test]
     fn test_create_task() {
         let new_task = Task::new("Test Task 1", None);
@@ -365,6 +709,32 @@ mod tests {
         new_task.create(&backend).unwrap();
     }
 
+    #[test]
+    fn invalid_tasks_are_not_stored() {
+        let blank = Task::new("  ", None);
+        assert_matches!(
+            blank.create(&TestBackend),
+            Err(HelixFlowError::ValidationFailed { field, .. }) if field == "name"
+        );
+        // `TestBackend` would fail the update with `NotFound` if it was asked
+        let too_long = Task::new("x".repeat(MAX_NAME_LENGTH + 1), None);
+        assert_matches!(
+            too_long.update(&TestBackend),
+            Err(HelixFlowError::ValidationFailed { field, .. }) if field == "name"
+        );
+        let longest = Task {
+            name: "x".repeat(MAX_NAME_LENGTH).into(),
+            ..TestBackend
+                .get(&uuid!("0196b4c9-8447-7959-ae1f-72c7c8a3dd36"))
+                .unwrap()
+        };
+        longest.update(&TestBackend).unwrap();
+        assert_matches!(
+            TaskList::new("").validate(),
+            Err(HelixFlowError::ValidationFailed { .. })
+        );
+    }
+
     #[test]
     fn test_failed_to_create_task() {
         let new_task = Task::new("FAIL", None);
@@ -387,6 +757,30 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_normalised_task_created() {
+        let new_task = Task::new("  Padded  ", None);
+        let backend = TestBackend;
+        let err = new_task.create(&backend).unwrap_err();
+        assert_matches!(err, HelixFlowError::Mismatch { .. });
+        new_task
+            .create_with(&backend, &Verification::Normalised)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_ignore_fields() {
+        let new_task = Task::new("MISMATCH", None);
+        let backend = TestBackend;
+        new_task
+            .create_with(&backend, &Verification::IgnoreFields(vec!["id"]))
+            .unwrap();
+        let err = new_task
+            .create_with(&backend, &Verification::IgnoreFields(vec!["name"]))
+            .unwrap_err();
+        assert_matches!(err, HelixFlowError::Mismatch { .. });
+    }
+
     #[test]
     fn test_get_task() {
         let backend = TestBackend;
@@ -397,7 +791,17 @@ mod tests {
             Task {
                 name: "Task 1".into(),
                 id,
-                description: None
+                description: None,
+                due: None,
+                colour: None,
+                status: Status::Todo,
+                priority: Priority::P2,
+                estimate: Some(Duration::from_secs(2 * 60 * 60)),
+                actual: None,
+                completed_at: None,
+                pinned: false,
+                updated_at: Default::default(),
+                version: Default::default(),
             }
         );
     }
@@ -417,22 +821,94 @@ mod tests {
                 if itemtype == "Task" && id == uuid!("0196b4c9-8447-78db-ae8a-be68a8095aa2"));
     }
 
+    #[test]
+    fn test_update_task() {
+        let backend = TestBackend;
+        let mut task = Task::get(&backend, &uuid!("0196b4c9-8447-7959-ae1f-72c7c8a3dd36")).unwrap();
+        task.transition(Status::Done).unwrap();
+        task.update(&backend).unwrap();
+    }
+
+    #[test]
+    fn pin_task() {
+        let backend = TestBackend;
+        let mut task = Task::get(&backend, &uuid!("0196b4c9-8447-7959-ae1f-72c7c8a3dd36")).unwrap();
+        task.pin();
+        assert!(task.pinned);
+        task.update(&backend).unwrap();
+        task.unpin();
+        assert!(!task.pinned);
+    }
+
+    #[test]
+    fn test_update_unknown_task() {
+        let backend = TestBackend;
+        let err = Task::new("Test Task", None).update(&backend).unwrap_err();
+        assert_matches!(err, HelixFlowError::NotFound { .. });
+    }
+
+    #[test]
+    fn test_mismatched_task_updated() {
+        let backend = TestBackend;
+        let task = Task {
+            name: "MISMATCH".into(),
+            ..Task::get(&backend, &uuid!("0196b4c9-8447-7959-ae1f-72c7c8a3dd36")).unwrap()
+        };
+        let err = task.update(&backend).unwrap_err();
+        assert_matches!(err, HelixFlowError::Mismatch { .. });
+    }
+
+    #[test]
+    fn test_delete_task() {
+        let backend = TestBackend;
+        let task = Task::get(&backend, &uuid!("0196b4c9-8447-7959-ae1f-72c7c8a3dd36")).unwrap();
+        task.delete(&backend).unwrap();
+    }
+
+    #[test]
+    fn test_delete_unknown_task() {
+        let backend = TestBackend;
+        let err = Task::new("Test Task", None).delete(&backend).unwrap_err();
+        assert_matches!(err, HelixFlowError::NotFound { .. });
+    }
+
     #[test]
     fn get_tasks_in_tasklist() {
         let backend = TestBackend;
         let backlog = TaskList {
             name: "Backlog".into(),
             id: uuid!("0196fe23-7c01-7d6b-9e09-5968eb370549"),
+            sort: Default::default(),
         };
         let task1 = Task {
             name: "Task 1".into(),
             id: uuid!("0196b4c9-8447-7959-ae1f-72c7c8a3dd36"),
             description: None,
+            due: None,
+            colour: None,
+            status: Status::Todo,
+            priority: Priority::P2,
+            estimate: Some(Duration::from_secs(2 * 60 * 60)),
+            actual: None,
+            completed_at: None,
+            pinned: false,
+            updated_at: Default::default(),
+            version: Default::default(),
         };
         let task2 = Task {
             name: "Task 2".into(),
             id: uuid!("0196ca5f-d934-7ec8-b042-ae37b94b8432"),
             description: None,
+            due: None,
+            colour: None,
+            status: Status::Todo,
+            priority: Priority::P2,
+            estimate: None,
+            actual: None,
+            completed_at: None,
+            pinned: false,
+            updated_at: Default::default(),
+            version: Default::default(),
         };
         let tasks: Vec<Contains<TaskList, Task>> =
             backlog.get_linked_items(&backend).unwrap().collect();
@@ -447,11 +923,12 @@ mod tests {
 
     #[test]
     fn create_task_in_tasklist() {
-        use crate::task::{Contains, Link};
+        use crate::{Link, task::Contains};
         let backend = TestBackend;
         let backlog = TaskList {
             name: "Backlog".into(),
             id: uuid!("0196fe23-7c01-7d6b-9e09-5968eb370549"),
+            sort: Default::default(),
         };
         let task3 = Task::new("Test task 3", None);
         let relationship: Contains<TaskList, Task> = backlog.link(&task3);
@@ -460,11 +937,12 @@ mod tests {
 
     #[test]
     fn create_task_in_tasklist_mismatch() {
-        use crate::task::{Contains, Link};
+        use crate::{Link, task::Contains};
         let backend = TestBackend;
         let backlog = TaskList {
             name: "Backlog".into(),
             id: uuid!("0196fe23-7c01-7d6b-9e09-5968eb370549"),
+            sort: Default::default(),
         };
         let task3 = Task::new("MISMATCH", None);
         let relationship: Contains<TaskList, Task> = backlog.link(&task3);
@@ -0,0 +1,302 @@
+//! Anonymous, opt-in usage metrics. Only compiled with `--features telemetry`.
+//!
+//! Events deliberately carry no names, ids or content - only _what kind_ of thing happened.
+//! They are buffered locally and handed to an `Exporter` in batches. Nothing is collected unless
+//! `Settings::telemetry_path` is set.
+
+use std::{
+    collections::BTreeMap,
+    fs::OpenOptions,
+    io::Write,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use anyhow::Context;
+use serde::Serialize;
+
+use crate::{
+    HelixFlowError,
+    events::{self, Event, Subscription},
+    settings::Settings,
+};
+
+/// Something worth counting.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum TelemetryEvent {
+    TaskCreated,
+    ViewOpened { view: &'static str },
+    Error { kind: &'static str },
+}
+
+impl From<&HelixFlowError> for TelemetryEvent {
+    fn from(error: &HelixFlowError) -> Self {
+        let kind = match error {
+            HelixFlowError::BackendError(_) => "BackendError",
+            HelixFlowError::Mismatch { .. } => "Mismatch",
+            HelixFlowError::InvalidID { .. } => "InvalidID",
+            HelixFlowError::NotFound { .. } => "NotFound",
+            HelixFlowError::RelationshipBetweenErrors { .. } => "RelationshipBetweenErrors",
+            HelixFlowError::CircuitOpen { .. } => "CircuitOpen",
+            HelixFlowError::ShareNotValid { .. } => "ShareNotValid",
+            HelixFlowError::InvalidLink { .. } => "InvalidLink",
+            HelixFlowError::InvalidValue { .. } => "InvalidValue",
+            HelixFlowError::InvalidTransition { .. } => "InvalidTransition",
+            HelixFlowError::DependencyCycle { .. } => "DependencyCycle",
+            HelixFlowError::ValidationFailed { .. } => "ValidationFailed",
+            HelixFlowError::Conflict { .. } => "Conflict",
+        };
+        TelemetryEvent::Error { kind }
+    }
+}
+
+impl TelemetryEvent {
+    /// What to count for a change on the event bus, if anything.
+    pub fn from_change(event: &Event) -> Option<Self> {
+        match event {
+            Event::Created {
+                itemtype: "Task", ..
+            } => Some(TelemetryEvent::TaskCreated),
+            _ => None,
+        }
+    }
+}
+
+/// Delivers a batch of events to wherever they are collected.
+pub trait Exporter {
+    fn export(&self, events: &[TelemetryEvent]) -> anyhow::Result<()>;
+}
+
+/// Appends each event to the file at `path` as a line of JSON, for a log shipper or collector
+/// to pick up.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JsonLinesExporter {
+    pub path: PathBuf,
+}
+
+impl Exporter for JsonLinesExporter {
+    fn export(&self, events: &[TelemetryEvent]) -> anyhow::Result<()> {
+        let mut lines = String::new();
+        for event in events {
+            lines += &serde_json::to_string(event)?;
+            lines.push('\n');
+        }
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut file| file.write_all(lines.as_bytes()))
+            .with_context(|| format!("Exporting telemetry to {:#?}", self.path))
+    }
+}
+
+/// Running totals of everything recorded since startup.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Counts {
+    pub tasks_created: u64,
+    pub views: BTreeMap<&'static str, u64>,
+    pub errors: u64,
+}
+
+/// Local buffer of `TelemetryEvent`s, flushed via `exporter`.
+#[derive(Debug)]
+pub struct Telemetry<E: Exporter> {
+    exporter: E,
+    /// Flush automatically once this many events are buffered.
+    batch_size: usize,
+    buffer: Mutex<Vec<TelemetryEvent>>,
+    counts: Mutex<Counts>,
+}
+
+impl<E: Exporter> Telemetry<E> {
+    pub fn new(exporter: E, batch_size: usize) -> Self {
+        Telemetry {
+            exporter,
+            batch_size,
+            buffer: Mutex::new(Vec::with_capacity(batch_size)),
+            counts: Mutex::new(Counts::default()),
+        }
+    }
+
+    /// Buffer an event, flushing if the batch is full.
+    ///
+    /// Export failures are not propagated: telemetry must never break the app. The events stay
+    /// buffered and are retried on the next flush.
+    pub fn record(&self, event: TelemetryEvent) {
+        {
+            let mut counts = self.counts.lock().unwrap();
+            match &event {
+                TelemetryEvent::TaskCreated => counts.tasks_created += 1,
+                TelemetryEvent::ViewOpened { view } => *counts.views.entry(view).or_default() += 1,
+                TelemetryEvent::Error { .. } => counts.errors += 1,
+            }
+        }
+        let full = {
+            let mut buffer = self.buffer.lock().unwrap();
+            buffer.push(event);
+            buffer.len() >= self.batch_size
+        };
+        if full {
+            let _ = self.flush();
+        }
+    }
+
+    /// Export all buffered events now.
+    pub fn flush(&self) -> anyhow::Result<()> {
+        let mut buffer = self.buffer.lock().unwrap();
+        if buffer.is_empty() {
+            return Ok(());
+        }
+        self.exporter.export(&buffer)?;
+        buffer.clear();
+        Ok(())
+    }
+
+    pub fn counts(&self) -> Counts {
+        self.counts.lock().unwrap().clone()
+    }
+
+    pub fn exporter(&self) -> &E {
+        &self.exporter
+    }
+}
+
+impl<E: Exporter + Send + Sync + 'static> Telemetry<E> {
+    /// Record every change on the event bus worth counting, until the `Subscription` is dropped.
+    pub fn collect(self: &Arc<Self>) -> Subscription {
+        let telemetry = self.clone();
+        events::subscribe(move |event| {
+            if let Some(event) = TelemetryEvent::from_change(event) {
+                telemetry.record(event);
+            }
+        })
+    }
+}
+
+impl Telemetry<JsonLinesExporter> {
+    /// Telemetry written to the file in `settings`, or `None` if the user has not opted in.
+    pub fn opted_in(settings: &Settings, batch_size: usize) -> Option<Self> {
+        let path = settings.telemetry_path()?.to_path_buf();
+        Some(Telemetry::new(JsonLinesExporter { path }, batch_size))
+    }
+}
+
+#[cfg(test)]
+#[coverage(off)]
+mod tests {
+    use anyhow::anyhow;
+    use uuid::{Uuid, uuid};
+
+    use super::*;
+    use crate::{
+        CRUD,
+        task::{Task, TestBackend},
+    };
+
+    #[derive(Default)]
+    struct TestExporter {
+        fail: bool,
+        exported: Mutex<Vec<Vec<TelemetryEvent>>>,
+    }
+
+    impl Exporter for TestExporter {
+        fn export(&self, events: &[TelemetryEvent]) -> anyhow::Result<()> {
+            if self.fail {
+                return Err(anyhow!("offline"));
+            }
+            self.exported.lock().unwrap().push(events.to_vec());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn counts() {
+        let telemetry = Telemetry::new(TestExporter::default(), 10);
+        telemetry.record(TelemetryEvent::TaskCreated);
+        telemetry.record(TelemetryEvent::TaskCreated);
+        telemetry.record(TelemetryEvent::ViewOpened { view: "Backlog" });
+        telemetry.record(
+            (&HelixFlowError::NotFound {
+                itemtype: "Task".into(),
+                id: uuid!("0196b4c9-8447-7959-ae1f-72c7c8a3dd36"),
+            })
+                .into(),
+        );
+        let counts = telemetry.counts();
+        assert_eq!(counts.tasks_created, 2);
+        assert_eq!(counts.views.get("Backlog"), Some(&1));
+        assert_eq!(counts.errors, 1);
+    }
+
+    #[test]
+    fn flush_when_batch_full() {
+        let telemetry = Telemetry::new(TestExporter::default(), 2);
+        telemetry.record(TelemetryEvent::TaskCreated);
+        assert!(telemetry.exporter.exported.lock().unwrap().is_empty());
+        telemetry.record(TelemetryEvent::Error { kind: "NotFound" });
+        let exported = telemetry.exporter.exported.lock().unwrap();
+        assert_eq!(
+            *exported,
+            vec![vec![
+                TelemetryEvent::TaskCreated,
+                TelemetryEvent::Error { kind: "NotFound" }
+            ]]
+        );
+    }
+
+    #[test]
+    fn collect_created_tasks() {
+        let telemetry = Arc::new(Telemetry::new(TestExporter::default(), 10));
+        let subscription = telemetry.collect();
+        Task::new("Counted", None).create(&TestBackend).unwrap();
+        drop(subscription);
+        Task::new("Not counted", None).create(&TestBackend).unwrap();
+        // Other tests may be creating tasks at the same time
+        assert!(telemetry.counts().tasks_created >= 1);
+        assert!(
+            telemetry
+                .buffer
+                .lock()
+                .unwrap()
+                .iter()
+                .all(|event| *event == TelemetryEvent::TaskCreated)
+        );
+    }
+
+    #[test]
+    fn only_when_opted_in() {
+        let mut settings = Settings::new(&Uuid::now_v7());
+        assert!(Telemetry::opted_in(&settings, 10).is_none());
+        settings.share_telemetry(Some("metrics.jsonl"));
+        let telemetry = Telemetry::opted_in(&settings, 10).unwrap();
+        assert_eq!(telemetry.exporter().path, PathBuf::from("metrics.jsonl"));
+    }
+
+    #[test]
+    fn export_json_lines() {
+        let path = std::env::temp_dir().join(format!("{}.jsonl", Uuid::now_v7()));
+        let exporter = JsonLinesExporter { path: path.clone() };
+        exporter.export(&[TelemetryEvent::TaskCreated]).unwrap();
+        exporter
+            .export(&[TelemetryEvent::ViewOpened { view: "Trash" }])
+            .unwrap();
+        let exported = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(
+            exported,
+            "\"TaskCreated\"\n{\"ViewOpened\":{\"view\":\"Trash\"}}\n"
+        );
+    }
+
+    #[test]
+    fn keep_events_if_export_fails() {
+        let exporter = TestExporter {
+            fail: true,
+            ..Default::default()
+        };
+        let telemetry = Telemetry::new(exporter, 1);
+        telemetry.record(TelemetryEvent::TaskCreated);
+        assert_eq!(telemetry.buffer.lock().unwrap().len(), 1);
+        assert!(telemetry.flush().is_err());
+    }
+}
@@ -0,0 +1,120 @@
+//! When items were created & last changed, e.g. to sort by recency or to spot conflicting changes
+//! when syncing.
+//!
+//! Every item knows when it was created from its UUIDv7 `id`. Items which also need to know when
+//! they last changed add an `updated_at: LastModified` field, which `CRUD` maintains.
+
+use std::cmp::Ordering;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// When an item was last stored via `CRUD::create` or `CRUD::update`, `None` if it never was.
+///
+/// This describes the stored record rather than the item itself, so it is ignored when comparing
+/// items: every `LastModified` is equal to every other. Compare the timestamps themselves with
+/// `HelixFlowItem::updated_at`.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct LastModified(pub Option<DateTime<Utc>>);
+
+impl PartialEq for LastModified {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for LastModified {}
+
+impl PartialOrd for LastModified {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for LastModified {
+    fn cmp(&self, _other: &Self) -> Ordering {
+        Ordering::Equal
+    }
+}
+
+/// When the item with this `id` was created, if it is a UUIDv7.
+pub fn created_at(id: &Uuid) -> Option<DateTime<Utc>> {
+    let (secs, nanos) = id.get_timestamp()?.to_unix();
+    DateTime::from_timestamp(secs.try_into().ok()?, nanos)
+}
+
+#[cfg(test)]
+#[coverage(off)]
+mod tests {
+    use std::sync::Mutex;
+
+    use chrono::TimeZone;
+    use uuid::uuid;
+
+    use super::*;
+    use crate::{CRUD, HelixFlowError, HelixFlowItem, HelixFlowResult, Store, task::Task};
+
+    /// Keeps the last task it was sent
+    #[derive(Default)]
+    struct LastSaved(Mutex<Option<Task>>);
+
+    impl Store<Task> for LastSaved {
+        fn create(&self, task: &Task) -> HelixFlowResult<Task> {
+            *self.0.lock().unwrap() = Some(task.clone());
+            Ok(task.clone())
+        }
+
+        fn get(&self, id: &Uuid) -> HelixFlowResult<Task> {
+            self.0
+                .lock()
+                .unwrap()
+                .clone()
+                .ok_or(HelixFlowError::NotFound {
+                    itemtype: "Task".into(),
+                    id: *id,
+                })
+        }
+
+        fn update(&self, task: &Task) -> HelixFlowResult<Task> {
+            self.create(task)
+        }
+
+        fn delete(&self, _id: &Uuid) -> HelixFlowResult<()> {
+            *self.0.lock().unwrap() = None;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn created_from_id() {
+        let task = Task {
+            id: uuid!("0196b4c9-8447-7959-ae1f-72c7c8a3dd36"),
+            ..Task::new("Task 1", None)
+        };
+        assert_eq!(
+            task.created_at(),
+            Some(Utc.timestamp_millis_opt(1_746_789_827_655).unwrap())
+        );
+        assert_eq!(created_at(&Uuid::nil()), None);
+    }
+
+    #[test]
+    fn updated_by_crud() {
+        let backend = LastSaved::default();
+        let task = Task::new("Task", None);
+        assert_eq!(task.updated_at(), None);
+
+        let before = Utc::now();
+        task.create(&backend).unwrap();
+        let created = Task::get(&backend, &task.id).unwrap();
+        assert!(created.updated_at().unwrap() >= before);
+        // Only the record changed, not the task
+        assert_eq!(created, task);
+
+        created.update(&backend).unwrap();
+        let updated = Task::get(&backend, &task.id).unwrap();
+        assert!(updated.updated_at() >= created.updated_at());
+    }
+}
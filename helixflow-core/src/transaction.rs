@@ -0,0 +1,101 @@
+//! All-or-nothing changes spanning several backend calls.
+//!
+//! Wrap multi-step operations in `atomically`. Backends which support transactions implement
+//! `begin`, `commit` & `rollback`; the others keep the default, which makes each step as it
+//! happens, so a failure part-way through leaves the earlier steps in place.
+
+use crate::{HelixFlowResult, task::TestBackend};
+
+/// A backend which can group changes so that either all or none of them are stored.
+pub trait Transaction: Send + Sync {
+    /// Start grouping changes. The default does nothing.
+    fn begin(&self) -> HelixFlowResult<()> {
+        Ok(())
+    }
+
+    /// Store all changes since `begin`. The default does nothing.
+    fn commit(&self) -> HelixFlowResult<()> {
+        Ok(())
+    }
+
+    /// Discard all changes since `begin`. The default does nothing, so changes already made
+    /// remain.
+    fn rollback(&self) -> HelixFlowResult<()> {
+        Ok(())
+    }
+}
+
+/// Run `steps` in a transaction on `backend`: commit if they succeed, roll back if they fail.
+/// The error from `steps` is returned even if rolling back also fails.
+pub fn atomically<B, T>(
+    backend: &B,
+    steps: impl FnOnce() -> HelixFlowResult<T>,
+) -> HelixFlowResult<T>
+where
+    B: Transaction,
+{
+    backend.begin()?;
+    match steps() {
+        Ok(result) => {
+            backend.commit()?;
+            Ok(result)
+        }
+        Err(e) => {
+            let _ = backend.rollback();
+            Err(e)
+        }
+    }
+}
+
+/// Best effort.
+impl Transaction for TestBackend {}
+
+#[cfg(test)]
+#[coverage(off)]
+mod tests {
+    use std::{assert_matches::assert_matches, sync::Mutex};
+
+    use super::*;
+    use crate::HelixFlowError;
+
+    /// Records the calls made
+    #[derive(Default)]
+    struct Recorder(Mutex<Vec<&'static str>>);
+
+    impl Transaction for Recorder {
+        fn begin(&self) -> HelixFlowResult<()> {
+            self.0.lock().unwrap().push("begin");
+            Ok(())
+        }
+
+        fn commit(&self) -> HelixFlowResult<()> {
+            self.0.lock().unwrap().push("commit");
+            Ok(())
+        }
+
+        fn rollback(&self) -> HelixFlowResult<()> {
+            self.0.lock().unwrap().push("rollback");
+            Err(anyhow::anyhow!("rollback failed").into())
+        }
+    }
+
+    #[test]
+    fn commit_or_rollback() {
+        let backend = Recorder::default();
+        assert_eq!(atomically(&backend, || Ok(1)).unwrap(), 1);
+        let failed: HelixFlowResult<()> = atomically(&backend, || {
+            Err(HelixFlowError::InvalidID {
+                id: "step failed".into(),
+            })
+        });
+        assert_matches!(failed, Err(HelixFlowError::InvalidID { .. }));
+        assert_eq!(
+            *backend.0.lock().unwrap(),
+            vec!["begin", "commit", "begin", "rollback"]
+        );
+        assert_eq!(
+            atomically(&TestBackend, || Ok("best effort")).unwrap(),
+            "best effort"
+        );
+    }
+}
@@ -0,0 +1,189 @@
+//! Deleted tasks go to the trash first, so they can be restored until the trash is emptied or
+//! they have been there longer than `RETENTION`.
+
+use chrono::{DateTime, TimeDelta, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::{Uuid, uuid};
+
+use crate::{
+    CRUD, HelixFlowError, HelixFlowResult, Link, Relate, Store,
+    task::{Contains, Priority, Status, Task, TaskList, TestBackend},
+    transaction::{Transaction, atomically},
+};
+
+/// How long a task stays in the trash before `expire` removes it for good.
+pub const RETENTION: TimeDelta = TimeDelta::days(30);
+
+/// A deleted task & where it came from.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Trashed {
+    pub task: Task,
+    /// The list the task was in, to restore it there
+    pub tasklist: Option<Uuid>,
+    pub deleted_at: DateTime<Utc>,
+}
+
+impl Trashed {
+    /// When `expire` will remove this task for good.
+    pub fn expires_at(&self) -> DateTime<Utc> {
+        self.deleted_at + RETENTION
+    }
+}
+
+/// Keep deleted tasks until they are restored or the trash is emptied. Trashed tasks are
+/// identified by the id of the task.
+pub trait Trash: Send + Sync {
+    /// Keep `trashed`, the task itself must be deleted separately.
+    fn put_in_trash(&self, trashed: &Trashed) -> HelixFlowResult<()>;
+
+    /// Everything in the trash, most recently deleted first.
+    fn trashed(&self) -> HelixFlowResult<Vec<Trashed>>;
+
+    /// Remove the task with `id` from the trash and return it.
+    fn take_from_trash(&self, id: &Uuid) -> HelixFlowResult<Trashed>;
+
+    /// Permanently remove everything deleted before `deleted_before`, returning how many tasks
+    /// were removed.
+    fn purge_trash(&self, deleted_before: DateTime<Utc>) -> HelixFlowResult<usize>;
+
+    /// Permanently remove everything in the trash.
+    fn empty_trash(&self) -> HelixFlowResult<usize> {
+        self.purge_trash(DateTime::<Utc>::MAX_UTC)
+    }
+}
+
+impl Task {
+    /// Delete this task, keeping it in the trash so it can be restored to `tasklist`.
+    ///
+    /// Only the task itself is restored, not its subtasks, tags or other links.
+    pub fn trash<B>(
+        &self,
+        tasklist: Option<&TaskList>,
+        backend: &B,
+        now: DateTime<Utc>,
+    ) -> HelixFlowResult<()>
+    where
+        B: Trash + Store<Task> + Transaction,
+    {
+        atomically(backend, || {
+            backend.put_in_trash(&Trashed {
+                task: self.clone(),
+                tasklist: tasklist.map(|tasklist| tasklist.id),
+                deleted_at: now,
+            })?;
+            self.delete(backend)
+        })
+    }
+}
+
+/// Take the task with `id` out of the trash and store it again, at the end of the list it was
+/// in. If that list no longer exists the task is stored without a list.
+pub fn restore<B>(backend: &B, id: &Uuid) -> HelixFlowResult<Task>
+where
+    B: Trash + Store<Task> + Store<TaskList> + Relate<Contains<TaskList, Task>> + Transaction,
+{
+    atomically(backend, || restore_steps(backend, id))
+}
+
+fn restore_steps<B>(backend: &B, id: &Uuid) -> HelixFlowResult<Task>
+where
+    B: Trash + Store<Task> + Store<TaskList> + Relate<Contains<TaskList, Task>>,
+{
+    let trashed = backend.take_from_trash(id)?;
+    let tasklist = match trashed.tasklist {
+        Some(id) => match TaskList::get(backend, &id) {
+            Ok(tasklist) => Some(tasklist),
+            Err(HelixFlowError::NotFound { .. }) => None,
+            Err(e) => return Err(e),
+        },
+        None => None,
+    };
+    match tasklist {
+        Some(tasklist) => tasklist.link(&trashed.task).create_linked_item(backend)?,
+        None => trashed.task.create(backend)?,
+    }
+    Ok(trashed.task)
+}
+
+/// Permanently remove everything which has been in the trash longer than `RETENTION` at `now`.
+pub fn expire<B: Trash>(backend: &B, now: DateTime<Utc>) -> HelixFlowResult<usize> {
+    backend.purge_trash(now - RETENTION)
+}
+
+fn test_trashed() -> Trashed {
+    Trashed {
+        task: Task {
+            name: "Deleted task".into(),
+            id: uuid!("0199c2d0-5e6f-7a8b-9c0d-1e2f3a4b5c6d"),
+            description: None,
+            due: None,
+            colour: None,
+            status: Status::Todo,
+            priority: Priority::P2,
+            estimate: None,
+            actual: None,
+            completed_at: None,
+            pinned: false,
+            updated_at: Default::default(),
+            version: Default::default(),
+        },
+        tasklist: Some(uuid!("0196fe23-7c01-7d6b-9e09-5968eb370549")),
+        deleted_at: Utc.with_ymd_and_hms(2025, 6, 1, 12, 0, 0).unwrap(),
+    }
+}
+
+/// `Deleted task` was deleted from `Test TaskList 1` on 1 June 2025.
+impl Trash for TestBackend {
+    fn put_in_trash(&self, _trashed: &Trashed) -> HelixFlowResult<()> {
+        Ok(())
+    }
+
+    fn trashed(&self) -> HelixFlowResult<Vec<Trashed>> {
+        Ok(vec![test_trashed()])
+    }
+
+    fn take_from_trash(&self, id: &Uuid) -> HelixFlowResult<Trashed> {
+        Some(test_trashed())
+            .filter(|trashed| trashed.task.id == *id)
+            .ok_or(HelixFlowError::NotFound {
+                itemtype: "Trashed".into(),
+                id: *id,
+            })
+    }
+
+    fn purge_trash(&self, deleted_before: DateTime<Utc>) -> HelixFlowResult<usize> {
+        Ok(usize::from(test_trashed().deleted_at < deleted_before))
+    }
+}
+
+#[cfg(test)]
+#[coverage(off)]
+mod tests {
+    use std::assert_matches::assert_matches;
+
+    use super::*;
+
+    #[test]
+    fn restore_from_trash() {
+        let trashed = &TestBackend.trashed().unwrap()[0];
+        assert_eq!(
+            trashed.expires_at(),
+            Utc.with_ymd_and_hms(2025, 7, 1, 12, 0, 0).unwrap()
+        );
+        let restored = restore(&TestBackend, &trashed.task.id).unwrap();
+        assert_eq!(restored, trashed.task);
+        assert_matches!(
+            restore(&TestBackend, &Uuid::now_v7()),
+            Err(HelixFlowError::NotFound { .. })
+        );
+    }
+
+    #[test]
+    fn expire_old_tasks() {
+        let soon = Utc.with_ymd_and_hms(2025, 6, 2, 0, 0, 0).unwrap();
+        assert_eq!(expire(&TestBackend, soon).unwrap(), 0);
+        let later = Utc.with_ymd_and_hms(2025, 7, 2, 0, 0, 0).unwrap();
+        assert_eq!(expire(&TestBackend, later).unwrap(), 1);
+        assert_eq!(TestBackend.empty_trash().unwrap(), 1);
+    }
+}
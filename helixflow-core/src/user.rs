@@ -0,0 +1,58 @@
+//! People who share a backend, and the tasks they keep an eye on.
+
+use std::borrow::Cow;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{HelixFlowItem, HelixFlowResult, Relationship, Validate, task::Task};
+
+/// Someone using a shared backend. Not an account: there is no authentication.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, HelixFlowItem)]
+pub struct User {
+    /// Used for `@mentions`, so should not contain whitespace
+    pub name: Cow<'static, str>,
+    pub id: Uuid,
+}
+
+impl Validate for User {}
+
+impl User {
+    /// Create a new `User` with valid `id`, suitable for usage as database key.
+    pub fn new<S>(name: S) -> User
+    where
+        S: Into<Cow<'static, str>>,
+    {
+        User {
+            name: name.into(),
+            id: Uuid::now_v7(),
+        }
+    }
+
+    /// Start watching `task`.
+    pub fn watch<B: Watch>(&self, task: &Task, backend: &B) -> HelixFlowResult<()> {
+        backend.create_watch(&Watches {
+            left: self.clone(),
+            right: task.clone(),
+        })
+    }
+}
+
+/// `left` wants to be notified about changes to `right`. Both must already exist.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Watches<LEFT, RIGHT> {
+    pub left: LEFT,
+    pub right: RIGHT,
+}
+
+impl Relationship for Watches<User, Task> {
+    type Left = User;
+    type Right = Task;
+    const NAME: &'static str = "Watches";
+}
+
+/// Methods to record and look up who is watching a task
+pub trait Watch {
+    fn create_watch(&self, watches: &Watches<User, Task>) -> HelixFlowResult<()>;
+    fn watchers(&self, task: &Task) -> HelixFlowResult<Vec<User>>;
+}
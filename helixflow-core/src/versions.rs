@@ -0,0 +1,158 @@
+//! Optimistic concurrency: spot changes which would overwrite someone else's.
+//!
+//! Backends count how often each record has been stored. Items read from a backend remember that
+//! version in a `version: Version` field, and `Store::update` fails with
+//! `HelixFlowError::Conflict` if the record has been stored again since. Items which were not
+//! read from a backend, e.g. built from UI fields, overwrite whatever is stored.
+//!
+//! To resolve a conflict, get the stored item again and either reapply the change to it or
+//! discard the change.
+
+use std::cmp::Ordering;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{HelixFlowError, HelixFlowItem, HelixFlowResult};
+
+/// The version of the stored record an item was read from, `None` if it was not read from a
+/// backend. Maintained by backends, see `next_version`.
+///
+/// As with `LastModified`, this describes the stored record rather than the item itself, so it
+/// is ignored when comparing items. Compare versions with `HelixFlowItem::version`.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Version(pub Option<u64>);
+
+impl PartialEq for Version {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for Version {}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, _other: &Self) -> Ordering {
+        Ordering::Equal
+    }
+}
+
+impl Version {
+    /// The version stored by successfully updating an item read at this version.
+    pub fn next(self) -> Version {
+        Version(self.0.map(|version| version + 1))
+    }
+}
+
+/// The version to store `item` as, replacing a record at version `stored`.
+///
+/// Fails with `HelixFlowError::Conflict` if `item` was read at a different version, i.e. the
+/// record has been stored again since.
+pub fn next_version<ITEM: HelixFlowItem>(item: &ITEM, stored: u64) -> HelixFlowResult<u64> {
+    match item.version() {
+        Some(expected) if expected != stored => Err(HelixFlowError::Conflict {
+            itemtype: item.itemtype().into(),
+            id: *item.id(),
+            expected,
+            stored,
+        }),
+        _ => Ok(stored + 1),
+    }
+}
+
+#[cfg(test)]
+#[coverage(off)]
+mod tests {
+    use std::{assert_matches::assert_matches, sync::Mutex};
+
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::{CRUD, Store, task::Task};
+
+    /// Keeps a single versioned task
+    #[derive(Default)]
+    struct Versioned(Mutex<Option<Task>>);
+
+    impl Store<Task> for Versioned {
+        fn create(&self, task: &Task) -> HelixFlowResult<Task> {
+            let stored = Task {
+                version: Version(Some(0)),
+                ..task.clone()
+            };
+            *self.0.lock().unwrap() = Some(stored.clone());
+            Ok(stored)
+        }
+
+        fn get(&self, id: &Uuid) -> HelixFlowResult<Task> {
+            self.0
+                .lock()
+                .unwrap()
+                .clone()
+                .ok_or(HelixFlowError::NotFound {
+                    itemtype: "Task".into(),
+                    id: *id,
+                })
+        }
+
+        fn update(&self, task: &Task) -> HelixFlowResult<Task> {
+            let stored = self.get(&task.id)?;
+            let stored = Task {
+                version: Version(Some(next_version(task, stored.version().unwrap())?)),
+                ..task.clone()
+            };
+            *self.0.lock().unwrap() = Some(stored.clone());
+            Ok(stored)
+        }
+
+        fn delete(&self, _id: &Uuid) -> HelixFlowResult<()> {
+            *self.0.lock().unwrap() = None;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn conflicting_updates() {
+        let backend = Versioned::default();
+        let task = Task::new("Task", None);
+        assert_eq!(task.version(), None);
+        task.create(&backend).unwrap();
+
+        let mut mine = Task::get(&backend, &task.id).unwrap();
+        let mut theirs = Task::get(&backend, &task.id).unwrap();
+        theirs.name = "Their name".into();
+        theirs.update(&backend).unwrap();
+        mine.name = "My name".into();
+        assert_matches!(
+            mine.update(&backend),
+            Err(HelixFlowError::Conflict {
+                expected: 0,
+                stored: 1,
+                ..
+            })
+        );
+
+        // Reapply the change to the stored task
+        mine = Task::get(&backend, &task.id).unwrap();
+        mine.name = "My name".into();
+        mine.update(&backend).unwrap();
+        let stored = Task::get(&backend, &task.id).unwrap();
+        assert_eq!(stored.version, mine.version.next());
+        assert_eq!(stored.version(), Some(2));
+
+        // Not read from the backend, so nothing to conflict with
+        Task {
+            name: "Overwritten".into(),
+            ..task
+        }
+        .update(&backend)
+        .unwrap();
+        assert_eq!(Task::get(&backend, &task.id).unwrap().name, "Overwritten");
+    }
+}
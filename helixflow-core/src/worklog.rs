@@ -0,0 +1,216 @@
+//! Time spent working on tasks.
+
+use std::borrow::Cow;
+
+use chrono::{DateTime, TimeDelta, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::{Uuid, uuid};
+
+use crate::{
+    HelixFlowError, HelixFlowItem, HelixFlowResult, Linkable, Relate, Relationship, Validate,
+    task::{Contains, Task, TaskList, TestBackend},
+};
+
+/// A period of work on a task, with an optional note of what was done.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, HelixFlowItem)]
+pub struct WorkLog {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub note: Option<Cow<'static, str>>,
+    pub id: Uuid,
+}
+
+/// Work cannot end before it started.
+impl Validate for WorkLog {
+    fn validate(&self) -> HelixFlowResult<()> {
+        if self.end < self.start {
+            return Err(HelixFlowError::ValidationFailed {
+                field: "end".into(),
+                reason: format!("must not be before the start ({})", self.start.to_rfc3339()),
+            });
+        }
+        Ok(())
+    }
+}
+
+impl WorkLog {
+    /// Create a new `WorkLog` with valid `id`, suitable for usage as database key.
+    ///
+    /// Fails with `InvalidValue` if `end` is before `start`.
+    pub fn new<S>(
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        note: Option<S>,
+    ) -> HelixFlowResult<WorkLog>
+    where
+        S: Into<Cow<'static, str>>,
+    {
+        if end < start {
+            return Err(HelixFlowError::InvalidValue {
+                field: "end".into(),
+                value: end.to_rfc3339(),
+            });
+        }
+        Ok(WorkLog {
+            start,
+            end,
+            note: note.map(Into::into),
+            id: Uuid::now_v7(),
+        })
+    }
+
+    pub fn duration(&self) -> TimeDelta {
+        self.end - self.start
+    }
+}
+
+impl Task {
+    /// Record `worklog` against this task, ready for `create_linked_item`.
+    pub fn log(&self, worklog: &WorkLog) -> LoggedAgainst<Task, WorkLog> {
+        Linkable::<LoggedAgainst<Task, WorkLog>>::link(self, worklog)
+    }
+
+    /// All time recorded against this task.
+    pub fn worklogs<B>(&self, backend: &B) -> HelixFlowResult<Vec<WorkLog>>
+    where
+        B: Relate<LoggedAgainst<Task, WorkLog>>,
+    {
+        Linkable::<LoggedAgainst<Task, WorkLog>>::get_linked_items(self, backend)?
+            .map(|logged| logged.right)
+            .collect()
+    }
+
+    /// Total time recorded against this task.
+    pub fn time_logged<B>(&self, backend: &B) -> HelixFlowResult<TimeDelta>
+    where
+        B: Relate<LoggedAgainst<Task, WorkLog>>,
+    {
+        Ok(self.worklogs(backend)?.iter().map(WorkLog::duration).sum())
+    }
+}
+
+impl TaskList {
+    /// Total time recorded against all tasks in this list.
+    pub fn time_logged<B>(&self, backend: &B) -> HelixFlowResult<TimeDelta>
+    where
+        B: Relate<Contains<TaskList, Task>> + Relate<LoggedAgainst<Task, WorkLog>>,
+    {
+        let mut total = TimeDelta::zero();
+        for contains in self.get_linked_items(backend)? {
+            total += contains.right?.time_logged(backend)?;
+        }
+        Ok(total)
+    }
+}
+
+/// `right` was spent working on `left`.
+///
+/// The task must already exist, `Relate::create_linked_item` creates the `WorkLog`.
+#[derive(Debug, Relationship)]
+#[relationship(Task => WorkLog)]
+pub struct LoggedAgainst<LEFT, RIGHT> {
+    pub left: HelixFlowResult<LEFT>,
+    pub right: HelixFlowResult<RIGHT>,
+}
+
+/// `Task 1` has 90 minutes logged against it, no other task has any.
+impl Relate<LoggedAgainst<Task, WorkLog>> for TestBackend {
+    fn create_linked_item(
+        &self,
+        link: &LoggedAgainst<Task, WorkLog>,
+    ) -> HelixFlowResult<LoggedAgainst<Task, WorkLog>> {
+        Ok(LoggedAgainst {
+            left: Ok(link.left.as_ref().unwrap().clone()),
+            right: Ok(link.right.as_ref().unwrap().clone()),
+        })
+    }
+    fn get_linked_items(
+        &self,
+        left: &Task,
+    ) -> HelixFlowResult<impl Iterator<Item = LoggedAgainst<Task, WorkLog>>> {
+        let worklogs = match left.id.to_string().as_str() {
+            "0196b4c9-8447-7959-ae1f-72c7c8a3dd36" => vec![
+                WorkLog {
+                    start: Utc.with_ymd_and_hms(2025, 6, 2, 9, 0, 0).unwrap(),
+                    end: Utc.with_ymd_and_hms(2025, 6, 2, 10, 0, 0).unwrap(),
+                    note: Some("Planning".into()),
+                    id: uuid!("0197c2d4-1a2b-7c3d-8e4f-5a6b7c8d9e0f"),
+                },
+                WorkLog {
+                    start: Utc.with_ymd_and_hms(2025, 6, 3, 14, 0, 0).unwrap(),
+                    end: Utc.with_ymd_and_hms(2025, 6, 3, 14, 30, 0).unwrap(),
+                    note: None,
+                    id: uuid!("0197c2d4-1a2b-7c3d-8e4f-5a6b7c8d9e10"),
+                },
+            ],
+            _ => vec![],
+        };
+        let left = left.clone();
+        Ok(worklogs.into_iter().map(move |worklog| left.log(&worklog)))
+    }
+    fn delete_link(&self, _link: &LoggedAgainst<Task, WorkLog>) -> HelixFlowResult<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[coverage(off)]
+mod tests {
+    use std::assert_matches::assert_matches;
+
+    use super::*;
+    use crate::{CRUD, Link};
+
+    #[test]
+    fn invalid_period() {
+        let start = Utc.with_ymd_and_hms(2025, 6, 2, 9, 0, 0).unwrap();
+        assert_matches!(
+            WorkLog::new(start, start - TimeDelta::minutes(1), None::<&str>),
+            Err(HelixFlowError::InvalidValue { field, .. }) if field == "end"
+        );
+        let worklog = WorkLog::new(start, start, Some("Quick check")).unwrap();
+        assert_eq!(worklog.duration(), TimeDelta::zero());
+        worklog.validate().unwrap();
+        // Changed after it was created
+        let reversed = WorkLog {
+            start: start + TimeDelta::minutes(1),
+            ..worklog
+        };
+        assert_matches!(
+            reversed.validate(),
+            Err(HelixFlowError::ValidationFailed { field, .. }) if field == "end"
+        );
+    }
+
+    #[test]
+    fn time_logged_per_task() {
+        let task_1 =
+            Task::get(&TestBackend, &uuid!("0196b4c9-8447-7959-ae1f-72c7c8a3dd36")).unwrap();
+        let task_2 =
+            Task::get(&TestBackend, &uuid!("0196ca5f-d934-7ec8-b042-ae37b94b8432")).unwrap();
+        assert_eq!(task_1.worklogs(&TestBackend).unwrap().len(), 2);
+        assert_eq!(
+            task_1.time_logged(&TestBackend).unwrap(),
+            TimeDelta::minutes(90)
+        );
+        assert_eq!(task_2.time_logged(&TestBackend).unwrap(), TimeDelta::zero());
+    }
+
+    #[test]
+    fn time_logged_per_tasklist() {
+        let tasklist =
+            TaskList::get(&TestBackend, &uuid!("0196fe23-7c01-7d6b-9e09-5968eb370549")).unwrap();
+        assert_eq!(
+            tasklist.time_logged(&TestBackend).unwrap(),
+            TimeDelta::minutes(90)
+        );
+    }
+
+    #[test]
+    fn log_time() {
+        let task = Task::new("Write report", None);
+        let start = Utc.with_ymd_and_hms(2025, 6, 2, 9, 0, 0).unwrap();
+        let worklog = WorkLog::new(start, start + TimeDelta::hours(2), None::<&str>).unwrap();
+        task.log(&worklog).create_linked_item(&TestBackend).unwrap();
+    }
+}
@@ -0,0 +1,174 @@
+//! Separate sets of projects, lists & tasks, e.g. to keep personal and work tasks apart.
+//!
+//! Each workspace is kept in its own backend namespace. `State` remembers all workspaces and
+//! which one is open, see `State::switch_workspace`.
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::HelixFlowResult;
+
+/// The namespace used before any workspaces were added.
+pub const DEFAULT_NAMESPACE: &str = "HelixFlow";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Workspace {
+    pub name: String,
+    /// Where the workspace's items are stored
+    pub namespace: String,
+    /// The project shown when the workspace was last open
+    #[serde(default)]
+    pub visible_project: Option<Uuid>,
+    /// The backlog shown when the workspace was last open
+    #[serde(default)]
+    pub visible_backlog: Option<Uuid>,
+}
+
+impl Default for Workspace {
+    fn default() -> Self {
+        Workspace {
+            name: "Personal".into(),
+            namespace: DEFAULT_NAMESPACE.into(),
+            visible_project: None,
+            visible_backlog: None,
+        }
+    }
+}
+
+impl Workspace {
+    /// A new workspace, stored in a namespace made from the letters & digits in `name`.
+    pub fn new<S: Into<String>>(name: S) -> Self {
+        let name = name.into();
+        let namespace = name
+            .chars()
+            .filter(|char| char.is_alphanumeric())
+            .flat_map(char::to_lowercase)
+            .collect();
+        Workspace {
+            name,
+            namespace,
+            visible_project: None,
+            visible_backlog: None,
+        }
+    }
+}
+
+/// A backend which can keep items in separate namespaces.
+pub trait Namespaces: Send + Sync {
+    /// Store & find all items in `namespace` from now on, creating it if needed.
+    fn use_namespace(&self, namespace: &str) -> HelixFlowResult<()>;
+}
+
+#[cfg(test)]
+#[coverage(off)]
+mod tests {
+    use std::{assert_matches::assert_matches, collections::HashMap, sync::Mutex};
+
+    use super::*;
+    use crate::{CRUD, HelixFlowError, Store, state::State, task::TaskList};
+
+    /// Keeps one `State` per namespace
+    #[derive(Default)]
+    struct Spaces {
+        namespace: Mutex<String>,
+        states: Mutex<HashMap<String, State>>,
+    }
+
+    impl Namespaces for Spaces {
+        fn use_namespace(&self, namespace: &str) -> HelixFlowResult<()> {
+            *self.namespace.lock().unwrap() = namespace.into();
+            Ok(())
+        }
+    }
+
+    impl Store<State> for Spaces {
+        fn create(&self, state: &State) -> HelixFlowResult<State> {
+            let namespace = self.namespace.lock().unwrap().clone();
+            self.states.lock().unwrap().insert(namespace, state.clone());
+            Ok(state.clone())
+        }
+
+        fn get(&self, id: &Uuid) -> HelixFlowResult<State> {
+            let namespace = self.namespace.lock().unwrap().clone();
+            self.states
+                .lock()
+                .unwrap()
+                .get(&namespace)
+                .cloned()
+                .ok_or(HelixFlowError::NotFound {
+                    itemtype: "State".into(),
+                    id: *id,
+                })
+        }
+
+        fn update(&self, state: &State) -> HelixFlowResult<State> {
+            self.get(&state.id)?;
+            self.create(state)
+        }
+
+        fn delete(&self, _id: &Uuid) -> HelixFlowResult<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn separate_workspaces() {
+        let backend = Spaces::default();
+        let mut state = State::new(&Uuid::now_v7());
+        let personal = TaskList::new("Chores");
+        state.visible_backlog(&personal);
+        assert_eq!(state.current_workspace().name, "Personal");
+
+        let work = Workspace::new("Day Job");
+        assert_eq!(work.namespace, "dayjob");
+        state.add_workspace(work.clone()).unwrap();
+        assert_matches!(
+            state.add_workspace(Workspace::new("day-job")),
+            Err(HelixFlowError::InvalidValue { .. })
+        );
+        assert_matches!(
+            state.add_workspace(Workspace::new("!")),
+            Err(HelixFlowError::InvalidValue { .. })
+        );
+        assert_eq!(
+            state
+                .workspaces()
+                .iter()
+                .map(|workspace| workspace.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["Personal", "Day Job"]
+        );
+
+        state.switch_workspace("dayjob", &backend).unwrap();
+        assert_eq!(*backend.namespace.lock().unwrap(), "dayjob");
+        assert_eq!(state.current_workspace().namespace, "dayjob");
+        assert_eq!(state.visible_backlog_id(), &None);
+        let sprint = TaskList::new("Sprint");
+        state.visible_backlog(&sprint);
+        // Next time, the default namespace points to the open workspace
+        backend.use_namespace(DEFAULT_NAMESPACE).unwrap();
+        let stored = State::get(&backend, &state.id).unwrap();
+        assert_eq!(stored.current_workspace().name, "Day Job");
+
+        state.switch_workspace(DEFAULT_NAMESPACE, &backend).unwrap();
+        assert_eq!(state.visible_backlog_id(), &Some(personal.id));
+        state.switch_workspace("dayjob", &backend).unwrap();
+        assert_eq!(state.visible_backlog_id(), &Some(sprint.id));
+        assert_matches!(
+            state.switch_workspace("missing", &backend),
+            Err(HelixFlowError::InvalidValue { .. })
+        );
+    }
+
+    #[test]
+    fn put_state() {
+        let backend = Spaces::default();
+        let mut state = State::new(&Uuid::now_v7());
+        state.put(&backend).unwrap();
+        assert_eq!(State::get(&backend, &state.id).unwrap(), state);
+
+        state.visible_backlog(&TaskList::new("Chores"));
+        state.put(&backend).unwrap();
+        assert_eq!(State::get(&backend, &state.id).unwrap(), state);
+    }
+}
@@ -0,0 +1,364 @@
+//! Derive macros for `helixflow_core`, to avoid copy-pasting the boilerplate needed for each new
+//! item or relationship.
+//!
+//! The generated code refers to `::helixflow_core`, so `helixflow_core` declares
+//! `extern crate self as helixflow_core` to use these macros itself.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{quote, quote_spanned};
+use syn::{
+    Data, DeriveInput, Error, Fields, Ident, Result, Token, Type, parse::ParseStream,
+    parse_macro_input,
+};
+
+/// Derive `HelixFlowItem` for a struct with an `id: Uuid` field, which is used as its database
+/// key.
+///
+/// Add an `updated_at: LastModified` field to record when the item was last stored, which `CRUD`
+/// then maintains, and a `version: Version` field for backends to detect conflicting changes.
+///
+/// Also checks that the struct is `Debug + Send + Sync + 'static`, as `HelixFlowItem` requires,
+/// reporting any missing bound against the struct rather than deep inside an error type.
+///
+/// ```ignore
+/// #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, HelixFlowItem)]
+/// pub struct Tag {
+///     pub name: Cow<'static, str>,
+///     pub id: Uuid,
+/// }
+/// ```
+#[proc_macro_derive(HelixFlowItem)]
+pub fn derive_helixflow_item(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    helixflow_item(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn helixflow_item(input: &DeriveInput) -> Result<TokenStream2> {
+    let name = &input.ident;
+    if !input.generics.params.is_empty() {
+        return Err(Error::new_spanned(
+            &input.generics,
+            "a HelixFlowItem cannot be generic, it is stored in a single table",
+        ));
+    }
+    let Data::Struct(data) = &input.data else {
+        return Err(Error::new_spanned(name, "a HelixFlowItem must be a struct"));
+    };
+    let Some(id) = data
+        .fields
+        .iter()
+        .find(|field| field.ident.as_ref().is_some_and(|ident| ident == "id"))
+    else {
+        return Err(Error::new_spanned(
+            name,
+            "a HelixFlowItem needs an `id: Uuid` field, used as its database key",
+        ));
+    };
+    let id_type = &id.ty;
+    let itemtype = name.to_string();
+    let has_field = |name: &str| {
+        data.fields
+            .iter()
+            .any(|field| field.ident.as_ref().is_some_and(|ident| ident == name))
+    };
+    let updated_at = has_field("updated_at").then(|| {
+        quote! {
+            fn updated_at(&self) -> ::std::option::Option<::chrono::DateTime<::chrono::Utc>> {
+                self.updated_at.0
+            }
+            fn stamped(&self, at: ::chrono::DateTime<::chrono::Utc>) -> Self {
+                Self {
+                    updated_at: ::helixflow_core::timestamps::LastModified(
+                        ::std::option::Option::Some(at),
+                    ),
+                    ..::std::clone::Clone::clone(self)
+                }
+            }
+        }
+    });
+    let version = has_field("version").then(|| {
+        quote! {
+            fn version(&self) -> ::std::option::Option<u64> {
+                self.version.0
+            }
+        }
+    });
+    let check_bounds = quote_spanned! {name.span()=>
+        const _: fn() = || {
+            fn item_bounds<T: ::helixflow_core::ItemBounds>() {}
+            item_bounds::<#name>();
+        };
+    };
+    Ok(quote! {
+        #check_bounds
+
+        impl ::helixflow_core::HelixFlowItem for #name {
+            fn as_any(&self) -> &dyn ::std::any::Any {
+                self
+            }
+            fn id(&self) -> &#id_type {
+                &self.id
+            }
+            fn itemtype(&self) -> &'static str {
+                #itemtype
+            }
+            #updated_at
+            #version
+        }
+    })
+}
+
+/// Derive `Relationship`, `Try`, `FromResidual`, `Link` & `Linkable` for a relationship struct.
+///
+/// The struct must take two generic parameters, `<LEFT, RIGHT>`, and have fields
+/// `left: HelixFlowResult<LEFT>` & `right: HelixFlowResult<RIGHT>`. Any other fields must
+/// implement `Default`, which `Linkable::link` uses for them.
+///
+/// List the valid pairings in `#[relationship(...)]` attributes, as `Left => Right`. Add
+/// `manual_link` to write `Link` yourself, e.g. to validate links before they are stored.
+///
+/// ```ignore
+/// /// `right` is attached to `left`.
+/// #[derive(Debug, Relationship)]
+/// #[relationship(Task => Attachment)]
+/// pub struct HasAttachment<LEFT, RIGHT> {
+///     pub left: HelixFlowResult<LEFT>,
+///     pub right: HelixFlowResult<RIGHT>,
+/// }
+/// ```
+///
+/// The generated `Try` impl needs `#![feature(try_trait_v2)]` in the crate using the derive.
+#[proc_macro_derive(Relationship, attributes(relationship))]
+pub fn derive_relationship(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    relationship(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// What the `#[relationship(...)]` attributes ask for.
+#[derive(Default)]
+struct RelationshipArgs {
+    pairings: Vec<(Type, Type)>,
+    manual_link: bool,
+}
+
+impl RelationshipArgs {
+    fn parse(input: &DeriveInput) -> Result<RelationshipArgs> {
+        let mut args = RelationshipArgs::default();
+        for attr in input
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("relationship"))
+        {
+            attr.parse_args_with(|entries: ParseStream| {
+                while !entries.is_empty() {
+                    let fork = entries.fork();
+                    let is_flag =
+                        fork.parse::<Ident>().is_ok() && (fork.is_empty() || fork.peek(Token![,]));
+                    if is_flag {
+                        let flag: Ident = entries.parse()?;
+                        if flag != "manual_link" {
+                            return Err(Error::new_spanned(
+                                flag,
+                                "expected `Left => Right` or `manual_link`",
+                            ));
+                        }
+                        args.manual_link = true;
+                    } else {
+                        let left: Type = entries.parse()?;
+                        entries.parse::<Token![=>]>()?;
+                        let right: Type = entries.parse()?;
+                        args.pairings.push((left, right));
+                    }
+                    if !entries.is_empty() {
+                        entries.parse::<Token![,]>()?;
+                    }
+                }
+                Ok(())
+            })?;
+        }
+        if args.pairings.is_empty() {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "list the valid pairings, e.g. `#[relationship(Task => Tag)]`",
+            ));
+        }
+        Ok(args)
+    }
+}
+
+fn relationship(input: &DeriveInput) -> Result<TokenStream2> {
+    let name = &input.ident;
+    let args = RelationshipArgs::parse(input)?;
+    let generics: Vec<&Ident> = input
+        .generics
+        .type_params()
+        .map(|param| &param.ident)
+        .collect();
+    let [left, right] = generics[..] else {
+        return Err(Error::new_spanned(
+            &input.generics,
+            "a relationship takes exactly two type parameters: `<LEFT, RIGHT>`",
+        ));
+    };
+    let Data::Struct(data) = &input.data else {
+        return Err(Error::new_spanned(name, "a relationship must be a struct"));
+    };
+    let Fields::Named(fields) = &data.fields else {
+        return Err(Error::new_spanned(
+            &data.fields,
+            "a relationship needs named `left` & `right` fields",
+        ));
+    };
+    let field_names: Vec<&Ident> = fields
+        .named
+        .iter()
+        .filter_map(|field| field.ident.as_ref())
+        .collect();
+    for required in ["left", "right"] {
+        if !field_names.iter().any(|field| *field == required) {
+            return Err(Error::new_spanned(
+                &data.fields,
+                format!("a relationship needs a `{required}` field"),
+            ));
+        }
+    }
+    let other_fields = field_names
+        .iter()
+        .filter(|field| **field != "left" && **field != "right");
+    let misuse = format!("{name}? should only be used in functions returning a Result");
+    let relationship_name = name.to_string();
+
+    let relationships = args.pairings.iter().map(|(left_type, right_type)| {
+        quote! {
+            impl ::helixflow_core::Relationship for #name<#left_type, #right_type> {
+                type Left = #left_type;
+                type Right = #right_type;
+                const NAME: &'static str = #relationship_name;
+            }
+        }
+    });
+
+    let link = (!args.manual_link).then(|| {
+        quote! {
+            impl<#left, #right> ::helixflow_core::Link for #name<#left, #right>
+            where
+                #name<#left, #right>: ::helixflow_core::Relationship,
+                #left: ::helixflow_core::HelixFlowItem,
+                #right: ::helixflow_core::HelixFlowItem + ::std::clone::Clone + ::std::cmp::PartialEq,
+            {
+                fn create_linked_item<B: ::helixflow_core::Relate<#name<#left, #right>>>(
+                    self,
+                    backend: &B,
+                ) -> ::helixflow_core::HelixFlowResult<()> {
+                    let mut valid_relationship = self?;
+                    // Backends store `right` if it is new, see `CRUD::create`
+                    valid_relationship.right = valid_relationship.right.map(|right| {
+                        ::helixflow_core::HelixFlowItem::stamped(&right, ::chrono::Utc::now())
+                    });
+                    let created = backend.create_linked_item(&valid_relationship)?;
+                    let left = created.left?;
+                    let expected = valid_relationship.right?;
+                    match created.right {
+                        Ok(right) if right == expected => {
+                            ::helixflow_core::events::emit(::helixflow_core::events::Event::Linked {
+                                relationship: #relationship_name,
+                                left: *::helixflow_core::HelixFlowItem::id(&left),
+                                right: *::helixflow_core::HelixFlowItem::id(&right),
+                            });
+                            Ok(())
+                        }
+                        Ok(_) => Err(::helixflow_core::HelixFlowError::Mismatch {
+                            expected: ::std::boxed::Box::new(expected.clone()),
+                            actual: ::std::boxed::Box::new(created.right?.clone()),
+                        }),
+                        Err(e) => Err(e),
+                    }
+                }
+            }
+        }
+    });
+
+    Ok(quote! {
+        #(#relationships)*
+
+        impl<#left, #right> ::std::ops::Try for #name<#left, #right>
+        where
+            #name<#left, #right>: ::helixflow_core::Relationship,
+        {
+            type Output = Self; // Continue
+            type Residual = Self; // Break
+            fn branch(self) -> ::std::ops::ControlFlow<Self::Residual, Self::Output> {
+                if self.left.is_ok() && self.right.is_ok() {
+                    ::std::ops::ControlFlow::Continue(self)
+                } else {
+                    ::std::ops::ControlFlow::Break(self)
+                }
+            }
+            fn from_output(_output: Self::Output) -> Self {
+                unimplemented!(#misuse)
+            }
+        }
+
+        impl<#left, #right> ::std::ops::FromResidual<#name<#left, #right>> for #name<#left, #right>
+        where
+            #name<#left, #right>: ::helixflow_core::Relationship,
+        {
+            fn from_residual(_residual: #name<#left, #right>) -> Self {
+                unimplemented!(#misuse)
+            }
+        }
+
+        impl<#left, #right> ::std::ops::FromResidual<#name<#left, #right>>
+            for ::helixflow_core::HelixFlowResult<()>
+        where
+            #name<#left, #right>: ::helixflow_core::Relationship,
+            #left: ::helixflow_core::HelixFlowItem,
+            #right: ::helixflow_core::HelixFlowItem,
+        {
+            fn from_residual(residual: #name<#left, #right>) -> Self {
+                Err(::helixflow_core::HelixFlowError::RelationshipBetweenErrors {
+                    left: match residual.left {
+                        Ok(item) => ::std::boxed::Box::new(Ok(::std::boxed::Box::new(item))),
+                        Err(e) => ::std::boxed::Box::new(Err(e)),
+                    },
+                    right: match residual.right {
+                        Ok(item) => ::std::boxed::Box::new(Ok(::std::boxed::Box::new(item))),
+                        Err(e) => ::std::boxed::Box::new(Err(e)),
+                    },
+                })
+            }
+        }
+
+        #link
+
+        impl<#left, #right> ::helixflow_core::Linkable<#name<#left, #right>> for #left
+        where
+            #name<#left, #right>: ::helixflow_core::Relationship<Left = #left, Right = #right>
+                + ::helixflow_core::Link,
+            #left: ::helixflow_core::HelixFlowItem + ::std::clone::Clone + ::std::cmp::PartialEq,
+            #right: ::helixflow_core::HelixFlowItem + ::std::clone::Clone + ::std::cmp::PartialEq,
+        {
+            fn link(&self, right: &#right) -> #name<#left, #right> {
+                #name {
+                    left: Ok(self.clone()),
+                    right: Ok(right.clone()),
+                    #(#other_fields: ::std::default::Default::default(),)*
+                }
+            }
+            fn get_linked_items<B>(
+                &self,
+                backend: &B,
+            ) -> ::helixflow_core::HelixFlowResult<impl Iterator<Item = #name<#left, #right>>>
+            where
+                B: ::helixflow_core::Relate<#name<#left, #right>>,
+            {
+                backend.get_linked_items(self)
+            }
+        }
+    })
+}
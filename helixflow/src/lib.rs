@@ -1,38 +1,519 @@
 #![feature(coverage_attribute)]
 #![feature(if_let_guard)]
 #![coverage(off)]
-use std::{path::PathBuf, rc::Rc};
+use std::{
+    cell::RefCell,
+    fs,
+    io::{BufRead, BufReader, Write},
+    net::TcpListener,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, OnceLock, Weak},
+    thread,
+    time::Duration,
+};
 
-use log::debug;
-use slint::ComponentHandle;
+use chrono::Utc;
+use global_hotkey::{
+    GlobalHotKeyEvent, GlobalHotKeyManager, HotKeyState,
+    hotkey::{CMD_OR_CTRL, Code, HotKey, Modifiers},
+};
+use log::{debug, error};
+use slint::{ComponentHandle, Global, PhysicalPosition, PhysicalSize};
 
-use helixflow_core::{CRUD, HelixFlowError, state::State, task::TaskList};
+use helixflow_core::{
+    CRUD, HelixFlowError, HelixFlowResult, Link as _, Linkable,
+    deeplink::DeepLink,
+    events::{self, Event, Subscribe, Subscription},
+    graph::DotGraph,
+    interop::{Export, ExportMode},
+    project::{Project, Projects},
+    relative, reminders,
+    settings::Settings,
+    share::{self, ShareResponse},
+    state::{State, WindowGeometry},
+    task::{Task, TaskList},
+    trash,
+    workspace::{Namespaces, Workspace},
+};
 use helixflow_slint::{
-    HelixFlow,
-    task::{create_task, create_task_in_backlog, load_backlog},
+    CurrentTask, Dashboard, HelixFlow, QuickCapture, SlintTask, TrashView, Triage,
+    contexts::{load_contexts, toggle_context},
+    dashboard::load_dashboard,
+    errors,
+    lists::{SharedListPanel, load_list_tree, select_list, toggle_list},
+    project::{SharedProjects, load_projects, select_project},
+    reminders::show_reminders,
+    task::{
+        assign_task, capture_task, change_status, create_task, create_task_in_backlog,
+        load_arranged_backlog, toggle_pin,
+    },
+    trash::{delete_task, empty_trash, load_trash, restore_task},
+    triage::{TriageQueue, load_triage, triage_due, triage_key},
+    undo::{SharedState, redo, undo},
+    workspace::show_workspaces,
 };
-use helixflow_surreal::SurrealDb;
-use uuid::uuid;
+use helixflow_surreal::{Db, ExportFormat, SurrealDb};
+use uuid::{Uuid, uuid};
 
-pub fn run_helixflow() {
-    debug!("Starting HelixFlow...");
+#[cfg(feature = "telemetry")]
+use helixflow_core::telemetry::{JsonLinesExporter, Telemetry, TelemetryEvent};
+
+/// Usage metrics, if the user opted in, see `start_telemetry`.
+#[cfg(feature = "telemetry")]
+static TELEMETRY: OnceLock<Arc<Telemetry<JsonLinesExporter>>> = OnceLock::new();
 
+/// Events buffered before they are written out, the rest are written on exit.
+#[cfg(feature = "telemetry")]
+const TELEMETRY_BATCH: usize = 20;
+
+/// Collect usage metrics, if `settings` opt in.
+#[cfg(feature = "telemetry")]
+fn start_telemetry(settings: &Settings) {
+    let Some(telemetry) = Telemetry::opted_in(settings, TELEMETRY_BATCH) else {
+        return;
+    };
+    TELEMETRY
+        .get_or_init(|| Arc::new(telemetry))
+        .collect()
+        .detach();
+}
+
+/// Count `event`, if collecting usage metrics.
+#[cfg(feature = "telemetry")]
+fn record_usage(event: TelemetryEvent) {
+    if let Some(telemetry) = TELEMETRY.get() {
+        telemetry.record(event);
+    }
+}
+
+/// Show `e` to the user, counting it in the usage metrics.
+fn show_error(helixflow: &HelixFlow, e: &HelixFlowError) {
+    #[cfg(feature = "telemetry")]
+    record_usage(e.into());
+    errors::show_error(helixflow, e);
+}
+
+fn db_path() -> PathBuf {
+    let mut db_path = PathBuf::new();
+    db_path.push("helixflow.db");
+    db_path
+}
+
+/// Where older versions saved the database on exit, imported when `db_path` is first created.
+fn legacy_export() -> PathBuf {
     let mut db_file = PathBuf::new();
     db_file.push("helixflow.kv");
+    db_file
+}
 
-    let backend = Rc::new(SurrealDb::new(Some(db_file)).unwrap());
-    let helixflow = HelixFlow::new().unwrap();
+fn open_database() -> anyhow::Result<SurrealDb<Db>> {
+    let path = db_path();
+    let new = !path.exists();
+    let backend = SurrealDb::open(&path)?;
+    if new {
+        backend.import(&legacy_export())?;
+    }
+    Ok(backend)
+}
 
+fn open_backend() -> SurrealDb<Db> {
+    open_database().unwrap()
+}
+
+/// The UI state saved last time, created on first run. If it cannot be loaded, `report` why and
+/// start from the defaults.
+fn load_ui_state(backend: &SurrealDb<Db>, report: impl FnOnce(&HelixFlowError)) -> State {
     let state_id = uuid!("867bb83c-730a-4470-9fcd-14359cf5292b");
-    let mut ui_state = match State::get(backend.as_ref(), &state_id) {
-        Ok(state) => state,
-        Err(e) => match e {
-            HelixFlowError::NotFound { itemtype, id } if itemtype == "State" && id == state_id => {
-                State::new(&state_id)
-            }
-            _ => panic!("{}", e),
-        },
+    let loaded = match State::get(backend, &state_id) {
+        Err(HelixFlowError::NotFound { itemtype, id }) if itemtype == "State" && id == state_id => {
+            let state = State::new(&state_id);
+            state.create(backend).map(|()| state)
+        }
+        loaded => loaded,
+    };
+    loaded.unwrap_or_else(|e| {
+        report(&e);
+        State::new(&state_id)
+    })
+}
+
+/// The user's settings, created on first run. If they cannot be loaded, `report` why and use the
+/// defaults.
+fn load_settings(backend: &SurrealDb<Db>, report: impl FnOnce(&HelixFlowError)) -> Settings {
+    let settings_id = uuid!("0197b3a6-5c1e-7f2d-9a4b-6e8c0d2f1a35");
+    let loaded = match Settings::get(backend, &settings_id) {
+        Err(HelixFlowError::NotFound { itemtype, id })
+            if itemtype == "Settings" && id == settings_id =>
+        {
+            let settings = Settings::new(&settings_id);
+            settings.create(backend).map(|()| settings)
+        }
+        loaded => loaded,
+    };
+    loaded.unwrap_or_else(|e| {
+        report(&e);
+        Settings::new(&settings_id)
+    })
+}
+
+/// Tell someone using the command line why the defaults are used, see `load_ui_state`.
+fn print_error(e: &HelixFlowError) {
+    eprintln!("Using the defaults: {e:#}");
+}
+
+/// Print the suggested next actions from the visible backlog to stdout
+pub fn print_next_actions() {
+    let backend = open_backend();
+    open_workspace(&backend, print_error);
+    let ui_state = load_ui_state(&backend, print_error);
+    let settings = load_settings(&backend, print_error);
+    let Some(id) = ui_state.visible_backlog_id() else {
+        println!("No backlog yet - start HelixFlow to create one");
+        return;
     };
+    let backlog = TaskList::get(&backend, id).unwrap();
+    let now = Utc::now();
+    for task in backlog.next_actions(&backend, 5).unwrap() {
+        match task.due {
+            Some(due) => println!(
+                "{} ({})",
+                task.name,
+                relative::due(due, now, settings.timezone(), settings.locale())
+            ),
+            None => println!("{}", task.name),
+        }
+    }
+}
+
+/// Print the visible backlog, its tasks and their tags as a Graphviz DOT graph to stdout
+pub fn print_dot() {
+    let backend = open_backend();
+    open_workspace(&backend, print_error);
+    let ui_state = load_ui_state(&backend, print_error);
+    let Some(id) = ui_state.visible_backlog_id() else {
+        eprintln!("No backlog yet - start HelixFlow to create one");
+        return;
+    };
+    let backlog = TaskList::get(&backend, id).unwrap();
+    print!("{}", DotGraph::of(&[backlog], &backend).unwrap().to_dot());
+}
+
+/// Print how many records each table of the open workspace holds, and the database's size.
+pub fn print_stats() {
+    let backend = open_backend();
+    open_workspace(&backend, print_error);
+    let stats = backend.stats().unwrap();
+    for (table, count) in &stats.records {
+        println!("{table}: {count}");
+    }
+    if let Some(bytes) = stats.size_on_disk {
+        println!("On disk: {:.1} MB", bytes as f64 / 1_000_000.0);
+    }
+}
+
+/// Save the open workspace to `path`: a portable JSON dump for `*.json`, otherwise SurrealQL.
+///
+/// A `Redacted` export is always JSON: every list in every project, with free text replaced, see
+/// `interop::Export`.
+pub fn export_database(path: &Path, mode: ExportMode) {
+    let backend = open_backend();
+    open_workspace(&backend, print_error);
+    match mode {
+        ExportMode::Full => backend
+            .export_to(path, ExportFormat::for_path(path))
+            .unwrap(),
+        ExportMode::Redacted => {
+            let mut tasklists = Vec::new();
+            for root in all_tasklists(&backend).unwrap() {
+                let tree = root.tree(&backend).unwrap();
+                tasklists.extend(tree.tasklists().into_iter().cloned());
+            }
+            let export = Export::of(&tasklists, &backend, mode).unwrap();
+            fs::write(path, export.to_json()).unwrap();
+        }
+    }
+    println!("Exported to {}", path.display());
+}
+
+/// Serve read-only views of shared lists at `http://<address>/share/<token>` until stopped.
+///
+/// Each request is answered by `share::respond`, so expired & revoked tokens are refused.
+pub fn serve_shares(address: &str) {
+    let backend = open_backend();
+    open_workspace(&backend, print_error);
+    let listener = TcpListener::bind(address).unwrap();
+    println!("Serving shared lists at http://{address}/share/<token>");
+    for stream in listener.incoming() {
+        let mut stream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                error!("Accepting a connection: {e}");
+                continue;
+            }
+        };
+        let mut request = String::new();
+        if let Err(e) = BufReader::new(&stream).read_line(&mut request) {
+            error!("Reading a request: {e}");
+            continue;
+        }
+        let mut request = request.split_whitespace();
+        let method = request.next().unwrap_or_default();
+        let path = request.next().unwrap_or_default();
+        let response = share::respond(&backend, method, path).unwrap_or_else(|e| {
+            error!("Serving {path}: {e:#}");
+            ShareResponse::new(500, "Something went wrong")
+        });
+        if let Err(e) = stream.write_all(response.to_http().as_bytes()) {
+            error!("Answering {path}: {e}");
+        }
+    }
+}
+
+/// Run the app, optionally opening the item at `link` (`helixflow://...`) as passed by the OS.
+pub fn run_helixflow(link: Option<DeepLink>) {
+    debug!("Starting HelixFlow...");
+
+    let helixflow = HelixFlow::new().unwrap();
+    helixflow.set_loading(true);
+    // Dropping the manager unregisters the hotkey, so keep it until the app closes
+    let _hotkeys = register_hotkeys(&helixflow);
+
+    // Callbacks only hold a `Weak` reference, so the backend is owned here until the app closes.
+    let backend: Arc<OnceLock<Arc<SurrealDb<Db>>>> = Arc::default();
+
+    // Opening the backend imports the whole database file, so do it in the background and
+    // connect the UI once it is ready.
+    let hf = helixflow.as_weak();
+    let be = backend.clone();
+    thread::spawn(move || {
+        let opened = open_database();
+        hf.upgrade_in_event_loop(move |helixflow| {
+            match opened {
+                Ok(db) => connect_backend(&helixflow, be.get_or_init(|| Arc::new(db)), link),
+                Err(e) => helixflow.set_error_message(
+                    format!("Could not open {}: {e:#}", db_path().display()).into(),
+                ),
+            }
+            helixflow.set_loading(false);
+        })
+        .unwrap();
+    });
+
+    helixflow.show().unwrap();
+    slint::run_event_loop().unwrap();
+    if let Some(backend) = backend.get() {
+        save_window(&helixflow, backend);
+    }
+    #[cfg(feature = "telemetry")]
+    if let Some(telemetry) = TELEMETRY.get()
+        && let Err(e) = telemetry.flush()
+    {
+        error!("Could not export usage metrics: {e:#}");
+    }
+    helixflow.hide().unwrap();
+}
+
+/// Open quick capture with Ctrl+Shift+N (Cmd on macOS) while any application has focus. Not every
+/// desktop allows this, e.g. Wayland, in which case the shortcut only works in the main window.
+fn register_hotkeys(helixflow: &HelixFlow) -> Option<GlobalHotKeyManager> {
+    let quick_capture = HotKey::new(Some(CMD_OR_CTRL | Modifiers::SHIFT), Code::KeyN);
+    let manager = GlobalHotKeyManager::new()
+        .and_then(|manager| manager.register(quick_capture).map(|()| manager))
+        .inspect_err(|e| error!("Could not register the quick capture hotkey: {e}"))
+        .ok()?;
+    let hf = helixflow.as_weak();
+    GlobalHotKeyEvent::set_event_handler(Some(move |event: GlobalHotKeyEvent| {
+        if event.id == quick_capture.id() && event.state == HotKeyState::Pressed {
+            let _ = hf.upgrade_in_event_loop(|helixflow| helixflow.invoke_quick_capture());
+        }
+    }));
+    Some(manager)
+}
+
+/// Remember where the window is and which task is selected, to restore them next time.
+fn save_window(helixflow: &HelixFlow, backend: &SurrealDb<Db>) {
+    let window = helixflow.window();
+    let (position, size) = (window.position(), window.size());
+    let mut ui_state = load_ui_state(backend, |e| show_error(helixflow, e));
+    ui_state.window(WindowGeometry {
+        x: position.x,
+        y: position.y,
+        width: size.width,
+        height: size.height,
+    });
+    let current = CurrentTask::get(helixflow).get_task();
+    if !current.id.is_empty()
+        && let Ok(task) = Task::try_from(current)
+    {
+        ui_state.select_task(&task);
+    }
+    if let Err(e) = ui_state.put(backend) {
+        error!("Could not save the window state: {e:#}");
+    }
+}
+
+/// How often to check for due reminders.
+const REMINDER_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Check for due reminders in the background every `REMINDER_INTERVAL` and notify the user about
+/// them, until the app closes.
+fn schedule_reminders(helixflow: slint::Weak<HelixFlow>, backend: Weak<SurrealDb<Db>>) {
+    thread::spawn(move || {
+        while let Some(db) = backend.upgrade() {
+            match reminders::fire_due(db.as_ref(), Utc::now()) {
+                Ok(tasks) if tasks.is_empty() => {}
+                Ok(tasks) => {
+                    let shown = helixflow
+                        .upgrade_in_event_loop(move |helixflow| show_reminders(&helixflow, &tasks));
+                    if shown.is_err() {
+                        break;
+                    }
+                }
+                Err(e) => error!("Could not check reminders: {e:#}"),
+            }
+            drop(db);
+            thread::sleep(REMINDER_INTERVAL);
+        }
+    });
+}
+
+/// Load the UI state from `backend`, show it in `helixflow` and connect all callbacks.
+fn connect_backend(helixflow: &HelixFlow, backend: &Arc<SurrealDb<Db>>, link: Option<DeepLink>) {
+    open_workspace(backend.as_ref(), |e| show_error(helixflow, e));
+
+    // Keep the backlog up to date, whichever callback (or background thread) changed it
+    let hf = helixflow.as_weak();
+    events::subscribe(move |event| {
+        let tasks_changed = match event {
+            Event::Created { itemtype, .. }
+            | Event::Updated { itemtype, .. }
+            | Event::Deleted { itemtype, .. } => *itemtype == "Task",
+            // Also sent as `Updated`
+            Event::TaskCompleted { .. } => false,
+            Event::Linked { relationship, .. } | Event::Unlinked { relationship, .. } => {
+                *relationship == "Contains"
+            }
+        };
+        if tasks_changed {
+            let _ = hf.upgrade_in_event_loop(|helixflow| helixflow.invoke_load_backlog());
+        }
+    })
+    .detach();
+
+    #[cfg(feature = "telemetry")]
+    start_telemetry(&load_settings(backend.as_ref(), |e| {
+        show_error(helixflow, e)
+    }));
+
+    schedule_reminders(helixflow.as_weak(), Arc::downgrade(backend));
+
+    let hf = helixflow.as_weak();
+    let be = Arc::downgrade(backend);
+    helixflow.on_switch_workspace(move |index| {
+        let helixflow = hf.unwrap();
+        let backend = be.upgrade().unwrap();
+        let mut ui_state = load_ui_state(backend.as_ref(), |e| show_error(&helixflow, e));
+        let Some(workspace) = ui_state.workspaces().get(index as usize).cloned() else {
+            return;
+        };
+        match ui_state.switch_workspace(&workspace.namespace, backend.as_ref()) {
+            Ok(()) => show_workspace(&helixflow, &backend, None),
+            Err(e) => show_error(&helixflow, &e),
+        }
+    });
+
+    let hf = helixflow.as_weak();
+    let be = Arc::downgrade(backend);
+    helixflow.on_add_workspace(move |name| {
+        let helixflow = hf.unwrap();
+        let backend = be.upgrade().unwrap();
+        let mut ui_state = load_ui_state(backend.as_ref(), |e| show_error(&helixflow, e));
+        match ui_state.add_workspace(Workspace::new(name.as_str())) {
+            Ok(()) => {
+                ui_state.put(backend.as_ref()).unwrap();
+                show_workspaces(&helixflow, &ui_state);
+            }
+            Err(e) => show_error(&helixflow, &e),
+        }
+    });
+
+    show_workspace(helixflow, backend, link);
+}
+
+/// Use the namespace of the workspace which was open last time.
+fn open_workspace(backend: &SurrealDb<Db>, report: impl FnOnce(&HelixFlowError)) {
+    let namespace = load_ui_state(backend, report).current_workspace().namespace;
+    backend.use_namespace(&namespace).unwrap();
+}
+
+/// Reloads the backlog when another process changes a task in the open workspace.
+static LIVE_TASKS: Mutex<Option<Subscription>> = Mutex::new(None);
+
+/// The top level lists of every project, for the dashboard & redacted exports.
+fn all_tasklists(backend: &SurrealDb<Db>) -> HelixFlowResult<Vec<TaskList>> {
+    let mut tasklists = Vec::new();
+    for project in backend.all_projects()? {
+        tasklists.extend(project.tasklists(backend)?);
+    }
+    Ok(tasklists)
+}
+
+/// Show `task` in the task box, with the name of the person it is assigned to.
+fn show_task_details(
+    helixflow: &HelixFlow,
+    backend: &SurrealDb<Db>,
+    task: Task,
+) -> HelixFlowResult<()> {
+    let assignee = task.assignee(backend)?;
+    CurrentTask::get(helixflow).set_task(SlintTask {
+        assignee: assignee
+            .map(|person| person.name.as_ref().into())
+            .unwrap_or_default(),
+        ..task.into()
+    });
+    Ok(())
+}
+
+/// Open the details of the linked task with `id`, in the project & list holding it if there is
+/// one.
+fn open_linked_task(
+    backend: &SurrealDb<Db>,
+    ui_state: &mut State,
+    id: &Uuid,
+) -> HelixFlowResult<()> {
+    let task = Task::get(backend, id)?;
+    ui_state.task_details(&task);
+    for project in backend.all_projects()? {
+        if let Some(tasklist) = project.tasklist_containing(&task, backend)? {
+            ui_state.visible_project(&project);
+            ui_state.visible_backlog(&tasklist);
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Show the open workspace in `helixflow` and connect the callbacks which depend on it.
+fn show_workspace(helixflow: &HelixFlow, backend: &Arc<SurrealDb<Db>>, link: Option<DeepLink>) {
+    backend.act_as(load_settings(backend.as_ref(), |e| show_error(helixflow, e)).current_actor());
+
+    // Changes made here are also reloaded by `connect_backend`'s subscriber: twice is harmless
+    let hf = helixflow.as_weak();
+    match Subscribe::<Task>::subscribe(backend.as_ref(), move |_| {
+        let _ = hf.upgrade_in_event_loop(|helixflow| helixflow.invoke_load_backlog());
+    }) {
+        Ok(subscription) => *LIVE_TASKS.lock().unwrap() = Some(subscription),
+        Err(e) => error!("Only showing changes to tasks made in this window: {e:#}"),
+    }
+    let mut ui_state = load_ui_state(backend.as_ref(), |e| show_error(helixflow, e));
+    show_workspaces(helixflow, &ui_state);
+
+    if let Some(DeepLink::Task(id)) = link {
+        match open_linked_task(backend.as_ref(), &mut ui_state, &id) {
+            Ok(()) => ui_state.put(backend.as_ref()).unwrap(),
+            Err(e) => show_error(helixflow, &e),
+        }
+    }
 
     let backlog = match ui_state.visible_backlog_id() {
         Some(id) => TaskList::get(backend.as_ref(), id).unwrap(),
@@ -40,27 +521,285 @@ pub fn run_helixflow() {
             let backlog = TaskList::new("This week");
             backlog.create(backend.as_ref()).unwrap();
             ui_state.visible_backlog(&backlog);
-            // TODO implement an Update in CRUD and create State earlier ...
-            ui_state.create(backend.as_ref()).unwrap();
+            ui_state.put(backend.as_ref()).unwrap();
             backlog
         }
     };
-    helixflow.set_backlog(backlog.into());
+    helixflow.set_backlog(backlog.clone().into());
+
+    if ui_state.visible_project_id().is_none() {
+        let mut projects = backend.all_projects().unwrap();
+        let project = if projects.is_empty() {
+            let project = Project::new("Personal", None::<&str>);
+            project.create(backend.as_ref()).unwrap();
+            project
+                .link(&backlog)
+                .create_linked_item(backend.as_ref())
+                .unwrap();
+            project
+        } else {
+            projects.remove(0)
+        };
+        ui_state.visible_project(&project);
+        ui_state.put(backend.as_ref()).unwrap();
+    }
+
+    if let Some(geometry) = ui_state.window_geometry() {
+        let window = helixflow.window();
+        window.set_position(PhysicalPosition::new(geometry.x, geometry.y));
+        window.set_size(PhysicalSize::new(geometry.width, geometry.height));
+    }
+
+    let view_mode = ui_state.current_view_mode();
+    helixflow.set_view_mode(view_mode.into());
+    helixflow.set_card_settings(ui_state.card_settings_for(view_mode).into());
+
+    if let Some(id) = ui_state.task_details_id().or(*ui_state.selected_task_id()) {
+        match Task::get(backend.as_ref(), &id) {
+            Ok(task) => {
+                if let Err(e) = show_task_details(helixflow, backend.as_ref(), task) {
+                    show_error(helixflow, &e);
+                }
+            }
+            Err(HelixFlowError::NotFound { .. }) => {
+                ui_state.close_task_details();
+                ui_state.put(backend.as_ref()).unwrap();
+            }
+            Err(e) => show_error(helixflow, &e),
+        }
+    }
+
+    let state = SharedState::new(RefCell::new(ui_state.clone()));
 
     let hf = helixflow.as_weak();
-    let be = Rc::downgrade(&backend);
-    helixflow.on_load_backlog(load_backlog(hf, be));
+    let be = Arc::downgrade(backend);
+    let settings = load_settings(backend.as_ref(), |e| show_error(helixflow, e));
+    helixflow.on_load_backlog(load_arranged_backlog(
+        hf,
+        be,
+        state.clone(),
+        settings.timezone(),
+        settings.locale(),
+    ));
     helixflow.invoke_load_backlog();
 
     let hf = helixflow.as_weak();
-    let be = Rc::downgrade(&backend);
-    helixflow.on_create_backlog_task(create_task_in_backlog(hf, be));
+    let be = Arc::downgrade(backend);
+    helixflow.on_create_backlog_task(create_task_in_backlog(hf, be, state.clone()));
 
     let hf = helixflow.as_weak();
-    let be = Rc::downgrade(&backend);
+    let be = Arc::downgrade(backend);
     helixflow.on_create_task(create_task(hf, be));
 
-    helixflow.show().unwrap();
-    slint::run_event_loop().unwrap();
-    helixflow.hide().unwrap();
+    let hf = helixflow.as_weak();
+    let be = Arc::downgrade(backend);
+    helixflow.on_change_view(move || {
+        let helixflow = hf.unwrap();
+        let backend = be.upgrade().unwrap();
+        let view_mode = helixflow.get_view_mode().into();
+        let mut ui_state = load_ui_state(backend.as_ref(), |e| show_error(&helixflow, e));
+        ui_state.view_mode(view_mode);
+        ui_state.put(backend.as_ref()).unwrap();
+        helixflow.set_card_settings(ui_state.card_settings_for(view_mode).into());
+    });
+
+    let hf = helixflow.as_weak();
+    let be = Arc::downgrade(backend);
+    helixflow.on_open_task_details(move |slint_task| {
+        let helixflow = hf.unwrap();
+        let backend = be.upgrade().unwrap();
+        let task = match Task::try_from(slint_task)
+            .and_then(|task| Task::get(backend.as_ref(), &task.id))
+        {
+            Ok(task) => task,
+            Err(e) => return show_error(&helixflow, &e),
+        };
+        let mut ui_state = load_ui_state(backend.as_ref(), |e| show_error(&helixflow, e));
+        ui_state.task_details(&task);
+        ui_state.select_task(&task);
+        ui_state.put(backend.as_ref()).unwrap();
+        if let Err(e) = show_task_details(&helixflow, backend.as_ref(), task) {
+            show_error(&helixflow, &e);
+        }
+    });
+
+    let hf = helixflow.as_weak();
+    let be = Arc::downgrade(backend);
+    helixflow.on_close_task_details(move || {
+        let helixflow = hf.unwrap();
+        let backend = be.upgrade().unwrap();
+        CurrentTask::get(&helixflow).set_task(SlintTask::default());
+        let mut ui_state = load_ui_state(backend.as_ref(), |e| show_error(&helixflow, e));
+        ui_state.close_task_details();
+        ui_state.deselect_task();
+        ui_state.put(backend.as_ref()).unwrap();
+    });
+
+    let hf = helixflow.as_weak();
+    let be = Arc::downgrade(backend);
+    helixflow.on_change_status(change_status(hf, be, state.clone()));
+
+    let hf = helixflow.as_weak();
+    let be = Arc::downgrade(backend);
+    helixflow.on_undo(undo(hf, be, state.clone()));
+
+    let hf = helixflow.as_weak();
+    let be = Arc::downgrade(backend);
+    helixflow.on_redo(redo(hf, be, state));
+
+    let hf = helixflow.as_weak();
+    let be = Arc::downgrade(backend);
+    helixflow.on_assign_task(assign_task(hf, be));
+
+    let hf = helixflow.as_weak();
+    let be = Arc::downgrade(backend);
+    helixflow.on_toggle_pin(toggle_pin(hf, be));
+
+    let hf = helixflow.as_weak();
+    let be = Arc::downgrade(backend);
+    helixflow.on_load_contexts(load_contexts(hf, be));
+    helixflow.invoke_load_contexts();
+
+    let hf = helixflow.as_weak();
+    let be = Arc::downgrade(backend);
+    helixflow.on_toggle_context(toggle_context(hf, be));
+
+    let projects = SharedProjects::default();
+    let hf = helixflow.as_weak();
+    let be = Arc::downgrade(backend);
+    helixflow.on_load_projects(load_projects(
+        hf,
+        be,
+        projects.clone(),
+        *ui_state.visible_project_id(),
+    ));
+    helixflow.invoke_load_projects();
+
+    let list_panel = SharedListPanel::default();
+    let hf = helixflow.as_weak();
+    let be = Arc::downgrade(backend);
+    helixflow.on_load_list_tree(load_list_tree(hf, be, projects.clone(), list_panel.clone()));
+    helixflow.on_toggle_list(toggle_list(helixflow.as_weak(), list_panel.clone()));
+    helixflow.on_select_list(select_list(helixflow.as_weak(), list_panel));
+    helixflow.invoke_load_list_tree();
+
+    // Created on first run
+    let inbox = TaskList::inbox(backend.as_ref()).unwrap();
+    let settings = load_settings(backend.as_ref(), |e| show_error(helixflow, e));
+    let hf = helixflow.as_weak();
+    let be = Arc::downgrade(backend);
+    let triage_projects = projects.clone();
+    helixflow.on_triage_inbox(move || {
+        let backend = be.upgrade().unwrap();
+        let current = hf.unwrap().get_current_project();
+        let destinations: Vec<TaskList> = match triage_projects.borrow().get(current as usize) {
+            Some(project) => project
+                .tasklists(backend.as_ref())
+                .unwrap()
+                .into_iter()
+                .filter(|tasklist| !tasklist.is_inbox())
+                .collect(),
+            None => vec![],
+        };
+        let (tz, locale) = (settings.timezone(), settings.locale());
+        let queue = TriageQueue::default();
+        #[cfg(feature = "telemetry")]
+        record_usage(TelemetryEvent::ViewOpened { view: "Triage" });
+        let triage = Triage::new().unwrap();
+        triage.on_load(load_triage(
+            triage.as_weak(),
+            be.clone(),
+            inbox.clone(),
+            destinations.clone(),
+            queue.clone(),
+            tz,
+            locale,
+        ));
+        triage.on_key(triage_key(
+            triage.as_weak(),
+            be.clone(),
+            inbox.clone(),
+            destinations,
+            queue.clone(),
+            tz,
+            locale,
+        ));
+        triage.on_set_due(triage_due(
+            triage.as_weak(),
+            be.clone(),
+            queue,
+            settings.clone(),
+        ));
+        triage.invoke_load();
+        triage.show().unwrap();
+    });
+
+    // Kept between captures, so the window opens again where it was
+    let quick_capture = QuickCapture::new().unwrap();
+    quick_capture.on_capture(capture_task(
+        quick_capture.as_weak(),
+        Arc::downgrade(backend),
+        load_settings(backend.as_ref(), |e| show_error(helixflow, e)),
+    ));
+    helixflow.on_quick_capture(move || {
+        quick_capture.set_error_message("".into());
+        quick_capture.show().unwrap();
+    });
+
+    if let Err(e) = trash::expire(backend.as_ref(), Utc::now()) {
+        error!("Could not remove old tasks from the trash: {e:#}");
+    }
+    let hf = helixflow.as_weak();
+    let be = Arc::downgrade(backend);
+    helixflow.on_delete_task(delete_task(hf, be));
+    let be = Arc::downgrade(backend);
+    let tz = load_settings(backend.as_ref(), |e| show_error(helixflow, e)).timezone();
+    helixflow.on_show_trash(move || {
+        #[cfg(feature = "telemetry")]
+        record_usage(TelemetryEvent::ViewOpened { view: "Trash" });
+        let trash_view = TrashView::new().unwrap();
+        trash_view.on_load(load_trash(trash_view.as_weak(), be.clone(), tz));
+        trash_view.on_restore(restore_task(trash_view.as_weak(), be.clone(), tz));
+        trash_view.on_empty(empty_trash(trash_view.as_weak(), be.clone(), tz));
+        trash_view.invoke_load();
+        trash_view.show().unwrap();
+    });
+
+    let hf = helixflow.as_weak();
+    let be = Arc::downgrade(backend);
+    helixflow.on_show_dashboard(move || {
+        let backend = be.upgrade().unwrap();
+        let tasklists = match all_tasklists(backend.as_ref()) {
+            Ok(tasklists) => tasklists,
+            Err(e) => return show_error(&hf.unwrap(), &e),
+        };
+        #[cfg(feature = "telemetry")]
+        record_usage(TelemetryEvent::ViewOpened { view: "Dashboard" });
+        let dashboard = Dashboard::new().unwrap();
+        dashboard.on_load(load_dashboard(
+            dashboard.as_weak(),
+            be.clone(),
+            tasklists,
+            tz,
+        ));
+        dashboard.invoke_load();
+        dashboard.show().unwrap();
+    });
+
+    let hf = helixflow.as_weak();
+    let be = Arc::downgrade(backend);
+    let mut show_project = select_project(hf.clone(), be.clone(), projects.clone());
+    helixflow.on_select_project(move |index| {
+        show_project(index);
+        let backend = be.upgrade().unwrap();
+        let Some(project) = projects.borrow().get(index as usize).cloned() else {
+            return;
+        };
+        let helixflow = hf.unwrap();
+        let backlog = TaskList::try_from(helixflow.get_backlog()).unwrap();
+        let mut ui_state = load_ui_state(backend.as_ref(), |e| show_error(&helixflow, e));
+        ui_state.visible_project(&project);
+        ui_state.visible_backlog(&backlog);
+        ui_state.put(backend.as_ref()).unwrap();
+    });
 }
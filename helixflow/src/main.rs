@@ -1,5 +1,45 @@
 #![feature(coverage_attribute)]
 #![coverage(off)]
-fn main() {
-    helixflow::run_helixflow();
+use std::{path::Path, process::ExitCode};
+
+use helixflow_core::interop::ExportMode;
+
+const USAGE: &str = "Usage: helixflow [next | dot | stats | \
+    export [--redacted] <file.surql|file.json> | serve-shares <address:port> | \
+    helixflow://task/<id>]";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().collect();
+    match args.get(1).map(String::as_str) {
+        Some("next") => helixflow::print_next_actions(),
+        Some("dot") => helixflow::print_dot(),
+        Some("stats") => helixflow::print_stats(),
+        Some("export") => match &args[2..] {
+            [file] => helixflow::export_database(Path::new(file), ExportMode::Full),
+            [flag, file] if flag == "--redacted" => {
+                helixflow::export_database(Path::new(file), ExportMode::Redacted)
+            }
+            _ => {
+                eprintln!("{USAGE}");
+                return ExitCode::FAILURE;
+            }
+        },
+        Some("serve-shares") => match &args[2..] {
+            [address] => helixflow::serve_shares(address),
+            _ => {
+                eprintln!("{USAGE}");
+                return ExitCode::FAILURE;
+            }
+        },
+        Some("-h" | "--help" | "help") => println!("{USAGE}"),
+        Some(link) => match link.parse() {
+            Ok(link) => helixflow::run_helixflow(Some(link)),
+            Err(e) => {
+                eprintln!("{e}\n{USAGE}");
+                return ExitCode::FAILURE;
+            }
+        },
+        None => helixflow::run_helixflow(None),
+    }
+    ExitCode::SUCCESS
 }
@@ -1,4 +1,4 @@
-use std::rc::Rc;
+use std::{cell::RefCell, sync::Arc};
 
 use helixflow_core::state::State;
 use slint::platform::PointerEventButton;
@@ -12,6 +12,7 @@ use helixflow_slint::{
     CurrentTask, HelixFlow,
     task::{create_task, create_task_in_backlog, load_backlog},
     test::*,
+    undo::{SharedState, redo, undo},
 };
 use helixflow_surreal::SurrealDb;
 
@@ -19,13 +20,13 @@ use helixflow_surreal::SurrealDb;
 fn test_create_task() {
     prepare_slint!();
 
-    let backend = Rc::new(SurrealDb::new(None).unwrap());
+    let backend = Arc::new(SurrealDb::new(None).unwrap());
 
     let helixflow = HelixFlow::new().unwrap();
     list_elements!(&helixflow);
 
     let hf = helixflow.as_weak();
-    let be = Rc::downgrade(&backend);
+    let be = Arc::downgrade(&backend);
     helixflow.on_create_task(create_task(hf, be));
 
     let hf = helixflow.as_weak();
@@ -67,7 +68,7 @@ fn test_create_task() {
 fn add_tasks_to_backlog() {
     prepare_slint!();
 
-    let backend = Rc::new(SurrealDb::new(None).unwrap());
+    let backend = Arc::new(SurrealDb::new(None).unwrap());
 
     let helixflow = HelixFlow::new().unwrap();
     list_elements!(&helixflow);
@@ -77,12 +78,12 @@ fn add_tasks_to_backlog() {
     helixflow.set_backlog(backlog.into());
 
     let hf = helixflow.as_weak();
-    let be = Rc::downgrade(&backend);
+    let be = Arc::downgrade(&backend);
     helixflow.on_load_backlog(load_backlog(hf, be));
 
     let hf = helixflow.as_weak();
-    let be = Rc::downgrade(&backend);
-    helixflow.on_create_backlog_task(create_task_in_backlog(hf, be));
+    let be = Arc::downgrade(&backend);
+    helixflow.on_create_backlog_task(create_task_in_backlog(hf, be, SharedState::default()));
 
     helixflow.invoke_load_backlog();
     let hf = helixflow.as_weak();
@@ -105,13 +106,57 @@ fn add_tasks_to_backlog() {
     assert_eq!(task_entry.accessible_value().unwrap(), "");
 }
 
+#[test]
+fn undo_new_task() {
+    use slint::Model;
+    use uuid::Uuid;
+
+    prepare_slint!();
+
+    let backend = Arc::new(SurrealDb::new(None).unwrap());
+    let helixflow = HelixFlow::new().unwrap();
+    let state = SharedState::new(RefCell::new(State::new(&Uuid::now_v7())));
+
+    let backlog = TaskList::new("This week");
+    backlog.create(backend.as_ref()).unwrap();
+    helixflow.set_backlog(backlog.into());
+
+    let hf = helixflow.as_weak();
+    let be = Arc::downgrade(&backend);
+    helixflow.on_load_backlog(load_backlog(hf, be));
+
+    let hf = helixflow.as_weak();
+    let be = Arc::downgrade(&backend);
+    helixflow.on_create_backlog_task(create_task_in_backlog(hf, be, state.clone()));
+
+    let hf = helixflow.as_weak();
+    let be = Arc::downgrade(&backend);
+    helixflow.on_undo(undo(hf, be, state.clone()));
+
+    let hf = helixflow.as_weak();
+    let be = Arc::downgrade(&backend);
+    helixflow.on_redo(redo(hf, be, state));
+
+    let task = Task::new("Undo me", None);
+    helixflow.invoke_create_backlog_task(task.clone().into());
+    assert_eq!(helixflow.get_backlog_contents().row_count(), 1);
+
+    helixflow.invoke_undo();
+    assert_eq!(helixflow.get_backlog_contents().row_count(), 0);
+    assert!(Task::get(backend.as_ref(), &task.id).is_err());
+
+    helixflow.invoke_redo();
+    assert_eq!(helixflow.get_backlog_contents().row_count(), 1);
+    assert_eq!(Task::get(backend.as_ref(), &task.id).unwrap(), task);
+}
+
 #[test]
 fn store_ui_state() {
     use uuid::Uuid;
 
     prepare_slint!();
 
-    let backend = Rc::new(SurrealDb::new(None).unwrap());
+    let backend = Arc::new(SurrealDb::new(None).unwrap());
 
     let helixflow = HelixFlow::new().unwrap();
     list_elements!(&helixflow);
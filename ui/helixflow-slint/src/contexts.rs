@@ -0,0 +1,182 @@
+use std::sync::Weak;
+
+use slint::{ComponentHandle, Global, ModelRc, SharedString, ToSharedString, VecModel};
+use uuid::Uuid;
+
+use helixflow_core::{CRUD, Link, Store, contexts::Contexts, task::Task};
+
+use crate::{CurrentTask, HelixFlow, SlintContextChip, TaskBox};
+
+trait ContextChips {
+    fn set_contexts(&self, model: ModelRc<SlintContextChip>);
+    fn current_task_id(&self) -> SharedString;
+}
+
+impl ContextChips for TaskBox {
+    fn set_contexts(&self, model: ModelRc<SlintContextChip>) {
+        self.set_contexts(model);
+    }
+    fn current_task_id(&self) -> SharedString {
+        CurrentTask::get(self).get_task().id
+    }
+}
+
+impl ContextChips for HelixFlow {
+    fn set_contexts(&self, model: ModelRc<SlintContextChip>) {
+        self.set_task_contexts(model);
+    }
+    fn current_task_id(&self) -> SharedString {
+        CurrentTask::get(self).get_task().id
+    }
+}
+
+/// The `CurrentTask`, `None` if no task is shown.
+fn current_task<ROOT, BKEND>(root_component: &ROOT, backend: &BKEND) -> Option<Task>
+where
+    ROOT: ContextChips,
+    BKEND: Store<Task>,
+{
+    let id = root_component.current_task_id();
+    let id = Uuid::try_parse(id.as_str()).ok()?;
+    Some(Task::get(backend, &id).unwrap())
+}
+
+fn show_contexts<ROOT, BKEND>(root_component: &ROOT, backend: &BKEND)
+where
+    ROOT: ComponentHandle + ContextChips,
+    BKEND: Store<Task> + Contexts,
+{
+    let selected = match current_task(root_component, backend) {
+        Some(task) => task.contexts(backend).unwrap(),
+        None => vec![],
+    };
+    let chips: Vec<SlintContextChip> = backend
+        .all_contexts()
+        .unwrap()
+        .iter()
+        .map(|context| SlintContextChip {
+            name: context.name.to_shared_string(),
+            selected: selected.contains(context),
+        })
+        .collect();
+    root_component.set_contexts(ModelRc::new(VecModel::from(chips)));
+}
+
+/// Offer every context as a chip, selecting those of the `CurrentTask`.
+#[allow(private_bounds)]
+pub fn load_contexts<ROOT, BKEND>(
+    root_component: slint::Weak<ROOT>,
+    backend: Weak<BKEND>,
+) -> impl FnMut() + 'static
+where
+    ROOT: ComponentHandle + ContextChips + 'static,
+    BKEND: Store<Task> + Contexts + 'static,
+{
+    move || {
+        let root_component = root_component.unwrap();
+        let backend = backend.upgrade().unwrap();
+        show_contexts(&root_component, backend.as_ref());
+    }
+}
+
+/// Add the context at `index` to the `CurrentTask`, or remove it if the task already has it.
+#[allow(private_bounds)]
+pub fn toggle_context<ROOT, BKEND>(
+    root_component: slint::Weak<ROOT>,
+    backend: Weak<BKEND>,
+) -> impl FnMut(i32) + 'static
+where
+    ROOT: ComponentHandle + ContextChips + 'static,
+    BKEND: Store<Task> + Contexts + 'static,
+{
+    move |index| {
+        let root_component = root_component.unwrap();
+        let backend = backend.upgrade().unwrap();
+        let Some(task) = current_task(&root_component, backend.as_ref()) else {
+            return;
+        };
+        let contexts = backend.all_contexts().unwrap();
+        let Some(context) = contexts.get(index as usize) else {
+            return;
+        };
+        if task.contexts(backend.as_ref()).unwrap().contains(context) {
+            task.remove_context(context, backend.as_ref()).unwrap();
+        } else {
+            task.add_context(context)
+                .create_linked_item(backend.as_ref())
+                .unwrap();
+        }
+        show_contexts(&root_component, backend.as_ref());
+    }
+}
+
+#[cfg(test)]
+#[coverage(off)]
+mod test_slint {
+    use std::sync::Arc;
+
+    use helixflow_core::task::TestBackend;
+    use i_slint_backend_testing::init_no_event_loop;
+    use rstest::*;
+    use slint::Model;
+    use uuid::uuid;
+
+    use super::*;
+    use crate::test::*;
+
+    #[fixture]
+    fn taskbox() -> TaskBox {
+        init_no_event_loop();
+        TaskBox::new().unwrap()
+    }
+
+    fn selected(taskbox: &TaskBox) -> Vec<(String, bool)> {
+        taskbox
+            .get_contexts()
+            .iter()
+            .map(|chip| (chip.name.to_string(), chip.selected))
+            .collect()
+    }
+
+    #[rstest]
+    fn context_chips(taskbox: TaskBox) {
+        let backend = Arc::new(TestBackend);
+        taskbox.on_load_contexts(load_contexts(taskbox.as_weak(), Arc::downgrade(&backend)));
+        taskbox.on_toggle_context(toggle_context(taskbox.as_weak(), Arc::downgrade(&backend)));
+        taskbox.invoke_load_contexts();
+        assert_eq!(
+            selected(&taskbox),
+            vec![
+                ("@home".into(), false),
+                ("@computer".into(), false),
+                ("@errands".into(), false)
+            ]
+        );
+
+        let task = Task::get(
+            backend.as_ref(),
+            &uuid!("0196b4c9-8447-7959-ae1f-72c7c8a3dd36"),
+        )
+        .unwrap();
+        CurrentTask::get(&taskbox).set_task(task.into());
+        taskbox.invoke_load_contexts();
+        assert!(selected(&taskbox)[1].1);
+        let chips = ElementHandle::find_by_element_type_name(&taskbox, "Button");
+        let expected_labels = [
+            "@home",
+            "@computer",
+            "@errands",
+            "Pin",
+            "Close",
+            "Create",
+            "Copy link",
+        ];
+        assert_components!(chips, expected_labels);
+
+        // `TestBackend` doesn't store changes
+        taskbox.invoke_toggle_context(0);
+        taskbox.invoke_toggle_context(1);
+        taskbox.invoke_toggle_context(5);
+        assert!(selected(&taskbox)[1].1);
+    }
+}
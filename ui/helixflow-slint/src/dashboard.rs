@@ -0,0 +1,137 @@
+use std::sync::Weak;
+
+use chrono::{Days, Utc};
+use chrono_tz::Tz;
+use slint::{ModelRc, ToSharedString, VecModel};
+
+use helixflow_core::{
+    Relate,
+    analytics::TaskListSummary,
+    dates::local_date,
+    query::Query,
+    reports::{DailyCount, ListCount, Report},
+    task::{Contains, Task, TaskList},
+};
+
+use crate::{Dashboard, SlintDailyCount, SlintListSummary};
+
+/// How many days, up to & including today, the velocity chart covers.
+const VELOCITY_DAYS: u64 = 14;
+
+impl From<(TaskListSummary, &ListCount)> for SlintListSummary {
+    fn from((summary, count): (TaskListSummary, &ListCount)) -> Self {
+        Self {
+            name: summary.tasklist.name.to_shared_string(),
+            total: summary.total as i32,
+            overdue: summary.overdue as i32,
+            due_today: summary.due_today as i32,
+            open: count.open as i32,
+            done: count.done as i32,
+        }
+    }
+}
+
+impl From<&DailyCount> for SlintDailyCount {
+    fn from(count: &DailyCount) -> Self {
+        Self {
+            day: count.date.format("%a %-d").to_shared_string(),
+            completed: count.completed as i32,
+        }
+    }
+}
+
+/// Summarise each of `tasklists` and show them on the `Dashboard`, with how many tasks were
+/// completed each day over the last two weeks.
+pub fn load_dashboard<BKEND>(
+    dashboard: slint::Weak<Dashboard>,
+    backend: Weak<BKEND>,
+    tasklists: Vec<TaskList>,
+    tz: Tz,
+) -> impl FnMut() + 'static
+where
+    BKEND: Relate<Contains<TaskList, Task>> + Query<Task> + 'static,
+{
+    move || {
+        let dashboard = dashboard.unwrap();
+        let backend = backend.upgrade().unwrap();
+        let now = Utc::now();
+        let today = local_date(now, tz);
+        let period = today - Days::new(VELOCITY_DAYS - 1)..=today;
+        let report = Report::generate(backend.as_ref(), &tasklists, period, now, tz).unwrap();
+        let summaries: Vec<SlintListSummary> = tasklists
+            .iter()
+            .map(|tasklist| TaskListSummary::of(tasklist, backend.as_ref(), now, tz))
+            .map(Result::unwrap)
+            .zip(&report.per_list)
+            .map(Into::into)
+            .collect();
+        let max_total = summaries.iter().map(|summary| summary.total).max();
+        dashboard.set_max_total(max_total.unwrap_or_default());
+        dashboard.set_lists(ModelRc::new(VecModel::from(summaries)));
+        let velocity: Vec<SlintDailyCount> =
+            report.completed_per_day.iter().map(Into::into).collect();
+        let max_completed = velocity.iter().map(|count| count.completed).max();
+        dashboard.set_max_completed(max_completed.unwrap_or_default());
+        dashboard.set_velocity(ModelRc::new(VecModel::from(velocity)));
+        dashboard.set_throughput(format!("{:.1}", report.throughput).into());
+    }
+}
+
+#[cfg(test)]
+#[coverage(off)]
+mod test_slint {
+    use std::sync::Arc;
+
+    use helixflow_core::{CRUD, task::TestBackend};
+    use i_slint_backend_testing::init_no_event_loop;
+    use rstest::*;
+    use slint::ComponentHandle;
+    use uuid::uuid;
+
+    use super::*;
+    use crate::test::*;
+
+    #[fixture]
+    fn dashboard() -> Dashboard {
+        init_no_event_loop();
+
+        let dashboard = Dashboard::new().unwrap();
+        list_elements!(&dashboard);
+        dashboard
+    }
+
+    #[rstest]
+    fn load_summaries(dashboard: Dashboard) {
+        let backend = Arc::new(TestBackend);
+        let tasklist = TaskList::get(
+            backend.as_ref(),
+            &uuid!("0196fe23-7c01-7d6b-9e09-5968eb370549"),
+        )
+        .unwrap();
+        dashboard.on_load(load_dashboard(
+            dashboard.as_weak(),
+            Arc::downgrade(&backend),
+            vec![tasklist],
+            Tz::UTC,
+        ));
+        dashboard.invoke_load();
+        list_elements!(&dashboard);
+
+        let rows = ElementHandle::find_by_element_type_name(&dashboard, "ListSummaryRow");
+        let expected_labels = ["Test TaskList 1"];
+        assert_components!(rows, expected_labels);
+
+        let rows = ElementHandle::find_by_element_type_name(&dashboard, "ListSummaryRow");
+        let expected_values = ["2 tasks, 0 overdue, 0 due today"];
+        assert_values!(rows, expected_values);
+        assert_eq!(dashboard.get_max_total(), 2);
+
+        let rows = ElementHandle::find_by_element_type_name(&dashboard, "OpenDoneRow");
+        let expected_values = ["2 open, 0 done"];
+        assert_values!(rows, expected_values);
+
+        let bars = ElementHandle::find_by_element_type_name(&dashboard, "VelocityBar");
+        assert_eq!(bars.count(), 14);
+        assert_eq!(dashboard.get_throughput(), "0.0");
+    }
+}
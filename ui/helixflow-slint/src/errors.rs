@@ -0,0 +1,76 @@
+use slint::SharedString;
+
+use helixflow_core::{HelixFlowError, errors::Severity};
+
+use crate::{Backlog, HelixFlow, TaskBox};
+
+/// Windows with an error banner, so that callbacks generic over the window can show errors.
+pub(crate) trait ErrorBanner {
+    fn set_error_message(&self, message: SharedString);
+}
+
+impl ErrorBanner for HelixFlow {
+    fn set_error_message(&self, message: SharedString) {
+        self.set_error_message(message);
+    }
+}
+
+impl ErrorBanner for TaskBox {
+    fn set_error_message(&self, message: SharedString) {
+        self.set_error_message(message);
+    }
+}
+
+impl ErrorBanner for Backlog {
+    fn set_error_message(&self, message: SharedString) {
+        self.set_error_message(message);
+    }
+}
+
+/// Tell the user about `e` in the error banner, unless it is only `Severity::Info`.
+#[allow(private_bounds)] // ErrorBanner is only impl'd here ...
+pub fn show_error<ROOT: ErrorBanner>(root_component: &ROOT, e: &HelixFlowError) {
+    if e.severity() == Severity::Info {
+        return;
+    }
+    root_component.set_error_message(format!("{} ({})", e.user_message(), e.code()).into());
+}
+
+#[cfg(test)]
+#[coverage(off)]
+mod test_slint {
+    use i_slint_backend_testing::init_no_event_loop;
+    use rstest::*;
+    use uuid::Uuid;
+
+    use super::*;
+
+    #[fixture]
+    fn helixflow() -> HelixFlow {
+        init_no_event_loop();
+        HelixFlow::new().unwrap()
+    }
+
+    #[rstest]
+    fn banner(helixflow: HelixFlow) {
+        show_error(
+            &helixflow,
+            &HelixFlowError::ValidationFailed {
+                field: "name".into(),
+                reason: "must not be blank".into(),
+            },
+        );
+        assert_eq!(helixflow.get_error_message(), "");
+        show_error(
+            &helixflow,
+            &HelixFlowError::NotFound {
+                itemtype: "Task".into(),
+                id: Uuid::now_v7(),
+            },
+        );
+        assert_eq!(
+            helixflow.get_error_message(),
+            "The task no longer exists. (HF0004)"
+        );
+    }
+}
@@ -3,7 +3,18 @@
 
 slint::include_modules!();
 
+pub mod contexts;
+pub mod dashboard;
+pub mod errors;
+pub mod lists;
+pub mod project;
+pub mod reminders;
+pub mod table;
 pub mod task;
+pub mod trash;
+pub mod triage;
+pub mod undo;
+pub mod workspace;
 
 /// Helper macros & re-exports to simplify testing: `use helixflow_slint::test::*`
 pub mod test {
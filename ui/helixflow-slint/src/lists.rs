@@ -0,0 +1,195 @@
+use std::{cell::RefCell, collections::HashSet, rc::Rc, sync::Weak};
+
+use slint::{ModelRc, ToSharedString, VecModel};
+use uuid::Uuid;
+
+use helixflow_core::{
+    Relate,
+    project::Project,
+    sublists::ListTree,
+    task::{Contains, TaskList},
+};
+
+use crate::{HelixFlow, SlintListNode, project::SharedProjects};
+
+/// The tasklists in the side panel: the trees for the current project, which lists are
+/// collapsed, and the lists currently shown, in display order.
+#[derive(Debug, Default)]
+pub struct ListPanel {
+    roots: Vec<ListTree>,
+    collapsed: HashSet<Uuid>,
+    shown: Vec<TaskList>,
+}
+
+pub type SharedListPanel = Rc<RefCell<ListPanel>>;
+
+impl ListPanel {
+    /// Flatten the trees, skipping the lists nested in collapsed lists.
+    fn nodes(&mut self) -> Vec<SlintListNode> {
+        let mut nodes = Vec::new();
+        self.shown.clear();
+        for root in &self.roots {
+            Self::flatten(root, 0, &self.collapsed, &mut nodes, &mut self.shown);
+        }
+        nodes
+    }
+
+    fn flatten(
+        tree: &ListTree,
+        depth: i32,
+        collapsed: &HashSet<Uuid>,
+        nodes: &mut Vec<SlintListNode>,
+        shown: &mut Vec<TaskList>,
+    ) {
+        let expanded = !collapsed.contains(&tree.tasklist.id);
+        nodes.push(SlintListNode {
+            name: tree.tasklist.name.to_shared_string(),
+            id: tree.tasklist.id.to_shared_string(),
+            depth,
+            expandable: !tree.children.is_empty(),
+            expanded,
+        });
+        shown.push(tree.tasklist.clone());
+        if expanded {
+            for child in &tree.children {
+                Self::flatten(child, depth + 1, collapsed, nodes, shown);
+            }
+        }
+    }
+
+    fn show(&mut self, helixflow: &HelixFlow) {
+        helixflow.set_list_tree(ModelRc::new(VecModel::from(self.nodes())));
+    }
+}
+
+/// Show the tasklists in the current project, and the lists nested in them, in the side panel.
+pub fn load_list_tree<BKEND>(
+    helixflow: slint::Weak<HelixFlow>,
+    backend: Weak<BKEND>,
+    projects: SharedProjects,
+    panel: SharedListPanel,
+) -> impl FnMut() + 'static
+where
+    BKEND: Relate<Contains<Project, TaskList>> + Relate<Contains<TaskList, TaskList>> + 'static,
+{
+    move || {
+        let helixflow = helixflow.unwrap();
+        let backend = backend.upgrade().unwrap();
+        let projects = projects.borrow();
+        let mut panel = panel.borrow_mut();
+        panel.roots = match projects.get(helixflow.get_current_project() as usize) {
+            Some(project) => project
+                .tasklists(backend.as_ref())
+                .unwrap()
+                .iter()
+                .map(|tasklist| tasklist.tree(backend.as_ref()))
+                .collect::<Result<_, _>>()
+                .unwrap(),
+            None => vec![],
+        };
+        panel.show(&helixflow);
+    }
+}
+
+/// Expand or collapse the list at `index` in the side panel.
+pub fn toggle_list(
+    helixflow: slint::Weak<HelixFlow>,
+    panel: SharedListPanel,
+) -> impl FnMut(i32) + 'static {
+    move |index| {
+        let helixflow = helixflow.unwrap();
+        let mut panel = panel.borrow_mut();
+        let Some(tasklist) = panel.shown.get(index as usize) else {
+            return;
+        };
+        let id = tasklist.id;
+        if !panel.collapsed.remove(&id) {
+            panel.collapsed.insert(id);
+        }
+        panel.show(&helixflow);
+    }
+}
+
+/// Show the list at `index` in the side panel as the backlog.
+pub fn select_list(
+    helixflow: slint::Weak<HelixFlow>,
+    panel: SharedListPanel,
+) -> impl FnMut(i32) + 'static {
+    move |index| {
+        let helixflow = helixflow.unwrap();
+        let panel = panel.borrow();
+        if let Some(tasklist) = panel.shown.get(index as usize) {
+            helixflow.set_backlog(tasklist.clone().into());
+            helixflow.invoke_load_backlog();
+        }
+    }
+}
+
+#[cfg(test)]
+#[coverage(off)]
+mod test_slint {
+    use std::sync::Arc;
+
+    use helixflow_core::task::TestBackend;
+    use i_slint_backend_testing::init_no_event_loop;
+    use rstest::*;
+    use slint::{ComponentHandle, Model};
+    use uuid::uuid;
+
+    use super::*;
+    use crate::{project::load_projects, task::load_backlog};
+
+    #[fixture]
+    fn helixflow() -> HelixFlow {
+        init_no_event_loop();
+        HelixFlow::new().unwrap()
+    }
+
+    fn names(helixflow: &HelixFlow) -> Vec<String> {
+        helixflow
+            .get_list_tree()
+            .iter()
+            .map(|node| format!("{}{}", "  ".repeat(node.depth as usize), node.name))
+            .collect()
+    }
+
+    #[rstest]
+    fn nested_lists(helixflow: HelixFlow) {
+        let backend = Arc::new(TestBackend);
+        let projects = SharedProjects::default();
+        let panel = SharedListPanel::default();
+        helixflow.on_load_backlog(load_backlog(helixflow.as_weak(), Arc::downgrade(&backend)));
+        helixflow.on_load_projects(load_projects(
+            helixflow.as_weak(),
+            Arc::downgrade(&backend),
+            projects.clone(),
+            Some(uuid!("0197c4f6-3c4d-7e5f-a0b1-2c3d4e5f6a7b")),
+        ));
+        helixflow.on_load_list_tree(load_list_tree(
+            helixflow.as_weak(),
+            Arc::downgrade(&backend),
+            projects,
+            panel.clone(),
+        ));
+        helixflow.on_toggle_list(toggle_list(helixflow.as_weak(), panel.clone()));
+        helixflow.on_select_list(select_list(helixflow.as_weak(), panel));
+
+        helixflow.invoke_load_projects();
+        helixflow.invoke_load_list_tree();
+        assert_eq!(
+            names(&helixflow),
+            vec!["Test TaskList 1", "  Test TaskList 2"]
+        );
+        assert!(helixflow.get_list_tree().row_data(0).unwrap().expanded);
+
+        helixflow.invoke_toggle_list(0);
+        assert_eq!(names(&helixflow), vec!["Test TaskList 1"]);
+        assert!(!helixflow.get_list_tree().row_data(0).unwrap().expanded);
+        helixflow.invoke_toggle_list(0);
+        assert_eq!(names(&helixflow).len(), 2);
+
+        helixflow.invoke_select_list(0);
+        assert_eq!(helixflow.get_backlog().name, "Test TaskList 1");
+        assert_eq!(helixflow.get_backlog_contents().row_count(), 2);
+    }
+}
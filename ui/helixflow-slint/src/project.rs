@@ -0,0 +1,126 @@
+use std::{cell::RefCell, rc::Rc, sync::Weak};
+
+use slint::{ModelRc, SharedString, ToSharedString, VecModel};
+use uuid::Uuid;
+
+use helixflow_core::{
+    Relate,
+    project::{Project, Projects},
+    task::{Contains, Task, TaskList},
+};
+
+use crate::HelixFlow;
+
+/// The projects offered by the project selector, in display order.
+pub type SharedProjects = Rc<RefCell<Vec<Project>>>;
+
+/// Offer all projects in the project selector, with the project with id `current` selected.
+pub fn load_projects<BKEND>(
+    helixflow: slint::Weak<HelixFlow>,
+    backend: Weak<BKEND>,
+    projects: SharedProjects,
+    current: Option<Uuid>,
+) -> impl FnMut() + 'static
+where
+    BKEND: Projects + 'static,
+{
+    move || {
+        let helixflow = helixflow.unwrap();
+        let backend = backend.upgrade().unwrap();
+        let mut projects = projects.borrow_mut();
+        *projects = backend.all_projects().unwrap();
+        let names: Vec<SharedString> = projects
+            .iter()
+            .map(|project| project.name.to_shared_string())
+            .collect();
+        let current = projects
+            .iter()
+            .position(|project| Some(project.id) == current);
+        helixflow.set_project_names(ModelRc::new(VecModel::from(names)));
+        helixflow.set_current_project(current.map_or(-1, |index| index as i32));
+    }
+}
+
+/// Show the first tasklist in the project at `index` as the backlog, and all its tasklists in
+/// the side panel. Projects without any tasklists leave the backlog unchanged.
+pub fn select_project<BKEND>(
+    helixflow: slint::Weak<HelixFlow>,
+    backend: Weak<BKEND>,
+    projects: SharedProjects,
+) -> impl FnMut(i32) + 'static
+where
+    BKEND: Relate<Contains<Project, TaskList>> + Relate<Contains<TaskList, Task>> + 'static,
+{
+    move |index| {
+        let helixflow = helixflow.unwrap();
+        let backend = backend.upgrade().unwrap();
+        let projects = projects.borrow();
+        let Some(project) = projects.get(index as usize) else {
+            return;
+        };
+        if let Some(tasklist) = project
+            .tasklists(backend.as_ref())
+            .unwrap()
+            .into_iter()
+            .next()
+        {
+            helixflow.set_backlog(tasklist.into());
+            helixflow.invoke_load_backlog();
+        }
+        helixflow.invoke_load_list_tree();
+    }
+}
+
+#[cfg(test)]
+#[coverage(off)]
+mod test_slint {
+    use std::sync::Arc;
+
+    use helixflow_core::task::TestBackend;
+    use i_slint_backend_testing::init_no_event_loop;
+    use rstest::*;
+    use slint::{ComponentHandle, Model};
+    use uuid::uuid;
+
+    use super::*;
+    use crate::task::load_backlog;
+
+    #[fixture]
+    fn helixflow() -> HelixFlow {
+        init_no_event_loop();
+        HelixFlow::new().unwrap()
+    }
+
+    #[rstest]
+    fn choose_project(helixflow: HelixFlow) {
+        let backend = Arc::new(TestBackend);
+        let projects = SharedProjects::default();
+        helixflow.on_load_backlog(load_backlog(helixflow.as_weak(), Arc::downgrade(&backend)));
+        helixflow.on_load_projects(load_projects(
+            helixflow.as_weak(),
+            Arc::downgrade(&backend),
+            projects.clone(),
+            Some(uuid!("0197c4f6-3c4d-7e5f-a0b1-2c3d4e5f6a7b")),
+        ));
+        helixflow.on_select_project(select_project(
+            helixflow.as_weak(),
+            Arc::downgrade(&backend),
+            projects,
+        ));
+
+        helixflow.invoke_load_projects();
+        assert_eq!(
+            helixflow.get_project_names().iter().collect::<Vec<_>>(),
+            vec!["Test Project 1"]
+        );
+        assert_eq!(helixflow.get_current_project(), 0);
+
+        helixflow.invoke_select_project(0);
+        assert_eq!(helixflow.get_backlog().name, "Test TaskList 1");
+        assert_eq!(helixflow.get_backlog_contents().row_count(), 2);
+
+        // Out of range, e.g. nothing selected
+        helixflow.invoke_select_project(-1);
+        assert_eq!(helixflow.get_backlog().name, "Test TaskList 1");
+    }
+}
@@ -0,0 +1,49 @@
+use helixflow_core::task::Task;
+
+use crate::HelixFlow;
+
+/// Notify the user that reminders fired for `tasks`, adding to any reminders not yet dismissed.
+pub fn show_reminders(helixflow: &HelixFlow, tasks: &[Task]) {
+    if tasks.is_empty() {
+        return;
+    }
+    let names: Vec<&str> = tasks.iter().map(|task| task.name.as_ref()).collect();
+    let shown = helixflow.get_reminder_message();
+    let message = if shown.is_empty() {
+        format!("Reminder: {}", names.join(", "))
+    } else {
+        format!("{shown}, {}", names.join(", "))
+    };
+    helixflow.set_reminder_message(message.into());
+}
+
+#[cfg(test)]
+#[coverage(off)]
+mod test_slint {
+    use i_slint_backend_testing::init_no_event_loop;
+    use rstest::*;
+
+    use super::*;
+
+    #[fixture]
+    fn helixflow() -> HelixFlow {
+        init_no_event_loop();
+        HelixFlow::new().unwrap()
+    }
+
+    #[rstest]
+    fn notify(helixflow: HelixFlow) {
+        show_reminders(&helixflow, &[]);
+        assert_eq!(helixflow.get_reminder_message(), "");
+        show_reminders(&helixflow, &[Task::new("Water plants", None)]);
+        assert_eq!(helixflow.get_reminder_message(), "Reminder: Water plants");
+        show_reminders(
+            &helixflow,
+            &[Task::new("Call Bob", None), Task::new("Pay rent", None)],
+        );
+        assert_eq!(
+            helixflow.get_reminder_message(),
+            "Reminder: Water plants, Call Bob, Pay rent"
+        );
+    }
+}
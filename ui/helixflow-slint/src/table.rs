@@ -0,0 +1,303 @@
+use std::{cell::RefCell, rc::Rc, sync::Weak};
+
+use chrono_tz::Tz;
+use slint::{
+    ModelRc, SharedString, StandardListViewItem, TableColumn, ToSharedString, VecModel,
+    language::SortOrder,
+};
+
+use helixflow_core::{
+    CRUD, HelixFlowError, HelixFlowResult, Relate, Store,
+    table::{Column, Row, TableLayout},
+    tag::{Tag, TaggedWith},
+    task::{Contains, Task, TaskList},
+};
+
+use crate::TaskTable;
+
+/// The layout of a `TaskTable` and the rows it currently shows, in display order.
+#[derive(Debug, Default)]
+pub struct TableContents {
+    pub layout: TableLayout,
+    pub rows: Vec<Row>,
+}
+
+pub type SharedTable = Rc<RefCell<TableContents>>;
+
+fn show(table: &TaskTable, contents: &TableContents, tz: Tz) {
+    let columns: Vec<TableColumn> = contents
+        .layout
+        .columns
+        .iter()
+        .map(|column| {
+            let mut table_column = TableColumn::default();
+            table_column.title = column.title().into();
+            if contents.layout.sort_by == Some(*column) {
+                table_column.sort_order = if contents.layout.descending {
+                    SortOrder::Descending
+                } else {
+                    SortOrder::Ascending
+                };
+            }
+            table_column
+        })
+        .collect();
+    let titles: Vec<SharedString> = contents
+        .layout
+        .columns
+        .iter()
+        .map(|column| column.title().into())
+        .collect();
+    let rows: Vec<ModelRc<StandardListViewItem>> = contents
+        .rows
+        .iter()
+        .map(|row| {
+            let cells: Vec<StandardListViewItem> = contents
+                .layout
+                .columns
+                .iter()
+                .map(|column| row.cell(*column, tz).to_shared_string().into())
+                .collect();
+            ModelRc::new(VecModel::from(cells))
+        })
+        .collect();
+    table.set_columns(ModelRc::new(VecModel::from(columns)));
+    table.set_column_titles(ModelRc::new(VecModel::from(titles)));
+    table.set_rows(ModelRc::new(VecModel::from(rows)));
+}
+
+/// Fill the table with the tasks in `tasklist`, laid out as `contents.layout`.
+pub fn load_table<BKEND>(
+    table: slint::Weak<TaskTable>,
+    backend: Weak<BKEND>,
+    tasklist: TaskList,
+    contents: SharedTable,
+    tz: Tz,
+) -> impl FnMut() + 'static
+where
+    BKEND: Relate<Contains<TaskList, Task>> + Relate<TaggedWith<Task, Tag>> + 'static,
+{
+    move || {
+        let table = table.unwrap();
+        let backend = backend.upgrade().unwrap();
+        let mut contents = contents.borrow_mut();
+        contents.rows = contents.layout.rows(&tasklist, backend.as_ref()).unwrap();
+        show(&table, &contents, tz);
+    }
+}
+
+/// Sort the rows by the column at `index`. The caller is responsible for saving the changed
+/// `contents.layout` to `State`.
+pub fn sort_table(
+    table: slint::Weak<TaskTable>,
+    contents: SharedTable,
+    tz: Tz,
+) -> impl FnMut(i32, bool) + 'static {
+    move |index, descending| {
+        let table = table.unwrap();
+        let mut contents = contents.borrow_mut();
+        let Some(column) = contents.layout.columns.get(index as usize).copied() else {
+            return;
+        };
+        contents.layout.sort_by = Some(column);
+        contents.layout.descending = descending;
+        let TableContents { layout, rows } = &mut *contents;
+        layout.sort(rows);
+        show(&table, &contents, tz);
+    }
+}
+
+/// `row` with the cell in `column` changed to `text`, once the task is saved.
+///
+/// If the task was changed elsewhere since the table was loaded, the edit is made to the stored
+/// task instead, so that neither change is lost.
+fn save_edit<BKEND>(
+    row: &Row,
+    column: Column,
+    text: &str,
+    tz: Tz,
+    backend: &BKEND,
+) -> HelixFlowResult<Row>
+where
+    BKEND: Store<Task>,
+{
+    let mut edited = row.clone();
+    edited.edit(column, text, tz)?;
+    match edited.task.update(backend) {
+        Err(HelixFlowError::Conflict { .. }) => {
+            edited = Row {
+                task: Task::get(backend, &row.task.id)?,
+                ..row.clone()
+            };
+            edited.edit(column, text, tz)?;
+            edited.task.update(backend)?;
+        }
+        result => result?,
+    }
+    edited.task.version = edited.task.version.next();
+    Ok(edited)
+}
+
+/// Change the cell at (`row`, `column`) and save the task. Returns `false`, leaving the task
+/// unchanged, if the text is not valid for the column or the task could not be saved.
+pub fn edit_cell<BKEND>(
+    table: slint::Weak<TaskTable>,
+    backend: Weak<BKEND>,
+    contents: SharedTable,
+    tz: Tz,
+) -> impl FnMut(i32, i32, SharedString) -> bool + 'static
+where
+    BKEND: Store<Task> + 'static,
+{
+    move |row, column, text| {
+        let table = table.unwrap();
+        let backend = backend.upgrade().unwrap();
+        let mut contents = contents.borrow_mut();
+        let Some(column) = contents.layout.columns.get(column as usize).copied() else {
+            return false;
+        };
+        let Some(row) = contents.rows.get_mut(row as usize) else {
+            return false;
+        };
+        let Ok(edited) = save_edit(row, column, &text, tz, backend.as_ref()) else {
+            return false;
+        };
+        *row = edited;
+        show(&table, &contents, tz);
+        true
+    }
+}
+
+#[cfg(test)]
+#[coverage(off)]
+mod test_slint {
+    use std::sync::Arc;
+
+    use helixflow_core::{task::TestBackend, versions::Version};
+    use i_slint_backend_testing::init_no_event_loop;
+    use rstest::*;
+    use slint::{ComponentHandle, Model};
+    use uuid::{Uuid, uuid};
+
+    use super::*;
+
+    #[fixture]
+    fn table() -> TaskTable {
+        init_no_event_loop();
+        TaskTable::new().unwrap()
+    }
+
+    fn cells(table: &TaskTable) -> Vec<Vec<String>> {
+        table
+            .get_rows()
+            .iter()
+            .map(|row| row.iter().map(|cell| cell.text.to_string()).collect())
+            .collect()
+    }
+
+    #[rstest]
+    fn load_sort_and_edit(table: TaskTable) {
+        let backend = Arc::new(TestBackend);
+        let tasklist = TaskList::get(
+            backend.as_ref(),
+            &uuid!("0196fe23-7c01-7d6b-9e09-5968eb370549"),
+        )
+        .unwrap();
+        let contents = SharedTable::default();
+        contents.borrow_mut().layout.columns = vec![Column::Name, Column::Priority, Column::Tags];
+        table.on_load(load_table(
+            table.as_weak(),
+            Arc::downgrade(&backend),
+            tasklist,
+            contents.clone(),
+            Tz::UTC,
+        ));
+        table.on_sort(sort_table(table.as_weak(), contents.clone(), Tz::UTC));
+        table.on_edit(edit_cell(
+            table.as_weak(),
+            Arc::downgrade(&backend),
+            contents.clone(),
+            Tz::UTC,
+        ));
+
+        table.invoke_load();
+        assert_eq!(
+            cells(&table),
+            vec![vec!["Task 1", "P2", "home"], vec!["Task 2", "P2", ""]]
+        );
+
+        table.invoke_sort(0, true);
+        assert_eq!(
+            table.get_columns().row_data(0).unwrap().sort_order,
+            SortOrder::Descending
+        );
+        assert_eq!(cells(&table)[0][0], "Task 2");
+
+        // Task 1 is now the second row
+        assert!(table.invoke_edit(1, 1, "P0".into()));
+        assert_eq!(cells(&table)[1], vec!["Task 1", "P0", "home"]);
+        assert!(!table.invoke_edit(1, 1, "urgent".into()));
+        assert!(!table.invoke_edit(1, 2, "work".into()));
+        assert_eq!(cells(&table)[1], vec!["Task 1", "P0", "home"]);
+    }
+
+    /// Task 1 was changed elsewhere, after being read at version 0
+    struct ChangedElsewhere;
+
+    impl Store<Task> for ChangedElsewhere {
+        fn create(&self, task: &Task) -> HelixFlowResult<Task> {
+            TestBackend.create(task)
+        }
+
+        fn get(&self, id: &Uuid) -> HelixFlowResult<Task> {
+            Ok(Task {
+                name: "Renamed elsewhere".into(),
+                version: Version(Some(1)),
+                ..TestBackend.get(id)?
+            })
+        }
+
+        fn update(&self, task: &Task) -> HelixFlowResult<Task> {
+            match task.version.0 {
+                Some(1) => Ok(task.clone()),
+                _ => Err(HelixFlowError::Conflict {
+                    itemtype: "Task".into(),
+                    id: task.id,
+                    expected: task.version.0.unwrap_or_default(),
+                    stored: 1,
+                }),
+            }
+        }
+
+        fn delete(&self, id: &Uuid) -> HelixFlowResult<()> {
+            Store::<Task>::delete(&TestBackend, id)
+        }
+    }
+
+    #[rstest]
+    fn edit_changed_elsewhere(table: TaskTable) {
+        let backend = Arc::new(ChangedElsewhere);
+        let task: Task = TestBackend
+            .get(&uuid!("0196b4c9-8447-7959-ae1f-72c7c8a3dd36"))
+            .unwrap();
+        let contents = SharedTable::default();
+        contents.borrow_mut().layout.columns = vec![Column::Name, Column::Priority];
+        contents.borrow_mut().rows = vec![Row {
+            task: Task {
+                version: Version(Some(0)),
+                ..task
+            },
+            tags: vec![],
+        }];
+        table.on_edit(edit_cell(
+            table.as_weak(),
+            Arc::downgrade(&backend),
+            contents.clone(),
+            Tz::UTC,
+        ));
+
+        assert!(table.invoke_edit(0, 1, "P0".into()));
+        assert_eq!(cells(&table), vec![vec!["Renamed elsewhere", "P0"]]);
+        assert_eq!(contents.borrow().rows[0].task.version.0, Some(2));
+    }
+}
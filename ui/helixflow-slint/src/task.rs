@@ -1,38 +1,158 @@
-use std::{fmt::Display, rc::Weak};
+use std::{fmt::Display, sync::Weak};
 
+use chrono::Utc;
+use chrono_tz::Tz;
 use uuid::Uuid;
 
-use slint::{ComponentHandle, VecModel};
+use slint::{Color, ComponentHandle, VecModel};
 use slint::{Global, ModelRc, SharedString, ToSharedString};
 
 use helixflow_core::{
-    CRUD, HelixFlowError, HelixFlowResult, Link, Linkable, Relate, Store,
-    task::{Contains, Task, TaskList},
+    CRUD, HelixFlowError, HelixFlowResult, Link, Relate, Store,
+    command::Command,
+    contexts::Contexts,
+    person::{People, Person},
+    quickadd::Locale,
+    relative,
+    settings::Settings,
+    sorting::SortRule,
+    state::{CardSettings, ViewMode},
+    tag::{Tag, TaggedWith},
+    task::{Backlogs, Colour, Contains, Priority, Status, Task, TaskList},
 };
 
-use crate::{Backlog, CurrentTask, HelixFlow, SlintTask, SlintTaskList};
+use crate::{
+    Backlog, CurrentTask, HelixFlow, QuickCapture, SlintCardSettings, SlintStatus, SlintTask,
+    SlintTaskList, SlintViewMode,
+    errors::{ErrorBanner, show_error},
+    undo::SharedState,
+};
 
 impl TryFrom<SlintTask> for Task {
     type Error = HelixFlowError;
     fn try_from(task: SlintTask) -> HelixFlowResult<Task> {
+        let colour = (task.colour.alpha() != 0)
+            .then(|| Colour::rgb(task.colour.red(), task.colour.green(), task.colour.blue()));
         Ok(if task.id.is_empty() {
-            Task::new(task.name.to_string(), None)
+            Task {
+                colour,
+                status: task.status.into(),
+                ..Task::new(task.name.to_string(), None)
+            }
         } else {
             Task {
                 name: task.name.to_string().into(),
                 id: Uuid::try_parse(task.id.as_str())
                     .map_err(|_| HelixFlowError::InvalidID { id: task.id.into() })?,
                 description: None,
+                due: None,
+                colour,
+                status: task.status.into(),
+                priority: Priority::default(),
+                estimate: None,
+                actual: None,
+                completed_at: None,
+                pinned: task.pinned,
+                updated_at: Default::default(),
+                version: Default::default(),
             }
         })
     }
 }
 
+/// Without the due date & tags, see `card`.
 impl From<Task> for SlintTask {
     fn from(task: Task) -> Self {
+        let link = task.deep_link().to_shared_string();
         Self {
             name: task.name.into_owned().into(),
             id: task.id.to_shared_string(),
+            colour: task.colour.map_or(Color::default(), |colour| {
+                Color::from_rgb_u8(colour.red, colour.green, colour.blue)
+            }),
+            link,
+            status: task.status.into(),
+            assignee: SharedString::default(),
+            pinned: task.pinned,
+            description: task
+                .description
+                .map(|description| description.into_owned().into())
+                .unwrap_or_default(),
+            due: SharedString::default(),
+            tags: SharedString::default(),
+        }
+    }
+}
+
+/// `task` as a card in the backlog, with its due date as seen in `tz` and its tags.
+fn card<BKEND>(task: Task, backend: &BKEND, tz: Tz, locale: Locale) -> HelixFlowResult<SlintTask>
+where
+    BKEND: Relate<TaggedWith<Task, Tag>>,
+{
+    let due = task
+        .due
+        .map(|due| relative::due(due, Utc::now(), tz, locale))
+        .unwrap_or_default();
+    let tags: Vec<String> = task
+        .tags(backend)?
+        .iter()
+        .map(|tag| format!("#{}", tag.name))
+        .collect();
+    Ok(SlintTask {
+        due: due.into(),
+        tags: tags.join(" ").into(),
+        ..task.into()
+    })
+}
+
+impl From<CardSettings> for SlintCardSettings {
+    fn from(settings: CardSettings) -> Self {
+        Self {
+            show_description: settings.show_description,
+            show_due_date: settings.show_due_date,
+            show_tags: settings.show_tags,
+        }
+    }
+}
+
+impl From<Status> for SlintStatus {
+    fn from(status: Status) -> Self {
+        match status {
+            Status::Todo => SlintStatus::Todo,
+            Status::InProgress => SlintStatus::InProgress,
+            Status::Blocked => SlintStatus::Blocked,
+            Status::Done => SlintStatus::Done,
+        }
+    }
+}
+
+impl From<SlintStatus> for Status {
+    fn from(status: SlintStatus) -> Self {
+        match status {
+            SlintStatus::Todo => Status::Todo,
+            SlintStatus::InProgress => Status::InProgress,
+            SlintStatus::Blocked => Status::Blocked,
+            SlintStatus::Done => Status::Done,
+        }
+    }
+}
+
+impl From<ViewMode> for SlintViewMode {
+    fn from(mode: ViewMode) -> Self {
+        match mode {
+            ViewMode::List => SlintViewMode::List,
+            ViewMode::Board => SlintViewMode::Board,
+            ViewMode::Table => SlintViewMode::Table,
+        }
+    }
+}
+
+impl From<SlintViewMode> for ViewMode {
+    fn from(mode: SlintViewMode) -> Self {
+        match mode {
+            SlintViewMode::List => ViewMode::List,
+            SlintViewMode::Board => ViewMode::Board,
+            SlintViewMode::Table => ViewMode::Table,
         }
     }
 }
@@ -71,6 +191,7 @@ impl TryFrom<SlintTaskList> for TaskList {
                         id: tasklist.id.into(),
                     }
                 })?,
+                sort: Default::default(),
             }
         })
     }
@@ -112,19 +233,23 @@ where
         helixflow.set_create_enabled(false);
         let task_name: String = helixflow.get_task_name().into();
         let task = Task::new(task_name, None);
-        task.create(backend.as_ref()).unwrap();
-        CurrentTask::get(&helixflow).set_task(task.into());
+        match task.create(backend.as_ref()) {
+            Ok(()) => CurrentTask::get(&helixflow).set_task(task.into()),
+            // e.g. no name yet, which is only `Severity::Info` so nothing is shown
+            Err(e) => show_error(&helixflow, &e),
+        }
         helixflow.set_create_enabled(true);
     }
 }
 
+/// Show the tasks still to do in the backlog, completed tasks are hidden.
 #[allow(private_bounds)] // BacklogSignature hack is private & should only be impl'd here ...
 pub fn load_backlog<ROOT, BKEND>(
     root_component: slint::Weak<ROOT>,
     backend: Weak<BKEND>,
 ) -> impl FnMut() + 'static
 where
-    BKEND: Relate<Contains<TaskList, Task>> + 'static,
+    BKEND: Backlogs + 'static,
     ROOT: ComponentHandle + BacklogSignature + 'static,
 {
     move || {
@@ -132,22 +257,69 @@ where
         let backend = backend.upgrade().unwrap();
         let tasklist = root_component.get_tasklist();
         let tl = TaskList::try_from(tasklist).unwrap();
-        let backlog_entries: VecModel<SlintTask> = tl
-            .get_linked_items(backend.as_ref())
+        let backlog_entries: VecModel<SlintTask> = backend
+            .backlog_pinned_first(&tl, false)
             .unwrap()
-            .map(|task| task.right.unwrap().into())
+            .into_iter()
+            .map(Into::into)
             .collect();
         root_component.set_tasks(ModelRc::new(backlog_entries));
     }
 }
 
+/// As `load_backlog`, but filtered & sorted as chosen in `state`, with dates as seen in `tz` and
+/// written for `locale`. Without a sort chosen in `state`, the list's own `SortRule` applies.
+#[allow(private_bounds)]
+pub fn load_arranged_backlog<ROOT, BKEND>(
+    root_component: slint::Weak<ROOT>,
+    backend: Weak<BKEND>,
+    state: SharedState,
+    tz: Tz,
+    locale: Locale,
+) -> impl FnMut() + 'static
+where
+    BKEND: Backlogs + Store<TaskList> + Relate<TaggedWith<Task, Tag>> + 'static,
+    ROOT: ComponentHandle + BacklogSignature + ErrorBanner + 'static,
+{
+    move || {
+        let root_component = root_component.unwrap();
+        let backend = backend.upgrade().unwrap();
+        let tl = TaskList::try_from(root_component.get_tasklist()).unwrap();
+        // The UI only knows the list's name & id
+        let list_sort = match TaskList::get(backend.as_ref(), &tl.id) {
+            Ok(stored) => stored.sort,
+            Err(HelixFlowError::NotFound { .. }) => SortRule::default(),
+            Err(e) => return show_error(&root_component, &e),
+        };
+        let state = state.borrow();
+        let filter = state.current_filter();
+        let mut tasks: Vec<Task> = backend
+            .backlog_pinned_first(&tl, filter.shows_done())
+            .unwrap()
+            .into_iter()
+            .filter(|task| filter.matches(task))
+            .collect();
+        state
+            .current_sort_mode()
+            .or(&list_sort)
+            .sort_pinned_first(&mut tasks, Utc::now(), tz);
+        let backlog_entries: VecModel<SlintTask> = tasks
+            .into_iter()
+            .map(|task| card(task, backend.as_ref(), tz, locale).unwrap())
+            .collect();
+        root_component.set_tasks(ModelRc::new(backlog_entries));
+    }
+}
+
+/// Add a new task to the end of the backlog, which can be undone.
 #[allow(private_bounds)] // BacklogSignature hack is private & should only be impl'd here ...
 pub fn create_task_in_backlog<ROOT, BKEND>(
     root_component: slint::Weak<ROOT>,
     backend: Weak<BKEND>,
+    state: SharedState,
 ) -> impl FnMut(SlintTask) + 'static
 where
-    BKEND: Relate<Contains<TaskList, Task>> + 'static,
+    BKEND: Store<Task> + Backlogs + 'static,
     ROOT: ComponentHandle + BacklogSignature + 'static,
 {
     move |slinttask| {
@@ -157,21 +329,168 @@ where
         let backlog: TaskList = root_component.get_tasklist().try_into().unwrap();
         let task: Task = slinttask.try_into().unwrap();
 
-        backlog
-            .link(&task)
-            .create_linked_item(backend.as_ref())
+        state
+            .borrow_mut()
+            .execute(
+                Command::CreateTask {
+                    task,
+                    tasklist: backlog.clone(),
+                },
+                backend.as_ref(),
+            )
             .unwrap();
-        let backlog_entries: VecModel<SlintTask> = backlog
-            .get_linked_items(backend.as_ref())
+        let backlog_entries: VecModel<SlintTask> = backend
+            .backlog(&backlog, false)
             .unwrap()
-            .map(|link| link.right)
-            .map(Result::unwrap)
+            .into_iter()
             .map(Into::into)
             .collect();
         root_component.set_tasks(ModelRc::new(backlog_entries));
     }
 }
 
+/// Move the `CurrentTask` to `status` and save it, which can be undone. Returns `false`, leaving
+/// the task unchanged, if this is not a valid step in its workflow or it could not be saved.
+#[allow(private_bounds)]
+pub fn change_status<ROOT, BKEND>(
+    root_component: slint::Weak<ROOT>,
+    backend: Weak<BKEND>,
+    state: SharedState,
+) -> impl FnMut(SlintStatus) -> bool + 'static
+where
+    ROOT: ComponentHandle + ErrorBanner + 'static,
+    for<'a> CurrentTask<'a>: Global<'a, ROOT>,
+    BKEND: Store<Task> + Relate<Contains<TaskList, Task>> + 'static,
+{
+    move |status| {
+        let root_component = root_component.unwrap();
+        let backend = backend.upgrade().unwrap();
+        let current_task = CurrentTask::get(&root_component);
+        let slint_task = current_task.get_task();
+        if slint_task.id.is_empty() {
+            return false;
+        }
+        // `SlintTask` doesn't carry every field, so start from the stored task, moving on from
+        // the status shown
+        let id = Uuid::try_parse(slint_task.id.as_str()).unwrap();
+        let task = Task {
+            status: slint_task.status.into(),
+            ..Task::get(backend.as_ref(), &id).unwrap()
+        };
+        let status = status.into();
+        let transition = Command::Transition {
+            task: task.clone(),
+            status,
+        };
+        match state.borrow_mut().execute(transition, backend.as_ref()) {
+            Ok(()) => {
+                current_task.set_task(SlintTask {
+                    assignee: slint_task.assignee,
+                    ..Task { status, ..task }.into()
+                });
+                true
+            }
+            Err(HelixFlowError::InvalidTransition { .. }) => false,
+            Err(e) => {
+                show_error(&root_component, &e);
+                false
+            }
+        }
+    }
+}
+
+/// Assign the `CurrentTask` to the person called `name`, adding them if they are not known yet.
+/// An empty name leaves the task unchanged.
+pub fn assign_task<ROOT, BKEND>(
+    root_component: slint::Weak<ROOT>,
+    backend: Weak<BKEND>,
+) -> impl FnMut(SharedString) + 'static
+where
+    ROOT: ComponentHandle + 'static,
+    for<'a> CurrentTask<'a>: Global<'a, ROOT>,
+    BKEND: Store<Task> + People + 'static,
+{
+    move |name| {
+        let root_component = root_component.unwrap();
+        let backend = backend.upgrade().unwrap();
+        let current_task = CurrentTask::get(&root_component);
+        let mut slint_task = current_task.get_task();
+        let name = name.trim();
+        if slint_task.id.is_empty() || name.is_empty() {
+            return;
+        }
+        let id = Uuid::try_parse(slint_task.id.as_str()).unwrap();
+        let task = Task::get(backend.as_ref(), &id).unwrap();
+        let person = backend
+            .person_named(name)
+            .unwrap()
+            .unwrap_or_else(|| Person::new(name.to_string(), None::<&str>));
+        task.assign_to(&person)
+            .create_linked_item(backend.as_ref())
+            .unwrap();
+        slint_task.assignee = person.name.to_shared_string();
+        current_task.set_task(slint_task);
+    }
+}
+
+/// Pin the `CurrentTask` to the top of its list, or unpin it if it is already pinned.
+pub fn toggle_pin<ROOT, BKEND>(
+    root_component: slint::Weak<ROOT>,
+    backend: Weak<BKEND>,
+) -> impl FnMut() + 'static
+where
+    ROOT: ComponentHandle + 'static,
+    for<'a> CurrentTask<'a>: Global<'a, ROOT>,
+    BKEND: Store<Task> + 'static,
+{
+    move || {
+        let root_component = root_component.unwrap();
+        let backend = backend.upgrade().unwrap();
+        let current_task = CurrentTask::get(&root_component);
+        let mut slint_task = current_task.get_task();
+        if slint_task.id.is_empty() {
+            return;
+        }
+        let id = Uuid::try_parse(slint_task.id.as_str()).unwrap();
+        let mut task = Task::get(backend.as_ref(), &id).unwrap();
+        if task.pinned {
+            task.unpin();
+        } else {
+            task.pin();
+        }
+        task.update(backend.as_ref()).unwrap();
+        slint_task.pinned = task.pinned;
+        current_task.set_task(slint_task);
+    }
+}
+
+/// Parse `text` with the user's quick-add rules and file the task in the inbox, see
+/// `QuickAdd::capture`, then hide the quick capture window. If the task can't be stored, the
+/// window stays open showing why.
+pub fn capture_task<BKEND>(
+    quick_capture: slint::Weak<QuickCapture>,
+    backend: Weak<BKEND>,
+    settings: Settings,
+) -> impl FnMut(SharedString) + 'static
+where
+    BKEND: Store<TaskList> + Relate<Contains<TaskList, Task>> + Contexts + 'static,
+{
+    move |text| {
+        let quick_capture = quick_capture.unwrap();
+        let backend = backend.upgrade().unwrap();
+        if !text.trim().is_empty() {
+            let tz = settings.timezone();
+            let parsed = settings.quick_add().parse_at(&text, Utc::now(), tz);
+            if let Err(e) = parsed.capture(backend.as_ref(), settings.calendar(), tz) {
+                quick_capture.set_error_message(format!("{e:#}").into());
+                return;
+            }
+        }
+        quick_capture.set_error_message("".into());
+        quick_capture.hide().unwrap();
+    }
+}
+
 #[cfg(test)]
 #[coverage(off)]
 mod test_rs {
@@ -187,6 +506,7 @@ mod test_rs {
         let slint_task = SlintTask {
             name: "Task 1".into(),
             id: "".into(),
+            ..Default::default()
         };
         let task: Task = slint_task.try_into().unwrap();
         assert_eq!(task.name, "Task 1");
@@ -199,12 +519,23 @@ mod test_rs {
         let slint_task = SlintTask {
             name: "Task 1".into(),
             id: "0196b4c9-8447-7959-ae1f-72c7c8a3dd36".into(),
+            ..Default::default()
         };
         let task: Task = slint_task.try_into().unwrap();
         let expected_task = Task {
             name: "Task 1".into(),
             id: uuid!("0196b4c9-8447-7959-ae1f-72c7c8a3dd36"),
             description: None,
+            due: None,
+            colour: None,
+            status: Status::Todo,
+            priority: Priority::P2,
+            estimate: None,
+            actual: None,
+            completed_at: None,
+            pinned: false,
+            updated_at: Default::default(),
+            version: Default::default(),
         };
         assert_eq!(task, expected_task);
     }
@@ -214,6 +545,7 @@ mod test_rs {
         let slint_task = SlintTask {
             name: "Task 1".into(),
             id: "foo".into(),
+            ..Default::default()
         };
         let task: HelixFlowResult<Task> = slint_task.try_into();
         let err = task.unwrap_err();
@@ -226,13 +558,75 @@ mod test_rs {
             name: "Task 1".into(),
             id: uuid!("0196b4c9-8447-7959-ae1f-72c7c8a3dd36"),
             description: None,
+            due: None,
+            colour: None,
+            status: Status::Todo,
+            priority: Priority::P2,
+            estimate: None,
+            actual: None,
+            completed_at: None,
+            pinned: false,
+            updated_at: Default::default(),
+            version: Default::default(),
         };
         let slint_task = SlintTask {
             name: "Task 1".into(),
             id: "0196b4c9-8447-7959-ae1f-72c7c8a3dd36".into(),
+            link: "helixflow://task/0196b4c9-8447-7959-ae1f-72c7c8a3dd36".into(),
+            ..Default::default()
         };
         assert_eq!(slint_task, task.into());
     }
+
+    #[rstest]
+    fn colour_roundtrip() {
+        let task = Task {
+            colour: Some(Colour::rgb(255, 128, 0)),
+            ..Task::new("Task 1", None)
+        };
+        let slint_task: SlintTask = task.clone().into();
+        assert_eq!(slint_task.colour, Color::from_rgb_u8(255, 128, 0));
+        let roundtrip: Task = slint_task.try_into().unwrap();
+        assert_eq!(roundtrip, task);
+    }
+
+    #[rstest]
+    fn task_link() {
+        let task = Task {
+            id: uuid!("0196b4c9-8447-7959-ae1f-72c7c8a3dd36"),
+            ..Task::new("Task 1", None)
+        };
+        let slint_task: SlintTask = task.into();
+        assert_eq!(
+            slint_task.link,
+            "helixflow://task/0196b4c9-8447-7959-ae1f-72c7c8a3dd36"
+        );
+    }
+
+    #[rstest]
+    fn status_roundtrip() {
+        let mut task = Task::new("Task 1", None);
+        task.transition(Status::InProgress).unwrap();
+        let slint_task: SlintTask = task.clone().into();
+        assert_eq!(slint_task.status, SlintStatus::InProgress);
+        let roundtrip: Task = slint_task.try_into().unwrap();
+        assert_eq!(roundtrip, task);
+    }
+
+    #[rstest]
+    fn view_mode_roundtrip() {
+        for mode in [ViewMode::List, ViewMode::Board, ViewMode::Table] {
+            assert_eq!(ViewMode::from(SlintViewMode::from(mode)), mode);
+        }
+    }
+
+    #[rstest]
+    fn no_colour_is_transparent() {
+        let slint_task: SlintTask = Task::new("Task 1", None).into();
+        assert_eq!(slint_task.colour.alpha(), 0);
+        let roundtrip: Task = slint_task.try_into().unwrap();
+        assert_eq!(roundtrip.colour, None);
+    }
 }
 
 #[cfg(test)]
@@ -263,8 +657,11 @@ mod test_slint {
             let inputboxes = ElementHandle::find_by_element_type_name(&taskbox, "LineEdit");
             let buttons = ElementHandle::find_by_element_type_name(&taskbox, "Button");
 
-            let expected_inputboxes = ["Task name"];
-            let expected_buttons = ["Create"];
+            let expected_inputboxes = ["Task name", "Assignee", "Task link"];
+            let comboboxes = ElementHandle::find_by_element_type_name(&taskbox, "ComboBox");
+            let expected_comboboxes = ["Status"];
+            assert_components!(comboboxes, expected_comboboxes);
+            let expected_buttons = ["Pin", "Close", "Create", "Copy link"];
 
             assert_components!(inputboxes, expected_inputboxes);
             assert_components!(buttons, expected_buttons);
@@ -301,11 +698,23 @@ mod test_slint {
                 assert_eq!(create.accessible_label().unwrap().as_str(), "Create");
                 assert_eq!(create.accessible_role(), Some(AccessibleRole::Button));
             }
+
+            #[rstest]
+            fn copy_link_disabled_without_task(taskbox: TaskBox) {
+                let copy_link = get!(&taskbox, "TaskBox::copy_link");
+                assert_eq!(copy_link.accessible_label().unwrap().as_str(), "Copy link");
+                assert_eq!(copy_link.accessible_enabled(), Some(false));
+            }
         }
 
         mod callbacks {
-            use super::*;
+            use std::sync::Arc;
+
+            use helixflow_core::task::TestBackend;
             use slint::Global;
+            use uuid::uuid;
+
+            use super::*;
 
             #[rstest]
             fn button_click(taskbox: TaskBox) {
@@ -314,6 +723,7 @@ mod test_slint {
                     CurrentTask::get(&tb.unwrap()).set_task(SlintTask {
                         name: "".into(),
                         id: "1".into(),
+                        ..Default::default()
                     });
                 });
 
@@ -325,10 +735,144 @@ mod test_slint {
                 assert_eq!(task_id.accessible_label().unwrap().as_str(), "Task ID");
                 assert_eq!(task_id.accessible_value().unwrap().as_str(), "1");
             }
+
+            #[rstest]
+            fn change_status_follows_workflow(taskbox: TaskBox) {
+                let backend = Arc::new(TestBackend);
+                let task = Task::get(
+                    backend.as_ref(),
+                    &uuid!("0196b4c9-8447-7959-ae1f-72c7c8a3dd36"),
+                )
+                .unwrap();
+                CurrentTask::get(&taskbox).set_task(task.into());
+                taskbox.on_change_status(change_status(
+                    taskbox.as_weak(),
+                    Arc::downgrade(&backend),
+                    SharedState::default(),
+                ));
+
+                assert!(taskbox.invoke_change_status(SlintStatus::Blocked));
+                assert_eq!(
+                    CurrentTask::get(&taskbox).get_task().status,
+                    SlintStatus::Blocked
+                );
+                assert!(!taskbox.invoke_change_status(SlintStatus::Done));
+                assert_eq!(
+                    CurrentTask::get(&taskbox).get_task().status,
+                    SlintStatus::Blocked
+                );
+            }
+
+            #[rstest]
+            fn assign(taskbox: TaskBox) {
+                let backend = Arc::new(TestBackend);
+                let task = Task::get(
+                    backend.as_ref(),
+                    &uuid!("0196b4c9-8447-7959-ae1f-72c7c8a3dd36"),
+                )
+                .unwrap();
+                CurrentTask::get(&taskbox).set_task(task.into());
+                taskbox.on_assign(assign_task(taskbox.as_weak(), Arc::downgrade(&backend)));
+
+                let assignee = get!(&taskbox, "TaskBox::assignee_entry");
+                assert_eq!(assignee.accessible_value().unwrap().as_str(), "");
+                taskbox.invoke_assign(" Alice ".into());
+                assert_eq!(CurrentTask::get(&taskbox).get_task().assignee, "Alice");
+                assert_eq!(assignee.accessible_value().unwrap().as_str(), "Alice");
+                taskbox.invoke_assign("".into());
+                assert_eq!(CurrentTask::get(&taskbox).get_task().assignee, "Alice");
+            }
+
+            #[rstest]
+            fn pin(taskbox: TaskBox) {
+                let backend = Arc::new(TestBackend);
+                taskbox.on_toggle_pin(toggle_pin(taskbox.as_weak(), Arc::downgrade(&backend)));
+                // Nothing to pin
+                taskbox.invoke_toggle_pin();
+                let task = Task::get(
+                    backend.as_ref(),
+                    &uuid!("0196b4c9-8447-7959-ae1f-72c7c8a3dd36"),
+                )
+                .unwrap();
+                CurrentTask::get(&taskbox).set_task(task.into());
+
+                let pin_button = get!(&taskbox, "TaskBox::pin_button");
+                assert_eq!(pin_button.accessible_label().unwrap().as_str(), "Pin");
+                taskbox.invoke_toggle_pin();
+                assert!(CurrentTask::get(&taskbox).get_task().pinned);
+            }
+
+            #[rstest]
+            fn change_status_without_task(taskbox: TaskBox) {
+                let backend = Arc::new(TestBackend);
+                taskbox.on_change_status(change_status(
+                    taskbox.as_weak(),
+                    Arc::downgrade(&backend),
+                    SharedState::default(),
+                ));
+                assert!(!taskbox.invoke_change_status(SlintStatus::Done));
+            }
+        }
+    }
+
+    mod quick_capture {
+        use std::sync::Arc;
+
+        use helixflow_core::task::TestBackend;
+
+        use super::*;
+        use crate::QuickCapture;
+
+        #[fixture]
+        fn quick_capture() -> QuickCapture {
+            init_no_event_loop();
+
+            let quick_capture = QuickCapture::new().unwrap();
+            list_elements!(&quick_capture);
+            quick_capture
+        }
+
+        #[rstest]
+        fn correct_elements(quick_capture: QuickCapture) {
+            let inputboxes = ElementHandle::find_by_element_type_name(&quick_capture, "LineEdit");
+            let expected_inputboxes = ["Quick capture"];
+            assert_components!(inputboxes, expected_inputboxes);
+        }
+
+        #[rstest]
+        fn capture_hides_window(quick_capture: QuickCapture) {
+            let backend = Arc::new(TestBackend);
+            quick_capture.on_capture(capture_task(
+                quick_capture.as_weak(),
+                Arc::downgrade(&backend),
+                Settings::new(&Uuid::now_v7()),
+            ));
+            quick_capture.show().unwrap();
+            assert!(quick_capture.window().is_visible());
+            quick_capture.invoke_capture("Buy milk tomorrow @home".into());
+            assert!(!quick_capture.window().is_visible());
+            assert_eq!(quick_capture.get_error_message(), "");
+        }
+
+        #[rstest]
+        fn invalid_task_stays_open(quick_capture: QuickCapture) {
+            let backend = Arc::new(TestBackend);
+            quick_capture.on_capture(capture_task(
+                quick_capture.as_weak(),
+                Arc::downgrade(&backend),
+                Settings::new(&Uuid::now_v7()),
+            ));
+            quick_capture.show().unwrap();
+            // Only a tag, so no name
+            quick_capture.invoke_capture("#home".into());
+            assert!(quick_capture.window().is_visible());
+            assert_ne!(quick_capture.get_error_message(), "");
         }
     }
 
     mod backlog {
+        use std::{cell::RefCell, rc::Rc};
+
         use slint::{ModelRc, VecModel};
 
         use super::*;
@@ -370,15 +914,64 @@ mod test_slint {
             assert_values!(tasks, expected_task_values);
         }
 
+        #[rstest]
+        fn arranged_backlog(backlog: Backlog) {
+            use std::sync::Arc;
+
+            use helixflow_core::{
+                sorting::{SortKey, SortRule},
+                state::{BacklogFilter, State},
+                task::TestBackend,
+            };
+            use slint::Model;
+
+            let backend = Arc::new(TestBackend);
+            let tasklist = TaskList::get(
+                backend.as_ref(),
+                &uuid::uuid!("0196fe23-7c01-7d6b-9e09-5968eb370549"),
+            )
+            .unwrap();
+            backlog.set_tasklist(tasklist.into());
+            let state = Rc::new(RefCell::new(State::new(&Uuid::now_v7())));
+            backlog.on_load(load_arranged_backlog(
+                backlog.as_weak(),
+                Arc::downgrade(&backend),
+                state.clone(),
+                Tz::UTC,
+                Locale::EnGb,
+            ));
+            let names = |backlog: &Backlog| -> Vec<String> {
+                backlog
+                    .get_tasks()
+                    .iter()
+                    .map(|task| task.name.to_string())
+                    .collect()
+            };
+
+            backlog.invoke_load();
+            assert_eq!(names(&backlog), vec!["Task 1", "Task 2"]);
+            assert_eq!(backlog.get_tasks().row_data(0).unwrap().tags, "#home");
+
+            state.borrow_mut().sort_mode(SortRule(vec![SortKey::Name]));
+            state.borrow_mut().filter(BacklogFilter {
+                text: Some("2".into()),
+                ..Default::default()
+            });
+            backlog.invoke_load();
+            assert_eq!(names(&backlog), vec!["Task 2"]);
+        }
+
         #[rstest]
         fn show_tasks(backlog: Backlog) {
             let task1 = SlintTask {
                 name: "Test task 1".into(),
                 id: "1".into(),
+                ..Default::default()
             };
             let task2 = SlintTask {
                 name: "Test task 2".into(),
                 id: "2".into(),
+                ..Default::default()
             };
             let tasks = vec![task1, task2];
             let backlog_entries: VecModel<SlintTask> = tasks.clone().into();
@@ -412,5 +1005,59 @@ mod test_slint {
                 "1: New task"
             );
         }
+
+        #[rstest]
+        fn board(backlog: Backlog) {
+            let todo = SlintTask {
+                name: "To do".into(),
+                ..Default::default()
+            };
+            let done = SlintTask {
+                name: "Done".into(),
+                status: SlintStatus::Done,
+                ..Default::default()
+            };
+            backlog.set_tasks(ModelRc::new(VecModel::from(vec![todo, done])));
+            backlog.set_view_mode(SlintViewMode::Board);
+            list_elements!(&backlog);
+            let lists = ElementHandle::find_by_element_type_name(&backlog, "ListView");
+            assert_eq!(lists.count(), 0);
+            let tasks = ElementHandle::find_by_element_type_name(&backlog, "TaskListItem");
+            assert_values!(tasks, ["To do", "Done"]);
+        }
+
+        #[rstest]
+        fn card_settings(backlog: Backlog) {
+            let task = SlintTask {
+                name: "Buy milk".into(),
+                description: "Semi-skimmed".into(),
+                due: "due tomorrow".into(),
+                tags: "#errands".into(),
+                ..Default::default()
+            };
+            backlog.set_tasks(ModelRc::new(VecModel::from(vec![task])));
+            let texts = ElementHandle::find_by_element_type_name(&backlog, "Text");
+            assert_components!(texts, ["Backlog name", "Description", "Due", "Tags"]);
+
+            backlog.set_card_settings(SlintCardSettings {
+                show_description: false,
+                show_due_date: true,
+                show_tags: false,
+            });
+            let texts = ElementHandle::find_by_element_type_name(&backlog, "Text");
+            assert_components!(texts, ["Backlog name", "Due"]);
+        }
+
+        #[rstest]
+        fn open_task(backlog: Backlog) {
+            let opened = Rc::new(RefCell::new(None));
+            let open = opened.clone();
+            backlog.on_open_task(move |task| *open.borrow_mut() = Some(task.name));
+            let item = ElementHandle::find_by_element_type_name(&backlog, "TaskListItem")
+                .next()
+                .unwrap();
+            item.invoke_accessible_default_action();
+            assert_eq!(opened.borrow().as_deref(), Some("Error loading tasks"));
+        }
     }
 }
@@ -0,0 +1,203 @@
+use std::sync::Weak;
+
+use chrono::Utc;
+use chrono_tz::Tz;
+use slint::{Global, ModelRc, ToSharedString, VecModel};
+use uuid::Uuid;
+
+use helixflow_core::{
+    CRUD, Relate, Store,
+    dates::local_date,
+    task::{Contains, Task, TaskList},
+    transaction::Transaction,
+    trash::{self, Trash},
+};
+
+use crate::{CurrentTask, HelixFlow, SlintTask, SlintTrashed, TrashView};
+
+fn show_trash<BKEND: Trash>(trash_view: &TrashView, backend: &BKEND, tz: Tz) {
+    let tasks: Vec<SlintTrashed> = backend
+        .trashed()
+        .unwrap()
+        .iter()
+        .map(|trashed| SlintTrashed {
+            name: trashed.task.name.to_shared_string(),
+            id: trashed.task.id.to_shared_string(),
+            deleted: local_date(trashed.deleted_at, tz).to_shared_string(),
+        })
+        .collect();
+    trash_view.set_tasks(ModelRc::new(VecModel::from(tasks)));
+}
+
+/// Move the `CurrentTask` from the backlog to the trash, and close it.
+pub fn delete_task<BKEND>(
+    helixflow: slint::Weak<HelixFlow>,
+    backend: Weak<BKEND>,
+) -> impl FnMut() + 'static
+where
+    BKEND: Trash + Store<Task> + Transaction + 'static,
+{
+    move || {
+        let helixflow = helixflow.unwrap();
+        let backend = backend.upgrade().unwrap();
+        let current_task = CurrentTask::get(&helixflow);
+        let Ok(id) = Uuid::try_parse(current_task.get_task().id.as_str()) else {
+            return;
+        };
+        let task = Task::get(backend.as_ref(), &id).unwrap();
+        let backlog = TaskList::try_from(helixflow.get_backlog()).ok();
+        task.trash(backlog.as_ref(), backend.as_ref(), Utc::now())
+            .unwrap();
+        current_task.set_task(SlintTask::default());
+        helixflow.invoke_load_backlog();
+    }
+}
+
+/// Show everything in the trash, with the date it was deleted in `tz`.
+pub fn load_trash<BKEND>(
+    trash_view: slint::Weak<TrashView>,
+    backend: Weak<BKEND>,
+    tz: Tz,
+) -> impl FnMut() + 'static
+where
+    BKEND: Trash + 'static,
+{
+    move || {
+        let trash_view = trash_view.unwrap();
+        let backend = backend.upgrade().unwrap();
+        show_trash(&trash_view, backend.as_ref(), tz);
+    }
+}
+
+/// Restore the task at `index` in the trash to the list it was deleted from.
+pub fn restore_task<BKEND>(
+    trash_view: slint::Weak<TrashView>,
+    backend: Weak<BKEND>,
+    tz: Tz,
+) -> impl FnMut(i32) + 'static
+where
+    BKEND: Trash
+        + Store<Task>
+        + Store<TaskList>
+        + Relate<Contains<TaskList, Task>>
+        + Transaction
+        + 'static,
+{
+    move |index| {
+        let trash_view = trash_view.unwrap();
+        let backend = backend.upgrade().unwrap();
+        let trashed = backend.trashed().unwrap();
+        let Some(trashed) = trashed.get(index as usize) else {
+            return;
+        };
+        trash::restore(backend.as_ref(), &trashed.task.id).unwrap();
+        show_trash(&trash_view, backend.as_ref(), tz);
+    }
+}
+
+/// Permanently delete everything in the trash.
+pub fn empty_trash<BKEND>(
+    trash_view: slint::Weak<TrashView>,
+    backend: Weak<BKEND>,
+    tz: Tz,
+) -> impl FnMut() + 'static
+where
+    BKEND: Trash + 'static,
+{
+    move || {
+        let trash_view = trash_view.unwrap();
+        let backend = backend.upgrade().unwrap();
+        backend.empty_trash().unwrap();
+        show_trash(&trash_view, backend.as_ref(), tz);
+    }
+}
+
+#[cfg(test)]
+#[coverage(off)]
+mod test_slint {
+    use std::sync::Arc;
+
+    use helixflow_core::task::TestBackend;
+    use i_slint_backend_testing::init_no_event_loop;
+    use rstest::*;
+    use slint::{ComponentHandle, Model};
+    use uuid::uuid;
+
+    use super::*;
+    use crate::{task::load_backlog, test::*};
+
+    #[fixture]
+    fn trash_view() -> TrashView {
+        init_no_event_loop();
+        TrashView::new().unwrap()
+    }
+
+    #[fixture]
+    fn helixflow() -> HelixFlow {
+        init_no_event_loop();
+        HelixFlow::new().unwrap()
+    }
+
+    #[rstest]
+    fn restore_from_trash(trash_view: TrashView) {
+        let backend = Arc::new(TestBackend);
+        trash_view.on_load(load_trash(
+            trash_view.as_weak(),
+            Arc::downgrade(&backend),
+            Tz::UTC,
+        ));
+        trash_view.on_restore(restore_task(
+            trash_view.as_weak(),
+            Arc::downgrade(&backend),
+            Tz::UTC,
+        ));
+        trash_view.on_empty(empty_trash(
+            trash_view.as_weak(),
+            Arc::downgrade(&backend),
+            Tz::UTC,
+        ));
+        trash_view.invoke_load();
+        list_elements!(&trash_view);
+
+        let rows = ElementHandle::find_by_element_type_name(&trash_view, "TrashedItem");
+        let expected_labels = ["Deleted task"];
+        assert_components!(rows, expected_labels);
+        let rows = ElementHandle::find_by_element_type_name(&trash_view, "TrashedItem");
+        let expected_values = ["Deleted 2025-06-01"];
+        assert_values!(rows, expected_values);
+        let buttons = ElementHandle::find_by_element_type_name(&trash_view, "Button");
+        let expected_labels = ["Restore Deleted task", "Empty trash"];
+        assert_components!(buttons, expected_labels);
+
+        // `TestBackend` doesn't store changes
+        trash_view.invoke_restore(0);
+        trash_view.invoke_restore(1);
+        trash_view.invoke_empty();
+    }
+
+    #[rstest]
+    fn delete_current_task(helixflow: HelixFlow) {
+        let backend = Arc::new(TestBackend);
+        helixflow.on_load_backlog(load_backlog(helixflow.as_weak(), Arc::downgrade(&backend)));
+        helixflow.on_delete_task(delete_task(helixflow.as_weak(), Arc::downgrade(&backend)));
+        let tasklist = TaskList::get(
+            backend.as_ref(),
+            &uuid!("0196fe23-7c01-7d6b-9e09-5968eb370549"),
+        )
+        .unwrap();
+        helixflow.set_backlog(tasklist.into());
+
+        // Nothing to delete
+        helixflow.invoke_delete_task();
+
+        let task = Task::get(
+            backend.as_ref(),
+            &uuid!("0196b4c9-8447-7959-ae1f-72c7c8a3dd36"),
+        )
+        .unwrap();
+        CurrentTask::get(&helixflow).set_task(task.into());
+        helixflow.invoke_delete_task();
+        assert_eq!(CurrentTask::get(&helixflow).get_task().id, "");
+        assert_eq!(helixflow.get_backlog_contents().row_count(), 2);
+    }
+}
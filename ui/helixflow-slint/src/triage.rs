@@ -0,0 +1,400 @@
+use std::{cell::RefCell, collections::VecDeque, rc::Rc, sync::Weak};
+
+use chrono::Utc;
+use chrono_tz::Tz;
+use slint::{ModelRc, SharedString, ToSharedString, VecModel};
+
+use helixflow_core::{
+    CRUD, Relate, Store,
+    quickadd::Locale,
+    relative,
+    settings::Settings,
+    task::{Contains, Priority, Task, TaskList},
+    transaction::Transaction,
+    trash::Trash,
+};
+
+use crate::Triage;
+
+/// Tasks still waiting to be triaged, the current one first.
+pub type TriageQueue = Rc<RefCell<VecDeque<Task>>>;
+
+fn show_current(triage: &Triage, queue: &VecDeque<Task>, tz: Tz, locale: Locale) {
+    if let Some(task) = queue.front() {
+        triage.set_task(task.clone().into());
+        let due = task
+            .due
+            .map(|due| relative::due(due, Utc::now(), tz, locale))
+            .unwrap_or_default();
+        triage.set_due(due.to_shared_string());
+    }
+    triage.set_remaining(queue.len() as i32);
+}
+
+/// Apply `change` to the current task and store it, keeping the stored version in the queue so
+/// that it can be changed again.
+fn update_current<BKEND: Store<Task>>(
+    queue: &mut VecDeque<Task>,
+    backend: &BKEND,
+    change: impl FnOnce(&mut Task),
+) {
+    if let Some(task) = queue.front_mut() {
+        change(task);
+        task.update(backend).unwrap();
+        *task = Task::get(backend, &task.id).unwrap();
+    }
+}
+
+/// Queue up every task in `tasklist` for triage and show the first one, with due dates as seen in
+/// `tz`. After `m`, the keys `1`-`9` move the current task to the matching list in
+/// `destinations`.
+pub fn load_triage<BKEND>(
+    triage: slint::Weak<Triage>,
+    backend: Weak<BKEND>,
+    tasklist: TaskList,
+    destinations: Vec<TaskList>,
+    queue: TriageQueue,
+    tz: Tz,
+    locale: Locale,
+) -> impl FnMut() + 'static
+where
+    BKEND: Relate<Contains<TaskList, Task>> + 'static,
+{
+    move || {
+        let triage = triage.unwrap();
+        let backend = backend.upgrade().unwrap();
+        let mut queue = queue.borrow_mut();
+        *queue = tasklist
+            .get_linked_items(backend.as_ref())
+            .unwrap()
+            .map(|link| link.right)
+            .map(Result::unwrap)
+            .collect();
+        let names: Vec<SharedString> = destinations
+            .iter()
+            .take(9)
+            .map(|destination| destination.name.to_shared_string())
+            .collect();
+        triage.set_destinations(ModelRc::new(VecModel::from(names)));
+        show_current(&triage, &queue, tz, locale);
+    }
+}
+
+/// Handle a single key press for the current task:
+/// - `space` skips it
+/// - `1`-`4` set its priority, from `P0` to `P3`
+/// - `d` asks for a due date, see `triage_due`
+/// - `m` then `1`-`9` moves it from `tasklist` to one of `destinations`, any other key cancels
+/// - `a` archives it to the trash, from where it can be restored to `tasklist`
+pub fn triage_key<BKEND>(
+    triage: slint::Weak<Triage>,
+    backend: Weak<BKEND>,
+    tasklist: TaskList,
+    destinations: Vec<TaskList>,
+    queue: TriageQueue,
+    tz: Tz,
+    locale: Locale,
+) -> impl FnMut(SharedString) + 'static
+where
+    BKEND: Store<Task> + Relate<Contains<TaskList, Task>> + Trash + Transaction + 'static,
+{
+    move |key| {
+        let triage = triage.unwrap();
+        let backend = backend.upgrade().unwrap();
+        let mut queue = queue.borrow_mut();
+        let number = key.parse::<usize>().ok();
+        if triage.get_moving() {
+            triage.set_moving(false);
+            if let Some(destination) = number
+                .filter(|number| (1..=9).contains(number))
+                .and_then(|number| destinations.get(number - 1))
+                && let Some(task) = queue.pop_front()
+            {
+                tasklist
+                    .move_all(&[task], destination, backend.as_ref())
+                    .unwrap();
+            }
+        } else {
+            match key.as_str() {
+                " " => {
+                    queue.pop_front();
+                }
+                "d" => triage.set_picking_due(!queue.is_empty()),
+                "m" => triage.set_moving(!queue.is_empty()),
+                "a" => {
+                    if let Some(task) = queue.pop_front() {
+                        task.trash(Some(&tasklist), backend.as_ref(), Utc::now())
+                            .unwrap();
+                    }
+                }
+                _ => {
+                    if let Some(priority) = number
+                        .filter(|number| (1..=4).contains(number))
+                        .and_then(|number| Priority::from_level(number as u8 - 1))
+                    {
+                        update_current(&mut queue, backend.as_ref(), |task| {
+                            task.priority = priority
+                        });
+                    }
+                }
+            }
+        }
+        show_current(&triage, &queue, tz, locale);
+    }
+}
+
+/// Set the due date of the current task from `text`, e.g. `tomorrow 9am`, as understood by the
+/// quick-add rules in `settings`. Text without a date leaves the task as it was.
+pub fn triage_due<BKEND>(
+    triage: slint::Weak<Triage>,
+    backend: Weak<BKEND>,
+    queue: TriageQueue,
+    settings: Settings,
+) -> impl FnMut(SharedString) + 'static
+where
+    BKEND: Store<Task> + 'static,
+{
+    move |text| {
+        let triage = triage.unwrap();
+        let backend = backend.upgrade().unwrap();
+        let mut queue = queue.borrow_mut();
+        let tz = settings.timezone();
+        let due = settings
+            .quick_add()
+            .parse_at(&text, Utc::now(), tz)
+            .to_task(settings.calendar(), tz)
+            .due;
+        if due.is_some() {
+            update_current(&mut queue, backend.as_ref(), |task| task.due = due);
+        }
+        triage.set_picking_due(false);
+        show_current(&triage, &queue, tz, settings.locale());
+    }
+}
+
+#[cfg(test)]
+#[coverage(off)]
+mod test_slint {
+    use std::{assert_matches::assert_matches, sync::Arc};
+
+    use helixflow_core::{HelixFlowError, Link, task::TestBackend};
+    use helixflow_mem::MemBackend;
+    use i_slint_backend_testing::init_no_event_loop;
+    use rstest::*;
+    use slint::{ComponentHandle, Model};
+    use uuid::{Uuid, uuid};
+
+    use super::*;
+    use crate::test::*;
+
+    #[fixture]
+    fn triage() -> Triage {
+        init_no_event_loop();
+
+        let triage = Triage::new().unwrap();
+        list_elements!(&triage);
+        triage
+    }
+
+    #[rstest]
+    fn skip_through_tasks(triage: Triage) {
+        let backend = Arc::new(TestBackend);
+        let tasklist = TaskList::get(
+            backend.as_ref(),
+            &uuid!("0196fe23-7c01-7d6b-9e09-5968eb370549"),
+        )
+        .unwrap();
+        let queue = TriageQueue::default();
+        let inbox = TaskList::inbox(backend.as_ref()).unwrap();
+        triage.on_load(load_triage(
+            triage.as_weak(),
+            Arc::downgrade(&backend),
+            tasklist.clone(),
+            vec![inbox.clone()],
+            queue.clone(),
+            Tz::UTC,
+            Locale::EnGb,
+        ));
+        triage.on_key(triage_key(
+            triage.as_weak(),
+            Arc::downgrade(&backend),
+            tasklist,
+            vec![inbox],
+            queue.clone(),
+            Tz::UTC,
+            Locale::EnGb,
+        ));
+        triage.invoke_load();
+
+        let current = get!(&triage, "Triage::current_task");
+        let remaining = get!(&triage, "Triage::remaining_count");
+        let due = get!(&triage, "Triage::due_text");
+        assert_eq!(current.accessible_value().unwrap().as_str(), "Task 1");
+        assert_eq!(remaining.accessible_value().unwrap().as_str(), "2");
+        assert_eq!(due.accessible_value().unwrap().as_str(), "");
+
+        triage.invoke_key("x".into());
+        assert_eq!(current.accessible_value().unwrap().as_str(), "Task 1");
+
+        triage.invoke_key(" ".into());
+        assert_eq!(current.accessible_value().unwrap().as_str(), "Task 2");
+        queue.borrow_mut().push_front(Task {
+            due: Some(Utc::now() + chrono::TimeDelta::days(3)),
+            ..Task::new("Due soon", None)
+        });
+        triage.invoke_key("x".into());
+        assert_eq!(due.accessible_value().unwrap().as_str(), "due in 3 days");
+        triage.invoke_key(" ".into());
+        triage.invoke_key(" ".into());
+        assert_eq!(current.accessible_value().unwrap().as_str(), "");
+        assert_eq!(remaining.accessible_value().unwrap().as_str(), "0");
+    }
+
+    /// An inbox holding `Task 1` & `Task 2`, and a sprint to move them to.
+    fn stored_inbox() -> (Arc<MemBackend>, TaskList, TaskList) {
+        let backend = Arc::new(MemBackend::new());
+        let inbox = TaskList::inbox(backend.as_ref()).unwrap();
+        for name in ["Task 1", "Task 2"] {
+            inbox
+                .link(&Task::new(name, None))
+                .create_linked_item(backend.as_ref())
+                .unwrap();
+        }
+        let sprint = TaskList::new("Sprint");
+        sprint.create(backend.as_ref()).unwrap();
+        (backend, inbox, sprint)
+    }
+
+    fn start_triage(
+        triage: &Triage,
+        backend: &Arc<MemBackend>,
+        inbox: &TaskList,
+        sprint: &TaskList,
+    ) {
+        let queue = TriageQueue::default();
+        triage.on_load(load_triage(
+            triage.as_weak(),
+            Arc::downgrade(backend),
+            inbox.clone(),
+            vec![sprint.clone()],
+            queue.clone(),
+            Tz::UTC,
+            Locale::EnGb,
+        ));
+        triage.on_key(triage_key(
+            triage.as_weak(),
+            Arc::downgrade(backend),
+            inbox.clone(),
+            vec![sprint.clone()],
+            queue.clone(),
+            Tz::UTC,
+            Locale::EnGb,
+        ));
+        triage.on_set_due(triage_due(
+            triage.as_weak(),
+            Arc::downgrade(backend),
+            queue,
+            Settings::new(&Uuid::now_v7()),
+        ));
+        triage.invoke_load();
+    }
+
+    fn stored(backend: &MemBackend, tasklist: &TaskList) -> Vec<Task> {
+        tasklist
+            .get_linked_items(backend)
+            .unwrap()
+            .map(|link| link.right.unwrap())
+            .collect()
+    }
+
+    #[rstest]
+    fn set_priority(triage: Triage) {
+        let (backend, inbox, sprint) = stored_inbox();
+        start_triage(&triage, &backend, &inbox, &sprint);
+
+        triage.invoke_key("1".into());
+        assert_eq!(stored(&backend, &inbox)[0].priority, Priority::P0);
+        // Stays on the task, which can be changed again
+        triage.invoke_key("4".into());
+        assert_eq!(stored(&backend, &inbox)[0].priority, Priority::P3);
+        triage.invoke_key("5".into());
+        assert_eq!(stored(&backend, &inbox)[0].priority, Priority::P3);
+        assert_eq!(triage.get_task().name, "Task 1");
+        assert_eq!(stored(&backend, &inbox)[1].priority, Priority::P2);
+    }
+
+    #[rstest]
+    fn set_due_date(triage: Triage) {
+        let (backend, inbox, sprint) = stored_inbox();
+        start_triage(&triage, &backend, &inbox, &sprint);
+
+        triage.invoke_key("d".into());
+        assert!(triage.get_picking_due());
+        triage.invoke_set_due("no date here".into());
+        assert!(!triage.get_picking_due());
+        assert_eq!(stored(&backend, &inbox)[0].due, None);
+
+        triage.invoke_key("d".into());
+        triage.invoke_set_due("tomorrow".into());
+        let tomorrow = Utc::now().date_naive() + chrono::TimeDelta::days(1);
+        assert_eq!(
+            stored(&backend, &inbox)[0].due.map(|due| due.date_naive()),
+            Some(tomorrow)
+        );
+        let due = get!(&triage, "Triage::due_text");
+        assert_eq!(due.accessible_value().unwrap().as_str(), "due tomorrow");
+    }
+
+    #[rstest]
+    fn move_to_list(triage: Triage) {
+        let (backend, inbox, sprint) = stored_inbox();
+        start_triage(&triage, &backend, &inbox, &sprint);
+        assert_eq!(
+            triage.get_destinations().iter().collect::<Vec<_>>(),
+            vec!["Sprint"]
+        );
+
+        let current = get!(&triage, "Triage::current_task");
+        // Any other key cancels
+        triage.invoke_key("m".into());
+        assert!(triage.get_moving());
+        triage.invoke_key("x".into());
+        assert!(!triage.get_moving());
+        // No second destination
+        triage.invoke_key("m".into());
+        triage.invoke_key("2".into());
+        assert_eq!(current.accessible_value().unwrap().as_str(), "Task 1");
+        assert!(stored(&backend, &sprint).is_empty());
+
+        triage.invoke_key("m".into());
+        triage.invoke_key("1".into());
+        assert_eq!(current.accessible_value().unwrap().as_str(), "Task 2");
+        assert_eq!(triage.get_remaining(), 1);
+        let names = |tasklist| -> Vec<String> {
+            stored(&backend, tasklist)
+                .into_iter()
+                .map(|task| task.name.to_string())
+                .collect()
+        };
+        assert_eq!(names(&inbox), vec!["Task 2"]);
+        assert_eq!(names(&sprint), vec!["Task 1"]);
+    }
+
+    #[rstest]
+    fn archive(triage: Triage) {
+        let (backend, inbox, sprint) = stored_inbox();
+        start_triage(&triage, &backend, &inbox, &sprint);
+        let task_1 = stored(&backend, &inbox)[0].clone();
+
+        triage.invoke_key("a".into());
+        assert_eq!(triage.get_task().name, "Task 2");
+        assert_eq!(stored(&backend, &inbox).len(), 1);
+        assert_matches!(
+            Task::get(backend.as_ref(), &task_1.id),
+            Err(HelixFlowError::NotFound { .. })
+        );
+        let trashed = backend.trashed().unwrap();
+        assert_eq!(trashed[0].task.id, task_1.id);
+        assert_eq!(trashed[0].tasklist, Some(inbox.id));
+    }
+}
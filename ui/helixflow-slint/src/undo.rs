@@ -0,0 +1,151 @@
+use std::{cell::RefCell, rc::Rc, sync::Weak};
+
+use slint::{Global, ToSharedString};
+
+use helixflow_core::{
+    CRUD, HelixFlowError, Relate, Store,
+    command::Command,
+    state::State,
+    task::{Contains, Task, TaskList},
+};
+
+use crate::{CurrentTask, HelixFlow, SlintTask, errors::show_error};
+
+/// The UI state shared by every callback which executes a `Command`, so they all use the same
+/// undo stack.
+pub type SharedState = Rc<RefCell<State>>;
+
+/// Undo the last command (Ctrl+Z) and show the result. Does nothing if there is nothing to undo.
+pub fn undo<BKEND>(
+    helixflow: slint::Weak<HelixFlow>,
+    backend: Weak<BKEND>,
+    state: SharedState,
+) -> impl FnMut() + 'static
+where
+    BKEND: Store<Task> + Relate<Contains<TaskList, Task>> + 'static,
+{
+    move || {
+        let helixflow = helixflow.unwrap();
+        let backend = backend.upgrade().unwrap();
+        let undone = state.borrow_mut().undo(backend.as_ref()).unwrap();
+        if let Some(command) = undone {
+            show_changes(&helixflow, backend.as_ref(), &command);
+        }
+    }
+}
+
+/// Redo the last command undone (Ctrl+Shift+Z) and show the result. Does nothing if there is
+/// nothing to redo.
+pub fn redo<BKEND>(
+    helixflow: slint::Weak<HelixFlow>,
+    backend: Weak<BKEND>,
+    state: SharedState,
+) -> impl FnMut() + 'static
+where
+    BKEND: Store<Task> + Relate<Contains<TaskList, Task>> + 'static,
+{
+    move || {
+        let helixflow = helixflow.unwrap();
+        let backend = backend.upgrade().unwrap();
+        let redone = state.borrow_mut().redo(backend.as_ref()).unwrap();
+        if let Some(command) = redone {
+            show_changes(&helixflow, backend.as_ref(), &command);
+        }
+    }
+}
+
+/// Reload the backlog, and the `CurrentTask` if `command` changed it.
+fn show_changes<BKEND>(helixflow: &HelixFlow, backend: &BKEND, command: &Command)
+where
+    BKEND: Store<Task>,
+{
+    helixflow.invoke_load_backlog();
+    let current_task = CurrentTask::get(helixflow);
+    let shown = current_task.get_task();
+    if shown.id != command.task().id.to_shared_string() {
+        return;
+    }
+    match Task::get(backend, &command.task().id) {
+        Ok(task) => current_task.set_task(SlintTask {
+            assignee: shown.assignee,
+            ..task.into()
+        }),
+        Err(HelixFlowError::NotFound { .. }) => current_task.set_task(SlintTask::default()),
+        Err(e) => show_error(helixflow, &e),
+    }
+}
+
+#[cfg(test)]
+#[coverage(off)]
+mod test_slint {
+    use std::sync::Arc;
+
+    use helixflow_core::task::TestBackend;
+    use i_slint_backend_testing::init_no_event_loop;
+    use rstest::*;
+    use slint::ComponentHandle;
+    use uuid::{Uuid, uuid};
+
+    use super::*;
+    use crate::{
+        SlintStatus,
+        task::{change_status, load_backlog},
+    };
+
+    #[fixture]
+    fn helixflow() -> HelixFlow {
+        init_no_event_loop();
+        HelixFlow::new().unwrap()
+    }
+
+    #[rstest]
+    fn undo_status_change(helixflow: HelixFlow) {
+        let backend = Arc::new(TestBackend);
+        let state = SharedState::new(RefCell::new(State::new(&Uuid::now_v7())));
+        let tasklist = TaskList::get(
+            backend.as_ref(),
+            &uuid!("0196fe23-7c01-7d6b-9e09-5968eb370549"),
+        )
+        .unwrap();
+        helixflow.set_backlog(tasklist.into());
+        helixflow.on_load_backlog(load_backlog(helixflow.as_weak(), Arc::downgrade(&backend)));
+        helixflow.on_change_status(change_status(
+            helixflow.as_weak(),
+            Arc::downgrade(&backend),
+            state.clone(),
+        ));
+        helixflow.on_undo(undo(
+            helixflow.as_weak(),
+            Arc::downgrade(&backend),
+            state.clone(),
+        ));
+        helixflow.on_redo(redo(
+            helixflow.as_weak(),
+            Arc::downgrade(&backend),
+            state.clone(),
+        ));
+        let task = Task::get(
+            backend.as_ref(),
+            &uuid!("0196b4c9-8447-7959-ae1f-72c7c8a3dd36"),
+        )
+        .unwrap();
+        CurrentTask::get(&helixflow).set_task(task.into());
+
+        // Nothing to undo yet
+        helixflow.invoke_undo();
+        assert!(helixflow.invoke_change_status(SlintStatus::Blocked));
+        assert!(state.borrow().can_undo());
+
+        helixflow.invoke_undo();
+        // `TestBackend` doesn't store changes, so this shows the task as it was before
+        assert_eq!(
+            CurrentTask::get(&helixflow).get_task().status,
+            SlintStatus::Todo
+        );
+        assert!(!state.borrow().can_undo());
+
+        helixflow.invoke_redo();
+        assert!(state.borrow().can_undo());
+        assert!(!state.borrow().can_redo());
+    }
+}
@@ -0,0 +1,55 @@
+use slint::{ModelRc, SharedString, ToSharedString, VecModel};
+
+use helixflow_core::state::State;
+
+use crate::HelixFlow;
+
+/// Offer all workspaces in `state` in the workspace picker, with the open one selected.
+pub fn show_workspaces(helixflow: &HelixFlow, state: &State) {
+    let workspaces = state.workspaces();
+    let names: Vec<SharedString> = workspaces
+        .iter()
+        .map(|workspace| workspace.name.to_shared_string())
+        .collect();
+    let current = state.current_workspace().namespace;
+    let index = workspaces
+        .iter()
+        .position(|workspace| workspace.namespace == current)
+        .unwrap_or_default();
+    helixflow.set_workspace_names(ModelRc::new(VecModel::from(names)));
+    helixflow.set_current_workspace(index as i32);
+}
+
+#[cfg(test)]
+#[coverage(off)]
+mod test_slint {
+    use helixflow_core::workspace::Workspace;
+    use i_slint_backend_testing::init_no_event_loop;
+    use rstest::*;
+    use slint::Model;
+    use uuid::Uuid;
+
+    use super::*;
+
+    #[fixture]
+    fn helixflow() -> HelixFlow {
+        init_no_event_loop();
+        HelixFlow::new().unwrap()
+    }
+
+    #[rstest]
+    fn list_workspaces(helixflow: HelixFlow) {
+        let mut state = State::new(&Uuid::now_v7());
+        state.add_workspace(Workspace::new("Work")).unwrap();
+        show_workspaces(&helixflow, &state);
+        assert_eq!(
+            helixflow.get_workspace_names().iter().collect::<Vec<_>>(),
+            vec!["Personal", "Work"]
+        );
+        assert_eq!(helixflow.get_current_workspace(), 0);
+
+        state.workspace("work");
+        show_workspaces(&helixflow, &state);
+        assert_eq!(helixflow.get_current_workspace(), 1);
+    }
+}
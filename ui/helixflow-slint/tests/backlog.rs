@@ -1,11 +1,11 @@
-use std::rc::Rc;
+use std::sync::Arc;
 
 use uuid::uuid;
 
 use slint::{ComponentHandle, ModelRc, VecModel};
 
 use helixflow_core::{
-    CRUD, Linkable,
+    CRUD,
     task::{TaskList, TestBackend},
 };
 use helixflow_slint::{Backlog, SlintTask, task::load_backlog, test::*};
@@ -18,10 +18,12 @@ fn update_tasks_in_event_loop() {
     let task1 = SlintTask {
         name: "Test task 1".into(),
         id: "1".into(),
+        ..Default::default()
     };
     let task2 = SlintTask {
         name: "Test task 2".into(),
         id: "2".into(),
+        ..Default::default()
     };
     let tasks = vec![task1, task2];
     let backlog_entries: VecModel<SlintTask> = tasks.clone().into();
@@ -46,13 +48,13 @@ fn initialise_backlog() {
     let backlog = Backlog::new().unwrap();
     list_elements!(&backlog);
 
-    let backend = Rc::new(TestBackend);
+    let backend = Arc::new(TestBackend);
 
     let backlog_id = uuid!("0196fe23-7c01-7d6b-9e09-5968eb370549");
     let tasklist = TaskList::get(backend.as_ref(), &backlog_id).unwrap();
     backlog.set_tasklist(tasklist.into());
 
-    let be = Rc::downgrade(&backend);
+    let be = Arc::downgrade(&backend);
     let bl = backlog.as_weak();
     backlog.on_load(load_backlog(bl, be));
     backlog.invoke_load();
@@ -1,7 +1,7 @@
 //! Use nextest - these tests will fail on cargo test as they MUST run is separate processes
 //! for `i_slint_backend_testing::init_integration_test_with_system_time`
 
-use std::rc::Rc;
+use std::sync::Arc;
 
 use slint::platform::PointerEventButton;
 use slint::{ComponentHandle, Global};
@@ -14,12 +14,12 @@ fn test_set_task_id() {
     prepare_slint!();
 
     let helixflow = HelixFlow::new().unwrap();
-    let backend = Rc::new(TestBackend);
+    let backend = Arc::new(TestBackend);
 
     list_elements!(&helixflow);
 
     let hf = helixflow.as_weak();
-    let be = Rc::downgrade(&backend);
+    let be = Arc::downgrade(&backend);
     helixflow.on_create_task(create_task(hf, be));
 
     let hf = helixflow.as_weak();
@@ -58,3 +58,18 @@ fn test_set_task_id() {
     assert!(helixflow.get_create_enabled());
     assert!(create.accessible_enabled().unwrap());
 }
+
+#[test]
+fn test_create_disabled_while_loading() {
+    prepare_slint!();
+
+    let helixflow = HelixFlow::new().unwrap();
+    helixflow.set_loading(true);
+    helixflow.set_task_name("A valid task".into());
+
+    let create = get!(&helixflow, "TaskBox::create");
+    assert!(!create.accessible_enabled().unwrap());
+
+    helixflow.set_loading(false);
+    assert!(create.accessible_enabled().unwrap());
+}